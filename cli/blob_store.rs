@@ -0,0 +1,279 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! The contents of `Blob` and `File` objects, kept outside of the V8 heap.
+//!
+//! A blob is a list of ranges of reference-counted segments. Bytes given to
+//! the `Blob` constructor are copied into a new segment once; blobs made out
+//! of other blobs, and slices of blobs, share the segments of those instead
+//! of copying them.
+//!
+//! Each isolate refers to blobs by id in its `Blobs` table. Object URLs are
+//! kept in the `BlobStore`, shared by all isolates, so the module loader and
+//! workers can resolve them too.
+
+use futures::stream::Stream;
+use std::cmp::min;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::sync::Mutex;
+use url::Url;
+
+/// Size of the chunks blobs are streamed in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone)]
+struct Range {
+  segment: Arc<[u8]>,
+  start: usize,
+  end: usize,
+}
+
+impl Range {
+  fn bytes(&self) -> &[u8] {
+    &self.segment[self.start..self.end]
+  }
+}
+
+pub struct Blob {
+  pub media_type: String,
+  ranges: Vec<Range>,
+  size: usize,
+}
+
+impl Blob {
+  pub fn new(media_type: String) -> Self {
+    Self {
+      media_type,
+      ranges: vec![],
+      size: 0,
+    }
+  }
+
+  pub fn size(&self) -> usize {
+    self.size
+  }
+
+  /// Appends `bytes`, copied into a new segment.
+  pub fn push_bytes(&mut self, bytes: Vec<u8>) {
+    if bytes.is_empty() {
+      return;
+    }
+    let end = bytes.len();
+    self.push_range(Range {
+      segment: bytes.into(),
+      start: 0,
+      end,
+    });
+  }
+
+  /// Appends the contents of `blob`, sharing its segments.
+  pub fn push_blob(&mut self, blob: &Blob) {
+    for range in &blob.ranges {
+      self.push_range(range.clone());
+    }
+  }
+
+  fn push_range(&mut self, range: Range) {
+    self.size += range.end - range.start;
+    self.ranges.push(range);
+  }
+
+  /// The bytes from `start` to `end`, sharing the segments of this blob.
+  /// Both are clamped to the size of the blob.
+  pub fn slice(&self, start: usize, end: usize, media_type: String) -> Blob {
+    let end = min(end, self.size);
+    let start = min(start, end);
+    let mut blob = Blob::new(media_type);
+    let mut offset = 0;
+    for range in &self.ranges {
+      let len = range.end - range.start;
+      if offset + len > start && offset < end {
+        blob.push_range(Range {
+          segment: range.segment.clone(),
+          start: range.start + start.saturating_sub(offset),
+          end: range.start + min(end - offset, len),
+        });
+      }
+      offset += len;
+    }
+    blob
+  }
+
+  /// Copies the bytes from `offset` into `buf`, returning how many there
+  /// were.
+  pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+    let mut nread = 0;
+    for chunk in self.chunks_from(offset) {
+      let n = min(chunk.len(), buf.len() - nread);
+      buf[nread..nread + n].copy_from_slice(&chunk[..n]);
+      nread += n;
+      if nread == buf.len() {
+        break;
+      }
+    }
+    nread
+  }
+
+  /// The contents of the blob from `offset`, segment by segment.
+  fn chunks_from(&self, offset: usize) -> impl Iterator<Item = &[u8]> {
+    let mut skip = offset;
+    self.ranges.iter().filter_map(move |range| {
+      let bytes = range.bytes();
+      if skip >= bytes.len() {
+        skip -= bytes.len();
+        None
+      } else {
+        let chunk = &bytes[skip..];
+        skip = 0;
+        Some(chunk)
+      }
+    })
+  }
+
+  pub fn to_vec(&self) -> Vec<u8> {
+    let mut bytes = vec![0; self.size];
+    self.read_at(0, &mut bytes);
+    bytes
+  }
+
+  /// The contents of the blob as a stream of chunks.
+  pub fn stream(
+    self: Arc<Self>,
+  ) -> impl Stream<Item = io::Result<Vec<u8>>> + Send + Sync {
+    let mut offset = 0;
+    futures::stream::iter(std::iter::from_fn(move || {
+      if offset >= self.size {
+        return None;
+      }
+      let mut chunk = vec![0; min(CHUNK_SIZE, self.size - offset)];
+      offset += self.read_at(offset, &mut chunk);
+      Some(Ok(chunk))
+    }))
+  }
+}
+
+/// The blobs an isolate has `Blob` objects for, by id.
+#[derive(Default)]
+pub struct Blobs {
+  blobs: HashMap<u32, Arc<Blob>>,
+  next_id: u32,
+}
+
+impl Blobs {
+  pub fn add(&mut self, blob: Blob) -> u32 {
+    self.add_shared(Arc::new(blob))
+  }
+
+  pub fn add_shared(&mut self, blob: Arc<Blob>) -> u32 {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.blobs.insert(id, blob);
+    id
+  }
+
+  pub fn get(&self, id: u32) -> Option<&Arc<Blob>> {
+    self.blobs.get(&id)
+  }
+
+  pub fn remove(&mut self, id: u32) {
+    self.blobs.remove(&id);
+  }
+}
+
+/// The blobs of object URLs, shared by all isolates.
+#[derive(Clone, Default)]
+pub struct BlobStore(Arc<Mutex<HashMap<String, Arc<Blob>>>>);
+
+impl BlobStore {
+  /// Returns a new `blob:` URL for `blob`, like `URL.createObjectURL()`.
+  pub fn create_object_url(&self, origin: &str, blob: Arc<Blob>) -> String {
+    let url = format!("blob:{}/{}", origin, uuid::Uuid::new_v4());
+    self.0.lock().unwrap().insert(url.clone(), blob);
+    url
+  }
+
+  /// Looks up the blob of an object URL. The fragment is ignored.
+  pub fn get(&self, url: &Url) -> Option<Arc<Blob>> {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    self.0.lock().unwrap().get(url.as_str()).cloned()
+  }
+
+  pub fn revoke_object_url(&self, url: &Url) {
+    self.0.lock().unwrap().remove(url.as_str());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::stream::StreamExt;
+
+  fn blob(parts: &[&[u8]]) -> Blob {
+    let mut blob = Blob::new(String::new());
+    for part in parts {
+      blob.push_bytes(part.to_vec());
+    }
+    blob
+  }
+
+  #[test]
+  fn slice_shares_segments() {
+    let a = blob(&[b"hello", b" ", b"world"]);
+    let slice = a.slice(3, 8, "text/plain".to_string());
+    assert_eq!(slice.size(), 5);
+    assert_eq!(slice.to_vec(), b"lo wo");
+    assert_eq!(slice.media_type, "text/plain");
+    assert!(Arc::ptr_eq(&slice.ranges[0].segment, &a.ranges[0].segment));
+    assert_eq!(slice.slice(1, 100, String::new()).to_vec(), b"o wo");
+    assert_eq!(a.slice(5, 5, String::new()).size(), 0);
+    assert_eq!(a.slice(20, 30, String::new()).size(), 0);
+  }
+
+  #[test]
+  fn push_blob() {
+    let a = blob(&[b"ab", b"cd"]);
+    let mut b = blob(&[b"x"]);
+    b.push_blob(&a.slice(1, 3, String::new()));
+    assert_eq!(b.size(), 3);
+    assert_eq!(b.to_vec(), b"xbc");
+  }
+
+  #[test]
+  fn read_at() {
+    let a = blob(&[b"abc", b"def", b"gh"]);
+    let mut buf = [0; 4];
+    assert_eq!(a.read_at(2, &mut buf), 4);
+    assert_eq!(&buf, b"cdef");
+    assert_eq!(a.read_at(6, &mut buf), 2);
+    assert_eq!(&buf[..2], b"gh");
+    assert_eq!(a.read_at(8, &mut buf), 0);
+  }
+
+  #[tokio::test]
+  async fn stream() {
+    let a = blob(&[&vec![1; CHUNK_SIZE + 1], &[2, 3]]);
+    let chunks: Vec<Vec<u8>> = Arc::new(a)
+      .stream()
+      .map(|chunk| chunk.unwrap())
+      .collect()
+      .await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].len(), CHUNK_SIZE);
+    assert_eq!(chunks[1], vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn object_urls() {
+    let store = BlobStore::default();
+    let url = store.create_object_url("null", Arc::new(blob(&[b"a"])));
+    assert!(url.starts_with("blob:null/"));
+    let url = Url::parse(&url).unwrap();
+    let mut with_fragment = url.clone();
+    with_fragment.set_fragment(Some("x"));
+    assert_eq!(store.get(&with_fragment).unwrap().to_vec(), b"a");
+    store.revoke_object_url(&url);
+    assert!(store.get(&url).is_none());
+  }
+}
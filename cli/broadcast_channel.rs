@@ -0,0 +1,116 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use deno_core::Buf;
+use futures::channel::mpsc;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// In-process message bus backing `BroadcastChannel` Web API.
+///
+/// It is stored in `GlobalState` and thus shared by the main isolate
+/// and all workers. Each `BroadcastChannel` object in JS corresponds to a
+/// single `Subscription`.
+#[derive(Clone, Default)]
+pub struct BroadcastChannels(Arc<Mutex<BroadcastChannelsInner>>);
+
+#[derive(Default)]
+struct BroadcastChannelsInner {
+  next_id: u32,
+  subscribers: HashMap<u32, Subscriber>,
+}
+
+struct Subscriber {
+  name: String,
+  sender: mpsc::UnboundedSender<Buf>,
+}
+
+/// Handle to a subscription on the bus; unsubscribes when dropped.
+///
+/// Subscriptions are stored in isolate's resource table, so they're
+/// cleaned up when the resource is closed or the isolate shuts down.
+pub struct Subscription {
+  pub id: u32,
+  pub receiver: mpsc::UnboundedReceiver<Buf>,
+  channels: BroadcastChannels,
+}
+
+impl Drop for Subscription {
+  fn drop(&mut self) {
+    // Dropping the only sender wakes up task that is waiting on `receiver`.
+    self.channels.unsubscribe(self.id);
+  }
+}
+
+impl BroadcastChannels {
+  pub fn subscribe(&self, name: String) -> Subscription {
+    let (sender, receiver) = mpsc::unbounded::<Buf>();
+    let mut inner = self.0.lock().unwrap();
+    let id = inner.next_id;
+    inner.next_id += 1;
+    inner.subscribers.insert(id, Subscriber { name, sender });
+    Subscription {
+      id,
+      receiver,
+      channels: self.clone(),
+    }
+  }
+
+  fn unsubscribe(&self, id: u32) {
+    let mut inner = self.0.lock().unwrap();
+    inner.subscribers.remove(&id);
+  }
+
+  /// Sends a copy of the message to every subscriber of the same channel,
+  /// except the subscription that posted it. Returns number of recipients.
+  pub fn post(&self, id: u32, data: &[u8]) -> usize {
+    let inner = self.0.lock().unwrap();
+    let name = match inner.subscribers.get(&id) {
+      Some(subscriber) => &subscriber.name,
+      None => return 0,
+    };
+    inner
+      .subscribers
+      .iter()
+      .filter(|(other_id, other)| **other_id != id && &other.name == name)
+      .filter(|(_, other)| {
+        // Receiving end might have been dropped already
+        other
+          .sender
+          .unbounded_send(data.to_vec().into_boxed_slice())
+          .is_ok()
+      })
+      .count()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn post_to_other_subscribers() {
+    let channels = BroadcastChannels::default();
+    let mut a1 = channels.subscribe("a".to_string());
+    let mut a2 = channels.subscribe("a".to_string());
+    let mut b = channels.subscribe("b".to_string());
+
+    assert_eq!(channels.post(a1.id, b"hello"), 1);
+    assert_eq!(&*a2.receiver.try_next().unwrap().unwrap(), b"hello");
+    // Sender doesn't receive its own message
+    assert!(a1.receiver.try_next().is_err());
+    assert!(b.receiver.try_next().is_err());
+  }
+
+  #[test]
+  fn unsubscribe_on_drop() {
+    let channels = BroadcastChannels::default();
+    let a1 = channels.subscribe("a".to_string());
+    let a2 = channels.subscribe("a".to_string());
+    assert_eq!(channels.post(a1.id, b"hello"), 1);
+    drop(a2);
+    assert_eq!(channels.post(a1.id, b"hello"), 0);
+    let id = a1.id;
+    drop(a1);
+    assert_eq!(channels.post(id, b"hello"), 0);
+  }
+}
@@ -2,9 +2,31 @@
 use deno_core::include_crate_modules;
 use deno_core::CoreIsolate;
 use deno_core::StartupData;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::env;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
+
+/// The snapshots created by the build, with the extension of the file that
+/// gets embedded. The compiler snapshot is the large one and is only needed
+/// once a module is compiled, so it's embedded zstd compressed. The runtime
+/// snapshot is needed by every program and is embedded as is, so that startup
+/// doesn't pay for decompressing it.
+const RUNTIME_SNAPSHOT: (&str, &str) = ("CLI_SNAPSHOT", "bin");
+const COMPILER_SNAPSHOT: (&str, &str) = ("COMPILER_SNAPSHOT", "bin.zst");
+
+/// Writes a zstd compressed copy of the snapshot next to it, as
+/// `<name>.bin.zst`.
+fn compress_snapshot(snapshot_path: &Path) {
+  let snapshot = std::fs::read(snapshot_path).unwrap();
+  let compressed = zstd::stream::encode_all(&snapshot[..], 19)
+    .expect("Failed to compress snapshot");
+  std::fs::write(snapshot_path.with_extension("bin.zst"), compressed).unwrap();
+}
 
 fn main() {
   // Don't build V8 if "cargo doc" is being run. This is to support docs.rs.
@@ -39,8 +61,111 @@ fn main() {
 
   let c = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
   let o = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+  let build_compiler = env::var_os("CARGO_FEATURE_TSC").is_some();
+
+  println!("cargo:rerun-if-env-changed=DENO_PREBUILT_SNAPSHOTS");
+  println!("cargo:rerun-if-env-changed=DENO_SNAPSHOT_RUNNER");
+
+  if let Ok(base) = env::var("DENO_PREBUILT_SNAPSHOTS") {
+    fetch_prebuilt_snapshot(&base, RUNTIME_SNAPSHOT, &o);
+    if build_compiler {
+      fetch_prebuilt_snapshot(&base, COMPILER_SNAPSHOT, &o);
+    }
+    return;
+  }
+
+  let runner = env::var("DENO_SNAPSHOT_RUNNER").ok();
+  if runner.is_none() {
+    check_host_can_snapshot();
+  }
+
+  // Snapshots of unchanged inputs are byte-for-byte identical, so they're
+  // only created again when the hash of their inputs changes.
+  let stamp_path = o.join("SNAPSHOTS.stamp");
+  let inputs_hash = hash_snapshot_inputs(&c, build_compiler, runner.as_deref());
+  let mut outputs = vec![RUNTIME_SNAPSHOT];
+  if build_compiler {
+    outputs.push(COMPILER_SNAPSHOT);
+  }
+  let outputs_exist = outputs.iter().all(|&(name, snapshot_extension)| {
+    [snapshot_extension, "js.map", "d.ts"]
+      .iter()
+      .all(|extension| o.join(format!("{}.{}", name, extension)).exists())
+  });
+  let stamp = std::fs::read_to_string(&stamp_path).ok();
+  if outputs_exist && stamp.as_ref() == Some(&inputs_hash) {
+    println!("Snapshots are up to date.");
+    return;
+  }
+  let _ = std::fs::remove_file(&stamp_path);
+
+  deno_typescript::predictable_snapshots();
+
+  create_runtime_snapshot(
+    &c,
+    &o,
+    extern_crate_modules.clone(),
+    runner.as_deref(),
+  );
+  // Builds without the "tsc" feature only embed the runtime snapshot.
+  if build_compiler {
+    create_compiler_snapshot(&c, &o, extern_crate_modules, runner.as_deref());
+  }
+
+  std::fs::write(&stamp_path, inputs_hash).unwrap();
+}
+
+/// Hashes everything snapshots are created from: the sources in `cli/js`,
+/// the build script itself, which includes V8 and the TypeScript compiler,
+/// and the build configuration. Also tells cargo to run the build script
+/// again when one of the sources changes.
+fn hash_snapshot_inputs(
+  c: &Path,
+  build_compiler: bool,
+  runner: Option<&str>,
+) -> String {
+  let js_dir = c.join("js");
+  println!("cargo:rerun-if-changed={}", js_dir.display());
+  let mut files = Vec::new();
+  collect_files(&js_dir, &mut files);
+  files.sort();
+
+  let mut hasher = DefaultHasher::new();
+  for path in files {
+    println!("cargo:rerun-if-changed={}", path.display());
+    let relative_path = path.strip_prefix(c).unwrap();
+    relative_path
+      .to_string_lossy()
+      .replace('\\', "/")
+      .hash(&mut hasher);
+    std::fs::read(&path).unwrap().hash(&mut hasher);
+  }
+  let build_script = std::fs::metadata(env::current_exe().unwrap()).unwrap();
+  build_script.len().hash(&mut hasher);
+  build_script.modified().unwrap().hash(&mut hasher);
+  env::var("TARGET").unwrap().hash(&mut hasher);
+  build_compiler.hash(&mut hasher);
+  runner.hash(&mut hasher);
+  format!("{:016x}", hasher.finish())
+}
 
-  // Main snapshot
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      collect_files(&path, files);
+    } else {
+      files.push(path);
+    }
+  }
+}
+
+fn create_runtime_snapshot(
+  c: &Path,
+  o: &Path,
+  extern_crate_modules: HashMap<String, String>,
+  runner: Option<&str>,
+) {
   let root_names = vec![c.join("js/main.ts")];
   let bundle_path = o.join("CLI_SNAPSHOT.js");
   let snapshot_path = o.join("CLI_SNAPSHOT.bin");
@@ -48,22 +173,39 @@ fn main() {
   let main_module_name = deno_typescript::compile_bundle(
     &bundle_path,
     root_names,
-    Some(extern_crate_modules.clone()),
+    Some(extern_crate_modules),
   )
   .expect("Bundle compilation failed");
   assert!(bundle_path.exists());
 
-  let mut runtime_isolate = CoreIsolate::new(StartupData::None, true);
+  if let Some(runner) = runner {
+    run_snapshot_runner(
+      runner,
+      "runtime",
+      &bundle_path,
+      &main_module_name,
+      &snapshot_path,
+      &HashMap::new(),
+    );
+  } else {
+    let mut runtime_isolate = CoreIsolate::new(StartupData::None, true);
 
-  deno_typescript::mksnapshot_bundle(
-    &mut runtime_isolate,
-    &snapshot_path,
-    &bundle_path,
-    &main_module_name,
-  )
-  .expect("Failed to create snapshot");
+    deno_typescript::mksnapshot_bundle(
+      &mut runtime_isolate,
+      &snapshot_path,
+      &bundle_path,
+      &main_module_name,
+    )
+    .expect("Failed to create snapshot");
+  }
+}
 
-  // Compiler snapshot
+fn create_compiler_snapshot(
+  c: &Path,
+  o: &Path,
+  extern_crate_modules: HashMap<String, String>,
+  runner: Option<&str>,
+) {
   let root_names = vec![c.join("js/compiler.ts")];
   let bundle_path = o.join("COMPILER_SNAPSHOT.js");
   let snapshot_path = o.join("COMPILER_SNAPSHOT.bin");
@@ -76,8 +218,6 @@ fn main() {
   .expect("Bundle compilation failed");
   assert!(bundle_path.exists());
 
-  let mut runtime_isolate = CoreIsolate::new(StartupData::None, true);
-
   let mut custom_libs: HashMap<String, PathBuf> = HashMap::new();
   custom_libs.insert(
     "lib.deno.window.d.ts".to_string(),
@@ -99,16 +239,119 @@ fn main() {
     "lib.deno.unstable.d.ts".to_string(),
     c.join("js/lib.deno.unstable.d.ts"),
   );
-  runtime_isolate.register_op(
-    "op_fetch_asset",
-    deno_typescript::op_fetch_asset(custom_libs),
-  );
 
-  deno_typescript::mksnapshot_bundle_ts(
-    &mut runtime_isolate,
-    &snapshot_path,
-    &bundle_path,
-    &main_module_name,
-  )
-  .expect("Failed to create snapshot");
+  if let Some(runner) = runner {
+    run_snapshot_runner(
+      runner,
+      "compiler",
+      &bundle_path,
+      &main_module_name,
+      &snapshot_path,
+      &custom_libs,
+    );
+  } else {
+    let mut runtime_isolate = CoreIsolate::new(StartupData::None, true);
+    runtime_isolate.register_op(
+      "op_fetch_asset",
+      deno_typescript::op_fetch_asset(custom_libs),
+    );
+
+    deno_typescript::mksnapshot_bundle_ts(
+      &mut runtime_isolate,
+      &snapshot_path,
+      &bundle_path,
+      &main_module_name,
+    )
+    .expect("Failed to create snapshot");
+  }
+  compress_snapshot(&snapshot_path);
+}
+
+/// A snapshot can only be deserialized by V8 built for the architecture that
+/// created it, and build scripts run on the host.
+fn check_host_can_snapshot() {
+  let host = env::var("HOST").unwrap();
+  let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+  let host_arch = host.split('-').next().unwrap();
+  if host_arch != target_arch {
+    panic!(
+      "Can't create {} snapshots on a {} host. Set DENO_SNAPSHOT_RUNNER to \
+       run deno_mksnapshot built for the target, or DENO_PREBUILT_SNAPSHOTS \
+       to use prebuilt snapshots.",
+      target_arch, host
+    );
+  }
+}
+
+/// Creates a snapshot with `DENO_SNAPSHOT_RUNNER`, a command that runs
+/// `deno_mksnapshot` (from the deno_typescript crate) built for the target,
+/// eg. under an emulator: "qemu-aarch64 -L /usr/aarch64-linux-gnu
+/// target/aarch64-unknown-linux-gnu/release/deno_mksnapshot".
+fn run_snapshot_runner(
+  runner: &str,
+  kind: &str,
+  bundle_path: &Path,
+  main_module_name: &str,
+  snapshot_path: &Path,
+  custom_libs: &HashMap<String, PathBuf>,
+) {
+  let mut runner_args = runner.split_whitespace();
+  let program = runner_args.next().expect("DENO_SNAPSHOT_RUNNER is empty");
+  let mut command = Command::new(program);
+  command
+    .args(runner_args)
+    .arg(kind)
+    .arg(bundle_path)
+    .arg(main_module_name)
+    .arg(snapshot_path);
+  // Sorted, so the runner sees the same arguments on every build.
+  let mut custom_libs: Vec<_> = custom_libs.iter().collect();
+  custom_libs.sort();
+  for (name, path) in custom_libs {
+    println!("cargo:rerun-if-changed={}", path.display());
+    command.arg(format!("{}={}", name, path.display()));
+  }
+  let status = command
+    .status()
+    .expect("Failed to start DENO_SNAPSHOT_RUNNER");
+  assert!(status.success(), "DENO_SNAPSHOT_RUNNER failed: {}", status);
+  assert!(snapshot_path.exists());
+}
+
+/// Uses the prebuilt snapshot `name` instead of creating it. `base` is a
+/// directory or an http(s) URL with the files of each release and target at
+/// `<base>/<version>/<target>/`: the snapshot itself, `<name>.js.map` and
+/// `<name>.d.ts`.
+fn fetch_prebuilt_snapshot(
+  base: &str,
+  (name, snapshot_extension): (&str, &str),
+  out_dir: &Path,
+) {
+  let version = env::var("CARGO_PKG_VERSION").unwrap();
+  let target = env::var("TARGET").unwrap();
+  for extension in &[snapshot_extension, "js.map", "d.ts"] {
+    let file_name = format!("{}.{}", name, extension);
+    let source = format!(
+      "{}/{}/{}/{}",
+      base.trim_end_matches('/'),
+      version,
+      target,
+      file_name
+    );
+    let dest = out_dir.join(&file_name);
+    if source.starts_with("http://") || source.starts_with("https://") {
+      let status = Command::new("curl")
+        .args(&["--silent", "--show-error", "--fail", "--location"])
+        .arg("--output")
+        .arg(&dest)
+        .arg(&source)
+        .status()
+        .expect("Failed to run curl");
+      assert!(status.success(), "Failed to download {}", source);
+    } else {
+      println!("cargo:rerun-if-changed={}", source);
+      std::fs::copy(&source, &dest)
+        .unwrap_or_else(|e| panic!("Failed to copy {}: {}", source, e));
+    }
+  }
 }
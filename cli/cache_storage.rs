@@ -0,0 +1,512 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Caches behind the `caches` (`CacheStorage`) Web API.
+//!
+//! Each origin has its own caches, under `$DENO_DIR/cache_storage` in a
+//! directory named by the hash of the origin. The requests and the response
+//! metadata are kept in a SQLite index, and the response bodies in files
+//! next to it, so they can be streamed in and out.
+
+use crate::checksum;
+use crate::op_error::OpError;
+use rusqlite::params;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+use rusqlite::NO_PARAMS;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+pub type HeaderList = Vec<(String, String)>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CacheRequest {
+  pub url: String,
+  pub method: String,
+  pub headers: HeaderList,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheResponse {
+  pub url: String,
+  pub status: u16,
+  pub status_text: String,
+  pub headers: HeaderList,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryOptions {
+  #[serde(default)]
+  pub ignore_search: bool,
+  #[serde(default)]
+  pub ignore_method: bool,
+  #[serde(default)]
+  pub ignore_vary: bool,
+}
+
+/// A request and response of a cache, whose body is in the file `body`.
+pub struct CachedEntry {
+  pub request: CacheRequest,
+  pub response: CacheResponse,
+  pub body: PathBuf,
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+  headers
+    .iter()
+    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+    .map(|(_, value)| value.as_str())
+}
+
+fn strip_fragment(url: &str) -> &str {
+  url.split('#').next().unwrap()
+}
+
+/// Whether `request` matches a cached request and its response, as in
+/// https://w3c.github.io/ServiceWorker/#request-matches-cached-item-algorithm
+fn request_matches(
+  request: &CacheRequest,
+  cached: &CacheRequest,
+  response: &CacheResponse,
+  options: QueryOptions,
+) -> bool {
+  if !options.ignore_method
+    && request.method != "GET"
+    && request.method != "HEAD"
+  {
+    return false;
+  }
+  let mut url = strip_fragment(&request.url);
+  let mut cached_url = strip_fragment(&cached.url);
+  if options.ignore_search {
+    url = url.split('?').next().unwrap();
+    cached_url = cached_url.split('?').next().unwrap();
+  }
+  if url != cached_url {
+    return false;
+  }
+  if options.ignore_vary {
+    return true;
+  }
+  let vary = match header(&response.headers, "vary") {
+    Some(vary) => vary,
+    None => return true,
+  };
+  vary.split(',').map(str::trim).all(|name| {
+    name != "*"
+      && header(&request.headers, name) == header(&cached.headers, name)
+  })
+}
+
+/// The caches of an origin.
+pub struct OriginCaches {
+  conn: Connection,
+  bodies: PathBuf,
+}
+
+impl OriginCaches {
+  fn open(dir: PathBuf) -> Result<Self, OpError> {
+    let bodies = dir.join("bodies");
+    fs::create_dir_all(&bodies)?;
+    let conn = Connection::open(dir.join("index.sqlite3"))?;
+    conn.execute_batch(
+      "CREATE TABLE IF NOT EXISTS caches (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL UNIQUE
+      );
+      CREATE TABLE IF NOT EXISTS entries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        cache_id INTEGER NOT NULL,
+        url TEXT NOT NULL,
+        request TEXT NOT NULL,
+        response TEXT NOT NULL,
+        body TEXT NOT NULL
+      );
+      CREATE INDEX IF NOT EXISTS entries_url ON entries (cache_id, url);",
+    )?;
+    Ok(OriginCaches { conn, bodies })
+  }
+
+  /// Returns the id of the cache named `name`, creating it if needed.
+  pub fn open_cache(&mut self, name: &str) -> Result<i64, OpError> {
+    self.conn.execute(
+      "INSERT OR IGNORE INTO caches (name) VALUES (?)",
+      params![name],
+    )?;
+    let id = self.conn.query_row(
+      "SELECT id FROM caches WHERE name = ?",
+      params![name],
+      |row| row.get(0),
+    )?;
+    Ok(id)
+  }
+
+  pub fn has_cache(&self, name: &str) -> Result<bool, OpError> {
+    let id: Option<i64> = self
+      .conn
+      .query_row(
+        "SELECT id FROM caches WHERE name = ?",
+        params![name],
+        |row| row.get(0),
+      )
+      .optional()?;
+    Ok(id.is_some())
+  }
+
+  /// Deletes the cache named `name` and its entries. Returns whether it
+  /// existed.
+  pub fn delete_cache(&mut self, name: &str) -> Result<bool, OpError> {
+    let id: Option<i64> = self
+      .conn
+      .query_row(
+        "SELECT id FROM caches WHERE name = ?",
+        params![name],
+        |row| row.get(0),
+      )
+      .optional()?;
+    let id = match id {
+      Some(id) => id,
+      None => return Ok(false),
+    };
+    let entries = self.entries(Some(id), None)?;
+    let ids: Vec<i64> = entries.iter().map(|(id, _)| *id).collect();
+    self.delete_entries(&ids)?;
+    self
+      .conn
+      .execute("DELETE FROM caches WHERE id = ?", params![id])?;
+    Ok(true)
+  }
+
+  /// The names of the caches, in the order they were created.
+  pub fn cache_names(&self) -> Result<Vec<String>, OpError> {
+    let mut statement =
+      self.conn.prepare("SELECT name FROM caches ORDER BY id")?;
+    let names = statement
+      .query_map(NO_PARAMS, |row| row.get(0))?
+      .collect::<Result<Vec<String>, _>>()?;
+    Ok(names)
+  }
+
+  /// The entries of a cache, or of every cache if `None`, in the order they
+  /// were put. Only the entries of `url` are read if given.
+  fn entries(
+    &self,
+    cache_id: Option<i64>,
+    url: Option<&str>,
+  ) -> Result<Vec<(i64, CachedEntry)>, OpError> {
+    let mut statement = self.conn.prepare(
+      "SELECT entries.id, request, response, body FROM entries
+      JOIN caches ON caches.id = entries.cache_id
+      WHERE (?1 IS NULL OR cache_id = ?1) AND (?2 IS NULL OR url = ?2)
+      ORDER BY cache_id, entries.id",
+    )?;
+    let rows = statement.query_map(params![cache_id, url], |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, String>(3)?,
+      ))
+    })?;
+    let mut entries = vec![];
+    for row in rows {
+      let (id, request, response, body) = row?;
+      let entry = CachedEntry {
+        request: serde_json::from_str(&request)?,
+        response: serde_json::from_str(&response)?,
+        body: self.bodies.join(body),
+      };
+      entries.push((id, entry));
+    }
+    Ok(entries)
+  }
+
+  fn delete_entries(&mut self, ids: &[i64]) -> Result<(), OpError> {
+    for id in ids {
+      let body: String = self.conn.query_row(
+        "SELECT body FROM entries WHERE id = ?",
+        params![id],
+        |row| row.get(0),
+      )?;
+      self
+        .conn
+        .execute("DELETE FROM entries WHERE id = ?", params![id])?;
+      // Bodies that are being read stay readable until they are closed,
+      // except on Windows where deleting them fails.
+      let _ = fs::remove_file(self.bodies.join(body));
+    }
+    Ok(())
+  }
+
+  /// The entries matching `request` in a cache, or in every cache if
+  /// `None`, or all the entries of the cache if `request` is `None`.
+  pub fn query(
+    &self,
+    cache_id: Option<i64>,
+    request: Option<&CacheRequest>,
+    options: QueryOptions,
+  ) -> Result<Vec<(i64, CachedEntry)>, OpError> {
+    let request = match request {
+      Some(request) => request,
+      None => return self.entries(cache_id, None),
+    };
+    let url = if options.ignore_search {
+      None
+    } else {
+      Some(strip_fragment(&request.url))
+    };
+    let entries = self
+      .entries(cache_id, url)?
+      .into_iter()
+      .filter(|(_, entry)| {
+        request_matches(request, &entry.request, &entry.response, options)
+      })
+      .collect();
+    Ok(entries)
+  }
+
+  /// Deletes the entries of a cache that match `request`. Returns whether
+  /// any entry was deleted.
+  pub fn delete(
+    &mut self,
+    cache_id: i64,
+    request: &CacheRequest,
+    options: QueryOptions,
+  ) -> Result<bool, OpError> {
+    let ids: Vec<i64> = self
+      .query(Some(cache_id), Some(request), options)?
+      .into_iter()
+      .map(|(id, _)| id)
+      .collect();
+    self.delete_entries(&ids)?;
+    Ok(!ids.is_empty())
+  }
+
+  /// A file to write the body of a new entry to, before `put()`.
+  pub fn new_body_path(&self) -> PathBuf {
+    self.bodies.join(format!("{}.tmp", uuid::Uuid::new_v4()))
+  }
+
+  /// Adds an entry to a cache, replacing the entries that `request` matches.
+  /// The body, written to `body_path`, is moved into the cache.
+  pub fn put(
+    &mut self,
+    cache_id: i64,
+    request: CacheRequest,
+    response: CacheResponse,
+    body_path: PathBuf,
+  ) -> Result<(), OpError> {
+    let body = body_path.file_stem().unwrap().to_string_lossy().to_string();
+    fs::rename(&body_path, self.bodies.join(&body))?;
+    self.delete(cache_id, &request, QueryOptions::default())?;
+    self.conn.execute(
+      "INSERT INTO entries (cache_id, url, request, response, body)
+      VALUES (?, ?, ?, ?, ?)",
+      params![
+        cache_id,
+        strip_fragment(&request.url),
+        serde_json::to_string(&request)?,
+        serde_json::to_string(&response)?,
+        body
+      ],
+    )?;
+    Ok(())
+  }
+}
+
+/// The caches of all origins, opened on first use. It is stored in
+/// `GlobalState`.
+#[derive(Clone)]
+pub struct CacheStorage(Arc<Mutex<CacheStorageInner>>);
+
+struct CacheStorageInner {
+  dir: PathBuf,
+  origins: HashMap<String, OriginCaches>,
+}
+
+impl CacheStorage {
+  pub fn new(dir: PathBuf) -> Self {
+    CacheStorage(Arc::new(Mutex::new(CacheStorageInner {
+      dir,
+      origins: HashMap::new(),
+    })))
+  }
+
+  /// Calls `f` with the caches of the origin.
+  pub fn with_origin<T>(
+    &self,
+    origin: &str,
+    f: impl FnOnce(&mut OriginCaches) -> Result<T, OpError>,
+  ) -> Result<T, OpError> {
+    let mut inner = self.0.lock().unwrap();
+    if !inner.origins.contains_key(origin) {
+      let dir = inner.dir.join(checksum::gen2(origin));
+      let caches = OriginCaches::open(dir)?;
+      inner.origins.insert(origin.to_string(), caches);
+    }
+    f(inner.origins.get_mut(origin).unwrap())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn request(url: &str, headers: &[(&str, &str)]) -> CacheRequest {
+    CacheRequest {
+      url: url.to_string(),
+      method: "GET".to_string(),
+      headers: headers
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+    }
+  }
+
+  fn response(status: u16, headers: &[(&str, &str)]) -> CacheResponse {
+    CacheResponse {
+      url: String::new(),
+      status,
+      status_text: String::new(),
+      headers: headers
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+    }
+  }
+
+  fn put(
+    caches: &mut OriginCaches,
+    cache_id: i64,
+    request: CacheRequest,
+    response: CacheResponse,
+    body: &str,
+  ) {
+    let path = caches.new_body_path();
+    fs::write(&path, body).unwrap();
+    caches.put(cache_id, request, response, path).unwrap();
+  }
+
+  #[test]
+  fn put_match_delete() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let storage = CacheStorage::new(temp_dir.path().to_path_buf());
+    storage
+      .with_origin("https://deno.land", |caches| {
+        let id = caches.open_cache("v1")?;
+        put(
+          caches,
+          id,
+          request("https://deno.land/a", &[]),
+          response(200, &[]),
+          "a",
+        );
+        // Replaces the entry.
+        put(
+          caches,
+          id,
+          request("https://deno.land/a#x", &[]),
+          response(201, &[]),
+          "b",
+        );
+        let entries = caches.query(
+          Some(id),
+          Some(&request("https://deno.land/a", &[])),
+          QueryOptions::default(),
+        )?;
+        assert_eq!(entries.len(), 1);
+        let (_, entry) = &entries[0];
+        assert_eq!(entry.response.status, 201);
+        assert_eq!(fs::read_to_string(&entry.body).unwrap(), "b");
+
+        let options = QueryOptions {
+          ignore_search: true,
+          ..QueryOptions::default()
+        };
+        let search = request("https://deno.land/a?q=1", &[]);
+        assert_eq!(caches.query(None, Some(&search), options)?.len(), 1);
+        assert!(caches
+          .query(None, Some(&search), QueryOptions::default())?
+          .is_empty());
+
+        assert!(caches.delete(
+          id,
+          &request("https://deno.land/a", &[]),
+          QueryOptions::default()
+        )?);
+        assert!(caches
+          .query(Some(id), None, QueryOptions::default())?
+          .is_empty());
+        Ok(())
+      })
+      .unwrap();
+  }
+
+  #[test]
+  fn vary() {
+    let cached = request("https://deno.land/", &[("accept", "text/html")]);
+    let varies = response(200, &[("Vary", "Accept")]);
+    let options = QueryOptions::default();
+    let html = request("https://deno.land/", &[("Accept", "text/html")]);
+    let json = request("https://deno.land/", &[("accept", "application/json")]);
+    assert!(request_matches(&html, &cached, &varies, options));
+    assert!(!request_matches(&json, &cached, &varies, options));
+    let options = QueryOptions {
+      ignore_vary: true,
+      ..options
+    };
+    assert!(request_matches(&json, &cached, &varies, options));
+    let any = response(200, &[("vary", "*")]);
+    assert!(!request_matches(
+      &html,
+      &cached,
+      &any,
+      QueryOptions::default()
+    ));
+  }
+
+  #[test]
+  fn caches_persist() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let storage = CacheStorage::new(temp_dir.path().to_path_buf());
+    storage
+      .with_origin("https://deno.land", |caches| {
+        let id = caches.open_cache("v1")?;
+        caches.open_cache("v2")?;
+        put(
+          caches,
+          id,
+          request("https://deno.land/", &[]),
+          response(200, &[]),
+          "",
+        );
+        Ok(())
+      })
+      .unwrap();
+
+    let storage = CacheStorage::new(temp_dir.path().to_path_buf());
+    storage
+      .with_origin("https://deno.land", |caches| {
+        assert_eq!(caches.cache_names()?, vec!["v1", "v2"]);
+        assert!(caches.delete_cache("v1")?);
+        assert!(!caches.has_cache("v1")?);
+        assert!(caches
+          .query(None, None, QueryOptions::default())?
+          .is_empty());
+        Ok(())
+      })
+      .unwrap();
+    storage
+      .with_origin("https://example.com", |caches| {
+        assert!(caches.cache_names()?.is_empty());
+        Ok(())
+      })
+      .unwrap();
+  }
+}
@@ -1,14 +1,24 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Terminal styling shared by error formatting, diagnostics, the REPL and the
+//! test reporter.
+//!
+//! Colors are turned off by setting `NO_COLOR` or passing `--no-color`, which
+//! is also reflected by `Deno.noColor` for user code. On Windows, virtual
+//! terminal processing is enabled for the console so escape codes are
+//! interpreted; if that's not supported colors are turned off too. Deno's own
+//! output is additionally not colored when stdout or stderr is not a
+//! terminal.
+
 use regex::Regex;
 use std::env;
 use std::fmt;
 use std::io::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use termcolor::Color::{Ansi256, Black, Magenta, Red, White};
 use termcolor::{Ansi, ColorSpec, WriteColor};
 
-#[cfg(windows)]
-use termcolor::{BufferWriter, ColorChoice};
-
 lazy_static! {
         // STRIP_ANSI_RE and strip_ansi_codes are lifted from the "console" crate.
         // Copyright 2017 Armin Ronacher <armin.ronacher@active-4.com>. MIT License.
@@ -25,13 +35,66 @@ pub fn strip_ansi_codes(s: &str) -> std::borrow::Cow<str> {
   STRIP_ANSI_RE.replace_all(s, "")
 }
 
+/// Set by `--no-color`, or if the console can't interpret escape codes.
+static FORCE_NO_COLOR: AtomicBool = AtomicBool::new(false);
+/// Set if stdout or stderr is not a terminal.
+static NOT_A_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+/// Whether colors are turned off for everything, including user code; this
+/// is the value of `Deno.noColor`.
+pub fn no_color() -> bool {
+  *NO_COLOR || FORCE_NO_COLOR.load(Ordering::Relaxed)
+}
+
+/// Whether deno's own output is colored.
 pub fn use_color() -> bool {
-  !(*NO_COLOR)
+  !no_color() && !NOT_A_TERMINAL.load(Ordering::Relaxed)
 }
 
+/// Decides whether output is colored, see the module documentation. Called
+/// once on startup; `no_color_flag` is the value of `--no-color`.
+pub fn init(no_color_flag: bool) {
+  if no_color_flag || !enable_ansi() {
+    FORCE_NO_COLOR.store(true, Ordering::Relaxed);
+  }
+  if !atty::is(atty::Stream::Stdout) || !atty::is(atty::Stream::Stderr) {
+    NOT_A_TERMINAL.store(true, Ordering::Relaxed);
+  }
+}
+
+/// Enables interpretation of escape codes by the Windows console, available
+/// since Windows 10. Returns false if the console doesn't support them, but
+/// not if the output is redirected, as files and pipes take escape codes as
+/// they are.
 #[cfg(windows)]
-pub fn enable_ansi() {
-  BufferWriter::stdout(ColorChoice::AlwaysAnsi);
+fn enable_ansi() -> bool {
+  use winapi::shared::minwindef::DWORD;
+  use winapi::um::consoleapi;
+  use winapi::um::processenv::GetStdHandle;
+  use winapi::um::winbase::STD_ERROR_HANDLE;
+  use winapi::um::winbase::STD_OUTPUT_HANDLE;
+  use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+  [STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+    .iter()
+    .all(|&std_handle| unsafe {
+      let handle = GetStdHandle(std_handle);
+      let mut mode: DWORD = 0;
+      if consoleapi::GetConsoleMode(handle, &mut mode) == 0 {
+        // Not a console.
+        return true;
+      }
+      mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+        || consoleapi::SetConsoleMode(
+          handle,
+          mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        ) != 0
+    })
+}
+
+#[cfg(not(windows))]
+fn enable_ansi() -> bool {
+  true
 }
 
 fn style(s: &str, colorspec: ColorSpec) -> impl fmt::Display {
@@ -106,6 +169,12 @@ pub fn gray(s: String) -> impl fmt::Display {
   style(&s, style_spec)
 }
 
+pub fn italic_gray(s: String) -> impl fmt::Display {
+  let mut style_spec = ColorSpec::new();
+  style_spec.set_fg(Some(Ansi256(8))).set_italic(true);
+  style(&s, style_spec)
+}
+
 pub fn italic_bold_gray(s: String) -> impl fmt::Display {
   let mut style_spec = ColorSpec::new();
   style_spec
@@ -0,0 +1,84 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Node compatibility mode (`deno run --compat`), built on `std/node`.
+//!
+//! Before the main module runs, a prelude module installs the `global`,
+//! `process` and `Buffer` globals and a `require()` function resolving
+//! relative to the main module. Main modules that Node would treat as
+//! CommonJS are then loaded through that `require()` instead of as ES modules.
+
+use deno_core::ModuleSpecifier;
+use std::env;
+use url::Url;
+
+/// Location of the `std/node` modules the prelude imports; kept in step with
+/// `std/version.ts`.
+pub const STD_NODE_URL: &str = "https://deno.land/std@0.50.0/node";
+
+/// Whether Node would load `main_module` as CommonJS: local `.js` and `.cjs`
+/// files.
+pub fn is_commonjs(main_module: &ModuleSpecifier) -> bool {
+  let url = main_module.as_url();
+  url.scheme() == "file"
+    && (url.path().ends_with(".js") || url.path().ends_with(".cjs"))
+}
+
+/// Source of the prelude module for `main_module`.
+pub fn prelude(main_module: &ModuleSpecifier) -> String {
+  let url = main_module.as_url();
+  // `require()` needs a file path to resolve against; remote main modules
+  // resolve `node_modules` from the current directory instead.
+  let base = if url.scheme() == "file" {
+    url.to_string()
+  } else {
+    env::current_dir()
+      .ok()
+      .and_then(|cwd| Url::from_directory_path(cwd).ok())
+      .map(|url| url.to_string())
+      .unwrap_or_else(|| "file:///".to_string())
+  };
+  let base = serde_json::to_string(&base).unwrap();
+
+  let mut code = format!(
+    "import \"{std}/global.ts\";\n\
+     import {{ createRequire }} from \"{std}/module.ts\";\n\
+     const require = createRequire({base});\n\
+     globalThis.require = require;\n",
+    std = STD_NODE_URL,
+    base = base,
+  );
+  if is_commonjs(main_module) {
+    let path = url.to_file_path().unwrap();
+    let path = serde_json::to_string(&path.to_string_lossy()).unwrap();
+    code.push_str(&format!("require({});\n", path));
+  }
+  code
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_commonjs() {
+    let spec = |s: &str| ModuleSpecifier::resolve_url(s).unwrap();
+    assert!(is_commonjs(&spec("file:///a/main.js")));
+    assert!(is_commonjs(&spec("file:///a/main.cjs")));
+    assert!(!is_commonjs(&spec("file:///a/main.mjs")));
+    assert!(!is_commonjs(&spec("file:///a/main.ts")));
+    assert!(!is_commonjs(&spec("https://example.com/main.js")));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_prelude() {
+    let main = ModuleSpecifier::resolve_url("file:///a/main.js").unwrap();
+    let code = prelude(&main);
+    assert!(code.contains(&format!("\"{}/global.ts\"", STD_NODE_URL)));
+    assert!(code.contains("createRequire(\"file:///a/main.js\")"));
+    assert!(code.contains("require(\"/a/main.js\");"));
+
+    let main = ModuleSpecifier::resolve_url("file:///a/main.ts").unwrap();
+    assert!(!prelude(&main).contains("require(\"/a/main.ts\");"));
+  }
+}
@@ -0,0 +1,250 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Incremental compression and decompression, shared by the
+//! `CompressionStream`/`DecompressionStream` ops and by `fetch()` for
+//! response encodings reqwest doesn't decode itself.
+
+use flate2::write::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::write::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::write::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+  Gzip,
+  /// The zlib format, which is what both the Compression Streams spec and
+  /// HTTP call "deflate".
+  Deflate,
+  DeflateRaw,
+  Brotli,
+}
+
+impl Format {
+  /// Parses a `CompressionStream` format name.
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "gzip" => Some(Format::Gzip),
+      "deflate" => Some(Format::Deflate),
+      "deflate-raw" => Some(Format::DeflateRaw),
+      "br" => Some(Format::Brotli),
+      _ => None,
+    }
+  }
+
+  /// Parses a `Content-Encoding` header value.
+  pub fn from_content_encoding(encoding: &str) -> Option<Self> {
+    match encoding.trim().to_ascii_lowercase().as_str() {
+      "gzip" | "x-gzip" => Some(Format::Gzip),
+      "deflate" => Some(Format::Deflate),
+      "br" => Some(Format::Brotli),
+      _ => None,
+    }
+  }
+}
+
+/// Collects the output of the wrapped encoder or decoder so it can be handed
+/// out after every write.
+#[derive(Clone, Default)]
+struct Sink(Arc<Mutex<Vec<u8>>>);
+
+impl Sink {
+  fn take(&self) -> Vec<u8> {
+    mem::replace(&mut *self.0.lock().unwrap(), Vec::new())
+  }
+}
+
+impl Write for Sink {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.0.lock().unwrap().extend_from_slice(buf);
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+trait Codec: Write + Send {
+  fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+macro_rules! impl_flate2_codec {
+  ($($t:ident),*) => {
+    $(
+      impl Codec for $t<Sink> {
+        fn finish(mut self: Box<Self>) -> io::Result<()> {
+          self.try_finish()
+        }
+      }
+    )*
+  };
+}
+
+impl_flate2_codec!(
+  GzEncoder,
+  GzDecoder,
+  ZlibEncoder,
+  ZlibDecoder,
+  DeflateEncoder,
+  DeflateDecoder
+);
+
+impl Codec for brotli::CompressorWriter<Sink> {
+  fn finish(self: Box<Self>) -> io::Result<()> {
+    self.into_inner();
+    Ok(())
+  }
+}
+
+impl Codec for brotli::DecompressorWriter<Sink> {
+  fn finish(self: Box<Self>) -> io::Result<()> {
+    self.into_inner().map(|_| ()).map_err(|_| {
+      io::Error::new(io::ErrorKind::InvalidData, "Truncated brotli stream")
+    })
+  }
+}
+
+const BROTLI_BUFFER_SIZE: usize = 4096;
+const BROTLI_QUALITY: u32 = 6;
+const BROTLI_LG_WINDOW_SIZE: u32 = 22;
+
+/// Compresses or decompresses a stream of chunks. Each call returns the
+/// output that became available, which may be empty.
+pub struct Transformer {
+  codec: Option<Box<dyn Codec>>,
+  sink: Sink,
+}
+
+impl Transformer {
+  pub fn compress(format: Format) -> Self {
+    let sink = Sink::default();
+    let out = sink.clone();
+    let level = Compression::default();
+    let codec: Box<dyn Codec> = match format {
+      Format::Gzip => Box::new(GzEncoder::new(out, level)),
+      Format::Deflate => Box::new(ZlibEncoder::new(out, level)),
+      Format::DeflateRaw => Box::new(DeflateEncoder::new(out, level)),
+      Format::Brotli => Box::new(brotli::CompressorWriter::new(
+        out,
+        BROTLI_BUFFER_SIZE,
+        BROTLI_QUALITY,
+        BROTLI_LG_WINDOW_SIZE,
+      )),
+    };
+    Self {
+      codec: Some(codec),
+      sink,
+    }
+  }
+
+  pub fn decompress(format: Format) -> Self {
+    let sink = Sink::default();
+    let out = sink.clone();
+    let codec: Box<dyn Codec> = match format {
+      Format::Gzip => Box::new(GzDecoder::new(out)),
+      Format::Deflate => Box::new(ZlibDecoder::new(out)),
+      Format::DeflateRaw => Box::new(DeflateDecoder::new(out)),
+      Format::Brotli => {
+        Box::new(brotli::DecompressorWriter::new(out, BROTLI_BUFFER_SIZE))
+      }
+    };
+    Self {
+      codec: Some(codec),
+      sink,
+    }
+  }
+
+  pub fn write(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+    let codec = self.codec.as_mut().ok_or_else(finished_error)?;
+    codec.write_all(chunk)?;
+    Ok(self.sink.take())
+  }
+
+  /// Ends the stream and returns the remaining output. Decompression fails
+  /// if the input was truncated.
+  pub fn finish(&mut self) -> io::Result<Vec<u8>> {
+    let codec = self.codec.take().ok_or_else(finished_error)?;
+    codec.finish()?;
+    Ok(self.sink.take())
+  }
+}
+
+fn finished_error() -> io::Error {
+  io::Error::new(io::ErrorKind::Other, "Stream already finished")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FORMATS: [Format; 4] = [
+    Format::Gzip,
+    Format::Deflate,
+    Format::DeflateRaw,
+    Format::Brotli,
+  ];
+
+  fn run(mut transformer: Transformer, chunks: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for chunk in chunks {
+      out.extend(transformer.write(chunk).unwrap());
+    }
+    out.extend(transformer.finish().unwrap());
+    out
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let input = b"hello world ".repeat(1000);
+    for format in FORMATS.iter() {
+      let compressed = run(
+        Transformer::compress(*format),
+        &[&input[..10], &input[10..]],
+      );
+      assert!(compressed.len() < input.len());
+      // Feed the compressed data back in small pieces.
+      let pieces: Vec<&[u8]> = compressed.chunks(7).collect();
+      let decompressed = run(Transformer::decompress(*format), &pieces);
+      assert_eq!(decompressed, input, "{:?}", format);
+    }
+  }
+
+  #[test]
+  fn test_truncated_input() {
+    for format in [Format::Gzip, Format::Brotli].iter() {
+      let compressed =
+        run(Transformer::compress(*format), &[&b"hello world"[..]]);
+      let mut transformer = Transformer::decompress(*format);
+      transformer
+        .write(&compressed[..compressed.len() / 2])
+        .unwrap();
+      assert!(transformer.finish().is_err(), "{:?}", format);
+    }
+  }
+
+  #[test]
+  fn test_write_after_finish() {
+    let mut transformer = Transformer::compress(Format::Gzip);
+    transformer.finish().unwrap();
+    assert!(transformer.write(b"hello").is_err());
+    assert!(transformer.finish().is_err());
+  }
+
+  #[test]
+  fn test_format_names() {
+    assert_eq!(Format::from_name("gzip"), Some(Format::Gzip));
+    assert_eq!(Format::from_name("deflate-raw"), Some(Format::DeflateRaw));
+    assert_eq!(Format::from_name("zstd"), None);
+    assert_eq!(Format::from_content_encoding("GZIP"), Some(Format::Gzip));
+    assert_eq!(Format::from_content_encoding(" br "), Some(Format::Brotli));
+    assert_eq!(Format::from_content_encoding("identity"), None);
+  }
+}
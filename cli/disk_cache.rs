@@ -1,4 +1,5 @@
 use crate::fs as deno_fs;
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs;
 use std::io;
@@ -7,7 +8,20 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::path::Prefix;
 use std::str;
+use std::time::SystemTime;
 use url::Url;
+use walkdir::WalkDir;
+
+/// Extensions of the files cached for a single module, longest first.
+const MODULE_EXTENSIONS: &[&str] = &[".js.map", ".buildinfo", ".meta", ".js"];
+
+/// Files cached for a single module.
+#[derive(Default)]
+struct CacheEntry {
+  files: Vec<PathBuf>,
+  size: u64,
+  modified: Option<SystemTime>,
+}
 
 #[derive(Clone)]
 pub struct DiskCache {
@@ -91,6 +105,15 @@ impl DiskCache {
 
         out = out.join(remaining_components);
       }
+      "npm" | "blob" => {
+        // Version ranges and the origins of object URLs may contain
+        // characters that Windows doesn't allow in filenames.
+        for path_seg in url.path().split('/') {
+          out.push(
+            path_seg.replace(|c: char| "<>:\"|?*".contains(c) || c == '%', "_"),
+          );
+        }
+      }
       scheme => {
         unimplemented!(
           "Don't know how to create cache name for scheme: {}",
@@ -139,6 +162,48 @@ impl DiskCache {
     let path = self.location.join(filename);
     fs::remove_file(path)
   }
+
+  /// Removes cached modules, least recently written first, until the cache
+  /// takes at most `max_size` bytes. Files belonging to the same module, eg.
+  /// `.js`, `.js.map` and `.meta`, are removed together. Returns the number
+  /// of bytes freed.
+  pub fn prune(&self, max_size: u64) -> std::io::Result<u64> {
+    let mut entries: HashMap<String, CacheEntry> = HashMap::new();
+    let mut total_size = 0;
+    for dir_entry in WalkDir::new(&self.location) {
+      let dir_entry = dir_entry?;
+      if !dir_entry.file_type().is_file() {
+        continue;
+      }
+      let metadata = dir_entry.metadata()?;
+      let path = dir_entry.path().to_string_lossy().to_string();
+      let key = MODULE_EXTENSIONS
+        .iter()
+        .find(|ext| path.ends_with(*ext))
+        .map_or(path.as_str(), |ext| &path[..path.len() - ext.len()])
+        .to_string();
+      let entry = entries.entry(key).or_default();
+      entry.files.push(dir_entry.path().to_owned());
+      entry.size += metadata.len();
+      entry.modified = entry.modified.max(Some(metadata.modified()?));
+      total_size += metadata.len();
+    }
+
+    let mut entries: Vec<CacheEntry> =
+      entries.into_iter().map(|e| e.1).collect();
+    entries.sort_by_key(|e| e.modified);
+    let mut freed = 0;
+    for entry in entries {
+      if total_size - freed <= max_size {
+        break;
+      }
+      for file in &entry.files {
+        fs::remove_file(file)?;
+      }
+      freed += entry.size;
+    }
+    Ok(freed)
+  }
 }
 
 #[cfg(test)]
@@ -156,6 +221,32 @@ mod tests {
     assert!(cache_path.is_dir());
   }
 
+  #[test]
+  fn test_prune() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache = DiskCache::new(temp_dir.path());
+    let old = Path::new("https/deno.land/old.ts");
+    let new = Path::new("https/deno.land/new.ts");
+    cache.set(&old.with_extension("ts.js"), &[0; 100]).unwrap();
+    cache
+      .set(&old.with_extension("ts.js.map"), &[0; 50])
+      .unwrap();
+    cache.set(&old.with_extension("ts.meta"), &[0; 10]).unwrap();
+    cache.set(&new.with_extension("ts.js"), &[0; 100]).unwrap();
+    cache.set(&new.with_extension("ts.meta"), &[0; 10]).unwrap();
+    for ext in &["ts.js", "ts.js.map", "ts.meta"] {
+      let path = temp_dir.path().join(old.with_extension(ext));
+      utime::set_file_times(&path, 1000, 1000).unwrap();
+    }
+
+    assert_eq!(cache.prune(1000).unwrap(), 0);
+    assert_eq!(cache.prune(200).unwrap(), 160);
+    assert!(cache.get(&old.with_extension("ts.js")).is_err());
+    assert!(cache.get(&old.with_extension("ts.js.map")).is_err());
+    assert!(cache.get(&new.with_extension("ts.js")).is_ok());
+    assert_eq!(cache.prune(0).unwrap(), 110);
+  }
+
   #[test]
   fn test_create_cache_if_dir_not_exits() {
     let temp_dir = TempDir::new().unwrap();
@@ -191,6 +282,12 @@ mod tests {
         "https://deno.land/std/http/file_server.ts",
         "https/deno.land/std/http/file_server.ts",
       ),
+      ("npm:express@4/lib/router", "npm/express@4/lib/router"),
+      ("npm:@scope/pkg@>=1", "npm/@scope/pkg@_=1"),
+      (
+        "blob:https://whatwg.org/d0360e2f-caee-469f-9a2f-87d5b0456f6f",
+        "blob/https_/whatwg.org/d0360e2f-caee-469f-9a2f-87d5b0456f6f",
+      ),
     ];
 
     if cfg!(target_os = "windows") {
@@ -0,0 +1,435 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Doc tests, run by `deno test --doc`: the JavaScript/TypeScript code blocks
+//! of JSDoc comments and Markdown files are turned into test modules, which
+//! are type checked and run like other tests.
+//!
+//! The test module of a code block from a JSDoc comment imports the exports
+//! of the documented module, so examples can use them without an import.
+//! Code blocks marked `ignore`, eg. ` ```ts ignore`, are skipped.
+
+use crate::swc_common::comments::CommentKind;
+use crate::swc_common::Span;
+use crate::swc_common::Spanned;
+use crate::swc_ecma_ast;
+use crate::swc_util::AstParser;
+use deno_core::ErrBox;
+use std::path::Path;
+use swc_ecma_visit::Node;
+use swc_ecma_visit::Visit;
+use url::Url;
+
+/// A test module generated for a code block.
+#[derive(Debug, PartialEq)]
+pub struct DocTest {
+  /// URL of the test module, next to the documented module so relative
+  /// imports resolve the same way.
+  pub url: Url,
+  pub source_code: String,
+}
+
+/// A fenced code block, with the lines of its fences.
+#[derive(Debug, PartialEq)]
+struct CodeBlock {
+  start_line: usize,
+  end_line: usize,
+  extension: &'static str,
+  code: String,
+}
+
+fn is_markdown(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|e| e.to_str())
+    .map_or(false, |e| {
+      let e = e.to_lowercase();
+      e == "md" || e == "markdown"
+    })
+}
+
+/// Whether a file may contain doc tests: JavaScript/TypeScript modules,
+/// which have JSDoc comments, and Markdown files.
+pub fn is_supported(path: &Path) -> bool {
+  crate::fmt::is_supported(path) || is_markdown(path)
+}
+
+/// Extension of the test module of a code block, by its info string.
+fn code_extension(info: &str) -> Option<&'static str> {
+  let mut words = info.split_whitespace();
+  let extension = match words.next()?.to_lowercase().as_str() {
+    "ts" | "typescript" => "ts",
+    "tsx" => "tsx",
+    "js" | "javascript" => "js",
+    "jsx" => "jsx",
+    _ => return None,
+  };
+  if words.any(|word| word == "ignore") {
+    return None;
+  }
+  Some(extension)
+}
+
+/// Finds the JavaScript/TypeScript code blocks of `text`, whose first line
+/// is line `first_line` of the file.
+fn code_blocks(text: &str, first_line: usize) -> Vec<CodeBlock> {
+  let lines: Vec<&str> = text.lines().collect();
+  let mut blocks = vec![];
+  let mut i = 0;
+  while i < lines.len() {
+    let line = lines[i].trim();
+    let fence_char = match line.chars().next() {
+      Some(c) if c == '`' || c == '~' => c,
+      _ => {
+        i += 1;
+        continue;
+      }
+    };
+    let fence = line.len() - line.trim_start_matches(fence_char).len();
+    if fence < 3 {
+      i += 1;
+      continue;
+    }
+    let info = &line[fence..];
+    let end = (i + 1..lines.len()).find(|j| {
+      let line = lines[*j].trim();
+      line.len() >= fence && line.chars().all(|c| c == fence_char)
+    });
+    let end = match end {
+      Some(end) => end,
+      None => break,
+    };
+    if let Some(extension) = code_extension(info) {
+      let indent = lines[i].len() - lines[i].trim_start().len();
+      let code = lines[i + 1..end]
+        .iter()
+        .map(|line| {
+          let line_indent = line.len() - line.trim_start().len();
+          &line[line_indent.min(indent)..]
+        })
+        .collect::<Vec<&str>>()
+        .join("\n");
+      blocks.push(CodeBlock {
+        start_line: first_line + i,
+        end_line: first_line + end,
+        extension,
+        code,
+      });
+    }
+    i = end + 1;
+  }
+  blocks
+}
+
+/// Text of a JSDoc comment, without the leading `*` of its lines.
+fn js_doc_text(comment_text: &str) -> String {
+  comment_text
+    .split('\n')
+    .map(|line| {
+      let line = line.trim_start();
+      let line = if line.starts_with('*') {
+        &line[1..]
+      } else {
+        line
+      };
+      if line.starts_with(' ') {
+        &line[1..]
+      } else {
+        line
+      }
+    })
+    .collect::<Vec<&str>>()
+    .join("\n")
+}
+
+/// Collects the code blocks of the JSDoc comments leading module items and
+/// class members, and the names the module exports.
+struct DocTestVisitor<'a> {
+  ast_parser: &'a AstParser,
+  code_blocks: Vec<CodeBlock>,
+  exports: Vec<String>,
+}
+
+impl<'a> DocTestVisitor<'a> {
+  fn collect(&mut self, span: Span) {
+    for comment in self.ast_parser.get_span_comments(span) {
+      if comment.kind != CommentKind::Block || !comment.text.starts_with('*') {
+        continue;
+      }
+      let line = self.ast_parser.get_span_location(comment.span).line;
+      let text = js_doc_text(&comment.text);
+      self.code_blocks.extend(code_blocks(&text, line));
+    }
+  }
+
+  fn add_export(&mut self, decl: &swc_ecma_ast::Decl) {
+    use swc_ecma_ast::Decl::*;
+    match decl {
+      Class(class_decl) => self.exports.push(class_decl.ident.sym.to_string()),
+      Fn(fn_decl) => self.exports.push(fn_decl.ident.sym.to_string()),
+      Var(var_decl) => {
+        for declarator in &var_decl.decls {
+          if let swc_ecma_ast::Pat::Ident(ident) = &declarator.name {
+            self.exports.push(ident.sym.to_string());
+          }
+        }
+      }
+      TsInterface(interface_decl) => {
+        self.exports.push(interface_decl.id.sym.to_string())
+      }
+      TsTypeAlias(type_alias_decl) => {
+        self.exports.push(type_alias_decl.id.sym.to_string())
+      }
+      TsEnum(enum_decl) => self.exports.push(enum_decl.id.sym.to_string()),
+      TsModule(_) => {}
+    }
+  }
+}
+
+impl<'a> Visit for DocTestVisitor<'a> {
+  fn visit_module_item(
+    &mut self,
+    module_item: &swc_ecma_ast::ModuleItem,
+    parent: &dyn Node,
+  ) {
+    use swc_ecma_ast::ModuleDecl;
+    self.collect(module_item.span());
+    match module_item {
+      swc_ecma_ast::ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(
+        export_decl,
+      )) => self.add_export(&export_decl.decl),
+      swc_ecma_ast::ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+        named_export,
+      )) if named_export.src.is_none() => {
+        for specifier in &named_export.specifiers {
+          if let swc_ecma_ast::ExportSpecifier::Named(specifier) = specifier {
+            let exported =
+              specifier.exported.as_ref().unwrap_or(&specifier.orig);
+            self.exports.push(exported.sym.to_string());
+          }
+        }
+      }
+      _ => {}
+    }
+    swc_ecma_visit::visit_module_item(self, module_item, parent);
+  }
+
+  fn visit_class_member(
+    &mut self,
+    class_member: &swc_ecma_ast::ClassMember,
+    parent: &dyn Node,
+  ) {
+    self.collect(class_member.span());
+    swc_ecma_visit::visit_class_member(self, class_member, parent);
+  }
+}
+
+/// Splits the code of a block into its import declarations, which must be at
+/// the top level of the test module, and the rest. Also returns the names
+/// the imports declare.
+fn split_imports(file_name: &str, code: &str) -> (String, String, Vec<String>) {
+  use swc_ecma_ast::ImportSpecifier::*;
+  let ast_parser = AstParser::new();
+  ast_parser.parse_module(file_name, code, |parse_result| {
+    let module = match parse_result {
+      Ok(module) => module,
+      // Left to the type checker to report.
+      Err(_) => return (String::new(), code.to_string(), vec![]),
+    };
+    let mut imports = vec![];
+    let mut body = code.to_string();
+    let mut names = vec![];
+    for module_item in module.body.iter().rev() {
+      let import_decl = match module_item {
+        swc_ecma_ast::ModuleItem::ModuleDecl(
+          swc_ecma_ast::ModuleDecl::Import(import_decl),
+        ) => import_decl,
+        _ => continue,
+      };
+      for specifier in &import_decl.specifiers {
+        let local = match specifier {
+          Specific(specifier) => &specifier.local,
+          Default(specifier) => &specifier.local,
+          Namespace(specifier) => &specifier.local,
+        };
+        names.push(local.sym.to_string());
+      }
+      let lo = ast_parser
+        .source_map
+        .lookup_byte_offset(import_decl.span.lo())
+        .pos
+        .0 as usize;
+      let hi = ast_parser
+        .source_map
+        .lookup_byte_offset(import_decl.span.hi())
+        .pos
+        .0 as usize;
+      imports.push(code[lo..hi].to_string());
+      body.replace_range(lo..hi, "");
+    }
+    imports.reverse();
+    (imports.join("\n"), body, names)
+  })
+}
+
+/// Renders the test module of a code block. `exports` are imported from
+/// `module_url`, unless the code imports a name of its own.
+fn render_doc_test(
+  module_url: &Url,
+  exports: &[String],
+  block: &CodeBlock,
+) -> Result<DocTest, ErrBox> {
+  let name = format!("{}${}-{}", module_url, block.start_line, block.end_line);
+  let url = Url::parse(&format!("{}.{}", name, block.extension))?;
+  let (imports, body, imported) = split_imports(url.as_str(), &block.code);
+
+  let mut source_code = String::new();
+  let exports: Vec<&str> = exports
+    .iter()
+    .filter(|export| !imported.contains(*export))
+    .map(String::as_str)
+    .collect();
+  if !exports.is_empty() {
+    source_code.push_str(&format!(
+      "import {{ {} }} from \"{}\";\n",
+      exports.join(", "),
+      module_url
+    ));
+  }
+  if !imports.is_empty() {
+    source_code.push_str(&imports);
+    source_code.push('\n');
+  }
+  source_code.push_str(&format!(
+    "Deno.test({}, async () => {{\n{}\n}});\n",
+    json!(name),
+    body.trim()
+  ));
+  Ok(DocTest { url, source_code })
+}
+
+/// Extracts the doc tests of the module or Markdown file at `module_url`.
+pub fn extract_doc_tests(
+  module_url: &Url,
+  source_code: &str,
+) -> Result<Vec<DocTest>, ErrBox> {
+  let (blocks, exports) = if is_markdown(Path::new(module_url.path())) {
+    (code_blocks(source_code, 1), vec![])
+  } else {
+    let ast_parser = AstParser::new();
+    ast_parser.parse_module(
+      module_url.as_str(),
+      source_code,
+      |parse_result| -> Result<_, ErrBox> {
+        let module = parse_result?;
+        let mut visitor = DocTestVisitor {
+          ast_parser: &ast_parser,
+          code_blocks: vec![],
+          exports: vec![],
+        };
+        visitor.visit_module(&module, &module);
+        Ok((visitor.code_blocks, visitor.exports))
+      },
+    )?
+  };
+  blocks
+    .iter()
+    .map(|block| render_doc_test(module_url, &exports, block))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_is_supported() {
+    assert!(is_supported(Path::new("mod.ts")));
+    assert!(is_supported(Path::new("README.md")));
+    assert!(!is_supported(Path::new("deno.json")));
+  }
+
+  #[test]
+  fn extract_markdown_code_blocks() {
+    let url = Url::parse("file:///project/README.md").unwrap();
+    let source = r#"# Title
+
+```ts
+import { assert } from "./testing.ts";
+assert(true);
+```
+
+```ts ignore
+notRun();
+```
+
+```sh
+deno test --doc
+```
+"#;
+    let doc_tests = extract_doc_tests(&url, source).unwrap();
+    assert_eq!(
+      doc_tests,
+      vec![DocTest {
+        url: Url::parse("file:///project/README.md$3-6.ts").unwrap(),
+        source_code: r#"import { assert } from "./testing.ts";
+Deno.test("file:///project/README.md$3-6", async () => {
+assert(true);
+});
+"#
+        .to_string(),
+      }]
+    );
+  }
+
+  #[test]
+  fn extract_js_doc_code_blocks() {
+    let url = Url::parse("file:///project/mod.ts").unwrap();
+    let source = r#"/**
+ * Adds numbers.
+ *
+ * ```js
+ * add(1, 2);
+ * ```
+ */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+export class Counter {
+  /**
+   * ```ts
+   * import { add } from "./mod.ts";
+   * new Counter().increment(add(1, 2));
+   * ```
+   */
+  increment(n: number) {}
+}
+"#;
+    let doc_tests = extract_doc_tests(&url, source).unwrap();
+    assert_eq!(
+      doc_tests,
+      vec![
+        DocTest {
+          url: Url::parse("file:///project/mod.ts$4-6.js").unwrap(),
+          source_code:
+            r#"import { add, Counter } from "file:///project/mod.ts";
+Deno.test("file:///project/mod.ts$4-6", async () => {
+add(1, 2);
+});
+"#
+            .to_string(),
+        },
+        DocTest {
+          url: Url::parse("file:///project/mod.ts$14-17.ts").unwrap(),
+          source_code: r#"import { Counter } from "file:///project/mod.ts";
+import { add } from "./mod.ts";
+Deno.test("file:///project/mod.ts$14-17", async () => {
+new Counter().increment(add(1, 2));
+});
+"#
+          .to_string(),
+        },
+      ]
+    );
+  }
+}
@@ -0,0 +1,358 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Foreign function interface behind `Deno.dlopen()`.
+//!
+//! Symbols of a dynamic library are called through libffi, with arguments
+//! and results converted from and to JSON numbers according to the types
+//! declared in JS. Pointers are plain numbers on the JS side; user space
+//! addresses fit in the 53 bits a number represents exactly.
+
+use crate::op_error::OpError;
+use dlopen::raw::Library;
+use libffi::middle::Arg;
+use libffi::middle::Cif;
+use libffi::middle::CodePtr;
+use libffi::middle::Type;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ffi::CStr;
+use std::ffi::OsStr;
+use std::os::raw::c_char;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NativeType {
+  Void,
+  U8,
+  I8,
+  U16,
+  I16,
+  U32,
+  I32,
+  U64,
+  I64,
+  USize,
+  ISize,
+  F32,
+  F64,
+  Pointer,
+  /// A `Uint8Array` passed as a pointer to its contents; parameters only.
+  Buffer,
+}
+
+impl From<NativeType> for Type {
+  fn from(native_type: NativeType) -> Self {
+    match native_type {
+      NativeType::Void => Type::void(),
+      NativeType::U8 => Type::u8(),
+      NativeType::I8 => Type::i8(),
+      NativeType::U16 => Type::u16(),
+      NativeType::I16 => Type::i16(),
+      NativeType::U32 => Type::u32(),
+      NativeType::I32 => Type::i32(),
+      NativeType::U64 => Type::u64(),
+      NativeType::I64 => Type::i64(),
+      NativeType::USize => Type::usize(),
+      NativeType::ISize => Type::isize(),
+      NativeType::F32 => Type::f32(),
+      NativeType::F64 => Type::f64(),
+      NativeType::Pointer | NativeType::Buffer => Type::pointer(),
+    }
+  }
+}
+
+/// Signature of a symbol as declared in the `symbols` of `Deno.dlopen()`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignFunctionDef {
+  pub parameters: Vec<NativeType>,
+  pub result: NativeType,
+  #[serde(default)]
+  pub nonblocking: bool,
+}
+
+/// A symbol ready to be called. Holds on to its library, which thus stays
+/// loaded during nonblocking calls even if the JS side closes it.
+#[derive(Clone)]
+pub struct ForeignFunction {
+  _lib: Arc<Library>,
+  ptr: usize,
+  pub def: ForeignFunctionDef,
+}
+
+pub struct DynamicLibraryResource {
+  pub symbols: HashMap<String, ForeignFunction>,
+}
+
+impl DynamicLibraryResource {
+  pub fn open<P: AsRef<OsStr>>(
+    path: P,
+    symbols: HashMap<String, ForeignFunctionDef>,
+  ) -> Result<Self, OpError> {
+    debug!("Loading dynamic library: {:#?}", path.as_ref());
+    let lib = Arc::new(Library::open(path)?);
+    let mut functions = HashMap::new();
+    for (name, def) in symbols {
+      if def.result == NativeType::Buffer {
+        return Err(OpError::type_error(format!(
+          "Symbol \"{}\": \"buffer\" is only valid as a parameter type",
+          name
+        )));
+      }
+      let buffers = def
+        .parameters
+        .iter()
+        .filter(|t| **t == NativeType::Buffer)
+        .count();
+      if buffers > 1 {
+        return Err(OpError::type_error(format!(
+          "Symbol \"{}\": at most one \"buffer\" parameter is supported",
+          name
+        )));
+      }
+      if def.parameters.contains(&NativeType::Void) {
+        return Err(OpError::type_error(format!(
+          "Symbol \"{}\": \"void\" is not a valid parameter type",
+          name
+        )));
+      }
+      let ptr = unsafe { lib.symbol::<*const c_void>(&name) }?;
+      let function = ForeignFunction {
+        _lib: lib.clone(),
+        ptr: ptr as usize,
+        def,
+      };
+      functions.insert(name, function);
+    }
+    Ok(Self { symbols: functions })
+  }
+}
+
+/// An argument converted to its native representation.
+enum NativeValue {
+  U8(u8),
+  I8(i8),
+  U16(u16),
+  I16(i16),
+  U32(u32),
+  I32(i32),
+  U64(u64),
+  I64(i64),
+  USize(usize),
+  ISize(isize),
+  F32(f32),
+  F64(f64),
+}
+
+impl NativeValue {
+  fn new(native_type: NativeType, value: &Value) -> Result<Self, OpError> {
+    let number = value.as_f64().ok_or_else(|| {
+      OpError::type_error(format!("Expected a number, got {}", value))
+    })?;
+    Ok(match native_type {
+      NativeType::U8 => NativeValue::U8(number as u8),
+      NativeType::I8 => NativeValue::I8(number as i8),
+      NativeType::U16 => NativeValue::U16(number as u16),
+      NativeType::I16 => NativeValue::I16(number as i16),
+      NativeType::U32 => NativeValue::U32(number as u32),
+      NativeType::I32 => NativeValue::I32(number as i32),
+      NativeType::U64 => NativeValue::U64(number as u64),
+      NativeType::I64 => NativeValue::I64(number as i64),
+      NativeType::USize | NativeType::Pointer => {
+        NativeValue::USize(number as usize)
+      }
+      NativeType::ISize => NativeValue::ISize(number as isize),
+      NativeType::F32 => NativeValue::F32(number as f32),
+      NativeType::F64 => NativeValue::F64(number),
+      NativeType::Void | NativeType::Buffer => unreachable!(),
+    })
+  }
+
+  fn as_arg(&self) -> Arg {
+    match self {
+      NativeValue::U8(v) => Arg::new(v),
+      NativeValue::I8(v) => Arg::new(v),
+      NativeValue::U16(v) => Arg::new(v),
+      NativeValue::I16(v) => Arg::new(v),
+      NativeValue::U32(v) => Arg::new(v),
+      NativeValue::I32(v) => Arg::new(v),
+      NativeValue::U64(v) => Arg::new(v),
+      NativeValue::I64(v) => Arg::new(v),
+      NativeValue::USize(v) => Arg::new(v),
+      NativeValue::ISize(v) => Arg::new(v),
+      NativeValue::F32(v) => Arg::new(v),
+      NativeValue::F64(v) => Arg::new(v),
+    }
+  }
+}
+
+impl ForeignFunction {
+  /// Calls the function with `args`, which hold `null` in place of the
+  /// `buffer` parameter, if any; `buffer` is passed there instead.
+  pub fn call(
+    &self,
+    args: &[Value],
+    buffer: Option<&mut [u8]>,
+  ) -> Result<Value, OpError> {
+    let def = &self.def;
+    if args.len() != def.parameters.len() {
+      return Err(OpError::type_error(format!(
+        "Expected {} arguments, got {}",
+        def.parameters.len(),
+        args.len()
+      )));
+    }
+    let buffer_ptr = buffer.map(|buf| buf.as_mut_ptr() as usize);
+    let values = def
+      .parameters
+      .iter()
+      .zip(args)
+      .map(|(native_type, value)| match native_type {
+        NativeType::Buffer => {
+          buffer_ptr.map(NativeValue::USize).ok_or_else(|| {
+            OpError::type_error("Expected a Uint8Array".to_string())
+          })
+        }
+        _ => NativeValue::new(*native_type, value),
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    let args: Vec<Arg> = values.iter().map(NativeValue::as_arg).collect();
+
+    let cif = Cif::new(
+      def.parameters.iter().map(|t| Type::from(*t)),
+      Type::from(def.result),
+    );
+    let code_ptr = CodePtr::from_ptr(self.ptr as *const c_void);
+    let result = unsafe {
+      match def.result {
+        NativeType::Void => {
+          cif.call::<()>(code_ptr, &args);
+          Value::Null
+        }
+        // libffi widens integer results smaller than a register to a
+        // whole `ffi_arg`, so those are read as such and truncated.
+        NativeType::U8 => json!(cif.call::<usize>(code_ptr, &args) as u8),
+        NativeType::I8 => json!(cif.call::<usize>(code_ptr, &args) as i8),
+        NativeType::U16 => json!(cif.call::<usize>(code_ptr, &args) as u16),
+        NativeType::I16 => json!(cif.call::<usize>(code_ptr, &args) as i16),
+        NativeType::U32 => json!(cif.call::<usize>(code_ptr, &args) as u32),
+        NativeType::I32 => json!(cif.call::<usize>(code_ptr, &args) as i32),
+        NativeType::U64 => json!(cif.call::<u64>(code_ptr, &args)),
+        NativeType::I64 => json!(cif.call::<i64>(code_ptr, &args)),
+        NativeType::USize | NativeType::Pointer => {
+          json!(cif.call::<usize>(code_ptr, &args))
+        }
+        NativeType::ISize => json!(cif.call::<isize>(code_ptr, &args)),
+        NativeType::F32 => json!(cif.call::<f32>(code_ptr, &args)),
+        NativeType::F64 => json!(cif.call::<f64>(code_ptr, &args)),
+        NativeType::Buffer => unreachable!(),
+      }
+    };
+    Ok(result)
+  }
+}
+
+/// Reads a value of `native_type` at `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must point to readable memory holding such a value.
+pub unsafe fn read_pointer(
+  ptr: usize,
+  native_type: NativeType,
+) -> Result<Value, OpError> {
+  let ptr = ptr as *const u8;
+  Ok(match native_type {
+    NativeType::U8 => json!(ptr.read_unaligned()),
+    NativeType::I8 => json!((ptr as *const i8).read_unaligned()),
+    NativeType::U16 => json!((ptr as *const u16).read_unaligned()),
+    NativeType::I16 => json!((ptr as *const i16).read_unaligned()),
+    NativeType::U32 => json!((ptr as *const u32).read_unaligned()),
+    NativeType::I32 => json!((ptr as *const i32).read_unaligned()),
+    NativeType::U64 => json!((ptr as *const u64).read_unaligned()),
+    NativeType::I64 => json!((ptr as *const i64).read_unaligned()),
+    NativeType::USize | NativeType::Pointer => {
+      json!((ptr as *const usize).read_unaligned())
+    }
+    NativeType::ISize => json!((ptr as *const isize).read_unaligned()),
+    NativeType::F32 => json!((ptr as *const f32).read_unaligned()),
+    NativeType::F64 => json!((ptr as *const f64).read_unaligned()),
+    NativeType::Void | NativeType::Buffer => {
+      return Err(OpError::type_error(
+        "Cannot read a value of this type".to_string(),
+      ))
+    }
+  })
+}
+
+/// Writes `value` as `native_type` at `ptr`.
+///
+/// # Safety
+///
+/// `ptr` must point to writable memory large enough for such a value.
+pub unsafe fn write_pointer(
+  ptr: usize,
+  native_type: NativeType,
+  value: &Value,
+) -> Result<(), OpError> {
+  if native_type == NativeType::Void || native_type == NativeType::Buffer {
+    return Err(OpError::type_error(
+      "Cannot write a value of this type".to_string(),
+    ));
+  }
+  let ptr = ptr as *mut u8;
+  match NativeValue::new(native_type, value)? {
+    NativeValue::U8(v) => ptr.write_unaligned(v),
+    NativeValue::I8(v) => (ptr as *mut i8).write_unaligned(v),
+    NativeValue::U16(v) => (ptr as *mut u16).write_unaligned(v),
+    NativeValue::I16(v) => (ptr as *mut i16).write_unaligned(v),
+    NativeValue::U32(v) => (ptr as *mut u32).write_unaligned(v),
+    NativeValue::I32(v) => (ptr as *mut i32).write_unaligned(v),
+    NativeValue::U64(v) => (ptr as *mut u64).write_unaligned(v),
+    NativeValue::I64(v) => (ptr as *mut i64).write_unaligned(v),
+    NativeValue::USize(v) => (ptr as *mut usize).write_unaligned(v),
+    NativeValue::ISize(v) => (ptr as *mut isize).write_unaligned(v),
+    NativeValue::F32(v) => (ptr as *mut f32).write_unaligned(v),
+    NativeValue::F64(v) => (ptr as *mut f64).write_unaligned(v),
+  }
+  Ok(())
+}
+
+/// Reads the NUL-terminated string at `ptr`, replacing invalid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must point to a NUL-terminated string.
+pub unsafe fn read_c_string(ptr: usize) -> String {
+  CStr::from_ptr(ptr as *const c_char)
+    .to_string_lossy()
+    .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_read_write_pointer() {
+    let mut memory = [0u8; 16];
+    let ptr = memory.as_mut_ptr() as usize;
+    unsafe {
+      write_pointer(ptr + 1, NativeType::I32, &json!(-42)).unwrap();
+      assert_eq!(read_pointer(ptr + 1, NativeType::I32).unwrap(), json!(-42));
+      write_pointer(ptr + 8, NativeType::F64, &json!(1.5)).unwrap();
+      assert_eq!(read_pointer(ptr + 8, NativeType::F64).unwrap(), json!(1.5));
+      assert!(write_pointer(ptr, NativeType::U8, &json!("a")).is_err());
+      assert!(read_pointer(ptr, NativeType::Void).is_err());
+    }
+  }
+
+  #[test]
+  fn test_read_c_string() {
+    let s = b"hello\0";
+    assert_eq!(unsafe { read_c_string(s.as_ptr() as usize) }, "hello");
+  }
+}
@@ -0,0 +1,398 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Collects the source files `deno fmt`, `deno lint` and `deno test` work
+//! on, from files, directories and glob patterns.
+//!
+//! Directories are walked in parallel. While walking, these are skipped:
+//!
+//! - `node_modules`, `vendor` and `.git` directories
+//! - entries matching the `.gitignore` files of the walked directories; they
+//!   follow the `.gitignore` syntax, including `!` negations, `/` anchors and
+//!   trailing `/` for directories
+//! - entries matching the `exclude` list, eg. of a config file
+//!
+//! Files given explicitly are only checked against the `exclude` list.
+
+use crate::fs::normalize_path;
+use deno_core::ErrBox;
+use glob::MatchOptions;
+use glob::Pattern;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::thread;
+
+const IGNORE_FILE_NAME: &str = ".gitignore";
+const SKIPPED_DIR_NAMES: &[&str] = &["node_modules", "vendor", ".git"];
+const MAX_WALKER_THREADS: usize = 8;
+
+fn match_options() -> MatchOptions {
+  MatchOptions {
+    require_literal_separator: true,
+    ..MatchOptions::new()
+  }
+}
+
+fn is_glob(entry: &str) -> bool {
+  entry.contains(|c| c == '*' || c == '?' || c == '[')
+}
+
+/// A line of an ignore file.
+#[derive(Clone, Debug)]
+struct IgnorePattern {
+  /// Directory of the ignore file.
+  base: PathBuf,
+  pattern: Pattern,
+  negated: bool,
+  dir_only: bool,
+  /// Patterns with a `/` match the path relative to `base`, others match
+  /// the file name at any depth.
+  anchored: bool,
+}
+
+impl IgnorePattern {
+  fn matches(&self, path: &Path, is_dir: bool) -> bool {
+    if self.dir_only && !is_dir {
+      return false;
+    }
+    let relative = match path.strip_prefix(&self.base) {
+      Ok(relative) => relative,
+      Err(_) => return false,
+    };
+    if self.anchored {
+      self.pattern.matches_path_with(relative, match_options())
+    } else {
+      path.file_name().map_or(false, |name| {
+        self
+          .pattern
+          .matches_with(&name.to_string_lossy(), match_options())
+      })
+    }
+  }
+}
+
+fn parse_ignore_file(base: &Path, source: &str) -> Vec<IgnorePattern> {
+  source
+    .lines()
+    .filter_map(|line| {
+      let line = line.trim_end();
+      if line.is_empty() || line.starts_with('#') {
+        return None;
+      }
+      let (negated, line) = if line.starts_with('!') {
+        (true, &line[1..])
+      } else {
+        (false, line)
+      };
+      let (dir_only, line) = if line.ends_with('/') {
+        (true, &line[..line.len() - 1])
+      } else {
+        (false, line)
+      };
+      let anchored = line.contains('/');
+      let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+      Some(IgnorePattern {
+        base: base.to_path_buf(),
+        pattern,
+        negated,
+        dir_only,
+        anchored,
+      })
+    })
+    .collect()
+}
+
+/// A directory to walk, with the ignore patterns in effect in it.
+struct Job {
+  dir: PathBuf,
+  ignore_patterns: Arc<Vec<IgnorePattern>>,
+}
+
+struct Queue {
+  jobs: Vec<Job>,
+  /// Jobs queued or being worked on.
+  pending: usize,
+}
+
+pub struct FileCollector {
+  filter: fn(&Path) -> bool,
+  /// Excluded directories, and the glob patterns of excluded files.
+  exclude: Vec<(PathBuf, Option<Pattern>)>,
+}
+
+impl FileCollector {
+  /// Files found in directories are collected if they match `filter`.
+  pub fn new(filter: fn(&Path) -> bool) -> Self {
+    FileCollector {
+      filter,
+      exclude: vec![],
+    }
+  }
+
+  /// Excludes directories and glob patterns, relative to `base`.
+  pub fn exclude(mut self, base: &Path, entries: &[String]) -> Self {
+    for entry in entries {
+      let path = normalize_path(&base.join(entry));
+      let pattern = Pattern::new(&path.to_string_lossy()).ok();
+      self.exclude.push((path, pattern));
+    }
+    self
+  }
+
+  fn is_excluded(&self, path: &Path) -> bool {
+    self.exclude.iter().any(|(excluded, pattern)| {
+      path.starts_with(excluded)
+        || pattern.as_ref().map_or(false, |pattern| {
+          pattern.matches_path_with(path, match_options())
+        })
+    })
+  }
+
+  fn is_ignored(
+    &self,
+    path: &Path,
+    is_dir: bool,
+    ignore_patterns: &[IgnorePattern],
+  ) -> bool {
+    if is_dir {
+      let name = path.file_name().map(|name| name.to_string_lossy());
+      if let Some(name) = name {
+        if SKIPPED_DIR_NAMES.contains(&name.as_ref()) {
+          return true;
+        }
+      }
+    }
+    if self.is_excluded(path) {
+      return true;
+    }
+    // The last matching pattern decides, so negations can re-include paths.
+    let mut ignored = false;
+    for pattern in ignore_patterns {
+      if pattern.matches(path, is_dir) {
+        ignored = !pattern.negated;
+      }
+    }
+    ignored
+  }
+
+  /// Collects the files in `include`, which are files, directories or glob
+  /// patterns relative to `root`. Returns sorted paths without duplicates.
+  pub fn collect(
+    self,
+    root: &Path,
+    include: &[String],
+  ) -> Result<Vec<PathBuf>, ErrBox> {
+    let mut files = vec![];
+    let mut dirs = vec![];
+    for entry in include {
+      if !is_glob(entry) {
+        let path = normalize_path(&root.join(entry));
+        if path.is_dir() {
+          dirs.push(path);
+        } else if !self.is_excluded(&path) {
+          files.push(path);
+        }
+        continue;
+      }
+      let pattern = root.join(entry);
+      let paths = glob::glob(&pattern.to_string_lossy())?;
+      for path in paths.filter_map(Result::ok) {
+        let in_skipped_dir = path.strip_prefix(root).map_or(false, |path| {
+          path.components().any(|component| {
+            let component = component.as_os_str().to_string_lossy();
+            SKIPPED_DIR_NAMES.contains(&component.as_ref())
+          })
+        });
+        if in_skipped_dir || self.is_excluded(&path) {
+          continue;
+        }
+        if path.is_dir() {
+          dirs.push(path);
+        } else if (self.filter)(&path) {
+          files.push(path);
+        }
+      }
+    }
+    dirs.retain(|dir| !self.is_excluded(dir));
+    files.extend(self.walk(dirs));
+    files.sort();
+    files.dedup();
+    Ok(files)
+  }
+
+  /// Returns the files matching the filter in the subtrees of `dirs`.
+  fn walk(self, dirs: Vec<PathBuf>) -> Vec<PathBuf> {
+    let jobs: Vec<Job> = dirs
+      .into_iter()
+      .map(|dir| Job {
+        dir,
+        ignore_patterns: Arc::new(vec![]),
+      })
+      .collect();
+    let pending = jobs.len();
+    let queue = Arc::new((Mutex::new(Queue { jobs, pending }), Condvar::new()));
+    let files = Arc::new(Mutex::new(vec![]));
+    let collector = Arc::new(self);
+
+    let thread_count = sys_info::cpu_num()
+      .map(|count| count as usize)
+      .unwrap_or(1)
+      .min(MAX_WALKER_THREADS)
+      .max(1);
+    let handles: Vec<_> = (0..thread_count)
+      .map(|_| {
+        let queue = queue.clone();
+        let files = files.clone();
+        let collector = collector.clone();
+        thread::spawn(move || collector.work(&queue, &files))
+      })
+      .collect();
+    for handle in handles {
+      handle.join().unwrap();
+    }
+
+    let mut files = files.lock().unwrap();
+    std::mem::take(&mut *files)
+  }
+
+  /// Walks directories from the queue until all of them are walked.
+  fn work(&self, queue: &(Mutex<Queue>, Condvar), files: &Mutex<Vec<PathBuf>>) {
+    let (queue, queue_changed) = queue;
+    loop {
+      let job = {
+        let mut queue = queue.lock().unwrap();
+        loop {
+          if let Some(job) = queue.jobs.pop() {
+            break job;
+          }
+          if queue.pending == 0 {
+            return;
+          }
+          queue = queue_changed.wait(queue).unwrap();
+        }
+      };
+      let (subdir_jobs, dir_files) = self.read_dir(job);
+      files.lock().unwrap().extend(dir_files);
+      let mut queue = queue.lock().unwrap();
+      queue.pending += subdir_jobs.len();
+      queue.pending -= 1;
+      queue.jobs.extend(subdir_jobs);
+      queue_changed.notify_all();
+    }
+  }
+
+  /// Returns the jobs of the subdirectories and the files of a directory.
+  fn read_dir(&self, job: Job) -> (Vec<Job>, Vec<PathBuf>) {
+    let mut ignore_patterns = job.ignore_patterns;
+    if let Ok(source) = fs::read_to_string(job.dir.join(IGNORE_FILE_NAME)) {
+      let patterns = parse_ignore_file(&job.dir, &source);
+      if !patterns.is_empty() {
+        let mut inherited = (*ignore_patterns).clone();
+        inherited.extend(patterns);
+        ignore_patterns = Arc::new(inherited);
+      }
+    }
+
+    let mut subdir_jobs = vec![];
+    let mut files = vec![];
+    let entries = match fs::read_dir(&job.dir) {
+      Ok(entries) => entries,
+      Err(_) => return (subdir_jobs, files),
+    };
+    for entry in entries.filter_map(Result::ok) {
+      let path = entry.path();
+      // Symlinks to directories aren't followed.
+      let is_dir = entry.file_type().map_or(false, |t| t.is_dir());
+      if self.is_ignored(&path, is_dir, &ignore_patterns) {
+        continue;
+      }
+      if is_dir {
+        subdir_jobs.push(Job {
+          dir: path,
+          ignore_patterns: ignore_patterns.clone(),
+        });
+      } else if path.is_file() && (self.filter)(&path) {
+        files.push(path);
+      }
+    }
+    (subdir_jobs, files)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn is_ts(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "ts")
+  }
+
+  fn relative(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+    files
+      .iter()
+      .map(|file| {
+        let file = file.strip_prefix(root).unwrap();
+        file.to_string_lossy().replace('\\', "/")
+      })
+      .collect()
+  }
+
+  #[test]
+  fn collect_files() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let root = temp_dir.path();
+    for file in &[
+      "a.ts",
+      "b.md",
+      "x.gen.ts",
+      "keep.gen.ts",
+      "build/d.ts",
+      "node_modules/m.ts",
+      "vendor/v.ts",
+      "sub/c.ts",
+      "sub/c2.ts",
+      "excluded/e.ts",
+    ] {
+      let path = root.join(file);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, "").unwrap();
+    }
+    fs::write(root.join(".gitignore"), "build/\n*.gen.ts\n!keep.gen.ts\n")
+      .unwrap();
+    fs::write(root.join("sub/.gitignore"), "# comment\n/c2.ts\n").unwrap();
+
+    let files = FileCollector::new(is_ts)
+      .exclude(root, &["excluded".to_string()])
+      .collect(root, &[".".to_string()])
+      .unwrap();
+    assert_eq!(
+      relative(root, files),
+      vec!["a.ts", "keep.gen.ts", "sub/c.ts"]
+    );
+
+    // Explicit files aren't filtered, and globs don't use ignore files.
+    let files = FileCollector::new(is_ts)
+      .collect(root, &["b.md".to_string(), "sub/*.ts".to_string()])
+      .unwrap();
+    assert_eq!(relative(root, files), vec!["b.md", "sub/c.ts", "sub/c2.ts"]);
+
+    let files = FileCollector::new(is_ts)
+      .exclude(root, &["**/c2.ts".to_string()])
+      .collect(root, &["**/*.ts".to_string()])
+      .unwrap();
+    assert_eq!(
+      relative(root, files),
+      vec![
+        "a.ts",
+        "build/d.ts",
+        "excluded/e.ts",
+        "keep.gen.ts",
+        "sub/c.ts",
+        "x.gen.ts"
+      ]
+    );
+  }
+}
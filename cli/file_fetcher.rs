@@ -1,10 +1,14 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::blob_store::BlobStore;
 use crate::colors;
 use crate::http_cache::HttpCache;
 use crate::http_util;
 use crate::http_util::create_http_client;
 use crate::http_util::FetchOnceResult;
+use crate::media_type;
 use crate::msg;
+use crate::npm;
+use crate::npm::NpmCache;
 use crate::op_error::OpError;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
@@ -15,7 +19,6 @@ use std::collections::HashMap;
 use std::fs;
 use std::future::Future;
 use std::io::Read;
-use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::result::Result;
@@ -57,7 +60,8 @@ impl SourceFileCache {
   }
 }
 
-const SUPPORTED_URL_SCHEMES: [&str; 3] = ["http", "https", "file"];
+const SUPPORTED_URL_SCHEMES: [&str; 5] =
+  ["http", "https", "file", "npm", "blob"];
 
 #[derive(Clone)]
 pub struct SourceFileFetcher {
@@ -66,18 +70,24 @@ pub struct SourceFileFetcher {
   use_disk_cache: bool,
   no_remote: bool,
   cached_only: bool,
+  compat: bool,
   http_client: reqwest::Client,
   // This field is public only to expose it's location
   pub http_cache: HttpCache,
+  npm_cache: NpmCache,
+  blob_store: BlobStore,
 }
 
 impl SourceFileFetcher {
   pub fn new(
     http_cache: HttpCache,
+    npm_cache: NpmCache,
+    blob_store: BlobStore,
     use_disk_cache: bool,
     cache_blacklist: Vec<String>,
     no_remote: bool,
     cached_only: bool,
+    compat: bool,
     ca_file: Option<String>,
   ) -> Result<Self, ErrBox> {
     let file_fetcher = Self {
@@ -87,7 +97,10 @@ impl SourceFileFetcher {
       use_disk_cache,
       no_remote,
       cached_only,
+      compat,
       http_client: create_http_client(ca_file)?,
+      npm_cache,
+      blob_store,
     };
 
     Ok(file_fetcher)
@@ -144,6 +157,30 @@ impl SourceFileFetcher {
     self.source_file_cache.set(specifier.to_string(), file);
   }
 
+  /// Reads the program of `deno run -` from stdin and saves it into cache as
+  /// the pseudo-module `$CWD/$deno$stdin.<ext>`, so its relative imports
+  /// resolve against the current directory and `ext` sets its media type.
+  pub fn save_stdin_source_file(
+    &self,
+    ext: &str,
+  ) -> Result<ModuleSpecifier, ErrBox> {
+    let mut source_code = Vec::new();
+    std::io::stdin().read_to_end(&mut source_code)?;
+    let filename =
+      std::env::current_dir()?.join(format!("$deno$stdin.{}", ext));
+    let url = Url::from_file_path(&filename).unwrap();
+    let specifier = ModuleSpecifier::from(url.clone());
+    let source_file = SourceFile {
+      media_type: media_type::map_file_extension(&filename),
+      url,
+      filename,
+      types_url: None,
+      source_code,
+    };
+    self.save_source_file_in_cache(&specifier, source_file);
+    Ok(specifier)
+  }
+
   pub async fn fetch_source_file(
     &self,
     specifier: &ModuleSpecifier,
@@ -232,6 +269,14 @@ impl SourceFileFetcher {
       return self.fetch_local_file(&module_url).map(Some);
     }
 
+    if url_scheme == "blob" {
+      return self.fetch_blob_url(&module_url).map(Some);
+    }
+
+    if url_scheme == "npm" {
+      return self.npm_cache.fetch_cached_source_file(&module_url);
+    }
+
     self.fetch_cached_remote_source(&module_url)
   }
 
@@ -262,6 +307,10 @@ impl SourceFileFetcher {
       return self.fetch_local_file(&module_url);
     }
 
+    if url_scheme == "blob" {
+      return self.fetch_blob_url(&module_url);
+    }
+
     // The file is remote, fail if `no_remote` is true.
     if no_remote {
       let e = std::io::Error::new(
@@ -274,6 +323,13 @@ impl SourceFileFetcher {
       return Err(e.into());
     }
 
+    if url_scheme == "npm" {
+      return self
+        .npm_cache
+        .fetch_source_file(&self.http_client, &module_url, cached_only)
+        .await;
+    }
+
     // Fetch remote file and cache on-disk for subsequent access
     self
       .fetch_remote_source(&module_url, use_disk_cache, cached_only, 10)
@@ -288,12 +344,25 @@ impl SourceFileFetcher {
       ))
     })?;
 
+    // In `--compat` mode, CommonJS modules of packages in `node_modules`
+    // are loaded through `require()`.
+    if self.compat && npm::is_commonjs_package_file(&filepath) {
+      let source = npm::wrapper_source(&filepath, false);
+      return Ok(SourceFile {
+        url: module_url.clone(),
+        filename: filepath,
+        media_type: msg::MediaType::JavaScript,
+        source_code: source.into_bytes(),
+        types_url: None,
+      });
+    }
+
     let source_code = match fs::read(filepath.clone()) {
       Ok(c) => c,
       Err(e) => return Err(e.into()),
     };
 
-    let media_type = map_content_type(&filepath, None);
+    let media_type = media_type::map_file_extension(&filepath);
     let types_url = match media_type {
       msg::MediaType::JavaScript | msg::MediaType::JSX => {
         get_types_url(&module_url, &source_code, None)
@@ -309,6 +378,31 @@ impl SourceFileFetcher {
     })
   }
 
+  /// Fetch the blob of an object URL made by `URL.createObjectURL()`. Its
+  /// type is the media type of the module.
+  fn fetch_blob_url(&self, module_url: &Url) -> Result<SourceFile, ErrBox> {
+    let blob = self.blob_store.get(module_url).ok_or_else(|| {
+      ErrBox::from(OpError::not_found(format!(
+        "Blob URL not found: \"{}\"",
+        module_url
+      )))
+    })?;
+    let filename = PathBuf::from(module_url.path());
+    let content_type = if blob.media_type.is_empty() {
+      None
+    } else {
+      Some(blob.media_type.as_str())
+    };
+    let media_type = media_type::map_content_type(&filename, content_type);
+    Ok(SourceFile {
+      url: module_url.clone(),
+      filename,
+      media_type,
+      source_code: blob.to_vec(),
+      types_url: None,
+    })
+  }
+
   /// Fetch cached remote file.
   ///
   /// This is a recursive operation if source file has redirections.
@@ -358,11 +452,10 @@ impl SourceFileFetcher {
     source_file.read_to_end(&mut source_code)?;
 
     let cache_filename = self.http_cache.get_cache_filename(module_url);
-    let fake_filepath = PathBuf::from(module_url.path());
-    let media_type = map_content_type(
-      &fake_filepath,
+    let media_type = media_type::map_remote(
+      module_url,
       headers.get("content-type").map(|e| e.as_str()),
-    );
+    )?;
     let types_url = match media_type {
       msg::MediaType::JavaScript | msg::MediaType::JSX => get_types_url(
         &module_url,
@@ -398,11 +491,16 @@ impl SourceFileFetcher {
 
     let is_blacklisted =
       check_cache_blacklist(module_url, self.cache_blacklist.as_ref());
+    // Cached copy that has to be revalidated with the server before use.
+    let mut stale_source_file = None;
     // First try local cache
     if use_disk_cache && !is_blacklisted {
       match self.fetch_cached_remote_source(&module_url) {
         Ok(Some(source_file)) => {
-          return futures::future::ok(source_file).boxed_local();
+          if cached_only || self.http_cache.is_fresh(&module_url) {
+            return futures::future::ok(source_file).boxed_local();
+          }
+          stale_source_file = Some(source_file);
         }
         Ok(None) => {
           // there's no cached version
@@ -437,16 +535,38 @@ impl SourceFileFetcher {
 
     let dir = self.clone();
     let module_url = module_url.clone();
-    let module_etag = match self.http_cache.get(&module_url) {
-      Ok((_, headers)) => headers.get("etag").map(String::from),
-      Err(_) => None,
-    };
+    let (module_etag, module_last_modified) =
+      match self.http_cache.get(&module_url) {
+        Ok((_, headers)) => (
+          headers.get("etag").map(String::from),
+          headers.get("last-modified").map(String::from),
+        ),
+        Err(_) => (None, None),
+      };
     let http_client = self.http_client.clone();
     // Single pass fetch, either yields code or yields redirect.
     let f = async move {
-      match http_util::fetch_once(http_client, &module_url, module_etag).await?
-      {
+      let result = http_util::fetch_once(
+        http_client,
+        &module_url,
+        module_etag,
+        module_last_modified,
+      )
+      .await;
+      let result = match (result, stale_source_file) {
+        // Keep working offline with the stale copy.
+        (Err(err), Some(source_file)) => {
+          warn!(
+            "Could not revalidate {}, using the cached copy: {}",
+            module_url, err
+          );
+          return Ok(source_file);
+        }
+        (result, _) => result?,
+      };
+      match result {
         FetchOnceResult::NotModified => {
+          dir.http_cache.mark_revalidated(&module_url)?;
           let source_file =
             dir.fetch_cached_remote_source(&module_url)?.unwrap();
 
@@ -471,12 +591,10 @@ impl SourceFileFetcher {
           dir.http_cache.set(&module_url, headers.clone(), &source)?;
 
           let cache_filepath = dir.http_cache.get_cache_filename(&module_url);
-          // Used to sniff out content type from file extension - probably to be removed
-          let fake_filepath = PathBuf::from(module_url.path());
-          let media_type = map_content_type(
-            &fake_filepath,
+          let media_type = media_type::map_remote(
+            &module_url,
             headers.get("content-type").map(String::as_str),
-          );
+          )?;
 
           let types_url = match media_type {
             msg::MediaType::JavaScript | msg::MediaType::JSX => get_types_url(
@@ -504,75 +622,6 @@ impl SourceFileFetcher {
   }
 }
 
-fn map_file_extension(path: &Path) -> msg::MediaType {
-  match path.extension() {
-    None => msg::MediaType::Unknown,
-    Some(os_str) => match os_str.to_str() {
-      Some("ts") => msg::MediaType::TypeScript,
-      Some("tsx") => msg::MediaType::TSX,
-      Some("js") => msg::MediaType::JavaScript,
-      Some("jsx") => msg::MediaType::JSX,
-      Some("mjs") => msg::MediaType::JavaScript,
-      Some("json") => msg::MediaType::Json,
-      Some("wasm") => msg::MediaType::Wasm,
-      _ => msg::MediaType::Unknown,
-    },
-  }
-}
-
-// convert a ContentType string into a enumerated MediaType
-fn map_content_type(path: &Path, content_type: Option<&str>) -> msg::MediaType {
-  match content_type {
-    Some(content_type) => {
-      // sometimes there is additional data after the media type in
-      // Content-Type so we have to do a bit of manipulation so we are only
-      // dealing with the actual media type
-      let ct_vector: Vec<&str> = content_type.split(';').collect();
-      let ct: &str = ct_vector.first().unwrap();
-      match ct.to_lowercase().as_ref() {
-        "application/typescript"
-        | "text/typescript"
-        | "video/vnd.dlna.mpeg-tts"
-        | "video/mp2t"
-        | "application/x-typescript" => {
-          map_js_like_extension(path, msg::MediaType::TypeScript)
-        }
-        "application/javascript"
-        | "text/javascript"
-        | "application/ecmascript"
-        | "text/ecmascript"
-        | "application/x-javascript" => {
-          map_js_like_extension(path, msg::MediaType::JavaScript)
-        }
-        "application/json" | "text/json" => msg::MediaType::Json,
-        "application/wasm" => msg::MediaType::Wasm,
-        // Handle plain and possibly webassembly
-        "text/plain" | "application/octet-stream" => map_file_extension(path),
-        _ => {
-          debug!("unknown content type: {}", content_type);
-          msg::MediaType::Unknown
-        }
-      }
-    }
-    None => map_file_extension(path),
-  }
-}
-
-fn map_js_like_extension(
-  path: &Path,
-  default: msg::MediaType,
-) -> msg::MediaType {
-  match path.extension() {
-    None => default,
-    Some(os_str) => match os_str.to_str() {
-      None => default,
-      Some("jsx") => msg::MediaType::JSX,
-      Some("tsx") => msg::MediaType::TSX,
-      Some(_) => default,
-    },
-  }
-}
-
 /// Take a module URL and source code and determines if the source code contains
 /// a type directive, and if so, returns the parsed URL for that type directive.
 fn get_types_url(
@@ -591,20 +640,23 @@ fn get_types_url(
   }
 
   match maybe_types_header {
-    Some(types_header) => match Url::parse(&types_header) {
+    // A broken header shouldn't make the module itself fail to load; it's
+    // type checked without types instead.
+    Some(types_header) => match module_url.join(types_header) {
       Ok(url) => Some(url),
-      _ => Some(module_url.join(&types_header).unwrap()),
-    },
-    _ => match DIRECTIVE_TYPES.captures(str::from_utf8(source_code).unwrap()) {
-      Some(cap) => {
-        let val = cap.get(1).unwrap().as_str();
-        match Url::parse(&val) {
-          Ok(url) => Some(url),
-          _ => Some(module_url.join(&val).unwrap()),
-        }
+      Err(err) => {
+        warn!(
+          "Ignoring invalid X-TypeScript-Types header \"{}\" of \"{}\": {}",
+          types_header, module_url, err
+        );
+        None
       }
-      _ => None,
     },
+    _ => {
+      let source_code = str::from_utf8(source_code).ok()?;
+      let cap = DIRECTIVE_TYPES.captures(source_code)?;
+      module_url.join(cap.get(1).unwrap().as_str()).ok()
+    }
   }
 }
 
@@ -657,15 +709,21 @@ pub struct SourceCodeHeaders {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::blob_store::Blob;
+  use crate::permissions::Permissions;
+  use std::path::Path;
   use tempfile::TempDir;
 
   fn setup_file_fetcher(dir_path: &Path) -> SourceFileFetcher {
     SourceFileFetcher::new(
       HttpCache::new(&dir_path.to_path_buf().join("deps")),
+      NpmCache::new(&dir_path.join("npm"), Permissions::default()),
+      BlobStore::default(),
       true,
       vec![],
       false,
       false,
+      false,
       None,
     )
     .expect("setup fail")
@@ -1339,6 +1397,26 @@ mod tests {
     assert!(r.is_ok());
   }
 
+  #[tokio::test]
+  async fn test_fetch_blob_url() {
+    let (_temp_dir, fetcher) = test_setup();
+    let mut blob = Blob::new("application/typescript".to_string());
+    blob.push_bytes(b"export const a: number = 1;".to_vec());
+    let url = fetcher
+      .blob_store
+      .create_object_url("http://localhost:4545", Arc::new(blob));
+    let specifier = ModuleSpecifier::resolve_url(&url).unwrap();
+    let source_file =
+      fetcher.fetch_source_file(&specifier, None).await.unwrap();
+    assert_eq!(source_file.media_type, msg::MediaType::TypeScript);
+    assert_eq!(source_file.source_code, b"export const a: number = 1;");
+
+    fetcher.blob_store.revoke_object_url(specifier.as_url());
+    let specifier =
+      ModuleSpecifier::resolve_url(&format!("{}#fragment", url)).unwrap();
+    assert!(fetcher.fetch_source_file(&specifier, None).await.is_err());
+  }
+
   #[tokio::test]
   async fn test_fetch_source_file_1() {
     /*recompile ts file*/
@@ -1376,7 +1454,7 @@ mod tests {
     // unsupported schemes
     let test_cases = [
       "ftp://localhost:4545/testdata/subdir/print_hello.ts",
-      "blob:https://whatwg.org/d0360e2f-caee-469f-9a2f-87d5b0456f6f",
+      "data:text/javascript,console.log(1)",
     ];
 
     for &test in test_cases.iter() {
@@ -1385,216 +1463,6 @@ mod tests {
     }
   }
 
-  #[test]
-  fn test_map_file_extension() {
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.ts")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.tsx")),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.d.ts")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.js")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.jsx")),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.json")),
-      msg::MediaType::Json
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.wasm")),
-      msg::MediaType::Wasm
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar.txt")),
-      msg::MediaType::Unknown
-    );
-    assert_eq!(
-      map_file_extension(Path::new("foo/bar")),
-      msg::MediaType::Unknown
-    );
-  }
-
-  #[test]
-  fn test_map_content_type_extension_only() {
-    // Extension only
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.ts"), None),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.tsx"), None),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.d.ts"), None),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.js"), None),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.txt"), None),
-      msg::MediaType::Unknown
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.jsx"), None),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.json"), None),
-      msg::MediaType::Json
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.wasm"), None),
-      msg::MediaType::Wasm
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), None),
-      msg::MediaType::Unknown
-    );
-  }
-
-  #[test]
-  fn test_map_content_type_media_type_with_no_extension() {
-    // Media Type
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/typescript")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("text/typescript")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("video/vnd.dlna.mpeg-tts")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("video/mp2t")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/x-typescript")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/javascript")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("text/javascript")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/ecmascript")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("text/ecmascript")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/x-javascript")),
-      msg::MediaType::JavaScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("application/json")),
-      msg::MediaType::Json
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar"), Some("text/json")),
-      msg::MediaType::Json
-    );
-  }
-
-  #[test]
-  fn test_map_file_extension_media_type_with_extension() {
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.ts"), Some("text/plain")),
-      msg::MediaType::TypeScript
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.ts"), Some("foo/bar")),
-      msg::MediaType::Unknown
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.tsx"),
-        Some("application/typescript"),
-      ),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.tsx"),
-        Some("application/javascript"),
-      ),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.tsx"),
-        Some("application/x-typescript"),
-      ),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.tsx"),
-        Some("video/vnd.dlna.mpeg-tts"),
-      ),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.tsx"), Some("video/mp2t")),
-      msg::MediaType::TSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.jsx"),
-        Some("application/javascript"),
-      ),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.jsx"),
-        Some("application/x-typescript"),
-      ),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.jsx"),
-        Some("application/ecmascript"),
-      ),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_content_type(Path::new("foo/bar.jsx"), Some("text/ecmascript")),
-      msg::MediaType::JSX
-    );
-    assert_eq!(
-      map_content_type(
-        Path::new("foo/bar.jsx"),
-        Some("application/x-javascript"),
-      ),
-      msg::MediaType::JSX
-    );
-  }
-
   #[test]
   fn test_filter_shebang() {
     assert_eq!(filter_shebang(b"#!"[..].to_owned()), b"");
@@ -1646,6 +1514,50 @@ mod tests {
     drop(http_server_guard);
   }
 
+  #[tokio::test]
+  async fn test_fetch_revalidates_stale() {
+    let http_server_guard = crate::test_util::http_server();
+    let (_temp_dir, fetcher) = test_setup();
+    let module_url =
+      Url::parse("http://127.0.0.1:4545/max_age_script.ts").unwrap();
+
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.source_code, b"console.log('max-age')");
+
+    // The response has `max-age=0`, so the cached copy is revalidated; the
+    // server says it's current.
+    let file_name = fetcher.http_cache.get_cache_filename(&module_url);
+    let _ = fs::write(&file_name, "changed content");
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.source_code, b"changed content");
+
+    // Without a validator the server sends the module again.
+    let mut metadata = fetcher.http_cache.get_metadata(&module_url).unwrap();
+    metadata.headers.remove("etag");
+    metadata.write(&file_name).unwrap();
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, false, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.source_code, b"console.log('max-age')");
+
+    // `--cached-only` never revalidates.
+    let _ = fs::write(&file_name, "changed content");
+    let source = fetcher
+      .fetch_remote_source(&module_url, true, true, 1)
+      .await
+      .unwrap();
+    assert_eq!(source.source_code, b"changed content");
+
+    drop(http_server_guard);
+  }
+
   #[test]
   fn test_get_types_url_1() {
     let module_url = Url::parse("https://example.com/mod.js").unwrap();
@@ -1725,6 +1637,17 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_get_types_url_invalid() {
+    let module_url = Url::parse("https://example.com/mod.js").unwrap();
+    let source_code = b"console.log(\"foo\");".to_vec();
+    let result =
+      get_types_url(&module_url, &source_code, Some("https://[mod.d.ts"));
+    assert_eq!(result, None);
+    let result = get_types_url(&module_url, &[0xff, 0xfe], None);
+    assert_eq!(result, None);
+  }
+
   #[tokio::test]
   async fn test_fetch_with_types_header() {
     let http_server_guard = crate::test_util::http_server();
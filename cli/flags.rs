@@ -6,8 +6,12 @@ use clap::Arg;
 use clap::ArgMatches;
 use clap::SubCommand;
 use log::Level;
+use std::env;
+use std::error::Error;
+use std::fmt;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use url::Url;
 
 /// Creates vector of strings, Vec<String>
 macro_rules! svec {
@@ -16,9 +20,18 @@ macro_rules! svec {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum DenoSubcommand {
+  Bench {
+    include: Option<Vec<String>>,
+    filter: Option<String>,
+  },
   Bundle {
     source_file: String,
     out_file: Option<PathBuf>,
+    minify: bool,
+  },
+  Compile {
+    source_file: String,
+    output: Option<PathBuf>,
   },
   Completions {
     buf: Box<[u8]>,
@@ -31,9 +44,11 @@ pub enum DenoSubcommand {
   Eval {
     code: String,
     as_typescript: bool,
+    print: bool,
   },
   Cache {
     files: Vec<String>,
+    prune: Option<u64>,
   },
   Fmt {
     check: bool,
@@ -42,6 +57,7 @@ pub enum DenoSubcommand {
   Help,
   Info {
     file: Option<String>,
+    json: bool,
   },
   Install {
     module_url: String,
@@ -50,7 +66,18 @@ pub enum DenoSubcommand {
     root: Option<PathBuf>,
     force: bool,
   },
-  Repl,
+  Lint {
+    files: Vec<String>,
+    rules: bool,
+    fix: bool,
+  },
+  Lsp,
+  Repl {
+    history_file: Option<PathBuf>,
+    history_size: Option<usize>,
+    history_ignore_space: bool,
+    check: bool,
+  },
   Run {
     script: String,
   },
@@ -58,20 +85,41 @@ pub enum DenoSubcommand {
     fail_fast: bool,
     quiet: bool,
     allow_none: bool,
+    trace_ops: bool,
     include: Option<Vec<String>>,
     filter: Option<String>,
+    jobs: usize,
+    reporter: String,
+    doc: bool,
+  },
+  Task {
+    task: Option<String>,
+    args: Vec<String>,
+  },
+  Types {
+    libs: Vec<String>,
+  },
+  Uninstall {
+    name: String,
+    root: Option<PathBuf>,
   },
-  Types,
   Upgrade {
     dry_run: bool,
     force: bool,
     version: Option<String>,
+    output: Option<PathBuf>,
+    canary: bool,
   },
 }
 
 impl Default for DenoSubcommand {
   fn default() -> DenoSubcommand {
-    DenoSubcommand::Repl
+    DenoSubcommand::Repl {
+      history_file: None,
+      history_size: None,
+      history_ignore_space: false,
+      check: false,
+    }
   }
 }
 
@@ -83,31 +131,63 @@ pub struct Flags {
   pub subcommand: DenoSubcommand,
 
   pub allow_env: bool,
+  pub allow_ffi: bool,
   pub allow_hrtime: bool,
   pub allow_net: bool,
   pub allow_plugin: bool,
   pub allow_read: bool,
   pub allow_run: bool,
   pub allow_write: bool,
+  pub blocking_threads: Option<usize>,
   pub cache_blacklist: Vec<String>,
   pub ca_file: Option<String>,
   pub cached_only: bool,
+  pub compat: bool,
   pub config_path: Option<String>,
+  /// Directory to write coverage to, set by `deno test --coverage=DIR`.
+  pub coverage_dir: Option<String>,
+  /// File to write a CPU profile to, set by `deno bench --profile=FILE`.
+  pub cpu_profile: Option<String>,
+  /// Constants substituted when compiling, set by `--define NAME=VALUE`.
+  /// Values are JSON literals.
+  pub defines: Vec<(String, String)>,
+  /// Set by `--dump-metrics`; holds the file to write metrics to, if given,
+  /// instead of stderr.
+  pub dump_metrics: Option<Option<String>>,
+  /// Extension, ie. media type, of the program read by `deno run -`.
+  pub ext: Option<String>,
   pub import_map_path: Option<String>,
   pub inspect: Option<SocketAddr>,
   pub inspect_brk: Option<SocketAddr>,
+  /// Set by `--location`; the origin of `localStorage` and `sessionStorage`.
+  pub location: Option<Url>,
   pub lock: Option<String>,
   pub lock_write: bool,
+  pub log_json: bool,
   pub log_level: Option<Level>,
   pub net_whitelist: Vec<String>,
+  pub no_color: bool,
   pub no_prompts: bool,
   pub no_remote: bool,
+  /// Set by `--no-remote-check`: type errors in remote modules are ignored.
+  pub no_remote_check: bool,
   pub read_whitelist: Vec<PathBuf>,
   pub reload: bool,
+  pub runtime_stack_size: Option<usize>,
+  pub runtime_threads: Option<usize>,
   pub seed: Option<u64>,
+  pub shutdown_timeout: Option<u64>,
+  /// Set by `--source-maps`: `external`, `inline` or `none`. Source maps are
+  /// external when it isn't given.
+  pub source_maps: Option<String>,
+  pub startup_timing: bool,
+  /// File to write op traces to, set by `deno run --trace-ops=FILE`.
+  pub trace_ops_file: Option<String>,
+  pub uncaught_exit_code: Option<i32>,
   pub unstable: bool,
   pub v8_flags: Option<Vec<String>>,
   pub version: bool,
+  pub warn_slow_ops: Option<u64>,
   pub write_whitelist: Vec<PathBuf>,
 }
 
@@ -165,6 +245,10 @@ impl Flags {
       args.push("--allow-plugin".to_string());
     }
 
+    if self.allow_ffi {
+      args.push("--allow-ffi".to_string());
+    }
+
     if self.allow_hrtime {
       args.push("--allow-hrtime".to_string());
     }
@@ -174,10 +258,21 @@ impl Flags {
 }
 
 static ENV_VARIABLES_HELP: &str = "ENVIRONMENT VARIABLES:
+    DENO_CERT            Load certificate authority from PEM encoded file,
+                         like --cert
     DENO_DIR             Set deno's base directory (defaults to $HOME/.deno)
-    DENO_INSTALL_ROOT    Set deno install's output directory
+    DENO_INSTALL_ROOT    Set deno install's output directory, like --root
                          (defaults to $HOME/.deno/bin)
-    NO_COLOR             Set to disable color
+    DENO_NO_COLOR        Set to disable color, like --no-color
+    DENO_REPL_HISTORY    Set REPL history file, empty value disables history
+                         (defaults to $DENO_DIR/deno_history.txt)
+    DENO_V8_FLAGS        Set V8 command line options, like --v8-flags;
+                         flags of --v8-flags are passed after these
+    DENO_WEB_WORKER_COUNT
+                         Number of workers running test files in parallel,
+                         like deno test --jobs
+    NO_COLOR             Set to disable color, like --no-color
+    RUST_LOG             Set log levels per module, eg. deno::tsc=debug,warn
     HTTP_PROXY           Proxy address for HTTP requests
                          (module downloads, fetch)
     HTTPS_PROXY          Same but for HTTPS";
@@ -207,37 +302,211 @@ lazy_static! {
   );
 }
 
-/// Main entry point for parsing deno's command line flags.
-/// Exits the process on error.
-pub fn flags_from_vec(args: Vec<String>) -> Flags {
-  match flags_from_vec_safe(args) {
-    Ok(flags) => flags,
-    Err(err) => err.exit(),
+/// Why the command line couldn't be parsed into `Flags`.
+#[derive(Debug, PartialEq)]
+pub enum FlagsError {
+  /// `--help` was passed; holds the help text to print.
+  Help(String),
+  /// `--version` was passed; the version was printed already.
+  Version,
+  /// A flag that isn't known in its context, with the known flag closest
+  /// to it, if one is close enough to be a misspelling.
+  UnknownFlag {
+    flag: String,
+    suggestion: Option<String>,
+    usage: String,
+  },
+  /// Any other error, eg. a missing or invalid value.
+  Invalid(String),
+}
+
+impl FlagsError {
+  fn from_clap(err: clap::Error, args: &[String]) -> Self {
+    use clap::ErrorKind;
+    match err.kind {
+      ErrorKind::HelpDisplayed => FlagsError::Help(err.message),
+      ErrorKind::VersionDisplayed => FlagsError::Version,
+      ErrorKind::UnknownArgument => {
+        let flag = err
+          .info
+          .as_ref()
+          .and_then(|info| info.first())
+          .filter(|arg| arg.starts_with("--"))
+          .map(|arg| arg.split('=').next().unwrap().to_string());
+        match flag {
+          Some(flag) => {
+            let known_flags = known_flags(args, &flag);
+            let suggestion = suggest_flag(&flag, &known_flags);
+            let usage = match err.message.find("USAGE:") {
+              Some(index) => err.message[index..].trim_end().to_string(),
+              None => String::new(),
+            };
+            FlagsError::UnknownFlag {
+              flag,
+              suggestion,
+              usage,
+            }
+          }
+          None => FlagsError::Invalid(err.message),
+        }
+      }
+      _ => FlagsError::Invalid(err.message),
+    }
+  }
+
+  /// The process exits with 0 for `--help` and `--version`, and with 2 for
+  /// a wrong command line.
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      FlagsError::Help(_) | FlagsError::Version => 0,
+      FlagsError::UnknownFlag { .. } | FlagsError::Invalid(_) => 2,
+    }
+  }
+}
+
+impl fmt::Display for FlagsError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      FlagsError::Help(help) => f.write_str(help),
+      FlagsError::Version => Ok(()),
+      FlagsError::UnknownFlag {
+        flag,
+        suggestion,
+        usage,
+      } => {
+        write!(f, "error: Found argument '{}' which wasn't expected", flag)?;
+        if let Some(suggestion) = suggestion {
+          write!(f, "\n\n  Did you mean `{}`?", suggestion)?;
+        }
+        if !usage.is_empty() {
+          write!(f, "\n\n{}", usage)?;
+        }
+        write!(f, "\n\nFor more information try --help")
+      }
+      FlagsError::Invalid(message) => f.write_str(message.trim_end()),
+    }
+  }
+}
+
+impl Error for FlagsError {}
+
+/// Long flags listed in the help of the subcommand that `flag` was passed
+/// to, or of deno itself.
+fn known_flags(args: &[String], flag: &str) -> Vec<String> {
+  let help_of = |subcommand: Option<&str>| {
+    let mut help_args = vec!["deno"];
+    help_args.extend(subcommand);
+    help_args.push("--help");
+    match clap_root().get_matches_from_safe(help_args) {
+      Err(err) if err.kind == clap::ErrorKind::HelpDisplayed => {
+        Some(err.message)
+      }
+      _ => None,
+    }
+  };
+  // The subcommand is the closest argument before `flag` that has a help;
+  // values of flags, like `-L debug`, don't.
+  let flag_index = args
+    .iter()
+    .position(|arg| arg.split('=').next() == Some(flag))
+    .unwrap_or_else(|| args.len());
+  let help = args[..flag_index]
+    .iter()
+    .skip(1)
+    .rev()
+    .filter(|arg| !arg.starts_with('-'))
+    .find_map(|arg| help_of(Some(arg.as_str())))
+    .or_else(|| help_of(None))
+    .unwrap_or_default();
+  let mut flags: Vec<String> = help
+    .split(|c: char| c.is_whitespace() || c == ',')
+    .filter(|word| word.starts_with("--") && word.len() > 2)
+    .map(|word| {
+      let end = word
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or_else(|| word.len());
+      word[..end].to_string()
+    })
+    .collect();
+  flags.sort();
+  flags.dedup();
+  flags
+}
+
+/// The known flag with the smallest edit distance to `flag`, if it's small
+/// enough for `flag` to be a misspelling of it.
+fn suggest_flag(flag: &str, known_flags: &[String]) -> Option<String> {
+  let max_distance = std::cmp::max(2, flag.len() / 4);
+  known_flags
+    .iter()
+    .map(|known| (edit_distance(flag, known), known))
+    .filter(|(distance, _)| *distance <= max_distance)
+    .min_by_key(|(distance, _)| *distance)
+    .map(|(_, known)| known.clone())
+}
+
+/// Levenshtein distance of `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut previous: Vec<usize> = (0..=b.len()).collect();
+  for (i, a_char) in a.chars().enumerate() {
+    let mut current = vec![i + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+      let deletion = previous[j + 1] + 1;
+      let insertion = current[j] + 1;
+      current.push(substitution.min(deletion).min(insertion));
+    }
+    previous = current;
   }
+  previous[b.len()]
+}
+
+/// Main entry point for parsing deno's command line flags, layered over the
+/// environment variables that stand in for them.
+pub fn flags_from_vec_safe(args: Vec<String>) -> Result<Flags, FlagsError> {
+  flags_from_vec_and_env(args, &|name| env::var(name).ok())
 }
 
-/// Same as flags_from_vec but does not exit on error.
-pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
+fn flags_from_vec_and_env(
+  args: Vec<String>,
+  env_var: &dyn Fn(&str) -> Option<String>,
+) -> Result<Flags, FlagsError> {
   let app = clap_root();
-  let matches = app.get_matches_from_safe(args)?;
+  let matches = app
+    .get_matches_from_safe(&args)
+    .map_err(|err| FlagsError::from_clap(err, &args))?;
 
   let mut flags = Flags::default();
 
   if matches.is_present("log-level") {
     flags.log_level = match matches.value_of("log-level").unwrap() {
-      "debug" => Some(Level::Debug),
+      "error" => Some(Level::Error),
+      "warn" => Some(Level::Warn),
       "info" => Some(Level::Info),
+      "debug" => Some(Level::Debug),
+      "trace" => Some(Level::Trace),
       _ => unreachable!(),
     };
   }
+  if matches.is_present("log-json") {
+    flags.log_json = true;
+  }
+  if matches.is_present("no-color") {
+    flags.no_color = true;
+  }
   if matches.is_present("quiet") {
     flags.log_level = Some(Level::Error);
   }
 
   if let Some(m) = matches.subcommand_matches("run") {
     run_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("bench") {
+    bench_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("fmt") {
     fmt_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("task") {
+    task_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("types") {
     types_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("cache") {
@@ -250,8 +519,16 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     repl_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("bundle") {
     bundle_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("compile") {
+    compile_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("install") {
     install_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("lint") {
+    lint_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("lsp") {
+    lsp_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("uninstall") {
+    uninstall_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("completions") {
     completions_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("test") {
@@ -264,9 +541,66 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     repl_parse(&mut flags, &matches);
   }
 
+  env_flags_parse(&mut flags, &matches, env_var)?;
+
   Ok(flags)
 }
 
+/// Environment variables stand in for flags that weren't passed on the
+/// command line, which take precedence. `DENO_V8_FLAGS` are passed to V8
+/// before those of `--v8-flags`, so the latter win. Empty variables are
+/// ignored.
+fn env_flags_parse(
+  flags: &mut Flags,
+  matches: &clap::ArgMatches,
+  env_var: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), FlagsError> {
+  let env_var = |name: &str| env_var(name).filter(|value| !value.is_empty());
+
+  if env_var("DENO_NO_COLOR").is_some() {
+    flags.no_color = true;
+  }
+  if flags.ca_file.is_none() {
+    flags.ca_file = env_var("DENO_CERT");
+  }
+  if let Some(env_v8_flags) = env_var("DENO_V8_FLAGS") {
+    let mut v8_flags: Vec<String> =
+      env_v8_flags.split(',').map(String::from).collect();
+    v8_flags.extend(flags.v8_flags.take().unwrap_or_default());
+    flags.v8_flags = Some(v8_flags);
+  }
+
+  let jobs_passed = matches
+    .subcommand_matches("test")
+    .map_or(false, |m| m.is_present("jobs"));
+  match &mut flags.subcommand {
+    DenoSubcommand::Install { root, .. }
+    | DenoSubcommand::Uninstall { root, .. }
+      if root.is_none() =>
+    {
+      *root = env_var("DENO_INSTALL_ROOT").map(PathBuf::from);
+    }
+    // Test files are run by workers of their own.
+    DenoSubcommand::Test { jobs, .. } if !jobs_passed => {
+      if let Some(count) = env_var("DENO_WEB_WORKER_COUNT") {
+        *jobs = match count.parse::<usize>() {
+          Ok(count) if count > 0 => count,
+          _ => {
+            let message = format!(
+              "error: DENO_WEB_WORKER_COUNT must be a positive number, not '{}'",
+              count
+            );
+            return Err(FlagsError::Invalid(message));
+          }
+        };
+      }
+    }
+    _ => {}
+  }
+
+  Ok(())
+}
+
 fn clap_root<'a, 'b>() -> App<'a, 'b> {
   clap::App::new("deno")
     .bin_name("deno")
@@ -286,7 +620,29 @@ fn clap_root<'a, 'b>() -> App<'a, 'b> {
         .long("log-level")
         .help("Set log level")
         .takes_value(true)
-        .possible_values(&["debug", "info"])
+        .possible_values(&["error", "warn", "info", "debug", "trace"])
+        .long_help(
+          "Set log level
+Log levels of specific modules can be set with the RUST_LOG environment
+variable, eg. RUST_LOG=deno::tsc=debug",
+        )
+        .global(true),
+    )
+    .arg(
+      Arg::with_name("no-color")
+        .long("no-color")
+        .help("Disable colored output")
+        .long_help(
+          "Disable colored output, including syntax highlighting in the REPL
+Same as setting the NO_COLOR environment variable. Deno's own output is also
+not colored if stdout or stderr is not a terminal.",
+        )
+        .global(true),
+    )
+    .arg(
+      Arg::with_name("log-json")
+        .long("log-json")
+        .help("Print log messages as JSON, one per line")
         .global(true),
     )
     .arg(
@@ -301,26 +657,70 @@ If the flag is set, restrict these messages to errors.",
         )
         .global(true),
     )
+    .subcommand(bench_subcommand())
     .subcommand(bundle_subcommand())
+    .subcommand(compile_subcommand())
     .subcommand(completions_subcommand())
     .subcommand(eval_subcommand())
     .subcommand(cache_subcommand())
     .subcommand(fmt_subcommand())
     .subcommand(info_subcommand())
     .subcommand(install_subcommand())
+    .subcommand(lint_subcommand())
+    .subcommand(lsp_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
     .subcommand(test_subcommand())
+    .subcommand(task_subcommand())
     .subcommand(types_subcommand())
+    .subcommand(uninstall_subcommand())
     .subcommand(upgrade_subcommand())
     .subcommand(doc_subcommand())
     .long_about(DENO_HELP)
     .after_help(ENV_VARIABLES_HELP)
 }
 
+fn task_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  flags.config_path = matches.value_of("config").map(ToOwned::to_owned);
+  // The first value is the task name, the rest are its arguments.
+  let mut values: Vec<String> = match matches.values_of("task") {
+    Some(values) => values.map(String::from).collect(),
+    None => vec![],
+  };
+  let task = if values.is_empty() {
+    None
+  } else {
+    Some(values.remove(0))
+  };
+  flags.subcommand = DenoSubcommand::Task { task, args: values };
+}
+
 fn types_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   unstable_arg_parse(flags, matches);
-  flags.subcommand = DenoSubcommand::Types;
+  let libs = match matches.values_of("lib") {
+    Some(libs) => libs.map(String::from).collect(),
+    None => vec![],
+  };
+  flags.subcommand = DenoSubcommand::Types { libs };
+}
+
+fn lint_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  flags.config_path = matches.value_of("config").map(ToOwned::to_owned);
+  let files = match matches.values_of("files") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  flags.subcommand = DenoSubcommand::Lint {
+    files,
+    rules: matches.is_present("rules"),
+    fix: matches.is_present("fix"),
+  }
+}
+
+fn lsp_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  unstable_arg_parse(flags, matches);
+  config_arg_parse(flags, matches);
+  flags.subcommand = DenoSubcommand::Lsp;
 }
 
 fn fmt_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -366,9 +766,42 @@ fn install_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   };
 }
 
+fn uninstall_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let root = matches.value_of("root").map(PathBuf::from);
+  let name = matches.value_of("name").unwrap().to_string();
+  flags.subcommand = DenoSubcommand::Uninstall { name, root };
+}
+
+fn bench_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  flags.allow_read = true;
+  // Benchmarks are timed with full precision.
+  flags.allow_hrtime = true;
+
+  run_test_args_parse(flags, matches);
+  flags.cpu_profile = matches.value_of("profile").map(String::from);
+
+  let filter = matches.value_of("filter").map(String::from);
+  let include = if matches.is_present("files") {
+    let files: Vec<String> = matches
+      .values_of("files")
+      .unwrap()
+      .map(String::from)
+      .collect();
+    Some(files)
+  } else {
+    None
+  };
+
+  script_args_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Bench { include, filter };
+}
+
 fn bundle_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   ca_file_arg_parse(flags, matches);
   config_arg_parse(flags, matches);
+  no_remote_check_arg_parse(flags, matches);
+  define_arg_parse(flags, matches);
   importmap_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
 
@@ -381,9 +814,29 @@ fn bundle_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None
   };
 
+  let minify = matches.is_present("minify");
+
   flags.subcommand = DenoSubcommand::Bundle {
     source_file,
     out_file,
+    minify,
+  };
+}
+
+fn compile_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  permission_args_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+  config_arg_parse(flags, matches);
+  no_remote_check_arg_parse(flags, matches);
+  importmap_arg_parse(flags, matches);
+  unstable_arg_parse(flags, matches);
+
+  let source_file = matches.value_of("source_file").unwrap().to_string();
+  let output = matches.value_of("output").map(PathBuf::from);
+
+  flags.subcommand = DenoSubcommand::Compile {
+    source_file,
+    output,
   };
 }
 
@@ -407,13 +860,21 @@ fn repl_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   ca_file_arg_parse(flags, matches);
   inspect_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
-  flags.subcommand = DenoSubcommand::Repl;
+  flags.subcommand = DenoSubcommand::Repl {
+    history_file: matches.value_of("history-file").map(PathBuf::from),
+    history_size: matches
+      .value_of("history-size")
+      .map(|val| val.parse::<usize>().unwrap()),
+    history_ignore_space: matches.is_present("history-ignore-space"),
+    check: matches.is_present("check"),
+  };
   flags.allow_net = true;
   flags.allow_env = true;
   flags.allow_run = true;
   flags.allow_read = true;
   flags.allow_write = true;
   flags.allow_plugin = true;
+  flags.allow_ffi = true;
   flags.allow_hrtime = true;
 }
 
@@ -428,12 +889,16 @@ fn eval_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   flags.allow_read = true;
   flags.allow_write = true;
   flags.allow_plugin = true;
+  flags.allow_ffi = true;
   flags.allow_hrtime = true;
-  let code = matches.value_of("code").unwrap().to_string();
+  let code = matches.value_of("code").unwrap_or("-").to_string();
   let as_typescript = matches.is_present("ts");
+  let print = matches.is_present("print");
+  script_args_parse(flags, matches);
   flags.subcommand = DenoSubcommand::Eval {
     code,
     as_typescript,
+    print,
   }
 }
 
@@ -443,6 +908,8 @@ fn info_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
   flags.subcommand = DenoSubcommand::Info {
     file: matches.value_of("file").map(|f| f.to_string()),
+    json: false,
+    json: matches.is_present("json"),
   };
 }
 
@@ -452,14 +919,19 @@ fn cache_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   importmap_arg_parse(flags, matches);
   config_arg_parse(flags, matches);
   no_remote_arg_parse(flags, matches);
+  no_remote_check_arg_parse(flags, matches);
+  source_maps_arg_parse(flags, matches);
+  define_arg_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
-  let files = matches
-    .values_of("file")
-    .unwrap()
-    .map(String::from)
-    .collect();
-  flags.subcommand = DenoSubcommand::Cache { files };
+  let files = match matches.values_of("file") {
+    Some(f) => f.map(String::from).collect(),
+    None => vec![],
+  };
+  let prune = matches
+    .value_of("prune")
+    .map(|val| val.parse::<u64>().unwrap());
+  flags.subcommand = DenoSubcommand::Cache { files, prune };
 }
 
 fn lock_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -487,10 +959,15 @@ fn run_test_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   config_arg_parse(flags, matches);
   v8_flags_arg_parse(flags, matches);
   no_remote_arg_parse(flags, matches);
+  no_remote_check_arg_parse(flags, matches);
+  source_maps_arg_parse(flags, matches);
+  define_arg_parse(flags, matches);
+  location_arg_parse(flags, matches);
   permission_args_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   inspect_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
+  runtime_args_parse(flags, matches);
 
   if matches.is_present("cached-only") {
     flags.cached_only = true;
@@ -517,6 +994,13 @@ fn run_test_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 fn run_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   run_test_args_parse(flags, matches);
 
+  if matches.is_present("compat") {
+    flags.compat = true;
+  }
+  flags.ext = matches.value_of("ext").map(String::from);
+  flags.startup_timing = matches.is_present("startup-timing");
+  flags.trace_ops_file = matches.value_of("trace-ops").map(String::from);
+
   let mut script: Vec<String> = matches
     .values_of("script_arg")
     .unwrap()
@@ -540,6 +1024,14 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   let failfast = matches.is_present("failfast");
   let allow_none = matches.is_present("allow_none");
   let quiet = matches.is_present("quiet");
+  let trace_ops = matches.is_present("trace-ops");
+  let doc = matches.is_present("doc");
+  flags.coverage_dir = matches.value_of("coverage").map(String::from);
+  // Snapshot assertions in std/testing/snapshot.ts look for it in `Deno.args`.
+  if matches.is_present("update") {
+    flags.argv.push("--update".to_string());
+  }
+  script_args_parse(flags, matches);
   let filter = matches.value_of("filter").map(String::from);
   let include = if matches.is_present("files") {
     let files: Vec<String> = matches
@@ -552,12 +1044,21 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None
   };
 
+  let jobs = matches
+    .value_of("jobs")
+    .map_or(1, |val| val.parse::<usize>().unwrap());
+  let reporter = matches.value_of("reporter").unwrap().to_string();
+
   flags.subcommand = DenoSubcommand::Test {
     fail_fast: failfast,
     quiet,
     include,
     filter,
     allow_none,
+    trace_ops,
+    jobs,
+    reporter,
+    doc,
   };
 }
 
@@ -565,10 +1066,14 @@ fn upgrade_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   let dry_run = matches.is_present("dry-run");
   let force = matches.is_present("force");
   let version = matches.value_of("version").map(|s| s.to_string());
+  let output = matches.value_of("output").map(PathBuf::from);
+  let canary = matches.is_present("canary");
   flags.subcommand = DenoSubcommand::Upgrade {
     dry_run,
     force,
     version,
+    output,
+    canary,
   };
 }
 
@@ -586,15 +1091,135 @@ fn doc_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   };
 }
 
+fn task_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("task")
+    .setting(AppSettings::TrailingVarArg)
+    .arg(
+      Arg::with_name("config")
+        .short("c")
+        .long("config")
+        .value_name("FILE")
+        .help("Load deno.json configuration file")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("task")
+        .multiple(true)
+        .allow_hyphen_values(true)
+        .value_name("TASK")
+        .help("Task to run and its arguments"),
+    )
+    .about("Run a task defined in the configuration file")
+    .long_about(
+      "Run a task defined in the \"tasks\" map of deno.json.
+  deno task build
+
+The configuration file is looked up in the current directory and its parents,
+unless it's given with --config. Tasks run in the directory of the
+configuration file.
+
+Without a task name, the available tasks are listed:
+  deno task
+
+Command lines support sequencing with &&, || and ;, quotes, environment
+variable expansion ($NAME) and assignments (NAME=value cmd), and behave the
+same on every platform.",
+    )
+}
+
 fn types_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("types")
     .arg(unstable_arg())
+    .arg(
+      Arg::with_name("lib")
+        .long("lib")
+        .takes_value(true)
+        .use_delimiter(true)
+        .value_name("LIBS")
+        .help("Print the declarations of the given libs, eg. dom,deno.ns"),
+    )
     .about("Print runtime TypeScript declarations")
     .long_about(
       "Print runtime TypeScript declarations.
   deno types > lib.deno.d.ts
 
-The declaration file could be saved and used for typing information.",
+The declaration file could be saved and used for typing information.
+
+Use --lib to print a self-contained declaration file for the given libs,
+including the libs they reference:
+  deno types --lib dom,deno.ns > lib.d.ts
+
+Libs are named like in the \"lib\" compiler option (eg. dom, esnext), plus
+deno.ns, deno.shared_globals, deno.window, deno.worker and deno.unstable.",
+    )
+}
+
+fn lint_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("lint")
+    .about("Lint source files")
+    .long_about(
+      "Lint JavaScript/TypeScript source code.
+  deno lint
+  deno lint myfile1.ts myfile2.ts
+
+List the available rules, and which of them are enabled:
+  deno lint --rules
+
+Fix the problems of rules with mechanical fixes, eg. eqeqeq and prefer-const:
+  deno lint --fix
+
+Files and rules are selected in the \"lint\" section of deno.json, which is
+looked up in the current directory and its parents unless it's given with
+--config. By default, the rules tagged \"recommended\" are enabled.
+
+Ignore diagnostics on the next line by preceding it with an ignore comment,
+optionally naming the rules to ignore:
+  // deno-lint-ignore no-explicit-any eqeqeq
+
+Ignore a whole file by adding an ignore comment at the top of the file:
+  // deno-lint-ignore-file",
+    )
+    .arg(
+      Arg::with_name("config")
+        .short("c")
+        .long("config")
+        .value_name("FILE")
+        .help("Load deno.json configuration file")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("rules")
+        .long("rules")
+        .help("List available rules")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("fix")
+        .long("fix")
+        .help("Fix the problems that can be fixed automatically")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("files")
+        .takes_value(true)
+        .multiple(true)
+        .required(false),
+    )
+}
+
+fn lsp_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("lsp")
+    .arg(unstable_arg())
+    .arg(config_arg())
+    .about("Start the language server")
+    .long_about(
+      "Start the language server, speaking the Language Server Protocol over
+stdin and stdout. It is meant to be started by an editor rather than run
+directly.
+
+The server provides diagnostics, hover information, go to definition
+(including into remote modules cached in DENO_DIR), formatting, and a code
+action adding @deno-types to imports of JavaScript modules without types.",
     )
 }
 
@@ -602,7 +1227,8 @@ fn fmt_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("fmt")
     .about("Format source files")
     .long_about(
-      "Auto-format JavaScript/TypeScript source code.
+      "Auto-format JavaScript/TypeScript source code, the code blocks of
+Markdown files and JSON files.
   deno fmt
   deno fmt myfile1.ts myfile2.ts
   deno fmt --check
@@ -636,6 +1262,31 @@ fn repl_subcommand<'a, 'b>() -> App<'a, 'b> {
     .arg(v8_flags_arg())
     .arg(ca_file_arg())
     .arg(unstable_arg())
+    .arg(
+      Arg::with_name("history-file")
+        .long("history-file")
+        .value_name("FILE")
+        .help("Path of the history file, overrides DENO_REPL_HISTORY")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("history-size")
+        .long("history-size")
+        .value_name("NUMBER")
+        .help("Maximum number of history entries kept (defaults to 1000)")
+        .takes_value(true)
+        .validator(positive_number_validate),
+    )
+    .arg(
+      Arg::with_name("history-ignore-space")
+        .long("history-ignore-space")
+        .help("Don't save lines starting with a space to history"),
+    )
+    .arg(
+      Arg::with_name("check")
+        .long("check")
+        .help("Type check input as TypeScript before evaluating it"),
+    )
 }
 
 fn install_subcommand<'a, 'b>() -> App<'a, 'b> {
@@ -694,6 +1345,79 @@ The installation root is determined, in order of precedence:
 These must be added to the path manually if required.")
 }
 
+fn uninstall_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("uninstall")
+    .arg(Arg::with_name("name").required(true))
+    .arg(
+      Arg::with_name("root")
+        .long("root")
+        .help("Installation root")
+        .takes_value(true)
+        .multiple(false),
+    )
+    .about("Uninstall a script previously installed with deno install")
+    .long_about(
+      "Uninstalls an executable script in the installation root's bin directory.
+  deno uninstall file_server
+
+To change the installation root, use --root:
+  deno uninstall --root /usr/local file_server
+
+The installation root is determined, in order of precedence:
+  - --root option
+  - DENO_INSTALL_ROOT environment variable
+  - $HOME/.deno
+
+Only executables generated by deno install are removed.",
+    )
+}
+
+fn bench_subcommand<'a, 'b>() -> App<'a, 'b> {
+  run_test_args(SubCommand::with_name("bench"))
+    .arg(
+      Arg::with_name("filter")
+        .long("filter")
+        .takes_value(true)
+        .help("A pattern to filter the benchmarks to run by"),
+    )
+    .arg(
+      Arg::with_name("profile")
+        .long("profile")
+        .value_name("FILE")
+        .help("Write a CPU profile of the benchmarks to given file, for Chrome DevTools")
+        .require_equals(true)
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("files")
+        .help("List of file names to run")
+        .takes_value(true)
+        .multiple(true),
+    )
+    .arg(script_args_arg())
+    .about("Run benchmarks")
+    .long_about(
+      "Run benchmarks using Deno's built-in benchmark runner.
+
+Evaluate the given modules, run all benchmarks declared with 'Deno.bench()'
+and report their timings to standard output:
+  deno bench --unstable src/parse_bench.ts
+
+Each benchmark is warmed up first and then run repeatedly for a target time;
+mean, median, 75th and 99th percentile of the time per iteration are reported.
+
+To see where the time goes, write a CPU profile of the benchmarks and open it
+in the Performance panel of Chrome DevTools:
+  deno bench --unstable --profile=bench.cpuprofile src/
+
+Directory arguments are expanded to all contained files matching the glob
+{*_,}bench.{js,ts,jsx,tsx}:
+  deno bench --unstable src/
+
+This command has implicit access to high resolution time (--allow-hrtime).",
+    )
+}
+
 fn bundle_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("bundle")
     .arg(
@@ -702,17 +1426,64 @@ fn bundle_subcommand<'a, 'b>() -> App<'a, 'b> {
         .required(true),
     )
     .arg(Arg::with_name("out_file").takes_value(true).required(false))
+    .arg(
+      Arg::with_name("minify")
+        .long("minify")
+        .help("Shorten local names and strip comments, with a source map"),
+    )
     .arg(ca_file_arg())
     .arg(importmap_arg())
     .arg(unstable_arg())
     .arg(config_arg())
+    .arg(no_remote_check_arg())
+    .arg(define_arg())
     .about("Bundle module and dependencies into single file")
     .long_about(
       "Output a single JavaScript file with all dependencies.
   deno bundle https://deno.land/std/examples/colors.ts colors.bundle.js
 
 If no output file is given, the output is written to standard output:
-  deno bundle https://deno.land/std/examples/colors.ts",
+  deno bundle https://deno.land/std/examples/colors.ts
+
+Exports of the bundled modules that no module uses are left out.
+
+With --minify, local names are shortened and comments are stripped. When
+writing to a file, a source map is written next to it:
+  deno bundle --minify https://deno.land/std/examples/colors.ts colors.bundle.js",
+    )
+}
+
+fn compile_subcommand<'a, 'b>() -> App<'a, 'b> {
+  permission_args(SubCommand::with_name("compile"))
+    .arg(
+      Arg::with_name("source_file")
+        .takes_value(true)
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("output")
+        .long("output")
+        .short("o")
+        .help("Output file (defaults to $PWD/<inferred-name>)")
+        .takes_value(true),
+    )
+    .arg(ca_file_arg())
+    .arg(importmap_arg())
+    .arg(unstable_arg())
+    .arg(config_arg())
+    .arg(no_remote_check_arg())
+    .about("Compile the script into a self contained executable")
+    .long_about(
+      "Compiles the given script into a self contained executable.
+  deno compile --allow-net --allow-read https://deno.land/std/http/file_server.ts
+  deno compile --output /usr/local/bin/colors https://deno.land/std/examples/colors.ts
+
+The script is bundled with all of its dependencies and appended to a copy of
+the deno executable. Permission flags and --unstable given to compile are
+applied whenever the executable runs; all other arguments are passed to the
+script.
+
+The executable name is inferred from the URL the same way as for deno install.",
     )
 }
 
@@ -744,6 +1515,12 @@ fn eval_subcommand<'a, 'b>() -> App<'a, 'b> {
 To evaluate as TypeScript:
   deno eval -T \"const v: string = 'hello'; console.log(v)\"
 
+To print the result of an expression:
+  deno eval -p \"Deno.pid\"
+
+To read the code from stdin, pass '-' or omit the code:
+  echo \"console.log(Deno.pid)\" | deno eval -
+
 This command has implicit access to all permissions (--allow-all).",
     )
     .arg(
@@ -754,7 +1531,20 @@ This command has implicit access to all permissions (--allow-all).",
         .takes_value(false)
         .multiple(false),
     )
-    .arg(Arg::with_name("code").takes_value(true).required(true))
+    .arg(
+      Arg::with_name("print")
+        .long("print")
+        .short("p")
+        .help("Print result of the evaluated expression to stdout")
+        .takes_value(false)
+        .multiple(false),
+    )
+    .arg(
+      Arg::with_name("code")
+        .takes_value(true)
+        .help("Code to evaluate, '-' (the default) reads it from stdin"),
+    )
+    .arg(script_args_arg())
     .arg(v8_flags_arg())
 }
 
@@ -778,10 +1568,23 @@ deps: Dependency tree of the source file.
 Without any additional arguments, 'deno info' shows:
 
 DENO_DIR: Directory containing Deno-managed files.
-Remote modules cache: Subdirectory containing downloaded remote modules.
-TypeScript compiler cache: Subdirectory containing TS compiler output.",
+Remote modules cache: Subdirectory containing downloaded remote modules, with
+  its size and the number of cached origins.
+TypeScript compiler cache: Subdirectory containing TS compiler output, with its
+  size.
+REPL history: File containing the REPL history, with its size.
+
+Print the cache information as JSON:
+  deno info --json",
     )
     .arg(Arg::with_name("file").takes_value(true).required(false))
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .help("Output cache information in JSON format")
+        .conflicts_with("file")
+        .takes_value(false),
+    )
     .arg(ca_file_arg())
     .arg(unstable_arg())
 }
@@ -795,10 +1598,24 @@ fn cache_subcommand<'a, 'b>() -> App<'a, 'b> {
     .arg(unstable_arg())
     .arg(config_arg())
     .arg(no_remote_arg())
+    .arg(no_remote_check_arg())
+    .arg(source_maps_arg())
+    .arg(define_arg())
+    .arg(
+      Arg::with_name("prune")
+        .long("prune")
+        .value_name("MB")
+        .help("Remove the oldest compiled modules until the cache fits in MB megabytes")
+        .takes_value(true)
+        .validator(|val: String| match val.parse::<u64>() {
+          Ok(_) => Ok(()),
+          Err(_) => Err("Cache size should be a number of megabytes".to_string()),
+        }),
+    )
     .arg(
       Arg::with_name("file")
         .takes_value(true)
-        .required(true)
+        .required_unless("prune")
         .min_values(1),
     )
     .arg(ca_file_arg())
@@ -811,7 +1628,19 @@ in the local cache, without running any code:
   deno cache https://deno.land/std/http/file_server.ts
 
 Future runs of this module will trigger no downloads or compilation unless
---reload is specified.",
+--reload is specified.
+
+Downloaded dependencies can be checked against a lock file:
+  deno cache --lock=lock.json https://deno.land/std/http/file_server.ts
+
+To refresh the cache and write a new lock file, combine --reload with
+--lock-write:
+  deno cache --reload --lock=lock.json --lock-write https://deno.land/std/http/file_server.ts
+
+Compiled modules are kept in DENO_DIR/gen and reused as long as the source,
+the compiler options and the deno version are unchanged. To keep the compiled
+cache under a size limit, removing the oldest modules first:
+  deno cache --prune=500",
     )
 }
 
@@ -824,7 +1653,15 @@ Defaults to latest.
 
 The version is downloaded from
 https://github.com/denoland/deno/releases
-and is used to replace the current executable.",
+and is used to replace the current executable.
+
+If you want to not replace the current Deno executable but instead download an
+update to a different location, use the --output flag
+  deno upgrade --output $HOME/my_deno
+
+Canary builds, identified by the commit hash, are installed with --canary:
+  deno upgrade --canary
+  deno upgrade --canary --version 1a2b3c4d",
     )
     .arg(
       Arg::with_name("version")
@@ -832,6 +1669,17 @@ and is used to replace the current executable.",
         .help("The version to upgrade to")
         .takes_value(true),
     )
+    .arg(
+      Arg::with_name("output")
+        .long("output")
+        .help("The path to output the updated version to")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("canary")
+        .long("canary")
+        .help("Upgrade to canary builds"),
+    )
     .arg(
       Arg::with_name("dry-run")
         .long("dry-run")
@@ -932,6 +1780,11 @@ fn permission_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         .long("allow-plugin")
         .help("Allow loading plugins"),
     )
+    .arg(
+      Arg::with_name("allow-ffi")
+        .long("allow-ffi")
+        .help("Allow loading dynamic libraries and calling into them"),
+    )
     .arg(
       Arg::with_name("allow-hrtime")
         .long("allow-hrtime")
@@ -946,7 +1799,7 @@ fn permission_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
 }
 
 fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-  permission_args(inspect_args(app))
+  permission_args(inspect_args(runtime_args(app)))
     .arg(importmap_arg())
     .arg(unstable_arg())
     .arg(reload_arg())
@@ -954,6 +1807,10 @@ fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
     .arg(lock_arg())
     .arg(lock_write_arg())
     .arg(no_remote_arg())
+    .arg(no_remote_check_arg())
+    .arg(source_maps_arg())
+    .arg(define_arg())
+    .arg(location_arg())
     .arg(v8_flags_arg())
     .arg(ca_file_arg())
     .arg(
@@ -977,6 +1834,34 @@ fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
 fn run_subcommand<'a, 'b>() -> App<'a, 'b> {
   run_test_args(SubCommand::with_name("run"))
     .setting(AppSettings::TrailingVarArg)
+    .arg(
+      Arg::with_name("compat")
+        .long("compat")
+        .requires("unstable")
+        .help("Provide Node globals and require() for CommonJS modules"),
+    )
+    .arg(
+      Arg::with_name("ext")
+        .long("ext")
+        .value_name("EXT")
+        .help("Set the media type of the program read from stdin with 'deno run -'")
+        .possible_values(&["js", "jsx", "ts", "tsx"])
+        .require_equals(true)
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("startup-timing")
+        .long("startup-timing")
+        .help("Print time spent in each phase of startup, up to evaluation of the main module"),
+    )
+    .arg(
+      Arg::with_name("trace-ops")
+        .long("trace-ops")
+        .value_name("FILE")
+        .help("Write ops, event loop ticks and module loading to given file in Trace Event Format, for chrome://tracing or Perfetto")
+        .require_equals(true)
+        .takes_value(true),
+    )
     .arg(script_arg())
     .about("Run a program given a filename or url to the module")
     .long_about(
@@ -993,7 +1878,15 @@ Grant permission to read from disk and listen to network:
   deno run --allow-read --allow-net https://deno.land/std/http/file_server.ts
 
 Grant permission to read whitelisted files from disk:
-  deno run --allow-read=/etc https://deno.land/std/http/file_server.ts",
+  deno run --allow-read=/etc https://deno.land/std/http/file_server.ts
+
+Read the program from stdin, as TypeScript; its relative imports are resolved
+against the current directory:
+  cat main.ts | deno run --ext=ts -
+
+Flags of deno end at the script, or at '--'. Arguments after the script are
+passed to it in Deno.args:
+  deno run --allow-read -- cat.ts --number file.txt",
     )
 }
 
@@ -1011,18 +1904,62 @@ fn test_subcommand<'a, 'b>() -> App<'a, 'b> {
         .help("Don't return error code if no test files are found")
         .takes_value(false),
     )
+    .arg(
+      Arg::with_name("trace-ops")
+        .long("trace-ops")
+        .help("Show where leaking async ops were started in failing tests")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("coverage")
+        .long("coverage")
+        .value_name("DIR")
+        .help("Write V8 coverage of the tests to given directory")
+        .require_equals(true)
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("update")
+        .long("update")
+        .help("Update snapshots instead of comparing against them")
+        .takes_value(false),
+    )
     .arg(
       Arg::with_name("filter")
         .long("filter")
         .takes_value(true)
         .help("A pattern to filter the tests to run by"),
     )
+    .arg(
+      Arg::with_name("jobs")
+        .long("jobs")
+        .short("j")
+        .value_name("NUMBER")
+        .takes_value(true)
+        .validator(positive_number_validate)
+        .help("Number of test files run in parallel (defaults to 1)"),
+    )
+    .arg(
+      Arg::with_name("reporter")
+        .long("reporter")
+        .takes_value(true)
+        .possible_values(&["pretty", "json", "junit"])
+        .default_value("pretty")
+        .help("Format of the test report"),
+    )
+    .arg(
+      Arg::with_name("doc")
+        .long("doc")
+        .help("Also run the code blocks of JSDoc comments and Markdown files")
+        .takes_value(false),
+    )
     .arg(
       Arg::with_name("files")
         .help("List of file names to run")
         .takes_value(true)
         .multiple(true),
     )
+    .arg(script_args_arg())
     .about("Run tests")
     .long_about(
       "Run tests using Deno's built-in test runner.
@@ -1033,10 +1970,48 @@ report results to standard output:
 
 Directory arguments are expanded to all contained files matching the glob
 {*_,}test.{js,ts,jsx,tsx}:
-  deno test src/",
+  deno test src/
+
+Test files can be run in parallel, each worker running a share of them in
+a separate isolate:
+  deno test --jobs=4 src/
+
+Results can be reported as JSON lines or JUnit XML instead:
+  deno test --reporter=junit src/ > report.xml
+
+Tests fail if they leave async ops pending or resources open. To see where
+the leaking ops were started:
+  deno test --trace-ops src/
+
+Coverage collected by V8 while the tests run is written as JSON, one file per
+test worker, to a directory:
+  deno test --coverage=cov/ src/
+
+Snapshots asserted with std/testing/snapshot.ts can be rewritten with:
+  deno test --update --allow-write src/
+
+The JS/TS code blocks of JSDoc comments and Markdown files are type checked
+and run as tests too with --doc, so examples can't go stale:
+  deno test --doc src/ README.md",
     )
 }
 
+/// Values after `--`, which ends the flags of deno, are passed to the program
+/// in `Deno.args`.
+fn script_args_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("script_args")
+    .multiple(true)
+    .last(true)
+    .value_name("ARGS")
+    .help("Arguments passed to the program in Deno.args, after --")
+}
+
+fn script_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  if let Some(args) = matches.values_of("script_args") {
+    flags.argv.extend(args.map(String::from));
+  }
+}
+
 fn script_arg<'a, 'b>() -> Arg<'a, 'b> {
   Arg::with_name("script_arg")
     .multiple(true)
@@ -1056,6 +2031,7 @@ fn lock_arg<'a, 'b>() -> Arg<'a, 'b> {
 fn lock_write_arg<'a, 'b>() -> Arg<'a, 'b> {
   Arg::with_name("lock-write")
     .long("lock-write")
+    .requires("lock")
     .help("Write lock file. Use with --lock.")
 }
 
@@ -1096,6 +2072,105 @@ fn unstable_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   }
 }
 
+fn positive_number_validate(val: String) -> Result<(), String> {
+  match val.parse::<usize>() {
+    Ok(n) if n > 0 => Ok(()),
+    _ => Err("Value should be a positive number".to_string()),
+  }
+}
+
+fn runtime_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+  app
+    .arg(
+      Arg::with_name("runtime-threads")
+        .long("runtime-threads")
+        .value_name("NUMBER")
+        .help("Number of threads driving async I/O (1 runs it on the main thread)")
+        .takes_value(true)
+        .validator(positive_number_validate),
+    )
+    .arg(
+      Arg::with_name("runtime-stack-size")
+        .long("runtime-stack-size")
+        .value_name("BYTES")
+        .help("Stack size of threads spawned by the async runtime")
+        .takes_value(true)
+        .validator(positive_number_validate),
+    )
+    .arg(
+      Arg::with_name("blocking-threads")
+        .long("blocking-threads")
+        .value_name("NUMBER")
+        .help("Number of threads used for blocking operations like file system access [default: 64]")
+        .takes_value(true)
+        .validator(positive_number_validate),
+    )
+    .arg(
+      Arg::with_name("shutdown-timeout")
+        .long("shutdown-timeout")
+        .value_name("MS")
+        .help("Time pending operations may take to settle after Deno.exit()")
+        .takes_value(true)
+        .validator(|val: String| match val.parse::<u64>() {
+          Ok(_) => Ok(()),
+          Err(_) => Err("Shutdown timeout should be a number".to_string()),
+        }),
+    )
+    .arg(
+      Arg::with_name("uncaught-exit-code")
+        .long("uncaught-exit-code")
+        .value_name("CODE")
+        .help("Exit code for uncaught exceptions and unhandled promise rejections (default: 1)")
+        .takes_value(true)
+        .validator(|val: String| match val.parse::<i32>() {
+          Ok(code) if code > 0 && code < 256 => Ok(()),
+          _ => Err("Exit code should be a number from 1 to 255".to_string()),
+        }),
+    )
+    .arg(
+      Arg::with_name("warn-slow-ops")
+        .long("warn-slow-ops")
+        .value_name("MS")
+        .help("Warn about sync ops and event loop ticks taking longer than given time; collects timings for Deno.metrics()")
+        .takes_value(true)
+        .validator(positive_number_validate),
+    )
+    .arg(
+      Arg::with_name("dump-metrics")
+        .long("dump-metrics")
+        .value_name("FILE")
+        .help("Write Deno.metrics(), including counters of every op, as JSON to given file (default: stderr) at exit")
+        .min_values(0)
+        .max_values(1)
+        .require_equals(true)
+        .takes_value(true),
+    )
+}
+
+fn runtime_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let parse = |name: &str| {
+    matches
+      .value_of(name)
+      .map(|val| val.parse::<usize>().unwrap())
+  };
+  flags.runtime_threads = parse("runtime-threads");
+  flags.runtime_stack_size = parse("runtime-stack-size");
+  flags.blocking_threads = parse("blocking-threads");
+  flags.shutdown_timeout = matches
+    .value_of("shutdown-timeout")
+    .map(|val| val.parse::<u64>().unwrap());
+  flags.uncaught_exit_code = matches
+    .value_of("uncaught-exit-code")
+    .map(|val| val.parse::<i32>().unwrap());
+  flags.warn_slow_ops = matches
+    .value_of("warn-slow-ops")
+    .map(|val| val.parse::<u64>().unwrap());
+  if matches.is_present("dump-metrics") {
+    flags.dump_metrics =
+      Some(matches.value_of("dump-metrics").map(String::from));
+  }
+}
+
 fn inspect_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
   app
     .arg(
@@ -1214,7 +2289,21 @@ fn v8_flags_arg<'a, 'b>() -> Arg<'a, 'b> {
     .takes_value(true)
     .use_delimiter(true)
     .require_equals(true)
+    .validator(v8_flag_validate)
     .help("Set V8 command line options. For help: --v8-flags=--help")
+    .long_help(
+      "Set V8 command line options, separated by commas:
+  --v8-flags=--max-old-space-size=512,--expose-gc
+To list the available options: --v8-flags=--help",
+    )
+}
+
+fn v8_flag_validate(val: String) -> Result<(), String> {
+  if val.starts_with('-') {
+    Ok(())
+  } else {
+    Err(format!("V8 flags must start with '-', got '{}'", val))
+  }
 }
 
 fn v8_flags_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
@@ -1236,6 +2325,121 @@ fn no_remote_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   }
 }
 
+fn no_remote_check_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("no-remote-check")
+    .long("no-remote-check")
+    .help("Ignore type errors in remote modules")
+    .long_help(
+      "Ignore type errors in remote modules. Local modules are still type
+checked, including their use of remote modules.",
+    )
+}
+
+fn no_remote_check_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  if matches.is_present("no-remote-check") {
+    flags.no_remote_check = true;
+  }
+}
+
+fn source_maps_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("source-maps")
+    .long("source-maps")
+    .value_name("MODE")
+    .help("Set how source maps of compiled modules are stored")
+    .long_help(
+      "Set how source maps of compiled modules are stored. 'external' (the
+default) caches them as .map files next to the compiled code, 'inline' appends
+them to the compiled code, and 'none' drops them, which makes the cache smaller
+but stack traces point into the compiled code.",
+    )
+    .possible_values(&["external", "inline", "none"])
+    .require_equals(true)
+    .takes_value(true)
+}
+
+fn source_maps_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  flags.source_maps = matches.value_of("source-maps").map(String::from);
+}
+
+fn location_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("location")
+    .long("location")
+    .value_name("HREF")
+    .help("Set the origin of localStorage and sessionStorage")
+    .long_help(
+      "Set the origin of localStorage and sessionStorage, from an http or
+https URL. Without it, the origin is the one of a remote main module, or each
+local main module has its own storage.",
+    )
+    .takes_value(true)
+    .validator(|href| match Url::parse(&href) {
+      Ok(url) if url.scheme() == "http" || url.scheme() == "https" => Ok(()),
+      _ => Err(format!("Expected an http or https URL, got: {}", href)),
+    })
+}
+
+fn location_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  flags.location = matches
+    .value_of("location")
+    .map(|href| Url::parse(href).unwrap());
+}
+
+fn define_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("define")
+    .long("define")
+    .value_name("NAME=VALUE")
+    .help("Replace a global name with a constant when compiling")
+    .long_help(
+      "Replace a global name, like DENO_ENV or process.env.NODE_ENV, with a
+constant when compiling. Conditions that become constant are folded, so
+branches that can't run are left out. The value is a JSON literal, or else a
+string:
+  --define DEBUG=false --define DENO_ENV=production",
+    )
+    .multiple(true)
+    .number_of_values(1)
+    .validator(define_validate)
+    .takes_value(true)
+}
+
+fn define_validate(val: String) -> Result<(), String> {
+  parse_define(&val).map(|_| ())
+}
+
+/// Parses `NAME=VALUE`, returning the value as a JSON literal.
+pub fn parse_define(define: &str) -> Result<(String, String), String> {
+  let mut parts = define.splitn(2, '=');
+  let name = parts.next().unwrap();
+  let value = match parts.next() {
+    Some(value) => value,
+    None => return Err("Expected NAME=VALUE".to_string()),
+  };
+  let is_name = !name.is_empty()
+    && name.split('.').all(|part| {
+      let mut chars = part.chars();
+      chars
+        .next()
+        .map_or(false, |c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    });
+  if !is_name {
+    return Err(format!("'{}' is not a name", name));
+  }
+  let literal = match serde_json::from_str::<serde_json::Value>(value) {
+    Ok(value) if !value.is_array() && !value.is_object() => value,
+    _ => serde_json::Value::String(value.to_string()),
+  };
+  Ok((name.to_string(), literal.to_string()))
+}
+
+fn define_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  if let Some(defines) = matches.values_of("define") {
+    flags.defines = defines
+      .map(|define| parse_define(define).unwrap())
+      .collect();
+  }
+}
+
 fn permission_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("allow-read") {
     if matches.value_of("allow-read").is_some() {
@@ -1279,6 +2483,9 @@ fn permission_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("allow-plugin") {
     flags.allow_plugin = true;
   }
+  if matches.is_present("allow-ffi") {
+    flags.allow_ffi = true;
+  }
   if matches.is_present("allow-hrtime") {
     flags.allow_hrtime = true;
   }
@@ -1290,6 +2497,7 @@ fn permission_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     flags.allow_read = true;
     flags.allow_write = true;
     flags.allow_plugin = true;
+    flags.allow_ffi = true;
     flags.allow_hrtime = true;
   }
 }
@@ -1318,10 +2526,15 @@ pub fn resolve_urls(urls: Vec<String>) -> Vec<String> {
 
 /// Expands "bare port" paths (eg. ":8080") into full paths with hosts. It
 /// expands to such paths into 3 paths with following hosts: `0.0.0.0:port`,
-/// `127.0.0.1:port` and `localhost:port`.
+/// `127.0.0.1:port` and `localhost:port`. CIDR ranges are passed through
+/// as-is, since IPv6 ranges contain colons of their own.
 fn resolve_hosts(paths: Vec<String>) -> Vec<String> {
   let mut out: Vec<String> = vec![];
   for host_and_port in paths.iter() {
+    if host_and_port.contains('/') {
+      out.push(host_and_port.to_owned());
+      continue;
+    }
     let parts = host_and_port.split(':').collect::<Vec<&str>>();
 
     match parts.len() {
@@ -1367,7 +2580,35 @@ mod tests {
         subcommand: DenoSubcommand::Upgrade {
           force: true,
           dry_run: true,
-          version: None
+          version: None,
+          output: None,
+          canary: false,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn upgrade_with_output_canary() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "upgrade",
+      "--canary",
+      "--version",
+      "1a2b3c4d",
+      "--output",
+      "/tmp/deno"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Upgrade {
+          force: false,
+          dry_run: false,
+          version: Some("1a2b3c4d".to_string()),
+          output: Some(PathBuf::from("/tmp/deno")),
+          canary: true,
         },
         ..Flags::default()
       }
@@ -1377,9 +2618,9 @@ mod tests {
   #[test]
   fn version() {
     let r = flags_from_vec_safe(svec!["deno", "--version"]);
-    assert_eq!(r.unwrap_err().kind, clap::ErrorKind::VersionDisplayed);
+    assert_eq!(r.unwrap_err(), FlagsError::Version);
     let r = flags_from_vec_safe(svec!["deno", "-V"]);
-    assert_eq!(r.unwrap_err().kind, clap::ErrorKind::VersionDisplayed);
+    assert_eq!(r.unwrap_err(), FlagsError::Version);
   }
 
   #[test]
@@ -1455,6 +2696,14 @@ mod tests {
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--v8-flags=--expose-gc,max-old-space-size=512",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -1480,6 +2729,21 @@ mod tests {
     );
   }
 
+  #[test]
+  fn allow_ffi() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "--allow-ffi", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "gist.ts".to_string(),
+        },
+        allow_ffi: true,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn allow_all() {
     let r = flags_from_vec_safe(svec!["deno", "run", "--allow-all", "gist.ts"]);
@@ -1495,6 +2759,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1561,39 +2826,289 @@ mod tests {
   }
 
   #[test]
-  fn fmt() {
-    let r =
-      flags_from_vec_safe(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
+  fn double_hyphen_before_script() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--allow-write",
+      "--",
+      "script.ts",
+      "--allow-net"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt {
-          check: false,
-          files: vec!["script_1.ts".to_string(), "script_2.ts".to_string()]
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
         },
+        argv: svec!["--allow-net"],
+        allow_write: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec_safe(svec!["deno", "fmt", "--check"]);
+  #[test]
+  fn double_hyphen_test() {
+    let r = flags_from_vec_safe(svec![
+      "deno", "test", "--update", "dir1", "--", "--foo", "bar"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Fmt {
-          check: true,
-          files: vec![],
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          allow_none: false,
+          quiet: false,
+          trace_ops: false,
+          filter: None,
+          include: Some(svec!["dir1"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
         },
+        argv: svec!["--update", "--foo", "bar"],
+        allow_read: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec_safe(svec!["deno", "fmt"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Fmt {
-          check: false,
-          files: vec![],
+  #[test]
+  fn unknown_flag_suggestion() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "--alow-net", "a.ts"]);
+    match r.unwrap_err() {
+      FlagsError::UnknownFlag {
+        flag,
+        suggestion,
+        usage,
+      } => {
+        assert_eq!(flag, "--alow-net");
+        assert_eq!(suggestion, Some("--allow-net".to_string()));
+        assert!(usage.starts_with("USAGE:"));
+      }
+      err => panic!("unexpected error: {:?}", err),
+    }
+
+    let r = flags_from_vec_safe(svec!["deno", "-L", "info", "fmt", "--chek"]);
+    match r.unwrap_err() {
+      FlagsError::UnknownFlag { suggestion, .. } => {
+        assert_eq!(suggestion, Some("--check".to_string()))
+      }
+      err => panic!("unexpected error: {:?}", err),
+    }
+
+    let r = flags_from_vec_safe(svec!["deno", "run", "--xyzzy", "a.ts"]);
+    let err = r.unwrap_err();
+    assert_eq!(err.exit_code(), 2);
+    match err {
+      FlagsError::UnknownFlag { suggestion, .. } => {
+        assert_eq!(suggestion, None)
+      }
+      err => panic!("unexpected error: {:?}", err),
+    }
+  }
+
+  #[test]
+  fn invalid_value_error() {
+    let r = flags_from_vec_safe(svec!["deno", "test", "--reporter=xml"]);
+    let err = r.unwrap_err();
+    assert!(matches!(err, FlagsError::Invalid(_)));
+    assert_eq!(err.exit_code(), 2);
+  }
+
+  #[test]
+  fn help_error() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "--help"]);
+    let err = r.unwrap_err();
+    assert!(err.to_string().contains("--allow-net"));
+    assert_eq!(err.exit_code(), 0);
+  }
+
+  fn flags_with_env(
+    args: Vec<String>,
+    vars: &[(&str, &str)],
+  ) -> Result<Flags, FlagsError> {
+    let vars: std::collections::HashMap<String, String> = vars
+      .iter()
+      .map(|(name, value)| (name.to_string(), value.to_string()))
+      .collect();
+    flags_from_vec_and_env(args, &|name| vars.get(name).cloned())
+  }
+
+  #[test]
+  fn env_flags() {
+    let r = flags_with_env(
+      svec!["deno", "run", "--v8-flags=--expose-gc", "script.ts"],
+      &[
+        ("DENO_NO_COLOR", "1"),
+        ("DENO_CERT", "ca.pem"),
+        ("DENO_V8_FLAGS", "--max-old-space-size=100,--expose-gc"),
+      ],
+    );
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        no_color: true,
+        ca_file: Some("ca.pem".to_string()),
+        v8_flags: Some(svec![
+          "--max-old-space-size=100",
+          "--expose-gc",
+          "--expose-gc"
+        ]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn env_flags_under_cli_flags() {
+    let r = flags_with_env(
+      svec!["deno", "run", "--cert", "other.pem", "script.ts"],
+      &[("DENO_CERT", "ca.pem"), ("DENO_NO_COLOR", "")],
+    );
+    let flags = r.unwrap();
+    assert_eq!(flags.ca_file, Some("other.pem".to_string()));
+    assert!(!flags.no_color);
+
+    let r = flags_with_env(
+      svec!["deno", "uninstall", "--root", "/opt", "file_server"],
+      &[("DENO_INSTALL_ROOT", "/usr/local")],
+    );
+    assert_eq!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Uninstall {
+        name: "file_server".to_string(),
+        root: Some(PathBuf::from("/opt")),
+      }
+    );
+
+    let r = flags_with_env(
+      svec!["deno", "uninstall", "file_server"],
+      &[("DENO_INSTALL_ROOT", "/usr/local")],
+    );
+    assert_eq!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Uninstall {
+        name: "file_server".to_string(),
+        root: Some(PathBuf::from("/usr/local")),
+      }
+    );
+  }
+
+  #[test]
+  fn env_web_worker_count() {
+    let env = [("DENO_WEB_WORKER_COUNT", "4")];
+    let r = flags_with_env(svec!["deno", "test"], &env);
+    match r.unwrap().subcommand {
+      DenoSubcommand::Test { jobs, .. } => assert_eq!(jobs, 4),
+      _ => unreachable!(),
+    }
+    let r = flags_with_env(svec!["deno", "test", "--jobs=2"], &env);
+    match r.unwrap().subcommand {
+      DenoSubcommand::Test { jobs, .. } => assert_eq!(jobs, 2),
+      _ => unreachable!(),
+    }
+    let r =
+      flags_with_env(svec!["deno", "test"], &[("DENO_WEB_WORKER_COUNT", "0")]);
+    assert!(matches!(r, Err(FlagsError::Invalid(_))));
+  }
+
+  #[test]
+  fn edit_distance_of_flags() {
+    assert_eq!(edit_distance("--allow-net", "--allow-net"), 0);
+    assert_eq!(edit_distance("--alow-net", "--allow-net"), 1);
+    assert_eq!(edit_distance("--allow-nte", "--allow-net"), 2);
+    assert_eq!(edit_distance("", "abc"), 3);
+  }
+
+  #[test]
+  fn fmt() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt {
+          check: false,
+          files: vec!["script_1.ts".to_string(), "script_2.ts".to_string()]
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "fmt", "--check"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt {
+          check: true,
+          files: vec![],
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "fmt"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt {
+          check: false,
+          files: vec![],
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn lint() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "lint", "script_1.ts", "script_2.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint {
+          files: svec!["script_1.ts", "script_2.ts"],
+          rules: false,
+          fix: false,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "lint",
+      "--rules",
+      "--config",
+      "other/deno.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint {
+          files: vec![],
+          rules: true,
+          fix: false,
+        },
+        config_path: Some("other/deno.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "lint", "--fix"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint {
+          files: vec![],
+          rules: false,
+          fix: true,
         },
         ..Flags::default()
       }
@@ -1606,7 +3121,7 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Types,
+        subcommand: DenoSubcommand::Types { libs: vec![] },
         ..Flags::default()
       }
     );
@@ -1619,7 +3134,41 @@ mod tests {
       r.unwrap(),
       Flags {
         unstable: true,
-        subcommand: DenoSubcommand::Types,
+        subcommand: DenoSubcommand::Types { libs: vec![] },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn lsp() {
+    let r = flags_from_vec_safe(svec!["deno", "lsp", "--unstable"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        unstable: true,
+        subcommand: DenoSubcommand::Lsp,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn types_lib() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "types",
+      "--unstable",
+      "--lib",
+      "dom,deno.ns"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        unstable: true,
+        subcommand: DenoSubcommand::Types {
+          libs: svec!["dom", "deno.ns"],
+        },
         ..Flags::default()
       }
     );
@@ -1633,10 +3182,29 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts"],
+          prune: None,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cache_prune() {
+    let r = flags_from_vec_safe(svec!["deno", "cache", "--prune=100"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache {
+          files: vec![],
+          prune: Some(100),
         },
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec!["deno", "cache"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -1649,6 +3217,7 @@ mod tests {
         unstable: true,
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts"],
+          prune: None,
         },
         ..Flags::default()
       }
@@ -1663,6 +3232,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info {
           file: Some("script.ts".to_string()),
+          json: false,
         },
         ..Flags::default()
       }
@@ -1672,10 +3242,28 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info { file: None },
+        subcommand: DenoSubcommand::Info {
+          file: None,
+          json: false,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "info", "--json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info {
+          file: None,
+          json: true,
+        },
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec!["deno", "info", "--json", "script.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -1709,6 +3297,7 @@ mod tests {
         subcommand: DenoSubcommand::Eval {
           code: "'console.log(\"hello\")'".to_string(),
           as_typescript: false,
+          print: false,
         },
         allow_net: true,
         allow_env: true,
@@ -1716,6 +3305,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1737,6 +3327,7 @@ mod tests {
         subcommand: DenoSubcommand::Eval {
           code: "'console.log(\"hello\")'".to_string(),
           as_typescript: false,
+          print: false,
         },
         allow_net: true,
         allow_env: true,
@@ -1744,6 +3335,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1764,6 +3356,7 @@ mod tests {
         subcommand: DenoSubcommand::Eval {
           code: "'console.log(\"hello\")'".to_string(),
           as_typescript: true,
+          print: false,
         },
         allow_net: true,
         allow_env: true,
@@ -1771,6 +3364,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1778,23 +3372,23 @@ mod tests {
   }
 
   #[test]
-  fn eval_with_v8_flags() {
-    let r =
-      flags_from_vec_safe(svec!["deno", "eval", "--v8-flags=--help", "42"]);
+  fn eval_print() {
+    let r = flags_from_vec_safe(svec!["deno", "eval", "-p", "1 + 2"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Eval {
-          code: "42".to_string(),
+          code: "1 + 2".to_string(),
           as_typescript: false,
+          print: true,
         },
-        v8_flags: Some(svec!["--help"]),
         allow_net: true,
         allow_env: true,
         allow_run: true,
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1802,18 +3396,23 @@ mod tests {
   }
 
   #[test]
-  fn repl() {
-    let r = flags_from_vec_safe(svec!["deno"]);
+  fn eval_stdin() {
+    let r = flags_from_vec_safe(svec!["deno", "eval", "--ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl,
+        subcommand: DenoSubcommand::Eval {
+          code: "-".to_string(),
+          as_typescript: true,
+          print: false,
+        },
         allow_net: true,
         allow_env: true,
         allow_run: true,
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1821,19 +3420,25 @@ mod tests {
   }
 
   #[test]
-  fn repl_unstable() {
-    let r = flags_from_vec_safe(svec!["deno", "repl", "--unstable"]);
+  fn eval_with_v8_flags() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "eval", "--v8-flags=--help", "42"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        unstable: true,
-        subcommand: DenoSubcommand::Repl,
+        subcommand: DenoSubcommand::Eval {
+          code: "42".to_string(),
+          as_typescript: false,
+          print: false,
+        },
+        v8_flags: Some(svec!["--help"]),
         allow_net: true,
         allow_env: true,
         allow_run: true,
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -1841,69 +3446,189 @@ mod tests {
   }
 
   #[test]
-  fn allow_read_whitelist() {
-    use tempfile::TempDir;
-    let temp_dir = TempDir::new().expect("tempdir fail").path().to_path_buf();
-
-    let r = flags_from_vec_safe(svec![
-      "deno",
-      "run",
-      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
-      "script.ts"
-    ]);
+  fn repl() {
+    let r = flags_from_vec_safe(svec!["deno"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_read: false,
-        read_whitelist: vec![current_dir().unwrap(), temp_dir],
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
-        },
+        subcommand: DenoSubcommand::default(),
+        allow_net: true,
+        allow_env: true,
+        allow_run: true,
+        allow_read: true,
+        allow_write: true,
+        allow_plugin: true,
+        allow_ffi: true,
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_write_whitelist() {
-    use tempfile::TempDir;
-    let temp_dir = TempDir::new().expect("tempdir fail").path().to_path_buf();
-
-    let r = flags_from_vec_safe(svec![
-      "deno",
-      "run",
-      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
-      "script.ts"
-    ]);
+  fn repl_no_color() {
+    let r = flags_from_vec_safe(svec!["deno", "repl", "--no-color"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_write: false,
-        write_whitelist: vec![current_dir().unwrap(), temp_dir],
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
-        },
+        no_color: true,
+        subcommand: DenoSubcommand::default(),
+        allow_net: true,
+        allow_env: true,
+        allow_run: true,
+        allow_read: true,
+        allow_write: true,
+        allow_plugin: true,
+        allow_ffi: true,
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_whitelist() {
+  fn repl_history() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "run",
-      "--allow-net=127.0.0.1",
-      "script.ts"
+      "repl",
+      "--history-file",
+      "history.txt",
+      "--history-size",
+      "50",
+      "--history-ignore-space"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Repl {
+          history_file: Some(PathBuf::from("history.txt")),
+          history_size: Some(50),
+          history_ignore_space: true,
+          check: false,
         },
-        allow_net: false,
-        net_whitelist: svec!["127.0.0.1"],
+        allow_net: true,
+        allow_env: true,
+        allow_run: true,
+        allow_read: true,
+        allow_write: true,
+        allow_plugin: true,
+        allow_ffi: true,
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl_check() {
+    let r = flags_from_vec_safe(svec!["deno", "repl", "--check"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Repl {
+          history_file: None,
+          history_size: None,
+          history_ignore_space: false,
+          check: true,
+        },
+        allow_net: true,
+        allow_env: true,
+        allow_run: true,
+        allow_read: true,
+        allow_write: true,
+        allow_plugin: true,
+        allow_ffi: true,
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl_unstable() {
+    let r = flags_from_vec_safe(svec!["deno", "repl", "--unstable"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        unstable: true,
+        subcommand: DenoSubcommand::default(),
+        allow_net: true,
+        allow_env: true,
+        allow_run: true,
+        allow_read: true,
+        allow_write: true,
+        allow_plugin: true,
+        allow_ffi: true,
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_read_whitelist() {
+    use tempfile::TempDir;
+    let temp_dir = TempDir::new().expect("tempdir fail").path().to_path_buf();
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        allow_read: false,
+        read_whitelist: vec![current_dir().unwrap(), temp_dir],
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_write_whitelist() {
+    use tempfile::TempDir;
+    let temp_dir = TempDir::new().expect("tempdir fail").path().to_path_buf();
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        allow_write: false,
+        write_whitelist: vec![current_dir().unwrap(), temp_dir],
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_net_whitelist() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--allow-net=127.0.0.1",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        allow_net: false,
+        net_whitelist: svec!["127.0.0.1"],
         ..Flags::default()
       }
     );
@@ -1918,7 +3643,31 @@ mod tests {
         subcommand: DenoSubcommand::Bundle {
           source_file: "source.ts".to_string(),
           out_file: None,
+          minify: false,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bundle_minify() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "bundle",
+      "--minify",
+      "source.ts",
+      "bundle.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle {
+          source_file: "source.ts".to_string(),
+          out_file: Some(PathBuf::from("bundle.js")),
+          minify: true,
         },
+        allow_write: true,
         ..Flags::default()
       }
     );
@@ -1935,6 +3684,7 @@ mod tests {
         subcommand: DenoSubcommand::Bundle {
           source_file: "source.ts".to_string(),
           out_file: None,
+          minify: false,
         },
         ..Flags::default()
       }
@@ -1957,6 +3707,7 @@ mod tests {
         subcommand: DenoSubcommand::Bundle {
           source_file: "source.ts".to_string(),
           out_file: Some(PathBuf::from("bundle.js")),
+          minify: false,
         },
         allow_write: true,
         config_path: Some("tsconfig.json".to_owned()),
@@ -1975,6 +3726,7 @@ mod tests {
         subcommand: DenoSubcommand::Bundle {
           source_file: "source.ts".to_string(),
           out_file: Some(PathBuf::from("bundle.js")),
+          minify: false,
         },
         allow_write: true,
         ..Flags::default()
@@ -2015,6 +3767,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts"],
+          prune: None,
         },
         import_map_path: Some("importmap.json".to_owned()),
         ..Flags::default()
@@ -2031,6 +3784,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts", "script_two.ts"],
+          prune: None,
         },
         ..Flags::default()
       }
@@ -2055,13 +3809,12 @@ mod tests {
   }
 
   #[test]
-  fn run_seed_with_v8_flags() {
+  fn run_blocking_threads() {
     let r = flags_from_vec_safe(svec![
       "deno",
       "run",
-      "--seed",
-      "250",
-      "--v8-flags=--expose-gc",
+      "--blocking-threads",
+      "4",
       "script.ts"
     ]);
     assert_eq!(
@@ -2070,126 +3823,848 @@ mod tests {
         subcommand: DenoSubcommand::Run {
           script: "script.ts".to_string(),
         },
-        seed: Some(250 as u64),
-        v8_flags: Some(svec!["--expose-gc", "--random-seed=250"]),
+        blocking_threads: Some(4),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--blocking-threads",
+      "0",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn install() {
+  fn run_shutdown_timeout() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "install",
-      "https://deno.land/std/examples/colors.ts"
+      "run",
+      "--shutdown-timeout",
+      "500",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: None,
-          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        shutdown_timeout: Some(500),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--shutdown-timeout",
+      "-1",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn run_uncaught_exit_code() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--uncaught-exit-code=3",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
         },
+        uncaught_exit_code: Some(3),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--uncaught-exit-code=0",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn install_unstable() {
+  fn run_compat() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "install",
+      "run",
       "--unstable",
-      "https://deno.land/std/examples/colors.ts"
+      "--compat",
+      "script.js"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.js".to_string(),
+        },
+        compat: true,
         unstable: true,
-        subcommand: DenoSubcommand::Install {
-          name: None,
-          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: svec![],
-          root: None,
-          force: false,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "run", "--compat", "script.js"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn run_warn_slow_ops() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--warn-slow-ops=50",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
         },
+        warn_slow_ops: Some(50),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--warn-slow-ops=0",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn install_with_args() {
+  fn run_dump_metrics() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--dump-metrics", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        dump_metrics: Some(None),
+        ..Flags::default()
+      }
+    );
+
     let r = flags_from_vec_safe(svec![
       "deno",
-      "install",
-      "--allow-net",
-      "--allow-read",
-      "-n",
-      "file_server",
-      "https://deno.land/std/http/file_server.ts"
+      "run",
+      "--dump-metrics=metrics.json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        dump_metrics: Some(Some("metrics.json".to_string())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_startup_timing() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--startup-timing",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        startup_timing: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_stdin() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "--ext=ts", "-"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "-".to_string(),
+        },
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "run", "--ext=py", "-"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn run_trace_ops() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--trace-ops=trace.json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        trace_ops_file: Some("trace.json".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_runtime_threads() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--runtime-threads",
+      "4",
+      "--runtime-stack-size",
+      "4194304",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        runtime_threads: Some(4),
+        runtime_stack_size: Some(4_194_304),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--runtime-threads",
+      "foo",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn run_seed_with_v8_flags() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--seed",
+      "250",
+      "--v8-flags=--expose-gc",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        seed: Some(250 as u64),
+        v8_flags: Some(svec!["--expose-gc", "--random-seed=250"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install {
+          name: None,
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          args: vec![],
+          root: None,
+          force: false,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn uninstall() {
+    let r = flags_from_vec_safe(svec!["deno", "uninstall", "file_server"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall {
+          name: "file_server".to_string(),
+          root: None,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "uninstall",
+      "--root",
+      "/usr/local",
+      "file_server"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall {
+          name: "file_server".to_string(),
+          root: Some(PathBuf::from("/usr/local")),
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn compile() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "compile",
+      "--allow-net",
+      "--unstable",
+      "-o",
+      "file_server",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Compile {
+          source_file: "https://deno.land/std/http/file_server.ts".to_string(),
+          output: Some(PathBuf::from("file_server")),
+        },
+        allow_net: true,
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn task() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "task",
+      "--config",
+      "other/deno.json",
+      "build",
+      "--release",
+      "out"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task {
+          task: Some("build".to_string()),
+          args: svec!["--release", "out"],
+        },
+        config_path: Some("other/deno.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "task"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task {
+          task: None,
+          args: vec![],
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_unstable() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--unstable",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        unstable: true,
+        subcommand: DenoSubcommand::Install {
+          name: None,
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          args: svec![],
+          root: None,
+          force: false,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_with_args() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--allow-net",
+      "--allow-read",
+      "-n",
+      "file_server",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install {
+          name: Some("file_server".to_string()),
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: vec![],
+          root: None,
+          force: false,
+        },
+        allow_net: true,
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_with_args_and_dir_and_force() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--root",
+      "/usr/local",
+      "-f",
+      "--allow-net",
+      "--allow-read",
+      "-n",
+      "file_server",
+      "https://deno.land/std/http/file_server.ts",
+      "arg1",
+      "arg2"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install {
+          name: Some("file_server".to_string()),
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: svec!["arg1", "arg2"],
+          root: Some(PathBuf::from("/usr/local")),
+          force: true,
+        },
+        allow_net: true,
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn log_level() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--log-level=debug",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        log_level: Some(Level::Debug),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn log_level_trace_json() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--log-level=trace",
+      "--log-json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        log_level: Some(Level::Trace),
+        log_json: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn no_color() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "--no-color", "run", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        no_color: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn quiet() {
+    let r = flags_from_vec_safe(svec!["deno", "run", "-q", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        log_level: Some(Level::Error),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn completions() {
+    let r = flags_from_vec_safe(svec!["deno", "completions", "bash"]).unwrap();
+
+    match r.subcommand {
+      DenoSubcommand::Completions { buf } => assert!(!buf.is_empty()),
+      _ => unreachable!(),
+    }
+  }
+
+  /* TODO(ry) Fix this test
+  #[test]
+  fn test_flags_from_vec_33() {
+    let (flags, subcommand, argv) =
+      flags_from_vec_safe(svec!["deno", "script.ts", "--allow-read", "--allow-net"]);
+    assert_eq!(
+      flags,
+      Flags {
+        allow_net: true,
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+    assert_eq!(subcommand, DenoSubcommand::Run);
+    assert_eq!(argv, svec!["script.ts"]);
+
+    let (flags, subcommand, argv) = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--allow-read",
+      "script.ts",
+      "--allow-net",
+      "-r",
+      "--help",
+      "--foo",
+      "bar"
+    ]);
+    assert_eq!(
+      flags,
+      Flags {
+        allow_net: true,
+        allow_read: true,
+        reload: true,
+        ..Flags::default()
+      }
+    );
+    assert_eq!(subcommand, DenoSubcommand::Run);
+    assert_eq!(argv, svec!["deno", "script.ts", "--help", "--foo", "bar"]);
+
+    let (flags, subcommand, argv) =
+      flags_from_vec_safe(svec!["deno""script.ts", "foo", "bar"]);
+    assert_eq!(flags, Flags::default());
+    assert_eq!(subcommand, DenoSubcommand::Run);
+  assert_eq!(argv, svec!["script.ts", "foo", "bar"]);
+
+    let (flags, subcommand, argv) =
+      flags_from_vec_safe(svec!["deno""script.ts", "-"]);
+    assert_eq!(flags, Flags::default());
+    assert_eq!(subcommand, DenoSubcommand::Run);
+    assert_eq!(argv, svec!["script.ts", "-"]);
+
+    let (flags, subcommand, argv) =
+      flags_from_vec_safe(svec!["deno""script.ts", "-", "foo", "bar"]);
+    assert_eq!(flags, Flags::default());
+    assert_eq!(subcommand, DenoSubcommand::Run);
+    assert_eq!(argv, svec!["script.ts", "-", "foo", "bar"]);
+  }
+  */
+
+  #[test]
+  fn no_remote() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--no-remote", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        no_remote: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn no_remote_check() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--no-remote-check",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        no_remote_check: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "bundle",
+      "--no-remote-check",
+      "source.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+          minify: false,
+        },
+        no_remote_check: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn source_maps() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--source-maps=inline",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        source_maps: Some("inline".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--source-maps=both",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn define() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--define",
+      "DEBUG=false",
+      "--define=process.env.NODE_ENV=production",
+      "--define",
+      "VERSION=\"1.0\"",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        defines: vec![
+          ("DEBUG".to_string(), "false".to_string()),
+          (
+            "process.env.NODE_ENV".to_string(),
+            "\"production\"".to_string()
+          ),
+          ("VERSION".to_string(), "\"1.0\"".to_string()),
+        ],
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec!["deno", "run", "--define", "DEBUG"]);
+    assert!(r.is_err());
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--define",
+      "1x=2",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn cached_only() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--cached-only", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        cached_only: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn location() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--location",
+      "https://example.com/app/",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        location: Some(Url::parse("https://example.com/app/").unwrap()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--location",
+      "file:///app/",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn allow_net_whitelist_with_ports() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--allow-net=deno.land,:8000,:4545",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: Some("file_server".to_string()),
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
         },
-        allow_net: true,
-        allow_read: true,
+        net_whitelist: svec![
+          "deno.land",
+          "0.0.0.0:8000",
+          "127.0.0.1:8000",
+          "localhost:8000",
+          "0.0.0.0:4545",
+          "127.0.0.1:4545",
+          "localhost:4545"
+        ],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn install_with_args_and_dir_and_force() {
+  fn allow_net_whitelist_with_patterns() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "install",
-      "--root",
-      "/usr/local",
-      "-f",
-      "--allow-net",
-      "--allow-read",
-      "-n",
-      "file_server",
-      "https://deno.land/std/http/file_server.ts",
-      "arg1",
-      "arg2"
+      "run",
+      "--allow-net=*.deno.land,10.0.0.0/8,fd00::/8:443",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: Some("file_server".to_string()),
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: svec!["arg1", "arg2"],
-          root: Some(PathBuf::from("/usr/local")),
-          force: true,
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
         },
-        allow_net: true,
-        allow_read: true,
+        net_whitelist: svec!["*.deno.land", "10.0.0.0/8", "fd00::/8:443"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn log_level() {
+  fn lock_write() {
     let r = flags_from_vec_safe(svec![
       "deno",
       "run",
-      "--log-level=debug",
+      "--lock-write",
+      "--lock=lock.json",
       "script.ts"
     ]);
     assert_eq!(
@@ -2198,208 +4673,232 @@ mod tests {
         subcommand: DenoSubcommand::Run {
           script: "script.ts".to_string(),
         },
-        log_level: Some(Level::Debug),
+        lock_write: true,
+        lock: Some("lock.json".to_string()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn quiet() {
-    let r = flags_from_vec_safe(svec!["deno", "run", "-q", "script.ts"]);
+  fn cache_reload_lock_write() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "cache",
+      "--reload",
+      "--lock=lock.json",
+      "--lock-write",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Cache {
+          files: svec!["script.ts"],
+          prune: None,
         },
-        log_level: Some(Level::Error),
+        reload: true,
+        lock_write: true,
+        lock: Some("lock.json".to_string()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn completions() {
-    let r = flags_from_vec_safe(svec!["deno", "completions", "bash"]).unwrap();
-
-    match r.subcommand {
-      DenoSubcommand::Completions { buf } => assert!(!buf.is_empty()),
-      _ => unreachable!(),
-    }
+  fn lock_write_requires_lock() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "cache", "--lock-write", "script.ts"]);
+    assert!(r.is_err());
   }
 
-  /* TODO(ry) Fix this test
   #[test]
-  fn test_flags_from_vec_33() {
-    let (flags, subcommand, argv) =
-      flags_from_vec_safe(svec!["deno", "script.ts", "--allow-read", "--allow-net"]);
-    assert_eq!(
-      flags,
-      Flags {
-        allow_net: true,
-        allow_read: true,
-        ..Flags::default()
-      }
-    );
-    assert_eq!(subcommand, DenoSubcommand::Run);
-    assert_eq!(argv, svec!["script.ts"]);
-
-    let (flags, subcommand, argv) = flags_from_vec_safe(svec![
+  fn test_with_allow_net() {
+    let r = flags_from_vec_safe(svec![
       "deno",
-      "run",
-      "--allow-read",
-      "script.ts",
+      "test",
       "--allow-net",
-      "-r",
-      "--help",
-      "--foo",
-      "bar"
+      "--allow-none",
+      "dir1/",
+      "dir2/"
     ]);
     assert_eq!(
-      flags,
+      r.unwrap(),
       Flags {
-        allow_net: true,
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          filter: None,
+          allow_none: true,
+          quiet: false,
+          trace_ops: false,
+          include: Some(svec!["dir1/", "dir2/"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
+        },
         allow_read: true,
-        reload: true,
+        allow_net: true,
         ..Flags::default()
       }
     );
-    assert_eq!(subcommand, DenoSubcommand::Run);
-    assert_eq!(argv, svec!["deno", "script.ts", "--help", "--foo", "bar"]);
-
-    let (flags, subcommand, argv) =
-      flags_from_vec_safe(svec!["deno""script.ts", "foo", "bar"]);
-    assert_eq!(flags, Flags::default());
-    assert_eq!(subcommand, DenoSubcommand::Run);
-  assert_eq!(argv, svec!["script.ts", "foo", "bar"]);
-
-    let (flags, subcommand, argv) =
-      flags_from_vec_safe(svec!["deno""script.ts", "-"]);
-    assert_eq!(flags, Flags::default());
-    assert_eq!(subcommand, DenoSubcommand::Run);
-    assert_eq!(argv, svec!["script.ts", "-"]);
-
-    let (flags, subcommand, argv) =
-      flags_from_vec_safe(svec!["deno""script.ts", "-", "foo", "bar"]);
-    assert_eq!(flags, Flags::default());
-    assert_eq!(subcommand, DenoSubcommand::Run);
-    assert_eq!(argv, svec!["script.ts", "-", "foo", "bar"]);
   }
-  */
 
   #[test]
-  fn no_remote() {
-    let r =
-      flags_from_vec_safe(svec!["deno", "run", "--no-remote", "script.ts"]);
+  fn test_filter() {
+    let r = flags_from_vec_safe(svec!["deno", "test", "--filter=foo", "dir1"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          allow_none: false,
+          quiet: false,
+          trace_ops: false,
+          filter: Some("foo".to_string()),
+          include: Some(svec!["dir1"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
         },
-        no_remote: true,
+        allow_read: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cached_only() {
-    let r =
-      flags_from_vec_safe(svec!["deno", "run", "--cached-only", "script.ts"]);
+  fn test_doc() {
+    let r = flags_from_vec_safe(svec!["deno", "test", "--doc", "README.md"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          allow_none: false,
+          quiet: false,
+          trace_ops: false,
+          filter: None,
+          include: Some(svec!["README.md"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: true,
         },
-        cached_only: true,
+        allow_read: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_whitelist_with_ports() {
+  fn bench() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "run",
-      "--allow-net=deno.land,:8000,:4545",
-      "script.ts"
+      "bench",
+      "--unstable",
+      "--filter=parse",
+      "dir1"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Bench {
+          filter: Some("parse".to_string()),
+          include: Some(svec!["dir1"]),
         },
-        net_whitelist: svec![
-          "deno.land",
-          "0.0.0.0:8000",
-          "127.0.0.1:8000",
-          "localhost:8000",
-          "0.0.0.0:4545",
-          "127.0.0.1:4545",
-          "localhost:4545"
-        ],
+        unstable: true,
+        allow_read: true,
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn lock_write() {
+  fn bench_profile() {
     let r = flags_from_vec_safe(svec![
       "deno",
-      "run",
-      "--lock-write",
-      "--lock=lock.json",
-      "script.ts"
+      "bench",
+      "--unstable",
+      "--profile=bench.cpuprofile",
+      "dir1"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Bench {
+          filter: None,
+          include: Some(svec!["dir1"]),
         },
-        lock_write: true,
-        lock: Some("lock.json".to_string()),
+        cpu_profile: Some("bench.cpuprofile".to_string()),
+        unstable: true,
+        allow_read: true,
+        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_with_allow_net() {
+  fn test_jobs_reporter() {
     let r = flags_from_vec_safe(svec![
       "deno",
       "test",
-      "--allow-net",
-      "--allow-none",
-      "dir1/",
-      "dir2/"
+      "--jobs=4",
+      "--reporter=junit",
+      "dir1"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Test {
           fail_fast: false,
+          allow_none: false,
+          quiet: false,
+          trace_ops: false,
           filter: None,
-          allow_none: true,
+          include: Some(svec!["dir1"]),
+          jobs: 4,
+          reporter: "junit".to_string(),
+          doc: false,
+        },
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec_safe(svec!["deno", "test", "--reporter=xml", "dir1"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_update() {
+    let r = flags_from_vec_safe(svec!["deno", "test", "--update", "dir1"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          allow_none: false,
           quiet: false,
-          include: Some(svec!["dir1/", "dir2/"]),
+          trace_ops: false,
+          filter: None,
+          include: Some(svec!["dir1"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
         },
+        argv: svec!["--update"],
         allow_read: true,
-        allow_net: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn test_filter() {
-    let r = flags_from_vec_safe(svec!["deno", "test", "--filter=foo", "dir1"]);
+  fn test_coverage() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "test", "--coverage=cov", "dir1"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -2407,8 +4906,36 @@ mod tests {
           fail_fast: false,
           allow_none: false,
           quiet: false,
-          filter: Some("foo".to_string()),
+          trace_ops: false,
+          filter: None,
+          include: Some(svec!["dir1"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
+        },
+        coverage_dir: Some("cov".to_string()),
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_trace_ops() {
+    let r = flags_from_vec_safe(svec!["deno", "test", "--trace-ops", "dir1"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test {
+          fail_fast: false,
+          allow_none: false,
+          quiet: false,
+          trace_ops: true,
+          filter: None,
           include: Some(svec!["dir1"]),
+          jobs: 1,
+          reporter: "pretty".to_string(),
+          doc: false,
         },
         allow_read: true,
         ..Flags::default()
@@ -2452,6 +4979,7 @@ mod tests {
         subcommand: DenoSubcommand::Bundle {
           source_file: "source.ts".to_string(),
           out_file: None,
+          minify: false,
         },
         ca_file: Some("example.crt".to_owned()),
         ..Flags::default()
@@ -2474,6 +5002,7 @@ mod tests {
         subcommand: DenoSubcommand::Eval {
           code: "console.log('hello world')".to_string(),
           as_typescript: false,
+          print: false,
         },
         ca_file: Some("example.crt".to_owned()),
         allow_net: true,
@@ -2482,6 +5011,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -2502,6 +5032,7 @@ mod tests {
         subcommand: DenoSubcommand::Eval {
           code: "const foo = 'bar'".to_string(),
           as_typescript: false,
+          print: false,
         },
         inspect: Some("127.0.0.1:9229".parse().unwrap()),
         allow_net: true,
@@ -2510,6 +5041,7 @@ mod tests {
         allow_read: true,
         allow_write: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -2531,6 +5063,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts", "script_two.ts"],
+          prune: None,
         },
         ca_file: Some("example.crt".to_owned()),
         ..Flags::default()
@@ -2552,6 +5085,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Info {
           file: Some("https://example.com".to_string()),
+          json: false,
         },
         ca_file: Some("example.crt".to_owned()),
         ..Flags::default()
@@ -2592,7 +5126,7 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl {},
+        subcommand: DenoSubcommand::default(),
         ca_file: Some("example.crt".to_owned()),
         allow_read: true,
         allow_write: true,
@@ -2600,6 +5134,7 @@ mod tests {
         allow_env: true,
         allow_run: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -2612,7 +5147,7 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl {},
+        subcommand: DenoSubcommand::default(),
         inspect: Some("127.0.0.1:9229".parse().unwrap()),
         allow_read: true,
         allow_write: true,
@@ -2620,6 +5155,7 @@ mod tests {
         allow_env: true,
         allow_run: true,
         allow_plugin: true,
+        allow_ffi: true,
         allow_hrtime: true,
         ..Flags::default()
       }
@@ -0,0 +1,369 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Formats JSON, and JSON with comments and trailing commas (`.jsonc`), in a
+//! canonical layout: every object member and array element on its own line,
+//! indented by two spaces, without trailing commas. Comments are kept, and
+//! strings, numbers and literals are written as they are.
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+  Punctuation(char),
+  /// A string, number, `true`, `false` or `null`.
+  Scalar,
+  Comment,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+  kind: TokenKind,
+  text: &'a str,
+  /// Whether a line break separates the token from the previous one.
+  newline_before: bool,
+  line: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = vec![];
+  let mut chars = source.char_indices().peekable();
+  let mut newline_before = true;
+  let mut line = 1;
+  while let Some((start, c)) = chars.next() {
+    let kind = match c {
+      '\n' => {
+        newline_before = true;
+        line += 1;
+        continue;
+      }
+      c if c.is_whitespace() => continue,
+      '{' | '}' | '[' | ']' | ',' | ':' => TokenKind::Punctuation(c),
+      '"' => {
+        let mut escaped = false;
+        loop {
+          match chars.next() {
+            Some((_, '\\')) if !escaped => escaped = true,
+            Some((_, '"')) if !escaped => break,
+            Some((_, '\n')) | None => {
+              return Err(format!("Unterminated string at line {}", line))
+            }
+            Some(_) => escaped = false,
+          }
+        }
+        TokenKind::Scalar
+      }
+      '/' => match chars.next() {
+        Some((_, '/')) => {
+          while let Some((_, c)) = chars.peek() {
+            if *c == '\n' {
+              break;
+            }
+            chars.next();
+          }
+          TokenKind::Comment
+        }
+        Some((_, '*')) => {
+          let mut previous = ' ';
+          loop {
+            match chars.next() {
+              Some((_, '/')) if previous == '*' => break,
+              Some((_, c)) => {
+                if c == '\n' {
+                  line += 1;
+                }
+                previous = c;
+              }
+              None => {
+                return Err(format!("Unterminated comment at line {}", line))
+              }
+            }
+          }
+          TokenKind::Comment
+        }
+        _ => return Err(format!("Unexpected '/' at line {}", line)),
+      },
+      _ => {
+        while let Some((_, c)) = chars.peek() {
+          if c.is_whitespace() || "{}[],:\"/".contains(*c) {
+            break;
+          }
+          chars.next();
+        }
+        TokenKind::Scalar
+      }
+    };
+    let end = chars.peek().map_or(source.len(), |(end, _)| *end);
+    tokens.push(Token {
+      kind,
+      text: &source[start..end],
+      newline_before,
+      line,
+    });
+    newline_before = false;
+  }
+  Ok(tokens)
+}
+
+struct Printer<'a> {
+  tokens: Vec<Token<'a>>,
+  position: usize,
+  out: String,
+  indent: usize,
+  /// Set after a comment, so the next token starts a new line.
+  need_newline: bool,
+}
+
+impl<'a> Printer<'a> {
+  fn peek(&self) -> Option<&Token<'a>> {
+    self.tokens.get(self.position)
+  }
+
+  fn peek_punctuation(&self) -> Option<char> {
+    match self.peek() {
+      Some(Token {
+        kind: TokenKind::Punctuation(c),
+        ..
+      }) => Some(*c),
+      _ => None,
+    }
+  }
+
+  fn newline(&mut self) {
+    self.out.push('\n');
+    for _ in 0..self.indent {
+      self.out.push_str("  ");
+    }
+    self.need_newline = false;
+  }
+
+  fn write(&mut self, text: &str) {
+    if self.need_newline {
+      self.newline();
+    }
+    self.out.push_str(text);
+  }
+
+  fn unexpected(&self) -> String {
+    match self.peek() {
+      Some(token) => {
+        format!("Unexpected '{}' at line {}", token.text, token.line)
+      }
+      None => "Unexpected end of input".to_string(),
+    }
+  }
+
+  fn expect(&mut self, c: char) -> Result<(), String> {
+    if self.peek_punctuation() == Some(c) {
+      self.position += 1;
+      Ok(())
+    } else {
+      Err(self.unexpected())
+    }
+  }
+
+  /// Consumes the comments at the current position.
+  fn take_comments(&mut self) -> Vec<(bool, &'a str)> {
+    let mut comments = vec![];
+    while let Some(token) = self.peek() {
+      if token.kind != TokenKind::Comment {
+        break;
+      }
+      comments.push((token.newline_before, token.text));
+      self.position += 1;
+    }
+    comments
+  }
+
+  /// Writes comments on their own line, or after the previous token if they
+  /// were on its line.
+  fn write_comments(&mut self, comments: Vec<(bool, &str)>) {
+    for (newline_before, text) in comments {
+      if self.out.is_empty() {
+        self.out.push_str(text);
+      } else if newline_before {
+        self.newline();
+        self.out.push_str(text);
+      } else {
+        self.out.push(' ');
+        self.out.push_str(text);
+      }
+      self.need_newline = true;
+    }
+  }
+
+  fn value(&mut self) -> Result<(), String> {
+    let comments = self.take_comments();
+    self.write_comments(comments);
+    match self.peek() {
+      Some(Token {
+        kind: TokenKind::Punctuation('{'),
+        ..
+      }) => self.container('{', '}', true),
+      Some(Token {
+        kind: TokenKind::Punctuation('['),
+        ..
+      }) => self.container('[', ']', false),
+      Some(Token {
+        kind: TokenKind::Scalar,
+        text,
+        ..
+      }) => {
+        let text = *text;
+        self.position += 1;
+        self.write(text);
+        Ok(())
+      }
+      _ => Err(self.unexpected()),
+    }
+  }
+
+  /// Prints an object, whose members are `key: value`, or an array.
+  fn container(
+    &mut self,
+    open: char,
+    close: char,
+    is_object: bool,
+  ) -> Result<(), String> {
+    self.expect(open)?;
+    self.write(&open.to_string());
+    self.indent += 1;
+    let mut is_empty = true;
+    loop {
+      let comments = self.take_comments();
+      if !comments.is_empty() {
+        is_empty = false;
+      }
+      self.write_comments(comments);
+      if self.peek_punctuation() == Some(close) {
+        break;
+      }
+      is_empty = false;
+      self.newline();
+      if is_object {
+        match self.peek() {
+          Some(Token {
+            kind: TokenKind::Scalar,
+            text,
+            ..
+          }) if text.starts_with('"') => {
+            let text = *text;
+            self.position += 1;
+            self.write(text);
+          }
+          _ => return Err(self.unexpected()),
+        }
+        let comments = self.take_comments();
+        self.write_comments(comments);
+        self.expect(':')?;
+        self.write(": ");
+      }
+      self.value()?;
+
+      // Comments may come before or after the comma; the comma is written
+      // right after the value, unless it's a trailing one.
+      let mut comments = self.take_comments();
+      let has_comma = self.peek_punctuation() == Some(',');
+      if has_comma {
+        self.position += 1;
+        comments.extend(self.take_comments());
+      }
+      let is_last = self.peek_punctuation() == Some(close);
+      if has_comma && !is_last {
+        self.write(",");
+      }
+      self.write_comments(comments);
+      if !has_comma && !is_last {
+        return Err(self.unexpected());
+      }
+    }
+    self.expect(close)?;
+    self.indent -= 1;
+    if is_empty {
+      self.write(&close.to_string());
+    } else {
+      self.newline();
+      self.out.push(close);
+    }
+    Ok(())
+  }
+}
+
+/// Formats JSON(C) source code. Errors with a message saying where the
+/// source isn't valid.
+pub fn format_json(source: &str) -> Result<String, String> {
+  let mut printer = Printer {
+    tokens: tokenize(source)?,
+    position: 0,
+    out: String::new(),
+    indent: 0,
+    need_newline: false,
+  };
+  if printer.peek().is_none() {
+    return Ok(String::new());
+  }
+  printer.value()?;
+  let comments = printer.take_comments();
+  printer.write_comments(comments);
+  if printer.peek().is_some() {
+    return Err(printer.unexpected());
+  }
+  printer.out.push('\n');
+  Ok(printer.out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn format_json_layout() {
+    let source = r#"{"a":1,"b":[true,null, "x\"y"],"c":{},"d":[ ]}"#;
+    assert_eq!(
+      format_json(source).unwrap(),
+      r#"{
+  "a": 1,
+  "b": [
+    true,
+    null,
+    "x\"y"
+  ],
+  "c": {},
+  "d": []
+}
+"#
+    );
+  }
+
+  #[test]
+  fn format_jsonc_comments_and_trailing_commas() {
+    let source = r#"// config
+{
+  /* options */
+  "a": 1, // one
+  "b": [2,],
+}"#;
+    assert_eq!(
+      format_json(source).unwrap(),
+      r#"// config
+{
+  /* options */
+  "a": 1, // one
+  "b": [
+    2
+  ]
+}
+"#
+    );
+  }
+
+  #[test]
+  fn format_json_errors() {
+    assert_eq!(
+      format_json("{\"a\": 1 \"b\": 2}").unwrap_err(),
+      "Unexpected '\"b\"' at line 1"
+    );
+    assert_eq!(
+      format_json("[1,\n2").unwrap_err(),
+      "Unexpected end of input"
+    );
+    assert!(format_json("{\"a\": \"b}").is_err());
+  }
+}
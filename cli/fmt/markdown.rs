@@ -0,0 +1,198 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Formats the JavaScript/TypeScript code blocks of Markdown documents. The
+//! rest of the document is left as it is.
+
+use dprint_plugin_typescript as dprint;
+use std::path::PathBuf;
+
+const IGNORE_COMMENT: &str = "<!-- deno-fmt-ignore -->";
+
+/// File name the code of a block is formatted as, by its info string.
+fn code_file_name(info: &str) -> Option<&'static str> {
+  let lang = info.split_whitespace().next()?;
+  match lang.to_lowercase().as_str() {
+    "ts" | "typescript" => Some("_code.ts"),
+    "tsx" => Some("_code.tsx"),
+    "js" | "javascript" => Some("_code.js"),
+    "jsx" => Some("_code.jsx"),
+    _ => None,
+  }
+}
+
+/// An opening code fence: its indentation, and its fence characters.
+fn parse_fence(line: &str) -> Option<(usize, &str, &str)> {
+  let trimmed = line.trim_start();
+  let indent = line.len() - trimmed.len();
+  let fence_char = trimmed.chars().next()?;
+  if indent > 3 || (fence_char != '`' && fence_char != '~') {
+    return None;
+  }
+  let fence_len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+  if fence_len < 3 {
+    return None;
+  }
+  let (fence, info) = trimmed.split_at(fence_len);
+  Some((indent, fence, info.trim()))
+}
+
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+  let trimmed = line.trim();
+  let fence_char = fence.chars().next().unwrap();
+  trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == fence_char)
+}
+
+/// Formats the code blocks of `source`. Blocks that don't parse, or that
+/// follow a `<!-- deno-fmt-ignore -->` line, are left as they are.
+pub fn format_markdown(source: &str, formatter: &dprint::Formatter) -> String {
+  let lines: Vec<&str> = source.split('\n').collect();
+  let mut out: Vec<String> = vec![];
+  let mut i = 0;
+  while i < lines.len() {
+    let line = lines[i];
+    out.push(line.to_string());
+    i += 1;
+    let (indent, fence, info) = match parse_fence(line) {
+      Some(fence) => fence,
+      None => continue,
+    };
+    let end =
+      match (i..lines.len()).find(|j| is_closing_fence(lines[*j], fence)) {
+        Some(end) => end,
+        None => {
+          // An unclosed block runs to the end of the document.
+          out.extend(lines[i..].iter().map(|line| line.to_string()));
+          break;
+        }
+      };
+    let code_lines = &lines[i..end];
+
+    let ignored = out
+      .iter()
+      .rev()
+      .skip(1)
+      .find(|line| !line.trim().is_empty())
+      .map_or(false, |line| line.trim() == IGNORE_COMMENT);
+    let formatted = match code_file_name(info) {
+      Some(file_name) if !ignored => {
+        format_code(code_lines, indent, file_name, formatter)
+      }
+      _ => None,
+    };
+    match formatted {
+      Some(formatted) => out.extend(formatted),
+      None => out.extend(code_lines.iter().map(|line| line.to_string())),
+    }
+    out.push(lines[end].to_string());
+    i = end + 1;
+  }
+  out.join("\n")
+}
+
+/// Formats the lines of a code block, whose fence is indented by `indent`.
+fn format_code(
+  lines: &[&str],
+  indent: usize,
+  file_name: &str,
+  formatter: &dprint::Formatter,
+) -> Option<Vec<String>> {
+  if lines.is_empty() {
+    return None;
+  }
+  let code = lines
+    .iter()
+    .map(|line| {
+      let line_indent = line.len() - line.trim_start_matches(' ').len();
+      &line[line_indent.min(indent)..]
+    })
+    .collect::<Vec<&str>>()
+    .join("\n");
+  let formatted = formatter
+    .format_text(&PathBuf::from(file_name), &code)
+    .ok()?;
+  let prefix = " ".repeat(indent);
+  Some(
+    formatted
+      .trim_end_matches('\n')
+      .split('\n')
+      .map(|line| {
+        if line.is_empty() {
+          String::new()
+        } else {
+          format!("{}{}", prefix, line)
+        }
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fmt::get_config;
+
+  #[test]
+  fn format_code_blocks() {
+    let formatter = dprint::Formatter::new(get_config());
+    let source = r#"# Title
+
+Some   prose, left as it is.
+
+```ts
+const a = {b:1}
+```
+
+- A list:
+
+  ```js
+  function f( ){return 1}
+  ```
+
+<!-- deno-fmt-ignore -->
+```ts
+const c = [1,0,
+           0,1];
+```
+
+```sh
+ls   -la
+```
+"#;
+    assert_eq!(
+      format_markdown(source, &formatter),
+      r#"# Title
+
+Some   prose, left as it is.
+
+```ts
+const a = { b: 1 };
+```
+
+- A list:
+
+  ```js
+  function f() {
+    return 1;
+  }
+  ```
+
+<!-- deno-fmt-ignore -->
+```ts
+const c = [1,0,
+           0,1];
+```
+
+```sh
+ls   -la
+```
+"#
+    );
+  }
+
+  #[test]
+  fn keeps_blocks_that_do_not_parse() {
+    let formatter = dprint::Formatter::new(get_config());
+    let source = "```ts\nconst = ;\n```\n";
+    assert_eq!(format_markdown(source, &formatter), source);
+  }
+}
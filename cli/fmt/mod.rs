@@ -6,9 +6,23 @@
 //! At the moment it is only consumed using CLI but in
 //! the future it can be easily extended to provide
 //! the same functions as ops available in JS runtime.
+//!
+//! Besides JavaScript/TypeScript files, the code blocks of Markdown files and
+//! JSON(C) files are formatted, unless turned off in the `fmt` section of
+//! `deno.json`:
+//!
+//! ```json
+//! {
+//!   "fmt": { "markdown": false, "json": false }
+//! }
+//! ```
+
+mod json;
+mod markdown;
 
-use crate::fs::files_in_subtree;
+use crate::file_collector::FileCollector;
 use crate::op_error::OpError;
+use crate::task_runner;
 use deno_core::ErrBox;
 use dprint_plugin_typescript as dprint;
 use std::fs;
@@ -21,32 +35,93 @@ use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-/// Format JavaScript/TypeScript files.
+/// The `fmt` section of the config file: which file types besides
+/// JavaScript/TypeScript are formatted.
+#[derive(Debug, PartialEq)]
+struct FmtConfig {
+  markdown: bool,
+  json: bool,
+}
+
+impl Default for FmtConfig {
+  fn default() -> Self {
+    FmtConfig {
+      markdown: true,
+      json: true,
+    }
+  }
+}
+
+impl FmtConfig {
+  fn read(config_path: &Path) -> Result<Self, ErrBox> {
+    let source = fs::read_to_string(config_path)?;
+    let config: serde_json::Value = serde_json::from_str(&source)?;
+    let mut fmt_config = FmtConfig::default();
+    let fmt = match config.get("fmt") {
+      Some(fmt) => fmt,
+      None => return Ok(fmt_config),
+    };
+    if let Some(markdown) = boolean(config_path, fmt, "markdown")? {
+      fmt_config.markdown = markdown;
+    }
+    if let Some(json) = boolean(config_path, fmt, "json")? {
+      fmt_config.json = json;
+    }
+    Ok(fmt_config)
+  }
+
+  fn is_enabled(&self, path: &Path) -> bool {
+    match lowercase_extension(path).as_deref() {
+      Some("md") | Some("markdown") => self.markdown,
+      Some("json") | Some("jsonc") => self.json,
+      _ => true,
+    }
+  }
+}
+
+/// Reads the boolean at `key` of the `fmt` config section.
+fn boolean(
+  config_path: &Path,
+  value: &serde_json::Value,
+  key: &str,
+) -> Result<Option<bool>, ErrBox> {
+  match value.get(key) {
+    None => Ok(None),
+    Some(serde_json::Value::Bool(value)) => Ok(Some(*value)),
+    Some(_) => Err(
+      OpError::type_error(format!(
+        "\"fmt\" entry \"{}\" in {} must be a boolean",
+        key,
+        config_path.display()
+      ))
+      .into(),
+    ),
+  }
+}
+
+/// Format JavaScript/TypeScript files, the code blocks of Markdown files and
+/// JSON files.
 ///
-/// First argument supports globs, and if it is `None`
+/// First argument supports files, directories and globs, and if it is empty
 /// then the current directory is recursively walked.
 pub async fn format(args: Vec<String>, check: bool) -> Result<(), ErrBox> {
   if args.len() == 1 && args[0] == "-" {
     return format_stdin(check);
   }
 
-  let mut target_files: Vec<PathBuf> = vec![];
-
-  if args.is_empty() {
-    target_files.extend(files_in_subtree(
-      std::env::current_dir().unwrap(),
-      is_supported,
-    ));
+  let cwd = std::env::current_dir()?;
+  let fmt_config = match task_runner::find_config(&cwd) {
+    Some(config_path) => FmtConfig::read(&config_path)?,
+    None => FmtConfig::default(),
+  };
+  let include = if args.is_empty() {
+    vec![".".to_string()]
   } else {
-    for arg in args {
-      let p = PathBuf::from(arg);
-      if p.is_dir() {
-        target_files.extend(files_in_subtree(p, is_supported));
-      } else {
-        target_files.push(p);
-      };
-    }
-  }
+    args
+  };
+  let mut target_files =
+    FileCollector::new(is_formattable).collect(&cwd, &include)?;
+  target_files.retain(|file_path| fmt_config.is_enabled(file_path));
   let config = get_config();
   if check {
     check_source_files(config, target_files).await
@@ -67,7 +142,7 @@ async fn check_source_files(
     let not_formatted_files_count = not_formatted_files_count.clone();
     move |file_path| {
       let file_contents = fs::read_to_string(&file_path)?;
-      let r = formatter.format_text(&file_path, &file_contents);
+      let r = format_file(&formatter, &file_path, &file_contents);
       match r {
         Ok(formatted_text) => {
           if formatted_text != file_contents {
@@ -113,7 +188,7 @@ async fn format_source_files(
     let formatted_files_count = formatted_files_count.clone();
     move |file_path| {
       let file_contents = fs::read_to_string(&file_path)?;
-      let r = formatter.format_text(&file_path, &file_contents);
+      let r = format_file(&formatter, &file_path, &file_contents);
       match r {
         Ok(formatted_text) => {
           if formatted_text != file_contents {
@@ -186,18 +261,46 @@ fn files_str(len: usize) -> &'static str {
   }
 }
 
-fn is_supported(path: &Path) -> bool {
-  let lowercase_ext = path
+/// Formats a file by its extension.
+fn format_file(
+  formatter: &dprint::Formatter,
+  file_path: &Path,
+  file_contents: &str,
+) -> Result<String, String> {
+  match lowercase_extension(file_path).as_deref() {
+    Some("md") | Some("markdown") => {
+      Ok(markdown::format_markdown(file_contents, formatter))
+    }
+    Some("json") | Some("jsonc") => json::format_json(file_contents),
+    _ => formatter.format_text(&file_path.to_path_buf(), file_contents),
+  }
+}
+
+fn lowercase_extension(path: &Path) -> Option<String> {
+  path
     .extension()
     .and_then(|e| e.to_str())
-    .map(|e| e.to_lowercase());
-  if let Some(ext) = lowercase_ext {
+    .map(|e| e.to_lowercase())
+}
+
+pub fn is_supported(path: &Path) -> bool {
+  if let Some(ext) = lowercase_extension(path) {
     ext == "ts" || ext == "tsx" || ext == "js" || ext == "jsx"
   } else {
     false
   }
 }
 
+/// Whether `deno fmt` formats the file: JavaScript/TypeScript, Markdown or
+/// JSON.
+fn is_formattable(path: &Path) -> bool {
+  is_supported(path)
+    || match lowercase_extension(path).as_deref() {
+      Some(ext) => ["md", "markdown", "json", "jsonc"].contains(&ext),
+      None => false,
+    }
+}
+
 fn get_config() -> dprint::configuration::Configuration {
   use dprint::configuration::*;
   ConfigurationBuilder::new().deno().build()
@@ -262,6 +365,30 @@ fn test_is_supported() {
   assert!(is_supported(Path::new("foo.JSX")));
 }
 
+#[test]
+fn test_is_formattable() {
+  assert!(is_formattable(Path::new("README.md")));
+  assert!(is_formattable(Path::new("docs/toc.json")));
+  assert!(is_formattable(Path::new("tsconfig.jsonc")));
+  assert!(is_formattable(Path::new("foo.ts")));
+  assert!(!is_formattable(Path::new("tests/subdir/redirects")));
+  assert!(!is_formattable(Path::new("Cargo.toml")));
+}
+
+#[test]
+fn test_fmt_config() {
+  let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+  let config_path = temp_dir.path().join(task_runner::CONFIG_FILE_NAME);
+  fs::write(&config_path, r#"{ "fmt": { "markdown": false } }"#).unwrap();
+  let config = FmtConfig::read(&config_path).unwrap();
+  assert!(!config.is_enabled(Path::new("README.md")));
+  assert!(config.is_enabled(Path::new("deno.json")));
+  assert!(config.is_enabled(Path::new("mod.ts")));
+
+  fs::write(&config_path, r#"{ "fmt": { "json": "no" } }"#).unwrap();
+  assert!(FmtConfig::read(&config_path).is_err());
+}
+
 #[tokio::test]
 async fn check_tests_dir() {
   // Because of cli/tests/error_syntax.js the following should fail but not
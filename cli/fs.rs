@@ -80,6 +80,49 @@ pub fn normalize_path(path: &Path) -> PathBuf {
   ret
 }
 
+/// Resolves symlinks like `fs::canonicalize()`, but also for paths that
+/// don't exist yet, eg. files about to be created: the longest existing
+/// ancestor is canonicalized and the rest of the path is appended to it.
+/// Dangling symlinks are followed, since creating a file through one
+/// creates it at the link's target.
+pub fn canonicalize_path_lenient(path: &Path) -> PathBuf {
+  canonicalize_path_lenient_inner(&normalize_path(path), 0)
+}
+
+/// Same limit as Linux' ELOOP, to stop at symlink cycles.
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+fn canonicalize_path_lenient_inner(path: &Path, depth: usize) -> PathBuf {
+  let mut ancestor = path;
+  let mut names = Vec::new();
+  loop {
+    let resolved = if let Ok(canonical) = ancestor.canonicalize() {
+      Some(canonical)
+    } else if depth < MAX_SYMLINK_DEPTH {
+      std::fs::read_link(ancestor).ok().map(|target| {
+        let target = match ancestor.parent() {
+          Some(parent) => parent.join(target),
+          None => target,
+        };
+        canonicalize_path_lenient_inner(&normalize_path(&target), depth + 1)
+      })
+    } else {
+      None
+    };
+    if let Some(mut resolved) = resolved {
+      resolved.extend(names.iter().rev());
+      return resolved;
+    }
+    match (ancestor.parent(), ancestor.file_name()) {
+      (Some(parent), Some(name)) => {
+        names.push(name);
+        ancestor = parent;
+      }
+      _ => return path.to_path_buf(),
+    }
+  }
+}
+
 pub fn resolve_from_cwd(path: &Path) -> Result<PathBuf, ErrBox> {
   let resolved_path = if path.is_absolute() {
     path.to_owned()
@@ -130,6 +173,41 @@ mod tests {
     }
   }
 
+  #[test]
+  fn canonicalize_path_lenient_missing() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let dir = temp_dir.path().canonicalize().unwrap();
+    assert_eq!(
+      canonicalize_path_lenient(&dir.join("a/../b/c.txt")),
+      dir.join("b/c.txt")
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn canonicalize_path_lenient_symlinks() {
+    use std::os::unix::fs::symlink;
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let dir = temp_dir.path().canonicalize().unwrap();
+    std::fs::create_dir(dir.join("target")).unwrap();
+    symlink(dir.join("target"), dir.join("link")).unwrap();
+    symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+    symlink(dir.join("cycle"), dir.join("cycle")).unwrap();
+
+    assert_eq!(
+      canonicalize_path_lenient(&dir.join("link/new.txt")),
+      dir.join("target/new.txt")
+    );
+    assert_eq!(
+      canonicalize_path_lenient(&dir.join("dangling")),
+      dir.join("missing")
+    );
+    assert_eq!(
+      canonicalize_path_lenient(&dir.join("cycle/a")),
+      dir.join("cycle/a")
+    );
+  }
+
   // TODO: Get a good expected value here for Windows.
   #[cfg(not(windows))]
   #[test]
@@ -139,16 +217,13 @@ mod tests {
   }
 }
 
-pub fn files_in_subtree<F>(root: PathBuf, filter: F) -> Vec<PathBuf>
-where
-  F: Fn(&Path) -> bool,
-{
-  assert!(root.is_dir());
-
+/// Total size in bytes of the files in the subtree, 0 if `root` doesn't exist.
+pub fn dir_size(root: &Path) -> u64 {
   WalkDir::new(root)
     .into_iter()
     .filter_map(|e| e.ok())
-    .map(|e| e.path().to_owned())
-    .filter(|p| if p.is_dir() { false } else { filter(&p) })
-    .collect()
+    .filter(|e| e.file_type().is_file())
+    .filter_map(|e| e.metadata().ok())
+    .map(|m| m.len())
+    .sum()
 }
@@ -1,14 +1,25 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::blob_store::BlobStore;
+use crate::broadcast_channel::BroadcastChannels;
+use crate::cache_storage::CacheStorage;
 use crate::deno_dir;
 use crate::file_fetcher::SourceFileFetcher;
 use crate::flags;
 use crate::http_cache;
+use crate::kv::KvStores;
 use crate::lockfile::Lockfile;
 use crate::msg;
+use crate::npm;
+use crate::performance::Performance;
 use crate::permissions::Permissions;
+use crate::startup_timing::Phase;
+use crate::startup_timing::StartupTiming;
+use crate::test_reporter::TestCollector;
+use crate::trace_events::TraceEvents;
 use crate::tsc::CompiledModule;
 use crate::tsc::TargetLib;
 use crate::tsc::TsCompiler;
+use crate::web_storage::WebStorage;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
 use std::env;
@@ -16,6 +27,7 @@ use std::ops::Deref;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Instant;
 use tokio::sync::Mutex as AsyncMutex;
 
 /// Holds state of the program and can be accessed by V8 isolate.
@@ -35,6 +47,23 @@ pub struct GlobalStateInner {
   pub ts_compiler: TsCompiler,
   pub lockfile: Option<Mutex<Lockfile>>,
   pub compiler_starts: AtomicUsize,
+  /// Message bus for `BroadcastChannel`, shared by all isolates.
+  pub broadcast_channels: BroadcastChannels,
+  /// Areas of `localStorage` and `sessionStorage`.
+  pub web_storage: WebStorage,
+  /// Caches of the `caches` Web API.
+  pub cache_storage: CacheStorage,
+  /// Databases opened with `Deno.openKv()`.
+  pub kv_stores: KvStores,
+  /// Blobs of object URLs.
+  pub blob_store: BlobStore,
+  /// Receives results of `Deno.test()` cases when run by `deno test`.
+  pub test_collector: Mutex<Option<TestCollector>>,
+  pub startup_timing: StartupTiming,
+  /// Time origins and User Timing entries of all isolates.
+  pub performance: Performance,
+  /// Recorded with `--trace-ops`.
+  pub trace_events: Option<Arc<TraceEvents>>,
   compile_lock: AsyncMutex<()>,
 }
 
@@ -47,18 +76,28 @@ impl Deref for GlobalState {
 
 impl GlobalState {
   pub fn new(flags: flags::Flags) -> Result<Self, ErrBox> {
+    let startup_timing = StartupTiming::default();
+    let performance = Performance::default();
     let custom_root = env::var("DENO_DIR").map(String::into).ok();
     let dir = deno_dir::DenoDir::new(custom_root)?;
     let deps_cache_location = dir.root.join("deps");
     let http_cache = http_cache::HttpCache::new(&deps_cache_location);
     http_cache.ensure_location()?;
 
+    let permissions = Permissions::from_flags(&flags);
+    let npm_cache =
+      npm::NpmCache::new(&dir.root.join("npm"), permissions.clone());
+
+    let blob_store = BlobStore::default();
     let file_fetcher = SourceFileFetcher::new(
       http_cache,
+      npm_cache,
+      blob_store.clone(),
       !flags.reload,
       flags.cache_blacklist.clone(),
       flags.no_remote,
       flags.cached_only,
+      flags.compat,
       flags.ca_file.clone(),
     )?;
 
@@ -67,6 +106,8 @@ impl GlobalState {
       dir.gen_cache.clone(),
       !flags.reload,
       flags.config_path.clone(),
+      flags.source_maps.clone(),
+      &flags.defines,
     )?;
 
     // Note: reads lazily from disk on first call to lockfile.check()
@@ -76,14 +117,31 @@ impl GlobalState {
       None
     };
 
+    let web_storage = WebStorage::new(dir.root.join("web_storage"));
+    let cache_storage = CacheStorage::new(dir.root.join("cache_storage"));
+
+    let trace_events = flags
+      .trace_ops_file
+      .as_ref()
+      .map(|_| Arc::new(TraceEvents::default()));
+
     let inner = GlobalStateInner {
       dir,
-      permissions: Permissions::from_flags(&flags),
+      permissions,
       flags,
       file_fetcher,
       ts_compiler,
       lockfile,
       compiler_starts: AtomicUsize::new(0),
+      broadcast_channels: BroadcastChannels::default(),
+      web_storage,
+      cache_storage,
+      kv_stores: KvStores::default(),
+      blob_store,
+      test_collector: Mutex::new(None),
+      startup_timing,
+      performance,
+      trace_events,
       compile_lock: AsyncMutex::new(()),
     };
 
@@ -100,15 +158,22 @@ impl GlobalState {
     let state2 = self.clone();
     let module_specifier = module_specifier.clone();
 
+    let fetch_start = Instant::now();
     let out = self
       .file_fetcher
       .fetch_source_file(&module_specifier, maybe_referrer)
-      .await?;
+      .await;
+    self
+      .startup_timing
+      .record(Phase::Fetch, fetch_start.elapsed());
+    self.trace_module("fetch", &module_specifier, fetch_start);
+    let out = out?;
 
     // TODO(ry) Try to lift compile_lock as high up in the call stack for
     // sanity.
     let compile_lock = self.compile_lock.lock().await;
 
+    let compile_start = Instant::now();
     let compiled_module = match out.media_type {
       msg::MediaType::TypeScript
       | msg::MediaType::TSX
@@ -147,8 +212,13 @@ impl GlobalState {
         code: String::from_utf8(out.source_code)?,
         name: out.url.to_string(),
       }),
-    }?;
+    };
     drop(compile_lock);
+    self
+      .startup_timing
+      .record(Phase::Compile, compile_start.elapsed());
+    self.trace_module("compile", &module_specifier, compile_start);
+    let compiled_module = compiled_module?;
 
     if let Some(ref lockfile) = state2.lockfile {
       let mut g = lockfile.lock().unwrap();
@@ -171,6 +241,19 @@ impl GlobalState {
     Ok(compiled_module)
   }
 
+  /// Records a phase of loading a module with `--trace-ops`.
+  fn trace_module(
+    &self,
+    phase: &str,
+    module_specifier: &ModuleSpecifier,
+    start: Instant,
+  ) {
+    if let Some(trace_events) = &self.trace_events {
+      let args = json!({ "specifier": module_specifier.to_string() });
+      trace_events.async_span("module", phase, start, args);
+    }
+  }
+
   #[cfg(test)]
   pub fn mock(argv: Vec<String>) -> GlobalState {
     GlobalState::new(flags::Flags {
@@ -14,6 +14,8 @@ use std::fs::File;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use url::Url;
 
 /// Turn base of url (scheme, hostname, port) into a valid filename.
@@ -74,10 +76,22 @@ pub struct HttpCache {
   pub location: PathBuf,
 }
 
+/// Seconds since the UNIX epoch.
+fn now_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Metadata {
   pub headers: HeadersMap,
   pub url: String,
+  /// When the response was received or last revalidated, in seconds since
+  /// the UNIX epoch. Missing from metadata written by older versions.
+  #[serde(default)]
+  pub date: Option<u64>,
 }
 
 impl Metadata {
@@ -99,6 +113,31 @@ impl Metadata {
   pub fn filename(cache_filename: &Path) -> PathBuf {
     cache_filename.with_extension("metadata.json")
   }
+
+  /// Whether the cached response can be used without revalidating it with
+  /// the server, according to its `Cache-Control` header. Responses without
+  /// `max-age` or `no-cache`, or without a date, stay fresh until `--reload`.
+  pub fn is_fresh(&self, now: u64) -> bool {
+    let cache_control = match self.headers.get("cache-control") {
+      Some(cache_control) => cache_control.to_lowercase(),
+      None => return true,
+    };
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+      match directive {
+        "no-cache" | "no-store" => return false,
+        "immutable" => return true,
+        _ if directive.starts_with("max-age=") => {
+          max_age = directive["max-age=".len()..].parse::<u64>().ok();
+        }
+        _ => {}
+      }
+    }
+    match (max_age, self.date) {
+      (Some(max_age), Some(date)) => now.saturating_sub(date) < max_age,
+      _ => true,
+    }
+  }
 }
 
 impl HttpCache {
@@ -167,9 +206,31 @@ impl HttpCache {
     let metadata = Metadata {
       url: url.to_string(),
       headers: headers_map,
+      date: Some(now_secs()),
     };
     metadata.write(&cache_filename)
   }
+
+  /// Whether the cached response for `url` can be used without revalidating
+  /// it. Missing entries are considered fresh, there's nothing to revalidate.
+  pub fn is_fresh(&self, url: &Url) -> bool {
+    self
+      .get_metadata(url)
+      .map_or(true, |metadata| metadata.is_fresh(now_secs()))
+  }
+
+  /// Restarts the freshness lifetime of a cached response after the server
+  /// confirmed it's still current.
+  pub fn mark_revalidated(&self, url: &Url) -> Result<(), ErrBox> {
+    let mut metadata = self.get_metadata(url)?;
+    // Freshness doesn't depend on the date without `Cache-Control`, so
+    // there's no need to touch the file.
+    if !metadata.headers.contains_key("cache-control") {
+      return Ok(());
+    }
+    metadata.date = Some(now_secs());
+    metadata.write(&self.get_cache_filename(url))
+  }
 }
 
 #[cfg(test)]
@@ -219,6 +280,29 @@ mod tests {
     drop(dir);
   }
 
+  #[test]
+  fn test_metadata_is_fresh() {
+    let metadata = |cache_control: Option<&str>, date: Option<u64>| {
+      let mut headers = HashMap::new();
+      if let Some(cache_control) = cache_control {
+        headers.insert("cache-control".to_string(), cache_control.to_string());
+      }
+      Metadata {
+        headers,
+        url: "https://deno.land/x/mod.ts".to_string(),
+        date,
+      }
+    };
+
+    assert!(metadata(None, Some(0)).is_fresh(1000));
+    assert!(metadata(Some("public, max-age=600"), Some(500)).is_fresh(1000));
+    assert!(!metadata(Some("public, max-age=600"), Some(100)).is_fresh(1000));
+    assert!(metadata(Some("max-age=0, immutable"), Some(0)).is_fresh(1000));
+    assert!(!metadata(Some("no-cache"), Some(1000)).is_fresh(1000));
+    // Cached by an older version, without a date.
+    assert!(metadata(Some("max-age=600"), None).is_fresh(1000));
+  }
+
   #[test]
   fn test_url_to_filename() {
     let test_cases = [
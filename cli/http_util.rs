@@ -1,10 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::compression::Transformer;
 use crate::version;
 use bytes::Bytes;
 use deno_core::ErrBox;
 use futures::future::FutureExt;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderValue;
+use reqwest::header::IF_MODIFIED_SINCE;
 use reqwest::header::IF_NONE_MATCH;
 use reqwest::header::LOCATION;
 use reqwest::header::USER_AGENT;
@@ -21,12 +23,38 @@ use std::io::Read;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 use tokio::io::AsyncRead;
 use url::Url;
 
+/// Connection pool settings for `create_http_client_with_options`. Unset
+/// fields keep reqwest's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct HttpClientOptions {
+  pub ca_file: Option<String>,
+  /// Maximum number of idle keep-alive connections kept per host.
+  pub pool_max_idle_per_host: Option<usize>,
+  /// How long an idle connection is kept in the pool.
+  pub pool_idle_timeout: Option<Duration>,
+  /// Speak HTTP/2 without negotiating it first, also over plain `http:`.
+  pub http2_prior_knowledge: bool,
+}
+
 /// Create new instance of async reqwest::Client. This client supports
 /// proxies and doesn't follow redirects.
 pub fn create_http_client(ca_file: Option<String>) -> Result<Client, ErrBox> {
+  create_http_client_with_options(HttpClientOptions {
+    ca_file,
+    ..Default::default()
+  })
+}
+
+/// Like `create_http_client`, with control over connection pooling. The
+/// returned client keeps connections alive between requests and negotiates
+/// HTTP/2 over TLS through ALPN.
+pub fn create_http_client_with_options(
+  options: HttpClientOptions,
+) -> Result<Client, ErrBox> {
   let mut headers = HeaderMap::new();
   headers.insert(
     USER_AGENT,
@@ -37,13 +65,23 @@ pub fn create_http_client(ca_file: Option<String>) -> Result<Client, ErrBox> {
     .default_headers(headers)
     .use_rustls_tls();
 
-  if let Some(ca_file) = ca_file {
+  if let Some(ca_file) = options.ca_file {
     let mut buf = Vec::new();
     File::open(ca_file)?.read_to_end(&mut buf)?;
     let cert = reqwest::Certificate::from_pem(&buf)?;
     builder = builder.add_root_certificate(cert);
   }
 
+  if let Some(max_idle) = options.pool_max_idle_per_host {
+    builder = builder.pool_max_idle_per_host(max_idle);
+  }
+  if let Some(timeout) = options.pool_idle_timeout {
+    builder = builder.pool_idle_timeout(timeout);
+  }
+  if options.http2_prior_knowledge {
+    builder = builder.http2_prior_knowledge();
+  }
+
   builder.build().map_err(|_| {
     ErrBox::from(io::Error::new(
       io::ErrorKind::Other,
@@ -95,10 +133,13 @@ pub enum FetchOnceResult {
 /// yields Code(ResultPayload).
 /// If redirect occurs, does not follow and
 /// yields Redirect(url).
+/// If `ETag` or `Last-Modified` of a cached response are given, the request
+/// is conditional and yields NotModified if the cached response is current.
 pub fn fetch_once(
   client: Client,
   url: &Url,
   cached_etag: Option<String>,
+  cached_last_modified: Option<String>,
 ) -> impl Future<Output = Result<FetchOnceResult, ErrBox>> {
   let url = url.clone();
 
@@ -109,6 +150,11 @@ pub fn fetch_once(
       let if_none_match_val = HeaderValue::from_str(&etag).unwrap();
       request = request.header(IF_NONE_MATCH, if_none_match_val);
     }
+    if let Some(last_modified) = cached_last_modified {
+      if let Ok(if_modified_since_val) = HeaderValue::from_str(&last_modified) {
+        request = request.header(IF_MODIFIED_SINCE, if_modified_since_val);
+      }
+    }
     let response = request.send().await?;
 
     if response.status() == StatusCode::NOT_MODIFIED {
@@ -119,11 +165,7 @@ pub fn fetch_once(
     let headers = response.headers();
 
     if let Some(warning) = headers.get("X-Deno-Warning") {
-      eprintln!(
-        "{} {}",
-        crate::colors::yellow("Warning".to_string()),
-        warning.to_str().unwrap()
-      );
+      warn!("{}", warning.to_str().unwrap());
     }
 
     for key in headers.keys() {
@@ -174,6 +216,11 @@ pub struct HttpBody {
   response: Response,
   chunk: Option<Bytes>,
   pos: usize,
+  /// Decodes a `Content-Encoding` that reqwest leaves alone.
+  decoder: Option<Transformer>,
+  /// Whether the decoder has seen any input; empty bodies, such as those of
+  /// `HEAD` responses, are passed through.
+  decoder_fed: bool,
 }
 
 impl HttpBody {
@@ -182,8 +229,30 @@ impl HttpBody {
       response: body,
       chunk: None,
       pos: 0,
+      decoder: None,
+      decoder_fed: false,
+    }
+  }
+
+  /// Like `from`, but decompresses the body with `decoder`.
+  pub fn with_decoder(body: Response, decoder: Transformer) -> Self {
+    Self {
+      decoder: Some(decoder),
+      ..Self::from(body)
     }
   }
+
+  /// Copies as much of `chunk` as fits into `buf`, keeping the rest for the
+  /// next read.
+  fn copy_chunk(&mut self, chunk: Bytes, buf: &mut [u8]) -> usize {
+    let n = min(buf.len(), chunk.len());
+    buf[..n].copy_from_slice(&chunk[..n]);
+    if buf.len() < chunk.len() {
+      self.pos = n;
+      self.chunk = Some(chunk);
+    }
+    n
+  }
 }
 
 impl AsyncRead for HttpBody {
@@ -216,29 +285,44 @@ impl AsyncRead for HttpBody {
       assert_eq!(inner.pos, 0);
     }
 
-    let chunk_future = inner.response.chunk();
-    futures::pin_mut!(chunk_future);
-
-    let result = match futures::ready!(chunk_future.poll(cx)) {
-      Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
-      Ok(Some(chunk)) => {
-        debug!(
-          "HttpBody Real Read buf {} chunk {} pos {}",
-          buf.len(),
-          chunk.len(),
-          inner.pos
-        );
-        let n = min(buf.len(), chunk.len());
-        buf[..n].copy_from_slice(&chunk[..n]);
-        if buf.len() < chunk.len() {
-          inner.pos = n;
-          inner.chunk = Some(chunk);
+    loop {
+      let result = {
+        let chunk_future = inner.response.chunk();
+        futures::pin_mut!(chunk_future);
+        futures::ready!(chunk_future.poll(cx))
+      };
+
+      let chunk = match result {
+        Err(e) => {
+          return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)))
         }
-        Ok(n)
+        Ok(Some(chunk)) => match inner.decoder.as_mut() {
+          Some(decoder) => {
+            inner.decoder_fed = true;
+            Bytes::from(decoder.write(&chunk)?)
+          }
+          None => chunk,
+        },
+        Ok(None) => match inner.decoder.take() {
+          Some(mut decoder) if inner.decoder_fed => {
+            Bytes::from(decoder.finish()?)
+          }
+          Some(_) => Bytes::new(),
+          None => return Poll::Ready(Ok(0)),
+        },
+      };
+      // The decoder may need more input before it produces any output.
+      if chunk.is_empty() && inner.decoder.is_some() {
+        continue;
       }
-      Ok(None) => Ok(0),
-    };
-    result.into()
+      debug!(
+        "HttpBody Real Read buf {} chunk {} pos {}",
+        buf.len(),
+        chunk.len(),
+        inner.pos
+      );
+      return Poll::Ready(Ok(inner.copy_chunk(chunk, buf)));
+    }
   }
 }
 
@@ -253,7 +337,7 @@ mod tests {
     let url =
       Url::parse("http://127.0.0.1:4545/cli/tests/fixture.json").unwrap();
     let client = create_http_client(None).unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(headers.get("content-type").unwrap(), "application/json");
@@ -274,7 +358,7 @@ mod tests {
     )
     .unwrap();
     let client = create_http_client(None).unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('gzip')");
       assert_eq!(
@@ -294,7 +378,7 @@ mod tests {
     let http_server_guard = crate::test_util::http_server();
     let url = Url::parse("http://127.0.0.1:4545/etag_script.ts").unwrap();
     let client = create_http_client(None).unwrap();
-    let result = fetch_once(client.clone(), &url, None).await;
+    let result = fetch_once(client.clone(), &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('etag')");
@@ -308,7 +392,8 @@ mod tests {
     }
 
     let res =
-      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string())).await;
+      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string()), None)
+        .await;
     assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
 
     drop(http_server_guard);
@@ -323,7 +408,7 @@ mod tests {
     )
     .unwrap();
     let client = create_http_client(None).unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('brotli');");
@@ -349,7 +434,7 @@ mod tests {
     let target_url =
       Url::parse("http://localhost:4545/cli/tests/fixture.json").unwrap();
     let client = create_http_client(None).unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Redirect(url, _)) = result {
       assert_eq!(url, target_url);
     } else {
@@ -410,7 +495,7 @@ mod tests {
         .unwrap(),
     )))
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(headers.get("content-type").unwrap(), "application/json");
@@ -437,7 +522,7 @@ mod tests {
         .unwrap(),
     )))
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('gzip')");
       assert_eq!(
@@ -463,7 +548,7 @@ mod tests {
         .unwrap(),
     )))
     .unwrap();
-    let result = fetch_once(client.clone(), &url, None).await;
+    let result = fetch_once(client.clone(), &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('etag')");
@@ -478,7 +563,8 @@ mod tests {
     }
 
     let res =
-      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string())).await;
+      fetch_once(client, &url, Some("33a64df551425fcc55e".to_string()), None)
+        .await;
     assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
 
     drop(http_server_guard);
@@ -499,7 +585,7 @@ mod tests {
         .unwrap(),
     )))
     .unwrap();
-    let result = fetch_once(client, &url, None).await;
+    let result = fetch_once(client, &url, None, None).await;
     if let Ok(FetchOnceResult::Code(body, headers)) = result {
       assert!(!body.is_empty());
       assert_eq!(String::from_utf8(body).unwrap(), "console.log('brotli');");
@@ -188,7 +188,7 @@ impl ImportMap {
 
       let url_string = url.to_string();
       if specifier_key.ends_with('/') && !url_string.ends_with('/') {
-        eprintln!(
+        warn!(
           "Invalid target address {:?} for package specifier {:?}.\
            Package address targets must end with \"/\".",
           url_string, specifier_key
@@ -291,7 +291,7 @@ impl ImportMap {
         match Url::parse(base_url).unwrap().join(scope_prefix) {
           Ok(url) => {
             if !SUPPORTED_FETCH_SCHEMES.contains(&url.scheme()) {
-              eprintln!(
+              warn!(
               "Invalid scope {:?}. Scope URLs must have a valid fetch scheme.",
               url.to_string()
             );
@@ -4,6 +4,7 @@
 //! https://chromedevtools.github.io/devtools-protocol/
 //! https://hyperandroid.com/2020/02/12/v8-inspector-from-an-embedder-standpoint/
 
+use crate::op_error::OpError;
 use core::convert::Infallible as Never; // Alias for the future `!` type.
 use deno_core::v8;
 use futures::channel::mpsc;
@@ -17,6 +18,7 @@ use futures::stream::FuturesUnordered;
 use futures::task;
 use futures::task::Context;
 use futures::task::Poll;
+use serde_json::Value;
 use std::cell::BorrowMutError;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -31,6 +33,7 @@ use std::pin::Pin;
 use std::process;
 use std::ptr;
 use std::ptr::NonNull;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::Once;
@@ -301,9 +304,11 @@ pub struct DenoInspector {
   v8_inspector: v8::UniqueRef<v8::inspector::V8Inspector>,
   sessions: RefCell<InspectorSessions>,
   flags: RefCell<InspectorFlags>,
+  local_session: LocalInspectorSession,
   waker: Arc<InspectorWaker>,
   _canary_tx: oneshot::Sender<Never>,
-  pub debugger_url: String,
+  /// Unset if the inspector isn't registered with the inspector server.
+  pub debugger_url: Option<String>,
 }
 
 impl Deref for DenoInspector {
@@ -325,6 +330,7 @@ impl Drop for DenoInspector {
     // Set the poll state to 'dropped' so it doesn't attempt to request an
     // interrupt from the isolate.
     self.waker.update(|w| w.poll_state = PollState::Dropped);
+    self.local_session.close();
     // V8 automatically deletes all sessions when an Inspector instance is
     // deleted, however InspectorSession also has a drop handler that cleans
     // up after itself. To avoid a double free, make sure the inspector is
@@ -371,10 +377,11 @@ impl Future for DenoInspector {
 impl DenoInspector {
   const CONTEXT_GROUP_ID: i32 = 1;
 
+  /// Creates the inspector of `isolate`. Debugger front-ends can connect to
+  /// it at `host`; without a host, it's only used through its local session.
   pub fn new(
     isolate: &mut deno_core::CoreIsolate,
-    host: SocketAddr,
-    wait_for_debugger: bool,
+    host: Option<SocketAddr>,
   ) -> Box<Self> {
     let deno_core::CoreIsolate {
       v8_isolate,
@@ -390,13 +397,13 @@ impl DenoInspector {
       mpsc::unbounded::<WebSocketProxy>();
     let (canary_tx, canary_rx) = oneshot::channel::<Never>();
 
-    let info = InspectorInfo {
+    let info = host.map(|host| InspectorInfo {
       host,
       uuid: Uuid::new_v4(),
       thread_name: thread::current().name().map(|n| n.to_owned()),
       new_websocket_tx,
       canary_rx,
-    };
+    });
 
     // Create DenoInspector instance.
     let mut self_ = new_box_with(|self_ptr| {
@@ -406,7 +413,7 @@ impl DenoInspector {
         v8::inspector::V8Inspector::create(scope, unsafe { &mut *self_ptr });
 
       let sessions = InspectorSessions::new(self_ptr, new_websocket_rx);
-      let flags = InspectorFlags::new(wait_for_debugger);
+      let flags = RefCell::new(InspectorFlags::default());
       let waker = InspectorWaker::new(scope.isolate().thread_safe_handle());
 
       Self {
@@ -414,9 +421,10 @@ impl DenoInspector {
         v8_inspector,
         sessions,
         flags,
+        local_session: LocalInspectorSession::default(),
         waker,
         _canary_tx: canary_tx,
-        debugger_url: info.get_websocket_debugger_url(),
+        debugger_url: info.as_ref().map(|i| i.get_websocket_debugger_url()),
       }
     });
 
@@ -425,11 +433,13 @@ impl DenoInspector {
     let context_name = v8::inspector::StringView::from(&b"global context"[..]);
     self_.context_created(context, Self::CONTEXT_GROUP_ID, context_name);
 
+    let local_session = LocalSession::new(&mut self_);
+    self_.local_session.0.borrow_mut().replace(local_session);
+
     // Register this inspector with the server thread.
-    // Note: poll_sessions() might block if we need to wait for a
-    // debugger front-end to connect. Therefore the server thread must to be
-    // nofified *before* polling.
-    InspectorServer::register_inspector(info);
+    if let Some(info) = info {
+      InspectorServer::register_inspector(info);
+    }
 
     // Poll the session handler so we will get notified whenever there is
     // new_incoming debugger activity.
@@ -438,6 +448,27 @@ impl DenoInspector {
     self_
   }
 
+  /// Returns the session that DevTools protocol messages can be posted to
+  /// from Rust, whether or not a debugger front-end is connected.
+  pub fn local_session(&self) -> LocalInspectorSession {
+    self.local_session.clone()
+  }
+
+  /// Blocks until a debugger front-end has connected, then schedules a pause
+  /// on the next JavaScript statement, ie. the first statement of the module
+  /// about to be evaluated. Used for `--inspect-brk`.
+  pub fn wait_for_session_and_break_on_next_statement(&mut self) {
+    loop {
+      match self.sessions.get_mut().established.iter_mut().next() {
+        Some(session) => break session.break_on_next_statement(),
+        None => {
+          self.flags.get_mut().waiting_for_session = true;
+          let _ = self.poll_sessions(None).unwrap();
+        }
+      };
+    }
+  }
+
   fn poll_sessions(
     &self,
     mut invoker_cx: Option<&mut Context>,
@@ -469,14 +500,9 @@ impl DenoInspector {
             replace(&mut self.flags.borrow_mut().session_handshake_done, false);
           match poll_result {
             Poll::Pending if handshake_done => {
-              let mut session = sessions.handshake.take().unwrap();
-              if replace(
-                &mut self.flags.borrow_mut().waiting_for_session,
-                false,
-              ) {
-                session.break_on_first_statement();
-              }
+              let session = sessions.handshake.take().unwrap();
               sessions.established.push(session);
+              take(&mut self.flags.borrow_mut().waiting_for_session);
             }
             Poll::Ready(_) => sessions.handshake = None,
             Poll::Pending => break,
@@ -556,16 +582,6 @@ struct InspectorFlags {
   on_pause: bool,
 }
 
-impl InspectorFlags {
-  fn new(waiting_for_session: bool) -> RefCell<Self> {
-    let self_ = Self {
-      waiting_for_session,
-      ..Default::default()
-    };
-    RefCell::new(self_)
-  }
-}
-
 struct InspectorSessions {
   new_incoming:
     Pin<Box<dyn Stream<Item = Box<DenoInspectorSession>> + 'static>>,
@@ -753,7 +769,7 @@ impl DenoInspectorSession {
     let _ = self.websocket_tx.unbounded_send(msg);
   }
 
-  pub fn break_on_first_statement(&mut self) {
+  pub fn break_on_next_statement(&mut self) {
     let reason = v8::inspector::StringView::from(&b"debugCommand"[..]);
     let detail = v8::inspector::StringView::empty();
     self.schedule_pause_on_next_statement(reason, detail);
@@ -794,6 +810,120 @@ impl Future for DenoInspectorSession {
   }
 }
 
+/// A session of an inspector that's driven from Rust instead of by a debugger
+/// front-end, eg. to collect coverage or CPU profiles. Clones share the same
+/// session, which is closed when the inspector is dropped.
+#[derive(Clone, Default)]
+pub struct LocalInspectorSession(Rc<RefCell<Option<Box<LocalSession>>>>);
+
+impl LocalInspectorSession {
+  /// Posts a DevTools protocol message and returns its result, along with
+  /// the notifications sent while it was handled; eg. the chunks of a heap
+  /// snapshot are sent as `HeapProfiler.addHeapSnapshotChunk` notifications.
+  pub fn post_message(
+    &self,
+    method: &str,
+    params: Value,
+  ) -> Result<(Value, Vec<Value>), OpError> {
+    match self.0.borrow_mut().as_mut() {
+      Some(session) => session.post_message(method, params),
+      None => Err(OpError::other("The inspector was closed".to_string())),
+    }
+  }
+
+  fn close(&self) {
+    self.0.borrow_mut().take();
+  }
+}
+
+struct LocalSession {
+  v8_channel: v8::inspector::ChannelBase,
+  v8_session: v8::UniqueRef<v8::inspector::V8InspectorSession>,
+  next_message_id: i32,
+  response: Option<Value>,
+  notifications: Vec<Value>,
+}
+
+impl LocalSession {
+  fn new(inspector: &mut DenoInspector) -> Box<Self> {
+    new_box_with(move |self_ptr| {
+      let v8_channel = v8::inspector::ChannelBase::new::<Self>();
+      let v8_session = inspector.connect(
+        DenoInspector::CONTEXT_GROUP_ID,
+        unsafe { &mut *self_ptr },
+        v8::inspector::StringView::empty(),
+      );
+
+      Self {
+        v8_channel,
+        v8_session,
+        next_message_id: 1,
+        response: None,
+        notifications: Vec::new(),
+      }
+    })
+  }
+
+  fn post_message(
+    &mut self,
+    method: &str,
+    params: Value,
+  ) -> Result<(Value, Vec<Value>), OpError> {
+    let id = self.next_message_id;
+    self.next_message_id += 1;
+    let message = json!({ "id": id, "method": method, "params": params });
+    let message = message.to_string();
+    let message = v8::inspector::StringView::from(message.as_bytes());
+    // Messages of the domains used locally are handled synchronously, so
+    // the response has been sent once this returns.
+    self.v8_session.dispatch_protocol_message(message);
+
+    let notifications = take(&mut self.notifications);
+    let mut response = match self.response.take() {
+      Some(response) => response,
+      None => {
+        let message = format!("The inspector didn't respond to {}", method);
+        return Err(OpError::other(message));
+      }
+    };
+    if let Some(message) = response["error"]["message"].as_str() {
+      return Err(OpError::other(format!("{}: {}", method, message)));
+    }
+    Ok((response["result"].take(), notifications))
+  }
+}
+
+impl v8::inspector::ChannelImpl for LocalSession {
+  fn base(&self) -> &v8::inspector::ChannelBase {
+    &self.v8_channel
+  }
+
+  fn base_mut(&mut self) -> &mut v8::inspector::ChannelBase {
+    &mut self.v8_channel
+  }
+
+  fn send_response(
+    &mut self,
+    _call_id: i32,
+    message: v8::UniquePtr<v8::inspector::StringBuffer>,
+  ) {
+    let message = message.unwrap().string().to_string();
+    self.response = serde_json::from_str(&message).ok();
+  }
+
+  fn send_notification(
+    &mut self,
+    message: v8::UniquePtr<v8::inspector::StringBuffer>,
+  ) {
+    let message = message.unwrap().string().to_string();
+    if let Ok(notification) = serde_json::from_str(&message) {
+      self.notifications.push(notification);
+    }
+  }
+
+  fn flush_protocol_notifications(&mut self) {}
+}
+
 fn new_box_with<T>(new_fn: impl FnOnce(*mut T) -> T) -> Box<T> {
   let b = Box::new(MaybeUninit::<T>::uninit());
   let p = Box::into_raw(b) as *mut T;
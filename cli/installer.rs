@@ -79,12 +79,8 @@ deno {} "$@"
   Ok(())
 }
 
+/// The default root, if neither `--root` nor `DENO_INSTALL_ROOT` is set.
 fn get_installer_root() -> Result<PathBuf, Error> {
-  if let Ok(env_dir) = env::var("DENO_INSTALL_ROOT") {
-    if !env_dir.is_empty() {
-      return PathBuf::from(env_dir).canonicalize();
-    }
-  }
   // In Windows's Powershell $HOME environmental variable maybe null
   // if so use $USERPROFILE instead.
   let home = env::var("HOME")
@@ -105,7 +101,7 @@ fn get_installer_root() -> Result<PathBuf, Error> {
   Ok(home_path)
 }
 
-fn infer_name_from_url(url: &Url) -> Option<String> {
+pub fn infer_name_from_url(url: &Url) -> Option<String> {
   let path = PathBuf::from(url.path());
   let stem = match path.file_stem() {
     Some(stem) => stem.to_string_lossy().to_string(),
@@ -121,6 +117,15 @@ fn infer_name_from_url(url: &Url) -> Option<String> {
   Some(stem)
 }
 
+fn get_executable_path(installation_dir: &PathBuf, name: &str) -> PathBuf {
+  let file_path = installation_dir.join(name);
+  if cfg!(windows) {
+    file_path.with_extension("cmd")
+  } else {
+    file_path
+  }
+}
+
 pub fn install(
   flags: Flags,
   module_url: &str,
@@ -173,11 +178,7 @@ pub fn install(
   };
 
   validate_name(name.as_str())?;
-  let mut file_path = installation_dir.join(&name);
-
-  if cfg!(windows) {
-    file_path = file_path.with_extension("cmd");
-  }
+  let file_path = get_executable_path(&installation_dir, &name);
 
   if file_path.exists() && !force {
     return Err(Error::new(
@@ -197,19 +198,12 @@ pub fn install(
       executable_args.push("--quiet".to_string());
     } else {
       executable_args.push("--log-level".to_string());
-      let log_level = match log_level {
-        Level::Debug => "debug",
-        Level::Info => "info",
-        _ => {
-          return Err(Error::new(
-            ErrorKind::Other,
-            format!("invalid log level {}", log_level),
-          ))
-        }
-      };
-      executable_args.push(log_level.to_string());
+      executable_args.push(log_level.to_string().to_lowercase());
     }
   }
+  if flags.log_json {
+    executable_args.push("--log-json".to_string());
+  }
 
   if flags.unstable {
     executable_args.push("--unstable".to_string());
@@ -236,6 +230,42 @@ pub fn install(
   Ok(())
 }
 
+/// Removes an executable previously created by `install`. Files that were not
+/// generated by `deno install` are left untouched.
+pub fn uninstall(name: String, root: Option<PathBuf>) -> Result<(), Error> {
+  let root = if let Some(root) = root {
+    root.canonicalize()?
+  } else {
+    get_installer_root()?
+  };
+  let installation_dir = root.join("bin");
+
+  validate_name(name.as_str())?;
+  let file_path = get_executable_path(&installation_dir, &name);
+
+  if !file_path.exists() {
+    return Err(Error::new(
+      ErrorKind::NotFound,
+      format!("No installation found for {}", name),
+    ));
+  }
+
+  let content = fs::read_to_string(&file_path)?;
+  if !content.contains("generated by deno install") {
+    return Err(Error::new(
+      ErrorKind::Other,
+      format!(
+        "{} was not installed by deno install. Aborting.",
+        file_path.to_string_lossy()
+      ),
+    ));
+  }
+
+  fs::remove_file(&file_path)?;
+  println!("✅ Successfully uninstalled {}", name);
+  Ok(())
+}
+
 fn is_in_path(dir: &PathBuf) -> bool {
   if let Some(paths) = env::var_os("PATH") {
     for p in env::split_paths(&paths) {
@@ -333,10 +363,8 @@ mod tests {
     // It means that other test can override env vars when this test is running.
     let original_home = env::var_os("HOME");
     let original_user_profile = env::var_os("HOME");
-    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
     env::set_var("HOME", &temp_dir_str);
     env::set_var("USERPROFILE", &temp_dir_str);
-    env::set_var("DENO_INSTALL_ROOT", "");
 
     install(
       Flags::default(),
@@ -367,9 +395,6 @@ mod tests {
     if let Some(user_profile) = original_user_profile {
       env::set_var("USERPROFILE", user_profile);
     }
-    if let Some(install_root) = original_install_root {
-      env::set_var("DENO_INSTALL_ROOT", install_root);
-    }
   }
 
   #[test]
@@ -486,39 +511,6 @@ mod tests {
       .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
   }
 
-  #[test]
-  fn install_custom_dir_env_var() {
-    let _guard = ENV_LOCK.lock().ok();
-    let temp_dir = TempDir::new().expect("tempdir fail");
-    let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
-    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
-    env::set_var("DENO_INSTALL_ROOT", temp_dir.path().to_path_buf());
-
-    install(
-      Flags::default(),
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      None,
-      false,
-    )
-    .expect("Install failed");
-
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
-
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
-    if let Some(install_root) = original_install_root {
-      env::set_var("DENO_INSTALL_ROOT", install_root);
-    }
-  }
-
   #[test]
   fn install_with_flags() {
     let temp_dir = TempDir::new().expect("tempdir fail");
@@ -633,4 +625,49 @@ mod tests {
     let file_content_2 = fs::read_to_string(&file_path).unwrap();
     assert!(file_content_2.contains("cat.ts"));
   }
+
+  #[test]
+  fn uninstall_basic() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags::default(),
+      "http://localhost:4545/cli/tests/echo_server.ts",
+      vec![],
+      Some("echo_test".to_string()),
+      Some(temp_dir.path().to_path_buf()),
+      false,
+    )
+    .expect("Install failed");
+
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    assert!(file_path.exists());
+
+    uninstall("echo_test".to_string(), Some(temp_dir.path().to_path_buf()))
+      .expect("Uninstall failed");
+    assert!(!file_path.exists());
+
+    let err =
+      uninstall("echo_test".to_string(), Some(temp_dir.path().to_path_buf()))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+  }
+
+  #[test]
+  fn uninstall_foreign_file() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let file_path = get_executable_path(&bin_dir, "foreign");
+    fs::write(&file_path, "#!/bin/sh\necho hello\n").unwrap();
+
+    assert!(uninstall(
+      "foreign".to_string(),
+      Some(temp_dir.path().to_path_buf())
+    )
+    .is_err());
+    assert!(file_path.exists());
+  }
 }
@@ -1,5 +1,7 @@
 pub const TS_VERSION: &str = env!("TS_VERSION");
 
+/// The runtime snapshot is embedded uncompressed, as every program starts
+/// from it; see `build.rs`.
 pub static CLI_SNAPSHOT: &[u8] =
   include_bytes!(concat!(env!("OUT_DIR"), "/CLI_SNAPSHOT.bin"));
 pub static CLI_SNAPSHOT_MAP: &[u8] =
@@ -8,18 +10,39 @@ pub static CLI_SNAPSHOT_MAP: &[u8] =
 pub static CLI_SNAPSHOT_DTS: &[u8] =
   include_bytes!(concat!(env!("OUT_DIR"), "/CLI_SNAPSHOT.d.ts"));
 
-pub static COMPILER_SNAPSHOT: &[u8] =
-  include_bytes!(concat!(env!("OUT_DIR"), "/COMPILER_SNAPSHOT.bin"));
+#[cfg(feature = "tsc")]
+static COMPILER_SNAPSHOT_ZSTD: &[u8] =
+  include_bytes!(concat!(env!("OUT_DIR"), "/COMPILER_SNAPSHOT.bin.zst"));
+#[cfg(feature = "tsc")]
 pub static COMPILER_SNAPSHOT_MAP: &[u8] =
   include_bytes!(concat!(env!("OUT_DIR"), "/COMPILER_SNAPSHOT.js.map"));
+#[cfg(feature = "tsc")]
 #[allow(dead_code)]
 pub static COMPILER_SNAPSHOT_DTS: &[u8] =
   include_bytes!(concat!(env!("OUT_DIR"), "/COMPILER_SNAPSHOT.d.ts"));
 
+#[cfg(feature = "tsc")]
+lazy_static! {
+  /// The compiler snapshot is embedded compressed to keep the binary small.
+  /// It's decompressed the first time a compiler isolate is created, which
+  /// programs that don't compile anything never do, and kept in memory for
+  /// the compiler isolates that follow.
+  pub static ref COMPILER_SNAPSHOT: Box<[u8]> =
+    decompress_snapshot(COMPILER_SNAPSHOT_ZSTD);
+}
+
+#[cfg(feature = "tsc")]
+fn decompress_snapshot(data: &[u8]) -> Box<[u8]> {
+  zstd::stream::decode_all(data)
+    .expect("Failed to decompress snapshot")
+    .into_boxed_slice()
+}
+
 pub static DENO_NS_LIB: &str = include_str!("js/lib.deno.ns.d.ts");
 pub static SHARED_GLOBALS_LIB: &str =
   include_str!("js/lib.deno.shared_globals.d.ts");
 pub static WINDOW_LIB: &str = include_str!("js/lib.deno.window.d.ts");
+pub static WORKER_LIB: &str = include_str!("js/lib.deno.worker.d.ts");
 pub static UNSTABLE_NS_LIB: &str = include_str!("js/lib.deno.unstable.d.ts");
 
 #[test]
@@ -39,11 +62,12 @@ fn cli_snapshot() {
   ));
 }
 
+#[cfg(feature = "tsc")]
 #[test]
 fn compiler_snapshot() {
   let mut isolate = deno_core::CoreIsolate::new(
     deno_core::StartupData::Snapshot(deno_core::Snapshot::Static(
-      COMPILER_SNAPSHOT,
+      &COMPILER_SNAPSHOT,
     )),
     false,
   );
@@ -0,0 +1,275 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { bold, cyan, gray, green, red, yellow } from "./colors.ts";
+import { exit } from "./ops/os.ts";
+import { now } from "./ops/timers.ts";
+import { stringifyArgs } from "./web/console.ts";
+import { stdout } from "./files.ts";
+import { exposeForTest } from "./internals.ts";
+import { inspector } from "./inspector.ts";
+import { TextEncoder } from "./web/text_encoding.ts";
+
+export interface BenchDefinition {
+  fn: () => void | Promise<void>;
+  name: string;
+  group?: string;
+  baseline?: boolean;
+  ignore?: boolean;
+}
+
+const BENCH_REGISTRY: BenchDefinition[] = [];
+
+export function bench(b: BenchDefinition): void;
+export function bench(name: string, fn: () => void | Promise<void>): void;
+export function bench(
+  b: string | BenchDefinition,
+  fn?: () => void | Promise<void>
+): void {
+  let benchDef: BenchDefinition;
+
+  if (typeof b === "string") {
+    if (!fn || typeof fn != "function") {
+      throw new TypeError("Missing benchmark function");
+    }
+    if (!b) {
+      throw new TypeError("The benchmark name can't be empty");
+    }
+    benchDef = { fn, name: b };
+  } else {
+    if (!b.fn) {
+      throw new TypeError("Missing benchmark function");
+    }
+    if (!b.name) {
+      throw new TypeError("The benchmark name can't be empty");
+    }
+    benchDef = { ...b };
+  }
+
+  BENCH_REGISTRY.push(benchDef);
+}
+
+// Time per iteration in milliseconds, measured over all samples.
+export interface BenchStats {
+  name: string;
+  group?: string;
+  baseline?: boolean;
+  iterations: number;
+  mean: number;
+  min: number;
+  max: number;
+  median: number;
+  p75: number;
+  p99: number;
+}
+
+// Milliseconds since start of the process, with nanosecond precision since
+// `deno bench` allows high resolution time.
+function nowMs(): number {
+  const { seconds, subsecNanos } = now();
+  return seconds * 1e3 + subsecNanos / 1e6;
+}
+
+async function runBatch(
+  fn: () => void | Promise<void>,
+  iterations: number,
+  isAsync: boolean
+): Promise<number> {
+  const start = nowMs();
+  if (isAsync) {
+    for (let i = 0; i < iterations; i++) {
+      await fn();
+    }
+  } else {
+    for (let i = 0; i < iterations; i++) {
+      fn();
+    }
+  }
+  return nowMs() - start;
+}
+
+function percentile(sorted: number[], p: number): number {
+  const index = Math.ceil(p * sorted.length) - 1;
+  return sorted[Math.min(sorted.length - 1, Math.max(0, index))];
+}
+
+interface MeasureOptions {
+  warmupTime: number;
+  targetTime: number;
+}
+
+// Samples shorter than this are distorted by the resolution of the timer and
+// the cost of the op reading it.
+const MIN_SAMPLE_TIME = 1;
+const MIN_SAMPLES = 10;
+
+async function measure(
+  { fn }: BenchDefinition,
+  { warmupTime, targetTime }: MeasureOptions
+): Promise<Omit<BenchStats, "name">> {
+  // Warm up the function, so it's optimized by V8 before it's measured, and
+  // estimate the time of a single iteration.
+  const start = nowMs();
+  const first = fn();
+  const isAsync = first instanceof Promise;
+  await first;
+  let warmupIterations = 1;
+  let warmupElapsed = nowMs() - start;
+  for (let batch = 1; warmupElapsed < warmupTime; batch *= 2) {
+    warmupElapsed += await runBatch(fn, batch, isAsync);
+    warmupIterations += batch;
+  }
+
+  const batchSize = Math.max(
+    1,
+    Math.ceil(MIN_SAMPLE_TIME / (warmupElapsed / warmupIterations))
+  );
+  const samples: number[] = [];
+  let elapsed = 0;
+  while (samples.length < MIN_SAMPLES || elapsed < targetTime) {
+    const sample = await runBatch(fn, batchSize, isAsync);
+    elapsed += sample;
+    samples.push(sample / batchSize);
+  }
+
+  samples.sort((a, b) => a - b);
+  return {
+    iterations: samples.length * batchSize,
+    mean: samples.reduce((sum, sample) => sum + sample, 0) / samples.length,
+    min: samples[0],
+    max: samples[samples.length - 1],
+    median: percentile(samples, 0.5),
+    p75: percentile(samples, 0.75),
+    p99: percentile(samples, 0.99),
+  };
+}
+
+function formatTime(ms: number): string {
+  if (ms < 1e-3) {
+    return `${(ms * 1e6).toFixed(2)}ns`;
+  } else if (ms < 1) {
+    return `${(ms * 1e3).toFixed(2)}µs`;
+  } else if (ms < 1e3) {
+    return `${ms.toFixed(2)}ms`;
+  }
+  return `${(ms / 1e3).toFixed(2)}s`;
+}
+
+const encoder = new TextEncoder();
+
+function log(msg: string): void {
+  stdout.writeSync(encoder.encode(msg + "\n"));
+}
+
+function reportGroup(group: string, results: BenchStats[]): void {
+  if (results.length < 2) {
+    return;
+  }
+  // Benchmarks are compared with the baseline, by default the fastest one.
+  const baseline =
+    results.find((r) => r.baseline) ??
+    results.reduce((a, b) => (b.mean < a.mean ? b : a));
+  log(`\nsummary of group ${bold(group)}:`);
+  for (const result of results) {
+    if (result === baseline) {
+      continue;
+    }
+    const ratio = result.mean / baseline.mean;
+    const comparison =
+      ratio >= 1
+        ? `${green(ratio.toFixed(2) + "x")} faster than`
+        : `${red((1 / ratio).toFixed(2) + "x")} slower than`;
+    log(`  ${baseline.name} is ${comparison} ${result.name}`);
+  }
+}
+
+interface RunBenchmarksOptions {
+  filter?: string | RegExp;
+  // Time spent running each benchmark before it's measured, in milliseconds.
+  warmupTime?: number;
+  // Minimum time spent measuring each benchmark, in milliseconds.
+  targetTime?: number;
+  exitOnFail?: boolean;
+  // Write a CPU profile of the benchmarks to the file given with
+  // `deno bench --profile`.
+  profile?: boolean;
+}
+
+async function runBenchmarks({
+  filter = undefined,
+  warmupTime = 100,
+  targetTime = 500,
+  exitOnFail = true,
+  profile = false,
+}: RunBenchmarksOptions = {}): Promise<BenchStats[]> {
+  const benchmarks = BENCH_REGISTRY.filter(({ name }) =>
+    filter == null
+      ? true
+      : filter instanceof RegExp
+      ? filter.test(name)
+      : name.includes(filter)
+  );
+  const filtered = BENCH_REGISTRY.length - benchmarks.length;
+  log(`running ${benchmarks.length} benchmarks`);
+  if (profile) {
+    inspector.startProfiling();
+  }
+
+  const results: BenchStats[] = [];
+  let failed = 0;
+  let ignored = 0;
+  for (const benchDef of benchmarks) {
+    const { name, group, baseline, ignore } = benchDef;
+    if (ignore) {
+      log(`bench ${name} ... ${yellow("ignored")}`);
+      ignored++;
+      continue;
+    }
+    try {
+      const stats = await measure(benchDef, { warmupTime, targetTime });
+      results.push({ name, group, baseline, ...stats });
+      log(
+        `bench ${name} ... ${cyan(formatTime(stats.mean))}/iter ` +
+          gray(
+            `(median ${formatTime(stats.median)}, ` +
+              `p75 ${formatTime(stats.p75)}, ` +
+              `p99 ${formatTime(stats.p99)}, ` +
+              `${stats.iterations} iterations)`
+          )
+      );
+    } catch (err) {
+      log(`bench ${name} ... ${red("FAILED")}`);
+      log(stringifyArgs([err]));
+      failed++;
+    }
+  }
+
+  const groups = new Map<string, BenchStats[]>();
+  for (const result of results) {
+    if (result.group != null) {
+      const groupResults = groups.get(result.group) ?? [];
+      groupResults.push(result);
+      groups.set(result.group, groupResults);
+    }
+  }
+  for (const [group, groupResults] of groups) {
+    reportGroup(group, groupResults);
+  }
+
+  if (profile) {
+    const path = inspector.report("profile");
+    log(`CPU profile written to ${path}`);
+  }
+
+  log(
+    `\nbench result: ${failed ? red("FAILED") : green("ok")}. ` +
+      `${results.length} measured; ${failed} failed; ${ignored} ignored; ` +
+      `${filtered} filtered out\n`
+  );
+
+  if (failed > 0 && exitOnFail) {
+    exit(1);
+  }
+
+  return results;
+}
+
+exposeForTest("runBenchmarks", runBenchmarks);
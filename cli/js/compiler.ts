@@ -67,63 +67,43 @@ const CHAR_FORWARD_SLASH = 47; /* / */
 const ASSETS = "$asset$";
 const OUT_DIR = "$deno$";
 
-// TODO(Bartlomieju): this check should be done in Rust
-const IGNORED_COMPILER_OPTIONS: readonly string[] = [
-  "allowSyntheticDefaultImports",
-  "baseUrl",
-  "build",
-  "composite",
-  "declaration",
-  "declarationDir",
-  "declarationMap",
-  "diagnostics",
-  "downlevelIteration",
-  "emitBOM",
-  "emitDeclarationOnly",
-  "esModuleInterop",
-  "extendedDiagnostics",
-  "forceConsistentCasingInFileNames",
-  "help",
-  "importHelpers",
-  "incremental",
-  "inlineSourceMap",
-  "inlineSources",
-  "init",
-  "isolatedModules",
-  "listEmittedFiles",
-  "listFiles",
-  "mapRoot",
-  "maxNodeModuleJsDepth",
-  "module",
-  "moduleResolution",
-  "newLine",
-  "noEmit",
-  "noEmitHelpers",
-  "noEmitOnError",
-  "noLib",
-  "noResolve",
-  "out",
-  "outDir",
-  "outFile",
-  "paths",
-  "preserveSymlinks",
-  "preserveWatchOutput",
-  "pretty",
-  "rootDir",
-  "rootDirs",
-  "showConfig",
-  "skipDefaultLibCheck",
-  "skipLibCheck",
-  "sourceMap",
-  "sourceRoot",
-  "stripInternal",
-  "target",
-  "traceResolution",
-  "tsBuildInfoFile",
-  "types",
-  "typeRoots",
-  "version",
-  "watch",
+/** The options of a `--config` file that are applied, all others are ignored
+ * with a warning. The rest of the options either don't make sense for Deno,
+ * like `module` or `paths`, or are set by Deno itself. */
+const SUPPORTED_COMPILER_OPTIONS: readonly string[] = [
+  "allowJs",
+  "allowUmdGlobalAccess",
+  "allowUnreachableCode",
+  "allowUnusedLabels",
+  "alwaysStrict",
+  "assumeChangesOnlyAffectDirectDependencies",
+  "checkJs",
+  "disableSizeLimit",
+  "emitDecoratorMetadata",
+  "experimentalDecorators",
+  "generateCpuProfile",
+  "jsx",
+  "jsxFactory",
+  "lib",
+  "noFallthroughCasesInSwitch",
+  "noImplicitAny",
+  "noImplicitReturns",
+  "noImplicitThis",
+  "noImplicitUseStrict",
+  "noStrictGenericChecks",
+  "noUnusedLocals",
+  "noUnusedParameters",
+  "preserveConstEnums",
+  "removeComments",
+  "resolveJsonModule",
+  "strict",
+  "strictBindCallApply",
+  "strictFunctionTypes",
+  "strictNullChecks",
+  "strictPropertyInitialization",
+  "suppressExcessPropertyErrors",
+  "suppressImplicitAnyIndexErrors",
+  "useDefineForClassFields",
 ];
 
 const DEFAULT_BUNDLER_OPTIONS: ts.CompilerOptions = {
@@ -392,7 +372,9 @@ function getAssetInternal(filename: string): SourceFile {
 }
 
 class Host implements ts.CompilerHost {
-  readonly #options = DEFAULT_COMPILE_OPTIONS;
+  // The compiler worker serves many requests, each host starts from the
+  // defaults.
+  readonly #options: ts.CompilerOptions = { ...DEFAULT_COMPILE_OPTIONS };
   #target: CompilerHostTarget;
   #writeFile: WriteFileCallback;
 
@@ -440,13 +422,15 @@ class Host implements ts.CompilerHost {
     );
     const ignoredOptions: string[] = [];
     for (const key of Object.keys(options)) {
-      if (
-        IGNORED_COMPILER_OPTIONS.includes(key) &&
-        (!(key in this.#options) || options[key] !== this.#options[key])
-      ) {
+      if (SUPPORTED_COMPILER_OPTIONS.includes(key)) {
+        continue;
+      }
+      // Options that are set to what Deno uses anyway, eg. `"target":
+      // "esnext"`, aren't worth a warning.
+      if (!(key in this.#options) || options[key] !== this.#options[key]) {
         ignoredOptions.push(key);
-        delete options[key];
       }
+      delete options[key];
     }
     Object.assign(this.#options, options);
     return {
@@ -843,6 +827,7 @@ interface WriteFileState {
   type: CompilerRequestType;
   bundle?: boolean;
   bundleOutput?: string;
+  bundleSourceMap?: string;
   host?: Host;
   rootNames: string[];
   emitMap?: Record<string, EmmitedSource>;
@@ -855,12 +840,14 @@ enum CompilerRequestType {
   Compile = 0,
   RuntimeCompile = 1,
   RuntimeTranspile = 2,
+  LanguageService = 3,
+  RuntimeGraph = 4,
 }
 
 // TODO(bartlomieju): probably could be defined inline?
 function createBundleWriteFile(state: WriteFileState): WriteFileCallback {
   return function writeFile(
-    _fileName: string,
+    fileName: string,
     data: string,
     sourceFiles?: readonly ts.SourceFile[]
   ): void {
@@ -870,6 +857,10 @@ function createBundleWriteFile(state: WriteFileState): WriteFileCallback {
     assert(state.bundle);
     // we only support single root names for bundles
     assert(state.rootNames.length === 1);
+    if (fileName.endsWith(".map")) {
+      state.bundleSourceMap = offsetBundleSourceMap(data);
+      return;
+    }
     state.bundleOutput = buildBundle(state.rootNames[0], data, sourceFiles);
   };
 }
@@ -992,6 +983,13 @@ function convertCompilerOptions(str: string): ConvertCompilerOptionsResult {
   };
 }
 
+/** Whether the diagnostic is about a module that was fetched over the
+ * network, whose errors the user usually can't fix. */
+function isRemoteDiagnostic({ file }: ts.Diagnostic): boolean {
+  const fileName = file?.fileName ?? "";
+  return fileName.startsWith("http://") || fileName.startsWith("https://");
+}
+
 const ignoredDiagnostics = [
   // TS2306: File 'file:///Users/rld/src/deno/cli/tests/subdir/amd_like.js' is
   // not a module.
@@ -1168,9 +1166,20 @@ function buildBundle(
       ? `await __instantiateAsync("${rootName}");\n`
       : `__instantiate("${rootName}");\n`;
   }
+  // The source map is returned separately, see `offsetBundleSourceMap()`.
+  data = data.replace(/\n\/\/# sourceMappingURL=.*$/, "");
   return `${SYSTEM_LOADER}\n${data}\n${instantiate}`;
 }
 
+/** Moves the mappings of the source map TypeScript emitted for the bundled
+ * modules below the loader that `buildBundle()` puts before them. */
+function offsetBundleSourceMap(sourceMap: string): string {
+  const map = JSON.parse(sourceMap);
+  map.mappings = ";".repeat(SYSTEM_LOADER.split("\n").length) + map.mappings;
+  delete map.file;
+  return JSON.stringify(map);
+}
+
 function setRootExports(program: ts.Program, rootModule: string): void {
   // get a reference to the type checker, this will let us find symbols from
   // the AST.
@@ -1209,6 +1218,686 @@ function setRootExports(program: ts.Program, rootModule: string): void {
     .map((sym) => sym.getName());
 }
 
+/** How source maps are emitted, set by `--source-maps`. */
+type SourceMapsMode = "external" | "inline" | "none";
+
+function sourceMapsOptions(mode: SourceMapsMode): ts.CompilerOptions {
+  return {
+    sourceMap: mode === "external",
+    inlineSourceMap: mode === "inline",
+  };
+}
+
+/** A constant substituted by `--define`. */
+type DefineValue = string | number | boolean | null;
+
+/** The name `node` refers to, if it's an identifier or a chain of property
+ * accesses of one, like `process.env.NODE_ENV`. */
+function getDefineName(node: ts.Node): string | undefined {
+  if (ts.isIdentifier(node)) {
+    return node.text;
+  }
+  if (ts.isPropertyAccessExpression(node) && ts.isIdentifier(node.name)) {
+    const object = getDefineName(node.expression);
+    return object === undefined ? undefined : `${object}.${node.name.text}`;
+  }
+  return undefined;
+}
+
+/** Whether `node` is read as a value where it is, rather than being
+ * declared, assigned, used as a property name or as a type. */
+function isValueReference(node: ts.Node): boolean {
+  const parent = node.parent;
+  if (!parent || ts.isTypeNode(parent) || ts.isQualifiedName(parent)) {
+    return false;
+  }
+  if (ts.isPropertyAccessExpression(parent) && parent.name === node) {
+    return false;
+  }
+  if (
+    ts.isBinaryExpression(parent) &&
+    parent.left === node &&
+    parent.operatorToken.kind >= ts.SyntaxKind.FirstAssignment &&
+    parent.operatorToken.kind <= ts.SyntaxKind.LastAssignment
+  ) {
+    return false;
+  }
+  const named = parent as { name?: ts.Node; propertyName?: ts.Node };
+  return named.name !== node && named.propertyName !== node;
+}
+
+/** Whether the name isn't declared by the program, other than by ambient
+ * declarations like `declare const DENO_ENV: string;`. */
+function isGlobalName(checker: ts.TypeChecker, node: ts.Node): boolean {
+  const identifier = ts.isPropertyAccessExpression(node)
+    ? getRootIdentifier(node)
+    : node;
+  const symbol = checker.getSymbolAtLocation(identifier);
+  if (!symbol || !symbol.declarations) {
+    return true;
+  }
+  return symbol.declarations.every((declaration) => {
+    const flags = ts.getCombinedModifierFlags(declaration);
+    return (
+      declaration.getSourceFile().isDeclarationFile ||
+      (flags & ts.ModifierFlags.Ambient) !== 0
+    );
+  });
+}
+
+function getRootIdentifier(node: ts.PropertyAccessExpression): ts.Node {
+  let expression: ts.Node = node;
+  while (ts.isPropertyAccessExpression(expression)) {
+    expression = expression.expression;
+  }
+  return expression;
+}
+
+function createDefineLiteral(value: DefineValue): ts.Expression {
+  if (value === null) {
+    return ts.createNull();
+  }
+  if (typeof value === "number" && value < 0) {
+    return ts.createPrefix(ts.SyntaxKind.MinusToken, ts.createLiteral(-value));
+  }
+  return ts.createLiteral(value);
+}
+
+/** The value of an expression made of literals, if it's constant. */
+function getConstantValue(
+  node: ts.Expression
+): { value: unknown } | undefined {
+  switch (node.kind) {
+    case ts.SyntaxKind.TrueKeyword:
+      return { value: true };
+    case ts.SyntaxKind.FalseKeyword:
+      return { value: false };
+    case ts.SyntaxKind.NullKeyword:
+      return { value: null };
+  }
+  if (ts.isNumericLiteral(node)) {
+    return { value: Number(node.text) };
+  }
+  if (ts.isStringLiteral(node) || ts.isNoSubstitutionTemplateLiteral(node)) {
+    return { value: node.text };
+  }
+  if (ts.isParenthesizedExpression(node)) {
+    return getConstantValue(node.expression);
+  }
+  if (ts.isPrefixUnaryExpression(node)) {
+    const operand = getConstantValue(node.operand);
+    if (operand === undefined) {
+      return undefined;
+    }
+    switch (node.operator) {
+      case ts.SyntaxKind.ExclamationToken:
+        return { value: !operand.value };
+      case ts.SyntaxKind.MinusToken:
+        return { value: -(operand.value as number) };
+    }
+    return undefined;
+  }
+  if (ts.isBinaryExpression(node)) {
+    const left = getConstantValue(node.left);
+    const right = getConstantValue(node.right);
+    if (left === undefined || right === undefined) {
+      return undefined;
+    }
+    switch (node.operatorToken.kind) {
+      case ts.SyntaxKind.EqualsEqualsEqualsToken:
+        return { value: left.value === right.value };
+      case ts.SyntaxKind.ExclamationEqualsEqualsToken:
+        return { value: left.value !== right.value };
+      case ts.SyntaxKind.EqualsEqualsToken:
+        return { value: left.value == right.value };
+      case ts.SyntaxKind.ExclamationEqualsToken:
+        return { value: left.value != right.value };
+      case ts.SyntaxKind.AmpersandAmpersandToken:
+        return { value: left.value && right.value };
+      case ts.SyntaxKind.BarBarToken:
+        return { value: left.value || right.value };
+    }
+  }
+  return undefined;
+}
+
+/** Whether the diagnostic is "Cannot find name", for a name of `defines`,
+ * which doesn't need to be declared. */
+function isUndefinedDefine(
+  { code, file, start, length }: ts.Diagnostic,
+  defines: Record<string, DefineValue>
+): boolean {
+  if ((code !== 2304 && code !== 2552) || !file || start === undefined) {
+    return false;
+  }
+  const name = file.text.substr(start, length);
+  return Object.keys(defines).some(
+    (define) => define === name || define.startsWith(`${name}.`)
+  );
+}
+
+/** Replaces the names of `defines` with their values, and folds `if`
+ * statements and conditional expressions whose condition became constant,
+ * which leaves out the branches that can't run. */
+function createDefineTransformer(
+  program: ts.Program,
+  defines: Record<string, DefineValue>
+): ts.TransformerFactory<ts.SourceFile> {
+  const checker = program.getTypeChecker();
+  return (context): ts.Transformer<ts.SourceFile> => {
+    function visit(node: ts.Node): ts.Node {
+      // Names are looked up on the original nodes, before their children are
+      // replaced.
+      const name = getDefineName(node);
+      if (
+        name !== undefined &&
+        name in defines &&
+        isValueReference(node) &&
+        isGlobalName(checker, node)
+      ) {
+        return createDefineLiteral(defines[name]);
+      }
+      const parent = node.parent;
+      node = ts.visitEachChild(node, visit, context);
+      if (ts.isIfStatement(node)) {
+        const condition = getConstantValue(node.expression);
+        if (condition !== undefined) {
+          if (condition.value) {
+            return node.thenStatement;
+          }
+          if (node.elseStatement) {
+            return node.elseStatement;
+          }
+          return parent && (ts.isBlock(parent) || ts.isSourceFile(parent))
+            ? ts.createNotEmittedStatement(node)
+            : ts.createBlock([]);
+        }
+      }
+      if (ts.isConditionalExpression(node)) {
+        const condition = getConstantValue(node.condition);
+        if (condition !== undefined) {
+          return ts.createParen(
+            condition.value ? node.whenTrue : node.whenFalse
+          );
+        }
+      }
+      return node;
+    }
+    return (sourceFile): ts.SourceFile =>
+      ts.visitNode(sourceFile, visit) as ts.SourceFile;
+  };
+}
+
+function resolveAlias(checker: ts.TypeChecker, symbol: ts.Symbol): ts.Symbol {
+  return symbol.flags & ts.SymbolFlags.Alias
+    ? checker.getAliasedSymbol(symbol)
+    : symbol;
+}
+
+function hasModifier(node: ts.Node, kind: ts.SyntaxKind): boolean {
+  return (node.modifiers ?? []).some((modifier) => modifier.kind === kind);
+}
+
+/** The declarations of a bundle that modules use from other modules: the
+ * ones they import by name, all the exports of the modules they import as a
+ * namespace or with `import()`, and the exports of the root module. Returns
+ * `undefined` when that can't be known, like for `import()` of a computed
+ * specifier. */
+function getUsedExports(
+  program: ts.Program,
+  rootName: string
+): Set<ts.Declaration> | undefined {
+  const checker = program.getTypeChecker();
+  const used = new Set<ts.Declaration>();
+  function useSymbol(symbol: ts.Symbol | undefined): void {
+    const declarations = symbol
+      ? resolveAlias(checker, symbol).declarations ?? []
+      : [];
+    for (const declaration of declarations) {
+      used.add(declaration);
+    }
+  }
+  function useModule(node: ts.Node): void {
+    const moduleSymbol = checker.getSymbolAtLocation(node);
+    if (moduleSymbol) {
+      checker.getExportsOfModule(moduleSymbol).forEach(useSymbol);
+    }
+  }
+
+  let isKnown = true;
+  function visit(node: ts.Node): void {
+    if (ts.isImportDeclaration(node) && node.importClause) {
+      const { name, namedBindings } = node.importClause;
+      if (name) {
+        useSymbol(checker.getSymbolAtLocation(name));
+      }
+      if (namedBindings && ts.isNamespaceImport(namedBindings)) {
+        useModule(node.moduleSpecifier);
+      } else if (namedBindings) {
+        for (const element of namedBindings.elements) {
+          useSymbol(checker.getSymbolAtLocation(element.name));
+        }
+      }
+    } else if (
+      ts.isExportDeclaration(node) &&
+      node.moduleSpecifier &&
+      node.exportClause &&
+      ts.isNamespaceExport(node.exportClause)
+    ) {
+      useModule(node.moduleSpecifier);
+    } else if (
+      ts.isCallExpression(node) &&
+      node.expression.kind === ts.SyntaxKind.ImportKeyword
+    ) {
+      const [specifier] = node.arguments;
+      if (specifier && ts.isStringLiteralLike(specifier)) {
+        useModule(specifier);
+      } else {
+        isKnown = false;
+      }
+    }
+    ts.forEachChild(node, visit);
+  }
+
+  const rootSourceFile = program.getSourceFile(rootName);
+  assert(rootSourceFile);
+  useModule(rootSourceFile);
+  for (const sourceFile of program.getSourceFiles()) {
+    if (!sourceFile.isDeclarationFile) {
+      visit(sourceFile);
+    }
+  }
+  return isKnown ? used : undefined;
+}
+
+/** The nodes that refer to each declaration of a module. */
+function getReferences(
+  checker: ts.TypeChecker,
+  sourceFile: ts.SourceFile
+): Map<ts.Declaration, ts.Node[]> {
+  const references = new Map<ts.Declaration, ts.Node[]>();
+  function visit(node: ts.Node): void {
+    let symbol: ts.Symbol | undefined;
+    if (ts.isShorthandPropertyAssignment(node)) {
+      symbol = checker.getShorthandAssignmentValueSymbol(node);
+    } else if (ts.isExportSpecifier(node)) {
+      symbol = checker.getExportSpecifierLocalTargetSymbol(node);
+    } else if (ts.isIdentifier(node)) {
+      symbol = checker.getSymbolAtLocation(node);
+    }
+    for (const declaration of symbol?.declarations ?? []) {
+      const nodes = references.get(declaration) ?? [];
+      nodes.push(node);
+      references.set(declaration, nodes);
+    }
+    ts.forEachChild(node, visit);
+  }
+  visit(sourceFile);
+  return references;
+}
+
+/** The declarations of a top level statement that tree shaking handles. */
+function getStatementDeclarations(
+  statement: ts.Statement
+): ts.Declaration[] | undefined {
+  if (ts.isFunctionDeclaration(statement) || ts.isClassDeclaration(statement)) {
+    return [statement];
+  }
+  if (ts.isVariableStatement(statement)) {
+    const { declarations } = statement.declarationList;
+    return declarations.every(({ name }) => ts.isIdentifier(name))
+      ? [...declarations]
+      : undefined;
+  }
+  return undefined;
+}
+
+/** Whether evaluating the statement can't have side effects, so it can be
+ * left out when nothing uses what it declares. */
+function isPureDeclaration(statement: ts.Statement): boolean {
+  if (ts.isFunctionDeclaration(statement)) {
+    return true;
+  }
+  if (ts.isClassDeclaration(statement)) {
+    return (
+      !statement.decorators &&
+      (statement.heritageClauses ?? []).every((clause) =>
+        clause.types.every(({ expression }) => ts.isIdentifier(expression))
+      ) &&
+      statement.members.every(
+        (member) =>
+          !member.decorators &&
+          !(
+            ts.isPropertyDeclaration(member) &&
+            member.initializer &&
+            hasModifier(member, ts.SyntaxKind.StaticKeyword)
+          )
+      )
+    );
+  }
+  if (ts.isVariableStatement(statement)) {
+    return statement.declarationList.declarations.every(
+      ({ initializer }) =>
+        !initializer ||
+        ts.isLiteralExpression(initializer) ||
+        ts.isFunctionExpression(initializer) ||
+        ts.isArrowFunction(initializer) ||
+        initializer.kind === ts.SyntaxKind.TrueKeyword ||
+        initializer.kind === ts.SyntaxKind.FalseKeyword ||
+        initializer.kind === ts.SyntaxKind.NullKeyword
+    );
+  }
+  return false;
+}
+
+/** The statement without its `export` and `default` modifiers. */
+function removeExport(statement: ts.Statement): ts.Statement {
+  const modifiers = (statement.modifiers ?? []).filter(
+    ({ kind }) =>
+      kind !== ts.SyntaxKind.ExportKeyword &&
+      kind !== ts.SyntaxKind.DefaultKeyword
+  );
+  if (ts.isFunctionDeclaration(statement)) {
+    return ts.updateFunctionDeclaration(
+      statement,
+      statement.decorators,
+      modifiers,
+      statement.asteriskToken,
+      statement.name,
+      statement.typeParameters,
+      statement.parameters,
+      statement.type,
+      statement.body
+    );
+  }
+  if (ts.isClassDeclaration(statement)) {
+    return ts.updateClassDeclaration(
+      statement,
+      statement.decorators,
+      modifiers,
+      statement.name,
+      statement.typeParameters,
+      statement.heritageClauses,
+      statement.members
+    );
+  }
+  assert(ts.isVariableStatement(statement));
+  return ts.updateVariableStatement(
+    statement,
+    modifiers,
+    statement.declarationList
+  );
+}
+
+/** Leaves out the exports of the bundled modules that no other module uses.
+ * Unused exported declarations without side effects are left out, other
+ * ones stay as declarations local to their module. The exports of the root
+ * module are kept. */
+function createTreeShakingTransformer(
+  program: ts.Program,
+  rootName: string
+): ts.TransformerFactory<ts.SourceFile> {
+  const checker = program.getTypeChecker();
+  const used = getUsedExports(program, rootName);
+  const rootSourceFile = program.getSourceFile(rootName);
+  return (): ts.Transformer<ts.SourceFile> => (sourceFile): ts.SourceFile => {
+    if (
+      !used ||
+      sourceFile.isDeclarationFile ||
+      ts.getOriginalNode(sourceFile) === rootSourceFile
+    ) {
+      return sourceFile;
+    }
+    const references = getReferences(checker, sourceFile);
+    const isReferencedOutside = (
+      declaration: ts.Declaration,
+      statement: ts.Statement
+    ): boolean =>
+      (references.get(declaration) ?? []).some(
+        ({ pos, end }) => pos < statement.pos || end > statement.end
+      );
+
+    const statements: ts.Statement[] = [];
+    for (const statement of sourceFile.statements) {
+      if (
+        ts.isExportDeclaration(statement) &&
+        !statement.moduleSpecifier &&
+        statement.exportClause &&
+        ts.isNamedExports(statement.exportClause) &&
+        statement.exportClause.elements.every((element) => {
+          const symbol = checker.getExportSpecifierLocalTargetSymbol(element);
+          return !(symbol?.declarations ?? []).some((declaration) =>
+            used.has(declaration)
+          );
+        })
+      ) {
+        continue;
+      }
+      const declarations = getStatementDeclarations(statement);
+      if (
+        !declarations ||
+        !hasModifier(statement, ts.SyntaxKind.ExportKeyword) ||
+        declarations.some((declaration) => used.has(declaration))
+      ) {
+        statements.push(statement);
+        continue;
+      }
+      const isReferenced = declarations.some((declaration) =>
+        isReferencedOutside(declaration, statement)
+      );
+      if (!isReferenced && isPureDeclaration(statement)) {
+        continue;
+      }
+      // Anonymous default exports can't be made local.
+      const name = (statement as ts.DeclarationStatement).name;
+      statements.push(
+        name || ts.isVariableStatement(statement)
+          ? removeExport(statement)
+          : statement
+      );
+    }
+    return ts.updateSourceFileNode(sourceFile, statements);
+  };
+}
+
+const SHORT_NAME_CHARS =
+  "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+const RESERVED_WORDS = new Set([
+  "do",
+  "if",
+  "in",
+  "for",
+  "let",
+  "new",
+  "try",
+  "var",
+  "case",
+  "else",
+  "enum",
+  "eval",
+  "null",
+  "this",
+  "true",
+  "void",
+  "with",
+  "await",
+  "break",
+  "catch",
+  "class",
+  "const",
+  "false",
+  "super",
+  "throw",
+  "while",
+  "yield",
+]);
+
+/** The `index`th name of `a`, ..., `Z`, `aa`, `ab`, ... */
+function getShortName(index: number): string {
+  let name = "";
+  do {
+    name += SHORT_NAME_CHARS[index % SHORT_NAME_CHARS.length];
+    index = Math.floor(index / SHORT_NAME_CHARS.length) - 1;
+  } while (index >= 0);
+  return name;
+}
+
+function isInsideFunction(node: ts.Node | undefined): boolean {
+  for (; node && !ts.isSourceFile(node); node = node.parent) {
+    if (
+      ts.isFunctionDeclaration(node) ||
+      ts.isFunctionExpression(node) ||
+      ts.isArrowFunction(node) ||
+      ts.isMethodDeclaration(node) ||
+      ts.isConstructorDeclaration(node) ||
+      ts.isGetAccessorDeclaration(node) ||
+      ts.isSetAccessorDeclaration(node)
+    ) {
+      return true;
+    }
+  }
+  return false;
+}
+
+/** Whether the symbol is a variable, function or class declared inside a
+ * function, so that only the code of the function refers to it by name. */
+function isLocalSymbol(symbol: ts.Symbol): boolean {
+  const flags =
+    ts.SymbolFlags.Variable | ts.SymbolFlags.Function | ts.SymbolFlags.Class;
+  if (!(symbol.flags & flags) || symbol.flags & ts.SymbolFlags.Property) {
+    return false;
+  }
+  const declarations = symbol.declarations ?? [];
+  return (
+    declarations.length > 0 &&
+    declarations.every((declaration) => {
+      if (ts.isParameter(declaration)) {
+        // Parameter properties are also class members.
+        return (
+          !declaration.modifiers &&
+          !(
+            ts.isIdentifier(declaration.name) &&
+            declaration.name.text === "this"
+          ) &&
+          isInsideFunction(declaration)
+        );
+      }
+      return (
+        (ts.isVariableDeclaration(declaration) ||
+          ts.isBindingElement(declaration) ||
+          ts.isFunctionDeclaration(declaration) ||
+          ts.isClassDeclaration(declaration)) &&
+        !(ts.getCombinedModifierFlags(declaration) & ts.ModifierFlags.Ambient) &&
+        isInsideFunction(declaration.parent)
+      );
+    })
+  );
+}
+
+/** Renames the variables, functions and classes declared inside functions
+ * to short names. Modules that use `eval`, `with` or JSX are left as they
+ * are, since the names they refer to can't all be known. */
+function createMinifyTransformer(
+  program: ts.Program
+): ts.TransformerFactory<ts.SourceFile> {
+  const checker = program.getTypeChecker();
+  return (context): ts.Transformer<ts.SourceFile> => (
+    sourceFile
+  ): ts.SourceFile => {
+    if (
+      sourceFile.isDeclarationFile ||
+      sourceFile.languageVariant === ts.LanguageVariant.JSX
+    ) {
+      return sourceFile;
+    }
+    // Short names must not shadow any name the module uses.
+    const taken = new Set<string>();
+    let hasWith = false;
+    function collectNames(node: ts.Node): void {
+      if (ts.isIdentifier(node)) {
+        taken.add(node.text);
+      } else if (node.kind === ts.SyntaxKind.WithStatement) {
+        hasWith = true;
+      }
+      ts.forEachChild(node, collectNames);
+    }
+    collectNames(ts.getOriginalNode(sourceFile));
+    if (hasWith || taken.has("eval")) {
+      return sourceFile;
+    }
+
+    const names = new Map<ts.Symbol, string>();
+    let nextName = 0;
+    function getName(symbol: ts.Symbol | undefined): string | undefined {
+      if (!symbol || !isLocalSymbol(symbol)) {
+        return undefined;
+      }
+      let name = names.get(symbol);
+      if (name === undefined) {
+        do {
+          name = getShortName(nextName++);
+        } while (taken.has(name) || RESERVED_WORDS.has(name));
+        names.set(symbol, name);
+      }
+      return name;
+    }
+    function createName(name: string, node: ts.Node): ts.Identifier {
+      const identifier = ts.createIdentifier(name);
+      ts.setOriginalNode(identifier, node);
+      return ts.setTextRange(identifier, node);
+    }
+
+    function visit(node: ts.Node): ts.Node {
+      if (ts.isIdentifier(node)) {
+        const name = getName(checker.getSymbolAtLocation(node));
+        return name ? createName(name, node) : node;
+      }
+      if (ts.isShorthandPropertyAssignment(node)) {
+        // `{ x }` becomes `{ x: a }`, keeping the property name.
+        const name = getName(checker.getShorthandAssignmentValueSymbol(node));
+        if (name) {
+          const value = createName(name, node.name);
+          const initializer = node.objectAssignmentInitializer;
+          return ts.setTextRange(
+            ts.createPropertyAssignment(
+              node.name.text,
+              initializer
+                ? ts.createAssignment(
+                    value,
+                    ts.visitNode(initializer, visit) as ts.Expression
+                  )
+                : value
+            ),
+            node
+          );
+        }
+      }
+      if (
+        ts.isBindingElement(node) &&
+        !node.propertyName &&
+        !node.dotDotDotToken &&
+        ts.isIdentifier(node.name) &&
+        ts.isObjectBindingPattern(ts.getOriginalNode(node).parent)
+      ) {
+        // `{ x } = o` becomes `{ x: a } = o`, keeping the property name.
+        const name = getName(checker.getSymbolAtLocation(node.name));
+        if (name) {
+          return ts.updateBindingElement(
+            node,
+            undefined,
+            ts.createIdentifier(node.name.text),
+            createName(name, node.name),
+            ts.visitNode(node.initializer, visit) as ts.Expression | undefined
+          );
+        }
+      }
+      return ts.visitEachChild(node, visit, context);
+    }
+    return ts.visitNode(sourceFile, visit) as ts.SourceFile;
+  };
+}
+
 interface CompilerRequestCompile {
   type: CompilerRequestType.Compile;
   target: CompilerHostTarget;
@@ -1218,7 +1907,14 @@ interface CompilerRequestCompile {
   configPath?: string;
   config?: string;
   unstable: boolean;
+  /** Whether type errors in remote modules are ignored. */
+  noRemoteCheck: boolean;
+  sourceMaps: SourceMapsMode;
+  /** Constants substituted by `--define`, by name. */
+  defines: Record<string, DefineValue>;
   bundle: boolean;
+  /** Whether the bundle is minified, with a source map. */
+  minify: boolean;
   cwd: string;
 }
 
@@ -1230,6 +1926,15 @@ interface CompilerRequestRuntimeCompile {
   unstable?: boolean;
   bundle?: boolean;
   options?: string;
+  /** Whether diagnostics are reported; defaults to `true`. */
+  check?: boolean;
+}
+
+interface CompilerRequestRuntimeGraph {
+  type: CompilerRequestType.RuntimeGraph;
+  target: CompilerHostTarget;
+  rootName: string;
+  sources?: Record<string, string>;
 }
 
 interface CompilerRequestRuntimeTranspile {
@@ -1238,14 +1943,44 @@ interface CompilerRequestRuntimeTranspile {
   options?: string;
 }
 
+// Positions and ranges use the shape of the Language Server Protocol, which
+// like TypeScript counts characters in UTF-16 code units.
+interface LspPosition {
+  line: number;
+  character: number;
+}
+
+interface LspRange {
+  start: LspPosition;
+  end: LspPosition;
+}
+
+interface CompilerRequestLanguageService {
+  type: CompilerRequestType.LanguageService;
+  target: CompilerHostTarget;
+  unstable?: boolean;
+  configPath?: string;
+  config?: string;
+  cwd?: string;
+  method: "diagnostics" | "hover" | "definition";
+  specifier: string;
+  // Contents of the documents open in the editor, which take precedence over
+  // files on disk.
+  sources: Record<string, string>;
+  position?: LspPosition;
+}
+
 type CompilerRequest =
   | CompilerRequestCompile
   | CompilerRequestRuntimeCompile
-  | CompilerRequestRuntimeTranspile;
+  | CompilerRequestRuntimeTranspile
+  | CompilerRequestRuntimeGraph
+  | CompilerRequestLanguageService;
 
 interface CompileResult {
   emitMap?: Record<string, EmmitedSource>;
   bundleOutput?: string;
+  bundleSourceMap?: string;
   diagnostics: Diagnostic;
 }
 
@@ -1259,6 +1994,11 @@ interface RuntimeBundleResult {
   diagnostics: DiagnosticItem[];
 }
 
+interface RuntimeGraphResult {
+  root: string;
+  modules: Record<string, { dependencies: string[] }>;
+}
+
 async function compile(
   request: CompilerRequestCompile
 ): Promise<CompileResult> {
@@ -1269,6 +2009,10 @@ async function compile(
     rootNames,
     target,
     unstable,
+    noRemoteCheck,
+    sourceMaps,
+    defines,
+    minify,
     cwd,
   } = request;
   util.log(">>> compile start", {
@@ -1299,6 +2043,15 @@ async function compile(
     writeFile,
     unstable,
   }));
+  if (!bundle) {
+    host.mergeOptions(sourceMapsOptions(sourceMaps));
+  } else if (minify) {
+    host.mergeOptions({
+      inlineSources: true,
+      removeComments: true,
+      sourceMap: true,
+    });
+  }
   let diagnostics: readonly ts.Diagnostic[] = [];
 
   // if there is a configuration supplied, we need to parse that
@@ -1332,7 +2085,11 @@ async function compile(
 
     diagnostics = ts
       .getPreEmitDiagnostics(program)
-      .filter(({ code }) => !ignoredDiagnostics.includes(code));
+      .filter(({ code }) => !ignoredDiagnostics.includes(code))
+      .filter(
+        (diagnostic) => !(noRemoteCheck && isRemoteDiagnostic(diagnostic))
+      )
+      .filter((diagnostic) => !isUndefinedDefine(diagnostic, defines));
 
     // We will only proceed with the emit if there are no diagnostics.
     if (diagnostics && diagnostics.length === 0) {
@@ -1341,7 +2098,27 @@ async function compile(
         assert(resolvedRootModules.length === 1);
         setRootExports(program, resolvedRootModules[0]);
       }
-      const emitResult = program.emit();
+      // All transforms happen in the emit, so its source map accounts for
+      // them.
+      const before: Array<ts.TransformerFactory<ts.SourceFile>> = [];
+      if (bundle) {
+        before.push(
+          createTreeShakingTransformer(program, resolvedRootModules[0])
+        );
+      }
+      if (Object.keys(defines).length > 0) {
+        before.push(createDefineTransformer(program, defines));
+      }
+      if (bundle && minify) {
+        before.push(createMinifyTransformer(program));
+      }
+      const emitResult = program.emit(
+        undefined,
+        undefined,
+        undefined,
+        undefined,
+        before.length > 0 ? { before } : undefined
+      );
       assert(emitResult.emitSkipped === false, "Unexpected skip of the emit.");
       // emitResult.diagnostics is `readonly` in TS3.5+ and can't be assigned
       // without casting.
@@ -1360,6 +2137,7 @@ async function compile(
   const result: CompileResult = {
     emitMap: state.emitMap,
     bundleOutput,
+    bundleSourceMap: state.bundleSourceMap,
     diagnostics: fromTypeScriptDiagnostic(diagnostics),
   };
 
@@ -1374,7 +2152,15 @@ async function compile(
 async function runtimeCompile(
   request: CompilerRequestRuntimeCompile
 ): Promise<RuntimeCompileResult | RuntimeBundleResult> {
-  const { bundle, options, rootName, sources, target, unstable } = request;
+  const {
+    bundle,
+    check = true,
+    options,
+    rootName,
+    sources,
+    target,
+    unstable,
+  } = request;
 
   util.log(">>> runtime compile start", {
     rootName,
@@ -1479,9 +2265,11 @@ async function runtimeCompile(
     setRootExports(program, rootNames[0]);
   }
 
-  const diagnostics = ts
-    .getPreEmitDiagnostics(program)
-    .filter(({ code }) => !ignoredDiagnostics.includes(code));
+  const diagnostics = check
+    ? ts
+        .getPreEmitDiagnostics(program)
+        .filter(({ code }) => !ignoredDiagnostics.includes(code))
+    : [];
 
   const emitResult = program.emit();
 
@@ -1512,6 +2300,53 @@ async function runtimeCompile(
   }
 }
 
+/** Loads the modules `rootName` depends on, like `runtimeCompile`, and
+ * returns the resolved dependencies of each of them. */
+async function runtimeGraph(
+  request: CompilerRequestRuntimeGraph
+): Promise<RuntimeGraphResult> {
+  const { rootName, sources } = request;
+
+  util.log(">>> runtime graph start", {
+    rootName,
+    sources: sources ? Object.keys(sources) : undefined,
+  });
+
+  const resolvedRootName = sources ? rootName : resolveModules([rootName])[0];
+  const specifiers = [
+    {
+      original: resolvedRootName,
+      mapped: resolvedRootName,
+    },
+  ];
+  if (sources) {
+    processLocalImports(sources, specifiers, undefined, true);
+  } else {
+    await processImports(specifiers, undefined, true);
+  }
+
+  // Resolved imports are cached by referrer while processing them.
+  const modules: Record<string, { dependencies: string[] }> = {};
+  const queue = [resolvedRootName];
+  for (let i = 0; i < queue.length; i++) {
+    const url = queue[i];
+    if (url in modules) {
+      continue;
+    }
+    const resolved = RESOLVED_SPECIFIER_CACHE.get(url);
+    const dependencies = resolved ? [...new Set(resolved.values())] : [];
+    modules[url] = { dependencies };
+    queue.push(...dependencies);
+  }
+
+  util.log("<<< runtime graph finish", {
+    rootName,
+    modules: Object.keys(modules),
+  });
+
+  return { root: resolvedRootName, modules };
+}
+
 function runtimeTranspile(
   request: CompilerRequestRuntimeTranspile
 ): Promise<Record<string, TranspileOnlyResult>> {
@@ -1538,11 +2373,231 @@ function runtimeTranspile(
   return Promise.resolve(result);
 }
 
+class LanguageServiceHost implements ts.LanguageServiceHost {
+  #host: Host;
+  #rootNames: string[];
+
+  constructor(host: Host, rootNames: string[]) {
+    this.#host = host;
+    this.#rootNames = rootNames;
+  }
+
+  getCompilationSettings(): ts.CompilerOptions {
+    return this.#host.getCompilationSettings();
+  }
+
+  getCurrentDirectory(): string {
+    return this.#host.getCurrentDirectory();
+  }
+
+  getDefaultLibFileName(options: ts.CompilerOptions): string {
+    return this.#host.getDefaultLibFileName(options);
+  }
+
+  getScriptFileNames(): string[] {
+    return this.#rootNames;
+  }
+
+  getScriptSnapshot(fileName: string): ts.IScriptSnapshot | undefined {
+    const sourceFile = this.#host.getSourceFile(
+      fileName,
+      ts.ScriptTarget.ESNext,
+      () => {}
+    );
+    return sourceFile && ts.ScriptSnapshot.fromString(sourceFile.text);
+  }
+
+  getScriptVersion(_fileName: string): string {
+    // Every request uses a new language service.
+    return "1";
+  }
+
+  resolveModuleNames(
+    moduleNames: string[],
+    containingFile: string
+  ): Array<ts.ResolvedModuleFull | undefined> {
+    return this.#host.resolveModuleNames(moduleNames, containingFile);
+  }
+}
+
+const ZERO_RANGE: LspRange = {
+  start: { line: 0, character: 0 },
+  end: { line: 0, character: 0 },
+};
+
+function toLspRange(
+  sourceFile: ts.SourceFile,
+  start: number,
+  length: number
+): LspRange {
+  return {
+    start: sourceFile.getLineAndCharacterOfPosition(start),
+    end: sourceFile.getLineAndCharacterOfPosition(start + length),
+  };
+}
+
+function toLspSeverity(category: ts.DiagnosticCategory): number {
+  switch (category) {
+    case ts.DiagnosticCategory.Error:
+      return 1;
+    case ts.DiagnosticCategory.Warning:
+      return 2;
+    case ts.DiagnosticCategory.Suggestion:
+      return 4;
+    default:
+      return 3;
+  }
+}
+
+async function languageService(
+  request: CompilerRequestLanguageService
+): Promise<unknown> {
+  const {
+    config,
+    configPath,
+    cwd,
+    method,
+    position,
+    sources,
+    specifier,
+    target,
+    unstable,
+  } = request;
+  util.log(">>> language service start", { method, specifier });
+
+  for (const [url, sourceCode] of Object.entries(sources)) {
+    if (!SourceFile.getCached(url)) {
+      SourceFile.addToCache({
+        url,
+        filename: url,
+        mediaType: getMediaType(url),
+        sourceCode,
+      });
+    }
+  }
+  const rootSourceFile = SourceFile.getCached(specifier);
+  assert(rootSourceFile, `Missing module in sources: "${specifier}"`);
+
+  // Dependencies that can't be fetched are reported as a diagnostic, the rest
+  // of the document can still be checked.
+  const importDiagnostics: unknown[] = [];
+  try {
+    await processImports(rootSourceFile.imports(false), specifier);
+  } catch (e) {
+    importDiagnostics.push({
+      range: ZERO_RANGE,
+      severity: 1,
+      source: "deno",
+      message: e instanceof Error ? e.message : String(e),
+    });
+  }
+
+  const host = new Host({ target, unstable, writeFile(): void {} });
+  // Options the configuration can't set are dropped without a warning here,
+  // `deno run` reports them.
+  if (config && configPath && cwd) {
+    host.configure(cwd, configPath, config);
+  }
+  const service = ts.createLanguageService(
+    new LanguageServiceHost(host, [specifier])
+  );
+  const program = service.getProgram();
+  assert(program);
+  const sourceFile = program.getSourceFile(specifier);
+  assert(sourceFile);
+  const pos = position
+    ? sourceFile.getPositionOfLineAndCharacter(
+        position.line,
+        position.character
+      )
+    : 0;
+
+  util.log("<<< language service end", { method, specifier });
+  switch (method) {
+    case "diagnostics": {
+      const diagnostics = [
+        ...service.getSyntacticDiagnostics(specifier),
+        ...service.getSemanticDiagnostics(specifier),
+      ].filter(
+        // TS7016 is kept, so that the language server can suggest adding a
+        // `@deno-types` directive for JavaScript imports.
+        ({ code }) => code === 7016 || !ignoredDiagnostics.includes(code)
+      );
+      return [
+        ...importDiagnostics,
+        ...diagnostics.map((diagnostic) => ({
+          range: toLspRange(
+            sourceFile,
+            diagnostic.start ?? 0,
+            diagnostic.length ?? 0
+          ),
+          severity:
+            diagnostic.code === 7016 ? 3 : toLspSeverity(diagnostic.category),
+          code: diagnostic.code,
+          source: "deno-ts",
+          message: ts.flattenDiagnosticMessageText(
+            diagnostic.messageText,
+            "\n"
+          ),
+        })),
+      ];
+    }
+    case "hover": {
+      const info = service.getQuickInfoAtPosition(specifier, pos);
+      if (!info) {
+        return null;
+      }
+      const signature = ts.displayPartsToString(info.displayParts);
+      const documentation = ts.displayPartsToString(info.documentation);
+      const tags = (info.tags ?? [])
+        .map(({ name, text }) => `*@${name}*${text ? ` - ${text}` : ""}`)
+        .join("\n\n");
+      const value = [
+        "```typescript\n" + signature + "\n```",
+        documentation,
+        tags,
+      ]
+        .filter((part) => part.length > 0)
+        .join("\n\n");
+      const { start, length } = info.textSpan;
+      return {
+        contents: { kind: "markdown", value },
+        range: toLspRange(sourceFile, start, length),
+      };
+    }
+    case "definition": {
+      const definitions = service.getDefinitionAtPosition(specifier, pos) ?? [];
+      return definitions.map(({ fileName, textSpan }) => {
+        const definitionFile = program.getSourceFile(fileName);
+        return {
+          uri: fileName,
+          range: definitionFile
+            ? toLspRange(definitionFile, textSpan.start, textSpan.length)
+            : ZERO_RANGE,
+        };
+      });
+    }
+  }
+}
+
+/** The compiler worker serves every compile request of the program, one
+ * after the other, but modules may change between requests, so only the
+ * bundled libraries are kept from previous ones. */
+function resetModuleCaches(): void {
+  for (const [url, sourceFile] of SOURCE_FILE_CACHE) {
+    if (!sourceFile.filename.startsWith(ASSETS)) {
+      SOURCE_FILE_CACHE.delete(url);
+    }
+  }
+  RESOLVED_SPECIFIER_CACHE.clear();
+}
+
 async function tsCompilerOnMessage({
   data: request,
 }: {
   data: CompilerRequest;
 }): Promise<void> {
+  resetModuleCaches();
   switch (request.type) {
     case CompilerRequestType.Compile: {
       const result = await compile(request as CompilerRequestCompile);
@@ -1563,6 +2618,18 @@ async function tsCompilerOnMessage({
       globalThis.postMessage(result);
       break;
     }
+    case CompilerRequestType.RuntimeGraph: {
+      const result = await runtimeGraph(request as CompilerRequestRuntimeGraph);
+      globalThis.postMessage(result);
+      break;
+    }
+    case CompilerRequestType.LanguageService: {
+      const result = await languageService(
+        request as CompilerRequestLanguageService
+      );
+      globalThis.postMessage(result);
+      break;
+    }
     default:
       util.log(
         `!!! unhandled CompilerRequestType: ${
@@ -1570,7 +2637,6 @@ async function tsCompilerOnMessage({
         } (${CompilerRequestType[(request as CompilerRequest).type]})`
       );
   }
-  // Currently Rust shuts down worker after single request
 }
 
 function bootstrapTsCompilerRuntime(): void {
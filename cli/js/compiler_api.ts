@@ -6,7 +6,10 @@
 import { DiagnosticItem } from "./diagnostics.ts";
 import * as util from "./util.ts";
 import * as runtimeCompilerOps from "./ops/runtime_compiler.ts";
-import { TranspileOnlyResult } from "./ops/runtime_compiler.ts";
+import {
+  GraphResponse,
+  TranspileOnlyResult,
+} from "./ops/runtime_compiler.ts";
 import { CompilerOptions } from "./compiler_options.ts";
 
 function checkRelative(specifier: string): string {
@@ -82,3 +85,67 @@ export async function bundle(
     result.diagnostics.length === 0 ? undefined : result.diagnostics;
   return [maybeDiagnostics, result.output];
 }
+
+export interface EmitOptions {
+  bundle?: "esm";
+  check?: boolean;
+  compilerOptions?: CompilerOptions;
+  sources?: Record<string, string>;
+}
+
+export interface EmitResult {
+  diagnostics: DiagnosticItem[];
+  files: Record<string, string>;
+}
+
+/** Name of the emitted file of a bundle in `EmitResult.files`. */
+const BUNDLE_FILE_NAME = "deno:///bundle.js";
+
+export async function emit(
+  rootSpecifier: string,
+  options: EmitOptions = {}
+): Promise<EmitResult> {
+  const { bundle, check = true, compilerOptions = {}, sources } = options;
+  const payload = {
+    rootName: sources ? rootSpecifier : checkRelative(rootSpecifier),
+    sources,
+    options: JSON.stringify(compilerOptions),
+    bundle: bundle === "esm",
+    check,
+  };
+  util.log("Deno.emit", {
+    rootName: payload.rootName,
+    sources: !!sources,
+    bundle,
+    check,
+  });
+  const result = await runtimeCompilerOps.emit(payload);
+  const files: Record<string, string> = {};
+  if (payload.bundle) {
+    util.assert(result.output != null);
+    files[BUNDLE_FILE_NAME] = result.output;
+  } else {
+    util.assert(result.emitMap);
+    for (const [key, emittedSource] of Object.entries(result.emitMap)) {
+      files[key] = emittedSource.contents;
+    }
+  }
+  return { diagnostics: result.diagnostics, files };
+}
+
+export interface GraphOptions {
+  sources?: Record<string, string>;
+}
+
+export function graph(
+  rootSpecifier: string,
+  options: GraphOptions = {}
+): Promise<GraphResponse> {
+  const { sources } = options;
+  const payload = {
+    rootName: sources ? rootSpecifier : checkRelative(rootSpecifier),
+    sources,
+  };
+  util.log("Deno.graph", { rootName: payload.rootName, sources: !!sources });
+  return runtimeCompilerOps.graph(payload);
+}
@@ -56,11 +56,22 @@ export {
   makeTempFile,
   MakeTempOptions,
 } from "./ops/fs/make_temp.ts";
-export { metrics, Metrics } from "./ops/runtime.ts";
+export {
+  metrics,
+  Metrics,
+  MetricsHistogram,
+  OpMetrics,
+} from "./ops/runtime.ts";
 export { mkdirSync, mkdir, MkdirOptions } from "./ops/fs/mkdir.ts";
 export { connect, listen, Listener, Conn } from "./net.ts";
 export { dir, env, exit, execPath } from "./ops/os.ts";
-export { run, RunOptions, Process, ProcessStatus } from "./process.ts";
+export {
+  run,
+  RunOptions,
+  Process,
+  ProcessOutput,
+  ProcessStatus,
+} from "./process.ts";
 export { DirEntry, readDirSync, readDir } from "./ops/fs/read_dir.ts";
 export { readFileSync, readFile } from "./read_file.ts";
 export { readTextFileSync, readTextFile } from "./read_text_file.ts";
@@ -74,7 +85,11 @@ export { connectTls, listenTls } from "./tls.ts";
 export { truncateSync, truncate } from "./ops/fs/truncate.ts";
 export { isatty } from "./ops/tty.ts";
 export { version } from "./version.ts";
-export { writeFileSync, writeFile, WriteFileOptions } from "./write_file.ts";
+export {
+  writeFileSync,
+  writeFile,
+  WriteFileOptions,
+} from "./ops/fs/write_file.ts";
 export { writeTextFileSync, writeTextFile } from "./write_text_file.ts";
 export const args: string[] = [];
 export { TestDefinition, test } from "./testing.ts";
@@ -5,17 +5,61 @@
 export { umask } from "./ops/fs/umask.ts";
 export { linkSync, link } from "./ops/fs/link.ts";
 export { symlinkSync, symlink } from "./ops/fs/symlink.ts";
-export { dir, loadavg, osRelease, hostname } from "./ops/os.ts";
+export {
+  dir,
+  loadavg,
+  osRelease,
+  hostname,
+  systemMemoryInfo,
+  SystemMemoryInfo,
+  memoryUsage,
+  MemoryUsage,
+  networkInterfaces,
+  NetworkInterfaceInfo,
+} from "./ops/os.ts";
 export { openPlugin } from "./ops/plugins.ts";
-export { transpileOnly, compile, bundle } from "./compiler_api.ts";
+export { openKv, Kv, KvListIterator, AtomicOperation } from "./kv.ts";
+export {
+  dlopen,
+  DynamicLibrary,
+  ForeignFunction,
+  NativeType,
+  UnsafePointer,
+  UnsafePointerView,
+} from "./ffi.ts";
+export {
+  transpileOnly,
+  compile,
+  bundle,
+  emit,
+  EmitOptions,
+  EmitResult,
+  graph,
+  GraphOptions,
+} from "./compiler_api.ts";
 export { applySourceMap, formatDiagnostics } from "./ops/errors.ts";
 export { signal, signals, Signal, SignalStream } from "./signals.ts";
 export { setRaw } from "./ops/tty.ts";
+export { readLines } from "./read_lines.ts";
+export {
+  readableStreamForRid,
+  writableStreamForRid,
+  resourceForReadableStream,
+} from "./io_streams.ts";
 export { utimeSync, utime } from "./ops/fs/utime.ts";
 export { ShutdownMode, shutdown } from "./net.ts";
-export { listen, listenDatagram, connect } from "./net_unstable.ts";
+export {
+  listen,
+  listenDatagram,
+  connect,
+  resolveDns,
+} from "./net_unstable.ts";
 export { startTls } from "./tls.ts";
+export { HttpClient, createHttpClient } from "./http_client.ts";
 export { kill } from "./ops/process.ts";
+export { BenchDefinition, bench } from "./bench.ts";
+export { workers } from "./ops/worker_host.ts";
+export { performanceEntries } from "./ops/performance.ts";
 export {
   permissions,
   PermissionName,
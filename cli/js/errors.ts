@@ -1,4 +1,5 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { DOMExceptionImpl } from "./web/dom_exception.ts";
 
 // Warning! The values in this enum are duplicated in cli/op_error.rs
 // Update carefully!
@@ -24,6 +25,9 @@ export enum ErrorKind {
   TypeError = 21,
   Other = 22,
   Busy = 23,
+  Aborted = 24,
+  NotSupported = 25,
+  QuotaExceeded = 26,
 }
 
 export function getErrorClass(kind: ErrorKind): { new (msg: string): Error } {
@@ -70,6 +74,12 @@ export function getErrorClass(kind: ErrorKind): { new (msg: string): Error } {
       return Http;
     case ErrorKind.Busy:
       return Busy;
+    case ErrorKind.Aborted:
+      return AbortError;
+    case ErrorKind.NotSupported:
+      return NotSupported;
+    case ErrorKind.QuotaExceeded:
+      return QuotaExceededError;
   }
 }
 
@@ -181,6 +191,28 @@ class Busy extends Error {
     this.name = "Busy";
   }
 }
+class NotSupported extends Error {
+  constructor(msg: string) {
+    super(msg);
+    this.name = "NotSupported";
+  }
+}
+
+/** The "AbortError" `DOMException` that ops reject with when their
+ * `AbortSignal` is aborted. */
+export class AbortError extends DOMExceptionImpl {
+  constructor(msg = "The operation was aborted") {
+    super(msg, "AbortError");
+  }
+}
+
+/** The "QuotaExceededError" `DOMException` that storage ops fail with when
+ * a storage area is full. */
+class QuotaExceededError extends DOMExceptionImpl {
+  constructor(msg: string) {
+    super(msg, "QuotaExceededError");
+  }
+}
 
 export const errors = {
   NotFound: NotFound,
@@ -201,4 +233,5 @@ export const errors = {
   BadResource: BadResource,
   Http: Http,
   Busy: Busy,
+  NotSupported: NotSupported,
 };
@@ -0,0 +1,119 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as ffiOps from "./ops/ffi.ts";
+import { ForeignFunction, NativeType } from "./ops/ffi.ts";
+import { close } from "./ops/resources.ts";
+
+export { ForeignFunction, NativeType };
+
+type SymbolCall = (
+  ...args: Array<number | ArrayBufferView>
+) => number | null | Promise<number | null>;
+
+function toUint8Array(view: ArrayBufferView): Uint8Array {
+  if (view instanceof Uint8Array) {
+    return view;
+  }
+  return new Uint8Array(view.buffer, view.byteOffset, view.byteLength);
+}
+
+export class DynamicLibrary<S extends Record<string, ForeignFunction>> {
+  readonly rid: number;
+  readonly symbols = {} as { [K in keyof S]: SymbolCall };
+
+  constructor(path: string, symbols: S) {
+    this.rid = ffiOps.loadLibrary(path, symbols);
+    for (const name in symbols) {
+      const { parameters, nonblocking } = symbols[name];
+      const bufferIndex = parameters.indexOf("buffer");
+      const call = nonblocking ? ffiOps.callSymbolAsync : ffiOps.callSymbol;
+      this.symbols[name] = (
+        ...args: Array<number | ArrayBufferView>
+      ): number | null | Promise<number | null> => {
+        let buffer: Uint8Array | undefined;
+        const values = args.map((arg, i): number | null => {
+          if (i === bufferIndex) {
+            if (!ArrayBuffer.isView(arg)) {
+              throw new TypeError(`Argument ${i} must be an ArrayBufferView`);
+            }
+            buffer = toUint8Array(arg);
+            return null;
+          }
+          if (typeof arg !== "number") {
+            throw new TypeError(`Argument ${i} must be a number`);
+          }
+          return arg;
+        });
+        return call(this.rid, name, values, buffer);
+      };
+    }
+  }
+
+  close(): void {
+    close(this.rid);
+  }
+}
+
+export function dlopen<S extends Record<string, ForeignFunction>>(
+  path: string,
+  symbols: S
+): DynamicLibrary<S> {
+  return new DynamicLibrary(path, symbols);
+}
+
+export class UnsafePointer {
+  static of(buffer: ArrayBufferView): number {
+    return ffiOps.pointerOf(toUint8Array(buffer));
+  }
+}
+
+export class UnsafePointerView {
+  constructor(readonly pointer: number) {}
+
+  getUint8(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "u8");
+  }
+
+  getInt8(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "i8");
+  }
+
+  getUint16(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "u16");
+  }
+
+  getInt16(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "i16");
+  }
+
+  getUint32(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "u32");
+  }
+
+  getInt32(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "i32");
+  }
+
+  getFloat32(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "f32");
+  }
+
+  getFloat64(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "f64");
+  }
+
+  getPointer(offset = 0): number {
+    return ffiOps.readPointer(this.pointer, offset, "pointer");
+  }
+
+  set(type: NativeType, value: number, offset = 0): void {
+    ffiOps.writePointer(this.pointer, offset, type, value);
+  }
+
+  getCString(offset = 0): string {
+    return ffiOps.readCString(this.pointer, offset);
+  }
+
+  copyInto(destination: ArrayBufferView, offset = 0): void {
+    ffiOps.copyFromPointer(this.pointer, offset, toUint8Array(destination));
+  }
+}
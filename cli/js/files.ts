@@ -12,6 +12,7 @@ import {
 import { close } from "./ops/resources.ts";
 import { read, readSync, write, writeSync } from "./ops/io.ts";
 import { seek, seekSync } from "./ops/fs/seek.ts";
+import { readableStreamForRid, writableStreamForRid } from "./io_streams.ts";
 export { seek, seekSync } from "./ops/fs/seek.ts";
 import {
   open as opOpen,
@@ -65,8 +66,30 @@ export class File
     Seeker,
     SeekerSync,
     Closer {
+  #readable?: ReadableStream<Uint8Array>;
+  #writable?: WritableStream<Uint8Array>;
+
   constructor(readonly rid: number) {}
 
+  get readable(): ReadableStream<Uint8Array> {
+    if (!this.#readable) {
+      this.#readable = readableStreamForRid(this.rid, {
+        onCancel: (): void => this.close(),
+      });
+    }
+    return this.#readable;
+  }
+
+  get writable(): WritableStream<Uint8Array> {
+    if (!this.#writable) {
+      this.#writable = writableStreamForRid(this.rid, {
+        onClose: (): void => this.close(),
+        onAbort: (): void => this.close(),
+      });
+    }
+    return this.#writable;
+  }
+
   write(p: Uint8Array): Promise<number> {
     return write(this.rid, p);
   }
@@ -98,10 +121,19 @@ export class File
 
 class Stdin implements Reader, ReaderSync, Closer {
   readonly rid: number;
+  #readable?: ReadableStream<Uint8Array>;
+
   constructor() {
     this.rid = 0;
   }
 
+  get readable(): ReadableStream<Uint8Array> {
+    if (!this.#readable) {
+      this.#readable = readableStreamForRid(this.rid);
+    }
+    return this.#readable;
+  }
+
   read(p: Uint8Array): Promise<number | null> {
     return read(this.rid, p);
   }
@@ -117,10 +149,19 @@ class Stdin implements Reader, ReaderSync, Closer {
 
 class Stdout implements Writer, WriterSync, Closer {
   readonly rid: number;
+  #writable?: WritableStream<Uint8Array>;
+
   constructor() {
     this.rid = 1;
   }
 
+  get writable(): WritableStream<Uint8Array> {
+    if (!this.#writable) {
+      this.#writable = writableStreamForRid(this.rid);
+    }
+    return this.#writable;
+  }
+
   write(p: Uint8Array): Promise<number> {
     return write(this.rid, p);
   }
@@ -136,10 +177,19 @@ class Stdout implements Writer, WriterSync, Closer {
 
 export class Stderr implements Writer, WriterSync, Closer {
   readonly rid: number;
+  #writable?: WritableStream<Uint8Array>;
+
   constructor() {
     this.rid = 2;
   }
 
+  get writable(): WritableStream<Uint8Array> {
+    if (!this.#writable) {
+      this.#writable = writableStreamForRid(this.rid);
+    }
+    return this.#writable;
+  }
+
   write(p: Uint8Array): Promise<number> {
     return write(this.rid, p);
   }
@@ -5,23 +5,29 @@ import "./lib.deno.shared_globals.d.ts";
 import * as abortController from "./web/abort_controller.ts";
 import * as abortSignal from "./web/abort_signal.ts";
 import * as blob from "./web/blob.ts";
+import * as cacheStorage from "./web/cache_storage.ts";
+import * as compression from "./web/compression.ts";
+import * as broadcastChannel from "./web/broadcast_channel.ts";
 import * as consoleTypes from "./web/console.ts";
 import * as csprng from "./ops/get_random_values.ts";
 import * as promiseTypes from "./web/promise.ts";
 import * as customEvent from "./web/custom_event.ts";
 import * as domException from "./web/dom_exception.ts";
 import * as domFile from "./web/dom_file.ts";
+import * as errorEvent from "./web/error_event.ts";
 import * as event from "./web/event.ts";
 import * as eventTarget from "./web/event_target.ts";
 import * as formData from "./web/form_data.ts";
 import * as fetchTypes from "./web/fetch.ts";
 import * as headers from "./web/headers.ts";
+import * as promiseRejectionEvent from "./web/promise_rejection_event.ts";
 import * as textEncoding from "./web/text_encoding.ts";
 import * as timers from "./web/timers.ts";
 import * as url from "./web/url.ts";
 import * as urlSearchParams from "./web/url_search_params.ts";
 import * as workers from "./web/workers.ts";
 import * as performanceUtil from "./web/performance.ts";
+import * as navigatorUtil from "./web/navigator.ts";
 import * as request from "./web/request.ts";
 import * as readableStream from "./web/streams/readable_stream.ts";
 import * as transformStream from "./web/streams/transform_stream.ts";
@@ -65,6 +71,8 @@ declare global {
   interface EvalErrorInfo {
     isNativeError: boolean;
     isCompileError: boolean;
+    // Set if the evaluation was interrupted by the embedder.
+    isInterrupted?: boolean;
     // eslint-disable-next-line @typescript-eslint/no-explicit-any
     thrown: any;
   }
@@ -103,6 +111,12 @@ declare global {
 
     setMacrotaskCallback(cb: () => boolean): void;
 
+    setPromiseRejectCallback(
+      cb: (promise: Promise<unknown>, reason: unknown) => boolean
+    ): void;
+
+    reportUncaughtException(error: unknown): void;
+
     shared: SharedArrayBuffer;
 
     evalContext(
@@ -124,6 +138,23 @@ declare global {
      */
     getPromiseDetails<T>(promise: Promise<T>): promiseTypes.PromiseDetails<T>;
 
+    /**
+     * Get the entries of a map as keys and values, ie.
+     * `[key0, value0, key1, value1, ...]`, read by V8 without calling the
+     * map's iterator methods.
+     *
+     * Throws `TypeError` if argument isn't a map.
+     */
+    getMapEntries(map: Map<unknown, unknown>): unknown[];
+
+    /**
+     * Get the number of elements of a typed array from the length of its view
+     * of the buffer, without calling the `length` getter.
+     *
+     * Throws `TypeError` if argument isn't a typed array.
+     */
+    getTypedArrayLength(typedArray: ArrayBufferView): number;
+
     decode(bytes: Uint8Array): string;
     encode(text: string): Uint8Array;
   }
@@ -136,8 +167,8 @@ declare global {
   var Deno: {
     core: DenoCore;
   };
-  var onload: ((e: Event) => void) | undefined;
-  var onunload: ((e: Event) => void) | undefined;
+  var onload: ((e: Event) => void) | null;
+  var onunload: ((e: Event) => void) | null;
 
   // These methods are used to prepare different runtime
   // environments. After bootrapping, this namespace
@@ -150,18 +181,10 @@ declare global {
     tsCompilerRuntime: (() => void) | undefined;
   };
 
-  var onerror:
-    | ((
-        msg: string,
-        source: string,
-        lineno: number,
-        colno: number,
-        e: Event
-      ) => boolean | void)
-    | undefined;
-
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   var onmessage: ((e: { data: any }) => Promise<void> | void) | undefined;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  var onmessageerror: ((e: { data: any }) => void) | undefined;
   // Called in compiler
   var close: () => void;
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
@@ -219,6 +242,12 @@ export const windowOrWorkerGlobalScopeProperties = {
   AbortController: nonEnumerable(abortController.AbortControllerImpl),
   AbortSignal: nonEnumerable(abortSignal.AbortSignalImpl),
   Blob: nonEnumerable(blob.DenoBlob),
+  BroadcastChannel: nonEnumerable(broadcastChannel.BroadcastChannelImpl),
+  Cache: nonEnumerable(cacheStorage.CacheImpl),
+  CacheStorage: nonEnumerable(cacheStorage.CacheStorageImpl),
+  caches: getterOnly(cacheStorage.getCacheStorage),
+  CompressionStream: nonEnumerable(compression.CompressionStreamImpl),
+  DecompressionStream: nonEnumerable(compression.DecompressionStreamImpl),
   ByteLengthQueuingStrategy: nonEnumerable(
     queuingStrategy.ByteLengthQueuingStrategyImpl
   ),
@@ -227,8 +256,12 @@ export const windowOrWorkerGlobalScopeProperties = {
   File: nonEnumerable(domFile.DomFileImpl),
   CustomEvent: nonEnumerable(customEvent.CustomEventImpl),
   DOMException: nonEnumerable(domException.DOMExceptionImpl),
+  ErrorEvent: nonEnumerable(errorEvent.ErrorEventImpl),
   Event: nonEnumerable(event.EventImpl),
   EventTarget: nonEnumerable(eventTarget.EventTargetImpl),
+  PromiseRejectionEvent: nonEnumerable(
+    promiseRejectionEvent.PromiseRejectionEventImpl
+  ),
   URL: nonEnumerable(url.URLImpl),
   URLSearchParams: nonEnumerable(urlSearchParams.URLSearchParamsImpl),
   Headers: nonEnumerable(headers.HeadersImpl),
@@ -239,9 +272,15 @@ export const windowOrWorkerGlobalScopeProperties = {
   TransformStream: nonEnumerable(transformStream.TransformStreamImpl),
   Request: nonEnumerable(request.Request),
   Response: nonEnumerable(fetchTypes.Response),
+  navigator: readOnly(new navigatorUtil.NavigatorImpl()),
   performance: writable(new performanceUtil.Performance()),
+  PerformanceEntry: nonEnumerable(performanceUtil.PerformanceEntryImpl),
+  PerformanceMark: nonEnumerable(performanceUtil.PerformanceMarkImpl),
+  PerformanceMeasure: nonEnumerable(performanceUtil.PerformanceMeasureImpl),
   Worker: nonEnumerable(workers.WorkerImpl),
   WritableStream: nonEnumerable(writableStream.WritableStreamImpl),
+  onerror: eventTarget.eventHandlerProperty("error"),
+  onunhandledrejection: eventTarget.eventHandlerProperty("unhandledrejection"),
 };
 
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
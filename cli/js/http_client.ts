@@ -0,0 +1,22 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import {
+  createHttpClient as opCreateHttpClient,
+  CreateHttpClientOptions,
+} from "./ops/fetch.ts";
+import { close } from "./ops/resources.ts";
+
+export { CreateHttpClientOptions };
+
+export class HttpClient {
+  constructor(readonly rid: number) {}
+
+  close(): void {
+    close(this.rid);
+  }
+}
+
+export function createHttpClient(
+  options: CreateHttpClientOptions = {}
+): HttpClient {
+  return new HttpClient(opCreateHttpClient(options));
+}
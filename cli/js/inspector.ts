@@ -0,0 +1,59 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+// Drives the profilers of the isolate's own inspector, which exists when
+// Deno runs with `--inspect`, `deno test --coverage` or `deno bench
+// --profile`, whether or not a debugger is connected.
+
+import { exposeForTest } from "./internals.ts";
+import { post, report } from "./ops/inspector.ts";
+
+// Posts a `Profiler` or `HeapProfiler` message and returns its result.
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function postMessage(method: string, params?: object): any {
+  return post(method, params).result;
+}
+
+function startProfiling(): void {
+  post("Profiler.enable");
+  post("Profiler.start");
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function stopProfiling(): any {
+  return post("Profiler.stop").result.profile;
+}
+
+function startCoverage(): void {
+  post("Profiler.enable");
+  post("Profiler.startPreciseCoverage", { callCount: true, detailed: true });
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function takeCoverage(): any[] {
+  return post("Profiler.takePreciseCoverage").result.result;
+}
+
+// Returns the heap snapshot as JSON, which is sent in chunks.
+function takeHeapSnapshot(): string {
+  const { notifications } = post("HeapProfiler.takeHeapSnapshot", {
+    reportProgress: false,
+  });
+  return notifications
+    .filter(({ method }) => method === "HeapProfiler.addHeapSnapshotChunk")
+    .map(({ params }) => params.chunk)
+    .join("");
+}
+
+export const inspector = {
+  post: postMessage,
+  startProfiling,
+  stopProfiling,
+  startCoverage,
+  takeCoverage,
+  takeHeapSnapshot,
+  // Writes the collected coverage or CPU profile to where `--coverage` or
+  // `--profile` was given, and returns the path of the file.
+  report,
+};
+
+exposeForTest("inspector", inspector);
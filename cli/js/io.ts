@@ -3,7 +3,11 @@
 // Documentation liberally lifted from them too.
 // Thank you! We love Go!
 
+import { copy as copyOp } from "./ops/io.ts";
+
 const DEFAULT_BUFFER_SIZE = 32 * 1024;
+// How often `copy()` reports progress between resources.
+const COPY_PROGRESS_INTERVAL = 1024 * 1024;
 
 // Seek whence values.
 // https://golang.org/pkg/io/#pkg-constants
@@ -49,14 +53,39 @@ export interface SeekerSync {
   seekSync(offset: number, whence: SeekMode): number;
 }
 
+function hasRid(x: unknown): x is { rid: number } {
+  return typeof (x as { rid?: unknown }).rid === "number";
+}
+
 export async function copy(
   src: Reader,
   dst: Writer,
   options?: {
     bufSize?: number;
+    onProgress?: (copied: number) => void;
   }
 ): Promise<number> {
   let n = 0;
+  const onProgress = options?.onProgress;
+  if (hasRid(src) && hasRid(dst)) {
+    // Both are resources, copy between them without going through JS.
+    const limit = onProgress ? COPY_PROGRESS_INTERVAL : undefined;
+    while (true) {
+      const { copied, done } = await copyOp(
+        src.rid,
+        dst.rid,
+        options?.bufSize,
+        limit
+      );
+      n += copied;
+      if (copied > 0) {
+        onProgress?.(n);
+      }
+      if (done) {
+        return n;
+      }
+    }
+  }
   const bufSize = options?.bufSize ?? DEFAULT_BUFFER_SIZE;
   const b = new Uint8Array(bufSize);
   let gotEOF = false;
@@ -70,6 +99,7 @@ export async function copy(
         nwritten += await dst.write(b.subarray(nwritten, result));
       }
       n += nwritten;
+      onProgress?.(n);
     }
   }
   return n;
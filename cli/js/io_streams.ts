@@ -0,0 +1,138 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+// Web stream adapters for resources that support `read`/`write` ops. Both
+// directions apply backpressure: the readable only reads from the resource
+// when its consumer pulls, and the writable only writes one chunk at a time,
+// so `writer.ready` stays pending while the resource can't keep up.
+//
+// In the other direction, `resourceForReadableStream()` backs a resource with
+// a JS stream, so anything taking a rid can read from it.
+
+import { errors } from "./errors.ts";
+import { read, write } from "./ops/io.ts";
+import { close } from "./ops/resources.ts";
+import * as streamResourceOps from "./ops/stream_resource.ts";
+import { ReadableStreamImpl } from "./web/streams/readable_stream.ts";
+import { WritableStreamImpl } from "./web/streams/writable_stream.ts";
+import {
+  ByteLengthQueuingStrategyImpl,
+  CountQueuingStrategyImpl,
+} from "./web/streams/queuing_strategy.ts";
+
+const DEFAULT_CHUNK_SIZE = 16 * 1024;
+
+// Number of chunks read ahead of the consumer.
+const READ_AHEAD_CHUNKS = 1;
+
+// Bytes queued by the writable before `writer.ready` starts blocking.
+const WRITE_HIGH_WATER_MARK = 64 * 1024;
+
+export interface ReadableStreamForRidOptions {
+  chunkSize?: number;
+  /** A cancel handle aborting pending reads. */
+  cancelRid?: number;
+  /** Called when the consumer cancels the stream. */
+  onCancel?: () => void;
+}
+
+export function readableStreamForRid(
+  rid: number,
+  {
+    chunkSize = DEFAULT_CHUNK_SIZE,
+    cancelRid,
+    onCancel,
+  }: ReadableStreamForRidOptions = {}
+): ReadableStream<Uint8Array> {
+  return new ReadableStreamImpl<Uint8Array>(
+    {
+      async pull(controller): Promise<void> {
+        const buf = new Uint8Array(chunkSize);
+        const n = await read(rid, buf, cancelRid);
+        if (n === null) {
+          controller.close();
+        } else {
+          controller.enqueue(buf.subarray(0, n));
+        }
+      },
+      cancel(): void {
+        onCancel?.();
+      },
+    },
+    new CountQueuingStrategyImpl({ highWaterMark: READ_AHEAD_CHUNKS })
+  );
+}
+
+export interface WritableStreamForRidOptions {
+  /** Called once all queued chunks have been written and the stream is
+   * closed. */
+  onClose?: () => void;
+  /** Called when the stream is aborted. */
+  onAbort?: () => void;
+}
+
+export function writableStreamForRid(
+  rid: number,
+  { onClose, onAbort }: WritableStreamForRidOptions = {}
+): WritableStream<Uint8Array> {
+  return new WritableStreamImpl<Uint8Array>(
+    {
+      async write(chunk): Promise<void> {
+        let nwritten = 0;
+        while (nwritten < chunk.length) {
+          nwritten += await write(rid, chunk.subarray(nwritten));
+        }
+      },
+      close(): void {
+        onClose?.();
+      },
+      abort(): void {
+        onAbort?.();
+      },
+    },
+    new ByteLengthQueuingStrategyImpl({ highWaterMark: WRITE_HIGH_WATER_MARK })
+  );
+}
+
+async function pumpToSink(
+  reader: ReadableStreamDefaultReader<Uint8Array>,
+  sinkRid: number
+): Promise<void> {
+  try {
+    while (true) {
+      const { value, done } = await reader.read();
+      if (done) {
+        break;
+      }
+      if (!(value instanceof Uint8Array)) {
+        throw new TypeError("Stream chunks must be Uint8Arrays");
+      }
+      if (value.byteLength > 0) {
+        await streamResourceOps.write(sinkRid, value);
+      }
+    }
+  } catch (error) {
+    if (error instanceof errors.BrokenPipe) {
+      // The resource was closed before the stream ended.
+      reader.cancel().catch((): void => {});
+    } else {
+      reader.cancel(error).catch((): void => {});
+      await streamResourceOps
+        .error(sinkRid, String(error))
+        .catch((): void => {});
+    }
+  } finally {
+    close(sinkRid);
+  }
+}
+
+/** Returns the rid of a resource reading the chunks of `stream`. The stream
+ * is only read as the resource is, and closing the resource cancels it. If the
+ * stream errors, so do reads of the resource. */
+export function resourceForReadableStream(
+  stream: ReadableStream<Uint8Array>
+): number {
+  const reader = stream.getReader();
+  const { rid, sinkRid } = streamResourceOps.open();
+  pumpToSink(reader, sinkRid);
+  return rid;
+}
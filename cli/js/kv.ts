@@ -0,0 +1,305 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as kvOps from "./ops/kv.ts";
+import { close } from "./ops/resources.ts";
+
+export type KvKeyPart = Uint8Array | string | number | boolean;
+export type KvKey = readonly KvKeyPart[];
+
+export interface KvEntry<T> {
+  key: KvKey;
+  value: T;
+  versionstamp: string;
+}
+
+export type KvEntryMaybe<T> =
+  | KvEntry<T>
+  | { key: KvKey; value: null; versionstamp: null };
+
+export type KvListSelector =
+  | { prefix: KvKey }
+  | { prefix: KvKey; start: KvKey }
+  | { prefix: KvKey; end: KvKey }
+  | { start: KvKey; end: KvKey };
+
+export interface KvListOptions {
+  limit?: number;
+  cursor?: string;
+  reverse?: boolean;
+  batchSize?: number;
+}
+
+export interface AtomicCheck {
+  key: KvKey;
+  versionstamp: string | null;
+}
+
+export interface KvCommitResult {
+  ok: true;
+  versionstamp: string;
+}
+
+export interface KvCommitError {
+  ok: false;
+}
+
+function toKeyPart(part: KvKeyPart): kvOps.KeyPart {
+  if (part instanceof Uint8Array) {
+    return { type: "bytes", value: Array.from(part) };
+  }
+  switch (typeof part) {
+    case "string":
+      return { type: "string", value: part };
+    case "number":
+      if (!Number.isFinite(part)) {
+        throw new TypeError("Number key parts must be finite");
+      }
+      return { type: "number", value: part };
+    case "boolean":
+      return { type: "boolean", value: part };
+    default:
+      throw new TypeError(
+        "Key parts must be Uint8Arrays, strings, numbers or booleans"
+      );
+  }
+}
+
+function toKey(key: KvKey): kvOps.Key {
+  if (!Array.isArray(key)) {
+    throw new TypeError("Keys must be arrays");
+  }
+  return key.map(toKeyPart);
+}
+
+function fromKey(key: kvOps.Key): KvKey {
+  return key.map((part): KvKeyPart =>
+    part.type === "bytes" ? new Uint8Array(part.value) : part.value
+  );
+}
+
+function toEntryMaybe<T>(
+  key: KvKey,
+  entry: kvOps.Entry | null
+): KvEntryMaybe<T> {
+  if (entry === null) {
+    return { key, value: null, versionstamp: null };
+  }
+  return { key, value: entry.value as T, versionstamp: entry.versionstamp };
+}
+
+function checkValue(value: unknown): void {
+  if (value === undefined || typeof value === "function") {
+    throw new TypeError("Values must be serializable to JSON");
+  }
+}
+
+/** Iterates over the entries of a selector, fetching them in batches. */
+export class KvListIterator<T> implements AsyncIterableIterator<KvEntry<T>> {
+  #rid: number;
+  #selector: kvOps.Selector;
+  #reverse: boolean;
+  #batchSize: number;
+  #remaining: number;
+  #cursor: string | undefined;
+  #buffered: kvOps.ListedEntry[] = [];
+  #exhausted = false;
+
+  constructor(rid: number, selector: KvListSelector, options: KvListOptions) {
+    const { limit, cursor, reverse = false, batchSize = 100 } = options;
+    this.#rid = rid;
+    this.#selector = {
+      prefix: "prefix" in selector ? toKey(selector.prefix) : undefined,
+      start: "start" in selector ? toKey(selector.start) : undefined,
+      end: "end" in selector ? toKey(selector.end) : undefined,
+    };
+    this.#reverse = reverse;
+    this.#batchSize = Math.max(1, batchSize);
+    this.#remaining = limit ?? Infinity;
+    this.#cursor = cursor;
+  }
+
+  /** Where to continue listing from, with the `cursor` option, after the
+   * entries iterated so far. */
+  get cursor(): string {
+    if (this.#cursor === undefined) {
+      throw new Error("Cannot get the cursor before the first entry");
+    }
+    return this.#cursor;
+  }
+
+  async next(): Promise<IteratorResult<KvEntry<T>>> {
+    if (this.#remaining <= 0) {
+      return { value: undefined, done: true };
+    }
+    if (this.#buffered.length === 0 && !this.#exhausted) {
+      const limit = Math.min(this.#batchSize, this.#remaining);
+      this.#buffered = await kvOps.list(
+        this.#rid,
+        this.#selector,
+        limit,
+        this.#cursor,
+        this.#reverse
+      );
+      this.#exhausted = this.#buffered.length < limit;
+    }
+    const entry = this.#buffered.shift();
+    if (!entry) {
+      return { value: undefined, done: true };
+    }
+    this.#remaining--;
+    this.#cursor = entry.cursor;
+    const { key, value, versionstamp } = entry;
+    return {
+      value: { key: fromKey(key), value: value as T, versionstamp },
+      done: false,
+    };
+  }
+
+  [Symbol.asyncIterator](): AsyncIterableIterator<KvEntry<T>> {
+    return this;
+  }
+}
+
+/** Checks and mutations committed together by `commit()`. */
+export class AtomicOperation {
+  #rid: number;
+  #checks: kvOps.Check[] = [];
+  #mutations: kvOps.Mutation[] = [];
+
+  constructor(rid: number) {
+    this.#rid = rid;
+  }
+
+  check(...checks: AtomicCheck[]): this {
+    for (const { key, versionstamp } of checks) {
+      this.#checks.push({ key: toKey(key), versionstamp });
+    }
+    return this;
+  }
+
+  set(key: KvKey, value: unknown): this {
+    checkValue(value);
+    this.#mutations.push({ type: "set", key: toKey(key), value });
+    return this;
+  }
+
+  delete(key: KvKey): this {
+    this.#mutations.push({ type: "delete", key: toKey(key) });
+    return this;
+  }
+
+  async commit(): Promise<KvCommitResult | KvCommitError> {
+    const versionstamp = await kvOps.atomic(
+      this.#rid,
+      this.#checks,
+      this.#mutations
+    );
+    if (versionstamp === null) {
+      return { ok: false };
+    }
+    return { ok: true, versionstamp };
+  }
+}
+
+/** Yields the entries of `keys` now, then each time any of them changes,
+ * until `return()` is called or the database is closed. */
+class KvWatcher<T> implements AsyncIterableIterator<Array<KvEntryMaybe<T>>> {
+  #rid: number;
+  #keys: KvKey[];
+  #opKeys: kvOps.Key[];
+  #versionstamps: Array<string | null> | undefined;
+  #done = false;
+
+  constructor(rid: number, keys: KvKey[]) {
+    this.#rid = rid;
+    this.#keys = keys;
+    this.#opKeys = keys.map(toKey);
+  }
+
+  async next(): Promise<IteratorResult<Array<KvEntryMaybe<T>>>> {
+    if (this.#done) {
+      return { value: undefined, done: true };
+    }
+    const entries = this.#versionstamps
+      ? await kvOps.watch(this.#rid, this.#opKeys, this.#versionstamps)
+      : await kvOps.get(this.#rid, this.#opKeys);
+    // `null` means that the database was closed.
+    if (entries === null || this.#done) {
+      this.#done = true;
+      return { value: undefined, done: true };
+    }
+    this.#versionstamps = entries.map((entry): string | null =>
+      entry ? entry.versionstamp : null
+    );
+    const value = entries.map((entry, i): KvEntryMaybe<T> =>
+      toEntryMaybe(this.#keys[i], entry)
+    );
+    return { value, done: false };
+  }
+
+  return(): Promise<IteratorResult<Array<KvEntryMaybe<T>>>> {
+    this.#done = true;
+    return Promise.resolve({ value: undefined, done: true });
+  }
+
+  [Symbol.asyncIterator](): AsyncIterableIterator<Array<KvEntryMaybe<T>>> {
+    return this;
+  }
+}
+
+export class Kv {
+  readonly #rid: number;
+
+  constructor(rid: number) {
+    this.#rid = rid;
+  }
+
+  async get<T = unknown>(key: KvKey): Promise<KvEntryMaybe<T>> {
+    const [entry] = await kvOps.get(this.#rid, [toKey(key)]);
+    return toEntryMaybe<T>(key, entry);
+  }
+
+  async getMany<T = unknown>(keys: KvKey[]): Promise<Array<KvEntryMaybe<T>>> {
+    const entries = await kvOps.get(this.#rid, keys.map(toKey));
+    return entries.map((entry, i): KvEntryMaybe<T> =>
+      toEntryMaybe(keys[i], entry)
+    );
+  }
+
+  async set(key: KvKey, value: unknown): Promise<KvCommitResult> {
+    const result = await this.atomic().set(key, value).commit();
+    return result as KvCommitResult;
+  }
+
+  async delete(key: KvKey): Promise<void> {
+    await this.atomic().delete(key).commit();
+  }
+
+  list<T = unknown>(
+    selector: KvListSelector,
+    options: KvListOptions = {}
+  ): KvListIterator<T> {
+    return new KvListIterator(this.#rid, selector, options);
+  }
+
+  atomic(): AtomicOperation {
+    return new AtomicOperation(this.#rid);
+  }
+
+  watch<T = unknown>(
+    keys: KvKey[]
+  ): AsyncIterableIterator<Array<KvEntryMaybe<T>>> {
+    return new KvWatcher(this.#rid, keys);
+  }
+
+  close(): void {
+    close(this.#rid);
+  }
+}
+
+export function openKv(path?: string): Promise<Kv> {
+  try {
+    return Promise.resolve(new Kv(kvOps.open(path)));
+  } catch (error) {
+    return Promise.reject(error);
+  }
+}
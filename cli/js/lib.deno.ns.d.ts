@@ -24,12 +24,14 @@ declare namespace Deno {
     BadResource: ErrorConstructor;
     Http: ErrorConstructor;
     Busy: ErrorConstructor;
+    NotSupported: ErrorConstructor;
   };
 
   /** The current process id of the runtime. */
   export let pid: number;
 
-  /** Reflects the `NO_COLOR` environment variable.
+  /** True if colored output is disabled, either by the `NO_COLOR`
+   * environment variable or the `--no-color` flag.
    *
    * See: https://no-color.org/ */
   export let noColor: boolean;
@@ -45,6 +47,34 @@ declare namespace Deno {
      * after the test has exactly the same contents as before the test. Defaults
      * to true. */
     sanitizeResources?: boolean;
+    /** Run the test in a worker that only has the given subset of the
+     * permissions of the process. Omitted permissions are inherited, `false`
+     * denies a permission and a list of paths or hosts narrows it down. The
+     * module declaring the test is loaded again in the worker. Asking for a
+     * permission the process doesn't have fails the test.
+     *
+     * Requires `--unstable`.
+     *
+     *          Deno.test({
+     *            name: "reads fixtures only",
+     *            permissions: { net: false, read: ["./fixtures"] },
+     *            fn(): void {
+     *              Deno.readTextFileSync("./fixtures/data.json");
+     *            },
+     *          });
+     */
+    permissions?: TestPermissions;
+  }
+
+  export interface TestPermissions {
+    read?: boolean | string[];
+    write?: boolean | string[];
+    net?: boolean | string[];
+    env?: boolean;
+    run?: boolean;
+    plugin?: boolean;
+    ffi?: boolean;
+    hrtime?: boolean;
   }
 
   /** Register a test which will be run when `deno test` is used on the command
@@ -100,6 +130,10 @@ declare namespace Deno {
   /** Exit the Deno process with optional exit code. If no exit code is supplied
    * then Deno will exit with return code of 0.
    *
+   * Execution of JavaScript stops immediately, then the `unload` event is
+   * dispatched and pending async operations are given `--shutdown-timeout`
+   * milliseconds (0 by default) to settle before the process exits.
+   *
    *       Deno.exit(5);
    */
   export function exit(code?: number): never;
@@ -294,6 +328,17 @@ declare namespace Deno {
    *       const bytesCopied1 = await Deno.copy(source, Deno.stdout);
    *       const bytesCopied2 = await Deno.copy(source, buffer);
    *
+   * When both `src` and `dst` are resources, like files and connections, the
+   * data is copied between them without going through JavaScript.
+   *
+   * `onProgress` is called with the number of bytes copied so far, every 1MB
+   * between resources and after every chunk otherwise.
+   *
+   *       const file = await Deno.open("my_file.txt");
+   *       await Deno.copy(file, Deno.stdout, {
+   *         onProgress: (copied) => console.error(`${copied} bytes`),
+   *       });
+   *
    * @param src The source to copy from
    * @param dst The destination to copy to
    * @param options Can be used to tune size of the buffer. Default size is 32kB
@@ -303,6 +348,7 @@ declare namespace Deno {
     dst: Writer,
     options?: {
       bufSize?: number;
+      onProgress?: (copied: number) => void;
     }
   ): Promise<number>;
 
@@ -551,6 +597,16 @@ declare namespace Deno {
       SeekerSync,
       Closer {
     readonly rid: number;
+    /** The contents of the file from the current position, as a stream.
+     * Cancelling the stream closes the file.
+     *
+     * **Unstable** because of lack of testing. */
+    readonly readable: ReadableStream<Uint8Array>;
+    /** A stream writing to the file at the current position. Closing or
+     * aborting the stream closes the file.
+     *
+     * **Unstable** because of lack of testing. */
+    readonly writable: WritableStream<Uint8Array>;
     constructor(rid: number);
     write(p: Uint8Array): Promise<number>;
     writeSync(p: Uint8Array): number;
@@ -561,12 +617,30 @@ declare namespace Deno {
     close(): void;
   }
 
-  /** A handle for `stdin`. */
-  export const stdin: Reader & ReaderSync & Closer & { rid: number };
-  /** A handle for `stdout`. */
-  export const stdout: Writer & WriterSync & Closer & { rid: number };
-  /** A handle for `stderr`. */
-  export const stderr: Writer & WriterSync & Closer & { rid: number };
+  /** A handle for `stdin`. Its `readable` stream is **unstable**;
+   * cancelling the stream leaves stdin open. */
+  export const stdin: Reader &
+    ReaderSync &
+    Closer & {
+      readonly rid: number;
+      readonly readable: ReadableStream<Uint8Array>;
+    };
+  /** A handle for `stdout`. Its `writable` stream is **unstable**; closing
+   * the stream leaves stdout open. */
+  export const stdout: Writer &
+    WriterSync &
+    Closer & {
+      readonly rid: number;
+      readonly writable: WritableStream<Uint8Array>;
+    };
+  /** A handle for `stderr`. Its `writable` stream is **unstable**; closing
+   * the stream leaves stderr open. */
+  export const stderr: Writer &
+    WriterSync &
+    Closer & {
+      readonly rid: number;
+      readonly writable: WritableStream<Uint8Array>;
+    };
 
   export interface OpenOptions {
     /** Sets the option for read access. This option, when `true`, means that the
@@ -898,7 +972,8 @@ declare namespace Deno {
    *
    * For a full description, see [chmod](#chmod)
    *
-   * NOTE: This API currently throws on Windows
+   * On Windows, only the owner's write permission is used: the file is made
+   * read-only when it's not set.
    *
    * Requires `allow-write` permission. */
   export function chmodSync(path: string, mode: number): void;
@@ -926,7 +1001,8 @@ declare namespace Deno {
    * | 1      | execute only |
    * | 0      | no permission |
    *
-   * NOTE: This API currently throws on Windows
+   * On Windows, only the owner's write permission is used: the file is made
+   * read-only when it's not set.
    *
    * Requires `allow-write` permission. */
   export function chmod(path: string, mode: number): Promise<void>;
@@ -938,7 +1014,7 @@ declare namespace Deno {
    *
    * Requires `allow-write` permission.
    *
-   * Throws Error (not implemented) if executed on Windows
+   * Throws `Deno.errors.NotSupported` on Windows
    *
    * @param path path to the file
    * @param uid user id (UID) of the new owner
@@ -953,7 +1029,7 @@ declare namespace Deno {
    *
    * Requires `allow-write` permission.
    *
-   * Throws Error (not implemented) if executed on Windows
+   * Throws `Deno.errors.NotSupported` on Windows
    *
    * @param path path to the file
    * @param uid user id (UID) of the new owner
@@ -1036,7 +1112,10 @@ declare namespace Deno {
    *       console.log(decoder.decode(data));
    *
    * Requires `allow-read` permission. */
-  export function readTextFile(path: string): Promise<string>;
+  export function readTextFile(
+    path: string,
+    options?: ReadFileOptions
+  ): Promise<string>;
 
   /** Synchronously reads and returns the entire contents of a file as an array
    * of bytes. `TextDecoder` can be used to transform the bytes to string if
@@ -1058,7 +1137,23 @@ declare namespace Deno {
    *       console.log(decoder.decode(data));
    *
    * Requires `allow-read` permission. */
-  export function readFile(path: string): Promise<Uint8Array>;
+  export function readFile(
+    path: string,
+    options?: ReadFileOptions
+  ): Promise<Uint8Array>;
+
+  export interface ReadFileOptions {
+    /** Aborting the signal stops reading the file, and the returned promise
+     * rejects with an "AbortError" `DOMException`.
+     *
+     *       const controller = new AbortController();
+     *       setTimeout(() => controller.abort(), 1000);
+     *       const data = await Deno.readFile("big.bin", {
+     *         signal: controller.signal,
+     *       });
+     */
+    signal?: AbortSignal;
+  }
 
   /** A FileInfo describes a file and is returned by `stat`, `lstat`,
    * `statSync`, `lstatSync`. */
@@ -1268,6 +1363,15 @@ declare namespace Deno {
     create?: boolean;
     /** Permissions always applied to file. */
     mode?: number;
+    /** Defaults to `false`. If set to `true`, `data` is written to a temporary
+     * file in the same directory, which then replaces the file at `path`, so
+     * that the file is never left partially written, eg. after a crash. A
+     * replaced file keeps its permissions unless `mode` is set. Can't be
+     * combined with `append`. */
+    atomic?: boolean;
+    /** Defaults to `false`. If set to `true`, the data is flushed to disk
+     * before returning. With `atomic`, the rename is flushed too. */
+    sync?: boolean;
   }
 
   /** Synchronously write `data` to the given `path`, by default creating a new
@@ -1279,9 +1383,9 @@ declare namespace Deno {
    *       Deno.writeFileSync("hello2.txt", data, {create: false});  // only works if "hello2.txt" exists
    *       Deno.writeFileSync("hello3.txt", data, {mode: 0o777});  // set permissions on new file
    *       Deno.writeFileSync("hello4.txt", data, {append: true});  // add data to the end of the file
+   *       Deno.writeFileSync("hello5.txt", data, {atomic: true});  // never leave "hello5.txt" half written
    *
-   * Requires `allow-write` permission, and `allow-read` if `options.create` is
-   * `false`.
+   * Requires `allow-write` permission.
    */
   export function writeFileSync(
     path: string,
@@ -1298,8 +1402,9 @@ declare namespace Deno {
    *       await Deno.writeFile("hello2.txt", data, {create: false});  // only works if "hello2.txt" exists
    *       await Deno.writeFile("hello3.txt", data, {mode: 0o777});  // set permissions on new file
    *       await Deno.writeFile("hello4.txt", data, {append: true});  // add data to the end of the file
+   *       await Deno.writeFile("hello5.txt", data, {atomic: true});  // never leave "hello5.txt" half written
    *
-   * Requires `allow-write` permission, and `allow-read` if `options.create` is `false`.
+   * Requires `allow-write` permission.
    */
   export function writeFile(
     path: string,
@@ -1310,20 +1415,32 @@ declare namespace Deno {
   /** Synchronously write string `data` to the given `path`, by default creating a new file if needed,
    * else overwriting.
    *
-   *       await Deno.writeTextFileSync("hello1.txt", "Hello world\n");  // overwrite "hello1.txt" or create it
+   *       Deno.writeTextFileSync("hello1.txt", "Hello world\n");  // overwrite "hello1.txt" or create it
    *
-   * Requires `allow-write` permission, and `allow-read` if `options.create` is `false`.
+   * Takes the same options as `Deno.writeFileSync()`.
+   *
+   * Requires `allow-write` permission.
    */
-  export function writeTextFileSync(path: string, data: string): void;
+  export function writeTextFileSync(
+    path: string,
+    data: string,
+    options?: WriteFileOptions
+  ): void;
 
   /** Asynchronously write string `data` to the given `path`, by default creating a new file if needed,
    * else overwriting.
    *
    *       await Deno.writeTextFile("hello1.txt", "Hello world\n");  // overwrite "hello1.txt" or create it
    *
-   * Requires `allow-write` permission, and `allow-read` if `options.create` is `false`.
+   * Takes the same options as `Deno.writeFile()`.
+   *
+   * Requires `allow-write` permission.
    */
-  export function writeTextFile(path: string, data: string): Promise<void>;
+  export function writeTextFile(
+    path: string,
+    data: string,
+    options?: WriteFileOptions
+  ): Promise<void>;
 
   /** Synchronously truncates or extends the specified file, to reach the
    * specified `len`.  If `len` is not specified then the entire file contents
@@ -1391,6 +1508,19 @@ declare namespace Deno {
     readonly remoteAddr: Addr;
     /** The resource ID of the connection. */
     readonly rid: number;
+    /** The data received on the connection, as a stream. Data is only read
+     * from the socket as the stream is consumed, so a slow consumer slows
+     * down the sender. Cancelling the stream closes the connection.
+     *
+     * **Unstable** because of lack of testing. */
+    readonly readable: ReadableStream<Uint8Array>;
+    /** A stream writing to the connection. `writer.ready` stays pending while
+     * earlier writes are still waiting for the socket. Closing the stream
+     * shuts down the writing side of the connection, aborting it closes the
+     * connection.
+     *
+     * **Unstable** because of lack of testing. */
+    readonly writable: WritableStream<Uint8Array>;
     /** Shuts down (`shutdown(2)`) the writing side of the TCP connection. Most
      * callers should just use `close()`.
      *
@@ -1406,6 +1536,10 @@ declare namespace Deno {
     /** A literal IP address or host name that can be resolved to an IP address.
      * If not specified, defaults to `0.0.0.0`. */
     hostname?: string;
+    /** Aborting the signal cancels a pending `accept()`, which rejects with
+     * an "AbortError" `DOMException`, and ends iteration over the listener.
+     * The listener itself still has to be closed. */
+    signal?: AbortSignal;
   }
 
   /** Listen announces on the local transport address.
@@ -1483,7 +1617,7 @@ declare namespace Deno {
    */
   export function connectTls(options: ConnectTlsOptions): Promise<Conn>;
 
-  export interface Metrics {
+  export interface OpMetrics {
     opsDispatched: number;
     opsDispatchedSync: number;
     opsDispatchedAsync: number;
@@ -1497,6 +1631,28 @@ declare namespace Deno {
     bytesReceived: number;
   }
 
+  export interface Metrics extends OpMetrics {
+    /** Durations of sync ops, collected with `--warn-slow-ops`. */
+    opSyncDuration: MetricsHistogram;
+    /** Time from dispatch to completion of async ops, collected with
+     * `--warn-slow-ops`. */
+    opAsyncDuration: MetricsHistogram;
+    /** Durations of event loop ticks (JS turns), collected with
+     * `--warn-slow-ops`. */
+    eventLoopTickDuration: MetricsHistogram;
+    /** Counters of each op that was dispatched, keyed by op name. */
+    ops: Record<string, OpMetrics>;
+  }
+
+  export interface MetricsHistogram {
+    count: number;
+    sumMs: number;
+    maxMs: number;
+    /** Number of samples keyed by upper bound of the bucket in
+     * milliseconds, e.g. `"5"` counts samples in the (1ms, 5ms] range. */
+    buckets: Record<string, number>;
+  }
+
   /** Receive metrics from the privileged side of Deno.  This is primarily used
    * in the development of Deno. 'Ops', also called 'bindings', are the go-between
    * between Deno Javascript and Deno Rust.
@@ -1517,6 +1673,8 @@ declare namespace Deno {
    *      │      bytesSentData      │   0    │
    *      │      bytesReceived      │  375   │
    *      └─────────────────────────┴────────┘
+   *
+   * `deno run --dump-metrics[=file]` writes these metrics as JSON at exit.
    */
   export function metrics(): Metrics;
 
@@ -1566,11 +1724,50 @@ declare namespace Deno {
   export class Process {
     readonly rid: number;
     readonly pid: number;
-    readonly stdin?: Writer & Closer;
-    readonly stdout?: Reader & Closer;
-    readonly stderr?: Reader & Closer;
+    readonly stdin?: Writer &
+      Closer & {
+        readonly rid: number;
+        /** A stream writing to the stdin of the process. Closing or aborting
+         * the stream closes stdin.
+         *
+         * **Unstable** because of lack of testing. */
+        readonly writable: WritableStream<Uint8Array>;
+      };
+    readonly stdout?: Reader &
+      Closer & {
+        readonly rid: number;
+        /** The stdout of the process, as a stream. Cancelling the stream
+         * closes stdout.
+         *
+         * **Unstable** because of lack of testing. */
+        readonly readable: ReadableStream<Uint8Array>;
+      };
+    readonly stderr?: Reader &
+      Closer & {
+        readonly rid: number;
+        /** The stderr of the process, as a stream. Cancelling the stream
+         * closes stderr.
+         *
+         * **Unstable** because of lack of testing. */
+        readonly readable: ReadableStream<Uint8Array>;
+      };
     /** Resolves to the current status of the process. */
     status(): Promise<ProcessStatus>;
+    /** Reads the piped stdout and stderr to the end at the same time, and
+     * waits for the process to exit. Unlike reading one after the other, this
+     * can't deadlock when the process fills the pipe that isn't being read.
+     * Outputs that aren't piped are empty.
+     *
+     *       const p = Deno.run({
+     *         cmd: ["deno", "--version"],
+     *         stdout: "piped",
+     *         stderr: "piped",
+     *       });
+     *       const { status, stdout, stderr } = await p.outputAll();
+     *       p.close();
+     *
+     * This closes stdout and stderr. */
+    outputAll(): Promise<ProcessOutput>;
     /** Buffer the stdout until EOF and return it as `Uint8Array`.
      *
      * You must set stdout to `"piped"` when creating the process.
@@ -1606,6 +1803,12 @@ declare namespace Deno {
         signal?: number;
       };
 
+  export interface ProcessOutput {
+    status: ProcessStatus;
+    stdout: Uint8Array;
+    stderr: Uint8Array;
+  }
+
   export interface RunOptions {
     /** Arguments to pass. Note, the first element needs to be a path to the
      * binary */
@@ -1617,6 +1820,24 @@ declare namespace Deno {
     stdout?: "inherit" | "piped" | "null" | number;
     stderr?: "inherit" | "piped" | "null" | number;
     stdin?: "inherit" | "piped" | "null" | number;
+    /** Keep the subprocess running when its resource is closed, eg. when the
+     * program exits. Defaults to `false`, ie. the subprocess is killed.
+     *
+     * On Unix, a detached subprocess also starts a new session, so it doesn't
+     * end with the terminal and leads its own process group: all its
+     * descendants can be signaled with `Deno.kill(-p.pid, signo)`. */
+    detached?: boolean;
+    /** Unix only: run the subprocess as this user id. Throws
+     * `Deno.errors.NotSupported` on Windows. */
+    uid?: number;
+    /** Unix only: run the subprocess with this group id. Throws
+     * `Deno.errors.NotSupported` on Windows. */
+    gid?: number;
+    /** Unix only: put the subprocess in the process group `pgid`, or in a new
+     * group that it leads if `pgid` is `0`. The group can be signaled with
+     * `Deno.kill(-pgid, signo)`. Can't be combined with `detached`. Throws
+     * `Deno.errors.NotSupported` on Windows. */
+    pgid?: number;
   }
 
   /** Spawns new subprocess.  RunOptions must contain at a minimum the `opt.cmd`,
@@ -1646,13 +1867,20 @@ declare namespace Deno {
    * `"null"` This stream will be ignored. This is the equivalent of attaching
    * the stream to `/dev/null`.
    *
+   * The subprocess is killed when `p.close()` is called or the program exits,
+   * unless `opt.detached` is `true`.
+   *
    * Details of the spawned process are returned.
    *
    * Requires `allow-run` permission. */
   export function run(opt: RunOptions): Process;
 
   interface InspectOptions {
+    /** Maximum depth of nested objects to show. Defaults to 4. */
     depth?: number;
+    /** Colorize the output using ANSI escape codes, as shown in the REPL.
+     * Defaults to `false`. */
+    colors?: boolean;
   }
 
   /** Converts the input into a string that has the same format as printed by
@@ -1680,7 +1908,7 @@ declare namespace Deno {
    *
    * Finally, a number of output options are also available.
    *
-   *      const out = Deno.inspect(obj, {showHidden: true, depth: 4, colors: true, indentLevel: 2});
+   *      const out = Deno.inspect(obj, { depth: 4, colors: true });
    *
    */
   export function inspect(value: unknown, options?: InspectOptions): string;
@@ -1718,6 +1946,19 @@ declare namespace Deno {
    */
   export const args: string[];
 
+  /** The URL of the entrypoint module of the program, ie. the module passed to
+   * `deno run`:
+   *
+   *      deno run https://deno.land/std/examples/welcome.ts
+   *
+   * Then `Deno.mainModule` will be:
+   *
+   *      "https://deno.land/std/examples/welcome.ts"
+   *
+   * Local paths are resolved to `file://` URLs.
+   */
+  export const mainModule: string;
+
   /** A symbol which can be used as a key for a custom method which will be
    * called when `Deno.inspect()` is called, or when the object is logged to
    * the console. */
@@ -434,6 +434,27 @@ declare class TransformStream<I = any, O = any> {
   readonly writable: WritableStream<I>;
 }
 
+/** Compresses a stream of bytes. `format` is one of "gzip", "deflate" (the
+ * zlib format), "deflate-raw" or "br".
+ *
+ *       const compressed = file.readable.pipeThrough(
+ *         new CompressionStream("gzip")
+ *       );
+ */
+declare class CompressionStream {
+  constructor(format: string);
+  readonly readable: ReadableStream<Uint8Array>;
+  readonly writable: WritableStream<BufferSource>;
+}
+
+/** Decompresses a stream of bytes compressed in one of the formats supported
+ * by `CompressionStream`. */
+declare class DecompressionStream {
+  constructor(format: string);
+  readonly readable: ReadableStream<Uint8Array>;
+  readonly writable: WritableStream<BufferSource>;
+}
+
 interface TransformStreamDefaultController<O = any> {
   readonly desiredSize: number | null;
   enqueue(chunk: O): void;
@@ -985,8 +1006,10 @@ declare class TextDecoder {
     label?: string,
     options?: { fatal?: boolean; ignoreBOM?: boolean }
   );
-  /** Returns the result of running encoding's decoder. */
-  decode(input?: BufferSource, options?: { stream?: false }): string;
+  /** Returns the result of running encoding's decoder. With `stream: true`
+   * a partial sequence at the end of `input` is kept and completed by the
+   * next call; the first call without it ends the stream. */
+  decode(input?: BufferSource, options?: { stream?: boolean }): string;
   readonly [Symbol.toStringTag]: string;
 }
 
@@ -1181,6 +1204,43 @@ declare class ErrorEvent extends Event {
   constructor(type: string, eventInitDict?: ErrorEventInit);
 }
 
+interface PromiseRejectionEventInit extends EventInit {
+  promise: Promise<any>;
+  reason?: any;
+}
+
+/** The event dispatched at the global scope when a promise is rejected and no
+ * handler is added to it by the end of the tick. Canceling the event handles
+ * the rejection, otherwise it's uncaught and ends the program.
+ *
+ *       addEventListener("unhandledrejection", (e) => {
+ *         console.error("Unhandled rejection:", e.reason);
+ *         e.preventDefault();
+ *       });
+ */
+declare class PromiseRejectionEvent extends Event {
+  readonly promise: Promise<any>;
+  readonly reason: any;
+  constructor(type: string, eventInitDict: PromiseRejectionEventInit);
+}
+
+/** Called for the `error` events dispatched at the global scope for uncaught
+ * exceptions, like ones thrown by a timer callback or an event listener.
+ * Returning true handles the exception, which otherwise ends the program. */
+declare var onerror:
+  | ((
+      message: string,
+      filename: string,
+      lineno: number,
+      colno: number,
+      error: any
+    ) => any)
+  | null;
+
+/** Called for the `unhandledrejection` events dispatched at the global scope.
+ * Calling `preventDefault()` on the event handles the rejection. */
+declare var onunhandledrejection: ((ev: PromiseRejectionEvent) => any) | null;
+
 interface PostMessageOptions {
   transfer?: any[];
 }
@@ -1241,17 +1301,236 @@ declare class Worker extends EventTarget {
   terminate(): void;
 }
 
-declare namespace performance {
-  /** Returns a current time from Deno's start in milliseconds.
+/** Allows communication between the main thread and workers (or multiple
+ * `BroadcastChannel` objects in the same thread) subscribed to a channel
+ * with the same name. Messages are cloned using the same algorithm as
+ * `Worker.postMessage()`.
+ *
+ *       const channel = new BroadcastChannel("updates");
+ *       channel.onmessage = (e) => console.log(e.data);
+ *       channel.postMessage({ hello: "world" });
+ *       // ...
+ *       channel.close();
+ *
+ * An open channel keeps the program alive until `close()` is called. */
+declare class BroadcastChannel extends EventTarget {
+  /** Name of the channel. */
+  readonly name: string;
+  onmessage: ((this: BroadcastChannel, ev: MessageEvent) => any) | null;
+  onmessageerror: ((this: BroadcastChannel, ev: MessageEvent) => any) | null;
+  constructor(name: string);
+  /** Sends a message to all other `BroadcastChannel` objects subscribed to
+   * this channel. */
+  postMessage(message: any): void;
+  /** Closes the channel, after which no messages are sent or received. */
+  close(): void;
+}
+
+/** A storage area of `localStorage` or `sessionStorage`, which maps string
+ * keys to string values. Items can also be used as properties of the area. */
+interface Storage {
+  /** The number of items. */
+  readonly length: number;
+  /** Removes all items. */
+  clear(): void;
+  /** Returns the value of the item with the key, or `null` if there is no
+   * such item. */
+  getItem(key: string): string | null;
+  /** Returns the key of the item at the index, or `null` if the index is out
+   * of range. Items are in the order they were first set. */
+  key(index: number): string | null;
+  /** Removes the item with the key, if any. */
+  removeItem(key: string): void;
+  /** Sets the value of the item with the key. Throws a "QuotaExceededError"
+   * `DOMException` if the storage area would grow past its quota. */
+  setItem(key: string, value: string): void;
+  [name: string]: any;
+}
+
+interface CacheQueryOptions {
+  /** Ignores the query string of URLs when matching. */
+  ignoreSearch?: boolean;
+  /** Matches requests of any method, not only `GET` and `HEAD`. */
+  ignoreMethod?: boolean;
+  /** Ignores the `Vary` header of cached responses when matching. */
+  ignoreVary?: boolean;
+}
+
+interface MultiCacheQueryOptions extends CacheQueryOptions {
+  /** Only looks in the cache with this name. */
+  cacheName?: string;
+}
+
+/** A cache of request/response pairs, opened with `caches.open()`. Response
+ * bodies are kept on disk, in the Deno directory. */
+interface Cache {
+  /** Fetches the request and puts the response in the cache. Rejects with a
+   * `TypeError` if the response isn't ok. */
+  add(request: RequestInfo): Promise<void>;
+  addAll(requests: RequestInfo[]): Promise<void>;
+  /** Deletes the entries matching the request. Resolves to whether any was
+   * deleted. */
+  delete(request: RequestInfo, options?: CacheQueryOptions): Promise<boolean>;
+  keys(
+    request?: RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<readonly Request[]>;
+  match(
+    request: RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<Response | undefined>;
+  matchAll(
+    request?: RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<readonly Response[]>;
+  /** Puts a response of a `GET` request in the cache, replacing the entries
+   * of the request. The body of the response is read and stored. */
+  put(request: RequestInfo, response: Response): Promise<void>;
+}
+
+declare var Cache: {
+  prototype: Cache;
+  new (): never;
+};
+
+/** The named caches of the origin. The origin is set by `--location`, like
+ * for `localStorage`.
+ *
+ *       const cache = await caches.open("v1");
+ *       const url = "https://deno.land/std/version.ts";
+ *       let response = await cache.match(url);
+ *       if (!response) {
+ *         await cache.add(url);
+ *         response = await cache.match(url);
+ *       }
+ */
+interface CacheStorage {
+  /** Deletes the cache and its entries. Resolves to whether it existed. */
+  delete(cacheName: string): Promise<boolean>;
+  has(cacheName: string): Promise<boolean>;
+  /** The names of the caches, in the order they were created. */
+  keys(): Promise<string[]>;
+  /** Looks for a response to the request in every cache, in the order they
+   * were created. */
+  match(
+    request: RequestInfo,
+    options?: MultiCacheQueryOptions
+  ): Promise<Response | undefined>;
+  /** Opens the cache, creating it if needed. */
+  open(cacheName: string): Promise<Cache>;
+}
+
+declare var CacheStorage: {
+  prototype: CacheStorage;
+  new (): never;
+};
+
+declare const caches: CacheStorage;
+
+interface Navigator {
+  /** The number of logical processors that are available to run threads. */
+  readonly hardwareConcurrency: number;
+}
+
+declare var navigator: Navigator;
+
+/** A mark or measure on the performance timeline. */
+declare class PerformanceEntry {
+  readonly name: string;
+  /** `"mark"` or `"measure"`. */
+  readonly entryType: string;
+  /** In milliseconds, relative to `performance.timeOrigin`. */
+  readonly startTime: number;
+  readonly duration: number;
+  toJSON(): any;
+}
+
+type PerformanceEntryList = PerformanceEntry[];
+
+interface PerformanceMarkOptions {
+  /** Cloned into the `detail` of the mark. */
+  detail?: any;
+  /** Defaults to `performance.now()`. */
+  startTime?: number;
+}
+
+/** A named timestamp, created with `performance.mark()`. */
+declare class PerformanceMark extends PerformanceEntry {
+  constructor(name: string, options?: PerformanceMarkOptions);
+  readonly detail: any;
+}
+
+interface PerformanceMeasureOptions {
+  /** Cloned into the `detail` of the measure. */
+  detail?: any;
+  /** A mark name or a timestamp. */
+  start?: string | number;
+  duration?: number;
+  /** A mark name or a timestamp. */
+  end?: string | number;
+}
+
+/** The time between two timestamps, created with `performance.measure()`. */
+declare class PerformanceMeasure extends PerformanceEntry {
+  readonly detail: any;
+}
+
+/** The number of input events dispatched by the runtime, by event type. Deno
+ * has no user input, so all counts are 0. */
+type EventCounts = ReadonlyMap<string, number>;
+
+interface Performance {
+  /** The time the program, or the worker, started, in milliseconds since
+   * the Unix epoch. The time origins of all workers are derived from the same
+   * clock, so `performance.timeOrigin + performance.now()` can be compared
+   * across workers. */
+  readonly timeOrigin: number;
+  readonly eventCounts: EventCounts;
+
+  /** Returns the time since `timeOrigin` in milliseconds.
    *
    * Use the flag --allow-hrtime return a precise value.
    *
    *       const t = performance.now();
    *       console.log(`${t} ms since start!`);
    */
-  export function now(): number;
+  now(): number;
+
+  /** Adds a mark named `markName` to the performance timeline.
+   *
+   *       performance.mark("start");
+   *       doWork();
+   *       performance.mark("end");
+   *       const { duration } = performance.measure("work", "start", "end");
+   */
+  mark(markName: string, markOptions?: PerformanceMarkOptions): PerformanceMark;
+
+  /** Adds a measure named `measureName` to the performance timeline. Start
+   * and end default to `timeOrigin` and now; mark names refer to the most
+   * recent mark of that name. */
+  measure(
+    measureName: string,
+    startOrMeasureOptions?: string | PerformanceMeasureOptions,
+    endMark?: string
+  ): PerformanceMeasure;
+
+  /** Returns the marks and measures of the timeline, ordered by
+   * `startTime`. */
+  getEntries(): PerformanceEntryList;
+  getEntriesByName(name: string, type?: string): PerformanceEntryList;
+  getEntriesByType(type: string): PerformanceEntryList;
+
+  /** Removes the marks named `markName` from the timeline, or all of them. */
+  clearMarks(markName?: string): void;
+  /** Removes the measures named `measureName` from the timeline, or all of
+   * them. */
+  clearMeasures(measureName?: string): void;
+
+  toJSON(): any;
 }
 
+declare var performance: Performance;
+
 interface EventInit {
   bubbles?: boolean;
   cancelable?: boolean;
@@ -1287,6 +1566,13 @@ declare class Event {
   /** Returns true if event was dispatched by the user agent, and false
    * otherwise. */
   readonly isTrusted: boolean;
+  /** Returns false if preventDefault() was invoked successfully, and true
+   * otherwise. Setting it to false cancels the event. Kept for legacy
+   * reasons, use defaultPrevented instead. */
+  returnValue: boolean;
+  /** Returns the target of the event. Kept for legacy reasons, use target
+   * instead. */
+  readonly srcElement: EventTarget | null;
   /** Returns the object to which event is dispatched (its target). */
   readonly target: EventTarget | null;
   /** Returns the event's timestamp as the number of milliseconds measured
@@ -15,7 +15,7 @@ declare namespace Deno {
    *        const prevUmaskValue = Deno.umask(0o077);  // e.g. 18 (0o022)
    *        console.log(Deno.umask());  // e.g. 63 (0o077)
    *
-   * NOTE:  This API is not implemented on Windows
+   * Throws `Deno.errors.NotSupported` on Windows.
    */
   export function umask(mask?: number): number;
 
@@ -268,6 +268,82 @@ declare namespace Deno {
    */
   export function osRelease(): string;
 
+  export interface SystemMemoryInfo {
+    /** Total installed memory in bytes. */
+    total: number;
+    /** Unused memory in bytes. */
+    free: number;
+    /** Memory that can be given to processes without swapping, in bytes. It
+     * includes caches that can be dropped. */
+    available: number;
+    /** Memory used by kernel buffers, in bytes. */
+    buffers: number;
+    /** Memory used by the page cache, in bytes. */
+    cached: number;
+    /** Total swap space in bytes. */
+    swapTotal: number;
+    /** Unused swap space in bytes. */
+    swapFree: number;
+  }
+
+  /** Returns how the memory of the machine is used. Fields the platform
+   * doesn't report are 0.
+   *
+   *       console.log(Deno.systemMemoryInfo().available);
+   *
+   * Requires `allow-env` permission.
+   *
+   * **Unstable** new API, yet to be vetted.
+   */
+  export function systemMemoryInfo(): SystemMemoryInfo;
+
+  export interface MemoryUsage {
+    /** The resident set size of the process in bytes. On Windows it's the
+     * working set size, and on other platforms than Linux and Windows it's
+     * the peak resident set size. */
+    rss: number;
+    /** The size of the V8 heap in bytes. */
+    heapTotal: number;
+    /** The used part of the V8 heap in bytes. */
+    heapUsed: number;
+    /** Memory of objects outside of the V8 heap that are held by JavaScript
+     * objects, in bytes. */
+    external: number;
+  }
+
+  /** Returns how much memory the process uses. The heap sizes are those of
+   * the calling isolate, so a worker reports its own heap.
+   *
+   *       const { heapUsed } = Deno.memoryUsage();
+   *
+   * **Unstable** new API, yet to be vetted.
+   */
+  export function memoryUsage(): MemoryUsage;
+
+  export interface NetworkInterfaceInfo {
+    /** The name of the interface, eg. `"eth0"`. */
+    name: string;
+    family: "IPv4" | "IPv6";
+    address: string;
+    netmask: string;
+    /** The hardware address, `"00:00:00:00:00:00"` when there's none. */
+    mac: string;
+    /** Whether it's a loopback interface. */
+    internal: boolean;
+  }
+
+  /** Returns an entry for every address assigned to a network interface, so
+   * an interface with several addresses is listed several times.
+   *
+   *       console.log(Deno.networkInterfaces());
+   *
+   * Requires `allow-env` permission. Throws `Deno.errors.NotSupported` on
+   * Windows.
+   *
+   * **Unstable** new API, yet to be vetted.
+   */
+  export function networkInterfaces(): NetworkInterfaceInfo[];
+
   /** **UNSTABLE**: new API, yet to be vetted.
    *
    * Open and initalize a plugin.
@@ -285,6 +361,110 @@ declare namespace Deno {
    */
   export function openPlugin(filename: string): number;
 
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Type of a parameter or result of a foreign function. Pointers are
+   * represented as numbers. `"buffer"` parameters take an `ArrayBufferView`
+   * and pass a pointer to its contents; a function can have at most one. */
+  export type NativeType =
+    | "void"
+    | "u8"
+    | "i8"
+    | "u16"
+    | "i16"
+    | "u32"
+    | "i32"
+    | "u64"
+    | "i64"
+    | "usize"
+    | "isize"
+    | "f32"
+    | "f64"
+    | "pointer"
+    | "buffer";
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Signature of a foreign function. Calls of `nonblocking` functions run on
+   * a separate thread and return a promise. */
+  export interface ForeignFunction {
+    parameters: NativeType[];
+    result: NativeType;
+    nonblocking?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A dynamic library opened with `Deno.dlopen()`. */
+  export class DynamicLibrary<S extends Record<string, ForeignFunction>> {
+    readonly rid: number;
+    /** Functions calling the declared symbols. 64 bit integers beyond
+     * `Number.MAX_SAFE_INTEGER` lose precision. */
+    readonly symbols: {
+      [K in keyof S]: (
+        ...args: Array<number | ArrayBufferView>
+      ) => number | null | Promise<number | null>;
+    };
+    /** Closes the library. Nonblocking calls in progress complete first. */
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Opens a dynamic library and makes the given symbols callable.
+   *
+   *       const lib = Deno.dlopen("./libexample.so", {
+   *         add: { parameters: ["i32", "i32"], result: "i32" },
+   *         sleep_ms: { parameters: ["u32"], result: "void", nonblocking: true },
+   *       });
+   *       console.log(lib.symbols.add(1, 2)); // 3
+   *       await lib.symbols.sleep_ms(100);
+   *       lib.close();
+   *
+   * Paths with a directory component are resolved from the current directory,
+   * other names are looked up by the system loader.
+   *
+   * Requires `allow-ffi` permission. */
+  export function dlopen<S extends Record<string, ForeignFunction>>(
+    path: string,
+    symbols: S
+  ): DynamicLibrary<S>;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Requires `allow-ffi` permission. */
+  export class UnsafePointer {
+    /** Address of the contents of `buffer`, valid while `buffer` is alive. */
+    static of(buffer: ArrayBufferView): number;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Reads and writes memory at a pointer returned by a foreign function.
+   * Offsets are in bytes. Nothing is checked: invalid pointers crash the
+   * process.
+   *
+   * Requires `allow-ffi` permission. */
+  export class UnsafePointerView {
+    constructor(pointer: number);
+    readonly pointer: number;
+    getUint8(offset?: number): number;
+    getInt8(offset?: number): number;
+    getUint16(offset?: number): number;
+    getInt16(offset?: number): number;
+    getUint32(offset?: number): number;
+    getInt32(offset?: number): number;
+    getFloat32(offset?: number): number;
+    getFloat64(offset?: number): number;
+    getPointer(offset?: number): number;
+    /** Writes `value` as `type` at `offset`. */
+    set(type: NativeType, value: number, offset?: number): void;
+    /** Reads the NUL-terminated string at `offset`. */
+    getCString(offset?: number): string;
+    /** Copies `destination.byteLength` bytes at `offset` into `destination`. */
+    copyInto(destination: ArrayBufferView, offset?: number): void;
+  }
+
   /** The log category for a diagnostic message. */
   export enum DiagnosticCategory {
     Log = 0,
@@ -664,6 +844,83 @@ declare namespace Deno {
     options?: CompilerOptions
   ): Promise<[DiagnosticItem[] | undefined, string]>;
 
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface EmitOptions {
+    /** Bundle the modules into a single ES module, like `deno bundle`. */
+    bundle?: "esm";
+    /** Type check the modules, reporting the errors in `diagnostics`.
+     * Defaults to `true`. */
+    check?: boolean;
+    /** A subset of ts.CompilerOptions which can be supported by Deno. */
+    compilerOptions?: CompilerOptions;
+    /** Sources to resolve the modules from, keyed by module name. If
+     * supplied, Deno will not attempt to resolve any modules externally. */
+    sources?: Record<string, string>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface EmitResult {
+    /** Type errors, and errors of the bundling. Empty if there are none. */
+    diagnostics: DiagnosticItem[];
+    /** The emitted JavaScript and source maps, keyed by file name. A bundle
+     * is emitted as `deno:///bundle.js`. */
+    files: Record<string, string>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Emits the JavaScript of `rootSpecifier` and the modules it depends on,
+   * with the compiler Deno uses itself, so build tools can reuse it:
+   *
+   *      const { diagnostics, files } = await Deno.emit("./mod.ts");
+   *
+   *      const { files } = await Deno.emit("/mod.ts", {
+   *        bundle: "esm",
+   *        sources: {
+   *          "/mod.ts": `export * from "./bar.ts";`,
+   *          "/bar.ts": `export const bar = "bar";`,
+   *        },
+   *      });
+   *      console.log(files["deno:///bundle.js"]);
+   *
+   * @param rootSpecifier The module to start from. Without `sources`, it's
+   *                      resolved as if it had been given on the command
+   *                      line.
+   */
+  export function emit(
+    rootSpecifier: string,
+    options?: EmitOptions
+  ): Promise<EmitResult>;
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface GraphOptions {
+    /** Sources to resolve the modules from, keyed by module name. If
+     * supplied, Deno will not attempt to resolve any modules externally. */
+    sources?: Record<string, string>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface ModuleGraph {
+    /** The resolved URL of the root module. */
+    root: string;
+    /** The modules of the graph, keyed by resolved URL, with the resolved
+     * URLs of the modules they import. */
+    modules: Record<string, { dependencies: string[] }>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Resolves and loads the modules `rootSpecifier` depends on, directly or
+   * not, without compiling them:
+   *
+   *      const { root, modules } = await Deno.graph("./mod.ts");
+   *      console.log(modules[root].dependencies);
+   */
+  export function graph(
+    rootSpecifier: string,
+    options?: GraphOptions
+  ): Promise<ModuleGraph>;
+
   /** **UNSTABLE**: Should not have same name as `window.location` type. */
   interface Location {
     /** The full url for the module, e.g. `file://some/file.ts` or
@@ -879,6 +1136,63 @@ declare namespace Deno {
    */
   export function setRaw(rid: number, mode: boolean): void;
 
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Returns an async iterator over the lines of a readable resource, eg. a
+   * file, `Deno.stdin` or a connection. Lines are split at "\n" or "\r\n",
+   * which aren't included, and decoded as UTF-8. The splitting happens in
+   * chunks outside of JavaScript, so large files are neither loaded fully
+   * nor processed byte per byte.
+   *
+   *       const file = await Deno.open("server.log");
+   *       for await (const line of Deno.readLines(file)) {
+   *         console.log(line);
+   *       }
+   *       file.close();
+   *
+   * The resource isn't closed when the iterator finishes. */
+  export function readLines(r: { rid: number }): AsyncIterableIterator<string>;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Returns a stream of the data read from a readable resource, eg. a file,
+   * a connection or the body of a response. The resource is only read as the
+   * stream is consumed. It isn't closed when the stream ends or is cancelled.
+   *
+   *       const file = await Deno.open("data.bin");
+   *       for await (const chunk of Deno.readableStreamForRid(file.rid)) {
+   *         console.log(chunk.length);
+   *       }
+   *       file.close();
+   */
+  export function readableStreamForRid(
+    rid: number,
+    options?: { chunkSize?: number }
+  ): ReadableStream<Uint8Array>;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Returns a stream writing to a writable resource. `writer.ready` stays
+   * pending while earlier chunks are still being written. The resource isn't
+   * closed when the stream is closed or aborted. */
+  export function writableStreamForRid(rid: number): WritableStream<Uint8Array>;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Returns the rid of a new resource reading the chunks of `stream`, so the
+   * stream can be passed to anything taking a readable resource. Chunks are
+   * only pulled from the stream as the resource is read, and closing the
+   * resource cancels the stream.
+   *
+   *       const rid = Deno.resourceForReadableStream(stream);
+   *       const file = await Deno.create("out.bin");
+   *       await Deno.copy(new Deno.File(rid), file);
+   *       Deno.close(rid);
+   */
+  export function resourceForReadableStream(
+    stream: ReadableStream<Uint8Array>
+  ): number;
+
   /** **UNSTABLE**: needs investigation into high precision time.
    *
    * Synchronously changes the access (`atime`) and modification (`mtime`) times
@@ -1024,6 +1338,64 @@ declare namespace Deno {
     options: ConnectOptions | UnixConnectOptions
   ): Promise<Conn>;
 
+  export type RecordType = "A" | "AAAA" | "TXT" | "MX" | "SRV" | "PTR";
+
+  export interface ResolveDnsOptions {
+    /** The name server to query instead of the system's configured ones. The
+     * port defaults to 53. */
+    nameServer?: {
+      ip: string;
+      port?: number;
+    };
+  }
+
+  export interface MXRecord {
+    preference: number;
+    exchange: string;
+  }
+
+  export interface SRVRecord {
+    priority: number;
+    weight: number;
+    port: number;
+    target: string;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Performs a DNS lookup of `query` for records of the given type. For "PTR"
+   * lookups `query` is the IP address to look up.
+   *
+   *     const a = await Deno.resolveDns("deno.land", "A");
+   *     const mx = await Deno.resolveDns("deno.land", "MX", {
+   *       nameServer: { ip: "1.1.1.1" },
+   *     });
+   *
+   * Requires `allow-net` permission for the query name and the name server. */
+  export function resolveDns(
+    query: string,
+    recordType: "A" | "AAAA" | "PTR",
+    options?: ResolveDnsOptions
+  ): Promise<string[]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "TXT",
+    options?: ResolveDnsOptions
+  ): Promise<string[][]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "MX",
+    options?: ResolveDnsOptions
+  ): Promise<MXRecord[]>;
+
+  export function resolveDns(
+    query: string,
+    recordType: "SRV",
+    options?: ResolveDnsOptions
+  ): Promise<SRVRecord[]>;
+
   export interface StartTlsOptions {
     /** A literal IP address or host name that can be resolved to an IP address.
      * If not specified, defaults to `127.0.0.1`. */
@@ -1071,6 +1443,228 @@ declare namespace Deno {
    * Requires `allow-run` permission. */
   export function kill(pid: number, signo: number): void;
 
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Information about a worker created by the current thread. */
+  export interface WorkerInfo {
+    /** Internal id of the worker, unique among workers spawned by the
+     * same thread. */
+    id: number;
+    /** Name given in `WorkerOptions`. */
+    name: string;
+    /** Resolved specifier of the worker's script. */
+    specifier: string;
+    /** `"terminated"` and `"errored"` workers are removed from the list once
+     * the parent processes their final event. */
+    status: "running" | "terminated" | "errored";
+    /** Number of messages posted to the worker that it didn't process yet. */
+    queuedMessages: number;
+    /** Time since the worker was created, in milliseconds. */
+    uptime: number;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Lists workers spawned by the current thread that are still alive. Useful
+   * for tracking down workers that are created but never terminated.
+   *
+   *       const worker = new Worker("./worker.ts", { type: "module" });
+   *       console.log(Deno.workers());
+   *       // [ { id: 0, name: "unknown", status: "running", ... } ]
+   */
+  export function workers(): WorkerInfo[];
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The marks and measures of a thread, created with `performance.mark()`
+   * and `performance.measure()`. */
+  export interface PerformanceTimeline {
+    /** Unique among the threads of the program. */
+    id: number;
+    /** `"main"` for the main thread; the name given in `WorkerOptions` for
+     * workers. */
+    name: string;
+    /** The `performance.timeOrigin` of the thread. Add it to the `startTime`
+     * of entries to compare them with the entries of other threads. */
+    timeOrigin: number;
+    entries: Array<{
+      name: string;
+      entryType: "mark" | "measure";
+      startTime: number;
+      duration: number;
+    }>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Collects the marks and measures of all threads of the program, including
+   * workers that were terminated, for aggregated profiling. Threads without
+   * entries are left out.
+   *
+   *       const worker = new Worker("./worker.ts", { type: "module" });
+   *       // ...
+   *       for (const timeline of Deno.performanceEntries()) {
+   *         for (const entry of timeline.entries) {
+   *           const time = timeline.timeOrigin + entry.startTime;
+   *           console.log(timeline.name, entry.name, time);
+   *         }
+   *       }
+   */
+  export function performanceEntries(): PerformanceTimeline[];
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A part of a `Deno.KvKey`. Parts of different types are ordered as
+   * `Uint8Array < string < number < boolean`; parts of the same type by
+   * value, with `false < true`. */
+  export type KvKeyPart = Uint8Array | string | number | boolean;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A key of a `Deno.Kv` database, like `["users", 42]`. Keys are ordered
+   * part by part, and a key comes before the longer keys it's a prefix of. */
+  export type KvKey = readonly KvKeyPart[];
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * An entry of a `Deno.Kv` database. */
+  export interface KvEntry<T> {
+    key: KvKey;
+    value: T;
+    /** Versionstamp of the commit that last set the entry. Versionstamps of
+     * later commits compare greater as strings. */
+    versionstamp: string;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * An entry of a `Deno.Kv` database, whose value and versionstamp are
+   * `null` if the key doesn't exist. */
+  export type KvEntryMaybe<T> =
+    | KvEntry<T>
+    | { key: KvKey; value: null; versionstamp: null };
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * The keys to list with `Deno.Kv.list()`: the keys starting with `prefix`
+   * (excluding `prefix` itself), from `start` (inclusive) and before `end`. */
+  export type KvListSelector =
+    | { prefix: KvKey }
+    | { prefix: KvKey; start: KvKey }
+    | { prefix: KvKey; end: KvKey }
+    | { start: KvKey; end: KvKey };
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface KvListOptions {
+    /** Maximum number of entries to list. Defaults to all of them. */
+    limit?: number;
+    /** Continues a previous listing, from the `cursor` of its iterator. */
+    cursor?: string;
+    /** Lists the entries in descending key order. */
+    reverse?: boolean;
+    /** Number of entries fetched at a time. Defaults to 100. */
+    batchSize?: number;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Iterates over the entries listed by `Deno.Kv.list()`. */
+  export class KvListIterator<T> implements AsyncIterableIterator<KvEntry<T>> {
+    /** Where to continue listing from, with the `cursor` option, after the
+     * entries iterated so far. Throws before the first entry. */
+    readonly cursor: string;
+    next(): Promise<IteratorResult<KvEntry<T>>>;
+    [Symbol.asyncIterator](): AsyncIterableIterator<KvEntry<T>>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A check of an atomic operation: it passes if the key has the given
+   * versionstamp, or doesn't exist if `versionstamp` is `null`. */
+  export interface AtomicCheck {
+    key: KvKey;
+    versionstamp: string | null;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted. */
+  export interface KvCommitResult {
+    ok: true;
+    /** Versionstamp of the commit, now the versionstamp of the keys set. */
+    versionstamp: string;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Returned by `Deno.AtomicOperation.commit()` when a check failed. */
+  export interface KvCommitError {
+    ok: false;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Checks and mutations applied together. If any check fails when the
+   * operation is committed, nothing is changed.
+   *
+   *       const account = await kv.get<number>(["balance", "alice"]);
+   *       const result = await kv.atomic()
+   *         .check(account)
+   *         .set(["balance", "alice"], (account.value ?? 0) - 10)
+   *         .commit();
+   *       if (!result.ok) {
+   *         // The balance changed in the meantime; try again.
+   *       }
+   */
+  export class AtomicOperation {
+    check(...checks: AtomicCheck[]): this;
+    /** Values must be serializable to JSON. */
+    set(key: KvKey, value: unknown): this;
+    delete(key: KvKey): this;
+    commit(): Promise<KvCommitResult | KvCommitError>;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * A transactional key-value database, opened with `Deno.openKv()`. */
+  export class Kv {
+    get<T = unknown>(key: KvKey): Promise<KvEntryMaybe<T>>;
+    getMany<T = unknown>(keys: KvKey[]): Promise<Array<KvEntryMaybe<T>>>;
+    /** Values must be serializable to JSON. */
+    set(key: KvKey, value: unknown): Promise<KvCommitResult>;
+    delete(key: KvKey): Promise<void>;
+    /** Lists entries in key order, fetching them in batches.
+     *
+     *       for await (const entry of kv.list({ prefix: ["users"] })) {
+     *         console.log(entry.key, entry.value);
+     *       }
+     */
+    list<T = unknown>(
+      selector: KvListSelector,
+      options?: KvListOptions
+    ): KvListIterator<T>;
+    atomic(): AtomicOperation;
+    /** Yields the entries of `keys` now, then each time any of them is
+     * changed, by this process, until the iteration is stopped or the
+     * database is closed. */
+    watch<T = unknown>(
+      keys: KvKey[]
+    ): AsyncIterableIterator<Array<KvEntryMaybe<T>>>;
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Opens the key-value database at `path`, creating it if needed, or an
+   * in-memory database if `path` is `":memory:"`. Without a path the
+   * database of the origin (like `localStorage`, see `--location`) in
+   * `$DENO_DIR` is opened.
+   *
+   *       const kv = await Deno.openKv();
+   *       await kv.set(["users", 1], { name: "Alice" });
+   *       const user = await kv.get(["users", 1]);
+   *
+   * Requires `allow-read` and `allow-write` permissions for `path`. */
+  export function openKv(path?: string): Promise<Kv>;
+
   /** The name of a "powerful feature" which needs permission.
    *
    * See: https://w3c.github.io/permissions/#permission-registry
@@ -1084,6 +1678,7 @@ declare namespace Deno {
     | "net"
     | "env"
     | "plugin"
+    | "ffi"
     | "hrtime";
 
   /** The current status of the permission.
@@ -1118,6 +1713,10 @@ declare namespace Deno {
     name: "plugin";
   }
 
+  export interface FfiPermissionDescriptor {
+    name: "ffi";
+  }
+
   export interface HrtimePermissionDescriptor {
     name: "hrtime";
   }
@@ -1133,6 +1732,7 @@ declare namespace Deno {
     | NetPermissionDescriptor
     | EnvPermissionDescriptor
     | PluginPermissionDescriptor
+    | FfiPermissionDescriptor
     | HrtimePermissionDescriptor;
 
   export class Permissions {
@@ -1182,4 +1782,83 @@ declare namespace Deno {
    *  Requires `allow-env` permission.
    */
   export function hostname(): string;
+
+  export interface BenchDefinition {
+    fn: () => void | Promise<void>;
+    name: string;
+    /** Benchmarks of the same group are compared with each other. */
+    group?: string;
+    /** Compare other benchmarks of the group with this one. Defaults to the
+     * fastest benchmark of the group. */
+    baseline?: boolean;
+    /** If truthy the benchmark will be ignored. */
+    ignore?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Register a benchmark which will be run when `deno bench` is used on the
+   * command line and the containing module looks like a bench module.
+   * Each benchmark is warmed up and then run repeatedly for a target time;
+   * `fn` can be async if required.
+   *
+   *       Deno.bench({
+   *         name: "JSON.parse",
+   *         group: "parse",
+   *         fn(): void {
+   *           JSON.parse('{"hello": "world"}');
+   *         },
+   *       });
+   */
+  export function bench(b: BenchDefinition): void;
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Register a benchmark which will be run when `deno bench` is used on the
+   * command line and the containing module looks like a bench module.
+   *
+   *       Deno.bench("Array.from", (): void => {
+   *         Array.from({ length: 100 });
+   *       });
+   */
+  export function bench(name: string, fn: () => void | Promise<void>): void;
+
+  export interface CreateHttpClientOptions {
+    /** A certificate authority to use when validating TLS certificates, in
+     * addition to the built-in roots. Requires `allow-read` permission. */
+    caFile?: string;
+    /** Maximum number of idle keep-alive connections kept per host. */
+    poolMaxIdlePerHost?: number;
+    /** Milliseconds after which idle connections are closed. */
+    poolIdleTimeout?: number;
+    /** Use HTTP/2 without negotiating it, also for `http:` URLs. Over TLS,
+     * HTTP/2 is negotiated automatically when the server supports it. */
+    http2?: boolean;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * An HTTP client with its own connection pool, for use with `fetch()`. */
+  export class HttpClient {
+    readonly rid: number;
+    close(): void;
+  }
+
+  /** **UNSTABLE**: new API, yet to be vetted.
+   *
+   * Create a custom HttpClient to use with `fetch()`. Requests made with the
+   * same client reuse its pooled connections.
+   *
+   *       const client = Deno.createHttpClient({ poolMaxIdlePerHost: 4 });
+   *       const res = await fetch("https://deno.land/", { client });
+   *       client.close();
+   */
+  export function createHttpClient(
+    options?: CreateHttpClientOptions
+  ): HttpClient;
 }
+
+declare function fetch(
+  input: Request | URL | string,
+  init?: RequestInit & { client?: Deno.HttpClient }
+): Promise<Response>;
@@ -12,8 +12,12 @@ declare interface Window extends EventTarget {
   readonly self: Window & typeof globalThis;
   onload: ((this: Window, ev: Event) => any) | null;
   onunload: ((this: Window, ev: Event) => any) | null;
+  onerror: typeof onerror;
+  onunhandledrejection: typeof onunhandledrejection;
   close: () => void;
   readonly closed: boolean;
+  readonly localStorage: Storage;
+  readonly sessionStorage: Storage;
   Deno: typeof Deno;
 }
 
@@ -22,4 +26,21 @@ declare const self: Window & typeof globalThis;
 declare const onload: ((this: Window, ev: Event) => any) | null;
 declare const onunload: ((this: Window, ev: Event) => any) | null;
 
+declare var Storage: {
+  prototype: Storage;
+  new (): never;
+};
+
+/** Storage of the origin that is kept across runs, in the Deno directory.
+ * The origin is set by `--location`; without it, it's the origin of a remote
+ * main module, or each local main module has its own storage. The storage
+ * area of an origin holds up to 10 MB.
+ *
+ *       const visits = Number(localStorage.getItem("visits") ?? 0) + 1;
+ *       localStorage.setItem("visits", String(visits));
+ */
+declare const localStorage: Storage;
+/** Storage of the origin that only lasts as long as the process. */
+declare const sessionStorage: Storage;
+
 /* eslint-enable @typescript-eslint/no-explicit-any */
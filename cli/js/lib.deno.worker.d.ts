@@ -11,7 +11,8 @@ declare interface DedicatedWorkerGlobalScope {
   self: DedicatedWorkerGlobalScope & typeof globalThis;
   onmessage: (e: MessageEvent) => void;
   onmessageerror: (e: MessageEvent) => void;
-  onerror: undefined | typeof onerror;
+  onerror: typeof onerror;
+  onunhandledrejection: typeof onunhandledrejection;
   name: typeof __workerMain.name;
   close: typeof __workerMain.close;
   postMessage: typeof __workerMain.postMessage;
@@ -20,15 +21,7 @@ declare interface DedicatedWorkerGlobalScope {
 
 declare const self: DedicatedWorkerGlobalScope & typeof globalThis;
 declare let onmessage: ((e: { data: any }) => Promise<void> | void) | undefined;
-declare let onerror:
-  | ((
-      msg: string,
-      source: string,
-      lineno: number,
-      colno: number,
-      e: Event
-    ) => boolean | void)
-  | undefined;
+declare let onmessageerror: ((e: MessageEvent) => void) | undefined;
 declare const close: typeof __workerMain.close;
 declare const name: typeof __workerMain.name;
 declare const postMessage: typeof __workerMain.postMessage;
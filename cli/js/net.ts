@@ -1,8 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { errors } from "./errors.ts";
+import { AbortError, errors } from "./errors.ts";
 import { Reader, Writer, Closer } from "./io.ts";
 import { read, write } from "./ops/io.ts";
 import { close } from "./ops/resources.ts";
+import { readableStreamForRid, writableStreamForRid } from "./io_streams.ts";
+import { abortable } from "./web/abort_signal.ts";
 import * as netOps from "./ops/net.ts";
 import { Addr } from "./ops/net.ts";
 export { ShutdownMode, shutdown, NetAddr, UnixAddr } from "./ops/net.ts";
@@ -30,12 +32,34 @@ export interface Listener extends AsyncIterable<Conn> {
 }
 
 export class ConnImpl implements Conn {
+  #readable?: ReadableStream<Uint8Array>;
+  #writable?: WritableStream<Uint8Array>;
+
   constructor(
     readonly rid: number,
     readonly remoteAddr: Addr,
     readonly localAddr: Addr
   ) {}
 
+  get readable(): ReadableStream<Uint8Array> {
+    if (!this.#readable) {
+      this.#readable = readableStreamForRid(this.rid, {
+        onCancel: (): void => this.close(),
+      });
+    }
+    return this.#readable;
+  }
+
+  get writable(): WritableStream<Uint8Array> {
+    if (!this.#writable) {
+      this.#writable = writableStreamForRid(this.rid, {
+        onClose: (): void => this.closeWrite(),
+        onAbort: (): void => this.close(),
+      });
+    }
+    return this.#writable;
+  }
+
   write(p: Uint8Array): Promise<number> {
     return write(this.rid, p);
   }
@@ -55,11 +79,17 @@ export class ConnImpl implements Conn {
 }
 
 export class ListenerImpl implements Listener {
-  constructor(readonly rid: number, readonly addr: Addr) {}
+  constructor(
+    readonly rid: number,
+    readonly addr: Addr,
+    protected readonly signal?: AbortSignal
+  ) {}
 
-  async accept(): Promise<Conn> {
-    const res = await netOps.accept(this.rid, this.addr.transport);
-    return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+  accept(): Promise<Conn> {
+    return abortable(this.signal, async (cancelRid) => {
+      const res = await netOps.accept(this.rid, this.addr.transport, cancelRid);
+      return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+    });
   }
 
   async next(): Promise<IteratorResult<Conn>> {
@@ -67,7 +97,8 @@ export class ListenerImpl implements Listener {
     try {
       conn = await this.accept();
     } catch (error) {
-      if (error instanceof errors.BadResource) {
+      // Aborting the signal ends the iteration, but leaves the listener open.
+      if (error instanceof errors.BadResource || error instanceof AbortError) {
         return { value: undefined, done: true };
       }
       throw error;
@@ -136,6 +167,8 @@ export interface Conn extends Reader, Writer, Closer {
   localAddr: Addr;
   remoteAddr: Addr;
   rid: number;
+  readonly readable: ReadableStream<Uint8Array>;
+  readonly writable: WritableStream<Uint8Array>;
   closeWrite(): void;
 }
 
@@ -143,19 +176,20 @@ export interface ListenOptions {
   port: number;
   hostname?: string;
   transport?: "tcp";
+  signal?: AbortSignal;
 }
 
 export function listen(
   options: ListenOptions & { transport?: "tcp" }
 ): Listener;
-export function listen(options: ListenOptions): Listener {
+export function listen({ signal, ...options }: ListenOptions): Listener {
   const res = netOps.listen({
     transport: "tcp",
     hostname: "127.0.0.1",
-    ...(options as ListenOptions),
+    ...options,
   });
 
-  return new ListenerImpl(res.rid, res.localAddr);
+  return new ListenerImpl(res.rid, res.localAddr, signal);
 }
 
 export interface ConnectOptions {
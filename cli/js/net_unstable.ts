@@ -77,3 +77,56 @@ export async function connect(
     return stableConnect(options as ConnectOptions);
   }
 }
+
+export type RecordType = "A" | "AAAA" | "TXT" | "MX" | "SRV" | "PTR";
+
+export interface ResolveDnsOptions {
+  nameServer?: {
+    ip: string;
+    port?: number;
+  };
+}
+
+export interface MXRecord {
+  preference: number;
+  exchange: string;
+}
+
+export interface SRVRecord {
+  priority: number;
+  weight: number;
+  port: number;
+  target: string;
+}
+
+export function resolveDns(
+  query: string,
+  recordType: "A" | "AAAA" | "PTR",
+  options?: ResolveDnsOptions
+): Promise<string[]>;
+export function resolveDns(
+  query: string,
+  recordType: "TXT",
+  options?: ResolveDnsOptions
+): Promise<string[][]>;
+export function resolveDns(
+  query: string,
+  recordType: "MX",
+  options?: ResolveDnsOptions
+): Promise<MXRecord[]>;
+export function resolveDns(
+  query: string,
+  recordType: "SRV",
+  options?: ResolveDnsOptions
+): Promise<SRVRecord[]>;
+export function resolveDns(
+  query: string,
+  recordType: RecordType,
+  options: ResolveDnsOptions = {}
+): Promise<unknown[]> {
+  return netOps.resolveDns({
+    query,
+    recordType,
+    nameServer: options.nameServer,
+  });
+}
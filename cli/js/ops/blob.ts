@@ -0,0 +1,53 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+/** A part of a new blob: the contents of another blob, or the next `len`
+ * bytes of the buffer passed to `create()`. */
+export type BlobPart = { blob: number } | { len: number };
+
+export interface ObjectUrlBlob {
+  id: number;
+  size: number;
+  type: string;
+}
+
+export function create(
+  type: string,
+  parts: BlobPart[],
+  bytes: Uint8Array
+): number {
+  return sendSync("op_blob_create", { type, parts }, bytes);
+}
+
+export function slice(
+  id: number,
+  start: number,
+  end: number,
+  type: string
+): number {
+  return sendSync("op_blob_slice", { id, start, end, type });
+}
+
+export function read(id: number, offset: number, buf: Uint8Array): number {
+  return sendSync("op_blob_read", { id, offset }, buf);
+}
+
+export function remove(id: number): void {
+  sendSync("op_blob_remove", { id });
+}
+
+export function stream(id: number): number {
+  return sendSync("op_blob_stream", { id });
+}
+
+export function createObjectUrl(id: number): string {
+  return sendSync("op_blob_create_object_url", { id });
+}
+
+export function revokeObjectUrl(url: string): void {
+  sendSync("op_blob_revoke_object_url", { url });
+}
+
+export function fromObjectUrl(url: string): ObjectUrlBlob | null {
+  return sendSync("op_blob_from_object_url", { url });
+}
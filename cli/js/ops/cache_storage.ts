@@ -0,0 +1,82 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export interface CacheRequest {
+  url: string;
+  method: string;
+  headers: Array<[string, string]>;
+}
+
+export interface CacheResponse {
+  url: string;
+  status: number;
+  statusText: string;
+  headers: Array<[string, string]>;
+}
+
+export interface CachedResponse extends CacheResponse {
+  bodyRid: number;
+}
+
+export interface QueryOptions {
+  ignoreSearch?: boolean;
+  ignoreMethod?: boolean;
+  ignoreVary?: boolean;
+}
+
+export function storageOpen(name: string): number {
+  return sendSync("op_cache_storage_open", { name });
+}
+
+export function storageHas(name: string): boolean {
+  return sendSync("op_cache_storage_has", { name });
+}
+
+export function storageDelete(name: string): boolean {
+  return sendSync("op_cache_storage_delete", { name });
+}
+
+export function storageKeys(): string[] {
+  return sendSync("op_cache_storage_keys");
+}
+
+export function putStart(
+  cacheId: number,
+  request: CacheRequest,
+  response: CacheResponse
+): number {
+  return sendSync("op_cache_put_start", { cacheId, request, response });
+}
+
+export function putWrite(rid: number, chunk: Uint8Array): void {
+  sendSync("op_cache_put_write", { rid }, chunk);
+}
+
+export function putFinish(rid: number): void {
+  sendSync("op_cache_put_finish", { rid });
+}
+
+export function match(
+  cacheId: number | null,
+  request: CacheRequest | null,
+  options: QueryOptions,
+  first: boolean
+): CachedResponse[] {
+  return sendSync("op_cache_match", { cacheId, request, options, first });
+}
+
+export function deleteEntries(
+  cacheId: number,
+  request: CacheRequest,
+  options: QueryOptions
+): boolean {
+  return sendSync("op_cache_delete", { cacheId, request, options });
+}
+
+export function keys(
+  cacheId: number,
+  request: CacheRequest | null,
+  options: QueryOptions
+): CacheRequest[] {
+  return sendSync("op_cache_keys", { cacheId, request, options });
+}
@@ -0,0 +1,10 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export function createCancelHandle(): number {
+  return sendSync("op_cancel_handle_new");
+}
+
+export function abortCancelHandle(rid: number): void {
+  sendSync("op_cancel_handle_abort", { rid });
+}
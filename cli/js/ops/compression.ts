@@ -0,0 +1,19 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import { sendSync } from "./dispatch_json.ts";
+
+export function create(format: string, decompress: boolean): number {
+  return sendSync("op_compression_new", { format, decompress });
+}
+
+export function write(rid: number, chunk: Uint8Array): number {
+  return sendSync("op_compression_write", { rid }, chunk);
+}
+
+export function finish(rid: number): number {
+  return sendSync("op_compression_finish", { rid });
+}
+
+export function read(rid: number, buffer: Uint8Array): number {
+  return sendSync("op_compression_read", { rid }, buffer);
+}
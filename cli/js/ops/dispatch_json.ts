@@ -3,6 +3,7 @@ import * as util from "../util.ts";
 import { core } from "../core.ts";
 import { OPS_CACHE } from "../runtime.ts";
 import { ErrorKind, getErrorClass } from "../errors.ts";
+import { trackPendingOp, untrackPendingOp } from "./pending_ops.ts";
 
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 type Ok = any;
@@ -92,6 +93,8 @@ export async function sendAsync(
   } else {
     // Async result.
     promiseTable[promiseId] = promise;
+    const token = trackPendingOp(opId);
+    promise.then(() => untrackPendingOp(token));
   }
 
   const res = await promise;
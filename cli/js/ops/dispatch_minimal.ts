@@ -3,6 +3,7 @@ import * as util from "../util.ts";
 import { core } from "../core.ts";
 import { TextDecoder } from "../web/text_encoding.ts";
 import { ErrorKind, errors, getErrorClass } from "../errors.ts";
+import { trackPendingOp, untrackPendingOp } from "./pending_ops.ts";
 
 // Using an object without a prototype because `Map` was causing GC problems.
 const promiseTableMin: {
@@ -65,7 +66,8 @@ function unwrapResponse(res: RecordMinimal): number {
   return res.result;
 }
 
-const scratch32 = new Int32Array(3);
+// The fourth int is the rid of a cancel handle for async ops, or -1.
+const scratch32 = new Int32Array(4);
 const scratchBytes = new Uint8Array(
   scratch32.buffer,
   scratch32.byteOffset,
@@ -85,12 +87,14 @@ export function asyncMsgFromRust(ui8: Uint8Array): void {
 export async function sendAsyncMinimal(
   opId: number,
   arg: number,
-  zeroCopy: Uint8Array
+  zeroCopy: Uint8Array,
+  cancelRid = -1
 ): Promise<number> {
   const promiseId = nextPromiseId(); // AKA cmdId
   scratch32[0] = promiseId;
   scratch32[1] = arg;
   scratch32[2] = 0; // result
+  scratch32[3] = cancelRid;
   const promise = util.createResolvable<RecordMinimal>();
   const buf = core.dispatch(opId, scratchBytes, zeroCopy);
   if (buf) {
@@ -100,6 +104,8 @@ export async function sendAsyncMinimal(
   } else {
     // Async result.
     promiseTableMin[promiseId] = promise;
+    const token = trackPendingOp(opId);
+    promise.then(() => untrackPendingOp(token));
   }
 
   const res = await promise;
@@ -113,6 +119,7 @@ export function sendSyncMinimal(
 ): number {
   scratch32[0] = 0; // promiseId 0 indicates sync
   scratch32[1] = arg;
+  scratch32[3] = -1;
   const res = core.dispatch(opId, scratchBytes, zeroCopy)!;
   const resRecord = recordFromBufMinimal(res);
   return unwrapResponse(resRecord);
@@ -0,0 +1,43 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export interface DecoderOptions {
+  encoding: string;
+  fatal: boolean;
+  ignoreBOM: boolean;
+}
+
+export function normalizeLabel(label: string): string | null {
+  return sendSync("op_encoding_normalize_label", { label });
+}
+
+export function decodeSingle(
+  input: Uint8Array,
+  { encoding, fatal, ignoreBOM }: DecoderOptions
+): string {
+  return sendSync(
+    "op_encoding_decode_single",
+    { encoding, fatal, ignoreBom: ignoreBOM },
+    input
+  );
+}
+
+export function createDecoder({
+  encoding,
+  fatal,
+  ignoreBOM,
+}: DecoderOptions): number {
+  return sendSync("op_encoding_new_decoder", {
+    encoding,
+    fatal,
+    ignoreBom: ignoreBOM,
+  });
+}
+
+export function decode(
+  rid: number,
+  input: Uint8Array,
+  stream: boolean
+): string {
+  return sendSync("op_encoding_decode", { rid, stream }, input);
+}
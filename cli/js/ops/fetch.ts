@@ -1,11 +1,14 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
-import { sendAsync } from "./dispatch_json.ts";
+import { sendAsync, sendSync } from "./dispatch_json.ts";
 
 interface FetchRequest {
   url: string;
   method: string | null;
   headers: Array<[string, string]>;
+  bodyRid?: number;
+  clientRid?: number;
+  cancelRid?: number;
 }
 
 export interface FetchResponse {
@@ -26,3 +29,14 @@ export function fetch(
 
   return sendAsync("op_fetch", args, zeroCopy);
 }
+
+export interface CreateHttpClientOptions {
+  caFile?: string;
+  poolMaxIdlePerHost?: number;
+  poolIdleTimeout?: number;
+  http2?: boolean;
+}
+
+export function createHttpClient(options: CreateHttpClientOptions): number {
+  return sendSync("op_create_http_client", options);
+}
@@ -0,0 +1,83 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync, sendAsync } from "./dispatch_json.ts";
+
+export type NativeType =
+  | "void"
+  | "u8"
+  | "i8"
+  | "u16"
+  | "i16"
+  | "u32"
+  | "i32"
+  | "u64"
+  | "i64"
+  | "usize"
+  | "isize"
+  | "f32"
+  | "f64"
+  | "pointer"
+  | "buffer";
+
+export interface ForeignFunction {
+  parameters: NativeType[];
+  result: NativeType;
+  nonblocking?: boolean;
+}
+
+export function loadLibrary(
+  path: string,
+  symbols: Record<string, ForeignFunction>
+): number {
+  return sendSync("op_ffi_load", { path, symbols });
+}
+
+export function callSymbol(
+  rid: number,
+  symbol: string,
+  args: Array<number | null>,
+  buffer?: Uint8Array
+): number | null {
+  return sendSync("op_ffi_call", { rid, symbol, args }, buffer);
+}
+
+export function callSymbolAsync(
+  rid: number,
+  symbol: string,
+  args: Array<number | null>,
+  buffer?: Uint8Array
+): Promise<number | null> {
+  return sendAsync("op_ffi_call", { rid, symbol, args }, buffer);
+}
+
+export function pointerOf(buffer: Uint8Array): number {
+  return sendSync("op_ffi_ptr_of", {}, buffer);
+}
+
+export function readPointer(
+  pointer: number,
+  offset: number,
+  type: NativeType
+): number {
+  return sendSync("op_ffi_ptr_read", { pointer, offset, type });
+}
+
+export function writePointer(
+  pointer: number,
+  offset: number,
+  type: NativeType,
+  value: number
+): void {
+  sendSync("op_ffi_ptr_write", { pointer, offset, type, value });
+}
+
+export function copyFromPointer(
+  pointer: number,
+  offset: number,
+  destination: Uint8Array
+): void {
+  sendSync("op_ffi_ptr_copy_into", { pointer, offset }, destination);
+}
+
+export function readCString(pointer: number, offset: number): string {
+  return sendSync("op_ffi_ptr_c_string", { pointer, offset });
+}
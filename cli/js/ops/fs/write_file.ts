@@ -0,0 +1,26 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync, sendAsync } from "../dispatch_json.ts";
+
+export interface WriteFileOptions {
+  append?: boolean;
+  create?: boolean;
+  mode?: number;
+  atomic?: boolean;
+  sync?: boolean;
+}
+
+export function writeFileSync(
+  path: string,
+  data: Uint8Array,
+  options: WriteFileOptions = {}
+): void {
+  sendSync("op_write_file", { path, ...options }, data);
+}
+
+export async function writeFile(
+  path: string,
+  data: Uint8Array,
+  options: WriteFileOptions = {}
+): Promise<void> {
+  await sendAsync("op_write_file", { path, ...options }, data);
+}
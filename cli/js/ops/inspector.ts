@@ -0,0 +1,23 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import { sendSync } from "./dispatch_json.ts";
+
+export interface InspectorNotification {
+  method: string;
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  params: any;
+}
+
+export interface InspectorResponse {
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  result: any;
+  notifications: InspectorNotification[];
+}
+
+export function post(method: string, params?: object): InspectorResponse {
+  return sendSync("op_inspector_post", { method, params });
+}
+
+export function report(kind: "coverage" | "profile"): string {
+  return sendSync("op_inspector_report", { kind });
+}
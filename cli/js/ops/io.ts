@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
 import { sendAsyncMinimal, sendSyncMinimal } from "./dispatch_minimal.ts";
+import { sendSync, sendAsync } from "./dispatch_json.ts";
 // TODO(bartlomieju): remove this import and maybe lazy-initialize
 // OPS_CACHE that belongs only to this module
 import { OPS_CACHE } from "../runtime.ts";
@@ -28,7 +29,8 @@ export function readSync(rid: number, buffer: Uint8Array): number | null {
 
 export async function read(
   rid: number,
-  buffer: Uint8Array
+  buffer: Uint8Array,
+  cancelRid?: number
 ): Promise<number | null> {
   if (buffer.length == 0) {
     return 0;
@@ -36,7 +38,7 @@ export async function read(
   if (OP_READ < 0) {
     OP_READ = OPS_CACHE["op_read"];
   }
-  const nread = await sendAsyncMinimal(OP_READ, rid, buffer);
+  const nread = await sendAsyncMinimal(OP_READ, rid, buffer, cancelRid);
   if (nread < 0) {
     throw new Error("read error");
   } else if (nread == 0) {
@@ -69,3 +71,30 @@ export async function write(rid: number, data: Uint8Array): Promise<number> {
     return result;
   }
 }
+
+export function openLineReader(rid: number): number {
+  return sendSync("op_line_reader_open", { rid });
+}
+
+export interface ReadLinesResult {
+  lines: string[];
+  done: boolean;
+}
+
+export function readLines(rid: number): Promise<ReadLinesResult> {
+  return sendAsync("op_read_lines", { rid });
+}
+
+export interface CopyResult {
+  copied: number;
+  done: boolean;
+}
+
+export function copy(
+  srcRid: number,
+  dstRid: number,
+  bufSize?: number,
+  limit?: number
+): Promise<CopyResult> {
+  return sendAsync("op_copy", { srcRid, dstRid, bufSize, limit });
+}
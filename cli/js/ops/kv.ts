@@ -0,0 +1,71 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync, sendAsync } from "./dispatch_json.ts";
+
+/** A key part as sent to the privileged side. */
+export type KeyPart =
+  | { type: "bytes"; value: number[] }
+  | { type: "string"; value: string }
+  | { type: "number"; value: number }
+  | { type: "boolean"; value: boolean };
+
+export type Key = KeyPart[];
+
+export interface Entry {
+  key: Key;
+  value: unknown;
+  versionstamp: string;
+}
+
+export interface ListedEntry extends Entry {
+  /** Where to continue listing from after this entry. */
+  cursor: string;
+}
+
+export interface Selector {
+  prefix?: Key;
+  start?: Key;
+  end?: Key;
+}
+
+export interface Check {
+  key: Key;
+  versionstamp: string | null;
+}
+
+export type Mutation =
+  | { type: "set"; key: Key; value: unknown }
+  | { type: "delete"; key: Key };
+
+export function open(path?: string): number {
+  return sendSync("op_kv_open", { path });
+}
+
+export function get(rid: number, keys: Key[]): Promise<Array<Entry | null>> {
+  return sendAsync("op_kv_get", { rid, keys });
+}
+
+export function list(
+  rid: number,
+  selector: Selector,
+  limit: number,
+  cursor: string | undefined,
+  reverse: boolean
+): Promise<ListedEntry[]> {
+  return sendAsync("op_kv_list", { rid, selector, limit, cursor, reverse });
+}
+
+export function atomic(
+  rid: number,
+  checks: Check[],
+  mutations: Mutation[]
+): Promise<string | null> {
+  return sendAsync("op_kv_atomic", { rid, checks, mutations });
+}
+
+export function watch(
+  rid: number,
+  keys: Key[],
+  versionstamps: Array<string | null>
+): Promise<Array<Entry | null> | null> {
+  return sendAsync("op_kv_watch", { rid, keys, versionstamps });
+}
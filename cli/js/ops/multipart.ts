@@ -0,0 +1,63 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+
+export interface AppendArgs {
+  name: string;
+  filename?: string;
+  contentType?: string;
+  /** The value of a field. */
+  value?: string;
+  /** The contents of a file, as a blob. */
+  blobId?: number;
+  /** A file part of a parsed body, sent from disk. */
+  partId?: number;
+}
+
+export interface EncodedBody {
+  /** A stream resource reading the body. */
+  rid: number;
+  contentType: string;
+  contentLength: number;
+}
+
+export interface ParsedField {
+  name: string;
+  value: string;
+}
+
+export interface ParsedFile {
+  name: string;
+  filename: string;
+  contentType: string | null;
+  partId: number;
+  size: number;
+}
+
+export type ParsedEntry = ParsedField | ParsedFile;
+
+export function encoderOpen(boundary?: string): number {
+  return sendSync("op_multipart_encoder_open", { boundary });
+}
+
+export function encoderAppend(rid: number, args: AppendArgs): void {
+  sendSync("op_multipart_encoder_append", { rid, ...args });
+}
+
+export function encoderFinish(rid: number): EncodedBody {
+  return sendSync("op_multipart_encoder_finish", { rid });
+}
+
+/** Parses the body read from the resource `rid`, which is consumed, or the
+ * body in `bytes`. */
+export function parse(
+  boundary: string,
+  rid?: number,
+  bytes?: Uint8Array
+): Promise<ParsedEntry[]> {
+  return sendAsync("op_multipart_parse", { boundary, rid }, bytes);
+}
+
+/** Reads a parsed file part into a blob, returning the id of the blob. */
+export function partBlob(id: number): number {
+  return sendSync("op_multipart_part_blob", { id });
+}
@@ -35,9 +35,10 @@ interface AcceptResponse {
 
 export function accept(
   rid: number,
-  transport: string
+  transport: string,
+  cancelRid?: number
 ): Promise<AcceptResponse> {
-  return sendAsync("op_accept", { rid, transport });
+  return sendAsync("op_accept", { rid, transport, cancelRid });
 }
 
 export type ListenRequest = Addr;
@@ -86,3 +87,13 @@ export async function send(
 ): Promise<void> {
   await sendAsync("op_send", args, zeroCopy);
 }
+
+export interface ResolveDnsRequest {
+  query: string;
+  recordType: string;
+  nameServer?: { ip: string; port?: number };
+}
+
+export function resolveDns(args: ResolveDnsRequest): Promise<unknown[]> {
+  return sendAsync("op_resolve_dns", args);
+}
@@ -1,5 +1,5 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { sendSync } from "./dispatch_json.ts";
+import { sendSync, sendAsync } from "./dispatch_json.ts";
 import { errors } from "../errors.ts";
 
 export function loadavg(): number[] {
@@ -14,11 +14,55 @@ export function osRelease(): string {
   return sendSync("op_os_release");
 }
 
+export interface SystemMemoryInfo {
+  total: number;
+  free: number;
+  available: number;
+  buffers: number;
+  cached: number;
+  swapTotal: number;
+  swapFree: number;
+}
+
+export function systemMemoryInfo(): SystemMemoryInfo {
+  return sendSync("op_system_memory_info");
+}
+
+export interface MemoryUsage {
+  rss: number;
+  heapTotal: number;
+  heapUsed: number;
+  external: number;
+}
+
+export function memoryUsage(): MemoryUsage {
+  return sendSync("op_memory_usage");
+}
+
+export interface NetworkInterfaceInfo {
+  name: string;
+  family: "IPv4" | "IPv6";
+  address: string;
+  netmask: string;
+  mac: string;
+  internal: boolean;
+}
+
+export function networkInterfaces(): NetworkInterfaceInfo[] {
+  return sendSync("op_network_interfaces");
+}
+
 export function exit(code = 0): never {
   sendSync("op_exit", { code });
   throw new Error("Code not reachable");
 }
 
+/** Makes Ctrl-C perform graceful shutdown, dispatching `unload`, instead of
+ * killing the process. */
+export function shutdownOnSigint(): void {
+  sendAsync("op_shutdown_on_sigint").catch((): void => {});
+}
+
 function setEnv(key: string, value: string): void {
   sendSync("op_set_env", { key, value });
 }
@@ -0,0 +1,62 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { OPS_CACHE } from "../runtime.ts";
+
+// Keeps track of async ops that were dispatched but haven't completed yet, so
+// the test runner can tell which ops a test case leaked.
+
+export interface PendingOp {
+  opName: string;
+  // Only recorded while op tracing is enabled, see `setTraceOps()`.
+  stack?: string;
+}
+
+interface PendingOpRecord {
+  opId: number;
+  stack?: string;
+}
+
+// Using an object without a prototype because `Map` was causing GC problems.
+const pendingOps: {
+  [token: number]: PendingOpRecord;
+} = Object.create(null);
+let nextToken = 1;
+let traceOps = false;
+
+export function setTraceOps(enabled: boolean): void {
+  traceOps = enabled;
+}
+
+export function trackPendingOp(opId: number): number {
+  const token = nextToken++;
+  let stack: string | undefined;
+  if (traceOps) {
+    // Drop the "Error" line and the frames of this function and of the
+    // dispatcher that called it.
+    stack = new Error().stack!.split("\n").slice(3).join("\n");
+  }
+  pendingOps[token] = { opId, stack };
+  return token;
+}
+
+export function untrackPendingOp(token: number): void {
+  delete pendingOps[token];
+}
+
+function opNameFromId(opId: number): string {
+  for (const [name, id] of Object.entries(OPS_CACHE)) {
+    if (id === opId) {
+      return name;
+    }
+  }
+  return `op ${opId}`;
+}
+
+// Returns a snapshot of the ops that are currently in flight, keyed by an
+// opaque token that stays the same for the lifetime of each op.
+export function pendingOpsSnapshot(): { [token: number]: PendingOp } {
+  const snapshot: { [token: number]: PendingOp } = {};
+  for (const [token, { opId, stack }] of Object.entries(pendingOps)) {
+    snapshot[Number(token)] = { opName: opNameFromId(opId), stack };
+  }
+  return snapshot;
+}
@@ -0,0 +1,28 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export interface PerformanceEntryInfo {
+  name: string;
+  entryType: "mark" | "measure";
+  startTime: number;
+  duration: number;
+}
+
+export interface PerformanceTimeline {
+  id: number;
+  name: string;
+  timeOrigin: number;
+  entries: PerformanceEntryInfo[];
+}
+
+export function record(entry: PerformanceEntryInfo): void {
+  sendSync("op_performance_record", entry);
+}
+
+export function clear(entryType: "mark" | "measure", name?: string): void {
+  sendSync("op_performance_clear", { entryType, name });
+}
+
+export function performanceEntries(): PerformanceTimeline[] {
+  return sendSync("op_performance_entries");
+}
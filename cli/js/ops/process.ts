@@ -10,7 +10,7 @@ export function kill(pid: number, signo: number): void {
   sendSync("op_kill", { pid, signo });
 }
 
-interface RunStatusResponse {
+export interface RunStatusResponse {
   gotSignal: boolean;
   exitCode: number;
   exitSignal: number;
@@ -20,6 +20,25 @@ export function runStatus(rid: number): Promise<RunStatusResponse> {
   return sendAsync("op_run_status", { rid });
 }
 
+interface RunOutputResponse {
+  status: RunStatusResponse;
+  outputRid: number;
+  stdoutLength: number;
+  stderrLength: number;
+}
+
+export function runOutput(
+  rid: number,
+  stdoutRid?: number,
+  stderrRid?: number
+): Promise<RunOutputResponse> {
+  return sendAsync("op_run_output", { rid, stdoutRid, stderrRid });
+}
+
+export function runOutputTake(rid: number, buf: Uint8Array): void {
+  sendSync("op_run_output_take", { rid }, buf);
+}
+
 interface RunRequest {
   cmd: string[];
   cwd?: string;
@@ -30,6 +49,10 @@ interface RunRequest {
   stdinRid: number;
   stdoutRid: number;
   stderrRid: number;
+  detached: boolean;
+  uid?: number;
+  gid?: number;
+  pgid?: number;
 }
 
 interface RunResponse {
@@ -1,11 +1,34 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
 import { sendSync, sendAsync } from "./dispatch_json.ts";
+import { DiagnosticItem } from "../diagnostics.ts";
 
-export function startRepl(historyFile: string): number {
+interface StartReplResponse {
+  rid: number;
+  // Whether input should be type checked (`deno repl --check`).
+  check: boolean;
+  // Whether results should be colored, ie. colors aren't turned off and
+  // output goes to a terminal.
+  colors: boolean;
+}
+
+export function startRepl(historyFile: string): StartReplResponse {
   return sendSync("op_repl_start", { historyFile });
 }
 
 export function readline(rid: number, prompt: string): Promise<string> {
   return sendAsync("op_repl_readline", { rid, prompt });
 }
+
+interface ReplCheckResponse {
+  diagnostics: DiagnosticItem[];
+  // Input transpiled to JavaScript.
+  source: string;
+}
+
+export function replCheck(
+  source: string,
+  input: string
+): Promise<ReplCheckResponse> {
+  return sendAsync("op_repl_check", { source, input });
+}
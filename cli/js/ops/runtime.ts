@@ -4,24 +4,28 @@ import { sendSync } from "./dispatch_json.ts";
 
 export interface Start {
   args: string[];
+  cpuCount: number;
   cwd: string;
   debugFlag: boolean;
   denoVersion: string;
+  mainModule: string;
   noColor: boolean;
   pid: number;
   repl: boolean;
   target: string;
+  timeOrigin: number;
   tsVersion: string;
   unstableFlag: boolean;
   v8Version: string;
   versionFlag: boolean;
 }
 
-export function opStart(): Start {
-  return sendSync("op_start");
+/** `name` names the isolate's performance timeline. */
+export function opStart(name?: string): Start {
+  return sendSync("op_start", { name });
 }
 
-export interface Metrics {
+export interface OpMetrics {
   opsDispatched: number;
   opsDispatchedSync: number;
   opsDispatchedAsync: number;
@@ -35,6 +39,20 @@ export interface Metrics {
   bytesReceived: number;
 }
 
+export interface Metrics extends OpMetrics {
+  opSyncDuration: MetricsHistogram;
+  opAsyncDuration: MetricsHistogram;
+  eventLoopTickDuration: MetricsHistogram;
+  ops: Record<string, OpMetrics>;
+}
+
+export interface MetricsHistogram {
+  count: number;
+  sumMs: number;
+  maxMs: number;
+  buckets: Record<string, number>;
+}
+
 export function metrics(): Metrics {
   return sendSync("op_metrics");
 }
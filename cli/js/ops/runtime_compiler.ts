@@ -20,6 +20,28 @@ export function compile(request: CompileRequest): Promise<CompileResponse> {
   return sendAsync("op_compile", request);
 }
 
+interface EmitRequest extends CompileRequest {
+  check: boolean;
+}
+
+export function emit(request: EmitRequest): Promise<CompileResponse> {
+  return sendAsync("op_emit", request);
+}
+
+interface GraphRequest {
+  rootName: string;
+  sources?: Record<string, string>;
+}
+
+export interface GraphResponse {
+  root: string;
+  modules: Record<string, { dependencies: string[] }>;
+}
+
+export function graph(request: GraphRequest): Promise<GraphResponse> {
+  return sendAsync("op_graph", request);
+}
+
 interface TranspileRequest {
   sources: Record<string, string>;
   options?: string;
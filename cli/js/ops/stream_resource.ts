@@ -0,0 +1,21 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+
+export interface StreamResource {
+  /** The reading half, usable wherever a readable resource is. */
+  rid: number;
+  /** The writing half. Closing it ends the reading half. */
+  sinkRid: number;
+}
+
+export function open(): StreamResource {
+  return sendSync("op_stream_resource_open");
+}
+
+export async function write(sinkRid: number, chunk: Uint8Array): Promise<void> {
+  await sendAsync("op_stream_resource_write", { rid: sinkRid }, chunk);
+}
+
+export async function error(sinkRid: number, message: string): Promise<void> {
+  await sendAsync("op_stream_resource_write", { rid: sinkRid, error: message });
+}
@@ -0,0 +1,18 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import { sendSync } from "./dispatch_json.ts";
+
+export type TestEvent =
+  | { plan: { pending: number; filtered: number } }
+  | {
+      result: {
+        name: string;
+        status: "passed" | "failed" | "ignored";
+        duration: number;
+        error?: string;
+      };
+    };
+
+export function reportTestEvent(event: TestEvent): void {
+  sendSync("op_test_event", event);
+}
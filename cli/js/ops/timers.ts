@@ -1,12 +1,37 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { sendSync, sendAsync } from "./dispatch_json.ts";
+import { abortCancelHandle, createCancelHandle } from "./cancel.ts";
+import { close } from "./resources.ts";
+import { AbortError } from "../errors.ts";
+
+// The cancel handle of the pending global timer, if any.
+let globalTimerCancelRid: number | null = null;
 
 export function stopGlobalTimer(): void {
-  sendSync("op_global_timer_stop");
+  if (globalTimerCancelRid !== null) {
+    abortCancelHandle(globalTimerCancelRid);
+    globalTimerCancelRid = null;
+  }
 }
 
+/** Resolves after `timeout` milliseconds, or earlier if the timer is stopped
+ * or replaced by a new one. */
 export async function startGlobalTimer(timeout: number): Promise<void> {
-  await sendAsync("op_global_timer", { timeout });
+  stopGlobalTimer();
+  const cancelRid = createCancelHandle();
+  globalTimerCancelRid = cancelRid;
+  try {
+    await sendAsync("op_global_timer", { timeout, cancelRid });
+  } catch (error) {
+    if (!(error instanceof AbortError)) {
+      throw error;
+    }
+  } finally {
+    if (globalTimerCancelRid === cancelRid) {
+      globalTimerCancelRid = null;
+    }
+    close(cancelRid);
+  }
 }
 
 interface NowResponse {
@@ -42,8 +42,11 @@ interface AcceptTLSResponse {
   };
 }
 
-export function acceptTLS(rid: number): Promise<AcceptTLSResponse> {
-  return sendAsync("op_accept_tls", { rid });
+export function acceptTLS(
+  rid: number,
+  cancelRid?: number
+): Promise<AcceptTLSResponse> {
+  return sendAsync("op_accept_tls", { rid, cancelRid });
 }
 
 export interface ListenTLSRequest {
@@ -0,0 +1,34 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export interface UrlParts {
+  href: string;
+  hash: string;
+  host: string;
+  hostname: string;
+  origin: string;
+  password: string;
+  pathname: string;
+  port: string;
+  protocol: string;
+  search: string;
+  username: string;
+}
+
+export type UrlSetters = Partial<Omit<UrlParts, "href" | "origin">>;
+
+export function parseUrl(
+  href: string,
+  baseHref?: string,
+  setters: UrlSetters = {}
+): UrlParts {
+  return sendSync("op_url_parse", { href, baseHref, ...setters });
+}
+
+export function parseUrlEncoded(query: string): Array<[string, string]> {
+  return sendSync("op_url_parse_search_params", { query });
+}
+
+export function stringifyUrlEncoded(pairs: Array<[string, string]>): string {
+  return sendSync("op_url_stringify_search_params", { pairs });
+}
@@ -0,0 +1,34 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+
+export function length(persistent: boolean): number {
+  return sendSync("op_webstorage_length", { persistent });
+}
+
+export function key(persistent: boolean, index: number): string | null {
+  return sendSync("op_webstorage_key", { persistent, index });
+}
+
+export function keys(persistent: boolean): string[] {
+  return sendSync("op_webstorage_keys", { persistent });
+}
+
+export function getItem(persistent: boolean, key: string): string | null {
+  return sendSync("op_webstorage_get", { persistent, key });
+}
+
+export function setItem(
+  persistent: boolean,
+  key: string,
+  value: string
+): void {
+  sendSync("op_webstorage_set", { persistent, key, value });
+}
+
+export function removeItem(persistent: boolean, key: string): void {
+  sendSync("op_webstorage_remove", { persistent, key });
+}
+
+export function clear(persistent: boolean): void {
+  sendSync("op_webstorage_clear", { persistent });
+}
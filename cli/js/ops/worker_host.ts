@@ -2,12 +2,26 @@
 /* eslint-disable @typescript-eslint/no-explicit-any */
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 
+// Keep in sync with `PermissionsSubset` in cli/permissions.rs
+export interface PermissionsSubset {
+  read?: boolean | string[];
+  write?: boolean | string[];
+  net?: boolean | string[];
+  env?: boolean;
+  run?: boolean;
+  plugin?: boolean;
+  ffi?: boolean;
+  hrtime?: boolean;
+}
+
 export function createWorker(
   specifier: string,
   hasSourceCode: boolean,
   sourceCode: string,
   useDenoNamespace: boolean,
-  name?: string
+  type: "classic" | "module",
+  name?: string,
+  permissions?: PermissionsSubset
 ): { id: number } {
   return sendSync("op_create_worker", {
     specifier,
@@ -15,6 +29,8 @@ export function createWorker(
     sourceCode,
     name,
     useDenoNamespace,
+    type,
+    permissions,
   });
 }
 
@@ -29,3 +45,28 @@ export function hostPostMessage(id: number, data: Uint8Array): void {
 export function hostGetMessage(id: number): Promise<any> {
   return sendAsync("op_host_get_message", { id });
 }
+
+export interface WorkerInfo {
+  id: number;
+  name: string;
+  specifier: string;
+  status: "running" | "terminated" | "errored";
+  queuedMessages: number;
+  uptime: number;
+}
+
+export function workers(): WorkerInfo[] {
+  return sendSync("op_host_get_workers");
+}
+
+export function broadcastSubscribe(name: string): { rid: number } {
+  return sendSync("op_broadcast_subscribe", { name });
+}
+
+export function broadcastPost(rid: number, data: Uint8Array): void {
+  sendSync("op_broadcast_post", { rid }, data);
+}
+
+export function broadcastRecv(rid: number): Promise<string | null> {
+  return sendAsync("op_broadcast_recv", { rid });
+}
@@ -8,6 +8,7 @@ export type PermissionName =
   | "env"
   | "run"
   | "plugin"
+  | "ffi"
   | "hrtime";
 // NOTE: Keep in sync with cli/permissions.rs
 
@@ -40,6 +41,10 @@ export interface PluginPermissionDescriptor {
   name: "plugin";
 }
 
+export interface FfiPermissionDescriptor {
+  name: "ffi";
+}
+
 export interface HrtimePermissionDescriptor {
   name: "hrtime";
 }
@@ -51,6 +56,7 @@ export type PermissionDescriptor =
   | NetPermissionDescriptor
   | EnvPermissionDescriptor
   | PluginPermissionDescriptor
+  | FfiPermissionDescriptor
   | HrtimePermissionDescriptor;
 
 export class PermissionStatus {
@@ -1,9 +1,15 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { File } from "./files.ts";
 import { close } from "./ops/resources.ts";
-import { Closer, Reader, Writer } from "./io.ts";
 import { readAll } from "./buffer.ts";
-import { kill, runStatus as runStatusOp, run as runOp } from "./ops/process.ts";
+import {
+  kill,
+  runStatus as runStatusOp,
+  run as runOp,
+  runOutput as runOutputOp,
+  runOutputTake as runOutputTakeOp,
+  RunStatusResponse,
+} from "./ops/process.ts";
 
 export type ProcessStdio = "inherit" | "piped" | "null";
 
@@ -16,11 +22,13 @@ export interface RunOptions {
   stdout?: ProcessStdio | number;
   stderr?: ProcessStdio | number;
   stdin?: ProcessStdio | number;
+  detached?: boolean;
+  uid?: number;
+  gid?: number;
+  pgid?: number;
 }
 
-async function runStatus(rid: number): Promise<ProcessStatus> {
-  const res = await runStatusOp(rid);
-
+function toProcessStatus(res: RunStatusResponse): ProcessStatus {
   if (res.gotSignal) {
     const signal = res.exitSignal;
     return { signal, success: false };
@@ -30,12 +38,22 @@ async function runStatus(rid: number): Promise<ProcessStatus> {
   }
 }
 
+async function runStatus(rid: number): Promise<ProcessStatus> {
+  return toProcessStatus(await runStatusOp(rid));
+}
+
+export interface ProcessOutput {
+  status: ProcessStatus;
+  stdout: Uint8Array;
+  stderr: Uint8Array;
+}
+
 export class Process {
   readonly rid: number;
   readonly pid: number;
-  readonly stdin?: Writer & Closer;
-  readonly stdout?: Reader & Closer;
-  readonly stderr?: Reader & Closer;
+  readonly stdin?: File;
+  readonly stdout?: File;
+  readonly stderr?: File;
 
   // @internal
   constructor(res: RunResponse) {
@@ -59,6 +77,21 @@ export class Process {
     return runStatus(this.rid);
   }
 
+  async outputAll(): Promise<ProcessOutput> {
+    const res = await runOutputOp(
+      this.rid,
+      this.stdout?.rid,
+      this.stderr?.rid
+    );
+    const buf = new Uint8Array(res.stdoutLength + res.stderrLength);
+    runOutputTakeOp(res.outputRid, buf);
+    return {
+      status: toProcessStatus(res.status),
+      stdout: buf.subarray(0, res.stdoutLength),
+      stderr: buf.subarray(res.stdoutLength),
+    };
+  }
+
   async output(): Promise<Uint8Array> {
     if (!this.stdout) {
       throw new Error("Process.output: stdout is undefined");
@@ -114,6 +147,10 @@ export function run({
   stdout = "inherit",
   stderr = "inherit",
   stdin = "inherit",
+  detached = false,
+  uid = undefined,
+  gid = undefined,
+  pgid = undefined,
 }: RunOptions): Process {
   const res = runOp({
     cmd: cmd.map(String),
@@ -125,6 +162,10 @@ export function run({
     stdinRid: isRid(stdin) ? stdin : 0,
     stdoutRid: isRid(stdout) ? stdout : 0,
     stderrRid: isRid(stderr) ? stderr : 0,
+    detached,
+    uid,
+    gid,
+    pgid,
   }) as RunResponse;
   return new Process(res);
 }
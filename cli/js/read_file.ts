@@ -1,6 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { open, openSync } from "./files.ts";
 import { readAll, readAllSync } from "./buffer.ts";
+import { read } from "./ops/io.ts";
+import { abortable } from "./web/abort_signal.ts";
+
+export interface ReadFileOptions {
+  signal?: AbortSignal;
+}
 
 export function readFileSync(path: string): Uint8Array {
   const file = openSync(path);
@@ -9,9 +15,19 @@ export function readFileSync(path: string): Uint8Array {
   return contents;
 }
 
-export async function readFile(path: string): Promise<Uint8Array> {
-  const file = await open(path);
-  const contents = await readAll(file);
-  file.close();
-  return contents;
+export function readFile(
+  path: string,
+  options: ReadFileOptions = {}
+): Promise<Uint8Array> {
+  return abortable(options.signal, async (cancelRid) => {
+    const file = await open(path);
+    try {
+      return await readAll({
+        read: (p: Uint8Array): Promise<number | null> =>
+          read(file.rid, p, cancelRid),
+      });
+    } finally {
+      file.close();
+    }
+  });
 }
@@ -0,0 +1,20 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { openLineReader, readLines as readLinesOp } from "./ops/io.ts";
+import { close } from "./ops/resources.ts";
+
+export async function* readLines(r: {
+  rid: number;
+}): AsyncIterableIterator<string> {
+  const rid = openLineReader(r.rid);
+  try {
+    while (true) {
+      const { lines, done } = await readLinesOp(rid);
+      yield* lines;
+      if (done) {
+        break;
+      }
+    }
+  } finally {
+    close(rid);
+  }
+}
@@ -1,5 +1,6 @@
-import { open, openSync } from "./files.ts";
-import { readAll, readAllSync } from "./buffer.ts";
+import { openSync } from "./files.ts";
+import { readAllSync } from "./buffer.ts";
+import { readFile, ReadFileOptions } from "./read_file.ts";
 
 export function readTextFileSync(path: string): string {
   const decoder = new TextDecoder();
@@ -9,10 +10,11 @@ export function readTextFileSync(path: string): string {
   return decoder.decode(content);
 }
 
-export async function readTextFile(path: string): Promise<string> {
+export async function readTextFile(
+  path: string,
+  options: ReadFileOptions = {}
+): Promise<string> {
   const decoder = new TextDecoder();
-  const file = await open(path);
-  const content = await readAll(file);
-  file.close();
+  const content = await readFile(path, options);
   return decoder.decode(content);
 }
@@ -2,8 +2,9 @@
 import { exit } from "./ops/os.ts";
 import { core } from "./core.ts";
 import { version } from "./version.ts";
-import { stringifyArgs } from "./web/console.ts";
-import { startRepl, readline } from "./ops/repl.ts";
+import { inspect, stringifyArgs } from "./web/console.ts";
+import { startRepl, readline, replCheck } from "./ops/repl.ts";
+import { formatDiagnostics } from "./ops/errors.ts";
 import { close } from "./ops/resources.ts";
 
 function replLog(...args: unknown[]): void {
@@ -24,28 +25,194 @@ const recoverableErrorMessages = [
   "Missing catch or finally after try", // try {}
   "missing ) after argument list", // console.log(1
   "Unterminated template literal", // `template
-  // TODO(kevinkassimo): need a parser to handling errors such as:
-  // "Missing } in template expression" // `${ or `${ a 123 }`
 ];
 
 function isRecoverableError(e: Error): boolean {
   return recoverableErrorMessages.includes(e.message);
 }
 
+// Characters after which `/` starts a regular expression literal rather
+// than being a division operator.
+const regExpPrecedingChars = "(,=:[!&|?{};+-*%<>~^";
+
+// Identifier or keyword found outside of any brackets.
+interface TopLevelWord {
+  word: string;
+  start: number;
+  // Whether the word starts a statement.
+  statementStart: boolean;
+}
+
+interface ScanResult {
+  // Input ends inside an open bracket, template literal (including `${`
+  // substitutions) or block comment.
+  incomplete: boolean;
+  words: TopLevelWord[];
+}
+
+function precededByNewline(code: string, pos: number): boolean {
+  let i = pos - 1;
+  while (i >= 0 && (code[i] === " " || code[i] === "\t")) {
+    i--;
+  }
+  return code[i] === "\n";
+}
+
+// Lightweight scanner used to tell whether the REPL should keep prompting
+// for more input before evaluating it, and to find top-level declarations.
+// Input with mismatched brackets is considered complete, so V8 can report
+// the syntax error.
+function scan(code: string): ScanResult {
+  // Stack of open brackets; "`" marks a template literal and "${" its
+  // substitution.
+  const stack: string[] = [];
+  const words: TopLevelWord[] = [];
+  let lastSignificant = "";
+  let i = 0;
+  while (i < code.length) {
+    const ch = code[i];
+    const next = code[i + 1];
+    const top = stack[stack.length - 1];
+    if (top === "`") {
+      if (ch === "\\") {
+        i += 2;
+      } else if (ch === "`") {
+        stack.pop();
+        lastSignificant = ch;
+        i++;
+      } else if (ch === "$" && next === "{") {
+        stack.push("${");
+        lastSignificant = "{";
+        i += 2;
+      } else {
+        i++;
+      }
+      continue;
+    }
+    if (ch === "/" && next === "/") {
+      const end = code.indexOf("\n", i);
+      if (end === -1) break;
+      i = end + 1;
+    } else if (ch === "/" && next === "*") {
+      const end = code.indexOf("*/", i + 2);
+      if (end === -1) return { incomplete: true, words };
+      i = end + 2;
+    } else if (ch === "'" || ch === '"' || ch === "/") {
+      if (ch === "/" && !regExpPrecedingChars.includes(lastSignificant)) {
+        // Division operator.
+        lastSignificant = ch;
+        i++;
+        continue;
+      }
+      // Skip string or regular expression literal; an unterminated one is
+      // a syntax error rather than incomplete input.
+      let inClass = false;
+      i++;
+      while (i < code.length && code[i] !== "\n") {
+        if (code[i] === "\\") {
+          i++;
+        } else if (ch === "/" && code[i] === "[") {
+          inClass = true;
+        } else if (ch === "/" && code[i] === "]") {
+          inClass = false;
+        } else if (code[i] === ch && !inClass) {
+          break;
+        }
+        i++;
+      }
+      lastSignificant = "a";
+      i++;
+    } else if (ch === "`") {
+      stack.push("`");
+      i++;
+    } else if (ch === "(" || ch === "[" || ch === "{") {
+      stack.push(ch);
+      lastSignificant = ch;
+      i++;
+    } else if (ch === ")" || ch === "]" || ch === "}") {
+      const open = stack.pop();
+      const expected = ch === ")" ? "(" : ch === "]" ? "[" : "{";
+      if (open !== expected && !(ch === "}" && open === "${")) {
+        return { incomplete: false, words };
+      }
+      lastSignificant = ch;
+      i++;
+    } else if (/[A-Za-z_$]/.test(ch)) {
+      const start = i;
+      while (i < code.length && /[\w$]/.test(code[i])) {
+        i++;
+      }
+      if (stack.length === 0) {
+        // A line break after an operand ends the statement too (ASI).
+        const statementStart =
+          ["", ";", "}"].includes(lastSignificant) ||
+          (precededByNewline(code, start) &&
+            !regExpPrecedingChars.includes(lastSignificant));
+        words.push({ word: code.slice(start, i), start, statementStart });
+      }
+      lastSignificant = "a";
+    } else {
+      if (!/\s/.test(ch)) {
+        lastSignificant = ch;
+      }
+      i++;
+    }
+  }
+  return { incomplete: stack.length > 0, words };
+}
+
+function isIncompleteInput(code: string): boolean {
+  return scan(code).incomplete;
+}
+
+// Rewrites top-level `let`, `const` and `class` declarations into `var`
+// declarations, so bindings persist on the global object across inputs and
+// can be redeclared, as in the Node.js REPL. Classes are left intact if
+// `classes` is false, so they can still be used as types in TypeScript.
+function rewriteTopLevelDeclarations(code: string, classes = true): string {
+  let out = "";
+  let lastEnd = 0;
+  for (const { word, start, statementStart } of scan(code).words) {
+    if (!statementStart) {
+      continue;
+    }
+    const rest = code.slice(start + word.length);
+    const isLexical = word === "let" || word === "const";
+    if (isLexical && /^\s*[A-Za-z_$[{]/.test(rest)) {
+      out += code.slice(lastEnd, start) + "var";
+      lastEnd = start + word.length;
+    } else if (classes && word === "class") {
+      const match = /^\s+([A-Za-z_$][\w$]*)/.exec(rest);
+      if (match) {
+        out += code.slice(lastEnd, start) + `var ${match[1]} = class`;
+        lastEnd = start + word.length;
+      }
+    }
+  }
+  return out + code.slice(lastEnd);
+}
+
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 type Value = any;
 
 let lastEvalResult: Value = undefined;
 let lastThrownError: Value = undefined;
+// Set from the response of `op_repl_start`.
+let useColors = false;
 
 // Evaluate code.
 // Returns true if code is consumed (no error/irrecoverable error).
 // Returns false if error is recoverable
 function evaluate(code: string): boolean {
-  const [result, errInfo] = core.evalContext(code);
+  const [result, errInfo] = core.evalContext(
+    rewriteTopLevelDeclarations(code)
+  );
   if (!errInfo) {
     lastEvalResult = result;
-    replLog(result);
+    replLog(inspect(result, { colors: useColors }));
+  } else if (errInfo.isInterrupted) {
+    // Ctrl-C was pressed while code was running.
+    replError("Interrupted");
   } else if (errInfo.isCompileError && isRecoverableError(errInfo.thrown)) {
     // Recoverable compiler error
     return false; // don't consume code.
@@ -61,12 +228,42 @@ function evaluate(code: string): boolean {
   return true;
 }
 
+// TypeScript source of the inputs evaluated so far in `--check` mode; new
+// input is type checked in its context.
+let checkContext = "";
+
+// Type checks input and prints diagnostics found in it, then evaluates it
+// transpiled to JavaScript. Returns the same as `evaluate()`.
+async function evaluateChecked(code: string): Promise<boolean> {
+  const { diagnostics, source } = await replCheck(checkContext + code, code);
+  const contextLines = checkContext.split("\n").length - 1;
+  const items = diagnostics
+    .filter(
+      ({ startPosition }) =>
+        startPosition == null || startPosition >= checkContext.length
+    )
+    .map((item) =>
+      item.lineNumber == null
+        ? item
+        : { ...item, lineNumber: item.lineNumber - contextLines }
+    );
+  if (items.length > 0) {
+    replError(formatDiagnostics(items));
+  }
+  const consumed = evaluate(source);
+  if (consumed) {
+    checkContext += rewriteTopLevelDeclarations(code, false) + "\n";
+  }
+  return consumed;
+}
+
 // @internal
 export async function replLoop(): Promise<void> {
   const { console } = globalThis;
 
   const historyFile = "deno_history.txt";
-  const rid = startRepl(historyFile);
+  const { rid, check, colors } = startRepl(historyFile);
+  useColors = colors;
 
   const quitRepl = (exitCode: number): void => {
     // Special handling in case user calls deno.close(3).
@@ -107,36 +304,47 @@ export async function replLoop(): Promise<void> {
   });
 
   replLog(`Deno ${version.deno}`);
-  replLog("exit using ctrl+d or close()");
+  replLog("exit using ctrl+d, ctrl+c twice or close()");
+
+  // Whether the previous top level read was interrupted by Ctrl-C.
+  let interrupted = false;
 
   while (true) {
     let code = "";
     // Top level read
     try {
       code = await readline(rid, "> ");
+      interrupted = false;
       if (code.trim() === "") {
         continue;
       }
     } catch (err) {
       if (err.message === "EOF") {
         quitRepl(0);
-      } else {
-        // If interrupted, don't print error.
-        if (err.message !== "Interrupted") {
-          // e.g. this happens when we have deno.close(3).
-          // We want to display the problem.
-          const formattedError = core.formatError(err);
-          replError(formattedError);
+      } else if (err.message === "Interrupted") {
+        // First Ctrl-C discards current line, second one in a row quits.
+        if (interrupted) {
+          quitRepl(0);
         }
-        // Quit REPL anyways.
+        interrupted = true;
+        replLog("(To exit, press ctrl+c again or ctrl+d or type close())");
+        continue;
+      } else {
+        // e.g. this happens when we have deno.close(3).
+        // We want to display the problem.
+        const formattedError = core.formatError(err);
+        replError(formattedError);
         quitRepl(1);
       }
     }
     // Start continued read
-    while (!evaluate(code)) {
+    while (
+      isIncompleteInput(code) ||
+      !(check ? await evaluateChecked(code) : evaluate(code))
+    ) {
       code += "\n";
       try {
-        code += await readline(rid, "  ");
+        code += await readline(rid, "... ");
       } catch (err) {
         // If interrupted on continued read,
         // abort this read instead of quitting.
@@ -8,6 +8,10 @@ import { setVersions } from "./version.ts";
 import { setPrepareStackTrace } from "./error_stack.ts";
 import { Start, opStart } from "./ops/runtime.ts";
 import { handleTimerMacrotask } from "./web/timers.ts";
+import { handleUncaughtException } from "./web/error_event.ts";
+import { handleUnhandledRejection } from "./web/promise_rejection_event.ts";
+import { setHardwareConcurrency } from "./web/navigator.ts";
+import { setTimeOrigin } from "./web/performance.ts";
 
 export let OPS_CACHE: { [name: string]: number };
 
@@ -21,6 +25,20 @@ function getAsyncHandler(opName: string): (msg: Uint8Array) => void {
   }
 }
 
+/** Runs the next ready timer. An exception thrown by its callback is
+ * dispatched as an `error` event, and is uncaught unless a listener handles
+ * it. */
+function handleMacrotask(): boolean {
+  try {
+    return handleTimerMacrotask();
+  } catch (error) {
+    if (!handleUncaughtException(error)) {
+      throw error;
+    }
+    return false;
+  }
+}
+
 // TODO(bartlomieju): temporary solution, must be fixed when moving
 // dispatches to separate crates
 export function initOps(): void {
@@ -28,17 +46,20 @@ export function initOps(): void {
   for (const [name, opId] of Object.entries(OPS_CACHE)) {
     core.setAsyncHandler(opId, getAsyncHandler(name));
   }
-  core.setMacrotaskCallback(handleTimerMacrotask);
+  core.setMacrotaskCallback(handleMacrotask);
+  core.setPromiseRejectCallback(handleUnhandledRejection);
 }
 
-export function start(source?: string): Start {
+export function start(source?: string, name?: string): Start {
   initOps();
-  // First we send an empty `Start` message to let the privileged side know we
+  // First we send a `Start` message to let the privileged side know we
   // are ready. The response should be a `StartRes` message containing the CLI
   // args and other info.
-  const s = opStart();
+  const s = opStart(name);
   setVersions(s.denoVersion, s.v8Version, s.tsVersion);
   setBuildInfo(s.target);
+  setHardwareConcurrency(s.cpuCount);
+  setTimeOrigin(s.timeOrigin);
   util.setLogDebug(s.debugFlag, source);
   setPrepareStackTrace(Error);
   return s;
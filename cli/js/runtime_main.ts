@@ -9,10 +9,11 @@
 
 import * as denoNs from "./deno.ts";
 import * as denoUnstableNs from "./deno_unstable.ts";
-import { exit } from "./ops/os.ts";
+import { exit, shutdownOnSigint } from "./ops/os.ts";
 import {
   readOnly,
   getterOnly,
+  nonEnumerable,
   writable,
   windowOrWorkerGlobalScopeMethods,
   windowOrWorkerGlobalScopeProperties,
@@ -24,6 +25,12 @@ import { internalObject, internalSymbol } from "./internals.ts";
 import { setSignals } from "./signals.ts";
 import { replLoop } from "./repl.ts";
 import { setTimeout } from "./web/timers.ts";
+import { eventHandlerProperty } from "./web/event_target.ts";
+import {
+  StorageImpl,
+  getLocalStorage,
+  getSessionStorage,
+} from "./web/storage.ts";
 import * as runtime from "./runtime.ts";
 import { log, immutableDefine } from "./util.ts";
 
@@ -58,10 +65,13 @@ export const mainRuntimeGlobalProperties = {
   self: readOnly(globalThis),
   // TODO(bartlomieju): from MDN docs (https://developer.mozilla.org/en-US/docs/Web/API/WorkerGlobalScope)
   // it seems those two properties should be available to workers as well
-  onload: writable(null),
-  onunload: writable(null),
+  onload: eventHandlerProperty("load"),
+  onunload: eventHandlerProperty("unload"),
   close: writable(windowClose),
   closed: getterOnly(() => windowIsClosing),
+  Storage: nonEnumerable(StorageImpl),
+  localStorage: getterOnly(getLocalStorage),
+  sessionStorage: getterOnly(getSessionStorage),
 };
 
 let hasBootstrapped = false;
@@ -80,27 +90,22 @@ export function bootstrapMainRuntime(): void {
   Object.defineProperties(globalThis, eventTargetProperties);
   Object.defineProperties(globalThis, mainRuntimeGlobalProperties);
   setEventTargetData(globalThis);
-  // Registers the handler for window.onload function.
-  globalThis.addEventListener("load", (e) => {
-    const { onload } = globalThis;
-    if (typeof onload === "function") {
-      onload(e);
-    }
-  });
-  // Registers the handler for window.onunload function.
-  globalThis.addEventListener("unload", (e) => {
-    const { onunload } = globalThis;
-    if (typeof onunload === "function") {
-      onunload(e);
-    }
-  });
 
-  const { args, cwd, noColor, pid, repl, unstableFlag } = runtime.start();
+  const {
+    args,
+    cwd,
+    mainModule,
+    noColor,
+    pid,
+    repl,
+    unstableFlag,
+  } = runtime.start();
 
   Object.defineProperties(denoNs, {
     pid: readOnly(pid),
     noColor: readOnly(noColor),
     args: readOnly(Object.freeze(args)),
+    mainModule: readOnly(mainModule),
   });
 
   if (unstableFlag) {
@@ -116,6 +121,8 @@ export function bootstrapMainRuntime(): void {
   Object.freeze(globalThis.Deno.core);
   Object.freeze(globalThis.Deno.core.sharedQueue);
   setSignals();
+  // Ctrl-C performs graceful shutdown so that `unload` listeners run.
+  shutdownOnSigint();
 
   log("cwd", cwd);
   log("args", args);
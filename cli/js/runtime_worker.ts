@@ -22,7 +22,8 @@ import * as denoNs from "./deno.ts";
 import * as denoUnstableNs from "./deno_unstable.ts";
 import * as webWorkerOps from "./ops/web_worker.ts";
 import { log, assert, immutableDefine } from "./util.ts";
-import { MessageEvent, ErrorEvent } from "./web/workers.ts";
+import { MessageEvent } from "./web/workers.ts";
+import { handleUncaughtException } from "./web/error_event.ts";
 import { TextEncoder } from "./web/text_encoding.ts";
 import * as runtime from "./runtime.ts";
 import { internalObject, internalSymbol } from "./internals.ts";
@@ -40,7 +41,7 @@ const encoder = new TextEncoder();
 // TODO(bartlomieju): remove these funtions
 // Stuff for workers
 export const onmessage: (e: { data: any }) => void = (): void => {};
-export const onerror: (e: { data: any }) => void = (): void => {};
+export const onmessageerror: (e: { data: any }) => void = (): void => {};
 
 export function postMessage(data: any): void {
   const dataJson = JSON.stringify(data);
@@ -60,7 +61,34 @@ export function close(): void {
   webWorkerOps.close();
 }
 
-export async function workerMessageRecvCallback(data: string): Promise<void> {
+function decodeMessage(dataJson: string): any {
+  // `JSON.stringify(undefined)` produces no output
+  if (dataJson === "") {
+    return undefined;
+  }
+  return JSON.parse(dataJson);
+}
+
+export async function workerMessageRecvCallback(
+  dataJson: string
+): Promise<void> {
+  let data;
+  try {
+    data = decodeMessage(dataJson);
+  } catch {
+    // Message that can't be deserialized is reported using `messageerror`
+    // event instead of `message` event and is not an exception.
+    const msgErrorEvent = new MessageEvent("messageerror", {
+      cancelable: false,
+      data,
+    });
+    if (globalThis["onmessageerror"]) {
+      globalThis.onmessageerror!(msgErrorEvent);
+    }
+    globalThis.dispatchEvent(msgErrorEvent);
+    return;
+  }
+
   const msgEvent = new MessageEvent("message", {
     cancelable: false,
     data,
@@ -75,34 +103,9 @@ export async function workerMessageRecvCallback(data: string): Promise<void> {
     }
     globalThis.dispatchEvent(msgEvent);
   } catch (e) {
-    let handled = false;
-
-    const errorEvent = new ErrorEvent("error", {
-      cancelable: true,
-      message: e.message,
-      lineno: e.lineNumber ? e.lineNumber + 1 : undefined,
-      colno: e.columnNumber ? e.columnNumber + 1 : undefined,
-      filename: e.fileName,
-      error: null,
-    });
-
-    if (globalThis["onerror"]) {
-      const ret = globalThis.onerror(
-        e.message,
-        e.fileName,
-        e.lineNumber,
-        e.columnNumber,
-        e
-      );
-      handled = ret === true;
-    }
-
-    globalThis.dispatchEvent(errorEvent);
-    if (errorEvent.defaultPrevented) {
-      handled = true;
-    }
-
-    if (!handled) {
+    // Unhandled exception is forwarded to the host where it's
+    // dispatched as `error` event on `Worker` object.
+    if (!handleUncaughtException(e)) {
       throw e;
     }
   }
@@ -111,7 +114,7 @@ export async function workerMessageRecvCallback(data: string): Promise<void> {
 export const workerRuntimeGlobalProperties = {
   self: readOnly(globalThis),
   onmessage: writable(onmessage),
-  onerror: writable(onerror),
+  onmessageerror: writable(onmessageerror),
   // TODO: should be readonly?
   close: nonEnumerable(close),
   postMessage: writable(postMessage),
@@ -137,8 +140,9 @@ export function bootstrapWorkerRuntime(
   Object.defineProperties(globalThis, eventTargetProperties);
   Object.defineProperties(globalThis, { name: readOnly(name) });
   setEventTargetData(globalThis);
-  const { unstableFlag, pid, noColor, args } = runtime.start(
-    internalName ?? name
+  const { unstableFlag, pid, noColor, args, mainModule } = runtime.start(
+    internalName ?? name,
+    name
   );
 
   if (unstableFlag) {
@@ -154,6 +158,7 @@ export function bootstrapWorkerRuntime(
       pid: readOnly(pid),
       noColor: readOnly(noColor),
       args: readOnly(Object.freeze(args)),
+      mainModule: readOnly(mainModule),
     });
     // Setup `Deno` global - we're actually overriding already
     // existing global `Deno` with `Deno` namespace from "./deno.ts".
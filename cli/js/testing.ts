@@ -7,12 +7,18 @@ import { exposeForTest } from "./internals.ts";
 import { TextEncoder } from "./web/text_encoding.ts";
 import { metrics } from "./ops/runtime.ts";
 import { resources } from "./ops/resources.ts";
-import { assert } from "./util.ts";
+import { reportTestEvent } from "./ops/testing.ts";
+import { inspector } from "./inspector.ts";
+import { pendingOpsSnapshot, setTraceOps } from "./ops/pending_ops.ts";
+import { PermissionsSubset } from "./ops/worker_host.ts";
+import { WorkerImpl } from "./web/workers.ts";
+import { AssertionError } from "./util.ts";
 
 const RED_FAILED = red("FAILED");
 const GREEN_OK = green("ok");
 const YELLOW_IGNORED = yellow("ignored");
 const disabledConsole = new Console((): void => {});
+let traceOps = false;
 
 function delay(n: number): Promise<void> {
   return new Promise((resolve: () => void, _) => {
@@ -33,6 +39,7 @@ function formatDuration(time = 0): string {
 function assertOps(fn: () => void | Promise<void>): () => void | Promise<void> {
   return async function asyncOpSanitizer(): Promise<void> {
     const pre = metrics();
+    const preOps = pendingOpsSnapshot();
     await fn();
     // Defer until next event loop turn - that way timeouts and intervals
     // cleared can actually be removed from resource table, otherwise
@@ -43,22 +50,71 @@ function assertOps(fn: () => void | Promise<void>): () => void | Promise<void> {
     // that will be a pending async op before test starts.
     const dispatchedDiff = post.opsDispatchedAsync - pre.opsDispatchedAsync;
     const completedDiff = post.opsCompletedAsync - pre.opsCompletedAsync;
-    assert(
-      dispatchedDiff === completedDiff,
-      `Test case is leaking async ops.
+    if (dispatchedDiff === completedDiff) {
+      return;
+    }
+
+    const leaked = Object.entries(pendingOpsSnapshot())
+      .filter(([token]) => !(token in preOps))
+      .map(([, op]) => op);
+    let details = "";
+    for (const { opName, stack } of leaked) {
+      details += `\n  - An async call to "${opName}" was started in this test, but never completed.`;
+      if (stack != null) {
+        details += ` The operation was started here:\n${stack}`;
+      }
+    }
+    if (leaked.length > 0 && !traceOps) {
+      details +=
+        "\n\nTo get a trace of where the leaking ops were started, " +
+        "run again with the --trace-ops flag.";
+    }
+    throw new AssertionError(
+      `Test case is leaking async ops.${details}
+
 Before:
   - dispatched: ${pre.opsDispatchedAsync}
   - completed: ${pre.opsCompletedAsync}
 After:
   - dispatched: ${post.opsDispatchedAsync}
   - completed: ${post.opsCompletedAsync}
-  
-Make sure to await all promises returned from Deno APIs before 
+
+Make sure to await all promises returned from Deno APIs before
 finishing test case.`
     );
   };
 }
 
+const RESOURCE_DESCRIPTIONS: { [name: string]: string } = {
+  fsFile: "A file",
+  fsEvents: "A file system watcher",
+  tcpListener: "A TCP listener",
+  tcpStream: "A TCP connection",
+  tlsListener: "A TLS listener",
+  clientTlsStream: "A TLS connection",
+  serverTlsStream: "A TLS connection",
+  udpSocket: "A UDP socket",
+  unixListener: "A Unix socket listener",
+  unixStream: "A Unix socket connection",
+  unixDatagram: "A Unix datagram socket",
+  child: "A child process",
+  childStdin: "A child process stdin",
+  childStdout: "A child process stdout",
+  childStderr: "A child process stderr",
+  httpBody: "A fetch response body",
+  httpClient: "An HTTP client",
+  signal: "A signal listener",
+  compression: "A compression stream",
+  textDecoder: "A streaming TextDecoder",
+  dynamicLibrary: "A dynamic library",
+  cancelHandle: "An AbortSignal cancel handle",
+};
+
+function describeResource(rid: string, name: string): string {
+  const description = RESOURCE_DESCRIPTIONS[name] ?? `A "${name}" resource`;
+  return `${description} (rid ${rid})`;
+}
+
 // Wrap test function in additional assertion that makes sure
 // the test case does not "leak" resources - ie. resource table after
 // the test has exactly the same contents as before the test.
@@ -70,15 +126,32 @@ function assertResources(
     await fn();
     const post = resources();
 
-    const preStr = JSON.stringify(pre, null, 2);
-    const postStr = JSON.stringify(post, null, 2);
-    const msg = `Test case is leaking resources.
-Before: ${preStr}
-After: ${postStr}
+    let details = "";
+    for (const [rid, name] of Object.entries(post)) {
+      if (pre[Number(rid)] !== name) {
+        details += `\n  - ${describeResource(
+          rid,
+          name
+        )} was opened during the test, but not closed.`;
+      }
+    }
+    for (const [rid, name] of Object.entries(pre)) {
+      if (post[Number(rid)] !== name) {
+        details += `\n  - ${describeResource(
+          rid,
+          name
+        )} was opened before the test started, but was closed during the test.`;
+      }
+    }
+    if (details === "") {
+      return;
+    }
+    throw new AssertionError(
+      `Test case is leaking resources.${details}
 
-Make sure to close all open resource handles returned from Deno APIs before 
-finishing test case.`;
-    assert(preStr === postStr, msg);
+Make sure to close all open resource handles returned from Deno APIs before
+finishing test case.`
+    );
   };
 }
 
@@ -88,10 +161,105 @@ export interface TestDefinition {
   ignore?: boolean;
   sanitizeOps?: boolean;
   sanitizeResources?: boolean;
+  permissions?: PermissionsSubset;
 }
 
 const TEST_REGISTRY: TestDefinition[] = [];
 
+// Tests with a `permissions` option are run in a worker with this name, which
+// loads the module that declared the test and runs only that test.
+const TEST_WORKER_NAME = "$deno$test";
+
+function isTestWorker(): boolean {
+  // @ts-ignore
+  return globalThis.name === TEST_WORKER_NAME;
+}
+
+// Returns the URL of the module that called `test()`.
+function callerModuleUrl(): string | null {
+  const originalPrepareStackTrace = Error.prepareStackTrace;
+  let fileName: string | null = null;
+  Error.prepareStackTrace = (_error, callSites): string => {
+    fileName = callSites[0]?.getFileName() ?? null;
+    return "";
+  };
+  try {
+    const holder: { stack?: string } = {};
+    // @ts-ignore
+    Error.captureStackTrace(holder, test);
+    // The stack trace is only prepared once it's accessed.
+    return holder.stack !== undefined ? fileName : null;
+  } finally {
+    Error.prepareStackTrace = originalPrepareStackTrace;
+  }
+}
+
+interface TestWorkerResult {
+  error?: { name: string; message: string; stack?: string };
+}
+
+// Wrap test function so it's run in a worker whose permissions are the
+// given subset of the permissions of this process. Sanitizers are applied
+// inside the worker.
+function runInWorker(
+  moduleUrl: string,
+  name: string,
+  permissions: PermissionsSubset
+): () => Promise<void> {
+  return function testInWorker(): Promise<void> {
+    const source =
+      `import ${JSON.stringify(moduleUrl)};\n` +
+      `Deno[Deno.internal].runTestInWorker(${JSON.stringify(name)});\n`;
+    const worker = new WorkerImpl(
+      `data:application/javascript,${encodeURIComponent(source)}`,
+      { type: "module", name: TEST_WORKER_NAME, deno: true, permissions }
+    );
+    return new Promise((resolve, reject): void => {
+      worker.onmessage = (e): void => {
+        worker.terminate();
+        const { error } = e.data as TestWorkerResult;
+        if (error == null) {
+          resolve();
+          return;
+        }
+        const err = new Error(error.message);
+        err.name = error.name;
+        err.stack = error.stack;
+        reject(err);
+      };
+      worker.onerror = (e): void => {
+        e.preventDefault();
+        worker.terminate();
+        reject(new Error(e.message));
+      };
+    });
+  };
+}
+
+// Entry point of a test worker, see `runInWorker()`.
+async function runTestInWorker(name: string): Promise<void> {
+  const testDef = TEST_REGISTRY.find(
+    (t) => t.name === name && t.permissions != null
+  );
+  const result: TestWorkerResult = {};
+  try {
+    if (testDef == null) {
+      throw new Error(`Test "${name}" was not declared in the test worker`);
+    }
+    await testDef.fn();
+  } catch (err) {
+    result.error = {
+      name: err?.name ?? "Error",
+      message: err?.message ?? String(err),
+      stack: err?.stack,
+    };
+  }
+  // @ts-ignore
+  globalThis.postMessage(result);
+}
+
+exposeForTest("runTestInWorker", runTestInWorker);
+
 export function test(t: TestDefinition): void;
 export function test(name: string, fn: () => void | Promise<void>): void;
 // Main test function provided by Deno, as you can see it merely
@@ -125,6 +293,18 @@ export function test(
     testDef = { ...defaults, ...t };
   }
 
+  if (testDef.permissions != null && !isTestWorker()) {
+    const moduleUrl = callerModuleUrl();
+    if (moduleUrl == null) {
+      throw new TypeError(
+        "Can't run a test with permissions: the module declaring it is unknown"
+      );
+    }
+    testDef.fn = runInWorker(moduleUrl, testDef.name, testDef.permissions);
+    TEST_REGISTRY.push(testDef);
+    return;
+  }
+
   if (testDef.sanitizeOps) {
     testDef.fn = assertOps(testDef.fn);
   }
@@ -223,6 +403,26 @@ function reportToConsole(message: TestMessage): void {
 
 exposeForTest("reportToConsole", reportToConsole);
 
+// Sends results to the Rust side of `deno test`, which aggregates them from
+// all test workers and prints the report.
+function reportToRunner(message: TestMessage): void {
+  if (message.start != null) {
+    const pending = message.start.tests.length;
+    const filtered = TEST_REGISTRY.length - pending;
+    reportTestEvent({ plan: { pending, filtered } });
+  } else if (message.testEnd != null) {
+    const { name, status, duration, error } = message.testEnd;
+    reportTestEvent({
+      result: {
+        name,
+        status,
+        duration,
+        error: error != null ? stringifyArgs([error]) : undefined,
+      },
+    });
+  }
+}
+
 // TODO: already implements AsyncGenerator<RunTestsMessage>, but add as "implements to class"
 // TODO: implements PromiseLike<RunTestsEndResult>
 class TestApi {
@@ -318,6 +518,10 @@ interface RunTestsOptions {
   skip?: string | RegExp;
   disableLog?: boolean;
   reportToConsole?: boolean;
+  reportToRunner?: boolean;
+  traceOps?: boolean;
+  // Write the coverage to the directory given with `deno test --coverage`.
+  coverage?: boolean;
   onMessage?: (message: TestMessage) => void | Promise<void>;
 }
 
@@ -328,8 +532,13 @@ async function runTests({
   skip = undefined,
   disableLog = false,
   reportToConsole: reportToConsole_ = true,
+  reportToRunner: reportToRunner_ = false,
+  traceOps: traceOps_ = false,
+  coverage = false,
   onMessage = undefined,
 }: RunTestsOptions = {}): Promise<TestMessage["end"] & {}> {
+  traceOps = traceOps_;
+  setTraceOps(traceOps);
   const filterFn = createFilterFn(filter, skip);
   const testApi = new TestApi(TEST_REGISTRY, filterFn, failFast);
 
@@ -350,6 +559,9 @@ async function runTests({
     if (reportToConsole_) {
       reportToConsole(message);
     }
+    if (reportToRunner_) {
+      reportToRunner(message);
+    }
     if (message.end != null) {
       endMsg = message.end;
     }
@@ -360,6 +572,10 @@ async function runTests({
     globalThis.console = originalConsole;
   }
 
+  if (coverage) {
+    inspector.report("coverage");
+  }
+
   if (endMsg!.failed > 0 && exitOnFail) {
     exit(1);
   }
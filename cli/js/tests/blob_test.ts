@@ -90,3 +90,65 @@ unitTest(async function blobStream(): Promise<void> {
   await read();
   assertEquals(decode(bytes), "Hello World");
 });
+
+unitTest(async function blobStreamChunks(): Promise<void> {
+  const data = new Uint8Array(100 * 1024).map((_, i): number => i % 251);
+  const blob = new Blob([data]);
+  const reader = blob.stream().getReader();
+  let bytes = new Uint8Array();
+  let chunks = 0;
+  while (true) {
+    const { done, value } = await reader.read();
+    if (done) {
+      break;
+    }
+    bytes = concat(bytes, value);
+    chunks++;
+  }
+  assertEquals(chunks, 2);
+  assertEquals(bytes, data);
+});
+
+unitTest(async function blobFromBlobs(): Promise<void> {
+  const a = new Blob([new Uint8Array([0, 1, 2, 255])]);
+  const b = new Blob([a.slice(1), "x", a.slice(-4, -3)]);
+  assertEquals(b.size, 5);
+  assertEquals(
+    new Uint8Array(await b.arrayBuffer()),
+    new Uint8Array([1, 2, 255, 120, 0])
+  );
+  const c = b.slice(1, 4).slice(1);
+  assertEquals(c.size, 2);
+  const bytes = new Uint8Array(await c.arrayBuffer());
+  assertEquals(bytes, new Uint8Array([255, 120]));
+});
+
+unitTest(async function blobObjectUrlFetch(): Promise<void> {
+  const blob = new Blob(["Hello World"], { type: "text/plain" });
+  const url = URL.createObjectURL(blob);
+  assert(url.startsWith("blob:null/"));
+  const response = await fetch(url);
+  assertEquals(response.status, 200);
+  assertEquals(response.headers.get("content-type"), "text/plain");
+  assertEquals(response.headers.get("content-length"), "11");
+  assertEquals(await response.text(), "Hello World");
+
+  URL.revokeObjectURL(url);
+  let err;
+  try {
+    await fetch(url);
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof TypeError);
+});
+
+unitTest(async function blobObjectUrlImport(): Promise<void> {
+  const blob = new Blob(["export const answer = 42;"], {
+    type: "application/javascript",
+  });
+  const url = URL.createObjectURL(blob);
+  const mod = await import(url);
+  assertEquals(mod.answer, 42);
+  URL.revokeObjectURL(url);
+});
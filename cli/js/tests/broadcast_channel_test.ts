@@ -0,0 +1,50 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import {
+  unitTest,
+  assert,
+  assertEquals,
+  assertThrows,
+  createResolvable,
+} from "./test_util.ts";
+
+unitTest(async function broadcastChannelPostMessage(): Promise<void> {
+  const promise = createResolvable();
+  const c1 = new BroadcastChannel("test");
+  const c2 = new BroadcastChannel("test");
+  const other = new BroadcastChannel("other");
+  assertEquals(c1.name, "test");
+
+  c1.onmessage = (): void => {
+    throw new Error("sender should not receive its own message");
+  };
+  other.onmessage = (): void => {
+    throw new Error("unreachable");
+  };
+  c2.onmessage = (e): void => {
+    assertEquals(e.data, { hello: "world" });
+    promise.resolve();
+  };
+
+  c1.postMessage({ hello: "world" });
+  await promise;
+  c1.close();
+  c2.close();
+  other.close();
+});
+
+unitTest(function broadcastChannelPostAfterClose(): void {
+  const c = new BroadcastChannel("test");
+  c.close();
+  // Closing twice is a noop
+  c.close();
+  assertThrows((): void => {
+    c.postMessage("hello");
+  }, DOMException);
+});
+
+unitTest(function broadcastChannelIsEventTarget(): void {
+  const c = new BroadcastChannel("test");
+  assert(c instanceof EventTarget);
+  assertEquals(Object.prototype.toString.call(c), "[object BroadcastChannel]");
+  c.close();
+});
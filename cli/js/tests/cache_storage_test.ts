@@ -0,0 +1,98 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals } from "./test_util.ts";
+
+const fixture = "http://localhost:4545/cli/tests/fixture.json";
+
+unitTest(async function cacheStorageOpenHasDelete(): Promise<void> {
+  await caches.open("cacheStorageOpenHasDelete");
+  assert(await caches.has("cacheStorageOpenHasDelete"));
+  assert((await caches.keys()).includes("cacheStorageOpenHasDelete"));
+
+  assert(await caches.delete("cacheStorageOpenHasDelete"));
+  assert(!(await caches.has("cacheStorageOpenHasDelete")));
+  assert(!(await caches.delete("cacheStorageOpenHasDelete")));
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function cachePutMatchDelete(): Promise<void> {
+    const cache = await caches.open("cachePutMatchDelete");
+    const expected = await (await fetch(fixture)).text();
+    const fetched = await fetch(fixture);
+    await cache.put(fixture, fetched);
+    assert(fetched.bodyUsed);
+
+    const response = await cache.match(fixture);
+    assert(response);
+    assertEquals(response.status, 200);
+    assertEquals(response.url, fixture);
+    assertEquals(await response.text(), expected);
+
+    // The fragment is ignored and the query string only with ignoreSearch.
+    assert(await cache.match(fixture + "#fragment"));
+    assertEquals(await cache.match(fixture + "?query"), undefined);
+    const ignored = await cache.match(fixture + "?query", {
+      ignoreSearch: true,
+    });
+    assert(ignored);
+    await ignored.body?.close();
+
+    const keys = await cache.keys();
+    assertEquals(keys.length, 1);
+    assertEquals(keys[0].url, fixture);
+
+    assert(await cache.delete(fixture));
+    assertEquals(await cache.match(fixture), undefined);
+    await caches.delete("cachePutMatchDelete");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function cacheStorageMatch(): Promise<void> {
+    const cache = await caches.open("cacheStorageMatch");
+    await cache.add(fixture);
+
+    const response = await caches.match(fixture);
+    assert(response);
+    assertEquals(await response.json(), await (await fetch(fixture)).json());
+    const missing = await caches.match(fixture, { cacheName: "missing" });
+    assertEquals(missing, undefined);
+    await caches.delete("cacheStorageMatch");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function cacheAddNotFound(): Promise<void> {
+    const cache = await caches.open("cacheAddNotFound");
+    let thrown = false;
+    try {
+      await cache.add("http://localhost:4545/cli/tests/missing.json");
+    } catch (err) {
+      assert(err instanceof TypeError);
+      thrown = true;
+    }
+    assert(thrown);
+    assertEquals(await cache.keys(), []);
+    await caches.delete("cacheAddNotFound");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function cachePutInvalid(): Promise<void> {
+    const cache = await caches.open("cachePutInvalid");
+    const response = await fetch(fixture);
+    let thrown = false;
+    try {
+      await cache.put(new Request(fixture, { method: "POST" }), response);
+    } catch (err) {
+      assert(err instanceof TypeError);
+      thrown = true;
+    }
+    assert(thrown);
+    await response.body?.close();
+    await caches.delete("cachePutInvalid");
+  }
+);
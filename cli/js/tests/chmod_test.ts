@@ -50,6 +50,26 @@ unitTest(
   }
 );
 
+unitTest(
+  { ignore: Deno.build.os !== "windows", perms: { read: true, write: true } },
+  function chmodSyncReadOnlyOnWindows(): void {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    Deno.writeFileSync(filename, new TextEncoder().encode("Hello"));
+
+    Deno.chmodSync(filename, 0o444);
+    let err;
+    try {
+      Deno.writeFileSync(filename, new TextEncoder().encode("World"));
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.PermissionDenied);
+
+    Deno.chmodSync(filename, 0o666);
+    Deno.writeFileSync(filename, new TextEncoder().encode("World"));
+  }
+);
+
 unitTest({ perms: { write: true } }, function chmodSyncFailure(): void {
   let err;
   try {
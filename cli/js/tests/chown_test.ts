@@ -1,7 +1,21 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { unitTest, assertEquals, assert } from "./test_util.ts";
 
-// chown on Windows is noop for now, so ignore its testing on Windows
+unitTest(
+  { ignore: Deno.build.os !== "windows", perms: { write: true } },
+  function chownSyncNotSupportedOnWindows(): void {
+    const filePath = Deno.makeTempFileSync();
+    let err;
+    try {
+      Deno.chownSync(filePath, 1000, 1000);
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.NotSupported);
+  }
+);
+
+// chown isn't supported on Windows, so ignore the other tests there
 if (Deno.build.os !== "windows") {
   async function getUidAndGid(): Promise<{ uid: number; gid: number }> {
     // get the user ID and group ID of the current process
@@ -0,0 +1,91 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals, assertThrows } from "./test_util.ts";
+
+async function readAll(
+  readable: ReadableStream<Uint8Array>
+): Promise<Uint8Array> {
+  const chunks: Uint8Array[] = [];
+  let length = 0;
+  for await (const chunk of readable.getIterator()) {
+    chunks.push(chunk);
+    length += chunk.length;
+  }
+  const result = new Uint8Array(length);
+  let offset = 0;
+  for (const chunk of chunks) {
+    result.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return result;
+}
+
+async function transform(
+  stream: CompressionStream | DecompressionStream,
+  chunks: Uint8Array[]
+): Promise<Uint8Array> {
+  const writer = stream.writable.getWriter();
+  const writing = (async (): Promise<void> => {
+    for (const chunk of chunks) {
+      await writer.write(chunk);
+    }
+    await writer.close();
+  })();
+  const [output] = await Promise.all([readAll(stream.readable), writing]);
+  return output;
+}
+
+async function assertRoundTrip(format: string): Promise<void> {
+  const input = new TextEncoder().encode("hello world ".repeat(1000));
+  const compressed = await transform(new CompressionStream(format), [
+    input.subarray(0, 10),
+    input.subarray(10),
+  ]);
+  assert(compressed.length < input.length);
+  const decompressed = await transform(new DecompressionStream(format), [
+    compressed.subarray(0, 5),
+    compressed.subarray(5),
+  ]);
+  assertEquals(decompressed, input);
+}
+
+unitTest(async function compressionStreamGzip(): Promise<void> {
+  await assertRoundTrip("gzip");
+});
+
+unitTest(async function compressionStreamDeflate(): Promise<void> {
+  await assertRoundTrip("deflate");
+});
+
+unitTest(async function compressionStreamDeflateRaw(): Promise<void> {
+  await assertRoundTrip("deflate-raw");
+});
+
+unitTest(async function compressionStreamBrotli(): Promise<void> {
+  await assertRoundTrip("br");
+});
+
+unitTest(async function compressionStreamGzipHeader(): Promise<void> {
+  const compressed = await transform(new CompressionStream("gzip"), [
+    new Uint8Array([1, 2, 3]),
+  ]);
+  assertEquals(compressed[0], 0x1f);
+  assertEquals(compressed[1], 0x8b);
+});
+
+unitTest(async function decompressionStreamInvalidData(): Promise<void> {
+  let err;
+  try {
+    await transform(new DecompressionStream("gzip"), [
+      new Uint8Array([1, 2, 3, 4, 5, 6, 7, 8, 9]),
+    ]);
+  } catch (e) {
+    err = e;
+  }
+  assert(err);
+});
+
+unitTest(function compressionStreamUnsupportedFormat(): void {
+  assertThrows(() => {
+    new CompressionStream("zstd");
+  }, TypeError);
+});
@@ -248,6 +248,49 @@ unitTest(function consoleTestStringifyWithDepth(): void {
   );
 });
 
+unitTest(function consoleTestInspectColors(): void {
+  const obj = { a: 1, b: "x", c: null, d: [true, undefined] };
+  assertEquals(
+    inspect(obj, { colors: true }),
+    '{ a: \x1b[33m1\x1b[39m, b: \x1b[32m"x"\x1b[39m, c: \x1b[1mnull\x1b[22m, ' +
+      "d: [ \x1b[33mtrue\x1b[39m, \x1b[90mundefined\x1b[39m ] }"
+  );
+  const circular: { self?: unknown } = {};
+  circular.self = circular;
+  assertEquals(
+    inspect(circular, { colors: true }),
+    "{ self: \x1b[36m[Circular]\x1b[39m }"
+  );
+  assertEquals(
+    inspect(Promise.resolve(/x/), { colors: true }),
+    "Promise { \x1b[31m/x/\x1b[39m }"
+  );
+  // Colors are off by default.
+  assertEquals(inspect({ a: 1 }), "{ a: 1 }");
+});
+
+unitTest(function consoleTestInspectInternals(): void {
+  // Contents of maps, sets and typed arrays don't depend on methods that
+  // can be overridden.
+  const map = new Map([[1, "one"]]);
+  map.entries = (): never => {
+    throw new Error("entries");
+  };
+  assertEquals(inspect(map), `Map { 1 => "one" }`);
+  const set = new Set([1, 2]);
+  set.entries = (): never => {
+    throw new Error("entries");
+  };
+  assertEquals(inspect(set), "Set { 1, 2 }");
+  const typedArray = new Uint8Array([1, 2, 3]);
+  Object.defineProperty(typedArray, "length", { value: 1 });
+  assertEquals(inspect(typedArray), "Uint8Array(3) [ 1, 2, 3 ]");
+  assertEquals(
+    inspect(new Float64Array(new ArrayBuffer(24), 8)),
+    "Float64Array(2) [ 0, 0 ]"
+  );
+});
+
 unitTest(function consoleTestStringifyLargeObject(): void {
   const obj = {
     a: 2,
@@ -677,6 +720,25 @@ unitTest(function consoleTestWithStringFormatSpecifier(): void {
   assertEquals(stringify("%s %s", 42, 43), "42 43");
   assertEquals(stringify("%s %s", 42), "42 %s");
   assertEquals(stringify("%s", Symbol("foo")), "Symbol(foo)");
+  assertEquals(stringify("%s", 5n), "5n");
+  assertEquals(stringify("%s", -0), "-0");
+  assertEquals(stringify("%s", { a: { b: 1 } }), "{ a: [Object] }");
+  assertEquals(stringify("%s", [1, 2]), "[ 1, 2 ]");
+  assertEquals(
+    stringify("%s", {
+      toString(): string {
+        return "custom";
+      },
+    }),
+    "custom"
+  );
+});
+
+unitTest(function consoleTestWithStyleFormatSpecifier(): void {
+  assertEquals(stringify("%cfoo", "color: red"), "foo");
+  assertEquals(stringify("%cfoo%cbar", "color: red", ""), "foobar");
+  assertEquals(stringify("%c%s", "color: red", "foo"), "foo");
+  assertEquals(stringify("%cfoo"), "%cfoo");
 });
 
 unitTest(function consoleTestWithObjectFormatSpecifier(): void {
@@ -1100,6 +1162,12 @@ unitTest(function consoleDir(): void {
     console.dir("DIR", { indentLevel: 2 });
     assertEquals(out.toString(), "  DIR\n");
   });
+  mockConsole((console, out): void => {
+    console.group();
+    console.dir({ a: 1 });
+    console.groupEnd();
+    assertEquals(out.toString(), "  { a: 1 }\n");
+  });
 });
 
 // console.dir test
@@ -25,3 +25,7 @@ unitTest(function toStringShouldBeWebCompatibility(): void {
   const event = new CustomEvent(type, {});
   assertEquals(event.toString(), "[object CustomEvent]");
 });
+
+unitTest(function customEventDetailDefaultsToNull(): void {
+  assertEquals(new CustomEvent("x").detail, null);
+});
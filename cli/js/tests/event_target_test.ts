@@ -229,3 +229,74 @@ unitTest(
     assertEquals(callCount, 2);
   }
 );
+
+unitTest(function removeEventListenerMatchesCapture(): void {
+  const target = new EventTarget();
+  let callCount = 0;
+  const listener = (): void => {
+    ++callCount;
+  };
+
+  target.addEventListener("foo", listener, true);
+  target.addEventListener("foo", listener, false);
+  target.removeEventListener("foo", listener, true);
+  target.dispatchEvent(new Event("foo"));
+  assertEquals(callCount, 1);
+});
+
+unitTest(function eventTargetReportsListenerExceptions(): void {
+  const target = new EventTarget();
+  const error = new Error("listener error");
+  let reported: unknown;
+  const onError = (e: Event): void => {
+    reported = (e as ErrorEvent).error;
+    e.preventDefault();
+  };
+  let callCount = 0;
+
+  addEventListener("error", onError);
+  target.addEventListener("foo", (): void => {
+    throw error;
+  });
+  target.addEventListener("foo", (): void => {
+    ++callCount;
+  });
+  target.dispatchEvent(new Event("foo"));
+  removeEventListener("error", onError);
+
+  assertEquals(reported, error);
+  assertEquals(callCount, 1);
+});
+
+unitTest(function globalEventHandlerAttributes(): void {
+  assertEquals(onerror, null);
+  let args: unknown[] = [];
+  onerror = (...rest: unknown[]): boolean => {
+    args = rest;
+    return true;
+  };
+  const error = new Error("boom");
+  const event = new ErrorEvent("error", {
+    cancelable: true,
+    message: "boom",
+    filename: "file:///a.ts",
+    lineno: 1,
+    colno: 2,
+    error,
+  });
+  dispatchEvent(event);
+  onerror = null;
+
+  assertEquals(args, ["boom", "file:///a.ts", 1, 2, error]);
+  assertEquals(event.defaultPrevented, true);
+
+  // Other handlers cancel the event by returning false.
+  onunhandledrejection = (): boolean => false;
+  const rejection = new PromiseRejectionEvent("unhandledrejection", {
+    cancelable: true,
+    promise: Promise.resolve(),
+  });
+  dispatchEvent(rejection);
+  onunhandledrejection = null;
+  assertEquals(rejection.defaultPrevented, true);
+});
@@ -92,3 +92,42 @@ unitTest(function eventIsTrusted(): void {
 
   assertEquals(desc1!.get, desc2!.get);
 });
+
+unitTest(function eventReturnValueAndSrcElement(): void {
+  const event = new Event("x", { cancelable: true });
+  assertEquals(event.returnValue, true);
+  assertEquals(event.srcElement, null);
+  event.returnValue = false;
+  assertEquals(event.defaultPrevented, true);
+  assertEquals(event.returnValue, false);
+  assertEquals(String(event), "[object Event]");
+});
+
+unitTest(function errorEventInitialized(): void {
+  const error = new Error("boom");
+  const event = new ErrorEvent("error", {
+    message: "boom",
+    filename: "file:///a.ts",
+    lineno: 1,
+    colno: 2,
+    error,
+  });
+  assertEquals(event.message, "boom");
+  assertEquals(event.filename, "file:///a.ts");
+  assertEquals(event.lineno, 1);
+  assertEquals(event.colno, 2);
+  assertEquals(event.error, error);
+  assertEquals(new ErrorEvent("error").error, null);
+  assertEquals(String(event), "[object ErrorEvent]");
+});
+
+unitTest(function promiseRejectionEventInitialized(): void {
+  const promise = Promise.resolve();
+  const event = new PromiseRejectionEvent("unhandledrejection", {
+    promise,
+    reason: "reason",
+  });
+  assertEquals(event.promise, promise);
+  assertEquals(event.reason, "reason");
+  assertEquals(String(event), "[object PromiseRejectionEvent]");
+});
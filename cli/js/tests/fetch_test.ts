@@ -43,6 +43,44 @@ unitTest({ perms: { net: true } }, async function fetchJsonSuccess(): Promise<
   assertEquals(json.name, "deno");
 });
 
+unitTest({ perms: { net: true } }, async function fetchAborted(): Promise<
+  void
+> {
+  const controller = new AbortController();
+  controller.abort();
+  let err;
+  try {
+    await fetch("http://localhost:4545/cli/tests/fixture.json", {
+      signal: controller.signal,
+    });
+  } catch (err_) {
+    err = err_;
+  }
+  assert(err instanceof DOMException);
+  assertEquals(err.name, "AbortError");
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchBodyReadAborted(): Promise<void> {
+    const controller = new AbortController();
+    const response = await fetch(
+      "http://localhost:4545/cli/tests/fixture.json",
+      { signal: controller.signal }
+    );
+    assertEquals(response.status, 200);
+    controller.abort();
+    let err;
+    try {
+      await response.text();
+    } catch (err_) {
+      err = err_;
+    }
+    assert(err instanceof DOMException);
+    assertEquals(err.name, "AbortError");
+  }
+);
+
 unitTest(async function fetchPerm(): Promise<void> {
   let err;
   try {
@@ -155,11 +193,11 @@ unitTest(
     assert(formData.has("field_1"));
     assertEquals(formData.get("field_1")!.toString(), "value_1 \r\n");
     assert(formData.has("field_2"));
-    /* TODO(ry) Re-enable this test once we bring back the global File type.
-  const file = formData.get("field_2") as File;
-  assertEquals(file.name, "file.js");
-  */
-    // Currently we cannot read from file...
+    const file = formData.get("field_2") as File;
+    assert(file instanceof File);
+    assertEquals(file.name, "file.js");
+    assertEquals(file.type, "text/javascript");
+    assertEquals(await file.text(), 'console.log("Hi")');
   }
 );
 
@@ -258,6 +296,96 @@ unitTest(
   }
 );
 
+unitTest(
+  { perms: { net: true } },
+  async function fetchInitReadableStreamBody(): Promise<void> {
+    const chunks = ["Hello", " ", "World"];
+    const stream = new ReadableStream<Uint8Array>({
+      pull(controller): void {
+        const chunk = chunks.shift();
+        if (chunk === undefined) {
+          controller.close();
+        } else {
+          controller.enqueue(new TextEncoder().encode(chunk));
+        }
+      },
+    });
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body: stream,
+    });
+    assertEquals(await response.text(), "Hello World");
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchInitErroredReadableStreamBody(): Promise<void> {
+    let pulls = 0;
+    const stream = new ReadableStream<Uint8Array>({
+      pull(controller): void {
+        if (pulls++ === 0) {
+          controller.enqueue(new TextEncoder().encode("partial"));
+        } else {
+          controller.error(new Error("body failed"));
+        }
+      },
+    });
+    let thrown = false;
+    try {
+      await fetch("http://localhost:4545/echo_server", {
+        method: "POST",
+        body: stream,
+      });
+    } catch {
+      thrown = true;
+    }
+    assert(thrown);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchResponseBodyReader(): Promise<void> {
+    const response = await fetch(
+      "http://localhost:4545/cli/tests/fixture.json"
+    );
+    const body = response.body!;
+    assert(!body.locked);
+    const reader = body.getReader();
+    assert(body.locked);
+    assert(response.bodyUsed);
+    const chunks: Uint8Array[] = [];
+    while (true) {
+      const { value, done } = await reader.read();
+      if (done) break;
+      chunks.push(value);
+    }
+    const text = chunks.map((c) => new TextDecoder().decode(c)).join("");
+    assertEquals(JSON.parse(text).name, "deno");
+    await body.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchResponseBodyPipeTo(): Promise<void> {
+    const response = await fetch(
+      "http://localhost:4545/cli/tests/fixture.json"
+    );
+    let length = 0;
+    await response.body!.pipeTo(
+      new WritableStream<Uint8Array>({
+        write(chunk): void {
+          length += chunk.length;
+        },
+      })
+    );
+    assertEquals(length, Number(response.headers.get("content-length")));
+    await response.body!.close();
+  }
+);
+
 unitTest(
   { perms: { net: true } },
   async function fetchInitURLSearchParamsBody(): Promise<void> {
@@ -307,6 +435,56 @@ unitTest(
   }
 );
 
+unitTest(
+  { perms: { net: true } },
+  async function fetchInitFormDataBlobBody(): Promise<void> {
+    const bytes = new Uint8Array([0, 13, 10, 45, 45, 255, 254]);
+    const form = new FormData();
+    form.append("field", "value \"quoted\"");
+    form.append("file", new Blob([bytes], { type: "image/png" }), "a.png");
+    const response = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body: form,
+    });
+    assertStrContains(
+      response.headers.get("content-type")!,
+      "multipart/form-data; boundary="
+    );
+    const resultForm = await response.formData();
+    assertEquals(resultForm.get("field"), 'value "quoted"');
+    const file = resultForm.get("file") as File;
+    assertEquals(file.name, "a.png");
+    assertEquals(file.type, "image/png");
+    assertEquals(file.size, bytes.length);
+    assertEquals(new Uint8Array(await file.arrayBuffer()), bytes);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchInitFormDataParsedFileBody(): Promise<void> {
+    const response = await fetch(
+      "http://localhost:4545/cli/tests/subdir/multipart_form_data.txt"
+    );
+    const formData = await response.formData();
+    // The parsed file is sent again without being read.
+    const echoed = await fetch("http://localhost:4545/echo_server", {
+      method: "POST",
+      body: formData,
+      headers: { "content-type": "multipart/form-data; boundary=deno" },
+    });
+    assertEquals(
+      echoed.headers.get("content-type"),
+      "multipart/form-data; boundary=deno"
+    );
+    const resultForm = await echoed.formData();
+    assertEquals(resultForm.get("field_1"), "value_1 \r\n");
+    const file = resultForm.get("field_2") as File;
+    assertEquals(file.name, "file.js");
+    assertEquals(await file.text(), 'console.log("Hi")');
+  }
+);
+
 unitTest({ perms: { net: true } }, async function fetchUserAgent(): Promise<
   void
 > {
@@ -532,3 +710,25 @@ unitTest(function responseConstructionHeaderRemoval(): void {
   );
   assert(res.headers.get("Set-Cookie") != "mysessionid");
 });
+
+unitTest(
+  { perms: { net: true } },
+  async function fetchWithCustomHttpClient(): Promise<void> {
+    const client = Deno.createHttpClient({ poolMaxIdlePerHost: 1 });
+    for (let i = 0; i < 2; i++) {
+      const response = await fetch(
+        "http://localhost:4545/cli/tests/fixture.json",
+        { client }
+      );
+      const json = await response.json();
+      assertEquals(json.name, "deno");
+    }
+    client.close();
+  }
+);
+
+unitTest(function createHttpClientCaFilePerm(): void {
+  assertThrows(() => {
+    Deno.createHttpClient({ caFile: "cli/tests/tls/RootCA.crt" });
+  }, Deno.errors.PermissionDenied);
+});
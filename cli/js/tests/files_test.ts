@@ -89,6 +89,45 @@ unitTest(
   }
 );
 
+unitTest(
+  { perms: { read: true } },
+  async function filesReadableStream(): Promise<void> {
+    const file = await Deno.open("cli/tests/hello.txt");
+    let text = "";
+    for await (const chunk of file.readable) {
+      text += new TextDecoder().decode(chunk);
+    }
+    assertEquals(text, "Hello world!");
+    file.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function filesPipeReadableToWritable(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/hello.txt";
+    const src = await Deno.open("cli/tests/hello.txt");
+    const dst = await Deno.create(filename);
+    await src.readable.pipeTo(dst.writable);
+    // Closing the writable closes the file, which the readable leaves open.
+    assert(!(dst.rid in Deno.resources()));
+    assert(src.rid in Deno.resources());
+    assertEquals(Deno.readTextFileSync(filename), "Hello world!");
+    src.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true } },
+  async function filesReadableStreamCancel(): Promise<void> {
+    const file = await Deno.open("cli/tests/hello.txt");
+    const reader = file.readable.getReader();
+    await reader.read();
+    await reader.cancel();
+    assert(!(file.rid in Deno.resources()));
+  }
+);
+
 unitTest(async function readerIter(): Promise<void> {
   // ref: https://github.com/denoland/deno/issues/2330
   const encoder = new TextEncoder();
@@ -1,5 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { unitTest, assertEquals } from "./test_util.ts";
+import {
+  unitTest,
+  assert,
+  assertEquals,
+  assertStrContains,
+} from "./test_util.ts";
 
 const DEFAULT_BUF_SIZE = 32 * 1024;
 
@@ -71,3 +76,116 @@ unitTest({ perms: { write: true } }, async function copyBufferToFile() {
   write.close();
   await Deno.remove(filePath);
 });
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function copyFileToFile(): Promise<void> {
+    const dir = Deno.makeTempDirSync();
+    const size = 2.5 * 1024 * 1024;
+    Deno.writeFileSync(dir + "/src.txt", repeat("b", size));
+    const src = await Deno.open(dir + "/src.txt");
+    const dst = await Deno.open(dir + "/dst.txt", {
+      write: true,
+      create: true,
+    });
+
+    const progress: number[] = [];
+    const n = await Deno.copy(src, dst, {
+      onProgress: (copied) => progress.push(copied),
+    });
+
+    assertEquals(n, size);
+    assertEquals(progress, [1024 * 1024, 2 * 1024 * 1024, size]);
+    assertEquals(Deno.readFileSync(dir + "/dst.txt"), repeat("b", size));
+    src.close();
+    dst.close();
+  }
+);
+
+unitTest(async function copyProgressWithoutResources(): Promise<void> {
+  const reader = new Deno.Buffer(repeat("b", 2048).buffer as ArrayBuffer);
+  const write = new Deno.Buffer();
+  const progress: number[] = [];
+  await Deno.copy(reader, write, {
+    bufSize: 1024,
+    onProgress: (copied) => progress.push(copied),
+  });
+  assertEquals(progress, [1024, 2048]);
+});
+
+function streamOf(chunks: string[]): ReadableStream<Uint8Array> {
+  return new ReadableStream<Uint8Array>({
+    pull(controller): void {
+      const chunk = chunks.shift();
+      if (chunk === undefined) {
+        controller.close();
+      } else {
+        controller.enqueue(new TextEncoder().encode(chunk));
+      }
+    },
+  });
+}
+
+unitTest(async function resourceForReadableStream(): Promise<void> {
+  const rid = Deno.resourceForReadableStream(streamOf(["a", "bc", "", "d"]));
+  const data = await Deno.readAll(new Deno.File(rid));
+  assertEquals(new TextDecoder().decode(data), "abcd");
+  Deno.close(rid);
+});
+
+unitTest(async function resourceForReadableStreamErrors(): Promise<void> {
+  let pulls = 0;
+  const stream = new ReadableStream<Uint8Array>({
+    pull(controller): void {
+      if (pulls++ === 0) {
+        controller.enqueue(new Uint8Array([1]));
+      } else {
+        controller.error(new Error("stream failed"));
+      }
+    },
+  });
+  const rid = Deno.resourceForReadableStream(stream);
+  const buf = new Uint8Array(8);
+  assertEquals(await Deno.read(rid, buf), 1);
+  let err;
+  try {
+    await Deno.read(rid, buf);
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof Error);
+  assertStrContains(err.message, "stream failed");
+  Deno.close(rid);
+});
+
+unitTest(async function resourceForReadableStreamClose(): Promise<void> {
+  let cancelled = false;
+  const stream = new ReadableStream<Uint8Array>({
+    pull(controller): void {
+      controller.enqueue(new Uint8Array(16));
+    },
+    cancel(): void {
+      cancelled = true;
+    },
+  });
+  const rid = Deno.resourceForReadableStream(stream);
+  assertEquals(await Deno.read(rid, new Uint8Array(16)), 16);
+  Deno.close(rid);
+  // The pending write to the closed resource fails, cancelling the stream.
+  while (!cancelled) {
+    await new Promise((resolve): number => setTimeout(resolve, 10));
+  }
+});
+
+unitTest(async function readableStreamForRid(): Promise<void> {
+  const rid = Deno.resourceForReadableStream(streamOf(["hello", " world"]));
+  const stream = Deno.readableStreamForRid(rid, { chunkSize: 4 });
+  let text = "";
+  for await (const chunk of stream) {
+    assert(chunk.length <= 4);
+    text += new TextDecoder().decode(chunk);
+  }
+  assertEquals(text, "hello world");
+  // The resource is left open.
+  Deno.close(rid);
+});
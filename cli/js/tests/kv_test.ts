@@ -0,0 +1,185 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals } from "./test_util.ts";
+
+unitTest(async function kvGetSetDelete(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  const missing = await kv.get(["users", 1]);
+  assertEquals(missing, { key: ["users", 1], value: null, versionstamp: null });
+
+  const result = await kv.set(["users", 1], { name: "Alice" });
+  assert(result.ok);
+  const entry = await kv.get<{ name: string }>(["users", 1]);
+  assertEquals(entry.value, { name: "Alice" });
+  assertEquals(entry.versionstamp, result.versionstamp);
+
+  await kv.delete(["users", 1]);
+  assertEquals((await kv.get(["users", 1])).value, null);
+  kv.close();
+});
+
+unitTest(async function kvAtomicChecks(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  const first = await kv
+    .atomic()
+    .check({ key: ["counter"], versionstamp: null })
+    .set(["counter"], 1)
+    .commit();
+  assert(first.ok);
+
+  // The counter exists now, so the same check fails and nothing changes.
+  const failed = await kv
+    .atomic()
+    .check({ key: ["counter"], versionstamp: null })
+    .set(["counter"], 100)
+    .set(["other"], true)
+    .commit();
+  assertEquals(failed, { ok: false });
+  assertEquals((await kv.get(["other"])).value, null);
+
+  const counter = await kv.get<number>(["counter"]);
+  const second = await kv
+    .atomic()
+    .check(counter)
+    .set(["counter"], counter.value! + 1)
+    .commit();
+  assert(second.ok);
+  assert(second.versionstamp > first.versionstamp);
+  assertEquals((await kv.get(["counter"])).value, 2);
+  kv.close();
+});
+
+unitTest(async function kvListPrefix(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  const atomic = kv.atomic();
+  for (const id of [3, 1, 2, -1]) {
+    atomic.set(["users", id], id);
+  }
+  atomic.set(["users"], "not listed");
+  atomic.set(["usersx", 1], "not listed");
+  await atomic.commit();
+
+  const keys = [];
+  for await (const entry of kv.list({ prefix: ["users"] })) {
+    keys.push(entry.key);
+  }
+  assertEquals(keys, [
+    ["users", -1],
+    ["users", 1],
+    ["users", 2],
+    ["users", 3],
+  ]);
+
+  const values = [];
+  const reversed = kv.list<number>({ prefix: ["users"] }, { reverse: true });
+  for await (const entry of reversed) {
+    values.push(entry.value);
+  }
+  assertEquals(values, [3, 2, 1, -1]);
+  kv.close();
+});
+
+unitTest(async function kvListPages(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  const atomic = kv.atomic();
+  for (let i = 0; i < 5; i++) {
+    atomic.set(["items", i], i);
+  }
+  await atomic.commit();
+
+  const page = kv.list<number>({ prefix: ["items"] }, { limit: 2 });
+  const values = [];
+  for await (const entry of page) {
+    values.push(entry.value);
+  }
+  assertEquals(values, [0, 1]);
+
+  const rest = kv.list<number>(
+    { prefix: ["items"] },
+    { cursor: page.cursor, batchSize: 1 }
+  );
+  values.length = 0;
+  for await (const entry of rest) {
+    values.push(entry.value);
+  }
+  assertEquals(values, [2, 3, 4]);
+
+  const range = kv.list({ start: ["items", 1], end: ["items", 3] });
+  values.length = 0;
+  for await (const entry of range) {
+    values.push(entry.value);
+  }
+  assertEquals(values, [1, 2]);
+  kv.close();
+});
+
+unitTest(async function kvKeyParts(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  const key = ["parts", new Uint8Array([0, 1]), "a", 1.5, true];
+  await kv.set(key, "value");
+  const entries = [];
+  for await (const entry of kv.list({ prefix: ["parts"] })) {
+    entries.push(entry);
+  }
+  assertEquals(entries.length, 1);
+  assertEquals(entries[0].key, key);
+
+  let thrown = false;
+  try {
+    await kv.set(["nan", NaN], "value");
+  } catch (err) {
+    assert(err instanceof TypeError);
+    thrown = true;
+  }
+  assert(thrown);
+  kv.close();
+});
+
+unitTest(async function kvWatch(): Promise<void> {
+  const kv = await Deno.openKv(":memory:");
+  await kv.set(["watched"], 1);
+  const watcher = kv.watch<number>([["watched"], ["missing"]]);
+
+  const first = await watcher.next();
+  assertEquals(first.value[0].value, 1);
+  assertEquals(first.value[1].value, null);
+
+  const next = watcher.next();
+  // Setting another key doesn't wake the watcher.
+  await kv.set(["unwatched"], 1);
+  await kv.set(["watched"], 2);
+  const second = await next;
+  assertEquals(second.value[0].value, 2);
+
+  // Closing the database ends the watch.
+  const last = watcher.next();
+  kv.close();
+  assert((await last).done);
+});
+
+unitTest(
+  { perms: { read: true, write: false } },
+  async function kvOpenPermissions(): Promise<void> {
+    let thrown = false;
+    try {
+      await Deno.openKv("kv.sqlite3");
+    } catch (err) {
+      assert(err instanceof Deno.errors.PermissionDenied);
+      thrown = true;
+    }
+    assert(thrown);
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function kvOpenFile(): Promise<void> {
+    const path = Deno.makeTempDirSync() + "/kv.sqlite3";
+    const kv = await Deno.openKv(path);
+    await kv.set(["saved"], true);
+    kv.close();
+
+    const reopened = await Deno.openKv(path);
+    assertEquals((await reopened.get(["saved"])).value, true);
+    reopened.close();
+  }
+);
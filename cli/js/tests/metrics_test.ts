@@ -28,6 +28,46 @@ unitTest(async function metrics(): Promise<void> {
   assert(m2.bytesReceived > m1.bytesReceived);
 });
 
+unitTest(async function metricsByOp(): Promise<void> {
+  const dataMsg = new Uint8Array([13, 13, 13]); // "\r\r\r",
+  const m1 = Deno.metrics();
+  await Deno.stdout.write(dataMsg);
+  const m2 = Deno.metrics();
+
+  const write1 = m1.ops["op_write"];
+  const write2 = m2.ops["op_write"];
+  assert(write2.opsDispatchedAsync === (write1?.opsDispatchedAsync ?? 0) + 1);
+  assert(write2.opsCompletedAsync === (write1?.opsCompletedAsync ?? 0) + 1);
+  assert(
+    write2.bytesSentData === (write1?.bytesSentData ?? 0) + dataMsg.byteLength
+  );
+  // The first call of `Deno.metrics()` is counted by the second one.
+  assert(m2.ops["op_metrics"].opsDispatchedSync > 0);
+
+  let dispatched = 0;
+  for (const op of Object.values(m2.ops)) {
+    assert(op.opsDispatched > 0);
+    dispatched += op.opsDispatched;
+  }
+  assert(dispatched === m2.opsDispatched);
+});
+
+unitTest(function metricsHistograms(): void {
+  // Histograms are only populated with `--warn-slow-ops`.
+  const m = Deno.metrics();
+  for (const histogram of [
+    m.opSyncDuration,
+    m.opAsyncDuration,
+    m.eventLoopTickDuration,
+  ]) {
+    assert(histogram.count >= 0);
+    assert(histogram.maxMs >= 0);
+    assert(histogram.buckets["Infinity"] >= 0);
+    const total = Object.values(histogram.buckets).reduce((a, b) => a + b, 0);
+    assert(total === histogram.count);
+  }
+});
+
 unitTest(
   { perms: { write: true } },
   function metricsUpdatedIfNoResponseSync(): void {
@@ -413,6 +413,29 @@ unitTest(
   }
 );
 
+unitTest(
+  { perms: { net: true } },
+  async function netListenAbortSignal(): Promise<void> {
+    const controller = new AbortController();
+    const listener = Deno.listen({
+      hostname: "127.0.0.1",
+      port: 4500,
+      signal: controller.signal,
+    });
+    let accepted = 0;
+    const acceptLoop = (async (): Promise<void> => {
+      for await (const conn of listener) {
+        accepted++;
+        conn.close();
+      }
+    })();
+    controller.abort();
+    await acceptLoop;
+    assertEquals(accepted, 0);
+    listener.close();
+  }
+);
+
 unitTest(
   {
     // FIXME(bartlomieju)
@@ -525,3 +548,109 @@ unitTest(
     await resolvable;
   }
 );
+
+unitTest(async function resolveDnsPerm(): Promise<void> {
+  let err;
+  try {
+    await Deno.resolveDns("deno.land", "A");
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof Deno.errors.PermissionDenied);
+  assertEquals(err.name, "PermissionDenied");
+});
+
+unitTest(
+  { perms: { net: true } },
+  async function resolveDnsUnsupportedRecordType(): Promise<void> {
+    let err;
+    try {
+      // eslint-disable-next-line @typescript-eslint/no-explicit-any
+      await Deno.resolveDns("deno.land", "CNAME" as any);
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function netTcpReadableWritableStreams(): Promise<void> {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 3505 });
+    const data = new Uint8Array(256 * 1024);
+    for (let i = 0; i < data.length; i++) {
+      data[i] = i % 251;
+    }
+
+    const serving = (async (): Promise<void> => {
+      const conn = await listener.accept();
+      const writer = conn.writable.getWriter();
+      // Write in pieces, waiting for the stream to have room each time.
+      for (let i = 0; i < data.length; i += 10000) {
+        await writer.ready;
+        writer.write(data.subarray(i, i + 10000));
+      }
+      await writer.close();
+      conn.close();
+    })();
+
+    const conn = await Deno.connect({ hostname: "127.0.0.1", port: 3505 });
+    const received = new Uint8Array(data.length);
+    let offset = 0;
+    for await (const chunk of conn.readable.getIterator()) {
+      received.set(chunk, offset);
+      offset += chunk.length;
+    }
+    await serving;
+    assertEquals(offset, data.length);
+    assertEquals(received, data);
+    conn.close();
+    listener.close();
+  }
+);
+
+unitTest(
+  { perms: { net: true } },
+  async function netTcpWritableBackpressure(): Promise<void> {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 3506 });
+    const accepting = listener.accept();
+    const conn = await Deno.connect({ hostname: "127.0.0.1", port: 3506 });
+    const serverConn = await accepting;
+
+    // Nothing reads on the other end, so the socket buffers fill up and the
+    // writable has to stop accepting chunks.
+    const writer = conn.writable.getWriter();
+    const chunk = new Uint8Array(64 * 1024);
+    let blocked = false;
+    for (let i = 0; i < 1024 && !blocked; i++) {
+      writer.write(chunk);
+      if (writer.desiredSize! <= 0) {
+        let timer: number | undefined;
+        blocked = await Promise.race([
+          writer.ready.then((): boolean => false),
+          new Promise<boolean>((resolve): void => {
+            timer = setTimeout((): void => resolve(true), 100);
+          }),
+        ]);
+        clearTimeout(timer);
+      }
+    }
+    assert(blocked);
+
+    // Drain the socket so the queued writes can finish.
+    const draining = (async (): Promise<void> => {
+      const buf = new Uint8Array(64 * 1024);
+      let n: number | null = 0;
+      while (n !== null) {
+        n = await serverConn.read(buf);
+      }
+    })();
+    await writer.close();
+    await draining;
+
+    serverConn.close();
+    conn.close();
+    listener.close();
+  }
+);
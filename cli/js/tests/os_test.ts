@@ -336,3 +336,49 @@ unitTest({ perms: { env: false } }, function releasePerm(): void {
   }
   assert(caughtError);
 });
+
+unitTest({ perms: { env: true } }, function systemMemoryInfo(): void {
+  const info = Deno.systemMemoryInfo();
+  assert(info.total > 0);
+  assert(info.free >= 0);
+  assert(info.free <= info.total);
+});
+
+unitTest({ perms: { env: false } }, function systemMemoryInfoPerm(): void {
+  assertThrows(() => {
+    Deno.systemMemoryInfo();
+  }, Deno.errors.PermissionDenied);
+});
+
+unitTest(function memoryUsage(): void {
+  const usage = Deno.memoryUsage();
+  assert(usage.rss > 0);
+  assert(usage.heapTotal > 0);
+  assert(usage.heapUsed > 0);
+  assert(usage.heapUsed <= usage.heapTotal);
+  assert(usage.external >= 0);
+});
+
+unitTest(
+  { ignore: Deno.build.os === "windows", perms: { env: true } },
+  function networkInterfaces(): void {
+    const interfaces = Deno.networkInterfaces();
+    const loopback = interfaces.find((i) => i.internal);
+    assert(loopback);
+    for (const { family, address } of interfaces) {
+      assert(family === "IPv4" || family === "IPv6");
+      assert(address.length > 0);
+    }
+  }
+);
+
+unitTest({ perms: { env: false } }, function networkInterfacesPerm(): void {
+  assertThrows(() => {
+    Deno.networkInterfaces();
+  }, Deno.errors.PermissionDenied);
+});
+
+unitTest(function navigatorHardwareConcurrency(): void {
+  assert(navigator.hardwareConcurrency >= 1);
+  assert(Number.isInteger(navigator.hardwareConcurrency));
+});
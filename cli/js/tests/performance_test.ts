@@ -1,5 +1,11 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { unitTest, assert, createResolvable } from "./test_util.ts";
+import {
+  unitTest,
+  assert,
+  assertEquals,
+  assertThrows,
+  createResolvable,
+} from "./test_util.ts";
 
 unitTest({ perms: { hrtime: false } }, async function performanceNow(): Promise<
   void
@@ -13,3 +19,119 @@ unitTest({ perms: { hrtime: false } }, async function performanceNow(): Promise<
   }, 10);
   await resolvable;
 });
+
+unitTest(function performanceTimeOrigin(): void {
+  const now = Date.now();
+  assert(performance.timeOrigin > 0);
+  assert(performance.timeOrigin <= now);
+  assert(Math.abs(performance.timeOrigin + performance.now() - now) < 100);
+  assertEquals(performance.toJSON(), { timeOrigin: performance.timeOrigin });
+});
+
+unitTest(function performanceMark(): void {
+  const mark = performance.mark("test", { detail: { a: 1 } });
+  assert(mark instanceof PerformanceMark);
+  assert(mark instanceof PerformanceEntry);
+  assertEquals(mark.name, "test");
+  assertEquals(mark.entryType, "mark");
+  assertEquals(mark.duration, 0);
+  assertEquals(mark.detail, { a: 1 });
+  assert(mark.startTime > 0);
+  assertEquals(mark.toJSON(), {
+    name: "test",
+    entryType: "mark",
+    startTime: mark.startTime,
+    duration: 0,
+  });
+  const entries = performance.getEntriesByName("test", "mark");
+  assertEquals(entries.length, 1);
+  assert(entries[0] === mark);
+  performance.clearMarks("test");
+  assertEquals(performance.getEntriesByName("test").length, 0);
+});
+
+unitTest(function performanceMarkConstructor(): void {
+  const mark = new PerformanceMark("test", { startTime: 5 });
+  assertEquals(mark.startTime, 5);
+  assertEquals(mark.detail, null);
+  // Marks constructed directly aren't added to the timeline.
+  assertEquals(performance.getEntriesByName("test").length, 0);
+  assertThrows(
+    (): void => {
+      new PerformanceMark("test", { startTime: -1 });
+    },
+    TypeError,
+    "startTime cannot be negative."
+  );
+});
+
+unitTest(function performanceMeasure(): void {
+  performance.mark("measureStart", { startTime: 10 });
+  performance.mark("measureEnd", { startTime: 30 });
+  const measure = performance.measure("test", "measureStart", "measureEnd");
+  assert(measure instanceof PerformanceMeasure);
+  assertEquals(measure.entryType, "measure");
+  assertEquals(measure.startTime, 10);
+  assertEquals(measure.duration, 20);
+
+  const fromOptions = performance.measure("test", {
+    start: "measureStart",
+    duration: 5,
+    detail: "detail",
+  });
+  assertEquals(fromOptions.startTime, 10);
+  assertEquals(fromOptions.duration, 5);
+  assertEquals(fromOptions.detail, "detail");
+
+  const untilNow = performance.measure("test");
+  assertEquals(untilNow.startTime, 0);
+  assert(untilNow.duration > 0);
+
+  const measures = performance.getEntriesByType("measure");
+  assertEquals(measures.length, 3);
+  assert(measures[0] === untilNow);
+  assert(measures[1] === measure);
+  assert(measures[2] === fromOptions);
+  performance.clearMarks();
+  performance.clearMeasures();
+  assertEquals(performance.getEntries().length, 0);
+});
+
+unitTest(function performanceMeasureIllegalArguments(): void {
+  assertThrows(
+    (): void => {
+      performance.measure("test", "missing");
+    },
+    DOMException,
+    'The mark "missing" does not exist.'
+  );
+  assertThrows((): void => {
+    performance.measure("test", { detail: 1 });
+  }, TypeError);
+  assertThrows((): void => {
+    performance.measure("test", { start: 0, end: 1, duration: 1 });
+  }, TypeError);
+  assertThrows((): void => {
+    performance.measure("test", { start: 0 }, "end");
+  }, TypeError);
+  assertEquals(performance.getEntries().length, 0);
+});
+
+unitTest(function performanceEventCounts(): void {
+  assertEquals(performance.eventCounts.get("click"), 0);
+  assert(performance.eventCounts.has("keydown"));
+  assert(!performance.eventCounts.has("message"));
+  for (const count of performance.eventCounts.values()) {
+    assertEquals(count, 0);
+  }
+});
+
+unitTest(function performanceEntryIllegalConstructor(): void {
+  assertThrows(
+    (): void => {
+      new PerformanceEntry();
+    },
+    TypeError,
+    "Illegal constructor."
+  );
+});
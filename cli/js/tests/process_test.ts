@@ -224,6 +224,71 @@ unitTest({ perms: { run: true } }, async function runStderrOutput(): Promise<
   p.close();
 });
 
+unitTest({ perms: { run: true } }, async function runOutputAll(): Promise<
+  void
+> {
+  // Both outputs are bigger than a pipe buffer, so reading them one after the
+  // other would deadlock.
+  const p = run({
+    cmd: [
+      "python",
+      "-c",
+      "import sys; sys.stdout.write('o' * 1000000); " +
+        "sys.stderr.write('e' * 1000000); sys.exit(3)",
+    ],
+    stdout: "piped",
+    stderr: "piped",
+  });
+  const { status, stdout, stderr } = await p.outputAll();
+  assertEquals(status.success, false);
+  assertEquals(status.code, 3);
+  assertEquals(stdout.length, 1000000);
+  assertEquals(stderr.length, 1000000);
+  assertEquals(stdout[0], "o".charCodeAt(0));
+  assertEquals(stderr[999999], "e".charCodeAt(0));
+  p.close();
+});
+
+unitTest(
+  { perms: { run: true } },
+  async function runOutputAllNotPiped(): Promise<void> {
+    const p = run({
+      cmd: ["python", "-c", "import sys; sys.stdout.write('hello')"],
+      stdout: "piped",
+    });
+    const { status, stdout, stderr } = await p.outputAll();
+    assert(status.success);
+    assertEquals(new TextDecoder().decode(stdout), "hello");
+    assertEquals(stderr.length, 0);
+    p.close();
+  }
+);
+
+unitTest({ perms: { run: true } }, async function runStdioStreams(): Promise<
+  void
+> {
+  const p = run({
+    cmd: ["python", "-c", "import sys; sys.stdout.write(sys.stdin.read())"],
+    stdin: "piped",
+    stdout: "piped",
+  });
+  const writer = p.stdin!.writable.getWriter();
+  await writer.write(new TextEncoder().encode("hello"));
+  await writer.close();
+
+  const chunks: Uint8Array[] = [];
+  const reader = p.stdout!.readable.getReader();
+  while (true) {
+    const { value, done } = await reader.read();
+    if (done) break;
+    chunks.push(value);
+  }
+  const s = chunks.map((c) => new TextDecoder().decode(c)).join("");
+  assertEquals(s, "hello");
+  assert((await p.status()).success);
+  p.close();
+});
+
 unitTest(
   { perms: { run: true, write: true, read: true } },
   async function runRedirectStdoutStderr(): Promise<void> {
@@ -381,4 +446,47 @@ if (Deno.build.os !== "windows") {
 
     p.close();
   });
+
+  unitTest({ perms: { run: true } }, async function killProcessGroup(): Promise<
+    void
+  > {
+    const p = run({
+      cmd: ["python", "-c", "from time import sleep; sleep(10000)"],
+      pgid: 0,
+    });
+
+    kill(-p.pid, Deno.Signal.SIGTERM);
+    const status = await p.status();
+
+    assertEquals(status.success, false);
+    assertEquals(status.signal, Deno.Signal.SIGTERM);
+    p.close();
+  });
+
+  unitTest({ perms: { run: true } }, async function runDetached(): Promise<
+    void
+  > {
+    const p = run({
+      cmd: [
+        "python",
+        "-c",
+        "import os; print(os.getsid(0) == os.getpid() == os.getpgid(0))",
+      ],
+      stdout: "piped",
+      detached: true,
+    });
+    const output = new TextDecoder().decode(await p.output());
+    assertEquals(output.trim(), "True");
+    p.close();
+  });
+
+  unitTest({ perms: { run: true } }, function runDetachedPgid(): void {
+    let err;
+    try {
+      run({ cmd: ["python", "-c", "pass"], detached: true, pgid: 0 });
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+  });
 }
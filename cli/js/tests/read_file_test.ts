@@ -63,3 +63,25 @@ unitTest({ perms: { read: true } }, function readFileSyncLoop(): void {
     Deno.readFileSync("cli/tests/fixture.json");
   }
 });
+
+unitTest({ perms: { read: true } }, async function readFileSignal(): Promise<
+  void
+> {
+  const controller = new AbortController();
+  const data = await Deno.readFile("cli/tests/fixture.json", {
+    signal: controller.signal,
+  });
+  assert(data.byteLength > 0);
+
+  controller.abort();
+  let err;
+  try {
+    await Deno.readFile("cli/tests/fixture.json", {
+      signal: controller.signal,
+    });
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof DOMException);
+  assertEquals(err.name, "AbortError");
+});
@@ -0,0 +1,62 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals } from "./test_util.ts";
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function readLinesSuccess(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    Deno.writeTextFileSync(filename, "one\ntwo\r\n\nfour");
+    const file = await Deno.open(filename);
+    const lines = [];
+    for await (const line of Deno.readLines(file)) {
+      lines.push(line);
+    }
+    assertEquals(lines, ["one", "two", "", "four"]);
+    // The line reader is closed, the file isn't.
+    assert(!Object.values(Deno.resources()).includes("lineReader"));
+    file.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function readLinesAcrossChunks(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    const line = "x".repeat(100_000);
+    Deno.writeTextFileSync(filename, `${line}\n${line}\n`);
+    const file = await Deno.open(filename);
+    const lines = [];
+    for await (const line of Deno.readLines(file)) {
+      lines.push(line);
+    }
+    assertEquals(lines, [line, line]);
+    file.close();
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function readLinesBreak(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    Deno.writeTextFileSync(filename, "one\ntwo\nthree\n");
+    const file = await Deno.open(filename);
+    for await (const line of Deno.readLines(file)) {
+      assertEquals(line, "one");
+      break;
+    }
+    assert(!Object.values(Deno.resources()).includes("lineReader"));
+    file.close();
+  }
+);
+
+unitTest(async function readLinesBadResource(): Promise<void> {
+  let err;
+  try {
+    for await (const _ of Deno.readLines({ rid: 9999 })) {
+      // unreachable
+    }
+  } catch (e) {
+    err = e;
+  }
+  assert(err instanceof Deno.errors.BadResource);
+});
@@ -1,5 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { unitTest, assert, assertEquals } from "./test_util.ts";
+import {
+  unitTest,
+  assert,
+  assertEquals,
+  assertThrows,
+} from "./test_util.ts";
 
 unitTest(function btoaSuccess(): void {
   const text = "hello world";
@@ -83,8 +88,9 @@ unitTest(function textDecoderIgnoreBOM(): void {
     0xf0, 0x9d, 0x94, 0x81,
     0xf0, 0x9d, 0x93, 0xbd
   ]);
+  assertEquals(new TextDecoder().decode(fixture), "𝓽𝓮𝔁𝓽");
   const decoder = new TextDecoder("utf-8", { ignoreBOM: true });
-  assertEquals(decoder.decode(fixture), "𝓽𝓮𝔁𝓽");
+  assertEquals(decoder.decode(fixture), "\ufeff𝓽𝓮𝔁𝓽");
 });
 
 unitTest(function textDecoderNotBOM(): void {
@@ -117,6 +123,56 @@ unitTest(function textDecoderErrorEncoding(): void {
   assert(didThrow);
 });
 
+unitTest(function textDecoderEncodingName(): void {
+  assertEquals(new TextDecoder().encoding, "utf-8");
+  assertEquals(new TextDecoder(" Latin1 ").encoding, "windows-1252");
+  assertEquals(new TextDecoder("sjis").encoding, "shift_jis");
+  assertEquals(new TextDecoder("utf-16").encoding, "utf-16le");
+  assertThrows(() => new TextDecoder("iso-2022-kr"), RangeError);
+});
+
+unitTest(function textDecoderLegacyEncodings(): void {
+  const shiftJis = new TextDecoder("shift_jis");
+  const hiragana = new Uint8Array([0x82, 0xa0, 0x82, 0xa2]);
+  assertEquals(shiftJis.decode(hiragana), "あい");
+  const utf16be = new TextDecoder("utf-16be");
+  assertEquals(utf16be.decode(new Uint8Array([0xfe, 0xff, 0x00, 0x61])), "a");
+  const latin2 = new TextDecoder("iso-8859-2");
+  const city = new Uint8Array([0xa3, 0xf3, 0x64, 0xbc]);
+  assertEquals(latin2.decode(city), "Łódź");
+});
+
+unitTest(function textDecoderFatal(): void {
+  const fixture = new Uint8Array([0x61, 0xff, 0x62]);
+  assertEquals(new TextDecoder().decode(fixture), "a\ufffdb");
+  assertThrows(
+    () => new TextDecoder("utf-8", { fatal: true }).decode(fixture),
+    TypeError,
+    "Decoder error."
+  );
+});
+
+unitTest(function textDecoderStream(): void {
+  // "€" is split across chunks.
+  const decoder = new TextDecoder();
+  const stream = true;
+  assertEquals(decoder.decode(new Uint8Array([0x61, 0xe2]), { stream }), "a");
+  assertEquals(decoder.decode(new Uint8Array([0x82]), { stream }), "");
+  assertEquals(decoder.decode(new Uint8Array([0xac, 0x62])), "€b");
+
+  const utf16le = new TextDecoder("utf-16le");
+  assertEquals(utf16le.decode(new Uint8Array([0xac]), { stream }), "");
+  assertEquals(utf16le.decode(new Uint8Array([0x20])), "€");
+});
+
+unitTest(function textDecoderStreamTruncated(): void {
+  const decoder = new TextDecoder("utf-8", { fatal: true });
+  decoder.decode(new Uint8Array([0xe2, 0x82]), { stream: true });
+  assertThrows(() => decoder.decode(), TypeError, "Decoder error.");
+  // A failed flush still ends the stream.
+  assertEquals(decoder.decode(new Uint8Array([0x61])), "a");
+});
+
 unitTest(function textEncoder(): void {
   const fixture = "𝓽𝓮𝔁𝓽";
   const encoder = new TextEncoder();
@@ -1,5 +1,5 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-import { unitTest, assertEquals } from "./test_util.ts";
+import { unitTest, assert, assertEquals } from "./test_util.ts";
 
 unitTest(
   {
@@ -13,3 +13,18 @@ unitTest(
     assertEquals(finalMask, prevMask);
   }
 );
+
+unitTest(
+  {
+    ignore: Deno.build.os !== "windows",
+  },
+  function umaskNotSupportedOnWindows(): void {
+    let err;
+    try {
+      Deno.umask();
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.NotSupported);
+  }
+);
@@ -7,10 +7,13 @@
 import "./abort_controller_test.ts";
 import "./blob_test.ts";
 import "./body_test.ts";
+import "./broadcast_channel_test.ts";
 import "./buffer_test.ts";
 import "./build_test.ts";
+import "./cache_storage_test.ts";
 import "./chmod_test.ts";
 import "./chown_test.ts";
+import "./compression_test.ts";
 import "./console_test.ts";
 import "./copy_file_test.ts";
 import "./custom_event_test.ts";
@@ -32,6 +35,7 @@ import "./globals_test.ts";
 import "./headers_test.ts";
 import "./internals_test.ts";
 import "./io_test.ts";
+import "./kv_test.ts";
 import "./link_test.ts";
 import "./make_temp_test.ts";
 import "./metrics_test.ts";
@@ -45,6 +49,7 @@ import "./real_path_test.ts";
 import "./read_dir_test.ts";
 import "./read_text_file_test.ts";
 import "./read_file_test.ts";
+import "./read_lines_test.ts";
 import "./read_link_test.ts";
 import "./remove_test.ts";
 import "./rename_test.ts";
@@ -66,6 +71,7 @@ import "./umask_test.ts";
 import "./url_test.ts";
 import "./url_search_params_test.ts";
 import "./utime_test.ts";
+import "./web_storage_test.ts";
 import "./write_file_test.ts";
 import "./write_text_file_test.ts";
 import "./performance_test.ts";
@@ -0,0 +1,56 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { unitTest, assert, assertEquals, assertThrows } from "./test_util.ts";
+
+unitTest(function webStorageItems(): void {
+  sessionStorage.clear();
+  assertEquals(sessionStorage.length, 0);
+  sessionStorage.setItem("a", "1");
+  sessionStorage.setItem("b", "2");
+  assertEquals(sessionStorage.length, 2);
+  assertEquals(sessionStorage.getItem("a"), "1");
+  assertEquals(sessionStorage.getItem("missing"), null);
+  assertEquals(sessionStorage.key(1), "b");
+  assertEquals(sessionStorage.key(2), null);
+  sessionStorage.removeItem("a");
+  assertEquals(sessionStorage.key(0), "b");
+  sessionStorage.clear();
+  assertEquals(sessionStorage.length, 0);
+});
+
+unitTest(function webStorageProperties(): void {
+  sessionStorage.clear();
+  sessionStorage.name = "deno";
+  sessionStorage.count = 3;
+  assertEquals(sessionStorage.getItem("name"), "deno");
+  // Values are converted to strings.
+  assertEquals(sessionStorage.count, "3");
+  assert("name" in sessionStorage);
+  assertEquals(Object.keys(sessionStorage), ["name", "count"]);
+  delete sessionStorage.name;
+  assertEquals(sessionStorage.name, undefined);
+  // Methods aren't shadowed by items.
+  sessionStorage.setItem("getItem", "value");
+  assertEquals(typeof sessionStorage.getItem, "function");
+  sessionStorage.clear();
+});
+
+unitTest(function webStorageQuota(): void {
+  sessionStorage.clear();
+  const value = "x".repeat(6 * 1024 * 1024);
+  sessionStorage.setItem("a", value);
+  const error = assertThrows(() => {
+    sessionStorage.setItem("b", value);
+  });
+  assert(error instanceof DOMException);
+  assertEquals(error.name, "QuotaExceededError");
+  assertEquals(sessionStorage.getItem("b"), null);
+  sessionStorage.clear();
+});
+
+unitTest(function webStorageIllegalConstructor(): void {
+  assertThrows(() => {
+    // @ts-ignore
+    new Storage();
+  }, TypeError);
+  assertEquals(String(localStorage), "[object Storage]");
+});
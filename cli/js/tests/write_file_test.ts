@@ -226,3 +226,61 @@ unitTest(
     assertEquals("Hello", actual);
   }
 );
+
+unitTest(
+  { perms: { read: true, write: true } },
+  function writeFileSyncAtomic(): void {
+    const enc = new TextEncoder();
+    const dir = Deno.makeTempDirSync();
+    const filename = dir + "/test.txt";
+    Deno.writeFileSync(filename, enc.encode("Hello"), { atomic: true });
+    if (Deno.build.os !== "windows") {
+      Deno.chmodSync(filename, 0o640);
+    }
+    Deno.writeFileSync(filename, enc.encode("World"), { atomic: true });
+    const dataRead = Deno.readFileSync(filename);
+    assertEquals("World", new TextDecoder().decode(dataRead));
+    if (Deno.build.os !== "windows") {
+      // The replaced file's permissions are kept.
+      assertEquals(Deno.statSync(filename).mode! & 0o777, 0o640);
+    }
+    // No temporary file is left behind.
+    const names = [...Deno.readDirSync(dir)].map((entry) => entry.name);
+    assertEquals(names, ["test.txt"]);
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  function writeFileSyncAtomicOptions(): void {
+    const data = new TextEncoder().encode("Hello");
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    let err;
+    try {
+      Deno.writeFileSync(filename, data, { atomic: true, create: false });
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof Deno.errors.NotFound);
+
+    err = undefined;
+    try {
+      Deno.writeFileSync(filename, data, { atomic: true, append: true });
+    } catch (e) {
+      err = e;
+    }
+    assert(err instanceof TypeError);
+  }
+);
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function writeFileAtomicSync(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    const data = new TextEncoder().encode("Hello");
+    await Deno.writeFile(filename, data, { atomic: true, sync: true });
+    await Deno.writeFile(filename, data, { append: true, sync: true });
+    const dataRead = Deno.readFileSync(filename);
+    assertEquals("HelloHello", new TextDecoder().decode(dataRead));
+  }
+);
@@ -77,3 +77,13 @@ unitTest(
     assert(caughtError);
   }
 );
+
+unitTest(
+  { perms: { read: true, write: true } },
+  async function writeTextFileOptions(): Promise<void> {
+    const filename = Deno.makeTempDirSync() + "/test.txt";
+    await Deno.writeTextFile(filename, "Hello", { atomic: true });
+    Deno.writeTextFileSync(filename, "World", { append: true });
+    assertEquals("HelloWorld", Deno.readTextFileSync(filename));
+  }
+);
@@ -1,6 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import * as tlsOps from "./ops/tls.ts";
 import { Listener, Conn, ConnImpl, ListenerImpl } from "./net.ts";
+import { abortable } from "./web/abort_signal.ts";
 
 // TODO(ry) There are many configuration options to add...
 // https://docs.rs/rustls/0.16.0/rustls/struct.ClientConfig.html
@@ -27,9 +28,11 @@ export async function connectTls({
 }
 
 class TLSListenerImpl extends ListenerImpl {
-  async accept(): Promise<Conn> {
-    const res = await tlsOps.acceptTLS(this.rid);
-    return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+  accept(): Promise<Conn> {
+    return abortable(this.signal, async (cancelRid) => {
+      const res = await tlsOps.acceptTLS(this.rid, cancelRid);
+      return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+    });
   }
 }
 
@@ -39,6 +42,7 @@ export interface ListenTlsOptions {
   transport?: "tcp";
   certFile: string;
   keyFile: string;
+  signal?: AbortSignal;
 }
 
 export function listenTls({
@@ -47,6 +51,7 @@ export function listenTls({
   keyFile,
   hostname = "0.0.0.0",
   transport = "tcp",
+  signal,
 }: ListenTlsOptions): Listener {
   const res = tlsOps.listenTls({
     port,
@@ -55,7 +60,7 @@ export function listenTls({
     hostname,
     transport,
   });
-  return new TLSListenerImpl(res.rid, res.localAddr);
+  return new TLSListenerImpl(res.rid, res.localAddr, signal);
 }
 
 interface StartTlsOptions {
@@ -1,6 +1,8 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { EventImpl } from "./event.ts";
 import { EventTargetImpl } from "./event_target.ts";
+import { abortCancelHandle, createCancelHandle } from "../ops/cancel.ts";
+import { close } from "../ops/resources.ts";
 
 export const add = Symbol("add");
 export const signalAbort = Symbol("signalAbort");
@@ -56,3 +58,44 @@ Object.defineProperty(AbortSignalImpl, "name", {
   value: "AbortSignal",
   configurable: true,
 });
+
+/** A cancel handle in Rust that is aborted together with `signal`. Async ops
+ * started with its rid reject with an "AbortError" once it is, and their
+ * futures are dropped. */
+export class CancelHandle {
+  readonly rid: number;
+  #signal: AbortSignalImpl;
+  #onAbort = (): void => abortCancelHandle(this.rid);
+
+  constructor(signal: AbortSignal) {
+    this.#signal = signal as AbortSignalImpl;
+    this.rid = createCancelHandle();
+    if (signal.aborted) {
+      this.#onAbort();
+    } else {
+      this.#signal[add](this.#onAbort);
+    }
+  }
+
+  close(): void {
+    this.#signal[remove](this.#onAbort);
+    close(this.rid);
+  }
+}
+
+/** Runs `fn`, which starts its async ops with `cancelRid`, so that aborting
+ * `signal` cancels them. Without a signal `cancelRid` is undefined. */
+export async function abortable<T>(
+  signal: AbortSignal | null | undefined,
+  fn: (cancelRid?: number) => Promise<T>
+): Promise<T> {
+  if (signal == null) {
+    return fn();
+  }
+  const cancelHandle = new CancelHandle(signal);
+  try {
+    return await fn(cancelHandle.rid);
+  } finally {
+    cancelHandle.close();
+  }
+}
@@ -1,9 +1,16 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as blobOps from "../ops/blob.ts";
 import { TextDecoder, TextEncoder } from "./text_encoding.ts";
 import { build } from "../build.ts";
 import { ReadableStreamImpl } from "./streams/readable_stream.ts";
 
+/** All the bytes of a blob, read on access. */
 export const bytesSymbol = Symbol("bytes");
+/** The id of the contents of a blob, kept outside of the V8 heap. */
+export const blobIdSymbol = Symbol("blobId");
+
+/** Size of the chunks `Blob.stream()` reads. */
+const CHUNK_SIZE = 64 * 1024;
 
 export function containsOnlyASCII(str: string): boolean {
   if (typeof str !== "string") {
@@ -61,11 +68,11 @@ function collectSequenceNotCRLF(
   return { collected: s.slice(start, position), newPosition: position };
 }
 
-function toUint8Arrays(
+function toBlobParts(
   blobParts: BlobPart[],
   doNormalizeLineEndingsToNative: boolean
-): Uint8Array[] {
-  const ret: Uint8Array[] = [];
+): Array<Uint8Array | DenoBlob> {
+  const ret: Array<Uint8Array | DenoBlob> = [];
   const enc = new TextEncoder();
   for (const element of blobParts) {
     if (typeof element === "string") {
@@ -76,7 +83,7 @@ function toUint8Arrays(
       ret.push(enc.encode(str));
       // eslint-disable-next-line @typescript-eslint/no-use-before-define
     } else if (element instanceof DenoBlob) {
-      ret.push(element[bytesSymbol]);
+      ret.push(element);
     } else if (element instanceof Uint8Array) {
       ret.push(element);
     } else if (element instanceof Uint16Array) {
@@ -100,119 +107,159 @@ function toUint8Arrays(
   return ret;
 }
 
+/** Creates the contents of a blob. Bytes are copied out of the V8 heap in a
+ * single op; blobs among the parts are shared, not copied. */
 function processBlobParts(
   blobParts: BlobPart[],
-  options: BlobPropertyBag
-): Uint8Array {
+  options: BlobPropertyBag & { type: string }
+): { id: number; size: number } {
   const normalizeLineEndingsToNative = options.ending === "native";
+  const parts = toBlobParts(blobParts, normalizeLineEndingsToNative);
+  const opParts: blobOps.BlobPart[] = [];
+  const chunks: Uint8Array[] = [];
+  let byteLength = 0;
+  let size = 0;
+  for (const part of parts) {
+    if (part instanceof DenoBlob) {
+      opParts.push({ blob: part[blobIdSymbol] });
+      size += part.size;
+    } else {
+      opParts.push({ len: part.byteLength });
+      chunks.push(part);
+      byteLength += part.byteLength;
+    }
+  }
   // ArrayBuffer.transfer is not yet implemented in V8, so we just have to
   // pre compute size of the array buffer and do some sort of static allocation
   // instead of dynamic allocation.
-  const uint8Arrays = toUint8Arrays(blobParts, normalizeLineEndingsToNative);
-  const byteLength = uint8Arrays
-    .map((u8): number => u8.byteLength)
-    .reduce((a, b): number => a + b, 0);
-  const ab = new ArrayBuffer(byteLength);
-  const bytes = new Uint8Array(ab);
+  const bytes = new Uint8Array(byteLength);
   let courser = 0;
-  for (const u8 of uint8Arrays) {
+  for (const u8 of chunks) {
     bytes.set(u8, courser);
     courser += u8.byteLength;
   }
+  const id = blobOps.create(options.type, opParts, bytes);
+  return { id, size: size + byteLength };
+}
 
-  return bytes;
+function normalizeType(type: string): string {
+  if (!containsOnlyASCII(type)) {
+    return "";
+  }
+  for (let i = 0; i < type.length; ++i) {
+    const char = type[i];
+    if (char < "\u0020" || char > "\u007E") {
+      return "";
+    }
+  }
+  return type.toLowerCase();
 }
 
-function getStream(blobBytes: Uint8Array): ReadableStream<ArrayBufferView> {
-  // TODO: Align to spec https://fetch.spec.whatwg.org/#concept-construct-readablestream
-  return new ReadableStreamImpl({
-    type: "bytes",
-    start: (controller: ReadableByteStreamController): void => {
-      controller.enqueue(blobBytes);
-      controller.close();
-    },
-  });
+// Not in the TypeScript lib yet.
+declare class FinalizationRegistry {
+  constructor(cleanup: (heldValue: number) => void);
+  register(target: object, heldValue: number): void;
 }
 
-async function readBytes(
-  reader: ReadableStreamReader<ArrayBufferView>
-): Promise<ArrayBuffer> {
-  const chunks: Uint8Array[] = [];
-  while (true) {
-    const { done, value } = await reader.read();
-    if (!done && value instanceof Uint8Array) {
-      chunks.push(value);
-    } else if (done) {
-      const size = chunks.reduce((p, i) => p + i.byteLength, 0);
-      const bytes = new Uint8Array(size);
-      let offs = 0;
-      for (const chunk of chunks) {
-        bytes.set(chunk, offs);
-        offs += chunk.byteLength;
-      }
-      return bytes;
-    } else {
-      throw new TypeError("Invalid reader result.");
-    }
+let registry: FinalizationRegistry | null | undefined;
+
+/** Removes the contents of `blob` once it is garbage collected. Where the
+ * engine has no `FinalizationRegistry`, they are kept for as long as the
+ * isolate lives. */
+export function registerBlobId(blob: object, id: number): void {
+  if (registry === undefined) {
+    registry =
+      typeof FinalizationRegistry === "function"
+        ? new FinalizationRegistry(blobOps.remove)
+        : null;
   }
+  registry?.register(blob, id);
 }
 
-// A WeakMap holding blob to byte array mapping.
-// Ensures it does not impact garbage collection.
-export const blobBytesWeakMap = new WeakMap<Blob, Uint8Array>();
-
 export class DenoBlob implements Blob {
-  [bytesSymbol]: Uint8Array;
+  [blobIdSymbol]: number;
   readonly size: number = 0;
   readonly type: string = "";
 
   constructor(blobParts?: BlobPart[], options?: BlobPropertyBag) {
-    if (arguments.length === 0) {
-      this[bytesSymbol] = new Uint8Array();
-      return;
-    }
-
     const { ending = "transparent", type = "" } = options ?? {};
-    // Normalize options.type.
-    let normalizedType = type;
-    if (!containsOnlyASCII(type)) {
-      normalizedType = "";
-    } else {
-      if (type.length) {
-        for (let i = 0; i < type.length; ++i) {
-          const char = type[i];
-          if (char < "\u0020" || char > "\u007E") {
-            normalizedType = "";
-            break;
-          }
-        }
-        normalizedType = type.toLowerCase();
-      }
-    }
-    const bytes = processBlobParts(blobParts!, { ending, type });
+    const { id, size } = processBlobParts(blobParts ?? [], { ending, type });
     // Set Blob object's properties.
-    this[bytesSymbol] = bytes;
-    this.size = bytes.byteLength;
-    this.type = normalizedType;
+    this[blobIdSymbol] = id;
+    registerBlobId(this, id);
+    this.size = size;
+    this.type = normalizeType(type);
+  }
+
+  get [bytesSymbol](): Uint8Array {
+    const bytes = new Uint8Array(this.size);
+    if (bytes.byteLength > 0) {
+      blobOps.read(this[blobIdSymbol], 0, bytes);
+    }
+    return bytes;
   }
 
   slice(start?: number, end?: number, contentType?: string): DenoBlob {
-    return new DenoBlob([this[bytesSymbol].slice(start, end)], {
-      type: contentType || this.type,
-    });
+    const size = this.size;
+    const relativeStart = clampIndex(start ?? 0, size);
+    const relativeEnd = clampIndex(end ?? size, size);
+    const span = Math.max(relativeEnd - relativeStart, 0);
+    const type = normalizeType(contentType || this.type);
+    const id = blobOps.slice(
+      this[blobIdSymbol],
+      relativeStart,
+      relativeStart + span,
+      type
+    );
+    return blobFromId(id, span, type);
   }
 
-  stream(): ReadableStream<ArrayBufferView> {
-    return getStream(this[bytesSymbol]);
+  stream(): ReadableStream<Uint8Array> {
+    // The stream keeps the blob, and so its contents, alive.
+    // eslint-disable-next-line @typescript-eslint/no-this-alias
+    const blob = this;
+    let offset = 0;
+    return new ReadableStreamImpl({
+      pull(controller: ReadableStreamDefaultController<Uint8Array>): void {
+        if (offset >= blob.size) {
+          controller.close();
+          return;
+        }
+        const chunk = new Uint8Array(Math.min(CHUNK_SIZE, blob.size - offset));
+        offset += blobOps.read(blob[blobIdSymbol], offset, chunk);
+        controller.enqueue(chunk);
+      },
+    });
   }
 
+  // eslint-disable-next-line require-await
   async text(): Promise<string> {
-    const reader = getStream(this[bytesSymbol]).getReader();
     const decoder = new TextDecoder();
-    return decoder.decode(await readBytes(reader));
+    return decoder.decode(this[bytesSymbol]);
   }
 
-  arrayBuffer(): Promise<ArrayBuffer> {
-    return readBytes(getStream(this[bytesSymbol]).getReader());
+  // eslint-disable-next-line require-await
+  async arrayBuffer(): Promise<ArrayBuffer> {
+    return this[bytesSymbol].buffer as ArrayBuffer;
   }
 }
+
+function clampIndex(index: number, size: number): number {
+  return index < 0 ? Math.max(size + index, 0) : Math.min(index, size);
+}
+
+/** A blob with the contents `id`, which it takes ownership of. */
+export function blobFromId(id: number, size: number, type: string): DenoBlob {
+  const blob = Object.create(DenoBlob.prototype);
+  blob[blobIdSymbol] = id;
+  registerBlobId(blob, id);
+  blob.size = size;
+  blob.type = type;
+  return blob;
+}
+
+/** The blob of an object URL, or null if there is none. */
+export function blobFromObjectUrl(url: string): DenoBlob | null {
+  const blob = blobOps.fromObjectUrl(url);
+  return blob && blobFromId(blob.id, blob.size, blob.type);
+}
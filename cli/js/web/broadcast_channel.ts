@@ -0,0 +1,90 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import {
+  broadcastSubscribe,
+  broadcastPost,
+  broadcastRecv,
+} from "../ops/worker_host.ts";
+import { close } from "../ops/resources.ts";
+import { TextEncoder } from "./text_encoding.ts";
+import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
+import { EventTargetImpl as EventTarget } from "./event_target.ts";
+import { MessageEvent } from "./workers.ts";
+
+const encoder = new TextEncoder();
+
+export class BroadcastChannelImpl extends EventTarget {
+  readonly #name: string;
+  readonly #rid: number;
+  #closed = false;
+
+  onmessage: ((ev: MessageEvent) => any) | null = null;
+  onmessageerror: ((ev: MessageEvent) => any) | null = null;
+
+  constructor(name: string) {
+    super();
+    if (arguments.length === 0) {
+      throw new TypeError(
+        "Failed to construct 'BroadcastChannel': 1 argument required, but only 0 present."
+      );
+    }
+    this.#name = String(name);
+    this.#rid = broadcastSubscribe(this.#name).rid;
+    this.#poll();
+  }
+
+  get name(): string {
+    return this.#name;
+  }
+
+  #dispatch = (type: "message" | "messageerror", data: any): void => {
+    const event = new MessageEvent(type, { cancelable: false, data });
+    const handler = type === "message" ? this.onmessage : this.onmessageerror;
+    if (typeof handler === "function") {
+      handler.call(this, event);
+    }
+    this.dispatchEvent(event);
+  };
+
+  #poll = async (): Promise<void> => {
+    while (true) {
+      const dataJson = await broadcastRecv(this.#rid);
+      // `null` means that subscription was closed
+      if (dataJson === null || this.#closed) {
+        return;
+      }
+
+      let data;
+      try {
+        data = JSON.parse(dataJson);
+      } catch {
+        this.#dispatch("messageerror", undefined);
+        continue;
+      }
+      this.#dispatch("message", data);
+    }
+  };
+
+  postMessage(message: any): void {
+    if (this.#closed) {
+      throw new DOMException("BroadcastChannel is closed.", "InvalidStateError");
+    }
+    const dataJson = JSON.stringify(message);
+    if (dataJson === undefined) {
+      throw new DOMException("Message can't be cloned.", "DataCloneError");
+    }
+    broadcastPost(this.#rid, encoder.encode(dataJson));
+  }
+
+  close(): void {
+    if (this.#closed) {
+      return;
+    }
+    this.#closed = true;
+    close(this.#rid);
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "BroadcastChannel";
+  }
+}
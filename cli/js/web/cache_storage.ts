@@ -0,0 +1,277 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as cacheOps from "../ops/cache_storage.ts";
+import { close } from "../ops/resources.ts";
+import * as domTypes from "./dom_types.d.ts";
+import { Request } from "./request.ts";
+import { Response, fetch } from "./fetch.ts";
+import { URLImpl } from "./url.ts";
+
+const illegalConstructorKey = Symbol("illegalConstructorKey");
+
+export interface CacheQueryOptions {
+  ignoreSearch?: boolean;
+  ignoreMethod?: boolean;
+  ignoreVary?: boolean;
+}
+
+export interface MultiCacheQueryOptions extends CacheQueryOptions {
+  cacheName?: string;
+}
+
+function toRequest(input: domTypes.RequestInfo): domTypes.Request {
+  return input instanceof Request ? input : new Request(String(input));
+}
+
+/** The request as kept in caches, with an absolute URL. */
+function toCacheRequest(input: domTypes.RequestInfo): cacheOps.CacheRequest {
+  const request = toRequest(input);
+  const url = new URLImpl(request.url);
+  url.hash = "";
+  return {
+    url: url.href,
+    method: request.method,
+    headers: Array.from(request.headers.entries()),
+  };
+}
+
+function toQueryOptions(
+  options: CacheQueryOptions = {}
+): cacheOps.QueryOptions {
+  return {
+    ignoreSearch: Boolean(options.ignoreSearch),
+    ignoreMethod: Boolean(options.ignoreMethod),
+    ignoreVary: Boolean(options.ignoreVary),
+  };
+}
+
+function toResponse(cached: cacheOps.CachedResponse): Response {
+  return new Response(
+    cached.url,
+    cached.status,
+    cached.statusText,
+    cached.headers,
+    cached.bodyRid,
+    false
+  );
+}
+
+function matchResponses(
+  cacheId: number | null,
+  request: domTypes.RequestInfo | undefined,
+  options: CacheQueryOptions | undefined,
+  first: boolean
+): Response[] {
+  const cacheRequest = request === undefined ? null : toCacheRequest(request);
+  const responses = cacheOps.match(
+    cacheId,
+    cacheRequest,
+    toQueryOptions(options),
+    first
+  );
+  return responses.map(toResponse);
+}
+
+/** A cache of request/response pairs, opened with `caches.open()`. Response
+ * bodies are kept on disk and streamed in and out. */
+export class CacheImpl {
+  #id: number;
+
+  constructor(key: symbol, id: number) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+    this.#id = id;
+  }
+
+  // eslint-disable-next-line require-await
+  async match(
+    request: domTypes.RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<Response | undefined> {
+    const [response] = matchResponses(this.#id, request, options, true);
+    return response;
+  }
+
+  // eslint-disable-next-line require-await
+  async matchAll(
+    request?: domTypes.RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<Response[]> {
+    return matchResponses(this.#id, request, options, false);
+  }
+
+  async add(request: domTypes.RequestInfo): Promise<void> {
+    await this.addAll([request]);
+  }
+
+  async addAll(requests: domTypes.RequestInfo[]): Promise<void> {
+    const fetched: Array<[domTypes.Request, Response]> = [];
+    try {
+      for (const input of requests) {
+        const request = toRequest(input);
+        if (request.method !== "GET") {
+          throw new TypeError("Only GET requests can be added to a cache");
+        }
+        const response = await fetch(request);
+        fetched.push([request, response]);
+        if (!response.ok) {
+          throw new TypeError(
+            `Failed to add ${request.url} to the cache: ${response.status} ${response.statusText}`
+          );
+        }
+      }
+    } catch (error) {
+      for (const [, response] of fetched) {
+        await response.body?.close();
+      }
+      throw error;
+    }
+    for (const [request, response] of fetched) {
+      await this.put(request, response);
+    }
+  }
+
+  async put(
+    request: domTypes.RequestInfo,
+    response: domTypes.Response
+  ): Promise<void> {
+    const cacheRequest = toCacheRequest(request);
+    if (!/^https?:$/.test(new URLImpl(cacheRequest.url).protocol)) {
+      throw new TypeError("Only http and https requests can be cached");
+    }
+    if (cacheRequest.method !== "GET") {
+      throw new TypeError("Only GET requests can be cached");
+    }
+    if (!(response instanceof Response)) {
+      throw new TypeError("Expected a Response");
+    }
+    if (response.status === 206) {
+      throw new TypeError("Partial responses can't be cached");
+    }
+    const vary = response.headers.get("vary") ?? "";
+    if (vary.split(",").some((name): boolean => name.trim() === "*")) {
+      throw new TypeError("Responses with `Vary: *` can't be cached");
+    }
+    if (response.bodyUsed) {
+      throw new TypeError("Response body is already used");
+    }
+
+    const rid = cacheOps.putStart(this.#id, cacheRequest, {
+      url: response.url,
+      status: response.status,
+      statusText: response.statusText,
+      headers: Array.from(response.headers.entries()),
+    });
+    const body = response.body;
+    try {
+      if (body) {
+        const buf = new Uint8Array(64 * 1024);
+        let nread: number | null;
+        while ((nread = await body.read(buf)) !== null) {
+          cacheOps.putWrite(rid, buf.subarray(0, nread));
+        }
+      }
+      cacheOps.putFinish(rid);
+    } catch (error) {
+      // Discards the body written so far.
+      close(rid);
+      throw error;
+    } finally {
+      await body?.close();
+    }
+  }
+
+  // eslint-disable-next-line require-await
+  async delete(
+    request: domTypes.RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<boolean> {
+    return cacheOps.deleteEntries(
+      this.#id,
+      toCacheRequest(request),
+      toQueryOptions(options)
+    );
+  }
+
+  // eslint-disable-next-line require-await
+  async keys(
+    request?: domTypes.RequestInfo,
+    options?: CacheQueryOptions
+  ): Promise<domTypes.Request[]> {
+    const cacheRequest = request === undefined ? null : toCacheRequest(request);
+    const requests = cacheOps.keys(
+      this.#id,
+      cacheRequest,
+      toQueryOptions(options)
+    );
+    return requests.map(
+      ({ url, method, headers }): domTypes.Request =>
+        new Request(url, { method, headers })
+    );
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "Cache";
+  }
+}
+
+/** The named caches of the origin, like `window.caches` in browsers. */
+export class CacheStorageImpl {
+  constructor(key: symbol) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+  }
+
+  // eslint-disable-next-line require-await
+  async open(cacheName: string): Promise<CacheImpl> {
+    const id = cacheOps.storageOpen(String(cacheName));
+    return new CacheImpl(illegalConstructorKey, id);
+  }
+
+  // eslint-disable-next-line require-await
+  async has(cacheName: string): Promise<boolean> {
+    return cacheOps.storageHas(String(cacheName));
+  }
+
+  // eslint-disable-next-line require-await
+  async delete(cacheName: string): Promise<boolean> {
+    return cacheOps.storageDelete(String(cacheName));
+  }
+
+  // eslint-disable-next-line require-await
+  async keys(): Promise<string[]> {
+    return cacheOps.storageKeys();
+  }
+
+  /** Looks for the request in every cache, in the order they were created,
+   * or only in `options.cacheName`. */
+  // eslint-disable-next-line require-await
+  async match(
+    request: domTypes.RequestInfo,
+    options: MultiCacheQueryOptions = {}
+  ): Promise<Response | undefined> {
+    let cacheId: number | null = null;
+    if (options.cacheName !== undefined) {
+      if (!cacheOps.storageHas(options.cacheName)) {
+        return undefined;
+      }
+      cacheId = cacheOps.storageOpen(options.cacheName);
+    }
+    const [response] = matchResponses(cacheId, request, options, true);
+    return response;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "CacheStorage";
+  }
+}
+
+let caches: CacheStorageImpl | undefined;
+
+export function getCacheStorage(): CacheStorageImpl {
+  if (!caches) {
+    caches = new CacheStorageImpl(illegalConstructorKey);
+  }
+  return caches;
+}
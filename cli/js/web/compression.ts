@@ -0,0 +1,95 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+import * as compressionOps from "../ops/compression.ts";
+import { close } from "../ops/resources.ts";
+import { TransformStreamImpl } from "./streams/transform_stream.ts";
+import { setFunctionName } from "./streams/internals.ts";
+import { customInspect, inspect } from "./console.ts";
+
+function toUint8Array(chunk: BufferSource): Uint8Array {
+  if (chunk instanceof ArrayBuffer) {
+    return new Uint8Array(chunk);
+  }
+  if (ArrayBuffer.isView(chunk)) {
+    return new Uint8Array(chunk.buffer, chunk.byteOffset, chunk.byteLength);
+  }
+  throw new TypeError("Chunk must be an ArrayBuffer or ArrayBufferView.");
+}
+
+function takeOutput(rid: number, size: number): Uint8Array {
+  const buffer = new Uint8Array(size);
+  const n = compressionOps.read(rid, buffer);
+  return buffer.subarray(0, n);
+}
+
+/** Runs chunks through a Rust compression resource; the resource is closed
+ * once the stream ends or fails. */
+function createTransform(
+  format: string,
+  decompress: boolean
+): TransformStreamImpl<BufferSource, Uint8Array> {
+  const rid = compressionOps.create(format, decompress);
+  return new TransformStreamImpl<BufferSource, Uint8Array>({
+    transform(chunk, controller): void {
+      try {
+        const size = compressionOps.write(rid, toUint8Array(chunk));
+        if (size > 0) {
+          controller.enqueue(takeOutput(rid, size));
+        }
+      } catch (err) {
+        close(rid);
+        throw err;
+      }
+    },
+    flush(controller): void {
+      try {
+        const size = compressionOps.finish(rid);
+        if (size > 0) {
+          controller.enqueue(takeOutput(rid, size));
+        }
+      } finally {
+        close(rid);
+      }
+    },
+  });
+}
+
+class CompressionTransform {
+  #transform: TransformStreamImpl<BufferSource, Uint8Array>;
+
+  constructor(format: string, decompress: boolean) {
+    this.#transform = createTransform(format, decompress);
+  }
+
+  get readable(): ReadableStream<Uint8Array> {
+    return this.#transform.readable;
+  }
+
+  get writable(): WritableStream<BufferSource> {
+    return this.#transform.writable;
+  }
+
+  [customInspect](): string {
+    return `${this.constructor.name} {\n  readable: ${inspect(
+      this.readable
+    )}\n  writable: ${inspect(this.writable)}\n}`;
+  }
+}
+
+export class CompressionStreamImpl extends CompressionTransform
+  implements CompressionStream {
+  constructor(format: string) {
+    super(format, false);
+  }
+}
+
+setFunctionName(CompressionStreamImpl, "CompressionStream");
+
+export class DecompressionStreamImpl extends CompressionTransform
+  implements DecompressionStream {
+  constructor(format: string) {
+    super(format, true);
+  }
+}
+
+setFunctionName(DecompressionStreamImpl, "DecompressionStream");
@@ -3,13 +3,52 @@ import { isInvalidDate, isTypedArray, TypedArray } from "./util.ts";
 import { cliTable } from "./console_table.ts";
 import { exposeForTest } from "../internals.ts";
 import { PromiseState } from "./promise.ts";
+import { stripColor } from "../colors.ts";
+
+// Objects being inspected, to detect circular references, and whether the
+// output should be colorized.
+interface ConsoleContext extends Set<unknown> {
+  colors: boolean;
+}
+
+function createContext(colors: boolean): ConsoleContext {
+  const ctx = new Set<unknown>() as ConsoleContext;
+  ctx.colors = colors;
+  return ctx;
+}
 
-type ConsoleContext = Set<unknown>;
 type InspectOptions = Partial<{
   depth: number;
   indentLevel: number;
+  colors: boolean;
 }>;
 
+// ANSI codes used for values when `colors` option is set, as in Node.js.
+const styles = {
+  special: [36, 39],
+  number: [33, 39],
+  bigint: [33, 39],
+  boolean: [33, 39],
+  undefined: [90, 39],
+  null: [1, 22],
+  string: [32, 39],
+  symbol: [32, 39],
+  date: [35, 39],
+  regexp: [31, 39],
+};
+
+function stylize(
+  str: string,
+  style: keyof typeof styles,
+  ctx: ConsoleContext
+): string {
+  if (!ctx.colors) {
+    return str;
+  }
+  const [open, close] = styles[style];
+  return `\x1b[${open}m${str}\x1b[${close}m`;
+}
+
 const DEFAULT_MAX_DEPTH = 4; // Default depth of logging nested objects
 const LINE_BREAKING_LENGTH = 80;
 const MAX_ITERABLE_LENGTH = 100;
@@ -50,14 +89,14 @@ function getClassInstanceName(instance: unknown): string {
   return "";
 }
 
-function createFunctionString(value: Function, _ctx: ConsoleContext): string {
+function createFunctionString(value: Function, ctx: ConsoleContext): string {
   // Might be Function/AsyncFunction/GeneratorFunction
   const cstrName = Object.getPrototypeOf(value).constructor.name;
   if (value.name && value.name !== "anonymous") {
     // from MDN spec
-    return `[${cstrName}: ${value.name}]`;
+    return stylize(`[${cstrName}: ${value.name}]`, "special", ctx);
   }
-  return `[${cstrName}]`;
+  return stylize(`[${cstrName}]`, "special", ctx);
 }
 
 interface IterablePrintConfig<T> {
@@ -72,6 +111,8 @@ interface IterablePrintConfig<T> {
     next: () => IteratorResult<[unknown, T], unknown>
   ) => string;
   group: boolean;
+  // Entries to print instead of the ones returned by `value.entries()`.
+  entries?: IterableIterator<[unknown, T]>;
 }
 type IterableEntries<T> = Iterable<T> & {
   entries(): IterableIterator<[unknown, T]>;
@@ -84,13 +125,13 @@ function createIterableString<T>(
   config: IterablePrintConfig<T>
 ): string {
   if (level >= maxLevel) {
-    return `[${config.typeName}]`;
+    return stylize(`[${config.typeName}]`, "special", ctx);
   }
   ctx.add(value);
 
   const entries: string[] = [];
 
-  const iter = value.entries();
+  const iter = config.entries ?? value.entries();
   let entriesLength = 0;
   const next = (): IteratorResult<[unknown, T], unknown> => {
     return iter.next();
@@ -124,7 +165,7 @@ function createIterableString<T>(
     )}${closingIndentation}`;
   } else {
     iContent = entries.length === 0 ? "" : ` ${entries.join(", ")} `;
-    if (iContent.length > LINE_BREAKING_LENGTH) {
+    if (stripColor(iContent).length > LINE_BREAKING_LENGTH) {
       const initIndentation = `\n${" ".repeat(level + 1)}`;
       const entryIndetation = `,\n${" ".repeat(level + 1)}`;
       const closingIndentation = `\n`;
@@ -155,10 +196,9 @@ function groupEntries<T>(
   const separatorSpace = 2; // Add 1 for the space and 1 for the separator.
   const dataLen = new Array(entriesLength);
   // Calculate the total length of all output entries and the individual max
-  // entries length of all output entries. In future colors should be taken
-  // here into the account
+  // entries length of all output entries. Colors are not taken into account.
   for (let i = 0; i < entriesLength; i++) {
-    const len = entries[i].length;
+    const len = stripColor(entries[i]).length;
     dataLen[i] = len;
     totalLength += len + separatorSpace;
     if (maxLength < len) maxLength = len;
@@ -223,8 +263,9 @@ function groupEntries<T>(
       let str = "";
       let j = i;
       for (; j < max - 1; j++) {
-        // In future, colors should be taken here into the account
-        const padding = maxLineLength[j - i];
+        // Account for the length of color codes in padding.
+        const padding =
+          maxLineLength[j - i] + entries[j].length - dataLen[j];
         //@ts-ignore
         str += `${entries[j]}, `[order](padding, " ");
       }
@@ -259,22 +300,26 @@ function stringify(
       return value;
     case "number":
       // Special handling of -0
-      return Object.is(value, -0) ? "-0" : `${value}`;
+      return stylize(Object.is(value, -0) ? "-0" : `${value}`, "number", ctx);
     case "boolean":
     case "undefined":
     case "symbol":
-      return String(value);
+      return stylize(
+        String(value),
+        typeof value as "boolean" | "undefined" | "symbol",
+        ctx
+      );
     case "bigint":
-      return `${value}n`;
+      return stylize(`${value}n`, "bigint", ctx);
     case "function":
       return createFunctionString(value as Function, ctx);
     case "object":
       if (value === null) {
-        return "null";
+        return stylize("null", "null", ctx);
       }
 
       if (ctx.has(value)) {
-        return "[Circular]";
+        return stylize("[Circular]", "special", ctx);
       }
 
       return createObjectString(value, ctx, level, maxLevel);
@@ -296,7 +341,7 @@ function stringifyWithQuotes(
         value.length > STR_ABBREVIATE_SIZE
           ? value.slice(0, STR_ABBREVIATE_SIZE) + "..."
           : value;
-      return JSON.stringify(trunc);
+      return stylize(JSON.stringify(trunc), "string", ctx);
     default:
       return stringify(value, ctx, level, maxLevel);
   }
@@ -333,6 +378,18 @@ function createArrayString(
   return createIterableString(value, ctx, level, maxLevel, printConfig);
 }
 
+// The elements of a typed array, up to the length of its view of the buffer
+// as reported by V8, so that overriding the `length` getter doesn't affect
+// them.
+function* typedArrayEntries(
+  value: TypedArray
+): IterableIterator<[number, unknown]> {
+  const length = Deno.core.getTypedArrayLength(value);
+  for (let i = 0; i < length; i++) {
+    yield [i, value[i]];
+  }
+}
+
 function createTypedArrayString(
   typedArrayName: string,
   value: TypedArray,
@@ -340,7 +397,7 @@ function createTypedArrayString(
   level: number,
   maxLevel: number
 ): string {
-  const valueLength = value.length;
+  const valueLength = Deno.core.getTypedArrayLength(value);
   const printConfig: IterablePrintConfig<unknown> = {
     typeName: typedArrayName,
     displayName: `${typedArrayName}(${valueLength})`,
@@ -350,10 +407,25 @@ function createTypedArrayString(
       return stringifyWithQuotes(val, ctx, level + 1, maxLevel);
     },
     group: true,
+    entries: typedArrayEntries(value),
   };
   return createIterableString(value, ctx, level, maxLevel, printConfig);
 }
 
+// Captured before user code runs, so that overriding it doesn't affect how
+// sets are printed. Unlike maps, V8 doesn't expose the contents of sets to
+// the runtime, as rusty_v8 has no binding for `v8::Set::AsArray()`.
+const setValues = Set.prototype.values;
+
+function* setEntries(
+  value: Set<unknown>
+): IterableIterator<[number, unknown]> {
+  let i = 0;
+  for (const entry of setValues.call(value)) {
+    yield [i++, entry];
+  }
+}
+
 function createSetString(
   value: Set<unknown>,
   ctx: ConsoleContext,
@@ -369,17 +441,29 @@ function createSetString(
       return stringifyWithQuotes(val, ctx, level + 1, maxLevel);
     },
     group: false,
+    entries: setEntries(value),
   };
   return createIterableString(value, ctx, level, maxLevel, printConfig);
 }
 
+// The keys and values of a map, as read by V8, so that overriding the map's
+// iterator methods doesn't affect them.
+function* mapEntries(
+  value: Map<unknown, unknown>
+): IterableIterator<[unknown, unknown]> {
+  const entries = Deno.core.getMapEntries(value);
+  for (let i = 0; i < entries.length; i += 2) {
+    yield [entries[i], entries[i + 1]];
+  }
+}
+
 function createMapString(
   value: Map<unknown, unknown>,
   ctx: ConsoleContext,
   level: number,
   maxLevel: number
 ): string {
-  const printConfig: IterablePrintConfig<[unknown]> = {
+  const printConfig: IterablePrintConfig<unknown> = {
     typeName: "Map",
     displayName: "Map",
     delims: ["{", "}"],
@@ -393,8 +477,8 @@ function createMapString(
       )} => ${stringifyWithQuotes(val, ctx, level + 1, maxLevel)}`;
     },
     group: false,
+    entries: mapEntries(value),
   };
-  //@ts-ignore
   return createIterableString(value, ctx, level, maxLevel, printConfig);
 }
 
@@ -406,12 +490,14 @@ function createWeakMapString(): string {
   return "WeakMap { [items unknown] }"; // as seen in Node
 }
 
-function createDateString(value: Date): string {
-  return isInvalidDate(value) ? "Invalid Date" : value.toISOString(); // without quotes, ISO format
+function createDateString(value: Date, ctx: ConsoleContext): string {
+  // without quotes, ISO format
+  const str = isInvalidDate(value) ? "Invalid Date" : value.toISOString();
+  return stylize(str, "date", ctx);
 }
 
-function createRegExpString(value: RegExp): string {
-  return value.toString();
+function createRegExpString(value: RegExp, ctx: ConsoleContext): string {
+  return stylize(value.toString(), "regexp", ctx);
 }
 
 /* eslint-disable @typescript-eslint/ban-types */
@@ -439,10 +525,13 @@ function createPromiseString(
   const [state, result] = Deno.core.getPromiseDetails(value);
 
   if (state === PromiseState.Pending) {
-    return "Promise { <pending> }";
+    return `Promise { ${stylize("<pending>", "special", ctx)} }`;
   }
 
-  const prefix = state === PromiseState.Fulfilled ? "" : "<rejected> ";
+  const prefix =
+    state === PromiseState.Fulfilled
+      ? ""
+      : `${stylize("<rejected>", "special", ctx)} `;
 
   const str = `${prefix}${stringifyWithQuotes(
     result,
@@ -451,7 +540,10 @@ function createPromiseString(
     maxLevel
   )}`;
 
-  if (str.length + PROMISE_STRING_BASE_LENGTH > LINE_BREAKING_LENGTH) {
+  if (
+    stripColor(str).length + PROMISE_STRING_BASE_LENGTH >
+    LINE_BREAKING_LENGTH
+  ) {
     return `Promise {\n${" ".repeat(level + 1)}${str}\n}`;
   }
 
@@ -467,7 +559,7 @@ function createRawObjectString(
   maxLevel: number
 ): string {
   if (level >= maxLevel) {
-    return "[Object]";
+    return stylize("[Object]", "special", ctx);
   }
   ctx.add(value);
 
@@ -504,7 +596,8 @@ function createRawObjectString(
     );
   }
 
-  const totalLength = entries.length + level + entries.join("").length;
+  const totalLength =
+    entries.length + level + stripColor(entries.join("")).length;
 
   ctx.delete(value);
 
@@ -549,9 +642,9 @@ function createObjectString(
   } else if (value instanceof Promise) {
     return createPromiseString(value, ...args);
   } else if (value instanceof RegExp) {
-    return createRegExpString(value);
+    return createRegExpString(value, args[0]);
   } else if (value instanceof Date) {
-    return createDateString(value);
+    return createDateString(value, args[0]);
   } else if (value instanceof Set) {
     return createSetString(value, ...args);
   } else if (value instanceof Map) {
@@ -572,9 +665,41 @@ function createObjectString(
   }
 }
 
+function hasUserDefinedToString(value: object): boolean {
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const toString = (value as any).toString;
+  return (
+    typeof toString === "function" &&
+    !Function.prototype.toString.call(toString).includes("[native code]")
+  );
+}
+
+// Formats a `%s` argument. Like `String(value)`, except that objects without
+// a `toString()` of their own are inspected one level deep.
+function stringifyFormatString(value: unknown, colors: boolean): string {
+  if (typeof value === "bigint") {
+    return `${value}n`;
+  }
+  if (Object.is(value, -0)) {
+    return "-0";
+  }
+  if (
+    typeof value === "object" &&
+    value !== null &&
+    !hasUserDefinedToString(value)
+  ) {
+    return stringify(value, createContext(colors), 0, 1);
+  }
+  return String(value);
+}
+
 export function stringifyArgs(
   args: unknown[],
-  { depth = DEFAULT_MAX_DEPTH, indentLevel = 0 }: InspectOptions = {}
+  {
+    depth = DEFAULT_MAX_DEPTH,
+    indentLevel = 0,
+    colors = false,
+  }: InspectOptions = {}
 ): string {
   const first = args[0];
   let a = 0;
@@ -592,7 +717,7 @@ export function stringifyArgs(
           switch (nextChar) {
             case CHAR_LOWERCASE_S:
               // format as a string
-              tempStr = String(args[++a]);
+              tempStr = stringifyFormatString(args[++a], colors);
               break;
             case CHAR_LOWERCASE_D:
             case CHAR_LOWERCASE_I:
@@ -618,15 +743,18 @@ export function stringifyArgs(
             case CHAR_LOWERCASE_O:
             case CHAR_UPPERCASE_O:
               // format as an object
-              tempStr = stringify(args[++a], new Set<unknown>(), 0, depth);
+              tempStr = stringify(args[++a], createContext(colors), 0, depth);
               break;
             case CHAR_PERCENT:
               str += first.slice(lastPos, i);
               lastPos = i + 1;
               continue;
             case CHAR_LOWERCASE_C:
-              // TODO: applies CSS style rules to the output string as specified
-              continue;
+              // CSS styles can't be applied to terminal output, so the
+              // argument is consumed and the specifier removed.
+              a++;
+              tempStr = "";
+              break;
             default:
               // any other character is not a correct placeholder
               continue;
@@ -661,7 +789,7 @@ export function stringifyArgs(
       str += value;
     } else {
       // use default maximum depth for null or undefined argument
-      str += stringify(value, new Set<unknown>(), 0, depth);
+      str += stringify(value, createContext(colors), 0, depth);
     }
     join = " ";
     a++;
@@ -716,7 +844,11 @@ export class Console {
   info = this.log;
 
   dir = (obj: unknown, options: InspectOptions = {}): void => {
-    this.#printFunc(stringifyArgs([obj], options) + "\n", false);
+    this.#printFunc(
+      stringifyArgs([obj], { indentLevel: this.indentLevel, ...options }) +
+        "\n",
+      false
+    );
   };
 
   dirxml = this.dir;
@@ -792,7 +924,7 @@ export class Console {
     const values: string[] = [];
 
     const stringifyValue = (value: unknown): string =>
-      stringifyWithQuotes(value, new Set<unknown>(), 0, 1);
+      stringifyWithQuotes(value, createContext(false), 0, 1);
     const toTable = (header: string[], body: string[][]): void =>
       this.log(cliTable(header, body));
     const createColumn = (value: unknown, shift?: number): string[] => [
@@ -876,7 +1008,7 @@ export class Console {
     label = String(label);
 
     if (!timerMap.has(label)) {
-      this.warn(`Timer '${label}' does not exists`);
+      this.warn(`Timer '${label}' does not exist`);
       return;
     }
 
@@ -890,7 +1022,7 @@ export class Console {
     label = String(label);
 
     if (!timerMap.has(label)) {
-      this.warn(`Timer '${label}' does not exists`);
+      this.warn(`Timer '${label}' does not exist`);
       return;
     }
 
@@ -942,12 +1074,12 @@ export const customInspect = Symbol("Deno.symbols.customInspect");
 
 export function inspect(
   value: unknown,
-  { depth = DEFAULT_MAX_DEPTH }: InspectOptions = {}
+  { depth = DEFAULT_MAX_DEPTH, colors = false }: InspectOptions = {}
 ): string {
   if (typeof value === "string") {
     return value;
   } else {
-    return stringify(value, new Set<unknown>(), 0, depth);
+    return stringify(value, createContext(colors), 0, depth);
   }
 }
 
@@ -0,0 +1,115 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { core } from "../core.ts";
+import { EventImpl as Event } from "./event.ts";
+import { defineEnumerableProps, requiredArguments } from "./util.ts";
+
+export interface ErrorEventInit extends EventInit {
+  message?: string;
+  filename?: string;
+  lineno?: number;
+  colno?: number;
+  error?: any;
+}
+
+export class ErrorEventImpl extends Event {
+  #message: string;
+  #filename: string;
+  #lineno: number;
+  #colno: number;
+  #error: any;
+
+  constructor(type: string, eventInitDict: ErrorEventInit = {}) {
+    super(type, eventInitDict);
+    requiredArguments("ErrorEvent", arguments.length, 1);
+    this.#message = eventInitDict.message ?? "";
+    this.#filename = eventInitDict.filename ?? "";
+    this.#lineno = eventInitDict.lineno ?? 0;
+    this.#colno = eventInitDict.colno ?? 0;
+    this.#error = "error" in eventInitDict ? eventInitDict.error : null;
+  }
+
+  get message(): string {
+    return this.#message;
+  }
+
+  get filename(): string {
+    return this.#filename;
+  }
+
+  get lineno(): number {
+    return this.#lineno;
+  }
+
+  get colno(): number {
+    return this.#colno;
+  }
+
+  get error(): any {
+    return this.#error;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "ErrorEvent";
+  }
+}
+
+defineEnumerableProps(ErrorEventImpl, [
+  "message",
+  "filename",
+  "lineno",
+  "colno",
+  "error",
+]);
+
+interface CallSiteLocation {
+  fileName: string | null;
+  lineNumber: number | null;
+  columnNumber: number | null;
+}
+
+/** Creates the `error` event for an uncaught exception, located at the top
+ * frame of its stack. */
+export function createErrorEvent(error: any): ErrorEventImpl {
+  let location: CallSiteLocation | null = null;
+  if (error instanceof Error) {
+    // Formatting the stack records its call sites.
+    String(error.stack);
+    // @ts-ignore
+    location = error.__callSiteEvals?.[0] ?? null;
+  }
+  return new ErrorEventImpl("error", {
+    cancelable: true,
+    message: error instanceof Error ? error.message : String(error),
+    filename: location?.fileName ?? "",
+    lineno: location?.lineNumber ?? 0,
+    colno: location?.columnNumber ?? 0,
+    error,
+  });
+}
+
+let dispatchingError = false;
+
+/** Dispatches an `error` event at the global scope for the uncaught
+ * exception. Returns whether it was handled, by canceling the event. An
+ * exception thrown while dispatching an `error` event is never handled. */
+export function handleUncaughtException(error: any): boolean {
+  if (dispatchingError) {
+    return false;
+  }
+  dispatchingError = true;
+  try {
+    return !globalThis.dispatchEvent(createErrorEvent(error));
+  } finally {
+    dispatchingError = false;
+  }
+}
+
+/** Reports an exception thrown by a callback that can't rethrow it, like an
+ * event listener. Unless an `error` listener handles it, the exception is
+ * uncaught and ends the program. */
+export function reportException(error: any): void {
+  if (!handleUncaughtException(error)) {
+    core.reportUncaughtException(error);
+  }
+}
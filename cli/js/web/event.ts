@@ -217,6 +217,23 @@ export class EventImpl implements Event {
     return true;
   }
 
+  /** Legacy alias of `!defaultPrevented`; setting it to false cancels the
+   * event. */
+  get returnValue(): boolean {
+    return !this.#canceledFlag;
+  }
+
+  set returnValue(value: boolean) {
+    if (!value) {
+      this.preventDefault();
+    }
+  }
+
+  /** Legacy alias of `target`. */
+  get srcElement(): EventTarget | null {
+    return this.#attributes.target;
+  }
+
   get target(): EventTarget | null {
     return this.#attributes.target;
   }
@@ -391,6 +408,10 @@ export class EventImpl implements Event {
   static get BUBBLING_PHASE(): number {
     return 3;
   }
+
+  get [Symbol.toStringTag](): string {
+    return "Event";
+  }
 }
 
 defineEnumerableProps(EventImpl, [
@@ -400,6 +421,8 @@ defineEnumerableProps(EventImpl, [
   "currentTarget",
   "defaultPrevented",
   "eventPhase",
+  "returnValue",
+  "srcElement",
   "target",
   "timeStamp",
   "type",
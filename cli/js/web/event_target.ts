@@ -7,6 +7,10 @@
 
 import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
 import * as domTypes from "./dom_types.d.ts";
+import {
+  ErrorEventImpl as ErrorEvent,
+  reportException,
+} from "./error_event.ts";
 import {
   EventImpl as Event,
   EventPath,
@@ -337,12 +341,18 @@ function innerInvokeEventListeners(
       setInPassiveListener(eventImpl, true);
     }
 
-    if (typeof listener.callback === "object") {
-      if (typeof listener.callback.handleEvent === "function") {
-        listener.callback.handleEvent(eventImpl);
+    // An exception thrown by a listener doesn't stop the dispatch, it's
+    // reported instead.
+    try {
+      if (typeof listener.callback === "object") {
+        if (typeof listener.callback.handleEvent === "function") {
+          listener.callback.handleEvent(eventImpl);
+        }
+      } else {
+        listener.callback.call(eventImpl.currentTarget, eventImpl);
       }
-    } else {
-      listener.callback.call(eventImpl.currentTarget, eventImpl);
+    } catch (error) {
+      reportException(error);
     }
 
     setInPassiveListener(eventImpl, false);
@@ -527,11 +537,7 @@ export class EventTargetImpl implements EventTarget {
     requiredArguments("EventTarget.removeEventListener", arguments.length, 2);
 
     const listeners = eventTargetData.get(this ?? globalThis)!.listeners;
-    if (callback !== null && type in listeners) {
-      listeners[type] = listeners[type].filter(
-        (listener) => listener.callback !== callback
-      );
-    } else if (callback === null || !listeners[type]) {
+    if (callback === null || !listeners[type]) {
       return;
     }
 
@@ -586,3 +592,80 @@ defineEnumerableProps(EventTargetImpl, [
   "removeEventListener",
   "dispatchEvent",
 ]);
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+type EventHandler = (this: EventTarget, ...args: any[]) => any;
+
+interface EventHandlerListener {
+  (this: EventTarget, event: Event): void;
+  handler: EventHandler;
+}
+
+const eventHandlers = new WeakMap<
+  EventTarget,
+  Record<string, EventHandlerListener>
+>();
+
+function createEventHandlerListener(
+  handler: EventHandler
+): EventHandlerListener {
+  const listener = function (this: EventTarget, event: Event): void {
+    // `onerror` gets the details of the error and cancels the event by
+    // returning true, other handlers cancel it by returning false.
+    if (event instanceof ErrorEvent && event.type === "error") {
+      const { message, filename, lineno, colno, error } = event;
+      const result = listener.handler.call(
+        this,
+        message,
+        filename,
+        lineno,
+        colno,
+        error
+      );
+      if (result === true) {
+        event.preventDefault();
+      }
+    } else if (listener.handler.call(this, event) === false) {
+      event.preventDefault();
+    }
+  } as EventHandlerListener;
+  listener.handler = handler;
+  return listener;
+}
+
+/** Returns the property for the `on<type>` event handler of an event target,
+ * like `onload` of the global scope. Setting a function adds it as a listener
+ * of `type` events, which keeps its place among the listeners when another
+ * function is set, until it's set to `null`.
+ *
+ * Ref: https://html.spec.whatwg.org/multipage/webappapis.html#event-handler-attributes */
+export function eventHandlerProperty(type: string): PropertyDescriptor {
+  return {
+    get(this: EventTarget): EventHandler | null {
+      const handlers = eventHandlers.get(this ?? globalThis);
+      return handlers?.[type]?.handler ?? null;
+    },
+    set(this: EventTarget, value: unknown): void {
+      const target = this ?? globalThis;
+      let handlers = eventHandlers.get(target);
+      if (!handlers) {
+        handlers = Object.create(null) as Record<string, EventHandlerListener>;
+        eventHandlers.set(target, handlers);
+      }
+      const listener = handlers[type];
+      if (typeof value !== "function") {
+        if (listener) {
+          target.removeEventListener(type, listener);
+          delete handlers[type];
+        }
+      } else if (listener) {
+        listener.handler = value as EventHandler;
+      } else {
+        handlers[type] = createEventHandlerListener(value as EventHandler);
+        target.addEventListener(type, handlers[type]);
+      }
+    },
+    enumerable: true,
+    configurable: true,
+  };
+}
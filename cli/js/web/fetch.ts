@@ -3,13 +3,21 @@ import { assert, createResolvable, notImplemented } from "../util.ts";
 import { isTypedArray } from "./util.ts";
 import * as domTypes from "./dom_types.d.ts";
 import { TextDecoder, TextEncoder } from "./text_encoding.ts";
-import { DenoBlob, bytesSymbol as blobBytesSymbol } from "./blob.ts";
+import { DenoBlob, blobFromObjectUrl, blobIdSymbol } from "./blob.ts";
+import { stream as streamBlob } from "../ops/blob.ts";
 import * as io from "../io.ts";
 import { read } from "../ops/io.ts";
 import { close } from "../ops/resources.ts";
 import { Buffer } from "../buffer.ts";
 import { fetch as opFetch, FetchResponse } from "../ops/fetch.ts";
-import { DomFileImpl } from "./dom_file.ts";
+import { HttpClient } from "../http_client.ts";
+import { CancelHandle } from "./abort_signal.ts";
+import {
+  readableStreamForRid,
+  resourceForReadableStream,
+} from "../io_streams.ts";
+import { ReadableStreamImpl } from "./streams/readable_stream.ts";
+import { encodeFormData, parseFormData } from "./multipart.ts";
 
 function getHeaderValueParams(value: string): Map<string, string> {
   const params = new Map();
@@ -34,11 +42,19 @@ class Body
   #bodyPromise: Promise<ArrayBuffer> | null = null;
   #data: ArrayBuffer | null = null;
   #rid: number;
-  readonly locked: boolean = false; // TODO
+  // Aborts reads of the body along with the request's signal.
+  #cancelHandle: CancelHandle | null;
+  // The body as a stream, once one of the stream methods is used.
+  #stream: ReadableStream<Uint8Array> | null = null;
   readonly body: ReadableStream<Uint8Array>;
 
-  constructor(rid: number, readonly contentType: string) {
+  constructor(
+    rid: number,
+    readonly contentType: string,
+    cancelHandle: CancelHandle | null = null
+  ) {
     this.#rid = rid;
+    this.#cancelHandle = cancelHandle;
     this.body = this;
   }
 
@@ -61,6 +77,19 @@ class Body
     return this.#data;
   };
 
+  #readable = (): ReadableStream<Uint8Array> => {
+    if (!this.#stream) {
+      this.#bodyUsed = true;
+      this.#stream = readableStreamForRid(this.#rid, {
+        cancelRid: this.#cancelHandle?.rid,
+        onCancel: (): void => {
+          this.close();
+        },
+      });
+    }
+    return this.#stream;
+  };
+
   // eslint-disable-next-line require-await
   async arrayBuffer(): Promise<ArrayBuffer> {
     // If we've already bufferred the response, just return it.
@@ -85,104 +114,31 @@ class Body
 
   // ref: https://fetch.spec.whatwg.org/#body-mixin
   async formData(): Promise<FormData> {
-    const formData = new FormData();
-    const enc = new TextEncoder();
     if (hasHeaderValueOf(this.contentType, "multipart/form-data")) {
       const params = getHeaderValueParams(this.contentType);
       if (!params.has("boundary")) {
         // TypeError is required by spec
         throw new TypeError("multipart/form-data must provide a boundary");
       }
-      // ref: https://tools.ietf.org/html/rfc2046#section-5.1
       const boundary = params.get("boundary")!;
-      const dashBoundary = `--${boundary}`;
-      const delimiter = `\r\n${dashBoundary}`;
-      const closeDelimiter = `${delimiter}--`;
-
-      const body = await this.text();
-      let bodyParts: string[];
-      const bodyEpilogueSplit = body.split(closeDelimiter);
-      if (bodyEpilogueSplit.length < 2) {
-        bodyParts = [];
-      } else {
-        // discard epilogue
-        const bodyEpilogueTrimmed = bodyEpilogueSplit[0];
-        // first boundary treated special due to optional prefixed \r\n
-        const firstBoundaryIndex = bodyEpilogueTrimmed.indexOf(dashBoundary);
-        if (firstBoundaryIndex < 0) {
-          throw new TypeError("Invalid boundary");
-        }
-        const bodyPreambleTrimmed = bodyEpilogueTrimmed
-          .slice(firstBoundaryIndex + dashBoundary.length)
-          .replace(/^[\s\r\n\t]+/, ""); // remove transport-padding CRLF
-        // trimStart might not be available
-        // Be careful! body-part allows trailing \r\n!
-        // (as long as it is not part of `delimiter`)
-        bodyParts = bodyPreambleTrimmed
-          .split(delimiter)
-          .map((s): string => s.replace(/^[\s\r\n\t]+/, ""));
-        // TODO: LWSP definition is actually trickier,
-        // but should be fine in our case since without headers
-        // we should just discard the part
+      if (this.#data != null || this.#bodyPromise != null) {
+        const bytes = new Uint8Array(await this.arrayBuffer());
+        return parseFormData(boundary, { bytes });
       }
-      for (const bodyPart of bodyParts) {
-        const headers = new Headers();
-        const headerOctetSeperatorIndex = bodyPart.indexOf("\r\n\r\n");
-        if (headerOctetSeperatorIndex < 0) {
-          continue; // Skip unknown part
-        }
-        const headerText = bodyPart.slice(0, headerOctetSeperatorIndex);
-        const octets = bodyPart.slice(headerOctetSeperatorIndex + 4);
-
-        // TODO: use textproto.readMIMEHeader from deno_std
-        const rawHeaders = headerText.split("\r\n");
-        for (const rawHeader of rawHeaders) {
-          const sepIndex = rawHeader.indexOf(":");
-          if (sepIndex < 0) {
-            continue; // Skip this header
-          }
-          const key = rawHeader.slice(0, sepIndex);
-          const value = rawHeader.slice(sepIndex + 1);
-          headers.set(key, value);
-        }
-        if (!headers.has("content-disposition")) {
-          continue; // Skip unknown part
-        }
-        // Content-Transfer-Encoding Deprecated
-        const contentDisposition = headers.get("content-disposition")!;
-        const partContentType = headers.get("content-type") || "text/plain";
-        // TODO: custom charset encoding (needs TextEncoder support)
-        // const contentTypeCharset =
-        //   getHeaderValueParams(partContentType).get("charset") || "";
-        if (!hasHeaderValueOf(contentDisposition, "form-data")) {
-          continue; // Skip, might not be form-data
-        }
-        const dispositionParams = getHeaderValueParams(contentDisposition);
-        if (!dispositionParams.has("name")) {
-          continue; // Skip, unknown name
-        }
-        const dispositionName = dispositionParams.get("name")!;
-        if (dispositionParams.has("filename")) {
-          const filename = dispositionParams.get("filename")!;
-          const blob = new DenoBlob([enc.encode(octets)], {
-            type: partContentType,
-          });
-          // TODO: based on spec
-          // https://xhr.spec.whatwg.org/#dom-formdata-append
-          // https://xhr.spec.whatwg.org/#create-an-entry
-          // Currently it does not mention how I could pass content-type
-          // to the internally created file object...
-          formData.append(dispositionName, blob, filename);
-        } else {
-          formData.append(dispositionName, octets);
-        }
+      // The body is parsed as it is read, with file parts written to disk.
+      this.#bodyUsed = true;
+      try {
+        return await parseFormData(boundary, { rid: this.#rid });
+      } finally {
+        this.#cancelHandle?.close();
+        this.#cancelHandle = null;
       }
-      return formData;
     } else if (
       hasHeaderValueOf(this.contentType, "application/x-www-form-urlencoded")
     ) {
       // From https://github.com/github/fetch/blob/master/fetch.js
       // Copyright (c) 2014-2016 GitHub, Inc. MIT License
+      const formData = new FormData();
       const body = await this.text();
       try {
         body
@@ -222,30 +178,36 @@ class Body
 
   read(p: Uint8Array): Promise<number | null> {
     this.#bodyUsed = true;
-    return read(this.#rid, p);
+    return read(this.#rid, p, this.#cancelHandle?.rid);
   }
 
   close(): Promise<void> {
     close(this.#rid);
+    this.#cancelHandle?.close();
+    this.#cancelHandle = null;
     return Promise.resolve();
   }
 
-  cancel(): Promise<void> {
-    return notImplemented();
+  get locked(): boolean {
+    return this.#stream?.locked ?? false;
   }
 
-  getIterator(_options?: {
+  cancel(reason?: unknown): Promise<void> {
+    return this.#readable().cancel(reason);
+  }
+
+  getIterator(options?: {
     preventCancel?: boolean;
   }): AsyncIterableIterator<Uint8Array> {
-    return notImplemented();
+    return this.#readable().getIterator(options);
   }
 
   getReader(): ReadableStreamDefaultReader<Uint8Array> {
-    return notImplemented();
+    return this.#readable().getReader();
   }
 
   tee(): [ReadableStream, ReadableStream] {
-    return notImplemented();
+    return this.#readable().tee();
   }
 
   [Symbol.asyncIterator](): AsyncIterableIterator<Uint8Array> {
@@ -257,20 +219,20 @@ class Body
   }
 
   pipeThrough<T>(
-    _: {
+    transform: {
       writable: WritableStream<Uint8Array>;
       readable: ReadableStream<T>;
     },
-    _options?: PipeOptions
+    options?: PipeOptions
   ): ReadableStream<T> {
-    return notImplemented();
+    return this.#readable().pipeThrough(transform, options);
   }
 
   pipeTo(
-    _dest: WritableStream<Uint8Array>,
-    _options?: PipeOptions
+    dest: WritableStream<Uint8Array>,
+    options?: PipeOptions
   ): Promise<void> {
-    return notImplemented();
+    return this.#readable().pipeTo(dest, options);
   }
 }
 
@@ -289,14 +251,15 @@ export class Response implements domTypes.Response {
     rid: number,
     redirected_: boolean,
     readonly type_: null | ResponseType = "default",
-    body_: null | Body = null
+    body_: null | Body = null,
+    cancelHandle: CancelHandle | null = null
   ) {
     this.trailer = createResolvable();
     this.headers = new Headers(headersList);
     const contentType = this.headers.get("content-type") || "";
 
     if (body_ == null) {
-      this.body = new Body(rid, contentType);
+      this.body = new Body(rid, contentType, cancelHandle);
     } else {
       this.body = body_;
     }
@@ -468,7 +431,10 @@ function sendFetchReq(
   url: string,
   method: string | null,
   headers: Headers | null,
-  body: ArrayBufferView | undefined
+  body: ArrayBufferView | undefined,
+  bodyRid: number | undefined,
+  clientRid: number | undefined,
+  cancelRid: number | undefined
 ): Promise<FetchResponse> {
   let headerArray: Array<[string, string]> = [];
   if (headers) {
@@ -479,19 +445,44 @@ function sendFetchReq(
     method,
     url,
     headers: headerArray,
+    bodyRid,
+    clientRid,
+    cancelRid,
   };
 
   return opFetch(args, body);
 }
 
+/** Responds with the blob of an object URL made by `URL.createObjectURL()`. */
+function fetchObjectUrl(url: string, method: string | null): Response {
+  if (method !== null && method.toUpperCase() !== "GET") {
+    throw new TypeError(`Object URLs can only be fetched with GET: "${url}"`);
+  }
+  const blob = blobFromObjectUrl(url);
+  if (!blob) {
+    throw new TypeError(`Object URL not found: "${url}"`);
+  }
+  const headers: Array<[string, string]> = [
+    ["content-length", String(blob.size)],
+    ["content-type", blob.type],
+  ];
+  const rid = streamBlob(blob[blobIdSymbol]);
+  return new Response(url, 200, "OK", headers, rid, false);
+}
+
 export async function fetch(
   input: domTypes.Request | URL | string,
-  init?: domTypes.RequestInit
+  init?: domTypes.RequestInit & { client?: HttpClient }
 ): Promise<Response> {
   let url: string;
   let method: string | null = null;
   let headers: Headers | null = null;
   let body: ArrayBufferView | undefined;
+  // A stream resource reading a `ReadableStream` body. The body can only be
+  // sent once, so this is null after the first request.
+  let bodyRid: number | null | undefined;
+  // A blob body, streamed from outside of the V8 heap for every request.
+  let bodyBlob: DenoBlob | undefined;
   let redirected = false;
   let remRedirectCount = 20; // TODO: use a better way to handle
 
@@ -525,50 +516,26 @@ export async function fetch(
           body = new TextEncoder().encode(init.body.toString());
           contentType = "application/x-www-form-urlencoded;charset=UTF-8";
         } else if (init.body instanceof DenoBlob) {
-          body = init.body[blobBytesSymbol];
+          bodyBlob = init.body;
           contentType = init.body.type;
+          if (!headers.has("content-length")) {
+            headers.set("content-length", String(init.body.size));
+          }
         } else if (init.body instanceof FormData) {
-          let boundary = "";
+          let boundary: string | undefined;
           if (headers.has("content-type")) {
-            const params = getHeaderValueParams("content-type");
-            if (params.has("boundary")) {
-              boundary = params.get("boundary")!;
-            }
-          }
-          if (!boundary) {
-            boundary =
-              "----------" +
-              Array.from(Array(32))
-                .map(() => Math.random().toString(36)[2] || 0)
-                .join("");
+            const params = getHeaderValueParams(headers.get("content-type")!);
+            boundary = params.get("boundary");
           }
-
-          let payload = "";
-          for (const [fieldName, fieldValue] of init.body.entries()) {
-            let part = `\r\n--${boundary}\r\n`;
-            part += `Content-Disposition: form-data; name=\"${fieldName}\"`;
-            if (fieldValue instanceof DomFileImpl) {
-              part += `; filename=\"${fieldValue.name}\"`;
-            }
-            part += "\r\n";
-            if (fieldValue instanceof DomFileImpl) {
-              part += `Content-Type: ${
-                fieldValue.type || "application/octet-stream"
-              }\r\n`;
-            }
-            part += "\r\n";
-            if (fieldValue instanceof DomFileImpl) {
-              part += new TextDecoder().decode(fieldValue[blobBytesSymbol]);
-            } else {
-              part += fieldValue;
-            }
-            payload += part;
+          const encoded = encodeFormData(init.body, boundary);
+          bodyRid = encoded.rid;
+          contentType = encoded.contentType;
+          if (!headers.has("content-length")) {
+            headers.set("content-length", String(encoded.contentLength));
           }
-          payload += `\r\n--${boundary}--`;
-          body = new TextEncoder().encode(payload);
-          contentType = "multipart/form-data; boundary=" + boundary;
+        } else if (init.body instanceof ReadableStreamImpl) {
+          bodyRid = resourceForReadableStream(init.body);
         } else {
-          // TODO: ReadableStream
           notImplemented();
         }
         if (contentType && !headers.has("content-type")) {
@@ -587,53 +554,97 @@ export async function fetch(
     }
   }
 
-  while (remRedirectCount) {
-    const fetchResponse = await sendFetchReq(url, method, headers, body);
+  if (/^blob:/i.test(url)) {
+    return fetchObjectUrl(url, method);
+  }
 
-    const response = new Response(
-      url,
-      fetchResponse.status,
-      fetchResponse.statusText,
-      fetchResponse.headers,
-      fetchResponse.bodyRid,
-      redirected
-    );
-    if ([301, 302, 303, 307, 308].includes(response.status)) {
-      // We won't use body of received response, so close it now
-      // otherwise it will be kept in resource table.
-      close(fetchResponse.bodyRid);
-      // We're in a redirect status
-      switch ((init && init.redirect) || "follow") {
-        case "error":
-          /* I suspect that deno will probably crash if you try to use that
-             rid, which suggests to me that Response needs to be refactored */
-          return new Response("", 0, "", [], -1, false, "error", null);
-        case "manual":
-          return new Response("", 0, "", [], -1, false, "opaqueredirect", null);
-        case "follow":
-        default:
-          let redirectUrl = response.headers.get("Location");
-          if (redirectUrl == null) {
-            return response; // Unspecified
-          }
-          if (
-            !redirectUrl.startsWith("http://") &&
-            !redirectUrl.startsWith("https://")
-          ) {
-            redirectUrl =
-              url.split("//")[0] +
-              "//" +
-              url.split("//")[1].split("/")[0] +
-              redirectUrl; // TODO: handle relative redirection more gracefully
-          }
-          url = redirectUrl;
-          redirected = true;
-          remRedirectCount--;
+  // Aborting the signal cancels the request and, once there is a response,
+  // reads of its body.
+  const cancelHandle = init?.signal ? new CancelHandle(init.signal) : null;
+  try {
+    while (remRedirectCount) {
+      if (bodyRid === null) {
+        throw new TypeError(
+          "Can't follow a redirect with a ReadableStream request body"
+        );
+      }
+      const fetchResponse = await sendFetchReq(
+        url,
+        method,
+        headers,
+        body,
+        bodyBlob ? streamBlob(bodyBlob[blobIdSymbol]) : bodyRid,
+        init?.client?.rid,
+        cancelHandle?.rid
+      );
+      if (bodyRid !== undefined) {
+        bodyRid = null;
+      }
+
+      const response = new Response(
+        url,
+        fetchResponse.status,
+        fetchResponse.statusText,
+        fetchResponse.headers,
+        fetchResponse.bodyRid,
+        redirected,
+        "default",
+        null,
+        cancelHandle
+      );
+      if ([301, 302, 303, 307, 308].includes(response.status)) {
+        // We won't use body of received response, so close it now
+        // otherwise it will be kept in resource table.
+        close(fetchResponse.bodyRid);
+        // We're in a redirect status
+        switch ((init && init.redirect) || "follow") {
+          case "error":
+            /* I suspect that deno will probably crash if you try to use that
+               rid, which suggests to me that Response needs to be refactored */
+            cancelHandle?.close();
+            return new Response("", 0, "", [], -1, false, "error", null);
+          case "manual":
+            cancelHandle?.close();
+            return new Response(
+              "",
+              0,
+              "",
+              [],
+              -1,
+              false,
+              "opaqueredirect",
+              null
+            );
+          case "follow":
+          default:
+            let redirectUrl = response.headers.get("Location");
+            if (redirectUrl == null) {
+              return response; // Unspecified
+            }
+            if (
+              !redirectUrl.startsWith("http://") &&
+              !redirectUrl.startsWith("https://")
+            ) {
+              // TODO: handle relative redirection more gracefully
+              redirectUrl =
+                url.split("//")[0] +
+                "//" +
+                url.split("//")[1].split("/")[0] +
+                redirectUrl;
+            }
+            url = redirectUrl;
+            redirected = true;
+            remRedirectCount--;
+        }
+      } else {
+        return response;
       }
-    } else {
-      return response;
     }
+  } catch (error) {
+    cancelHandle?.close();
+    throw error;
   }
+  cancelHandle?.close();
   // Return a network error due to too many redirections
   throw notImplemented();
 }
@@ -0,0 +1,94 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as multipartOps from "../ops/multipart.ts";
+import { close } from "../ops/resources.ts";
+import { blobIdSymbol, registerBlobId } from "./blob.ts";
+import { DomFileImpl } from "./dom_file.ts";
+
+const partIdSymbol = Symbol("partId");
+
+/** A file part of a parsed `multipart/form-data` body. Its contents stay on
+ * disk until read, and are sent from there when the file is part of another
+ * form data body. */
+class DiskFileImpl extends DomFileImpl {
+  [partIdSymbol]: number;
+
+  constructor(partId: number, size: number, name: string, type: string) {
+    super([], name, { type });
+    this[partIdSymbol] = partId;
+    Object.defineProperty(this, "size", { value: size });
+    let id: number | null = null;
+    Object.defineProperty(this, blobIdSymbol, {
+      get(this: DiskFileImpl): number {
+        if (id === null) {
+          id = multipartOps.partBlob(partId);
+          registerBlobId(this, id);
+        }
+        return id;
+      },
+    });
+  }
+}
+
+/** Encodes the entries of `formData`, returning a stream resource reading
+ * the body. */
+export function encodeFormData(
+  formData: FormData,
+  boundary?: string
+): multipartOps.EncodedBody {
+  const rid = multipartOps.encoderOpen(boundary);
+  try {
+    for (const [name, value] of formData.entries()) {
+      if (typeof value === "string") {
+        multipartOps.encoderAppend(rid, { name, value });
+        continue;
+      }
+      const args = {
+        name,
+        filename: value.name,
+        contentType: value.type || "application/octet-stream",
+      };
+      if (value instanceof DiskFileImpl) {
+        multipartOps.encoderAppend(rid, {
+          ...args,
+          partId: value[partIdSymbol],
+        });
+      } else {
+        multipartOps.encoderAppend(rid, {
+          ...args,
+          blobId: (value as DomFileImpl)[blobIdSymbol],
+        });
+      }
+    }
+  } catch (error) {
+    close(rid);
+    throw error;
+  }
+  return multipartOps.encoderFinish(rid);
+}
+
+/** Parses a `multipart/form-data` body, read from the resource `rid`, which
+ * is consumed, or given as `bytes`. */
+export async function parseFormData(
+  boundary: string,
+  body: { rid: number } | { bytes: Uint8Array }
+): Promise<FormData> {
+  const entries =
+    "rid" in body
+      ? await multipartOps.parse(boundary, body.rid)
+      : await multipartOps.parse(boundary, undefined, body.bytes);
+  const formData = new FormData();
+  for (const entry of entries) {
+    if ("partId" in entry) {
+      const file = new DiskFileImpl(
+        entry.partId,
+        entry.size,
+        entry.filename,
+        entry.contentType ?? ""
+      );
+      formData.append(entry.name, file);
+    } else {
+      formData.append(entry.name, entry.value);
+    }
+  }
+  return formData;
+}
@@ -0,0 +1,17 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+let hardwareConcurrency = 1;
+
+export function setHardwareConcurrency(cpuCount: number): void {
+  hardwareConcurrency = cpuCount;
+}
+
+export class NavigatorImpl implements Navigator {
+  get hardwareConcurrency(): number {
+    return hardwareConcurrency;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "Navigator";
+  }
+}
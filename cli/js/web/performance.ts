@@ -1,9 +1,411 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
 import { now as opNow } from "../ops/timers.ts";
+import * as performanceOps from "../ops/performance.ts";
+import { DOMExceptionImpl as DOMException } from "./dom_exception.ts";
+import { cloneValue, requiredArguments } from "./util.ts";
+
+const illegalConstructorKey = Symbol("illegalConstructorKey");
+
+// Set from the response of `op_start`, so that the time origins of all
+// isolates are derived from the same clock.
+let timeOrigin = 0;
+
+export function setTimeOrigin(origin: number): void {
+  timeOrigin = origin;
+}
+
+function now(): number {
+  const res = opNow();
+  return res.seconds * 1e3 + res.subsecNanos / 1e6;
+}
+
+/** The marks and measures created with `performance.mark()` and
+ * `performance.measure()`, in the order they were created. */
+const entries: PerformanceEntryImpl[] = [];
+
+function filterEntries(name?: string, type?: string): PerformanceEntryImpl[] {
+  return entries
+    .filter(
+      (entry): boolean =>
+        (name === undefined || entry.name === name) &&
+        (type === undefined || entry.entryType === type)
+    )
+    .sort((a, b): number => a.startTime - b.startTime);
+}
+
+function clearEntries(type: "mark" | "measure", name?: string): void {
+  for (let i = entries.length - 1; i >= 0; i--) {
+    const entry = entries[i];
+    if (
+      entry.entryType === type &&
+      (name === undefined || entry.name === name)
+    ) {
+      entries.splice(i, 1);
+    }
+  }
+  performanceOps.clear(type, name);
+}
+
+function addEntry(entry: PerformanceEntryImpl): void {
+  entries.push(entry);
+  performanceOps.record({
+    name: entry.name,
+    entryType: entry.entryType as "mark" | "measure",
+    startTime: entry.startTime,
+    duration: entry.duration,
+  });
+}
+
+export class PerformanceEntryImpl implements PerformanceEntry {
+  #name: string;
+  #entryType: string;
+  #startTime: number;
+  #duration: number;
+
+  constructor(
+    key: symbol,
+    name: string,
+    entryType: string,
+    startTime: number,
+    duration: number
+  ) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+    this.#name = name;
+    this.#entryType = entryType;
+    this.#startTime = startTime;
+    this.#duration = duration;
+  }
+
+  get name(): string {
+    return this.#name;
+  }
+
+  get entryType(): string {
+    return this.#entryType;
+  }
+
+  get startTime(): number {
+    return this.#startTime;
+  }
+
+  get duration(): number {
+    return this.#duration;
+  }
+
+  toJSON(): any {
+    return {
+      name: this.#name,
+      entryType: this.#entryType,
+      startTime: this.#startTime,
+      duration: this.#duration,
+    };
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "PerformanceEntry";
+  }
+}
+
+function markTime(options: PerformanceMarkOptions | null): number {
+  const startTime = Number(options?.startTime ?? now());
+  if (startTime < 0) {
+    throw new TypeError("startTime cannot be negative.");
+  }
+  return startTime;
+}
+
+export class PerformanceMarkImpl extends PerformanceEntryImpl
+  implements PerformanceMark {
+  #detail: any;
+
+  constructor(name: string, options: PerformanceMarkOptions = {}) {
+    super(illegalConstructorKey, String(name), "mark", markTime(options), 0);
+    requiredArguments("PerformanceMark", arguments.length, 1);
+    this.#detail =
+      options?.detail === undefined ? null : cloneValue(options.detail);
+  }
+
+  get detail(): any {
+    return this.#detail;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "PerformanceMark";
+  }
+}
+
+export class PerformanceMeasureImpl extends PerformanceEntryImpl
+  implements PerformanceMeasure {
+  #detail: any;
+
+  constructor(
+    key: symbol,
+    name: string,
+    startTime: number,
+    duration: number,
+    detail: any
+  ) {
+    super(key, name, "measure", startTime, duration);
+    this.#detail = detail;
+  }
+
+  get detail(): any {
+    return this.#detail;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "PerformanceMeasure";
+  }
+}
+
+/** Types of the events counted by `EventCounts`, from the Event Timing
+ * spec. */
+const countedEventTypes = [
+  "auxclick",
+  "click",
+  "contextmenu",
+  "dblclick",
+  "mousedown",
+  "mouseenter",
+  "mouseleave",
+  "mouseout",
+  "mouseover",
+  "mouseup",
+  "pointerover",
+  "pointerenter",
+  "pointerdown",
+  "pointerup",
+  "pointercancel",
+  "pointerout",
+  "pointerleave",
+  "gotpointercapture",
+  "lostpointercapture",
+  "touchstart",
+  "touchend",
+  "touchcancel",
+  "keydown",
+  "keypress",
+  "keyup",
+  "beforeinput",
+  "input",
+  "compositionstart",
+  "compositionupdate",
+  "compositionend",
+  "dragstart",
+  "dragend",
+  "dragenter",
+  "dragleave",
+  "dragover",
+  "drop",
+];
+
+/** The number of input events dispatched by the runtime, by type. There's no
+ * user input in Deno, so all counts stay 0. */
+export class EventCountsImpl implements EventCounts {
+  #counts: Map<string, number>;
+
+  constructor(key: symbol) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+    this.#counts = new Map(
+      countedEventTypes.map((type): [string, number] => [type, 0])
+    );
+  }
+
+  get size(): number {
+    return this.#counts.size;
+  }
+
+  get(type: string): number | undefined {
+    return this.#counts.get(type);
+  }
+
+  has(type: string): boolean {
+    return this.#counts.has(type);
+  }
+
+  keys(): IterableIterator<string> {
+    return this.#counts.keys();
+  }
+
+  values(): IterableIterator<number> {
+    return this.#counts.values();
+  }
+
+  entries(): IterableIterator<[string, number]> {
+    return this.#counts.entries();
+  }
+
+  forEach(
+    callbackfn: (value: number, key: string, parent: EventCounts) => void,
+    thisArg?: any
+  ): void {
+    for (const [key, value] of this.#counts) {
+      callbackfn.call(thisArg, value, key, this);
+    }
+  }
+
+  [Symbol.iterator](): IterableIterator<[string, number]> {
+    return this.#counts.entries();
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "EventCounts";
+  }
+}
+
+/** Converts a mark name or timestamp given to `performance.measure()` to a
+ * timestamp; names refer to the most recent mark with that name. */
+function convertMarkToTimestamp(mark: string | number): number {
+  if (typeof mark === "string") {
+    const marks = filterEntries(mark, "mark");
+    if (marks.length === 0) {
+      throw new DOMException(
+        `The mark "${mark}" does not exist.`,
+        "SyntaxError"
+      );
+    }
+    return marks[marks.length - 1].startTime;
+  }
+  if (mark < 0) {
+    throw new TypeError("Mark cannot be negative.");
+  }
+  return mark;
+}
 
 export class Performance {
+  #eventCounts = new EventCountsImpl(illegalConstructorKey);
+
+  get timeOrigin(): number {
+    return timeOrigin;
+  }
+
+  get eventCounts(): EventCounts {
+    return this.#eventCounts;
+  }
+
   now(): number {
-    const res = opNow();
-    return res.seconds * 1e3 + res.subsecNanos / 1e6;
+    return now();
+  }
+
+  mark(
+    markName: string,
+    markOptions?: PerformanceMarkOptions
+  ): PerformanceMark {
+    requiredArguments("Performance.mark", arguments.length, 1);
+    const entry = new PerformanceMarkImpl(markName, markOptions);
+    addEntry(entry);
+    return entry;
+  }
+
+  measure(
+    measureName: string,
+    startOrMeasureOptions: string | PerformanceMeasureOptions = {},
+    endMark?: string
+  ): PerformanceMeasure {
+    requiredArguments("Performance.measure", arguments.length, 1);
+    let options: PerformanceMeasureOptions = {};
+    let startMark: string | undefined;
+    if (
+      startOrMeasureOptions === null ||
+      typeof startOrMeasureOptions === "object"
+    ) {
+      options = startOrMeasureOptions ?? {};
+      const { start, end, duration, detail } = options;
+      const hasOptions =
+        start !== undefined ||
+        end !== undefined ||
+        duration !== undefined ||
+        detail !== undefined;
+      if (hasOptions && endMark !== undefined) {
+        throw new TypeError("Options cannot be passed with an end mark.");
+      }
+      if (hasOptions && start === undefined && end === undefined) {
+        throw new TypeError("A start or end mark must be given.");
+      }
+      if (start !== undefined && end !== undefined && duration !== undefined) {
+        throw new TypeError(
+          "Only two of start, end and duration can be given."
+        );
+      }
+    } else {
+      startMark = String(startOrMeasureOptions);
+    }
+
+    let endTime: number;
+    if (endMark !== undefined) {
+      endTime = convertMarkToTimestamp(String(endMark));
+    } else if (options.end !== undefined) {
+      endTime = convertMarkToTimestamp(options.end);
+    } else if (options.start !== undefined && options.duration !== undefined) {
+      endTime =
+        convertMarkToTimestamp(options.start) + Number(options.duration);
+    } else {
+      endTime = now();
+    }
+
+    let startTime: number;
+    if (options.start !== undefined) {
+      startTime = convertMarkToTimestamp(options.start);
+    } else if (options.duration !== undefined && options.end !== undefined) {
+      startTime = endTime - Number(options.duration);
+    } else if (startMark !== undefined) {
+      startTime = convertMarkToTimestamp(startMark);
+    } else {
+      startTime = 0;
+    }
+
+    const entry = new PerformanceMeasureImpl(
+      illegalConstructorKey,
+      String(measureName),
+      startTime,
+      endTime - startTime,
+      options.detail === undefined ? null : cloneValue(options.detail)
+    );
+    addEntry(entry);
+    return entry;
+  }
+
+  getEntries(): PerformanceEntryList {
+    return filterEntries();
+  }
+
+  getEntriesByName(name: string, type?: string): PerformanceEntryList {
+    requiredArguments("Performance.getEntriesByName", arguments.length, 1);
+    return filterEntries(
+      String(name),
+      type === undefined ? undefined : String(type)
+    );
+  }
+
+  getEntriesByType(type: string): PerformanceEntryList {
+    requiredArguments("Performance.getEntriesByType", arguments.length, 1);
+    return filterEntries(undefined, String(type));
+  }
+
+  clearMarks(markName?: string): void {
+    clearEntries(
+      "mark",
+      markName === undefined ? undefined : String(markName)
+    );
+  }
+
+  clearMeasures(measureName?: string): void {
+    clearEntries(
+      "measure",
+      measureName === undefined ? undefined : String(measureName)
+    );
+  }
+
+  toJSON(): any {
+    return { timeOrigin };
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "Performance";
   }
 }
@@ -0,0 +1,53 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+/* eslint-disable @typescript-eslint/no-explicit-any */
+import { EventImpl as Event } from "./event.ts";
+import { defineEnumerableProps, requiredArguments } from "./util.ts";
+
+export interface PromiseRejectionEventInit extends EventInit {
+  promise: Promise<any>;
+  reason?: any;
+}
+
+export class PromiseRejectionEventImpl extends Event {
+  #promise: Promise<any>;
+  #reason: any;
+
+  constructor(type: string, eventInitDict: PromiseRejectionEventInit) {
+    super(type, eventInitDict);
+    requiredArguments("PromiseRejectionEvent", arguments.length, 2);
+    if (!(eventInitDict?.promise instanceof Promise)) {
+      throw new TypeError("Expected a promise");
+    }
+    this.#promise = eventInitDict.promise;
+    this.#reason = eventInitDict.reason;
+  }
+
+  get promise(): Promise<any> {
+    return this.#promise;
+  }
+
+  get reason(): any {
+    return this.#reason;
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "PromiseRejectionEvent";
+  }
+}
+
+defineEnumerableProps(PromiseRejectionEventImpl, ["promise", "reason"]);
+
+/** Dispatches an `unhandledrejection` event at the global scope for a promise
+ * rejected without a handler. Returns whether it was handled, by canceling
+ * the event. Registered with `Deno.core.setPromiseRejectCallback()`. */
+export function handleUnhandledRejection(
+  promise: Promise<any>,
+  reason: any
+): boolean {
+  const event = new PromiseRejectionEventImpl("unhandledrejection", {
+    cancelable: true,
+    promise,
+    reason,
+  });
+  return !globalThis.dispatchEvent(event);
+}
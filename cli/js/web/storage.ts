@@ -0,0 +1,111 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import * as webStorage from "../ops/web_storage.ts";
+
+const illegalConstructorKey = Symbol("illegalConstructorKey");
+const persistentKey = Symbol("persistent");
+
+/** A storage area of `localStorage` (persistent) or `sessionStorage`. Items
+ * are kept by the privileged side, per origin. */
+export class StorageImpl {
+  // A symbol rather than a private field, since methods are called on the
+  // proxy returned by `createStorage()`.
+  [persistentKey]: boolean;
+
+  constructor(key: symbol, persistent: boolean) {
+    if (key !== illegalConstructorKey) {
+      throw new TypeError("Illegal constructor.");
+    }
+    this[persistentKey] = persistent;
+  }
+
+  get length(): number {
+    return webStorage.length(this[persistentKey]);
+  }
+
+  key(index: number): string | null {
+    return webStorage.key(this[persistentKey], Number(index));
+  }
+
+  getItem(key: string): string | null {
+    return webStorage.getItem(this[persistentKey], String(key));
+  }
+
+  setItem(key: string, value: string): void {
+    webStorage.setItem(this[persistentKey], String(key), String(value));
+  }
+
+  removeItem(key: string): void {
+    webStorage.removeItem(this[persistentKey], String(key));
+  }
+
+  clear(): void {
+    webStorage.clear(this[persistentKey]);
+  }
+
+  get [Symbol.toStringTag](): string {
+    return "Storage";
+  }
+}
+
+/** A storage area whose items can also be used as properties, like
+ * `localStorage.name = "value"`. */
+function createStorage(persistent: boolean): Storage {
+  const storage = new StorageImpl(illegalConstructorKey, persistent);
+  const isOwn = (target: StorageImpl, key: PropertyKey): boolean =>
+    typeof key === "symbol" || key in target;
+  return new Proxy(storage, {
+    get(target, key, receiver): unknown {
+      if (isOwn(target, key)) {
+        return Reflect.get(target, key, receiver);
+      }
+      return target.getItem(key as string) ?? undefined;
+    },
+    set(target, key, value, receiver): boolean {
+      if (typeof key === "symbol") {
+        return Reflect.set(target, key, value, receiver);
+      }
+      target.setItem(key as string, value);
+      return true;
+    },
+    deleteProperty(target, key): boolean {
+      if (typeof key === "symbol") {
+        return Reflect.deleteProperty(target, key);
+      }
+      target.removeItem(key as string);
+      return true;
+    },
+    has(target, key): boolean {
+      return isOwn(target, key) || target.getItem(key as string) !== null;
+    },
+    ownKeys(target): PropertyKey[] {
+      return webStorage.keys(target[persistentKey]);
+    },
+    getOwnPropertyDescriptor(target, key): PropertyDescriptor | undefined {
+      if (isOwn(target, key)) {
+        return Reflect.getOwnPropertyDescriptor(target, key);
+      }
+      const value = target.getItem(key as string);
+      if (value === null) {
+        return undefined;
+      }
+      return { value, writable: true, enumerable: true, configurable: true };
+    },
+  }) as Storage;
+}
+
+let localStorage: Storage | undefined;
+let sessionStorage: Storage | undefined;
+
+export function getLocalStorage(): Storage {
+  if (!localStorage) {
+    localStorage = createStorage(true);
+  }
+  return localStorage;
+}
+
+export function getSessionStorage(): Storage {
+  if (!sessionStorage) {
+    sessionStorage = createStorage(false);
+  }
+  return sessionStorage;
+}
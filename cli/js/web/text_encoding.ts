@@ -24,28 +24,16 @@
 // OTHER DEALINGS IN THE SOFTWARE.
 
 import * as base64 from "./base64.ts";
-import { decodeUtf8 } from "./decode_utf8.ts";
 import { core } from "../core.ts";
+import * as encodingOps from "../ops/encoding.ts";
+import { close } from "../ops/resources.ts";
 
-const CONTINUE = null;
 const END_OF_STREAM = -1;
-const FINISHED = -1;
-
-function decoderError(fatal: boolean): number | never {
-  if (fatal) {
-    throw new TypeError("Decoder error.");
-  }
-  return 0xfffd; // default code point
-}
 
 function inRange(a: number, min: number, max: number): boolean {
   return min <= a && a <= max;
 }
 
-function isASCIIByte(a: number): boolean {
-  return inRange(a, 0x00, 0x7f);
-}
-
 function stringToCodePoints(input: string): number[] {
   const u: number[] = [];
   for (const c of input) {
@@ -134,238 +122,10 @@ export function btoa(s: string): string {
   return result;
 }
 
-interface DecoderOptions {
-  fatal?: boolean;
-  ignoreBOM?: boolean;
-}
-
-interface Decoder {
-  handler(stream: Stream, byte: number): number | null;
-}
-
 interface Encoder {
   handler(codePoint: number): "finished" | number[];
 }
 
-class SingleByteDecoder implements Decoder {
-  #index: number[];
-  #fatal: boolean;
-
-  constructor(
-    index: number[],
-    { ignoreBOM = false, fatal = false }: DecoderOptions = {}
-  ) {
-    if (ignoreBOM) {
-      throw new TypeError("Ignoring the BOM is available only with utf-8.");
-    }
-    this.#fatal = fatal;
-    this.#index = index;
-  }
-  handler(_stream: Stream, byte: number): number {
-    if (byte === END_OF_STREAM) {
-      return FINISHED;
-    }
-    if (isASCIIByte(byte)) {
-      return byte;
-    }
-    const codePoint = this.#index[byte - 0x80];
-
-    if (codePoint == null) {
-      return decoderError(this.#fatal);
-    }
-
-    return codePoint;
-  }
-}
-
-// The encodingMap is a hash of labels that are indexed by the conical
-// encoding.
-const encodingMap: { [key: string]: string[] } = {
-  "windows-1252": [
-    "ansi_x3.4-1968",
-    "ascii",
-    "cp1252",
-    "cp819",
-    "csisolatin1",
-    "ibm819",
-    "iso-8859-1",
-    "iso-ir-100",
-    "iso8859-1",
-    "iso88591",
-    "iso_8859-1",
-    "iso_8859-1:1987",
-    "l1",
-    "latin1",
-    "us-ascii",
-    "windows-1252",
-    "x-cp1252",
-  ],
-  "utf-8": ["unicode-1-1-utf-8", "utf-8", "utf8"],
-};
-// We convert these into a Map where every label resolves to its canonical
-// encoding type.
-const encodings = new Map<string, string>();
-for (const key of Object.keys(encodingMap)) {
-  const labels = encodingMap[key];
-  for (const label of labels) {
-    encodings.set(label, key);
-  }
-}
-
-// A map of functions that return new instances of a decoder indexed by the
-// encoding type.
-const decoders = new Map<string, (options: DecoderOptions) => Decoder>();
-
-// Single byte decoders are an array of code point lookups
-const encodingIndexes = new Map<string, number[]>();
-// prettier-ignore
-encodingIndexes.set("windows-1252", [
-  8364,
-  129,
-  8218,
-  402,
-  8222,
-  8230,
-  8224,
-  8225,
-  710,
-  8240,
-  352,
-  8249,
-  338,
-  141,
-  381,
-  143,
-  144,
-  8216,
-  8217,
-  8220,
-  8221,
-  8226,
-  8211,
-  8212,
-  732,
-  8482,
-  353,
-  8250,
-  339,
-  157,
-  382,
-  376,
-  160,
-  161,
-  162,
-  163,
-  164,
-  165,
-  166,
-  167,
-  168,
-  169,
-  170,
-  171,
-  172,
-  173,
-  174,
-  175,
-  176,
-  177,
-  178,
-  179,
-  180,
-  181,
-  182,
-  183,
-  184,
-  185,
-  186,
-  187,
-  188,
-  189,
-  190,
-  191,
-  192,
-  193,
-  194,
-  195,
-  196,
-  197,
-  198,
-  199,
-  200,
-  201,
-  202,
-  203,
-  204,
-  205,
-  206,
-  207,
-  208,
-  209,
-  210,
-  211,
-  212,
-  213,
-  214,
-  215,
-  216,
-  217,
-  218,
-  219,
-  220,
-  221,
-  222,
-  223,
-  224,
-  225,
-  226,
-  227,
-  228,
-  229,
-  230,
-  231,
-  232,
-  233,
-  234,
-  235,
-  236,
-  237,
-  238,
-  239,
-  240,
-  241,
-  242,
-  243,
-  244,
-  245,
-  246,
-  247,
-  248,
-  249,
-  250,
-  251,
-  252,
-  253,
-  254,
-  255,
-]);
-for (const [key, index] of encodingIndexes) {
-  decoders.set(
-    key,
-    (options: DecoderOptions): SingleByteDecoder => {
-      return new SingleByteDecoder(index, options);
-    }
-  );
-}
-
-function codePointsToString(codePoints: number[]): string {
-  let s = "";
-  for (const cp of codePoints) {
-    s += String.fromCodePoint(cp);
-  }
-  return s;
-}
-
 class Stream {
   #tokens: number[];
   constructor(tokens: number[] | Uint8Array) {
@@ -403,7 +163,7 @@ class Stream {
 }
 
 export interface TextDecodeOptions {
-  stream?: false;
+  stream?: boolean;
 }
 
 export interface TextDecoderOptions {
@@ -418,8 +178,23 @@ function isEitherArrayBuffer(x: any): x is EitherArrayBuffer {
   return x instanceof SharedArrayBuffer || x instanceof ArrayBuffer;
 }
 
+// The labels of the default encoding are resolved without an op, so that
+// decoders can be created while the snapshot is built.
+const utf8Labels = ["unicode-1-1-utf-8", "utf-8", "utf8"];
+
+function hasUtf8BOM(bytes: Uint8Array): boolean {
+  return (
+    bytes.length >= 3 &&
+    bytes[0] === 0xef &&
+    bytes[1] === 0xbb &&
+    bytes[2] === 0xbf
+  );
+}
+
 export class TextDecoder {
   #encoding: string;
+  // The decoder holding partial sequences between `stream: true` calls.
+  #rid: number | null = null;
 
   get encoding(): string {
     return this.#encoding;
@@ -435,15 +210,14 @@ export class TextDecoder {
       this.fatal = true;
     }
     label = String(label).trim().toLowerCase();
-    const encoding = encodings.get(label);
+    const encoding = utf8Labels.includes(label)
+      ? "utf-8"
+      : encodingOps.normalizeLabel(label);
     if (!encoding) {
       throw new RangeError(
         `The encoding label provided ('${label}') is invalid.`
       );
     }
-    if (!decoders.has(encoding) && encoding !== "utf-8") {
-      throw new TypeError(`Internal decoder ('${encoding}') not found.`);
-    }
     this.#encoding = encoding;
   }
 
@@ -451,10 +225,6 @@ export class TextDecoder {
     input?: BufferSource,
     options: TextDecodeOptions = { stream: false }
   ): string {
-    if (options.stream) {
-      throw new TypeError("Stream not supported.");
-    }
-
     let bytes: Uint8Array;
     if (input instanceof Uint8Array) {
       bytes = input;
@@ -470,44 +240,39 @@ export class TextDecoder {
       bytes = new Uint8Array(0);
     }
 
-    // For simple utf-8 decoding "Deno.core.decode" can be used for performance
-    if (
-      this.#encoding === "utf-8" &&
-      this.fatal === false &&
-      this.ignoreBOM === false
-    ) {
-      return core.decode(bytes);
-    }
-
-    // For performance reasons we utilise a highly optimised decoder instead of
-    // the general decoder.
-    if (this.#encoding === "utf-8") {
-      return decodeUtf8(bytes, this.fatal, this.ignoreBOM);
-    }
-
-    const decoder = decoders.get(this.#encoding)!({
+    const stream = Boolean(options.stream);
+    const decoderOptions = {
+      encoding: this.#encoding,
       fatal: this.fatal,
       ignoreBOM: this.ignoreBOM,
-    });
-    const inputStream = new Stream(bytes);
-    const output: number[] = [];
-
-    while (true) {
-      const result = decoder.handler(inputStream, inputStream.read());
-      if (result === FINISHED) {
-        break;
-      }
+    };
 
-      if (result !== CONTINUE) {
-        output.push(result);
+    if (!stream && this.#rid === null) {
+      // For simple utf-8 decoding "Deno.core.decode" can be used for
+      // performance.
+      if (
+        this.#encoding === "utf-8" &&
+        this.fatal === false &&
+        this.ignoreBOM === false
+      ) {
+        return core.decode(hasUtf8BOM(bytes) ? bytes.subarray(3) : bytes);
       }
+      return encodingOps.decodeSingle(bytes, decoderOptions);
     }
 
-    if (output.length > 0 && output[0] === 0xfeff) {
-      output.shift();
+    if (this.#rid === null) {
+      this.#rid = encodingOps.createDecoder(decoderOptions);
+    }
+    const rid = this.#rid;
+    try {
+      return encodingOps.decode(rid, bytes, stream);
+    } finally {
+      // The last chunk ends the stream; the next call starts a new one.
+      if (!stream) {
+        this.#rid = null;
+        close(rid);
+      }
     }
-
-    return codePointsToString(output);
   }
 
   get [Symbol.toStringTag](): string {
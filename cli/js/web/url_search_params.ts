@@ -1,5 +1,10 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 import { parts } from "./url.ts";
+import {
+  parseUrl,
+  parseUrlEncoded,
+  stringifyUrlEncoded,
+} from "../ops/url.ts";
 import { isIterable, requiredArguments } from "./util.ts";
 
 /** @internal */
@@ -44,16 +49,7 @@ export class URLSearchParamsImpl implements URLSearchParams {
       init = init.slice(1);
     }
 
-    for (const pair of init.split("&")) {
-      // Empty params are ignored
-      if (pair.length === 0) {
-        continue;
-      }
-      const position = pair.indexOf("=");
-      const name = pair.slice(0, position === -1 ? pair.length : position);
-      const value = pair.slice(name.length + 1);
-      this.#append(decodeURIComponent(name), decodeURIComponent(value));
-    }
+    this.#params = parseUrlEncoded(init);
   };
 
   #handleArrayInitialization = (
@@ -77,12 +73,8 @@ export class URLSearchParamsImpl implements URLSearchParams {
       return;
     }
 
-    let query: string | null = this.toString();
-    if (query === "") {
-      query = null;
-    }
-
-    parts.get(url)!.query = query;
+    // An empty query removes the "?" from the URL.
+    parts.set(url, parseUrl(url.href, undefined, { search: this.toString() }));
   };
 
   #append = (name: string, value: string): void => {
@@ -215,11 +207,6 @@ export class URLSearchParamsImpl implements URLSearchParams {
   }
 
   toString(): string {
-    return this.#params
-      .map(
-        (tuple) =>
-          `${encodeURIComponent(tuple[0])}=${encodeURIComponent(tuple[1])}`
-      )
-      .join("&");
+    return stringifyUrlEncoded(this.#params);
   }
 }
@@ -5,14 +5,15 @@ import {
   hostTerminateWorker,
   hostPostMessage,
   hostGetMessage,
+  PermissionsSubset,
 } from "../ops/worker_host.ts";
 import { log } from "../util.ts";
 import { TextDecoder, TextEncoder } from "./text_encoding.ts";
-/*
-import { blobURLMap } from "./web/url.ts";
-*/
+import { blobFromObjectUrl, bytesSymbol } from "./blob.ts";
+import { toByteArray } from "./base64.ts";
 import { EventImpl as Event } from "./event.ts";
 import { EventTargetImpl as EventTarget } from "./event_target.ts";
+import { ErrorEventImpl as ErrorEvent } from "./error_event.ts";
 
 const encoder = new TextEncoder();
 const decoder = new TextDecoder();
@@ -41,36 +42,6 @@ export class MessageEvent extends Event {
   }
 }
 
-export interface ErrorEventInit extends EventInit {
-  message?: string;
-  filename?: string;
-  lineno?: number;
-  colno?: number;
-  error?: any;
-}
-
-export class ErrorEvent extends Event {
-  readonly message: string;
-  readonly filename: string;
-  readonly lineno: number;
-  readonly colno: number;
-  readonly error: any;
-
-  constructor(type: string, eventInitDict?: ErrorEventInit) {
-    super(type, {
-      bubbles: eventInitDict?.bubbles ?? false,
-      cancelable: eventInitDict?.cancelable ?? false,
-      composed: eventInitDict?.composed ?? false,
-    });
-
-    this.message = eventInitDict?.message ?? "";
-    this.filename = eventInitDict?.filename ?? "";
-    this.lineno = eventInitDict?.lineno ?? 0;
-    this.colno = eventInitDict?.colno ?? 0;
-    this.error = eventInitDict?.error ?? null;
-  }
-}
-
 function encodeMessage(data: any): Uint8Array {
   const dataJson = JSON.stringify(data);
   return encoder.encode(dataJson);
@@ -78,6 +49,10 @@ function encodeMessage(data: any): Uint8Array {
 
 function decodeMessage(dataIntArray: Uint8Array): any {
   const dataJson = decoder.decode(dataIntArray);
+  // `JSON.stringify(undefined)` produces no output
+  if (dataJson === "") {
+    return undefined;
+  }
   return JSON.parse(dataJson);
 }
 
@@ -94,6 +69,37 @@ interface WorkerHostMessage {
   error?: WorkerHostError;
 }
 
+// Decodes body of `data:` URL as described in
+// https://fetch.spec.whatwg.org/#data-url-processor
+function decodeDataUrl(specifier: string): string {
+  const commaIndex = specifier.indexOf(",");
+  if (commaIndex === -1) {
+    throw new TypeError(`Invalid data URL: "${specifier}"`);
+  }
+  const mediaType = specifier.slice("data:".length, commaIndex);
+  const body = specifier.slice(commaIndex + 1);
+  if (/;\s*base64\s*$/i.test(mediaType)) {
+    return decoder.decode(toByteArray(body));
+  }
+  return decodeURIComponent(body);
+}
+
+function getInlineSourceCode(specifier: string): string | undefined {
+  if (specifier.startsWith("blob:")) {
+    const b = blobFromObjectUrl(specifier);
+    if (!b) {
+      throw new Error("No Blob associated with the given URL is found");
+    }
+    return decoder.decode(b[bytesSymbol]);
+  }
+
+  if (specifier.startsWith("data:")) {
+    return decodeDataUrl(specifier);
+  }
+
+  return undefined;
+}
+
 export interface Worker {
   onerror?: (e: ErrorEvent) => void;
   onmessage?: (e: MessageEvent) => void;
@@ -106,6 +112,9 @@ export interface WorkerOptions {
   type?: "classic" | "module";
   name?: string;
   deno?: boolean;
+  // Not part of the public API, used by `Deno.test()` to run tests with
+  // reduced permissions.
+  permissions?: PermissionsSubset;
 }
 
 export class WorkerImpl extends EventTarget implements Worker {
@@ -121,31 +130,16 @@ export class WorkerImpl extends EventTarget implements Worker {
     super();
     const { type = "classic", name = "unknown" } = options ?? {};
 
-    if (type !== "module") {
-      throw new Error(
-        'Not yet implemented: only "module" type workers are supported'
+    if (type !== "classic" && type !== "module") {
+      throw new TypeError(
+        `Failed to construct 'Worker': The provided value '${type}' is not a valid enum value of type WorkerType.`
       );
     }
 
     this.#name = name;
-    const hasSourceCode = false;
-    const sourceCode = decoder.decode(new Uint8Array());
-
-    /* TODO(bartlomieju):
-    // Handle blob URL.
-    if (specifier.startsWith("blob:")) {
-      hasSourceCode = true;
-      const b = blobURLMap.get(specifier);
-      if (!b) {
-        throw new Error("No Blob associated with the given URL is found");
-      }
-      const blobBytes = blobBytesWeakMap.get(b!);
-      if (!blobBytes) {
-        throw new Error("Invalid Blob");
-      }
-      sourceCode = blobBytes!;
-    }
-    */
+    const maybeSourceCode = getInlineSourceCode(specifier);
+    const hasSourceCode = maybeSourceCode !== undefined;
+    const sourceCode = maybeSourceCode ?? "";
 
     const useDenoNamespace = options ? !!options.deno : false;
 
@@ -154,7 +148,9 @@ export class WorkerImpl extends EventTarget implements Worker {
       hasSourceCode,
       sourceCode,
       useDenoNamespace,
-      options?.name
+      type,
+      options?.name,
+      options?.permissions
     );
     this.#id = id;
     this.#poll();
@@ -172,6 +168,7 @@ export class WorkerImpl extends EventTarget implements Worker {
       if (this.onmessageerror) {
         this.onmessageerror(msgErrorEvent);
       }
+      this.dispatchEvent(msgErrorEvent);
       return;
     }
 
@@ -187,6 +184,9 @@ export class WorkerImpl extends EventTarget implements Worker {
     this.dispatchEvent(msgEvent);
   };
 
+  // Returns `true` if one of the listeners called `preventDefault()`
+  // on the event, in which case error should not be propagated to
+  // the host.
   #handleError = (e: WorkerHostError): boolean => {
     const event = new ErrorEvent("error", {
       cancelable: true,
@@ -197,17 +197,12 @@ export class WorkerImpl extends EventTarget implements Worker {
       error: null,
     });
 
-    let handled = false;
     if (this.onerror) {
       this.onerror(event);
     }
 
     this.dispatchEvent(event);
-    if (event.defaultPrevented) {
-      handled = true;
-    }
-
-    return handled;
+    return event.defaultPrevented;
   };
 
   #poll = async (): Promise<void> => {
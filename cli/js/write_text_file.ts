@@ -1,18 +1,23 @@
-import { open, openSync } from "./files.ts";
-import { writeAll, writeAllSync } from "./buffer.ts";
+import {
+  writeFile,
+  writeFileSync,
+  WriteFileOptions,
+} from "./ops/fs/write_file.ts";
 
-export function writeTextFileSync(path: string, data: string): void {
-  const file = openSync(path, { write: true, create: true, truncate: true });
-  const enc = new TextEncoder();
-  const contents = enc.encode(data);
-  writeAllSync(file, contents);
-  file.close();
+export function writeTextFileSync(
+  path: string,
+  data: string,
+  options: WriteFileOptions = {}
+): void {
+  const contents = new TextEncoder().encode(data);
+  writeFileSync(path, contents, options);
 }
 
-export async function writeTextFile(path: string, data: string): Promise<void> {
-  const file = await open(path, { write: true, create: true, truncate: true });
-  const enc = new TextEncoder();
-  const contents = enc.encode(data);
-  await writeAll(file, contents);
-  file.close();
+export function writeTextFile(
+  path: string,
+  data: string,
+  options: WriteFileOptions = {}
+): Promise<void> {
+  const contents = new TextEncoder().encode(data);
+  return writeFile(path, contents, options);
 }
@@ -0,0 +1,564 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! The key-value database behind `Deno.openKv()`, kept in a SQLite file.
+//!
+//! Keys are arrays of parts (bytes, strings, numbers and booleans), encoded
+//! to bytes that sort the way the keys do, so ranges of keys can be listed
+//! in order. Values are JSON. Every commit gets a versionstamp, greater than
+//! the ones before it, that atomic operations can check keys against.
+
+use crate::op_error::OpError;
+use rusqlite::params;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+use rusqlite::TransactionBehavior;
+use rusqlite::NO_PARAMS;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+use tokio::sync::watch;
+
+const BYTES: u8 = 0x01;
+const STRING: u8 = 0x02;
+const NUMBER: u8 = 0x21;
+const FALSE: u8 = 0x26;
+const TRUE: u8 = 0x27;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum KeyPart {
+  Bytes(Vec<u8>),
+  String(String),
+  Number(f64),
+  Boolean(bool),
+}
+
+/// Writes `bytes` so that no encoded part is a prefix of another: zero
+/// bytes are escaped as `00 ff`, and `00` ends the part.
+fn encode_escaped(out: &mut Vec<u8>, bytes: &[u8]) {
+  for byte in bytes {
+    out.push(*byte);
+    if *byte == 0 {
+      out.push(0xff);
+    }
+  }
+  out.push(0);
+}
+
+pub fn encode_key(key: &[KeyPart]) -> Vec<u8> {
+  let mut out = vec![];
+  for part in key {
+    match part {
+      KeyPart::Bytes(bytes) => {
+        out.push(BYTES);
+        encode_escaped(&mut out, bytes);
+      }
+      KeyPart::String(s) => {
+        out.push(STRING);
+        encode_escaped(&mut out, s.as_bytes());
+      }
+      KeyPart::Number(n) => {
+        out.push(NUMBER);
+        // `-0` is the same key as `0`.
+        let bits = if *n == 0.0 { 0 } else { n.to_bits() };
+        // Flips the sign bit of positive numbers and every bit of negative
+        // ones, so the bytes compare like the numbers.
+        let bits = if bits >> 63 == 1 {
+          !bits
+        } else {
+          bits ^ (1 << 63)
+        };
+        out.extend_from_slice(&bits.to_be_bytes());
+      }
+      KeyPart::Boolean(false) => out.push(FALSE),
+      KeyPart::Boolean(true) => out.push(TRUE),
+    }
+  }
+  out
+}
+
+fn decode_escaped(bytes: &[u8], i: &mut usize) -> Result<Vec<u8>, OpError> {
+  let mut out = vec![];
+  loop {
+    match bytes.get(*i) {
+      Some(0) if bytes.get(*i + 1) == Some(&0xff) => {
+        out.push(0);
+        *i += 2;
+      }
+      Some(0) => {
+        *i += 1;
+        return Ok(out);
+      }
+      Some(byte) => {
+        out.push(*byte);
+        *i += 1;
+      }
+      None => return Err(invalid_key()),
+    }
+  }
+}
+
+fn invalid_key() -> OpError {
+  OpError::invalid_data("Invalid key in the database".to_string())
+}
+
+pub fn decode_key(bytes: &[u8]) -> Result<Vec<KeyPart>, OpError> {
+  let mut key = vec![];
+  let mut i = 0;
+  while i < bytes.len() {
+    let tag = bytes[i];
+    i += 1;
+    let part = match tag {
+      BYTES => KeyPart::Bytes(decode_escaped(bytes, &mut i)?),
+      STRING => {
+        let s = String::from_utf8(decode_escaped(bytes, &mut i)?)
+          .map_err(|_| invalid_key())?;
+        KeyPart::String(s)
+      }
+      NUMBER => {
+        if bytes.len() < i + 8 {
+          return Err(invalid_key());
+        }
+        let mut be_bytes = [0; 8];
+        be_bytes.copy_from_slice(&bytes[i..i + 8]);
+        i += 8;
+        let bits = u64::from_be_bytes(be_bytes);
+        let bits = if bits >> 63 == 1 {
+          bits ^ (1 << 63)
+        } else {
+          !bits
+        };
+        KeyPart::Number(f64::from_bits(bits))
+      }
+      FALSE => KeyPart::Boolean(false),
+      TRUE => KeyPart::Boolean(true),
+      _ => return Err(invalid_key()),
+    };
+    key.push(part);
+  }
+  Ok(key)
+}
+
+fn format_versionstamp(version: i64) -> String {
+  format!("{:020x}", version)
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Entry {
+  pub key: Vec<KeyPart>,
+  pub value: Value,
+  pub versionstamp: String,
+}
+
+/// Keys to list: those starting with `prefix` (but not `prefix` itself),
+/// from `start` (inclusive) and before `end`. At least a prefix, or both a
+/// start and an end, are given.
+#[derive(Default, Deserialize)]
+pub struct Selector {
+  pub prefix: Option<Vec<KeyPart>>,
+  pub start: Option<Vec<KeyPart>>,
+  pub end: Option<Vec<KeyPart>>,
+}
+
+impl Selector {
+  /// The range of encoded keys, as an inclusive start and exclusive end.
+  fn range(&self) -> Result<(Vec<u8>, Vec<u8>), OpError> {
+    let prefix = self.prefix.as_ref().map(|prefix| encode_key(prefix));
+    let start = match (&self.start, &prefix) {
+      (Some(start), _) => encode_key(start),
+      // No key is `prefix` followed by a zero byte, and every longer key
+      // comes after it.
+      (None, Some(prefix)) => [prefix.as_slice(), &[0]].concat(),
+      (None, None) => return Err(invalid_selector()),
+    };
+    let end = match (&self.end, &prefix) {
+      (Some(end), _) => encode_key(end),
+      // Every tag byte is below `ff`.
+      (None, Some(prefix)) => [prefix.as_slice(), &[0xff]].concat(),
+      (None, None) => return Err(invalid_selector()),
+    };
+    if let Some(prefix) = &prefix {
+      if !start.starts_with(prefix) || !end.starts_with(prefix) {
+        return Err(OpError::type_error(
+          "Start and end keys must be within the prefix".to_string(),
+        ));
+      }
+    }
+    Ok((start, end))
+  }
+}
+
+fn invalid_selector() -> OpError {
+  OpError::type_error(
+    "A selector needs a prefix, or both a start and an end".to_string(),
+  )
+}
+
+fn decode_cursor(cursor: &str) -> Result<Vec<u8>, OpError> {
+  base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+    .map_err(|_| OpError::type_error("Invalid cursor".to_string()))
+}
+
+#[derive(Deserialize)]
+pub struct Check {
+  pub key: Vec<KeyPart>,
+  /// The versionstamp the key must have, or `None` if it mustn't exist.
+  pub versionstamp: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Mutation {
+  Set { key: Vec<KeyPart>, value: Value },
+  Delete { key: Vec<KeyPart> },
+}
+
+pub struct KvStore {
+  conn: Mutex<Connection>,
+  /// Bumped after every commit, to wake up watchers.
+  commits: watch::Sender<u64>,
+  commits_rx: watch::Receiver<u64>,
+}
+
+impl KvStore {
+  pub fn open(path: Option<&Path>) -> Result<Self, OpError> {
+    let conn = match path {
+      Some(path) => {
+        if let Some(dir) = path.parent() {
+          std::fs::create_dir_all(dir)?;
+        }
+        Connection::open(path)?
+      }
+      None => Connection::open_in_memory()?,
+    };
+    // Other processes may be writing to the same file.
+    conn.busy_timeout(Duration::from_secs(5))?;
+    conn.execute_batch(
+      "PRAGMA journal_mode = WAL;
+      CREATE TABLE IF NOT EXISTS kv (
+        key BLOB PRIMARY KEY,
+        value TEXT NOT NULL,
+        version INTEGER NOT NULL
+      );
+      CREATE TABLE IF NOT EXISTS kv_version (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        version INTEGER NOT NULL
+      );
+      INSERT OR IGNORE INTO kv_version (id, version) VALUES (0, 0);",
+    )?;
+    let (commits, commits_rx) = watch::channel(0);
+    Ok(KvStore {
+      conn: Mutex::new(conn),
+      commits,
+      commits_rx,
+    })
+  }
+
+  /// The entries of `keys`, `None` for the keys that don't exist.
+  pub fn get(
+    &self,
+    keys: &[Vec<KeyPart>],
+  ) -> Result<Vec<Option<Entry>>, OpError> {
+    let conn = self.conn.lock().unwrap();
+    let mut statement =
+      conn.prepare_cached("SELECT value, version FROM kv WHERE key = ?")?;
+    let mut entries = vec![];
+    for key in keys {
+      let row = statement
+        .query_row(params![encode_key(key)], |row| {
+          Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })
+        .optional()?;
+      let entry = match row {
+        Some((value, version)) => Some(Entry {
+          key: key.clone(),
+          value: serde_json::from_str(&value)?,
+          versionstamp: format_versionstamp(version),
+        }),
+        None => None,
+      };
+      entries.push(entry);
+    }
+    Ok(entries)
+  }
+
+  /// Up to `limit` entries of `selector`, in key order or in reverse,
+  /// with the cursor to continue from after each of them. The listing
+  /// continues after `cursor` if given.
+  pub fn list(
+    &self,
+    selector: &Selector,
+    limit: u32,
+    cursor: Option<&str>,
+    reverse: bool,
+  ) -> Result<Vec<(Entry, String)>, OpError> {
+    let (mut start, mut end) = selector.range()?;
+    if let Some(cursor) = cursor {
+      let cursor = decode_cursor(cursor)?;
+      if reverse {
+        end = cursor;
+      } else {
+        start = [cursor.as_slice(), &[0]].concat();
+      }
+    }
+    let conn = self.conn.lock().unwrap();
+    let sql = format!(
+      "SELECT key, value, version FROM kv WHERE key >= ? AND key < ?
+      ORDER BY key {} LIMIT ?",
+      if reverse { "DESC" } else { "ASC" }
+    );
+    let mut statement = conn.prepare_cached(&sql)?;
+    let rows = statement.query_map(params![start, end, limit], |row| {
+      Ok((
+        row.get::<_, Vec<u8>>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, i64>(2)?,
+      ))
+    })?;
+    let mut entries = vec![];
+    for row in rows {
+      let (key, value, version) = row?;
+      // Where the listing continues from after this entry.
+      let cursor = base64::encode_config(&key, base64::URL_SAFE_NO_PAD);
+      let entry = Entry {
+        key: decode_key(&key)?,
+        value: serde_json::from_str(&value)?,
+        versionstamp: format_versionstamp(version),
+      };
+      entries.push((entry, cursor));
+    }
+    Ok(entries)
+  }
+
+  /// Applies `mutations` in one transaction if every check passes, and
+  /// returns the versionstamp of the commit. Returns `None`, changing
+  /// nothing, if a check fails.
+  pub fn atomic(
+    &self,
+    checks: &[Check],
+    mutations: &[Mutation],
+  ) -> Result<Option<String>, OpError> {
+    let mut conn = self.conn.lock().unwrap();
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    for check in checks {
+      let version: Option<i64> = tx
+        .query_row(
+          "SELECT version FROM kv WHERE key = ?",
+          params![encode_key(&check.key)],
+          |row| row.get(0),
+        )
+        .optional()?;
+      if version.map(format_versionstamp) != check.versionstamp {
+        return Ok(None);
+      }
+    }
+    tx.execute(
+      "UPDATE kv_version SET version = version + 1 WHERE id = 0",
+      NO_PARAMS,
+    )?;
+    let version: i64 = tx.query_row(
+      "SELECT version FROM kv_version WHERE id = 0",
+      NO_PARAMS,
+      |row| row.get(0),
+    )?;
+    for mutation in mutations {
+      match mutation {
+        Mutation::Set { key, value } => {
+          if key.is_empty() {
+            return Err(OpError::type_error(
+              "Key must not be empty".to_string(),
+            ));
+          }
+          tx.execute(
+            "INSERT OR REPLACE INTO kv (key, value, version) VALUES (?, ?, ?)",
+            params![encode_key(key), value.to_string(), version],
+          )?;
+        }
+        Mutation::Delete { key } => {
+          tx.execute("DELETE FROM kv WHERE key = ?", params![encode_key(key)])?;
+        }
+      }
+    }
+    tx.commit()?;
+    let _ = self.commits.broadcast(version as u64);
+    Ok(Some(format_versionstamp(version)))
+  }
+
+  /// A receiver that is notified after every commit to this store.
+  pub fn subscribe(&self) -> watch::Receiver<u64> {
+    self.commits_rx.clone()
+  }
+}
+
+/// The stores opened by the process, by path, so that every `Deno.Kv` of a
+/// file, in any worker, sees the commits of the others. It is stored in
+/// `GlobalState`.
+#[derive(Clone, Default)]
+pub struct KvStores(Arc<Mutex<HashMap<PathBuf, Weak<KvStore>>>>);
+
+impl KvStores {
+  /// Opens the store at `path`, or a new in-memory store if `None`.
+  pub fn open(&self, path: Option<PathBuf>) -> Result<Arc<KvStore>, OpError> {
+    let path = match path {
+      Some(path) => path,
+      None => return Ok(Arc::new(KvStore::open(None)?)),
+    };
+    let mut stores = self.0.lock().unwrap();
+    if let Some(store) = stores.get(&path).and_then(Weak::upgrade) {
+      return Ok(store);
+    }
+    let store = Arc::new(KvStore::open(Some(&path))?);
+    stores.retain(|_, store| store.strong_count() > 0);
+    stores.insert(path, Arc::downgrade(&store));
+    Ok(store)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn string(s: &str) -> KeyPart {
+    KeyPart::String(s.to_string())
+  }
+
+  fn set(key: Vec<KeyPart>, value: Value) -> Mutation {
+    Mutation::Set { key, value }
+  }
+
+  #[test]
+  fn keys_sort_like_their_parts() {
+    let keys = vec![
+      vec![KeyPart::Bytes(vec![0])],
+      vec![KeyPart::Bytes(vec![0, 1])],
+      vec![KeyPart::Bytes(vec![1])],
+      vec![string("a")],
+      vec![string("a"), string("b")],
+      vec![string("a"), KeyPart::Number(1.0)],
+      vec![string("a\0")],
+      vec![string("b")],
+      vec![KeyPart::Number(-2.5)],
+      vec![KeyPart::Number(-1.0)],
+      vec![KeyPart::Number(0.0)],
+      vec![KeyPart::Number(1.0)],
+      vec![KeyPart::Number(1e20)],
+      vec![KeyPart::Boolean(false)],
+      vec![KeyPart::Boolean(true)],
+    ];
+    let encoded: Vec<Vec<u8>> = keys.iter().map(|k| encode_key(k)).collect();
+    let mut sorted = encoded.clone();
+    sorted.sort();
+    assert_eq!(encoded, sorted);
+    for (key, encoded) in keys.iter().zip(encoded) {
+      assert_eq!(&decode_key(&encoded).unwrap(), key);
+    }
+    assert_eq!(
+      encode_key(&[KeyPart::Number(-0.0)]),
+      encode_key(&[KeyPart::Number(0.0)])
+    );
+  }
+
+  #[test]
+  fn atomic_checks() {
+    let store = KvStore::open(None).unwrap();
+    let key = vec![string("a")];
+    let check = |versionstamp: Option<String>| Check {
+      key: key.clone(),
+      versionstamp,
+    };
+    let first = store
+      .atomic(&[check(None)], &[set(key.clone(), json!(1))])
+      .unwrap()
+      .unwrap();
+    // The key exists now, so the same operation fails.
+    let result = store
+      .atomic(&[check(None)], &[set(key.clone(), json!(2))])
+      .unwrap();
+    assert_eq!(result, None);
+    let second = store
+      .atomic(&[check(Some(first.clone()))], &[set(key.clone(), json!(3))])
+      .unwrap()
+      .unwrap();
+    assert!(second > first);
+    let entries = store.get(&[key.clone(), vec![string("b")]]).unwrap();
+    assert_eq!(
+      entries,
+      vec![
+        Some(Entry {
+          key: key.clone(),
+          value: json!(3),
+          versionstamp: second,
+        }),
+        None
+      ]
+    );
+  }
+
+  #[test]
+  fn list_pages() {
+    let store = KvStore::open(None).unwrap();
+    let mutations: Vec<Mutation> = (0..5)
+      .map(|i| set(vec![string("users"), KeyPart::Number(i as f64)], json!(i)))
+      .chain(vec![
+        set(vec![string("users")], json!("not listed")),
+        set(vec![string("usersx")], json!("not listed")),
+      ])
+      .collect();
+    store.atomic(&[], &mutations).unwrap();
+
+    let selector = Selector {
+      prefix: Some(vec![string("users")]),
+      ..Selector::default()
+    };
+    let page = store.list(&selector, 3, None, false).unwrap();
+    let values: Vec<Value> =
+      page.iter().map(|(e, _)| e.value.clone()).collect();
+    assert_eq!(values, vec![json!(0), json!(1), json!(2)]);
+    let cursor = &page.last().unwrap().1;
+    let page = store.list(&selector, 3, Some(cursor), false).unwrap();
+    let values: Vec<Value> =
+      page.iter().map(|(e, _)| e.value.clone()).collect();
+    assert_eq!(values, vec![json!(3), json!(4)]);
+
+    let page = store.list(&selector, 2, None, true).unwrap();
+    let values: Vec<Value> =
+      page.iter().map(|(e, _)| e.value.clone()).collect();
+    assert_eq!(values, vec![json!(4), json!(3)]);
+
+    let selector = Selector {
+      prefix: Some(vec![string("users")]),
+      start: Some(vec![string("users"), KeyPart::Number(1.0)]),
+      end: Some(vec![string("users"), KeyPart::Number(3.0)]),
+    };
+    let page = store.list(&selector, 10, None, false).unwrap();
+    let values: Vec<Value> =
+      page.iter().map(|(e, _)| e.value.clone()).collect();
+    assert_eq!(values, vec![json!(1), json!(2)]);
+  }
+
+  #[test]
+  fn stores_are_shared_by_path() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let path = temp_dir.path().join("kv.sqlite3");
+    let stores = KvStores::default();
+    let a = stores.open(Some(path.clone())).unwrap();
+    let b = stores.open(Some(path.clone())).unwrap();
+    assert!(Arc::ptr_eq(&a, &b));
+    a.atomic(&[], &[set(vec![string("a")], json!(true))])
+      .unwrap();
+    drop(a);
+    drop(b);
+
+    // Reopened from the file.
+    let store = stores.open(Some(path)).unwrap();
+    let entries = store.get(&[vec![string("a")]]).unwrap();
+    assert_eq!(entries[0].as_ref().unwrap().value, json!(true));
+  }
+}
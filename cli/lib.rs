@@ -21,60 +21,96 @@ extern crate serde_derive;
 extern crate tokio;
 extern crate url;
 
+mod blob_store;
+mod broadcast_channel;
+mod cache_storage;
 mod checksum;
 pub mod colors;
+mod compat;
+mod compression;
 pub mod deno_dir;
 pub mod diagnostics;
 mod disk_cache;
+#[cfg(feature = "tools")]
 mod doc;
+#[cfg(feature = "tools")]
+mod doc_test;
+mod ffi;
+mod file_collector;
 mod file_fetcher;
 pub mod flags;
+#[cfg(feature = "tools")]
 mod fmt;
 pub mod fmt_errors;
 mod fs;
 pub mod global_state;
-mod global_timer;
 pub mod http_cache;
 mod http_util;
 mod import_map;
 mod inspector;
 pub mod installer;
 mod js;
+mod kv;
+#[cfg(feature = "tools")]
+mod lint;
 mod lockfile;
+mod logger;
+#[cfg(feature = "tools")]
+mod lsp;
+mod media_type;
 mod metrics;
 pub mod msg;
+mod multipart;
+mod npm;
 pub mod op_error;
 pub mod ops;
+mod performance;
 pub mod permissions;
+#[cfg(feature = "repl")]
 mod repl;
 pub mod resolve_addr;
 pub mod signal;
 pub mod source_maps;
+mod standalone;
 mod startup_data;
+mod startup_timing;
 pub mod state;
+#[cfg(feature = "tools")]
 mod swc_util;
+mod task_runner;
+mod test_reporter;
 mod test_runner;
 pub mod test_util;
 mod tokio_util;
+mod trace_events;
 mod tsc;
+mod types;
 mod upgrade;
 pub mod version;
+mod web_storage;
 mod web_worker;
 pub mod worker;
 
+#[cfg(feature = "tools")]
 pub use dprint_plugin_typescript::swc_common;
+#[cfg(feature = "tools")]
 pub use dprint_plugin_typescript::swc_ecma_ast;
+#[cfg(feature = "tools")]
 pub use dprint_plugin_typescript::swc_ecma_parser;
 
+#[cfg(feature = "tools")]
 use crate::doc::parser::DocFileLoader;
 use crate::file_fetcher::SourceFile;
+#[cfg(feature = "tools")]
 use crate::file_fetcher::SourceFileFetcher;
 use crate::global_state::GlobalState;
 use crate::msg::MediaType;
 use crate::op_error::OpError;
 use crate::ops::io::get_stdio;
+use crate::startup_timing::Phase;
 use crate::state::DebugType;
 use crate::state::State;
+use crate::test_reporter::TestCollector;
 use crate::tsc::TargetLib;
 use crate::worker::MainWorker;
 use deno_core::v8_set_flags;
@@ -82,47 +118,22 @@ use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
 use flags::DenoSubcommand;
 use flags::Flags;
+use flags::FlagsError;
 use futures::future::FutureExt;
+#[cfg(feature = "tools")]
 use futures::Future;
-use log::Level;
-use log::Metadata;
-use log::Record;
+use std::collections::HashMap;
 use std::env;
+use std::io::Read;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+#[cfg(feature = "tools")]
 use std::pin::Pin;
+use std::time::Instant;
 use upgrade::upgrade_command;
 use url::Url;
 
-static LOGGER: Logger = Logger;
-
-// TODO(ry) Switch to env_logger or other standard crate.
-struct Logger;
-
-impl log::Log for Logger {
-  fn enabled(&self, metadata: &Metadata) -> bool {
-    metadata.level() <= log::max_level()
-  }
-
-  fn log(&self, record: &Record) {
-    if self.enabled(record.metadata()) {
-      let mut target = record.target().to_string();
-
-      if let Some(line_no) = record.line() {
-        target.push_str(":");
-        target.push_str(&line_no.to_string());
-      }
-
-      if record.level() >= Level::Info {
-        eprintln!("{}", record.args());
-      } else {
-        eprintln!("{} RS - {} - {}", record.level(), target, record.args());
-      }
-    }
-  }
-  fn flush(&self) {}
-}
-
 fn write_to_stdout_ignore_sigpipe(bytes: &[u8]) -> Result<(), std::io::Error> {
   use std::io::ErrorKind;
 
@@ -139,13 +150,18 @@ fn create_main_worker(
   global_state: GlobalState,
   main_module: ModuleSpecifier,
 ) -> Result<MainWorker, ErrBox> {
-  let state = State::new(global_state, None, main_module, DebugType::Main)?;
+  let state =
+    State::new(global_state.clone(), None, main_module, DebugType::Main)?;
 
+  let isolate_start = Instant::now();
   let mut worker = MainWorker::new(
     "main".to_string(),
     startup_data::deno_isolate_init(),
     state,
   );
+  global_state
+    .startup_timing
+    .record(Phase::Isolate, isolate_start.elapsed());
 
   {
     let (stdin, stdout, stderr) = get_stdio();
@@ -155,26 +171,115 @@ fn create_main_worker(
     t.add("stderr", Box::new(stderr));
   }
 
+  let bootstrap_start = Instant::now();
   worker.execute("bootstrap.mainRuntime()")?;
+  global_state
+    .startup_timing
+    .record(Phase::Bootstrap, bootstrap_start.elapsed());
   Ok(worker)
 }
 
-fn print_cache_info(state: &GlobalState) {
+fn human_size(bytes: u64) -> String {
+  let units = ["B", "KB", "MB", "GB", "TB"];
+  let mut size = bytes as f64;
+  let mut unit = 0;
+  while size >= 1024.0 && unit < units.len() - 1 {
+    size /= 1024.0;
+    unit += 1;
+  }
+  if unit == 0 {
+    format!("{}B", bytes)
+  } else {
+    format!("{:.2}{}", size, units[unit])
+  }
+}
+
+/// Number of origins in the remote modules cache, which is laid out as
+/// `deps/<scheme>/<host>/<hash>`.
+fn count_cached_origins(deps_path: &Path) -> usize {
+  fn subdirs(path: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(path)
+      .into_iter()
+      .flatten()
+      .flatten()
+      .map(|entry| entry.path())
+      .filter(|path| path.is_dir())
+      .collect()
+  }
+  subdirs(deps_path)
+    .iter()
+    .map(|scheme| subdirs(scheme).len())
+    .sum()
+}
+
+fn print_cache_info(state: &GlobalState, json: bool) {
+  let deno_dir = &state.dir.root;
+  let modules_cache = &state.file_fetcher.http_cache.location;
+  let modules_cache_size = fs::dir_size(modules_cache);
+  let origins = count_cached_origins(modules_cache);
+  let typescript_cache = &state.dir.gen_cache.location;
+  let typescript_cache_size = fs::dir_size(typescript_cache);
+  #[cfg(feature = "repl")]
+  let repl_history = repl::history_path(
+    &state.dir,
+    "deno_history.txt",
+    None,
+    env::var_os("DENO_REPL_HISTORY"),
+  );
+  #[cfg(not(feature = "repl"))]
+  let repl_history: Option<PathBuf> = None;
+  let repl_history_size = repl_history
+    .as_ref()
+    .and_then(|path| std::fs::metadata(path).ok())
+    .map_or(0, |metadata| metadata.len());
+
+  if json {
+    let output = json!({
+      "denoDir": deno_dir,
+      "modulesCache": {
+        "location": modules_cache,
+        "size": modules_cache_size,
+        "origins": origins,
+      },
+      "typescriptCache": {
+        "location": typescript_cache,
+        "size": typescript_cache_size,
+      },
+      "replHistory": repl_history.as_ref().map(|location| json!({
+        "location": location,
+        "size": repl_history_size,
+      })),
+    });
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    return;
+  }
+
   println!(
     "{} {:?}",
     colors::bold("DENO_DIR location:".to_string()),
-    state.dir.root
+    deno_dir
   );
   println!(
-    "{} {:?}",
+    "{} {:?} ({}, {} origins)",
     colors::bold("Remote modules cache:".to_string()),
-    state.file_fetcher.http_cache.location
+    modules_cache,
+    human_size(modules_cache_size),
+    origins
   );
   println!(
-    "{} {:?}",
+    "{} {:?} ({})",
     colors::bold("TypeScript compiler cache:".to_string()),
-    state.dir.gen_cache.location
+    typescript_cache,
+    human_size(typescript_cache_size)
   );
+  if let Some(repl_history) = repl_history {
+    println!(
+      "{} {:?} ({})",
+      colors::bold("REPL history:".to_string()),
+      repl_history,
+      human_size(repl_history_size)
+    );
+  }
 }
 
 // TODO(bartlomieju): this function de facto repeats
@@ -275,11 +380,12 @@ fn get_types(unstable: bool) -> String {
 async fn info_command(
   flags: Flags,
   file: Option<String>,
+  json: bool,
 ) -> Result<(), ErrBox> {
   let global_state = GlobalState::new(flags)?;
   // If it was just "deno info" print location of caches and exit
   if file.is_none() {
-    print_cache_info(&global_state);
+    print_cache_info(&global_state, json);
     return Ok(());
   }
 
@@ -308,7 +414,18 @@ async fn install_command(
     .map_err(ErrBox::from)
 }
 
-async fn cache_command(flags: Flags, files: Vec<String>) -> Result<(), ErrBox> {
+async fn uninstall_command(
+  name: String,
+  root: Option<PathBuf>,
+) -> Result<(), ErrBox> {
+  installer::uninstall(name, root).map_err(ErrBox::from)
+}
+
+async fn cache_command(
+  flags: Flags,
+  files: Vec<String>,
+  prune: Option<u64>,
+) -> Result<(), ErrBox> {
   let main_module =
     ModuleSpecifier::resolve_url_or_path("./__$deno$fetch.ts").unwrap();
   let global_state = GlobalState::new(flags)?;
@@ -321,27 +438,64 @@ async fn cache_command(flags: Flags, files: Vec<String>) -> Result<(), ErrBox> {
   }
 
   if global_state.flags.lock_write {
-    if let Some(ref lockfile) = global_state.lockfile {
-      let g = lockfile.lock().unwrap();
-      g.write()?;
-    } else {
-      eprintln!("--lock flag must be specified when using --lock-write");
-      std::process::exit(11);
-    }
+    // `--lock-write` requires `--lock`, so the lockfile is always set.
+    let lockfile = global_state.lockfile.as_ref().unwrap();
+    lockfile.lock().unwrap().write()?;
+  }
+
+  if let Some(max_size_mb) = prune {
+    let freed = global_state
+      .dir
+      .gen_cache
+      .prune(max_size_mb * 1024 * 1024)?;
+    info!(
+      "Pruned {} bytes from {:?}",
+      freed, global_state.dir.gen_cache.location
+    );
   }
 
   Ok(())
 }
 
+/// Name of the code transpiled by `deno eval --ts --print`.
+const EVAL_FILE: &str = "__$deno$eval.ts";
+
 async fn eval_command(
   flags: Flags,
   code: String,
   as_typescript: bool,
+  print: bool,
 ) -> Result<(), ErrBox> {
+  let code = if code == "-" {
+    let mut source = String::new();
+    std::io::stdin().read_to_string(&mut source)?;
+    source
+  } else {
+    code
+  };
   // Force TypeScript compile.
   let main_module =
     ModuleSpecifier::resolve_url_or_path("./__$deno$eval.ts").unwrap();
   let global_state = GlobalState::new(flags)?;
+  let code = if print {
+    let script = if as_typescript {
+      let mut sources = HashMap::new();
+      sources.insert(EVAL_FILE.to_string(), code);
+      let transpiled =
+        tsc::runtime_transpile(global_state.clone(), &sources, &None).await?;
+      transpiled[EVAL_FILE]["source"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+    } else {
+      code
+    };
+    // Indirect eval runs the code as a script in the global scope and
+    // returns its completion value, eg. that of the last expression.
+    format!("console.log(globalThis.eval({}));\n", json!(script))
+  } else {
+    code
+  };
   let mut worker = create_main_worker(global_state, main_module.clone())?;
   let main_module_url = main_module.as_url().to_owned();
   // Create a dummy source file.
@@ -349,7 +503,8 @@ async fn eval_command(
     filename: main_module_url.to_file_path().unwrap(),
     url: main_module_url,
     types_url: None,
-    media_type: if as_typescript {
+    // With `--print` TypeScript was transpiled already.
+    media_type: if as_typescript && !print {
       MediaType::TypeScript
     } else {
       MediaType::JavaScript
@@ -365,10 +520,9 @@ async fn eval_command(
     .file_fetcher
     .save_source_file_in_cache(&main_module, source_file);
   debug!("main_module {}", &main_module);
-  worker.execute_module(&main_module).await?;
-  worker.execute("window.dispatchEvent(new Event('load'))")?;
-  (&mut *worker).await?;
-  worker.execute("window.dispatchEvent(new Event('unload'))")?;
+  let result = run_main_module(&mut worker, &main_module).await;
+  worker.handle_exit(result).await?;
+  worker.dispatch_unload()?;
   Ok(())
 }
 
@@ -376,18 +530,167 @@ async fn bundle_command(
   flags: Flags,
   source_file: String,
   out_file: Option<PathBuf>,
+  minify: bool,
 ) -> Result<(), ErrBox> {
   let module_name = ModuleSpecifier::resolve_url_or_path(&source_file)?;
   let global_state = GlobalState::new(flags)?;
   debug!(">>>>> bundle START");
   let bundle_result = global_state
     .ts_compiler
-    .bundle(global_state.clone(), module_name.to_string(), out_file)
+    .bundle(
+      global_state.clone(),
+      module_name.to_string(),
+      out_file,
+      minify,
+    )
     .await;
   debug!(">>>>> bundle END");
   bundle_result
 }
 
+async fn compile_command(
+  flags: Flags,
+  source_file: String,
+  output: Option<PathBuf>,
+) -> Result<(), ErrBox> {
+  let module_name = ModuleSpecifier::resolve_url_or_path(&source_file)?;
+  let output = match output {
+    Some(output) => output,
+    None => match installer::infer_name_from_url(module_name.as_url()) {
+      Some(name) => PathBuf::from(name),
+      None => {
+        return Err(
+          OpError::other(
+            "An executable name was not provided. One could not be inferred from the URL. Aborting.".to_string(),
+          )
+          .into(),
+        )
+      }
+    },
+  };
+  let output = if cfg!(windows) {
+    output.with_extension("exe")
+  } else {
+    output
+  };
+
+  let global_state = GlobalState::new(flags.clone())?;
+  let bundle = global_state
+    .ts_compiler
+    .bundle_to_string(global_state.clone(), module_name.to_string())
+    .await?;
+  let metadata = standalone::Metadata::from_flags(&flags);
+  standalone::create_standalone_binary(
+    &env::current_exe()?,
+    &bundle,
+    &metadata,
+    &output,
+  )?;
+  eprintln!("Emitted standalone binary to {}", output.display());
+  Ok(())
+}
+
+/// Specifier of the bundle embedded in a standalone binary.
+const STANDALONE_MODULE: &str = "file:///$deno$/standalone.js";
+
+/// Returns flags and the bundle to run, if this executable was produced by
+/// `deno compile`. The flags baked in at compile time come first, so that
+/// all command line arguments are passed to the script.
+fn standalone_flags(args: &[String]) -> Option<(Flags, String)> {
+  let exe_path = env::current_exe().ok()?;
+  let standalone = match standalone::extract_standalone(&exe_path) {
+    Ok(standalone) => standalone?,
+    Err(err) => {
+      eprintln!("{}", err);
+      std::process::exit(1);
+    }
+  };
+  let mut run_args = vec!["deno".to_string(), "run".to_string()];
+  run_args.extend(standalone.metadata.args);
+  run_args.push(STANDALONE_MODULE.to_string());
+  run_args.extend(args.iter().skip(1).cloned());
+  let flags = flags::flags_from_vec_safe(run_args)
+    .unwrap_or_else(|err| exit_for_flags_error(err));
+  Some((flags, standalone.bundle))
+}
+
+async fn run_standalone(flags: Flags, bundle: String) -> Result<(), ErrBox> {
+  let global_state = GlobalState::new(flags)?;
+  let main_module = ModuleSpecifier::resolve_url(STANDALONE_MODULE)?;
+  let mut worker = create_main_worker(global_state, main_module.clone())?;
+  let result = async {
+    worker
+      .execute_module_from_code(&main_module, bundle)
+      .await?;
+    worker.dispatch_load()?;
+    (&mut *worker).await
+  }
+  .await;
+  worker.handle_exit(result).await?;
+  worker.dispatch_unload()?;
+  Ok(())
+}
+
+async fn task_command(
+  flags: Flags,
+  task: Option<String>,
+  args: Vec<String>,
+) -> Result<(), ErrBox> {
+  let config_path = match flags.config_path {
+    Some(config_path) => env::current_dir()?.join(config_path),
+    None => {
+      task_runner::find_config(&env::current_dir()?).ok_or_else(|| {
+        OpError::other(format!(
+          "No {} found in the current directory or its parents",
+          task_runner::CONFIG_FILE_NAME
+        ))
+      })?
+    }
+  };
+  let tasks = task_runner::read_tasks(&config_path)?;
+
+  let task = match task {
+    Some(task) => task,
+    None => {
+      println!("{}", colors::bold("Available tasks:".to_string()));
+      for (name, script) in &tasks {
+        println!("- {}", colors::cyan(name.to_string()));
+        println!("    {}", script);
+      }
+      return Ok(());
+    }
+  };
+  let script = match tasks.get(&task) {
+    Some(script) => script,
+    None => {
+      return Err(
+        OpError::other(format!(
+          "Task \"{}\" not found in {}",
+          task,
+          config_path.display()
+        ))
+        .into(),
+      )
+    }
+  };
+
+  eprintln!("{} {} {}", colors::green("Task".to_string()), task, script);
+  let cwd = config_path.parent().unwrap();
+  let exit_code = task_runner::run_task(script, cwd, &args)?;
+  if exit_code != 0 {
+    std::process::exit(exit_code);
+  }
+  Ok(())
+}
+
+#[cfg(feature = "tools")]
+async fn lsp_command(flags: Flags) -> Result<(), ErrBox> {
+  let global_state = GlobalState::new(flags)?;
+  let exit_code = lsp::start(global_state).await?;
+  std::process::exit(exit_code);
+}
+
+#[cfg(feature = "tools")]
 async fn doc_command(
   flags: Flags,
   source_file: Option<String>,
@@ -456,50 +759,110 @@ async fn doc_command(
   }
 }
 
+#[cfg(feature = "repl")]
 async fn run_repl(flags: Flags) -> Result<(), ErrBox> {
   let main_module =
     ModuleSpecifier::resolve_url_or_path("./__$deno$repl.ts").unwrap();
   let global_state = GlobalState::new(flags)?;
   let mut worker = create_main_worker(global_state, main_module)?;
   loop {
-    (&mut *worker).await?;
+    let result = (&mut *worker).await;
+    worker.handle_exit(result).await?;
+  }
+}
+
+/// Stands in for a subcommand that needs a cargo feature this build of Deno
+/// was made without.
+#[allow(dead_code)]
+async fn feature_disabled(
+  subcommand: &'static str,
+  feature: &'static str,
+) -> Result<(), ErrBox> {
+  let message = format!(
+    "`deno {}` isn't included in this build (\"{}\" feature)",
+    subcommand, feature
+  );
+  Err(OpError::other(message).into())
+}
+
+/// Executes main module and runs event loop to completion, dispatching
+/// `load` event in between.
+async fn run_main_module(
+  worker: &mut MainWorker,
+  main_module: &ModuleSpecifier,
+) -> Result<(), ErrBox> {
+  worker.execute_module(main_module).await?;
+  worker.finish_startup();
+  worker.dispatch_load()?;
+  (&mut **worker).await
+}
+
+/// Like `run_main_module`, but first runs the `--compat` prelude, which may
+/// itself load the main module through `require()`.
+async fn run_compat_main_module(
+  worker: &mut MainWorker,
+  main_module: &ModuleSpecifier,
+) -> Result<(), ErrBox> {
+  let prelude =
+    ModuleSpecifier::resolve_url_or_path("./__$deno$compat.js").unwrap();
+  worker
+    .execute_module_from_code(&prelude, compat::prelude(main_module))
+    .await?;
+  if !compat::is_commonjs(main_module) {
+    worker.execute_module(main_module).await?;
   }
+  worker.finish_startup();
+  worker.dispatch_load()?;
+  (&mut **worker).await
 }
 
 async fn run_command(flags: Flags, script: String) -> Result<(), ErrBox> {
   let global_state = GlobalState::new(flags.clone())?;
-  let main_module = ModuleSpecifier::resolve_url_or_path(&script).unwrap();
+  let main_module = if script == "-" {
+    let ext = flags.ext.as_deref().unwrap_or("js");
+    global_state.file_fetcher.save_stdin_source_file(ext)?
+  } else {
+    ModuleSpecifier::resolve_url_or_path(&script).unwrap()
+  };
   let mut worker =
     create_main_worker(global_state.clone(), main_module.clone())?;
   debug!("main_module {}", main_module);
-  worker.execute_module(&main_module).await?;
-  worker.execute("window.dispatchEvent(new Event('load'))")?;
-  (&mut *worker).await?;
-  worker.execute("window.dispatchEvent(new Event('unload'))")?;
+  let result = if flags.compat {
+    run_compat_main_module(&mut worker, &main_module).await
+  } else {
+    run_main_module(&mut worker, &main_module).await
+  };
+  worker.handle_exit(result).await?;
+  worker.dispatch_unload()?;
   if global_state.flags.lock_write {
-    if let Some(ref lockfile) = global_state.lockfile {
-      let g = lockfile.lock().unwrap();
-      g.write()?;
-    } else {
-      eprintln!("--lock flag must be specified when using --lock-write");
-      std::process::exit(11);
-    }
+    // `--lock-write` requires `--lock`, so the lockfile is always set.
+    let lockfile = global_state.lockfile.as_ref().unwrap();
+    lockfile.lock().unwrap().write()?;
   }
   Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn test_command(
   flags: Flags,
   include: Option<Vec<String>>,
   fail_fast: bool,
   quiet: bool,
   allow_none: bool,
+  trace_ops: bool,
   filter: Option<String>,
+  jobs: usize,
+  reporter: String,
+  doc: bool,
 ) -> Result<(), ErrBox> {
   let global_state = GlobalState::new(flags.clone())?;
   let cwd = std::env::current_dir().expect("No current directory");
   let include = include.unwrap_or_else(|| vec![".".to_string()]);
-  let test_modules = test_runner::prepare_test_modules_urls(include, &cwd)?;
+  let mut test_modules =
+    test_runner::prepare_test_modules_urls(include.clone(), &cwd)?;
+  if doc {
+    test_modules.extend(prepare_doc_tests(&global_state, include, &cwd)?);
+  }
 
   if test_modules.is_empty() {
     println!("No matching test modules found");
@@ -509,22 +872,178 @@ async fn test_command(
     return Ok(());
   }
 
-  let test_file_path = cwd.join(".deno.test.ts");
-  let test_file_url =
-    Url::from_file_path(&test_file_path).expect("Should be valid file url");
-  let test_file =
-    test_runner::render_test_file(test_modules, fail_fast, quiet, filter);
-  let main_module =
-    ModuleSpecifier::resolve_url(&test_file_url.to_string()).unwrap();
+  let reporter = test_reporter::create_reporter(&reporter, quiet);
+  *global_state.test_collector.lock().unwrap() =
+    Some(TestCollector::new(reporter));
+  let start = Instant::now();
+
+  // Failing fast requires test files to be run one after another.
+  let jobs = if fail_fast {
+    1
+  } else {
+    jobs.min(test_modules.len())
+  };
+  if jobs == 1 {
+    let test_file_path = cwd.join(".deno.test.ts");
+    run_test_modules(
+      global_state.clone(),
+      test_file_path,
+      test_modules,
+      fail_fast,
+      quiet,
+      trace_ops,
+      filter,
+    )
+    .await?;
+  } else {
+    // Distribute test files among workers, each running in its own thread.
+    let mut chunks = vec![vec![]; jobs];
+    for (i, module) in test_modules.into_iter().enumerate() {
+      chunks[i % jobs].push(module);
+    }
+    let mut handles = vec![];
+    for (i, modules) in chunks.into_iter().enumerate() {
+      let global_state = global_state.clone();
+      let test_file_path = cwd.join(format!(".deno.test.{}.ts", i));
+      let filter = filter.clone();
+      let handle = std::thread::Builder::new()
+        .name(format!("deno-test-{}", i))
+        .spawn(move || {
+          tokio_util::run_basic(run_test_modules(
+            global_state,
+            test_file_path,
+            modules,
+            false,
+            quiet,
+            trace_ops,
+            filter,
+          ))
+        })?;
+      handles.push(handle);
+    }
+    for handle in handles {
+      handle.join().unwrap()?;
+    }
+  }
+
+  let collector = global_state.test_collector.lock().unwrap().take().unwrap();
+  let summary = collector.done(start.elapsed());
+  if summary.failed > 0 {
+    std::process::exit(1);
+  }
+  Ok(())
+}
+
+/// Generates the test modules of the doc tests in `include`, and saves them
+/// in the file fetcher cache for the test workers to load.
+#[cfg(feature = "tools")]
+fn prepare_doc_tests(
+  global_state: &GlobalState,
+  include: Vec<String>,
+  root_path: &PathBuf,
+) -> Result<Vec<Url>, ErrBox> {
+  let include: Vec<String> = include
+    .into_iter()
+    .filter(|n| !installer::is_remote_url(n))
+    .collect();
+  let files = file_collector::FileCollector::new(doc_test::is_supported)
+    .collect(root_path, &include)?;
+  let mut urls = vec![];
+  for file in files {
+    let source_code = std::fs::read_to_string(&file)?;
+    let module_url = Url::from_file_path(&file).unwrap();
+    for doc_test in doc_test::extract_doc_tests(&module_url, &source_code)? {
+      let specifier =
+        ModuleSpecifier::resolve_url(doc_test.url.as_str()).unwrap();
+      let filename = doc_test.url.to_file_path().unwrap();
+      let source_file = SourceFile {
+        media_type: media_type::map_file_extension(&filename),
+        filename,
+        url: doc_test.url.clone(),
+        types_url: None,
+        source_code: doc_test.source_code.into_bytes(),
+      };
+      global_state
+        .file_fetcher
+        .save_source_file_in_cache(&specifier, source_file);
+      urls.push(doc_test.url);
+    }
+  }
+  Ok(urls)
+}
+
+#[cfg(not(feature = "tools"))]
+fn prepare_doc_tests(
+  _global_state: &GlobalState,
+  _include: Vec<String>,
+  _root_path: &PathBuf,
+) -> Result<Vec<Url>, ErrBox> {
+  let message =
+    "`deno test --doc` isn't included in this build (\"tools\" feature)";
+  Err(OpError::other(message.to_string()).into())
+}
+
+/// Runs tests from given modules in a new main worker.
+async fn run_test_modules(
+  global_state: GlobalState,
+  test_file_path: PathBuf,
+  test_modules: Vec<Url>,
+  fail_fast: bool,
+  quiet: bool,
+  trace_ops: bool,
+  filter: Option<String>,
+) -> Result<(), ErrBox> {
+  let coverage = global_state.flags.coverage_dir.is_some();
+  let test_file = test_runner::render_test_file(
+    test_modules,
+    fail_fast,
+    quiet,
+    trace_ops,
+    coverage,
+    filter,
+  );
+  run_generated_module(global_state, test_file_path, test_file).await
+}
+
+async fn bench_command(
+  flags: Flags,
+  include: Option<Vec<String>>,
+  filter: Option<String>,
+) -> Result<(), ErrBox> {
+  let global_state = GlobalState::new(flags)?;
+  let cwd = std::env::current_dir().expect("No current directory");
+  let include = include.unwrap_or_else(|| vec![".".to_string()]);
+  let bench_modules = test_runner::prepare_bench_modules_urls(include, &cwd)?;
+
+  if bench_modules.is_empty() {
+    println!("No matching bench modules found");
+    std::process::exit(1);
+  }
+
+  let bench_file_path = cwd.join(".deno.bench.ts");
+  let profile = global_state.flags.cpu_profile.is_some();
+  let bench_file =
+    test_runner::render_bench_file(bench_modules, profile, filter);
+  run_generated_module(global_state, bench_file_path, bench_file).await
+}
+
+/// Runs `source` as the main module at `path`, which doesn't exist on disk.
+async fn run_generated_module(
+  global_state: GlobalState,
+  path: PathBuf,
+  source: String,
+) -> Result<(), ErrBox> {
+  let url = Url::from_file_path(&path).expect("Should be valid file url");
+  let main_module = ModuleSpecifier::resolve_url(&url.to_string()).unwrap();
   let mut worker =
     create_main_worker(global_state.clone(), main_module.clone())?;
   // Create a dummy source file.
   let source_file = SourceFile {
-    filename: test_file_url.to_file_path().unwrap(),
-    url: test_file_url,
+    filename: path,
+    url,
     types_url: None,
     media_type: MediaType::TypeScript,
-    source_code: test_file.clone().into_bytes(),
+    source_code: source.into_bytes(),
   };
   // Save our fake file into file fetcher cache
   // to allow module access by TS compiler (e.g. op_fetch_source_files)
@@ -534,54 +1053,157 @@ async fn test_command(
     .global_state
     .file_fetcher
     .save_source_file_in_cache(&main_module, source_file);
-  let execute_result = worker.execute_module(&main_module).await;
-  execute_result?;
-  worker.execute("window.dispatchEvent(new Event('load'))")?;
-  (&mut *worker).await?;
-  worker.execute("window.dispatchEvent(new Event('unload'))")
+  let result = run_main_module(&mut worker, &main_module).await;
+  worker.handle_exit(result).await?;
+  worker.dispatch_unload()
 }
 
-pub fn main() {
-  #[cfg(windows)]
-  colors::enable_ansi(); // For Windows 10
+/// Passes `--v8-flags` to V8; must be called before any isolate is created.
+/// Exits if V8 doesn't recognize a flag, or after V8 printed its help.
+fn init_v8_flags(v8_flags: &[String]) {
+  let includes_help = v8_flags.iter().any(|f| f == "-help" || f == "--help");
+  let mut args = vec!["UNUSED_BUT_NECESSARY_ARG0".to_string()];
+  args.extend_from_slice(v8_flags);
+  // V8 returns the arguments it didn't recognize, including the first one.
+  let unrecognized: Vec<String> =
+    v8_set_flags(args).into_iter().skip(1).collect();
+  if !unrecognized.is_empty() {
+    for flag in unrecognized {
+      eprintln!("error: V8 did not recognize flag '{}'", flag);
+    }
+    eprintln!("\nFor a list of V8 flags, use '--v8-flags=--help'");
+    std::process::exit(1);
+  }
+  if includes_help {
+    std::process::exit(0);
+  }
+}
 
-  log::set_logger(&LOGGER).unwrap();
+/// Prints the help or error of a command line that wasn't parsed, and exits.
+fn exit_for_flags_error(err: FlagsError) -> ! {
+  match err {
+    FlagsError::Help(_) | FlagsError::Version => println!("{}", err),
+    FlagsError::UnknownFlag { .. } | FlagsError::Invalid(_) => {
+      eprintln!("{}", err)
+    }
+  }
+  std::process::exit(err.exit_code())
+}
+
+pub fn main() {
   let args: Vec<String> = env::args().collect();
-  let flags = flags::flags_from_vec(args);
+  let (flags, standalone_bundle) = match standalone_flags(&args) {
+    Some((flags, bundle)) => (flags, Some(bundle)),
+    None => match flags::flags_from_vec_safe(args) {
+      Ok(flags) => (flags, None),
+      Err(err) => exit_for_flags_error(err),
+    },
+  };
 
   if let Some(ref v8_flags) = flags.v8_flags {
-    let mut v8_flags_ = v8_flags.clone();
-    v8_flags_.insert(0, "UNUSED_BUT_NECESSARY_ARG0".to_string());
-    v8_set_flags(v8_flags_);
+    init_v8_flags(v8_flags);
   }
 
-  let log_level = match flags.log_level {
-    Some(level) => level,
-    None => Level::Info, // Default log level
+  let log_spec = env::var("RUST_LOG").ok();
+  let logger = match logger::Logger::new(
+    flags.log_level,
+    log_spec.as_deref(),
+    flags.log_json,
+  ) {
+    Ok(logger) => logger,
+    Err(e) => {
+      eprintln!("Invalid RUST_LOG: {}", e);
+      std::process::exit(1);
+    }
   };
-  log::set_max_level(log_level.to_level_filter());
+  log::set_max_level(logger.max_level());
+  // The logger lives as long as the process.
+  log::set_logger(Box::leak(Box::new(logger))).unwrap();
 
-  let fut = match flags.clone().subcommand {
+  colors::init(flags.no_color);
+
+  if let Some(threads) = flags.blocking_threads {
+    tokio_util::set_blocking_threads(threads);
+  }
+  let runtime_threads = flags.runtime_threads;
+  let runtime_stack_size = flags.runtime_stack_size;
+
+  match flags.subcommand {
+    DenoSubcommand::Completions { ref buf } => {
+      if let Err(e) = write_to_stdout_ignore_sigpipe(buf) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+      }
+      return;
+    }
+    DenoSubcommand::Types { ref libs } => {
+      let types = if libs.is_empty() {
+        get_types(flags.unstable)
+      } else {
+        match types::compose_libs(libs, flags.unstable) {
+          Ok(types) => types,
+          Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+          }
+        }
+      };
+      if let Err(e) = write_to_stdout_ignore_sigpipe(types.as_bytes()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+      }
+      return;
+    }
+    _ => {}
+  }
+
+  // The future is created on the isolate thread, as isolates can't be moved
+  // between threads.
+  let create_future = move || match flags.clone().subcommand {
+    DenoSubcommand::Run { .. } if standalone_bundle.is_some() => {
+      run_standalone(flags, standalone_bundle.unwrap()).boxed_local()
+    }
+    DenoSubcommand::Bench { include, filter } => {
+      bench_command(flags, include, filter).boxed_local()
+    }
     DenoSubcommand::Bundle {
       source_file,
       out_file,
-    } => bundle_command(flags, source_file, out_file).boxed_local(),
+      minify,
+    } => bundle_command(flags, source_file, out_file, minify).boxed_local(),
+    DenoSubcommand::Compile {
+      source_file,
+      output,
+    } => compile_command(flags, source_file, output).boxed_local(),
+    #[cfg(feature = "tools")]
     DenoSubcommand::Doc {
       source_file,
       json,
       filter,
     } => doc_command(flags, source_file, json, filter).boxed_local(),
+    #[cfg(not(feature = "tools"))]
+    DenoSubcommand::Doc { .. } => {
+      feature_disabled("doc", "tools").boxed_local()
+    }
     DenoSubcommand::Eval {
       code,
       as_typescript,
-    } => eval_command(flags, code, as_typescript).boxed_local(),
-    DenoSubcommand::Cache { files } => {
-      cache_command(flags, files).boxed_local()
+      print,
+    } => eval_command(flags, code, as_typescript, print).boxed_local(),
+    DenoSubcommand::Cache { files, prune } => {
+      cache_command(flags, files, prune).boxed_local()
     }
+    #[cfg(feature = "tools")]
     DenoSubcommand::Fmt { check, files } => {
       fmt::format(files, check).boxed_local()
     }
-    DenoSubcommand::Info { file } => info_command(flags, file).boxed_local(),
+    #[cfg(not(feature = "tools"))]
+    DenoSubcommand::Fmt { .. } => {
+      feature_disabled("fmt", "tools").boxed_local()
+    }
+    DenoSubcommand::Info { file, json } => {
+      info_command(flags, file, json).boxed_local()
+    }
     DenoSubcommand::Install {
       module_url,
       args,
@@ -591,40 +1213,59 @@ pub fn main() {
     } => {
       install_command(flags, module_url, args, name, root, force).boxed_local()
     }
-    DenoSubcommand::Repl => run_repl(flags).boxed_local(),
+    DenoSubcommand::Task { task, args } => {
+      task_command(flags, task, args).boxed_local()
+    }
+    DenoSubcommand::Uninstall { name, root } => {
+      uninstall_command(name, root).boxed_local()
+    }
+    #[cfg(feature = "tools")]
+    DenoSubcommand::Lint { files, rules, fix } => {
+      lint::lint(files, rules, fix, flags.config_path).boxed_local()
+    }
+    #[cfg(not(feature = "tools"))]
+    DenoSubcommand::Lint { .. } => {
+      feature_disabled("lint", "tools").boxed_local()
+    }
+    #[cfg(feature = "tools")]
+    DenoSubcommand::Lsp => lsp_command(flags).boxed_local(),
+    #[cfg(not(feature = "tools"))]
+    DenoSubcommand::Lsp => feature_disabled("lsp", "tools").boxed_local(),
+    #[cfg(feature = "repl")]
+    DenoSubcommand::Repl { .. } => run_repl(flags).boxed_local(),
+    #[cfg(not(feature = "repl"))]
+    DenoSubcommand::Repl { .. } => {
+      feature_disabled("repl", "repl").boxed_local()
+    }
     DenoSubcommand::Run { script } => run_command(flags, script).boxed_local(),
     DenoSubcommand::Test {
       fail_fast,
       quiet,
       include,
       allow_none,
+      trace_ops,
       filter,
-    } => test_command(flags, include, fail_fast, quiet, allow_none, filter)
-      .boxed_local(),
-    DenoSubcommand::Completions { buf } => {
-      if let Err(e) = write_to_stdout_ignore_sigpipe(&buf) {
-        eprintln!("{}", e);
-        std::process::exit(1);
-      }
-      return;
-    }
-    DenoSubcommand::Types => {
-      let types = get_types(flags.unstable);
-      if let Err(e) = write_to_stdout_ignore_sigpipe(types.as_bytes()) {
-        eprintln!("{}", e);
-        std::process::exit(1);
-      }
-      return;
-    }
+      jobs,
+      reporter,
+      doc,
+    } => test_command(
+      flags, include, fail_fast, quiet, allow_none, trace_ops, filter, jobs,
+      reporter, doc,
+    )
+    .boxed_local(),
     DenoSubcommand::Upgrade {
       force,
       dry_run,
       version,
-    } => upgrade_command(dry_run, force, version).boxed_local(),
+      output,
+      canary,
+    } => upgrade_command(dry_run, force, version, output, canary).boxed_local(),
     _ => unreachable!(),
   };
 
-  let result = tokio_util::run_basic(fut);
+  let mut rt =
+    tokio_util::create_main_runtime(runtime_threads, runtime_stack_size);
+  let result = tokio_util::run_on_isolate_thread(&mut rt, create_future);
   if let Err(err) = result {
     let msg = format!(
       "{}: {}",
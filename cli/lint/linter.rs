@@ -0,0 +1,401 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::rules::LintRule;
+use crate::swc_common::comments::CommentKind;
+use crate::swc_common::SourceMap;
+use crate::swc_common::Span;
+use crate::swc_common::Spanned;
+use crate::swc_ecma_ast;
+use crate::swc_util::AstParser;
+use crate::swc_util::SwcDiagnosticBuffer;
+use swc_ecma_visit::Node;
+use swc_ecma_visit::Visit;
+
+use std::ops::Range;
+use std::sync::Arc;
+
+const IGNORE_DIRECTIVE: &str = "deno-lint-ignore";
+const IGNORE_FILE_DIRECTIVE: &str = "deno-lint-ignore-file";
+const MAX_FIX_PASSES: usize = 10;
+
+/// Replaces `range`, in bytes, of the source code with `text`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintFix {
+  pub range: Range<usize>,
+  pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintDiagnostic {
+  pub code: String,
+  pub message: String,
+  pub file_name: String,
+  /// 1-based line.
+  pub line: usize,
+  /// 0-based column.
+  pub col: usize,
+  pub fix: Option<LintFix>,
+}
+
+/// State shared by the rules linting a module.
+pub struct Context {
+  file_name: String,
+  source_code: String,
+  source_map: Arc<SourceMap>,
+  diagnostics: Vec<LintDiagnostic>,
+}
+
+impl Context {
+  pub fn add_diagnostic(&mut self, span: Span, code: &str, message: &str) {
+    self.push_diagnostic(span, code, message, None);
+  }
+
+  /// Adds a diagnostic that `deno lint --fix` fixes with `fix`.
+  pub fn add_fixable_diagnostic(
+    &mut self,
+    span: Span,
+    code: &str,
+    message: &str,
+    fix: LintFix,
+  ) {
+    self.push_diagnostic(span, code, message, Some(fix));
+  }
+
+  fn push_diagnostic(
+    &mut self,
+    span: Span,
+    code: &str,
+    message: &str,
+    fix: Option<LintFix>,
+  ) {
+    let location = self.source_map.lookup_char_pos(span.lo());
+    self.diagnostics.push(LintDiagnostic {
+      code: code.to_string(),
+      message: message.to_string(),
+      file_name: self.file_name.clone(),
+      line: location.line,
+      col: location.col.0,
+      fix,
+    });
+  }
+
+  pub fn source_code(&self) -> &str {
+    &self.source_code
+  }
+
+  /// Byte range of `span` in the source code.
+  pub fn range(&self, span: Span) -> Range<usize> {
+    let lo = self.source_map.lookup_byte_offset(span.lo()).pos.0 as usize;
+    let hi = self.source_map.lookup_byte_offset(span.hi()).pos.0 as usize;
+    lo..hi
+  }
+
+  /// Source code of `span`.
+  pub fn snippet(&self, span: Span) -> &str {
+    &self.source_code[self.range(span)]
+  }
+}
+
+/// Applies the fixes of `diagnostics` to `source_code`. Fixes overlapping
+/// one that was applied are left for the next pass, once the rules ran on
+/// the fixed code. Returns the fixed code and the number of applied fixes.
+pub fn apply_fixes(
+  source_code: &str,
+  diagnostics: &[LintDiagnostic],
+) -> (String, usize) {
+  let mut fixes: Vec<&LintFix> = diagnostics
+    .iter()
+    .filter_map(|diagnostic| diagnostic.fix.as_ref())
+    .collect();
+  fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+
+  let mut fixed = String::new();
+  let mut end = 0;
+  let mut count = 0;
+  for fix in fixes {
+    if fix.range.start < end {
+      continue;
+    }
+    fixed.push_str(&source_code[end..fix.range.start]);
+    fixed.push_str(&fix.text);
+    end = fix.range.end;
+    count += 1;
+  }
+  fixed.push_str(&source_code[end..]);
+  (fixed, count)
+}
+
+/// A `// deno-lint-ignore` comment. Without rule codes, it ignores every
+/// rule.
+#[derive(Debug, PartialEq)]
+struct IgnoreDirective {
+  line: usize,
+  codes: Vec<String>,
+}
+
+impl IgnoreDirective {
+  fn ignores(&self, code: &str) -> bool {
+    self.codes.is_empty() || self.codes.iter().any(|c| c == code)
+  }
+}
+
+/// Returns the directive name and the rule codes following it, if the
+/// comment is an ignore directive.
+fn parse_directive(text: &str) -> Option<(&'static str, Vec<String>)> {
+  let text = text.trim();
+  let directive = if text.starts_with(IGNORE_FILE_DIRECTIVE) {
+    IGNORE_FILE_DIRECTIVE
+  } else if text.starts_with(IGNORE_DIRECTIVE) {
+    IGNORE_DIRECTIVE
+  } else {
+    return None;
+  };
+  let rest = &text[directive.len()..];
+  if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+    return None;
+  }
+  let codes = rest.split_whitespace().map(String::from).collect();
+  Some((directive, codes))
+}
+
+/// Collects the ignore directives from the comments leading statements,
+/// module declarations and class members.
+struct IgnoreDirectiveVisitor<'a> {
+  ast_parser: &'a AstParser,
+  /// Start of the first module item; file directives must come before it.
+  body_start: Option<Span>,
+  line_directives: Vec<IgnoreDirective>,
+  file_directives: Vec<IgnoreDirective>,
+}
+
+impl<'a> IgnoreDirectiveVisitor<'a> {
+  fn collect(&mut self, span: Span) {
+    for comment in self.ast_parser.get_span_comments(span) {
+      if comment.kind != CommentKind::Line {
+        continue;
+      }
+      let (name, codes) = match parse_directive(&comment.text) {
+        Some(directive) => directive,
+        None => continue,
+      };
+      let line = self.ast_parser.get_span_location(comment.span).line;
+      let directive = IgnoreDirective { line, codes };
+      if name == IGNORE_FILE_DIRECTIVE {
+        let at_top = self
+          .body_start
+          .map_or(false, |start| comment.span.hi() <= start.lo());
+        if at_top {
+          self.file_directives.push(directive);
+        }
+      } else {
+        self.line_directives.push(directive);
+      }
+    }
+  }
+}
+
+impl<'a> Visit for IgnoreDirectiveVisitor<'a> {
+  fn visit_stmt(&mut self, stmt: &swc_ecma_ast::Stmt, parent: &dyn Node) {
+    self.collect(stmt.span());
+    swc_ecma_visit::visit_stmt(self, stmt, parent);
+  }
+
+  fn visit_module_decl(
+    &mut self,
+    module_decl: &swc_ecma_ast::ModuleDecl,
+    parent: &dyn Node,
+  ) {
+    self.collect(module_decl.span());
+    swc_ecma_visit::visit_module_decl(self, module_decl, parent);
+  }
+
+  fn visit_class_member(
+    &mut self,
+    class_member: &swc_ecma_ast::ClassMember,
+    parent: &dyn Node,
+  ) {
+    self.collect(class_member.span());
+    swc_ecma_visit::visit_class_member(self, class_member, parent);
+  }
+}
+
+pub struct Linter {
+  ast_parser: AstParser,
+}
+
+impl Linter {
+  pub fn new() -> Self {
+    Linter {
+      ast_parser: AstParser::new(),
+    }
+  }
+
+  /// Lints a module with the given rules. Diagnostics suppressed by ignore
+  /// directives aren't returned.
+  pub fn lint(
+    &self,
+    file_name: &str,
+    source_code: &str,
+    rules: &[Box<dyn LintRule>],
+  ) -> Result<Vec<LintDiagnostic>, SwcDiagnosticBuffer> {
+    self
+      .ast_parser
+      .parse_module(file_name, source_code, |parse_result| {
+        let module = parse_result?;
+
+        let mut context = Context {
+          file_name: file_name.to_string(),
+          source_code: source_code.to_string(),
+          source_map: self.ast_parser.source_map.clone(),
+          diagnostics: vec![],
+        };
+        for rule in rules {
+          rule.lint_module(&mut context, &module);
+        }
+
+        let mut visitor = IgnoreDirectiveVisitor {
+          ast_parser: &self.ast_parser,
+          body_start: module.body.first().map(|item| item.span()),
+          line_directives: vec![],
+          file_directives: vec![],
+        };
+        visitor.visit_module(&module, &module);
+
+        let mut diagnostics = context.diagnostics;
+        diagnostics.retain(|diagnostic| {
+          let file_ignored = visitor
+            .file_directives
+            .iter()
+            .any(|directive| directive.ignores(&diagnostic.code));
+          let line_ignored = visitor.line_directives.iter().any(|directive| {
+            directive.line + 1 == diagnostic.line
+              && directive.ignores(&diagnostic.code)
+          });
+          !file_ignored && !line_ignored
+        });
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.col));
+        Ok(diagnostics)
+      })
+  }
+}
+
+/// Lints and fixes a module until no fixes are left. Returns the fixed code
+/// and its remaining diagnostics.
+pub fn lint_and_fix(
+  file_name: &str,
+  source_code: &str,
+  rules: &[Box<dyn LintRule>],
+) -> Result<(String, Vec<LintDiagnostic>), SwcDiagnosticBuffer> {
+  let mut source_code = source_code.to_string();
+  let mut diagnostics = Linter::new().lint(file_name, &source_code, rules)?;
+  for _ in 0..MAX_FIX_PASSES {
+    let (fixed, count) = apply_fixes(&source_code, &diagnostics);
+    if count == 0 {
+      break;
+    }
+    source_code = fixed;
+    diagnostics = Linter::new().lint(file_name, &source_code, rules)?;
+  }
+  Ok((source_code, diagnostics))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lint::rules::get_all_rules;
+
+  fn lint(source_code: &str) -> Vec<(String, usize)> {
+    Linter::new()
+      .lint("test.ts", source_code, &get_all_rules())
+      .expect("Failed to parse")
+      .into_iter()
+      .map(|diagnostic| (diagnostic.code, diagnostic.line))
+      .collect()
+  }
+
+  #[test]
+  fn parse_ignore_directives() {
+    assert_eq!(
+      parse_directive(" deno-lint-ignore no-var eqeqeq"),
+      Some((IGNORE_DIRECTIVE, vec!["no-var".into(), "eqeqeq".into()]))
+    );
+    assert_eq!(
+      parse_directive(" deno-lint-ignore-file"),
+      Some((IGNORE_FILE_DIRECTIVE, vec![]))
+    );
+    assert_eq!(parse_directive(" deno-lint-ignored"), None);
+    assert_eq!(parse_directive(" some comment"), None);
+  }
+
+  #[test]
+  fn reports_diagnostics() {
+    let source_code = r#"
+var a = 1;
+function f(b: any) {
+  debugger;
+  return a == b;
+}
+"#;
+    assert_eq!(
+      lint(source_code),
+      vec![
+        ("no-var".to_string(), 2),
+        ("no-explicit-any".to_string(), 3),
+        ("no-debugger".to_string(), 4),
+        ("eqeqeq".to_string(), 5),
+      ]
+    );
+  }
+
+  #[test]
+  fn ignore_next_line() {
+    let source_code = r#"
+// deno-lint-ignore no-var
+var a = 1;
+var b = 2;
+function f() {
+  // deno-lint-ignore
+  debugger;
+  // deno-lint-ignore eqeqeq
+  var c = a == b;
+}
+"#;
+    assert_eq!(
+      lint(source_code),
+      vec![("no-var".to_string(), 4), ("no-var".to_string(), 9)]
+    );
+  }
+
+  #[test]
+  fn ignore_file() {
+    let source_code = r#"
+// deno-lint-ignore-file no-var
+var a = 1;
+// deno-lint-ignore-file
+debugger;
+"#;
+    assert_eq!(lint(source_code), vec![("no-debugger".to_string(), 5)]);
+  }
+
+  #[test]
+  fn fix() {
+    let source_code = r#"import { a, b } from "./a.ts";
+import c from "./c.ts";
+import { x, y } from "./x.ts";
+let d = a == 1;
+let e = 0;
+e++;
+"#;
+    let (fixed, diagnostics) =
+      lint_and_fix("test.ts", source_code, &get_all_rules())
+        .expect("Failed to parse");
+    assert_eq!(
+      fixed,
+      r#"import { a } from "./a.ts";
+const d = a === 1;
+let e = 0;
+e++;
+"#
+    );
+    assert!(diagnostics.is_empty());
+  }
+}
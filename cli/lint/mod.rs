@@ -0,0 +1,331 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! `deno lint` checks JavaScript/TypeScript source files with the rules in
+//! `rules.rs`.
+//!
+//! The `lint` section of `deno.json` selects the files and rules:
+//!
+//! ```json
+//! {
+//!   "lint": {
+//!     "files": { "include": ["src/"], "exclude": ["src/testdata/"] },
+//!     "rules": { "tags": ["recommended"], "exclude": ["no-explicit-any"] }
+//!   }
+//! }
+//! ```
+//!
+//! Diagnostics are suppressed with `// deno-lint-ignore [rules...]` on the
+//! line before, or `// deno-lint-ignore-file [rules...]` at the top of the
+//! file.
+//!
+//! Rules may attach a fix to their diagnostics, which `deno lint --fix`
+//! applies to the file.
+
+mod linter;
+mod rules;
+
+use crate::colors;
+use crate::file_collector::FileCollector;
+use crate::fmt::is_supported;
+use crate::op_error::OpError;
+use crate::task_runner;
+use deno_core::ErrBox;
+use linter::lint_and_fix;
+use linter::Linter;
+use rules::get_all_rules;
+use rules::LintRule;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+const RECOMMENDED_TAG: &str = "recommended";
+
+/// The `lint` section of the config file.
+#[derive(Debug, PartialEq)]
+struct LintConfig {
+  /// Directory of the config file, which file patterns are relative to.
+  base: PathBuf,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  tags: Vec<String>,
+  rules_include: Vec<String>,
+  rules_exclude: Vec<String>,
+}
+
+impl LintConfig {
+  fn new(base: PathBuf) -> Self {
+    LintConfig {
+      base,
+      include: vec![],
+      exclude: vec![],
+      tags: vec![RECOMMENDED_TAG.to_string()],
+      rules_include: vec![],
+      rules_exclude: vec![],
+    }
+  }
+
+  fn read(config_path: &Path) -> Result<Self, ErrBox> {
+    let source = fs::read_to_string(config_path)?;
+    let config: serde_json::Value = serde_json::from_str(&source)?;
+    let base = config_path.parent().unwrap().to_path_buf();
+    let mut lint_config = LintConfig::new(base);
+    let lint = match config.get("lint") {
+      Some(lint) => lint,
+      None => return Ok(lint_config),
+    };
+    if let Some(files) = lint.get("files") {
+      if let Some(include) = strings(config_path, files, "include")? {
+        lint_config.include = include;
+      }
+      if let Some(exclude) = strings(config_path, files, "exclude")? {
+        lint_config.exclude = exclude;
+      }
+    }
+    if let Some(rules) = lint.get("rules") {
+      if let Some(tags) = strings(config_path, rules, "tags")? {
+        lint_config.tags = tags;
+      }
+      if let Some(include) = strings(config_path, rules, "include")? {
+        lint_config.rules_include = include;
+      }
+      if let Some(exclude) = strings(config_path, rules, "exclude")? {
+        lint_config.rules_exclude = exclude;
+      }
+    }
+    Ok(lint_config)
+  }
+
+  fn is_enabled(&self, rule: &dyn LintRule) -> bool {
+    let code = rule.code().to_string();
+    if self.rules_exclude.contains(&code) {
+      return false;
+    }
+    self.rules_include.contains(&code)
+      || rule
+        .tags()
+        .iter()
+        .any(|tag| self.tags.iter().any(|t| t == tag))
+  }
+
+  /// Errors if a rule the config refers to doesn't exist.
+  fn check_rules(&self, all_rules: &[Box<dyn LintRule>]) -> Result<(), ErrBox> {
+    for code in self.rules_include.iter().chain(self.rules_exclude.iter()) {
+      if !all_rules.iter().any(|rule| rule.code() == code) {
+        return Err(
+          OpError::type_error(format!("Unknown lint rule \"{}\"", code)).into(),
+        );
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Reads the array of strings at `key` of a `lint` config entry.
+fn strings(
+  config_path: &Path,
+  value: &serde_json::Value,
+  key: &str,
+) -> Result<Option<Vec<String>>, ErrBox> {
+  let strings = match value.get(key) {
+    None => return Ok(None),
+    Some(serde_json::Value::Array(values)) => values
+      .iter()
+      .map(|value| value.as_str().map(String::from))
+      .collect::<Option<Vec<String>>>(),
+    Some(_) => None,
+  };
+  match strings {
+    Some(strings) => Ok(Some(strings)),
+    None => Err(
+      OpError::type_error(format!(
+        "\"lint\" entry \"{}\" in {} must be an array of strings",
+        key,
+        config_path.display()
+      ))
+      .into(),
+    ),
+  }
+}
+
+/// Lints JavaScript/TypeScript files. `args` are files, directories or
+/// globs; without them, the files included by the config file, or the
+/// directory of the config file, or the current directory, are linted. With
+/// `fix`, fixable problems are fixed in place.
+pub async fn lint(
+  args: Vec<String>,
+  list_rules: bool,
+  fix: bool,
+  config_path: Option<String>,
+) -> Result<(), ErrBox> {
+  let cwd = env::current_dir()?;
+  let config_path = match config_path {
+    Some(config_path) => Some(cwd.join(config_path)),
+    None => task_runner::find_config(&cwd),
+  };
+  let config = match &config_path {
+    Some(config_path) => LintConfig::read(config_path)?,
+    None => LintConfig::new(cwd.clone()),
+  };
+
+  let all_rules = get_all_rules();
+  config.check_rules(&all_rules)?;
+  if list_rules {
+    print_rules(&config, &all_rules);
+    return Ok(());
+  }
+  let rules: Vec<Box<dyn LintRule>> = all_rules
+    .into_iter()
+    .filter(|rule| config.is_enabled(rule.as_ref()))
+    .collect();
+
+  // Without arguments, the files included by the config file are linted.
+  let (root, include) = if !args.is_empty() {
+    (cwd.clone(), args)
+  } else if !config.include.is_empty() {
+    (config.base.clone(), config.include.clone())
+  } else {
+    (config.base.clone(), vec![".".to_string()])
+  };
+  let target_files = FileCollector::new(is_supported)
+    .exclude(&config.base, &config.exclude)
+    .collect(&root, &include)?;
+
+  let mut problems_count = 0;
+  let mut fixable_count = 0;
+  for file_path in target_files {
+    let source_code = fs::read_to_string(&file_path)?;
+    let file_name = file_path
+      .strip_prefix(&cwd)
+      .unwrap_or(&file_path)
+      .to_string_lossy()
+      .to_string();
+    let result = if fix {
+      lint_and_fix(&file_name, &source_code, &rules)
+    } else {
+      Linter::new()
+        .lint(&file_name, &source_code, &rules)
+        .map(|diagnostics| (source_code.clone(), diagnostics))
+    };
+    match result {
+      Ok((fixed_source_code, diagnostics)) => {
+        if fixed_source_code != source_code {
+          fs::write(&file_path, fixed_source_code)?;
+        }
+        for diagnostic in diagnostics {
+          problems_count += 1;
+          if diagnostic.fix.is_some() {
+            fixable_count += 1;
+          }
+          eprintln!(
+            "({}) {}\n    at {}:{}:{}",
+            colors::red(diagnostic.code),
+            diagnostic.message,
+            diagnostic.file_name,
+            diagnostic.line,
+            diagnostic.col
+          );
+        }
+      }
+      Err(e) => {
+        problems_count += 1;
+        eprintln!("Error linting: {}", file_name);
+        eprintln!("   {}", e);
+      }
+    }
+  }
+
+  if problems_count == 0 {
+    return Ok(());
+  }
+  let mut message = format!(
+    "Found {} {}",
+    problems_count,
+    if problems_count == 1 {
+      "problem"
+    } else {
+      "problems"
+    }
+  );
+  if fixable_count > 0 {
+    message.push_str(&format!(" ({} fixable with --fix)", fixable_count));
+  }
+  Err(OpError::other(message).into())
+}
+
+fn print_rules(config: &LintConfig, rules: &[Box<dyn LintRule>]) {
+  println!("{}", colors::bold("Available rules:".to_string()));
+  for rule in rules {
+    let mut notes = vec![];
+    if config.is_enabled(rule.as_ref()) {
+      notes.push("enabled".to_string());
+    }
+    if rule.recoverable() {
+      notes.push("recoverable".to_string());
+    }
+    if rule.fixable() {
+      notes.push("fixable".to_string());
+    }
+    notes.extend(rule.tags().iter().map(|tag| tag.to_string()));
+    if notes.is_empty() {
+      println!("- {}", colors::cyan(rule.code().to_string()));
+    } else {
+      println!(
+        "- {} ({})",
+        colors::cyan(rule.code().to_string()),
+        notes.join(", ")
+      );
+    }
+    println!("    {}", colors::gray(rule.docs_url()));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn read_config() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let config_path = temp_dir.path().join(task_runner::CONFIG_FILE_NAME);
+    fs::write(
+      &config_path,
+      r#"{
+        "lint": {
+          "files": { "include": ["src/"], "exclude": ["src/testdata/"] },
+          "rules": { "include": ["eqeqeq"], "exclude": ["no-var"] }
+        }
+      }"#,
+    )
+    .unwrap();
+    let config = LintConfig::read(&config_path).unwrap();
+    assert_eq!(config.tags, vec![RECOMMENDED_TAG.to_string()]);
+
+    assert_eq!(config.include, vec!["src/".to_string()]);
+    assert_eq!(config.exclude, vec!["src/testdata/".to_string()]);
+
+    let enabled: Vec<&str> = get_all_rules()
+      .into_iter()
+      .filter(|rule| config.is_enabled(rule.as_ref()))
+      .map(|rule| rule.code())
+      .collect();
+    assert_eq!(
+      enabled,
+      vec![
+        "eqeqeq",
+        "no-debugger",
+        "no-explicit-any",
+        "no-unused-imports",
+        "prefer-const"
+      ]
+    );
+  }
+
+  #[test]
+  fn unknown_rule() {
+    let mut config = LintConfig::new(PathBuf::from("/project"));
+    assert!(config.check_rules(&get_all_rules()).is_ok());
+    config.rules_exclude = vec!["no-such-rule".to_string()];
+    assert!(config.check_rules(&get_all_rules()).is_err());
+  }
+}
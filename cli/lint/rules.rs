@@ -0,0 +1,494 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::linter::Context;
+use super::linter::LintFix;
+use crate::swc_common::Span;
+use crate::swc_common::Spanned;
+use crate::swc_ecma_ast;
+use std::collections::HashSet;
+use swc_ecma_visit::Node;
+use swc_ecma_visit::Visit;
+
+pub trait LintRule {
+  /// Name of the rule, used in config files and ignore directives.
+  fn code(&self) -> &'static str;
+
+  /// Rules tagged "recommended" are enabled unless the config file selects
+  /// other tags.
+  fn tags(&self) -> &'static [&'static str] {
+    &[]
+  }
+
+  /// Whether code the rule reports still runs as intended, ie. the problem is
+  /// only one of style.
+  fn recoverable(&self) -> bool;
+
+  /// Whether `deno lint --fix` fixes the reported code.
+  fn fixable(&self) -> bool {
+    false
+  }
+
+  fn docs_url(&self) -> String {
+    format!("https://deno.land/manual/tools/linter#{}", self.code())
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module);
+}
+
+pub fn get_all_rules() -> Vec<Box<dyn LintRule>> {
+  vec![
+    Box::new(Eqeqeq),
+    Box::new(NoDebugger),
+    Box::new(NoExplicitAny),
+    Box::new(NoUnusedImports),
+    Box::new(NoVar),
+    Box::new(PreferConst),
+  ]
+}
+
+pub struct Eqeqeq;
+
+struct EqeqeqVisitor<'a> {
+  context: &'a mut Context,
+}
+
+impl<'a> EqeqeqVisitor<'a> {
+  /// Returns the fix replacing `op` with `strict_op`, which is the first
+  /// operator between the operands.
+  fn fix(
+    &self,
+    bin_expr: &swc_ecma_ast::BinExpr,
+    op: &str,
+    strict_op: &str,
+  ) -> Option<LintFix> {
+    let start = self.context.range(bin_expr.left.span()).end;
+    let end = self.context.range(bin_expr.right.span()).start;
+    let offset = self.context.source_code()[start..end].find(op)?;
+    Some(LintFix {
+      range: start + offset..start + offset + op.len(),
+      text: strict_op.to_string(),
+    })
+  }
+}
+
+impl<'a> Visit for EqeqeqVisitor<'a> {
+  fn visit_bin_expr(
+    &mut self,
+    bin_expr: &swc_ecma_ast::BinExpr,
+    parent: &dyn Node,
+  ) {
+    use swc_ecma_ast::BinaryOp::*;
+    let ops = match bin_expr.op {
+      EqEq => Some(("==", "===")),
+      NotEq => Some(("!=", "!==")),
+      _ => None,
+    };
+    if let Some((op, strict_op)) = ops {
+      let message =
+        format!("expected '{}' and instead saw '{}'", strict_op, op);
+      match self.fix(bin_expr, op, strict_op) {
+        Some(fix) => self.context.add_fixable_diagnostic(
+          bin_expr.span,
+          "eqeqeq",
+          &message,
+          fix,
+        ),
+        None => self
+          .context
+          .add_diagnostic(bin_expr.span, "eqeqeq", &message),
+      }
+    }
+    swc_ecma_visit::visit_bin_expr(self, bin_expr, parent);
+  }
+}
+
+impl LintRule for Eqeqeq {
+  fn code(&self) -> &'static str {
+    "eqeqeq"
+  }
+
+  fn recoverable(&self) -> bool {
+    false
+  }
+
+  fn fixable(&self) -> bool {
+    true
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    let mut visitor = EqeqeqVisitor { context };
+    visitor.visit_module(module, module);
+  }
+}
+
+pub struct NoDebugger;
+
+struct NoDebuggerVisitor<'a> {
+  context: &'a mut Context,
+}
+
+impl<'a> Visit for NoDebuggerVisitor<'a> {
+  fn visit_debugger_stmt(
+    &mut self,
+    debugger_stmt: &swc_ecma_ast::DebuggerStmt,
+    _parent: &dyn Node,
+  ) {
+    self.context.add_diagnostic(
+      debugger_stmt.span,
+      "no-debugger",
+      "`debugger` statement is not allowed",
+    );
+  }
+}
+
+impl LintRule for NoDebugger {
+  fn code(&self) -> &'static str {
+    "no-debugger"
+  }
+
+  fn tags(&self) -> &'static [&'static str] {
+    &["recommended"]
+  }
+
+  fn recoverable(&self) -> bool {
+    false
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    let mut visitor = NoDebuggerVisitor { context };
+    visitor.visit_module(module, module);
+  }
+}
+
+pub struct NoExplicitAny;
+
+struct NoExplicitAnyVisitor<'a> {
+  context: &'a mut Context,
+}
+
+impl<'a> Visit for NoExplicitAnyVisitor<'a> {
+  fn visit_ts_keyword_type(
+    &mut self,
+    ts_keyword_type: &swc_ecma_ast::TsKeywordType,
+    _parent: &dyn Node,
+  ) {
+    use swc_ecma_ast::TsKeywordTypeKind::*;
+    if ts_keyword_type.kind == TsAnyKeyword {
+      self.context.add_diagnostic(
+        ts_keyword_type.span,
+        "no-explicit-any",
+        "`any` type is not allowed",
+      );
+    }
+  }
+}
+
+impl LintRule for NoExplicitAny {
+  fn code(&self) -> &'static str {
+    "no-explicit-any"
+  }
+
+  fn tags(&self) -> &'static [&'static str] {
+    &["recommended"]
+  }
+
+  fn recoverable(&self) -> bool {
+    true
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    let mut visitor = NoExplicitAnyVisitor { context };
+    visitor.visit_module(module, module);
+  }
+}
+
+pub struct NoUnusedImports;
+
+struct NoUnusedImportsVisitor {
+  import_decls: Vec<swc_ecma_ast::ImportDecl>,
+  /// Names of the identifiers outside of import declarations.
+  used: HashSet<String>,
+}
+
+impl Visit for NoUnusedImportsVisitor {
+  fn visit_import_decl(
+    &mut self,
+    import_decl: &swc_ecma_ast::ImportDecl,
+    _parent: &dyn Node,
+  ) {
+    self.import_decls.push(import_decl.clone());
+  }
+
+  fn visit_ident(&mut self, ident: &swc_ecma_ast::Ident, _parent: &dyn Node) {
+    self.used.insert(ident.sym.to_string());
+  }
+}
+
+impl NoUnusedImports {
+  /// Returns the fix removing the unused specifiers of `import_decl`, or the
+  /// whole declaration if none of them is used.
+  fn fix(
+    context: &Context,
+    import_decl: &swc_ecma_ast::ImportDecl,
+    used: &[&swc_ecma_ast::ImportSpecifier],
+  ) -> LintFix {
+    use swc_ecma_ast::ImportSpecifier::*;
+    let mut range = context.range(import_decl.span);
+    if used.is_empty() {
+      let rest = &context.source_code()[range.end..];
+      if rest.starts_with("\r\n") {
+        range.end += 2;
+      } else if rest.starts_with('\n') {
+        range.end += 1;
+      }
+      return LintFix {
+        range,
+        text: "".to_string(),
+      };
+    }
+
+    let mut clauses = vec![];
+    let mut named = vec![];
+    for specifier in used {
+      match specifier {
+        Default(_) | Namespace(_) => {
+          clauses.push(context.snippet(specifier.span()).to_string())
+        }
+        Specific(_) => named.push(context.snippet(specifier.span())),
+      }
+    }
+    if !named.is_empty() {
+      clauses.push(format!("{{ {} }}", named.join(", ")));
+    }
+    let semicolon = if context.snippet(import_decl.span).ends_with(';') {
+      ";"
+    } else {
+      ""
+    };
+    LintFix {
+      range,
+      text: format!(
+        "import {} from {}{}",
+        clauses.join(", "),
+        context.snippet(import_decl.src.span),
+        semicolon
+      ),
+    }
+  }
+}
+
+impl LintRule for NoUnusedImports {
+  fn code(&self) -> &'static str {
+    "no-unused-imports"
+  }
+
+  fn tags(&self) -> &'static [&'static str] {
+    &["recommended"]
+  }
+
+  fn recoverable(&self) -> bool {
+    true
+  }
+
+  fn fixable(&self) -> bool {
+    true
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    use swc_ecma_ast::ImportSpecifier::*;
+    let mut visitor = NoUnusedImportsVisitor {
+      import_decls: vec![],
+      used: HashSet::new(),
+    };
+    visitor.visit_module(module, module);
+
+    for import_decl in &visitor.import_decls {
+      let (used, unused): (Vec<_>, Vec<_>) =
+        import_decl.specifiers.iter().partition(|specifier| {
+          let local = match specifier {
+            Specific(specifier) => &specifier.local,
+            Default(specifier) => &specifier.local,
+            Namespace(specifier) => &specifier.local,
+          };
+          visitor.used.contains(&local.sym.to_string())
+        });
+      if unused.is_empty() {
+        continue;
+      }
+      let fix = NoUnusedImports::fix(context, import_decl, &used);
+      for specifier in unused {
+        let message = format!(
+          "'{}' is imported but never used",
+          context.snippet(specifier.span())
+        );
+        context.add_fixable_diagnostic(
+          specifier.span(),
+          "no-unused-imports",
+          &message,
+          fix.clone(),
+        );
+      }
+    }
+  }
+}
+
+pub struct NoVar;
+
+struct NoVarVisitor<'a> {
+  context: &'a mut Context,
+}
+
+impl<'a> Visit for NoVarVisitor<'a> {
+  fn visit_var_decl(
+    &mut self,
+    var_decl: &swc_ecma_ast::VarDecl,
+    parent: &dyn Node,
+  ) {
+    if var_decl.kind == swc_ecma_ast::VarDeclKind::Var {
+      self.context.add_diagnostic(
+        var_decl.span,
+        "no-var",
+        "`var` keyword is not allowed",
+      );
+    }
+    swc_ecma_visit::visit_var_decl(self, var_decl, parent);
+  }
+}
+
+impl LintRule for NoVar {
+  fn code(&self) -> &'static str {
+    "no-var"
+  }
+
+  fn tags(&self) -> &'static [&'static str] {
+    &["recommended"]
+  }
+
+  fn recoverable(&self) -> bool {
+    true
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    let mut visitor = NoVarVisitor { context };
+    visitor.visit_module(module, module);
+  }
+}
+
+pub struct PreferConst;
+
+struct PreferConstVisitor {
+  /// `let` declarations, with the names they declare.
+  let_decls: Vec<(Span, Vec<String>)>,
+  /// Names of the identifiers assigned to after their declaration.
+  reassigned: HashSet<String>,
+}
+
+/// Collects the names of the identifiers in an assignment target.
+struct AssignTargetVisitor<'a> {
+  reassigned: &'a mut HashSet<String>,
+}
+
+impl<'a> Visit for AssignTargetVisitor<'a> {
+  fn visit_ident(&mut self, ident: &swc_ecma_ast::Ident, _parent: &dyn Node) {
+    self.reassigned.insert(ident.sym.to_string());
+  }
+}
+
+impl Visit for PreferConstVisitor {
+  fn visit_var_decl(
+    &mut self,
+    var_decl: &swc_ecma_ast::VarDecl,
+    parent: &dyn Node,
+  ) {
+    // Only plain `let x = ...` declarations are considered; the ones
+    // without initializer, eg. in `for...of`, or destructuring are not.
+    if var_decl.kind == swc_ecma_ast::VarDeclKind::Let && !var_decl.declare {
+      let names: Option<Vec<String>> = var_decl
+        .decls
+        .iter()
+        .map(|decl| match (&decl.name, &decl.init) {
+          (swc_ecma_ast::Pat::Ident(ident), Some(_)) => {
+            Some(ident.sym.to_string())
+          }
+          _ => None,
+        })
+        .collect();
+      if let Some(names) = names {
+        self.let_decls.push((var_decl.span, names));
+      }
+    }
+    swc_ecma_visit::visit_var_decl(self, var_decl, parent);
+  }
+
+  fn visit_assign_expr(
+    &mut self,
+    assign_expr: &swc_ecma_ast::AssignExpr,
+    parent: &dyn Node,
+  ) {
+    let mut target_visitor = AssignTargetVisitor {
+      reassigned: &mut self.reassigned,
+    };
+    match &assign_expr.left {
+      swc_ecma_ast::PatOrExpr::Pat(pat) => {
+        target_visitor.visit_pat(pat, assign_expr)
+      }
+      swc_ecma_ast::PatOrExpr::Expr(expr) => {
+        target_visitor.visit_expr(expr, assign_expr)
+      }
+    }
+    swc_ecma_visit::visit_assign_expr(self, assign_expr, parent);
+  }
+
+  fn visit_update_expr(
+    &mut self,
+    update_expr: &swc_ecma_ast::UpdateExpr,
+    parent: &dyn Node,
+  ) {
+    let mut target_visitor = AssignTargetVisitor {
+      reassigned: &mut self.reassigned,
+    };
+    target_visitor.visit_expr(&update_expr.arg, update_expr);
+    swc_ecma_visit::visit_update_expr(self, update_expr, parent);
+  }
+}
+
+impl LintRule for PreferConst {
+  fn code(&self) -> &'static str {
+    "prefer-const"
+  }
+
+  fn tags(&self) -> &'static [&'static str] {
+    &["recommended"]
+  }
+
+  fn recoverable(&self) -> bool {
+    true
+  }
+
+  fn fixable(&self) -> bool {
+    true
+  }
+
+  fn lint_module(&self, context: &mut Context, module: &swc_ecma_ast::Module) {
+    let mut visitor = PreferConstVisitor {
+      let_decls: vec![],
+      reassigned: HashSet::new(),
+    };
+    visitor.visit_module(module, module);
+
+    // Names are compared without regard to scopes, so a `let` is only
+    // reported if no variable of the same name is ever reassigned.
+    for (span, names) in visitor.let_decls {
+      if names.iter().any(|name| visitor.reassigned.contains(name)) {
+        continue;
+      }
+      let start = context.range(span).start;
+      let fix = LintFix {
+        range: start..start + "let".len(),
+        text: "const".to_string(),
+      };
+      let message =
+        format!("'{}' is never reassigned, use 'const' instead", names[0]);
+      context.add_fixable_diagnostic(span, "prefer-const", &message, fix);
+    }
+  }
+}
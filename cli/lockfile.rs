@@ -2,6 +2,7 @@ use crate::tsc::CompiledModule;
 use serde_json::json;
 pub use serde_json::Value;
 use std::collections::HashMap;
+use std::io::Error;
 use std::io::Result;
 
 pub struct Lockfile {
@@ -35,7 +36,12 @@ impl Lockfile {
 
   pub fn read(&mut self) -> Result<()> {
     debug!("lockfile read {}", self.filename);
-    let s = std::fs::read_to_string(&self.filename)?;
+    let s = std::fs::read_to_string(&self.filename).map_err(|e| {
+      Error::new(
+        e.kind(),
+        format!("Unable to read lock file {}: {}", self.filename, e),
+      )
+    })?;
     self.map = serde_json::from_str(&s)?;
     self.need_read = false;
     Ok(())
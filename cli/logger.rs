@@ -0,0 +1,231 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Logger for the diagnostic messages printed to stderr.
+//!
+//! The level is set with `--log-level` (or `--quiet`) and defaults to `info`.
+//! It can be adjusted per module with `RUST_LOG`-style directives in the
+//! `RUST_LOG` environment variable, eg. `RUST_LOG=deno::tsc=debug,warn`.
+//! Levels given on the command line take precedence over a bare level in
+//! `RUST_LOG`, but not over module directives.
+//!
+//! With `--log-json`, each message is printed as a single line of JSON.
+
+use crate::colors;
+use log::Level;
+use log::LevelFilter;
+use log::Metadata;
+use log::Record;
+use std::io::Write;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq)]
+struct Directive {
+  /// Module path prefix, `None` for the default level.
+  module: Option<String>,
+  level: LevelFilter,
+}
+
+/// Parses a comma separated list of `level`, `module` or `module=level`
+/// directives. A module without a level logs everything.
+fn parse_directives(spec: &str) -> Result<Vec<Directive>, String> {
+  let mut directives = vec![];
+  for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+    let mut parts = part.splitn(2, '=');
+    let name = parts.next().unwrap().trim();
+    let directive = match parts.next() {
+      Some(level) => Directive {
+        module: Some(name.to_string()),
+        level: LevelFilter::from_str(level.trim())
+          .map_err(|_| format!("Invalid log level \"{}\"", level))?,
+      },
+      None => match LevelFilter::from_str(name) {
+        Ok(level) => Directive {
+          module: None,
+          level,
+        },
+        Err(_) => Directive {
+          module: Some(name.to_string()),
+          level: LevelFilter::Trace,
+        },
+      },
+    };
+    directives.push(directive);
+  }
+  Ok(directives)
+}
+
+fn matches_module(target: &str, module: &str) -> bool {
+  target == module
+    || (target.starts_with(module) && target[module.len()..].starts_with("::"))
+}
+
+pub struct Logger {
+  default_level: LevelFilter,
+  /// Module directives, the most specific module first.
+  directives: Vec<Directive>,
+  json: bool,
+}
+
+impl Logger {
+  /// `level` is the level given on the command line, `spec` the value of
+  /// `RUST_LOG`.
+  pub fn new(
+    level: Option<Level>,
+    spec: Option<&str>,
+    json: bool,
+  ) -> Result<Self, String> {
+    let directives = match spec {
+      Some(spec) => parse_directives(spec)?,
+      None => vec![],
+    };
+    let spec_level = directives
+      .iter()
+      .rev()
+      .find(|d| d.module.is_none())
+      .map(|d| d.level);
+    let default_level = level
+      .map(|l| l.to_level_filter())
+      .or(spec_level)
+      .unwrap_or(LevelFilter::Info);
+    let mut directives: Vec<Directive> = directives
+      .into_iter()
+      .filter(|d| d.module.is_some())
+      .collect();
+    directives
+      .sort_by_key(|d| std::cmp::Reverse(d.module.as_ref().unwrap().len()));
+
+    Ok(Self {
+      default_level,
+      directives,
+      json,
+    })
+  }
+
+  /// The most verbose level enabled for any module.
+  pub fn max_level(&self) -> LevelFilter {
+    self
+      .directives
+      .iter()
+      .map(|d| d.level)
+      .fold(self.default_level, std::cmp::max)
+  }
+
+  fn level_for(&self, target: &str) -> LevelFilter {
+    self
+      .directives
+      .iter()
+      .find(|d| matches_module(target, d.module.as_ref().unwrap()))
+      .map_or(self.default_level, |d| d.level)
+  }
+
+  fn format(&self, record: &Record) -> String {
+    let message = record.args().to_string();
+    if self.json {
+      return json!({
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "message": colors::strip_ansi_codes(&message),
+      })
+      .to_string();
+    }
+    match record.level() {
+      Level::Error => {
+        format!("{}: {}", colors::red_bold("error".to_string()), message)
+      }
+      Level::Warn => {
+        format!("{} {}", colors::yellow("Warning".to_string()), message)
+      }
+      Level::Info => message,
+      Level::Debug | Level::Trace => {
+        let mut target = record.target().to_string();
+        if let Some(line_no) = record.line() {
+          target.push_str(":");
+          target.push_str(&line_no.to_string());
+        }
+        format!("{} RS - {} - {}", record.level(), target, message)
+      }
+    }
+  }
+}
+
+impl log::Log for Logger {
+  fn enabled(&self, metadata: &Metadata) -> bool {
+    metadata.level() <= self.level_for(metadata.target())
+  }
+
+  fn log(&self, record: &Record) {
+    if self.enabled(record.metadata()) {
+      let line = self.format(record);
+      // Logging must not fail, eg. when stderr is closed.
+      let _ = writeln!(std::io::stderr(), "{}", line);
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_directives_spec() {
+    assert_eq!(
+      parse_directives("warn, deno::tsc=debug,deno_core").unwrap(),
+      vec![
+        Directive {
+          module: None,
+          level: LevelFilter::Warn,
+        },
+        Directive {
+          module: Some("deno::tsc".to_string()),
+          level: LevelFilter::Debug,
+        },
+        Directive {
+          module: Some("deno_core".to_string()),
+          level: LevelFilter::Trace,
+        },
+      ]
+    );
+    assert!(parse_directives("deno=loud").is_err());
+  }
+
+  #[test]
+  fn module_levels() {
+    let logger =
+      Logger::new(None, Some("error,deno=info,deno::tsc=trace"), false)
+        .unwrap();
+    assert_eq!(logger.level_for("deno_core::isolate"), LevelFilter::Error);
+    assert_eq!(logger.level_for("deno::worker"), LevelFilter::Info);
+    assert_eq!(logger.level_for("deno::tsc"), LevelFilter::Trace);
+    assert_eq!(logger.level_for("deno::tsc_config"), LevelFilter::Info);
+    assert_eq!(logger.max_level(), LevelFilter::Trace);
+  }
+
+  #[test]
+  fn flag_level_takes_precedence() {
+    let logger =
+      Logger::new(Some(Level::Debug), Some("error,deno::tsc=warn"), false)
+        .unwrap();
+    assert_eq!(logger.level_for("deno::worker"), LevelFilter::Debug);
+    assert_eq!(logger.level_for("deno::tsc"), LevelFilter::Warn);
+    let logger = Logger::new(None, None, false).unwrap();
+    assert_eq!(logger.max_level(), LevelFilter::Info);
+  }
+
+  #[test]
+  fn format_json() {
+    let logger = Logger::new(None, None, true).unwrap();
+    let line = logger.format(
+      &Record::builder()
+        .args(format_args!("{} done", "\x1b[32mDownload\x1b[0m"))
+        .level(Level::Info)
+        .target("deno::file_fetcher")
+        .build(),
+    );
+    assert_eq!(
+      line,
+      r#"{"level":"INFO","target":"deno::file_fetcher","message":"Download done"}"#
+    );
+  }
+}
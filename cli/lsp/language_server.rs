@@ -0,0 +1,426 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Handling of Language Server Protocol requests and notifications.
+//!
+//! Diagnostics, hover and go to definition are answered by the TypeScript
+//! language service running in the compiler isolate (see
+//! `tsc::language_service`); formatting uses `deno fmt`.
+
+use crate::fmt::format_text;
+use crate::global_state::GlobalState;
+use crate::tsc;
+use crate::version;
+use deno_core::ErrBox;
+use serde_json::Value;
+use std::collections::HashMap;
+use url::Url;
+
+// JSON-RPC error codes.
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// TS7016: Could not find a declaration file for module '...'.
+const NO_DECLARATION_FILE: u64 = 7016;
+
+struct ResponseError {
+  code: i64,
+  message: String,
+}
+
+impl ResponseError {
+  fn invalid_params(message: &str) -> Self {
+    Self {
+      code: INVALID_PARAMS,
+      message: message.to_string(),
+    }
+  }
+}
+
+impl From<ErrBox> for ResponseError {
+  fn from(err: ErrBox) -> Self {
+    Self {
+      code: INTERNAL_ERROR,
+      message: err.to_string(),
+    }
+  }
+}
+
+pub struct LanguageServer {
+  global_state: GlobalState,
+  /// Contents of the documents open in the editor, by URI.
+  documents: HashMap<String, String>,
+  shutdown_requested: bool,
+  /// Set once the client sent `exit`.
+  pub exit_code: Option<i32>,
+}
+
+impl LanguageServer {
+  pub fn new(global_state: GlobalState) -> Self {
+    Self {
+      global_state,
+      documents: HashMap::new(),
+      shutdown_requested: false,
+      exit_code: None,
+    }
+  }
+
+  /// Handles a message from the client and returns messages to send back.
+  pub async fn handle_message(&mut self, message: Value) -> Vec<Value> {
+    let method = match message.get("method").and_then(Value::as_str) {
+      Some(method) => method.to_string(),
+      // Responses to requests of the server, which doesn't send any.
+      None => return vec![],
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    match message.get("id").cloned() {
+      Some(id) => {
+        let response = match self.handle_request(&method, params).await {
+          Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+          Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+          }),
+        };
+        vec![response]
+      }
+      None => self.handle_notification(&method, params).await,
+    }
+  }
+
+  async fn handle_request(
+    &mut self,
+    method: &str,
+    params: Value,
+  ) -> Result<Value, ResponseError> {
+    if self.shutdown_requested {
+      return Err(ResponseError {
+        code: INVALID_REQUEST,
+        message: "Server is shutting down".to_string(),
+      });
+    }
+    match method {
+      "initialize" => Ok(json!({
+        "capabilities": {
+          // Documents are synced by sending their full content.
+          "textDocumentSync": 1,
+          "hoverProvider": true,
+          "definitionProvider": true,
+          "documentFormattingProvider": true,
+          "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+        },
+        "serverInfo": {
+          "name": "deno-language-server",
+          "version": version::DENO,
+        },
+      })),
+      "shutdown" => {
+        self.shutdown_requested = true;
+        Ok(Value::Null)
+      }
+      "textDocument/hover" => {
+        let (specifier, position) = self.document_position(&params)?;
+        let hover = tsc::language_service(
+          self.global_state.clone(),
+          "hover",
+          &specifier,
+          &self.documents,
+          Some(position),
+        )
+        .await?;
+        Ok(hover)
+      }
+      "textDocument/definition" => {
+        let (specifier, position) = self.document_position(&params)?;
+        let definitions = tsc::language_service(
+          self.global_state.clone(),
+          "definition",
+          &specifier,
+          &self.documents,
+          Some(position),
+        )
+        .await?;
+        Ok(self.map_definitions(definitions))
+      }
+      "textDocument/formatting" => {
+        let uri = document_uri(&params)?;
+        let text = self
+          .documents
+          .get(&uri)
+          .ok_or_else(|| ResponseError::invalid_params("Unknown document"))?;
+        Ok(format_edits(text)?)
+      }
+      "textDocument/codeAction" => {
+        let uri = document_uri(&params)?;
+        let diagnostics = params["context"]["diagnostics"]
+          .as_array()
+          .cloned()
+          .unwrap_or_default();
+        Ok(Value::Array(code_actions(&uri, &diagnostics)))
+      }
+      _ => Err(ResponseError {
+        code: METHOD_NOT_FOUND,
+        message: format!("Unhandled method {}", method),
+      }),
+    }
+  }
+
+  async fn handle_notification(
+    &mut self,
+    method: &str,
+    params: Value,
+  ) -> Vec<Value> {
+    match method {
+      "exit" => {
+        self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+        vec![]
+      }
+      "textDocument/didOpen" => {
+        let document = &params["textDocument"];
+        match (document["uri"].as_str(), document["text"].as_str()) {
+          (Some(uri), Some(text)) => {
+            let uri = normalize_uri(uri);
+            self.documents.insert(uri.clone(), text.to_string());
+            self.publish_diagnostics(uri).await
+          }
+          _ => vec![],
+        }
+      }
+      "textDocument/didChange" => {
+        let uri = match document_uri(&params) {
+          Ok(uri) => uri,
+          Err(_) => return vec![],
+        };
+        // With full sync, the last change holds the whole document.
+        let text = params["contentChanges"]
+          .as_array()
+          .and_then(|changes| changes.last())
+          .and_then(|change| change["text"].as_str());
+        match text {
+          Some(text) => {
+            self.documents.insert(uri.clone(), text.to_string());
+            self.publish_diagnostics(uri).await
+          }
+          None => vec![],
+        }
+      }
+      "textDocument/didClose" => match document_uri(&params) {
+        Ok(uri) => {
+          self.documents.remove(&uri);
+          vec![diagnostics_notification(&uri, vec![])]
+        }
+        Err(_) => vec![],
+      },
+      // `initialized`, `$/cancelRequest`, etc.
+      _ => vec![],
+    }
+  }
+
+  fn document_position(
+    &self,
+    params: &Value,
+  ) -> Result<(String, Value), ResponseError> {
+    let uri = document_uri(params)?;
+    if !self.documents.contains_key(&uri) {
+      return Err(ResponseError::invalid_params("Unknown document"));
+    }
+    let position = params
+      .get("position")
+      .cloned()
+      .ok_or_else(|| ResponseError::invalid_params("Missing position"))?;
+    Ok((uri, position))
+  }
+
+  async fn publish_diagnostics(&self, uri: String) -> Vec<Value> {
+    if !uri.starts_with("file:") {
+      return vec![];
+    }
+    let result = tsc::language_service(
+      self.global_state.clone(),
+      "diagnostics",
+      &uri,
+      &self.documents,
+      None,
+    )
+    .await;
+    match result {
+      Ok(Value::Array(diagnostics)) => {
+        vec![diagnostics_notification(&uri, diagnostics)]
+      }
+      Ok(_) => vec![],
+      Err(err) => vec![log_message(&format!(
+        "Failed to get diagnostics for {}: {}",
+        uri, err
+      ))],
+    }
+  }
+
+  /// Remote modules are mapped to their copy in `DENO_DIR`, so the editor
+  /// can open them. Definitions in the built-in libs are dropped.
+  fn map_definitions(&self, definitions: Value) -> Value {
+    let definitions = match definitions {
+      Value::Array(definitions) => definitions,
+      _ => return json!([]),
+    };
+    let http_cache = &self.global_state.file_fetcher.http_cache;
+    let locations = definitions
+      .into_iter()
+      .filter_map(|mut definition| {
+        let url = Url::parse(definition["uri"].as_str()?).ok()?;
+        let uri = match url.scheme() {
+          "file" => url,
+          "http" | "https" => {
+            Url::from_file_path(http_cache.get_cache_filename(&url)).ok()?
+          }
+          _ => return None,
+        };
+        definition["uri"] = json!(uri.as_str());
+        Some(definition)
+      })
+      .collect();
+    Value::Array(locations)
+  }
+}
+
+/// Editors may escape URIs differently than `Url` does, eg. the drive letter
+/// colon on Windows; documents are stored under the normalized form.
+fn normalize_uri(uri: &str) -> String {
+  match Url::parse(uri) {
+    Ok(url) => url.to_string(),
+    Err(_) => uri.to_string(),
+  }
+}
+
+fn document_uri(params: &Value) -> Result<String, ResponseError> {
+  params["textDocument"]["uri"]
+    .as_str()
+    .map(normalize_uri)
+    .ok_or_else(|| ResponseError::invalid_params("Missing textDocument.uri"))
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Value>) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "method": "textDocument/publishDiagnostics",
+    "params": { "uri": uri, "diagnostics": diagnostics },
+  })
+}
+
+fn log_message(message: &str) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "method": "window/logMessage",
+    // MessageType.Error
+    "params": { "type": 1, "message": message },
+  })
+}
+
+/// Position of the end of `text`, in UTF-16 code units like LSP expects.
+fn end_position(text: &str) -> Value {
+  let line = text.matches('\n').count();
+  let last_line = text.rsplit('\n').next().unwrap_or("");
+  json!({ "line": line, "character": last_line.encode_utf16().count() })
+}
+
+/// Returns an edit replacing the whole document with its formatted version,
+/// or no edits if it's formatted already.
+fn format_edits(text: &str) -> Result<Value, ErrBox> {
+  let formatted = format_text(text)?;
+  if formatted == text {
+    return Ok(json!([]));
+  }
+  Ok(json!([{
+    "range": {
+      "start": { "line": 0, "character": 0 },
+      "end": end_position(text),
+    },
+    "newText": formatted,
+  }]))
+}
+
+/// Suggests a `@deno-types` directive for imports of JavaScript modules that
+/// have no type declarations.
+fn code_actions(uri: &str, diagnostics: &[Value]) -> Vec<Value> {
+  diagnostics
+    .iter()
+    .filter(|diagnostic| {
+      diagnostic["code"].as_u64() == Some(NO_DECLARATION_FILE)
+    })
+    .filter_map(|diagnostic| {
+      // The message quotes the module specifier first.
+      let message = diagnostic["message"].as_str()?;
+      let specifier = message.split('\'').nth(1)?;
+      let types = match specifier.rfind('.') {
+        Some(i) => format!("{}.d.ts", &specifier[..i]),
+        None => format!("{}.d.ts", specifier),
+      };
+      let line = diagnostic["range"]["start"]["line"].as_u64()?;
+      let position = json!({ "line": line, "character": 0 });
+      Some(json!({
+        "title": format!("Add @deno-types=\"{}\"", types),
+        "kind": "quickfix",
+        "diagnostics": [diagnostic],
+        "edit": {
+          "changes": {
+            uri: [{
+              "range": { "start": position, "end": position },
+              "newText": format!("// @deno-types=\"{}\"\n", types),
+            }],
+          },
+        },
+      }))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn end_position_utf16() {
+    assert_eq!(end_position(""), json!({ "line": 0, "character": 0 }));
+    assert_eq!(end_position("a\n"), json!({ "line": 1, "character": 0 }));
+    assert_eq!(end_position("a\nb😀"), json!({ "line": 1, "character": 3 }));
+  }
+
+  #[test]
+  fn format_document() {
+    assert_eq!(format_edits("const a = 1;\n").unwrap(), json!([]));
+    let edits = format_edits("const a  =  1").unwrap();
+    assert_eq!(edits[0]["newText"], json!("const a = 1;\n"));
+    assert_eq!(
+      edits[0]["range"]["end"],
+      json!({ "line": 0, "character": 13 })
+    );
+  }
+
+  #[test]
+  fn deno_types_code_action() {
+    let diagnostic = json!({
+      "range": {
+        "start": { "line": 2, "character": 16 },
+        "end": { "line": 2, "character": 26 },
+      },
+      "code": 7016,
+      "message": "Could not find a declaration file for module './foo.js'.",
+    });
+    let other = json!({ "code": 2304, "message": "Cannot find name 'x'." });
+    let actions = code_actions("file:///a.ts", &[diagnostic, other]);
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0]["title"], json!("Add @deno-types=\"./foo.d.ts\""));
+    assert_eq!(
+      actions[0]["edit"]["changes"]["file:///a.ts"][0],
+      json!({
+        "range": {
+          "start": { "line": 2, "character": 0 },
+          "end": { "line": 2, "character": 0 },
+        },
+        "newText": "// @deno-types=\"./foo.d.ts\"\n",
+      })
+    );
+  }
+}
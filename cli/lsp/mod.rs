@@ -0,0 +1,52 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! `deno lsp` runs a language server speaking the Language Server Protocol
+//! over stdio, so editors get diagnostics, hover, go to definition,
+//! formatting and code actions backed by the same compiler as `deno run`.
+
+mod language_server;
+mod transport;
+
+use crate::global_state::GlobalState;
+use deno_core::ErrBox;
+use futures::channel::mpsc;
+use futures::stream::StreamExt;
+use language_server::LanguageServer;
+use std::io;
+use std::thread;
+
+/// Serves requests until the client sends `exit`. Returns the exit code of
+/// the process: 0 if the client asked the server to shut down first.
+pub async fn start(global_state: GlobalState) -> Result<i32, ErrBox> {
+  // Reading stdin blocks, so it's done on a separate thread while requests
+  // are answered on this one.
+  let (sender, mut receiver) = mpsc::unbounded();
+  thread::spawn(move || {
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    loop {
+      let message = transport::read_message(&mut reader);
+      let done = !matches!(message, Ok(Some(_)));
+      if sender.unbounded_send(message).is_err() || done {
+        break;
+      }
+    }
+  });
+
+  let mut server = LanguageServer::new(global_state);
+  let stdout = io::stdout();
+  while let Some(message) = receiver.next().await {
+    let message = match message? {
+      Some(message) => message,
+      // The client closed the connection without sending `exit`.
+      None => return Ok(1),
+    };
+    for response in server.handle_message(message).await {
+      transport::write_message(&mut stdout.lock(), &response)?;
+    }
+    if let Some(exit_code) = server.exit_code {
+      return Ok(exit_code);
+    }
+  }
+  Ok(1)
+}
@@ -0,0 +1,102 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Framing of JSON-RPC messages exchanged with the editor: each message is
+//! preceded by a `Content-Length` header and an empty line.
+
+use serde_json::Value;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+fn invalid_data(msg: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Reads the next message. Returns `None` once the input is closed.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+  let mut content_length: Option<usize> = None;
+  loop {
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 {
+      return Ok(None);
+    }
+    let header = header.trim_end();
+    if header.is_empty() {
+      break;
+    }
+    let mut parts = header.splitn(2, ':');
+    let name = parts.next().unwrap().trim();
+    let value = parts.next().unwrap_or("").trim();
+    // Other headers, ie. `Content-Type`, are ignored.
+    if name.eq_ignore_ascii_case("Content-Length") {
+      let length = value.parse::<usize>().map_err(|_| {
+        invalid_data(format!("Invalid Content-Length: {}", value))
+      })?;
+      content_length = Some(length);
+    }
+  }
+
+  let content_length = content_length
+    .ok_or_else(|| invalid_data("Missing Content-Length".to_string()))?;
+  let mut content = vec![0; content_length];
+  reader.read_exact(&mut content)?;
+  let message = serde_json::from_slice(&content)
+    .map_err(|e| invalid_data(format!("Invalid message: {}", e)))?;
+  Ok(Some(message))
+}
+
+pub fn write_message(
+  writer: &mut impl Write,
+  message: &Value,
+) -> io::Result<()> {
+  let content = message.to_string();
+  write!(
+    writer,
+    "Content-Length: {}\r\n\r\n{}",
+    content.len(),
+    content
+  )?;
+  writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn read_write_roundtrip() {
+    let mut buf = vec![];
+    write_message(&mut buf, &json!({ "jsonrpc": "2.0", "method": "exit" }))
+      .unwrap();
+    write_message(&mut buf, &json!({ "jsonrpc": "2.0", "id": 1 })).unwrap();
+
+    let mut reader = io::Cursor::new(buf);
+    assert_eq!(
+      read_message(&mut reader).unwrap(),
+      Some(json!({ "jsonrpc": "2.0", "method": "exit" }))
+    );
+    assert_eq!(
+      read_message(&mut reader).unwrap(),
+      Some(json!({ "jsonrpc": "2.0", "id": 1 }))
+    );
+    assert_eq!(read_message(&mut reader).unwrap(), None);
+  }
+
+  #[test]
+  fn read_with_content_type() {
+    let content = r#"{"id":1}"#;
+    let input = format!(
+      "Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: {}\r\n\r\n{}",
+      content.len(),
+      content
+    );
+    let mut reader = io::Cursor::new(input.into_bytes());
+    assert_eq!(read_message(&mut reader).unwrap(), Some(json!({ "id": 1 })));
+  }
+
+  #[test]
+  fn read_missing_content_length() {
+    let mut reader = io::Cursor::new(b"Content-Type: x\r\n\r\n{}".to_vec());
+    assert!(read_message(&mut reader).is_err());
+  }
+}
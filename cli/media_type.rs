@@ -0,0 +1,359 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Resolution of the media type of modules, from their extension and, for
+//! remote modules, the `Content-Type` header sent by the server.
+
+use crate::msg::MediaType;
+use crate::op_error::OpError;
+use deno_core::ErrBox;
+use std::path::Path;
+use std::path::PathBuf;
+use url::Url;
+
+/// Media type of a local file, or a remote module without `Content-Type`,
+/// from its extension.
+pub fn map_file_extension(path: &Path) -> MediaType {
+  match path.extension() {
+    None => MediaType::Unknown,
+    Some(os_str) => match os_str.to_str() {
+      Some("ts") => MediaType::TypeScript,
+      Some("tsx") => MediaType::TSX,
+      Some("js") => MediaType::JavaScript,
+      Some("jsx") => MediaType::JSX,
+      Some("mjs") => MediaType::JavaScript,
+      Some("json") => MediaType::Json,
+      Some("wasm") => MediaType::Wasm,
+      _ => MediaType::Unknown,
+    },
+  }
+}
+
+/// Media type from `Content-Type`, refined by the extension where the
+/// content type is ambiguous, eg. TSX served as `application/typescript`.
+pub fn map_content_type(path: &Path, content_type: Option<&str>) -> MediaType {
+  match content_type {
+    Some(content_type) => {
+      // sometimes there is additional data after the media type in
+      // Content-Type so we have to do a bit of manipulation so we are only
+      // dealing with the actual media type
+      let ct_vector: Vec<&str> = content_type.split(';').collect();
+      let ct: &str = ct_vector.first().unwrap();
+      match ct.to_lowercase().as_ref() {
+        "application/typescript"
+        | "text/typescript"
+        | "video/vnd.dlna.mpeg-tts"
+        | "video/mp2t"
+        | "application/x-typescript" => {
+          map_js_like_extension(path, MediaType::TypeScript)
+        }
+        "application/javascript"
+        | "text/javascript"
+        | "application/ecmascript"
+        | "text/ecmascript"
+        | "application/x-javascript" => {
+          map_js_like_extension(path, MediaType::JavaScript)
+        }
+        "text/jsx" => MediaType::JSX,
+        "text/tsx" => MediaType::TSX,
+        "application/json" | "text/json" => MediaType::Json,
+        "application/wasm" => MediaType::Wasm,
+        // Handle plain and possibly webassembly
+        "text/plain" | "application/octet-stream" => map_file_extension(path),
+        _ => {
+          debug!("unknown content type: {}", content_type);
+          MediaType::Unknown
+        }
+      }
+    }
+    None => map_file_extension(path),
+  }
+}
+
+fn map_js_like_extension(path: &Path, default: MediaType) -> MediaType {
+  match path.extension() {
+    None => default,
+    Some(os_str) => match os_str.to_str() {
+      None => default,
+      Some("jsx") => MediaType::JSX,
+      Some("tsx") => MediaType::TSX,
+      // Declaration files are never JavaScript, whatever the server says.
+      Some("ts") if path.to_string_lossy().ends_with(".d.ts") => {
+        MediaType::TypeScript
+      }
+      Some(_) => default,
+    },
+  }
+}
+
+/// Media type of a remote module. Servers often send a wrong or generic
+/// `Content-Type`, so the extension is used to refine it; a module that still
+/// can't be classified, eg. an HTML page, is an error rather than being run
+/// as JavaScript.
+pub fn map_remote(
+  url: &Url,
+  content_type: Option<&str>,
+) -> Result<MediaType, ErrBox> {
+  let path = PathBuf::from(url.path());
+  match map_content_type(&path, content_type) {
+    MediaType::Unknown => {
+      let reason = match content_type {
+        Some(content_type) => {
+          format!("unsupported Content-Type \"{}\"", content_type)
+        }
+        None => "unknown extension and no Content-Type".to_string(),
+      };
+      Err(
+        OpError::other(format!(
+          "Cannot determine the media type of \"{}\": {}. Expected JavaScript, TypeScript, JSON or WebAssembly.",
+          url, reason
+        ))
+        .into(),
+      )
+    }
+    media_type => Ok(media_type),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_map_file_extension() {
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.ts")),
+      MediaType::TypeScript
+    );
+    assert_eq!(map_file_extension(Path::new("foo/bar.tsx")), MediaType::TSX);
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.d.ts")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.js")),
+      MediaType::JavaScript
+    );
+    assert_eq!(map_file_extension(Path::new("foo/bar.jsx")), MediaType::JSX);
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.json")),
+      MediaType::Json
+    );
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.wasm")),
+      MediaType::Wasm
+    );
+    assert_eq!(
+      map_file_extension(Path::new("foo/bar.txt")),
+      MediaType::Unknown
+    );
+    assert_eq!(map_file_extension(Path::new("foo/bar")), MediaType::Unknown);
+  }
+
+  #[test]
+  fn test_map_content_type_extension_only() {
+    // Extension only
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.ts"), None),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.tsx"), None),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.d.ts"), None),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.js"), None),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.txt"), None),
+      MediaType::Unknown
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.jsx"), None),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.json"), None),
+      MediaType::Json
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.wasm"), None),
+      MediaType::Wasm
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), None),
+      MediaType::Unknown
+    );
+  }
+
+  #[test]
+  fn test_map_content_type_media_type_with_no_extension() {
+    // Media Type
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/typescript")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/typescript")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("video/vnd.dlna.mpeg-tts")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("video/mp2t")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/x-typescript")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/javascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/javascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/ecmascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/ecmascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/x-javascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("application/json")),
+      MediaType::Json
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/json")),
+      MediaType::Json
+    );
+  }
+
+  #[test]
+  fn test_map_file_extension_media_type_with_extension() {
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.ts"), Some("text/plain")),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.ts"), Some("foo/bar")),
+      MediaType::Unknown
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.tsx"),
+        Some("application/typescript"),
+      ),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.tsx"),
+        Some("application/javascript"),
+      ),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.tsx"),
+        Some("application/x-typescript"),
+      ),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.tsx"),
+        Some("video/vnd.dlna.mpeg-tts"),
+      ),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.tsx"), Some("video/mp2t")),
+      MediaType::TSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.jsx"),
+        Some("application/javascript"),
+      ),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.jsx"),
+        Some("application/x-typescript"),
+      ),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.jsx"),
+        Some("application/ecmascript"),
+      ),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.jsx"), Some("text/ecmascript")),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.jsx"),
+        Some("application/x-javascript"),
+      ),
+      MediaType::JSX
+    );
+  }
+
+  #[test]
+  fn test_map_content_type_overrides() {
+    assert_eq!(
+      map_content_type(
+        Path::new("foo/bar.d.ts"),
+        Some("application/javascript")
+      ),
+      MediaType::TypeScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar.ts"), Some("application/javascript")),
+      MediaType::JavaScript
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/jsx")),
+      MediaType::JSX
+    );
+    assert_eq!(
+      map_content_type(Path::new("foo/bar"), Some("text/tsx; charset=utf-8")),
+      MediaType::TSX
+    );
+  }
+
+  #[test]
+  fn test_map_remote() {
+    let url = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    assert_eq!(
+      map_remote(&url, Some("text/plain")).unwrap(),
+      MediaType::TypeScript
+    );
+    let err = map_remote(&url, Some("text/html")).unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("unsupported Content-Type \"text/html\""));
+    let url = Url::parse("https://example.com/mod").unwrap();
+    assert!(map_remote(&url, None).is_err());
+  }
+}
@@ -1,6 +1,59 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use deno_core::OpId;
+use deno_core::OpRegistry;
+use serde_json::Value;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Upper bounds (in milliseconds) of `Histogram` buckets; the last bucket
+/// holds everything above the last bound.
+const HISTOGRAM_BOUNDS_MS: [u64; 7] = [1, 5, 10, 50, 100, 500, 1000];
+
+/// Aggregated durations, collected when `--warn-slow-ops` is given.
 #[derive(Default, Debug)]
-pub struct Metrics {
+pub struct Histogram {
+  pub count: u64,
+  pub sum: Duration,
+  pub max: Duration,
+  pub buckets: [u64; HISTOGRAM_BOUNDS_MS.len() + 1],
+}
+
+impl Histogram {
+  pub fn record(&mut self, duration: Duration) {
+    self.count += 1;
+    self.sum += duration;
+    if duration > self.max {
+      self.max = duration;
+    }
+    let ms = duration.as_millis();
+    let index = HISTOGRAM_BOUNDS_MS
+      .iter()
+      .position(|bound| ms <= *bound as u128)
+      .unwrap_or(HISTOGRAM_BOUNDS_MS.len());
+    self.buckets[index] += 1;
+  }
+
+  pub fn to_json(&self) -> Value {
+    let mut buckets = serde_json::Map::new();
+    for (i, count) in self.buckets.iter().enumerate() {
+      let key = match HISTOGRAM_BOUNDS_MS.get(i) {
+        Some(bound) => bound.to_string(),
+        None => "Infinity".to_string(),
+      };
+      buckets.insert(key, json!(count));
+    }
+    json!({
+      "count": self.count,
+      "sumMs": self.sum.as_secs_f64() * 1000.0,
+      "maxMs": self.max.as_secs_f64() * 1000.0,
+      "buckets": buckets,
+    })
+  }
+}
+
+/// Op counters, kept for all ops together and for each op separately.
+#[derive(Default, Debug)]
+pub struct OpMetrics {
   pub ops_dispatched: u64,
   pub ops_dispatched_sync: u64,
   pub ops_dispatched_async: u64,
@@ -12,10 +65,9 @@ pub struct Metrics {
   pub bytes_sent_control: u64,
   pub bytes_sent_data: u64,
   pub bytes_received: u64,
-  pub resolve_count: u64,
 }
 
-impl Metrics {
+impl OpMetrics {
   fn op_dispatched(&mut self, bytes_sent_control: u64, bytes_sent_data: u64) {
     self.ops_dispatched += 1;
     self.bytes_sent_control += bytes_sent_control;
@@ -27,7 +79,7 @@ impl Metrics {
     self.bytes_received += bytes_received;
   }
 
-  pub fn op_sync(
+  fn op_sync(
     &mut self,
     bytes_sent_control: u64,
     bytes_sent_data: u64,
@@ -39,7 +91,7 @@ impl Metrics {
     self.op_completed(bytes_received);
   }
 
-  pub fn op_dispatched_async(
+  fn op_dispatched_async(
     &mut self,
     bytes_sent_control: u64,
     bytes_sent_data: u64,
@@ -48,7 +100,7 @@ impl Metrics {
     self.op_dispatched(bytes_sent_control, bytes_sent_data)
   }
 
-  pub fn op_dispatched_async_unref(
+  fn op_dispatched_async_unref(
     &mut self,
     bytes_sent_control: u64,
     bytes_sent_data: u64,
@@ -57,13 +109,188 @@ impl Metrics {
     self.op_dispatched(bytes_sent_control, bytes_sent_data)
   }
 
-  pub fn op_completed_async(&mut self, bytes_received: u64) {
+  fn op_completed_async(&mut self, bytes_received: u64) {
     self.ops_completed_async += 1;
     self.op_completed(bytes_received);
   }
 
-  pub fn op_completed_async_unref(&mut self, bytes_received: u64) {
+  fn op_completed_async_unref(&mut self, bytes_received: u64) {
     self.ops_completed_async_unref += 1;
     self.op_completed(bytes_received);
   }
+
+  pub fn to_json(&self) -> Value {
+    json!({
+      "opsDispatched": self.ops_dispatched,
+      "opsDispatchedSync": self.ops_dispatched_sync,
+      "opsDispatchedAsync": self.ops_dispatched_async,
+      "opsDispatchedAsyncUnref": self.ops_dispatched_async_unref,
+      "opsCompleted": self.ops_completed,
+      "opsCompletedSync": self.ops_completed_sync,
+      "opsCompletedAsync": self.ops_completed_async,
+      "opsCompletedAsyncUnref": self.ops_completed_async_unref,
+      "bytesSentControl": self.bytes_sent_control,
+      "bytesSentData": self.bytes_sent_data,
+      "bytesReceived": self.bytes_received,
+    })
+  }
+}
+
+/// Op counters take the id of the op they're about, as returned by
+/// `CoreIsolate::current_op_id()`; `None` only updates the totals.
+#[derive(Default, Debug)]
+pub struct Metrics {
+  pub totals: OpMetrics,
+  /// Indexed by op id.
+  pub by_op: Vec<OpMetrics>,
+  pub resolve_count: u64,
+  pub op_sync_duration: Histogram,
+  pub op_async_duration: Histogram,
+  pub tick_duration: Histogram,
+}
+
+impl Metrics {
+  fn update(&mut self, op_id: Option<OpId>, f: impl Fn(&mut OpMetrics)) {
+    f(&mut self.totals);
+    if let Some(op_id) = op_id {
+      let index = op_id as usize;
+      if index >= self.by_op.len() {
+        self.by_op.resize_with(index + 1, OpMetrics::default);
+      }
+      f(&mut self.by_op[index]);
+    }
+  }
+
+  pub fn op_sync(
+    &mut self,
+    op_id: Option<OpId>,
+    bytes_sent_control: u64,
+    bytes_sent_data: u64,
+    bytes_received: u64,
+  ) {
+    self.update(op_id, |m| {
+      m.op_sync(bytes_sent_control, bytes_sent_data, bytes_received)
+    });
+  }
+
+  pub fn op_dispatched_async(
+    &mut self,
+    op_id: Option<OpId>,
+    bytes_sent_control: u64,
+    bytes_sent_data: u64,
+  ) {
+    self.update(op_id, |m| {
+      m.op_dispatched_async(bytes_sent_control, bytes_sent_data)
+    });
+  }
+
+  pub fn op_dispatched_async_unref(
+    &mut self,
+    op_id: Option<OpId>,
+    bytes_sent_control: u64,
+    bytes_sent_data: u64,
+  ) {
+    self.update(op_id, |m| {
+      m.op_dispatched_async_unref(bytes_sent_control, bytes_sent_data)
+    });
+  }
+
+  pub fn op_completed_async(
+    &mut self,
+    op_id: Option<OpId>,
+    bytes_received: u64,
+  ) {
+    self.update(op_id, |m| m.op_completed_async(bytes_received));
+  }
+
+  pub fn op_completed_async_unref(
+    &mut self,
+    op_id: Option<OpId>,
+    bytes_received: u64,
+  ) {
+    self.update(op_id, |m| m.op_completed_async_unref(bytes_received));
+  }
+
+  /// Records latency of an async op if it was measured.
+  pub fn record_op_async_duration(&mut self, dispatched_at: Option<Instant>) {
+    if let Some(dispatched_at) = dispatched_at {
+      self.op_async_duration.record(dispatched_at.elapsed());
+    }
+  }
+
+  /// Serializes metrics the way `Deno.metrics()` returns them; the `ops`
+  /// table has counters of every op that was dispatched, keyed by op name.
+  pub fn to_json(&self, op_registry: &OpRegistry) -> Value {
+    let mut ops = serde_json::Map::new();
+    for (op_id, op_metrics) in self.by_op.iter().enumerate() {
+      if op_metrics.ops_dispatched == 0 {
+        continue;
+      }
+      if let Some(name) = op_registry.name(op_id as OpId) {
+        ops.insert(name.to_string(), op_metrics.to_json());
+      }
+    }
+    let mut json = self.totals.to_json();
+    let object = json.as_object_mut().unwrap();
+    object.insert(
+      "opSyncDuration".to_string(),
+      self.op_sync_duration.to_json(),
+    );
+    object.insert(
+      "opAsyncDuration".to_string(),
+      self.op_async_duration.to_json(),
+    );
+    object.insert(
+      "eventLoopTickDuration".to_string(),
+      self.tick_duration.to_json(),
+    );
+    object.insert("ops".to_string(), Value::Object(ops));
+    json
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn histogram_record() {
+    let mut histogram = Histogram::default();
+    histogram.record(Duration::from_micros(500));
+    histogram.record(Duration::from_millis(5));
+    histogram.record(Duration::from_millis(70));
+    histogram.record(Duration::from_secs(2));
+    assert_eq!(histogram.count, 4);
+    assert_eq!(histogram.max, Duration::from_secs(2));
+    assert_eq!(histogram.buckets, [1, 1, 0, 0, 1, 0, 0, 1]);
+    let json = histogram.to_json();
+    assert_eq!(json["buckets"]["100"], 1);
+    assert_eq!(json["buckets"]["Infinity"], 1);
+    assert_eq!(json["maxMs"], 2000.0);
+  }
+
+  #[test]
+  fn metrics_by_op() {
+    let mut registry = OpRegistry::new();
+    let read = registry.register("op_read", |_, _, _| unreachable!());
+    let write = registry.register("op_write", |_, _, _| unreachable!());
+    registry.register("op_close", |_, _, _| unreachable!());
+
+    let mut metrics = Metrics::default();
+    metrics.op_sync(Some(write), 10, 3, 4);
+    metrics.op_dispatched_async(Some(read), 10, 0);
+    metrics.op_completed_async(Some(read), 20);
+    metrics.op_sync(None, 1, 0, 1);
+    assert_eq!(metrics.totals.ops_dispatched, 3);
+    assert_eq!(metrics.totals.bytes_sent_control, 21);
+
+    let json = metrics.to_json(&registry);
+    assert_eq!(json["opsDispatched"], 3);
+    assert_eq!(json["ops"]["op_write"]["opsDispatchedSync"], 1);
+    assert_eq!(json["ops"]["op_write"]["bytesSentData"], 3);
+    assert_eq!(json["ops"]["op_read"]["opsCompletedAsync"], 1);
+    assert_eq!(json["ops"]["op_read"]["bytesReceived"], 20);
+    // Ops that were never dispatched are left out.
+    assert!(json["ops"].get("op_close").is_none());
+  }
 }
@@ -36,4 +36,6 @@ pub enum CompilerRequestType {
   Compile = 0,
   RuntimeCompile = 1,
   RuntimeTranspile = 2,
+  LanguageService = 3,
+  RuntimeGraph = 4,
 }
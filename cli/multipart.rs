@@ -0,0 +1,708 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Encoding and parsing of `multipart/form-data` bodies (RFC 7578).
+//!
+//! File parts are never held in the V8 heap as a whole: the encoder streams
+//! them from blobs or from disk, and the parser writes them to files in a
+//! directory as they arrive. Those files are kept in `PartFiles` for as long
+//! as the isolate lives, so the `File` objects JS gets for them can be read
+//! lazily, or sent in another body without reading them at all.
+
+use crate::blob_store::Blob;
+use futures::stream::Stream;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use tempfile::TempDir;
+
+/// Size of the chunks file parts are streamed in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Maximum size of the headers of a part.
+const MAX_HEADERS_SIZE: usize = 16 * 1024;
+
+/// Returns a boundary that is very unlikely to appear in any part.
+pub fn generate_boundary() -> String {
+  let suffix: String =
+    thread_rng().sample_iter(&Alphanumeric).take(24).collect();
+  format!("----DenoFormBoundary{}", suffix)
+}
+
+/// Escapes a name or filename for a `Content-Disposition` header, like
+/// browsers do.
+fn escape(value: &str) -> String {
+  value
+    .replace('"', "%22")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
+}
+
+fn unescape(value: &str) -> String {
+  value
+    .replace("%22", "\"")
+    .replace("%0D", "\r")
+    .replace("%0A", "\n")
+}
+
+pub enum PartBody {
+  Bytes(Vec<u8>),
+  /// The contents of a `Blob`.
+  Blob(Arc<Blob>),
+  /// A file and its size, streamed from disk.
+  File(PathBuf, u64),
+}
+
+pub struct Part {
+  pub name: String,
+  /// Set for file parts.
+  pub filename: Option<String>,
+  pub content_type: Option<String>,
+  pub body: PartBody,
+}
+
+impl Part {
+  fn headers(&self, boundary: &str) -> Vec<u8> {
+    let mut headers = format!(
+      "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+      boundary,
+      escape(&self.name)
+    );
+    if let Some(filename) = &self.filename {
+      headers.push_str(&format!("; filename=\"{}\"", escape(filename)));
+    }
+    headers.push_str("\r\n");
+    if let Some(content_type) = &self.content_type {
+      headers.push_str(&format!("Content-Type: {}\r\n", content_type));
+    }
+    headers.push_str("\r\n");
+    headers.into_bytes()
+  }
+}
+
+enum Segment {
+  Bytes(Vec<u8>),
+  Blob(Arc<Blob>),
+  File(PathBuf),
+}
+
+/// A `multipart/form-data` body, built up part by part.
+pub struct Encoder {
+  boundary: String,
+  segments: Vec<Segment>,
+  len: u64,
+}
+
+impl Encoder {
+  pub fn new(boundary: String) -> Self {
+    Self {
+      boundary,
+      segments: vec![],
+      len: 0,
+    }
+  }
+
+  fn push_bytes(&mut self, bytes: Vec<u8>) {
+    self.len += bytes.len() as u64;
+    self.segments.push(Segment::Bytes(bytes));
+  }
+
+  pub fn append(&mut self, part: Part) {
+    let headers = part.headers(&self.boundary);
+    self.push_bytes(headers);
+    match part.body {
+      PartBody::Bytes(bytes) => self.push_bytes(bytes),
+      PartBody::Blob(blob) => {
+        self.len += blob.size() as u64;
+        self.segments.push(Segment::Blob(blob));
+      }
+      PartBody::File(path, size) => {
+        self.len += size;
+        self.segments.push(Segment::File(path));
+      }
+    }
+    self.push_bytes(b"\r\n".to_vec());
+  }
+
+  /// The `Content-Type` of the body.
+  pub fn content_type(&self) -> String {
+    format!("multipart/form-data; boundary={}", self.boundary)
+  }
+
+  /// Ends the body, returning its length and its contents as a stream.
+  pub fn finish(mut self) -> (u64, EncoderStream) {
+    let end = format!("--{}--\r\n", self.boundary).into_bytes();
+    self.push_bytes(end);
+    let stream = EncoderStream {
+      segments: self.segments.into(),
+      blob: None,
+      file: None,
+    };
+    (self.len, stream)
+  }
+}
+
+/// The contents of an encoded body. Files are only opened once the stream
+/// gets to them.
+pub struct EncoderStream {
+  segments: VecDeque<Segment>,
+  /// The blob being streamed, and the offset in it.
+  blob: Option<(Arc<Blob>, usize)>,
+  file: Option<fs::File>,
+}
+
+impl Stream for EncoderStream {
+  type Item = io::Result<Vec<u8>>;
+
+  fn poll_next(
+    self: Pin<&mut Self>,
+    _cx: &mut Context,
+  ) -> Poll<Option<Self::Item>> {
+    let inner = self.get_mut();
+    loop {
+      if let Some((blob, offset)) = inner.blob.as_mut() {
+        if *offset < blob.size() {
+          let mut chunk = vec![0; min(CHUNK_SIZE, blob.size() - *offset)];
+          *offset += blob.read_at(*offset, &mut chunk);
+          return Poll::Ready(Some(Ok(chunk)));
+        }
+        inner.blob = None;
+      }
+      if let Some(file) = inner.file.as_mut() {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        match file.read(&mut chunk) {
+          Ok(0) => inner.file = None,
+          Ok(n) => {
+            chunk.truncate(n);
+            return Poll::Ready(Some(Ok(chunk)));
+          }
+          Err(e) => return Poll::Ready(Some(Err(e))),
+        }
+      }
+      match inner.segments.pop_front() {
+        Some(Segment::Bytes(bytes)) => return Poll::Ready(Some(Ok(bytes))),
+        Some(Segment::Blob(blob)) => inner.blob = Some((blob, 0)),
+        Some(Segment::File(path)) => match fs::File::open(path) {
+          Ok(file) => inner.file = Some(file),
+          Err(e) => return Poll::Ready(Some(Err(e))),
+        },
+        None => return Poll::Ready(None),
+      }
+    }
+  }
+}
+
+/// The files written for the file parts of parsed bodies, by id.
+pub struct PartFiles {
+  dir: TempDir,
+  files: Vec<(PathBuf, u64)>,
+}
+
+impl PartFiles {
+  pub fn new() -> io::Result<Self> {
+    Ok(Self {
+      dir: TempDir::new()?,
+      files: vec![],
+    })
+  }
+
+  /// The directory the parser should write file parts to.
+  pub fn dir(&self) -> &Path {
+    self.dir.path()
+  }
+
+  /// Keeps a file written by the parser, returning its id.
+  pub fn add(&mut self, path: PathBuf, size: u64) -> usize {
+    self.files.push((path, size));
+    self.files.len() - 1
+  }
+
+  /// The path and size of a file.
+  pub fn get(&self, id: usize) -> Option<&(PathBuf, u64)> {
+    self.files.get(id)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Entry {
+  Field {
+    name: String,
+    value: String,
+  },
+  File {
+    name: String,
+    filename: String,
+    content_type: Option<String>,
+    path: PathBuf,
+    size: u64,
+  },
+}
+
+enum PartSink {
+  Field(Vec<u8>),
+  File(fs::File, PathBuf, u64),
+  /// Parts that aren't form data.
+  Skip,
+}
+
+struct CurrentPart {
+  name: String,
+  filename: Option<String>,
+  content_type: Option<String>,
+  sink: PartSink,
+}
+
+impl CurrentPart {
+  fn write(&mut self, data: &[u8]) -> io::Result<()> {
+    match &mut self.sink {
+      PartSink::Field(value) => value.extend_from_slice(data),
+      PartSink::File(file, _, size) => {
+        file.write_all(data)?;
+        *size += data.len() as u64;
+      }
+      PartSink::Skip => {}
+    }
+    Ok(())
+  }
+
+  fn finish(self) -> Option<Entry> {
+    let entry = match self.sink {
+      PartSink::Field(value) => Entry::Field {
+        name: self.name,
+        value: String::from_utf8_lossy(&value).into_owned(),
+      },
+      PartSink::File(_, path, size) => Entry::File {
+        name: self.name,
+        filename: self.filename.unwrap_or_default(),
+        content_type: self.content_type,
+        path,
+        size,
+      },
+      PartSink::Skip => return None,
+    };
+    Some(entry)
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ParserState {
+  Preamble,
+  AfterBoundary,
+  Headers,
+  Body,
+  Epilogue,
+}
+
+fn invalid(msg: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  haystack
+    .windows(needle.len())
+    .position(|window| window == needle)
+}
+
+/// Returns the `name` and `filename` parameters of a `form-data`
+/// `Content-Disposition`, or `None` for another disposition.
+fn parse_content_disposition(
+  value: &str,
+) -> Option<(Option<String>, Option<String>)> {
+  let mut rest = value.trim_start();
+  let disposition_end = rest.find(';').unwrap_or_else(|| rest.len());
+  if !rest[..disposition_end]
+    .trim()
+    .eq_ignore_ascii_case("form-data")
+  {
+    return None;
+  }
+  rest = &rest[disposition_end..];
+  let mut name = None;
+  let mut filename = None;
+  while rest.starts_with(';') {
+    rest = rest[1..].trim_start();
+    let key_end = rest
+      .find(|c: char| c == '=' || c == ';')
+      .unwrap_or_else(|| rest.len());
+    let key = rest[..key_end].trim().to_ascii_lowercase();
+    rest = &rest[key_end..];
+    let mut param = String::new();
+    if rest.starts_with('=') {
+      rest = rest[1..].trim_start();
+      if rest.starts_with('"') {
+        let quoted = &rest[1..];
+        let mut chars = quoted.char_indices();
+        let mut end = quoted.len();
+        while let Some((i, c)) = chars.next() {
+          match c {
+            '\\' => {
+              if let Some((_, escaped)) = chars.next() {
+                param.push(escaped);
+              }
+            }
+            '"' => {
+              end = i + 1;
+              break;
+            }
+            c => param.push(c),
+          }
+        }
+        rest = &quoted[end..];
+        rest = &rest[rest.find(';').unwrap_or_else(|| rest.len())..];
+      } else {
+        let value_end = rest.find(';').unwrap_or_else(|| rest.len());
+        param = rest[..value_end].trim().to_string();
+        rest = &rest[value_end..];
+      }
+    }
+    match key.as_str() {
+      "name" => name = Some(unescape(&param)),
+      "filename" => filename = Some(unescape(&param)),
+      _ => {}
+    }
+  }
+  Some((name, filename))
+}
+
+/// An incremental parser of `multipart/form-data` bodies, fed with the body
+/// as it's read.
+pub struct Parser {
+  delimiter: Vec<u8>,
+  dir: PathBuf,
+  buf: Vec<u8>,
+  state: ParserState,
+  part: Option<CurrentPart>,
+  entries: Vec<Entry>,
+}
+
+impl Parser {
+  /// File parts are written to new files in `dir`.
+  pub fn new(boundary: &str, dir: PathBuf) -> Self {
+    Self {
+      delimiter: format!("\r\n--{}", boundary).into_bytes(),
+      dir,
+      // The first boundary doesn't need to follow a line break.
+      buf: b"\r\n".to_vec(),
+      state: ParserState::Preamble,
+      part: None,
+      entries: vec![],
+    }
+  }
+
+  pub fn feed(&mut self, data: &[u8]) -> io::Result<()> {
+    self.buf.extend_from_slice(data);
+    loop {
+      match self.state {
+        ParserState::Preamble => match find(&self.buf, &self.delimiter) {
+          Some(pos) => {
+            self.buf.drain(..pos + self.delimiter.len());
+            self.state = ParserState::AfterBoundary;
+          }
+          None => {
+            self.keep_tail();
+            return Ok(());
+          }
+        },
+        ParserState::AfterBoundary => {
+          if self.buf.starts_with(b"--") {
+            self.state = ParserState::Epilogue;
+            continue;
+          }
+          match find(&self.buf, b"\r\n") {
+            Some(pos) => {
+              // Only transport padding may follow the boundary.
+              if self.buf[..pos].iter().any(|b| *b != b' ' && *b != b'\t') {
+                return Err(invalid("Invalid multipart boundary"));
+              }
+              self.buf.drain(..pos + 2);
+              self.state = ParserState::Headers;
+            }
+            None if self.buf.len() > MAX_HEADERS_SIZE => {
+              return Err(invalid("Invalid multipart boundary"))
+            }
+            None => return Ok(()),
+          }
+        }
+        ParserState::Headers => {
+          let headers_end = if self.buf.starts_with(b"\r\n") {
+            Some(0)
+          } else {
+            find(&self.buf, b"\r\n\r\n").map(|pos| pos + 2)
+          };
+          match headers_end {
+            Some(end) => {
+              let headers = String::from_utf8_lossy(&self.buf[..end]);
+              self.part = Some(self.start_part(&headers)?);
+              self.buf.drain(..end + 2);
+              self.state = ParserState::Body;
+            }
+            None if self.buf.len() > MAX_HEADERS_SIZE => {
+              return Err(invalid("Multipart part headers are too large"))
+            }
+            None => return Ok(()),
+          }
+        }
+        ParserState::Body => {
+          let part = self.part.as_mut().unwrap();
+          match find(&self.buf, &self.delimiter) {
+            Some(pos) => {
+              part.write(&self.buf[..pos])?;
+              self.buf.drain(..pos + self.delimiter.len());
+              if let Some(entry) = self.part.take().unwrap().finish() {
+                self.entries.push(entry);
+              }
+              self.state = ParserState::AfterBoundary;
+            }
+            None => {
+              let keep = self.delimiter.len() - 1;
+              if self.buf.len() > keep {
+                let end = self.buf.len() - keep;
+                part.write(&self.buf[..end])?;
+                self.buf.drain(..end);
+              }
+              return Ok(());
+            }
+          }
+        }
+        ParserState::Epilogue => {
+          self.buf.clear();
+          return Ok(());
+        }
+      }
+    }
+  }
+
+  /// Keeps the bytes that may be the start of a delimiter.
+  fn keep_tail(&mut self) {
+    let keep = self.delimiter.len() - 1;
+    if self.buf.len() > keep {
+      self.buf.drain(..self.buf.len() - keep);
+    }
+  }
+
+  fn start_part(&self, headers: &str) -> io::Result<CurrentPart> {
+    let mut disposition = None;
+    let mut content_type = None;
+    for line in headers.split("\r\n") {
+      let sep = match line.find(':') {
+        Some(sep) => sep,
+        None => continue,
+      };
+      let value = line[sep + 1..].trim().to_string();
+      match line[..sep].trim().to_ascii_lowercase().as_str() {
+        "content-disposition" => disposition = Some(value),
+        "content-type" => content_type = Some(value),
+        _ => {}
+      }
+    }
+    let (name, filename) =
+      match disposition.as_deref().and_then(parse_content_disposition) {
+        Some((Some(name), filename)) => (name, filename),
+        _ => {
+          return Ok(CurrentPart {
+            name: String::new(),
+            filename: None,
+            content_type: None,
+            sink: PartSink::Skip,
+          })
+        }
+      };
+    let sink = if filename.is_some() {
+      let (file, path) = tempfile::Builder::new()
+        .prefix("part")
+        .tempfile_in(&self.dir)?
+        .keep()
+        .map_err(|e| e.error)?;
+      PartSink::File(file, path, 0)
+    } else {
+      PartSink::Field(vec![])
+    };
+    Ok(CurrentPart {
+      name,
+      filename,
+      content_type,
+      sink,
+    })
+  }
+
+  /// Returns the entries of the body, failing if it ended early.
+  pub fn finish(self) -> io::Result<Vec<Entry>> {
+    if self.state != ParserState::Epilogue {
+      return Err(invalid("Unexpected end of multipart body"));
+    }
+    Ok(self.entries)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::stream::StreamExt;
+
+  fn encode(parts: Vec<Part>) -> (u64, Vec<u8>) {
+    let mut encoder = Encoder::new("BOUNDARY".to_string());
+    for part in parts {
+      encoder.append(part);
+    }
+    let (len, stream) = encoder.finish();
+    let chunks: Vec<io::Result<Vec<u8>>> =
+      futures::executor::block_on(stream.collect());
+    let body = chunks.into_iter().flat_map(Result::unwrap).collect();
+    (len, body)
+  }
+
+  fn field(name: &str, value: &str) -> Part {
+    Part {
+      name: name.to_string(),
+      filename: None,
+      content_type: None,
+      body: PartBody::Bytes(value.as_bytes().to_vec()),
+    }
+  }
+
+  #[test]
+  fn boundaries_differ() {
+    let boundary = generate_boundary();
+    assert!(boundary.starts_with("----DenoFormBoundary"));
+    assert_ne!(boundary, generate_boundary());
+  }
+
+  #[test]
+  fn encode_parts() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let path = temp_dir.path().join("a.txt");
+    fs::write(&path, b"file contents").unwrap();
+    let mut blob = Blob::new(String::new());
+    blob.push_bytes(b"blob ".to_vec());
+    blob.push_bytes(b"contents".to_vec());
+    let (len, body) = encode(vec![
+      field("field \"1\"", "value"),
+      Part {
+        name: "file".to_string(),
+        filename: Some("a.txt".to_string()),
+        content_type: Some("text/plain".to_string()),
+        body: PartBody::File(path, 13),
+      },
+      Part {
+        name: "blob".to_string(),
+        filename: Some("blob".to_string()),
+        content_type: None,
+        body: PartBody::Blob(Arc::new(blob)),
+      },
+    ]);
+    let expected = "--BOUNDARY\r\n\
+      Content-Disposition: form-data; name=\"field %221%22\"\r\n\r\n\
+      value\r\n\
+      --BOUNDARY\r\n\
+      Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+      Content-Type: text/plain\r\n\r\n\
+      file contents\r\n\
+      --BOUNDARY\r\n\
+      Content-Disposition: form-data; name=\"blob\"; filename=\"blob\"\r\n\r\n\
+      blob contents\r\n\
+      --BOUNDARY--\r\n";
+    assert_eq!(String::from_utf8(body).unwrap(), expected);
+    assert_eq!(len, expected.len() as u64);
+  }
+
+  #[test]
+  fn parse_in_chunks() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let path = temp_dir.path().join("data.bin");
+    let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+    fs::write(&path, &data).unwrap();
+    let (_, body) = encode(vec![
+      field("a", "1"),
+      Part {
+        name: "data".to_string(),
+        filename: Some("data.bin".to_string()),
+        content_type: Some("application/octet-stream".to_string()),
+        body: PartBody::File(path, data.len() as u64),
+      },
+      field("b", "\r\n--BOUNDAR"),
+    ]);
+
+    for chunk_size in &[1, 7, 4096, body.len()] {
+      let mut parser = Parser::new("BOUNDARY", temp_dir.path().to_owned());
+      for chunk in body.chunks(*chunk_size) {
+        parser.feed(chunk).unwrap();
+      }
+      let entries = parser.finish().unwrap();
+      assert_eq!(entries.len(), 3);
+      assert_eq!(
+        entries[0],
+        Entry::Field {
+          name: "a".to_string(),
+          value: "1".to_string()
+        }
+      );
+      match &entries[1] {
+        Entry::File {
+          name,
+          filename,
+          content_type,
+          path,
+          size,
+        } => {
+          assert_eq!(name, "data");
+          assert_eq!(filename, "data.bin");
+          assert_eq!(content_type.as_deref(), Some("application/octet-stream"));
+          assert_eq!(*size, data.len() as u64);
+          assert_eq!(fs::read(path).unwrap(), data);
+        }
+        _ => panic!("expected a file"),
+      }
+      assert_eq!(
+        entries[2],
+        Entry::Field {
+          name: "b".to_string(),
+          value: "\r\n--BOUNDAR".to_string()
+        }
+      );
+    }
+  }
+
+  #[test]
+  fn parse_preamble_and_epilogue() {
+    let body = b"preamble\r\n--b \r\n\
+      content-disposition: form-data; name=\"x; y\"; filename=\"a\\\"b%0A\"\r\n\
+      \r\n\
+      1\r\n\
+      --b\r\n\
+      Content-Disposition: attachment\r\n\r\n\
+      skipped\r\n\
+      --b--\r\nepilogue";
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let mut parser = Parser::new("b", temp_dir.path().to_owned());
+    parser.feed(body).unwrap();
+    let entries = parser.finish().unwrap();
+    assert_eq!(entries.len(), 1);
+    match &entries[0] {
+      Entry::File { name, filename, .. } => {
+        assert_eq!(name, "x; y");
+        assert_eq!(filename, "a\"b\n");
+      }
+      _ => panic!("expected a file"),
+    }
+  }
+
+  #[test]
+  fn parse_truncated() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let mut parser = Parser::new("b", temp_dir.path().to_owned());
+    parser
+      .feed(b"--b\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1")
+      .unwrap();
+    assert!(parser.finish().is_err());
+  }
+}
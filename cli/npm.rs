@@ -0,0 +1,1048 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! `npm:` specifiers, e.g. `import express from "npm:express@4"`.
+//!
+//! Packages are downloaded from the npm registry into `$DENO_DIR/npm`, one
+//! directory per version, along with their dependencies. Each package's
+//! dependencies are linked into its own `node_modules` directory, so that
+//! node-style resolution inside the package works. The module seen by Deno
+//! wraps the package entry point: CommonJS entry points are loaded through the
+//! `require()` of `std/node`, as in `--compat` mode.
+
+use crate::colors;
+use crate::compat;
+use crate::file_fetcher::SourceFile;
+use crate::fs::normalize_path;
+use crate::http_util;
+use crate::http_util::FetchOnceResult;
+use crate::msg;
+use crate::op_error::OpError;
+use crate::permissions::Permissions;
+use deno_core::ErrBox;
+use deno_core::ModuleSpecifier;
+use flate2::read::GzDecoder;
+use ring::digest;
+use semver::Version;
+use semver::VersionReq;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tar::Archive;
+use url::Url;
+
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Conditions matched against the `"exports"` of a `package.json`, in order
+/// of preference when the package is loaded through `require()`.
+pub const REQUIRE_CONDITIONS: &[&str] = &["require", "node", "default"];
+
+/// Conditions used when a package is imported as an ES module.
+pub const IMPORT_CONDITIONS: &[&str] = &["import", "node", "default"];
+
+/// Registry to download packages from, `$NPM_CONFIG_REGISTRY` if set.
+pub fn registry_url() -> Url {
+  let mut registry = env::var("NPM_CONFIG_REGISTRY")
+    .unwrap_or_else(|_| DEFAULT_REGISTRY.to_string());
+  if !registry.ends_with('/') {
+    registry.push('/');
+  }
+  Url::parse(&registry).unwrap_or_else(|_| {
+    warn!(
+      "Invalid NPM_CONFIG_REGISTRY \"{}\", using default.",
+      registry
+    );
+    Url::parse(DEFAULT_REGISTRY).unwrap()
+  })
+}
+
+/// A parsed `npm:<name>[@<version range or tag>][/<sub path>]` specifier.
+#[derive(Debug, PartialEq)]
+pub struct NpmReference {
+  pub name: String,
+  /// Version range or dist-tag; empty means the `latest` tag.
+  pub req: String,
+  pub sub_path: Option<String>,
+}
+
+impl NpmReference {
+  pub fn from_url(url: &Url) -> Result<Self, ErrBox> {
+    let invalid = || {
+      ErrBox::from(OpError::uri_error(format!(
+        "Invalid npm specifier \"{}\"",
+        url
+      )))
+    };
+    let path = url.path().replace("%20", " ");
+    // Scoped packages have a slash in their name: `@scope/name`.
+    let name_start = if path.starts_with('@') {
+      path.find('/').ok_or_else(invalid)? + 1
+    } else {
+      0
+    };
+    let (package, sub_path) = match path[name_start..].find('/') {
+      Some(i) => {
+        let (package, sub_path) = path.split_at(name_start + i);
+        (package, Some(sub_path[1..].to_string()))
+      }
+      None => (path.as_str(), None),
+    };
+    let (name, req) = match package[name_start..].find('@') {
+      Some(i) => {
+        let (name, req) = package.split_at(name_start + i);
+        (name, &req[1..])
+      }
+      None => (package, ""),
+    };
+    if !is_valid_package_name(name) {
+      return Err(invalid());
+    }
+    Ok(Self {
+      name: name.to_string(),
+      req: req.trim().to_string(),
+      sub_path: sub_path.filter(|p| !p.is_empty()),
+    })
+  }
+}
+
+/// Whether `name` follows the npm rules for package names: either `name` or
+/// `@scope/name`, where neither part starts with `.` or `_` and only URL-safe
+/// characters are used. Names are used as paths in the cache and in
+/// `node_modules`, so anything else could escape those directories.
+fn is_valid_package_name(name: &str) -> bool {
+  let is_valid_part = |part: &str| {
+    !part.is_empty()
+      && !part.starts_with('.')
+      && !part.starts_with('_')
+      && part
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-._~!*'()".contains(c))
+  };
+  if name.len() > 214 {
+    return false;
+  }
+  if name.starts_with('@') {
+    let mut parts = name[1..].splitn(2, '/');
+    match (parts.next(), parts.next()) {
+      (Some(scope), Some(package)) => {
+        is_valid_part(scope) && is_valid_part(package)
+      }
+      _ => false,
+    }
+  } else {
+    is_valid_part(name)
+  }
+}
+
+/// Parses an npm version range into `VersionReq`s, one per `||` alternative.
+///
+/// npm separates comparators with spaces, and a plain version means that
+/// exact version, unlike `semver::VersionReq`.
+fn parse_range(range: &str) -> Option<Vec<VersionReq>> {
+  range
+    .split("||")
+    .map(|alternative| {
+      let alternative = alternative.trim();
+      if alternative.is_empty() || alternative == "x" || alternative == "X" {
+        return VersionReq::parse("*").ok();
+      }
+      if let Ok(version) = Version::parse(alternative.trim_start_matches('v')) {
+        return Some(VersionReq::exact(&version));
+      }
+      let parts: Vec<&str> = alternative.split_whitespace().collect();
+      if let [from, "-", to] = parts.as_slice() {
+        return VersionReq::parse(&format!(">={}, <={}", from, to)).ok();
+      }
+      // Glue operators to their version (`>= 1.0.0` is valid npm syntax).
+      let mut comparators: Vec<String> = vec![];
+      for part in parts {
+        match comparators.last_mut() {
+          Some(last) if last.ends_with(|c: char| "<>=~^".contains(c)) => {
+            last.push_str(part)
+          }
+          _ => comparators.push(part.to_string()),
+        }
+      }
+      VersionReq::parse(&comparators.join(", ")).ok()
+    })
+    .collect()
+}
+
+fn satisfies(range: &[VersionReq], version: &Version) -> bool {
+  range.iter().any(|req| req.matches(version))
+}
+
+#[derive(Debug, Deserialize)]
+struct Packument {
+  #[serde(rename = "dist-tags", default)]
+  dist_tags: HashMap<String, String>,
+  versions: HashMap<String, PackageVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageVersion {
+  dist: Dist,
+}
+
+#[derive(Debug, Deserialize)]
+struct Dist {
+  tarball: String,
+  shasum: Option<String>,
+  integrity: Option<String>,
+}
+
+/// Checks a downloaded tarball against the `integrity` (SHA-512) or, for
+/// older packages, the `shasum` (SHA-1) published by the registry.
+fn verify_tarball(data: &[u8], dist: &Dist) -> Result<(), ErrBox> {
+  let (expected, actual) = match (&dist.integrity, &dist.shasum) {
+    (Some(integrity), _) if integrity.starts_with("sha512-") => (
+      integrity["sha512-".len()..].to_string(),
+      base64::encode(digest::digest(&digest::SHA512, data)),
+    ),
+    (_, Some(shasum)) => (
+      shasum.to_lowercase(),
+      digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, data)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect(),
+    ),
+    _ => return Ok(()),
+  };
+  if expected != actual {
+    return Err(
+      OpError::other(format!(
+        "Integrity check failed for {}: expected {}, got {}",
+        dist.tarball, expected, actual
+      ))
+      .into(),
+    );
+  }
+  Ok(())
+}
+
+/// Unpacks a gzipped package tarball into `dest`. Registry tarballs keep all
+/// files under a single top-level directory, usually `package/`, which is
+/// stripped.
+fn extract_tarball(data: &[u8], dest: &Path) -> Result<(), ErrBox> {
+  let mut archive = Archive::new(GzDecoder::new(data));
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    let entry_type = entry.header().entry_type();
+    if !entry_type.is_file() && !entry_type.is_dir() {
+      continue;
+    }
+    let path = entry.path()?.into_owned();
+    let relative: PathBuf = path.components().skip(1).collect();
+    let is_safe = relative.components().all(|c| match c {
+      Component::Normal(_) => true,
+      _ => false,
+    });
+    if relative.as_os_str().is_empty() || !is_safe {
+      continue;
+    }
+    let target = dest.join(relative);
+    if let Some(parent) = target.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    entry.unpack(&target)?;
+  }
+  Ok(())
+}
+
+fn read_package_json(package_dir: &Path) -> Result<Value, ErrBox> {
+  let path = package_dir.join("package.json");
+  let json = fs::read_to_string(&path)?;
+  serde_json::from_str(&json).map_err(|e| {
+    OpError::other(format!("Invalid package.json {}: {}", path.display(), e))
+      .into()
+  })
+}
+
+fn resolve_export_target(
+  target: &Value,
+  conditions: &[&str],
+) -> Option<String> {
+  match target {
+    Value::String(target) => Some(target.clone()),
+    Value::Array(targets) => targets
+      .iter()
+      .find_map(|target| resolve_export_target(target, conditions)),
+    Value::Object(map) => map
+      .iter()
+      .filter(|(condition, _)| conditions.contains(&condition.as_str()))
+      .find_map(|(_, target)| resolve_export_target(target, conditions)),
+    _ => None,
+  }
+}
+
+/// Resolves `sub_path` (`"."` or `"./name"`) against the `"exports"` field of
+/// a `package.json`.
+fn resolve_exports(
+  exports: &Value,
+  sub_path: &str,
+  conditions: &[&str],
+) -> Option<String> {
+  let is_sub_path_map = exports
+    .as_object()
+    .map(|map| map.keys().any(|key| key.starts_with('.')))
+    .unwrap_or(false);
+  if is_sub_path_map {
+    resolve_export_target(exports.get(sub_path)?, conditions)
+  } else if sub_path == "." {
+    resolve_export_target(exports, conditions)
+  } else {
+    None
+  }
+}
+
+/// Resolves a file like `require()` does: as is, with an extension added, or
+/// as a directory with an `index.js`.
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+  if path.is_file() {
+    return Some(path.to_path_buf());
+  }
+  for ext in &["js", "json", "cjs", "mjs"] {
+    let mut with_ext = path.as_os_str().to_owned();
+    with_ext.push(".");
+    with_ext.push(ext);
+    let with_ext = PathBuf::from(with_ext);
+    if with_ext.is_file() {
+      return Some(with_ext);
+    }
+  }
+  let index = path.join("index.js");
+  if index.is_file() {
+    return Some(index);
+  }
+  None
+}
+
+/// Resolves the module to load for `sub_path` of the package in
+/// `package_dir`, or its main module, following `"exports"` and `"main"`.
+pub fn resolve_package_entry(
+  package_dir: &Path,
+  sub_path: Option<&str>,
+  conditions: &[&str],
+) -> Result<PathBuf, ErrBox> {
+  let package_json = read_package_json(package_dir)?;
+  let export_key = match sub_path {
+    Some(sub_path) => format!("./{}", sub_path),
+    None => ".".to_string(),
+  };
+  let target = if let Some(exports) = package_json.get("exports") {
+    resolve_exports(exports, &export_key, conditions).ok_or_else(|| {
+      OpError::not_found(format!(
+        "Package subpath \"{}\" is not exported by {}",
+        export_key,
+        package_dir.display()
+      ))
+    })?
+  } else if sub_path.is_some() {
+    export_key
+  } else {
+    package_json
+      .get("main")
+      .and_then(Value::as_str)
+      .unwrap_or("index.js")
+      .to_string()
+  };
+  // Targets come from the package, and must not point outside of it.
+  let package_dir = normalize_path(package_dir);
+  let path = normalize_path(&package_dir.join(&target));
+  if !path.starts_with(&package_dir) {
+    return Err(
+      OpError::other(format!(
+        "Module \"{}\" is outside of package {}",
+        target,
+        package_dir.display()
+      ))
+      .into(),
+    );
+  }
+  resolve_file(&path).ok_or_else(|| {
+    OpError::not_found(format!(
+      "Cannot find module \"{}\" in {}",
+      target,
+      package_dir.display()
+    ))
+    .into()
+  })
+}
+
+/// Whether Node would load `path`, part of the package in `package_dir`, as
+/// an ES module.
+pub fn is_es_module(package_dir: &Path, path: &Path) -> bool {
+  match path.extension().and_then(|ext| ext.to_str()) {
+    Some("mjs") => true,
+    Some("js") => read_package_json(package_dir)
+      .map(|json| json.get("type") == Some(&Value::from("module")))
+      .unwrap_or(false),
+    _ => false,
+  }
+}
+
+/// Nearest directory containing `path` that has a `package.json`.
+fn find_package_dir(path: &Path) -> Option<&Path> {
+  path
+    .ancestors()
+    .skip(1)
+    .find(|dir| dir.join("package.json").is_file())
+}
+
+/// Whether `path` is a CommonJS module of a package in a `node_modules`
+/// directory, which has to be loaded through `require()`.
+pub fn is_commonjs_package_file(path: &Path) -> bool {
+  let in_node_modules = path
+    .components()
+    .any(|c| c == Component::Normal("node_modules".as_ref()));
+  if !in_node_modules {
+    return false;
+  }
+  match path.extension().and_then(|ext| ext.to_str()) {
+    Some("cjs") => true,
+    Some("js") => match find_package_dir(path) {
+      Some(package_dir) => !is_es_module(package_dir, path),
+      None => true,
+    },
+    _ => false,
+  }
+}
+
+/// Splits a bare specifier like `lodash/fp` or `@scope/pkg/sub` into the
+/// package name and the path within the package.
+fn parse_bare_specifier(specifier: &str) -> Option<(&str, Option<&str>)> {
+  let name_start = if specifier.starts_with('@') {
+    specifier.find('/')? + 1
+  } else {
+    0
+  };
+  let (name, sub_path) = match specifier[name_start..].find('/') {
+    Some(i) => {
+      let (name, sub_path) = specifier.split_at(name_start + i);
+      (name, Some(&sub_path[1..]).filter(|p| !p.is_empty()))
+    }
+    None => (specifier, None),
+  };
+  if !is_valid_package_name(name) {
+    return None;
+  }
+  Some((name, sub_path))
+}
+
+/// Resolves a bare specifier imported by `referrer` the way Node does: from
+/// the `node_modules` directories of the referrer's directory and its
+/// ancestors. Returns `None` if no such package is installed.
+pub fn resolve_bare_specifier(
+  specifier: &str,
+  referrer: &str,
+) -> Result<Option<ModuleSpecifier>, ErrBox> {
+  let referrer_path = match Url::parse(referrer) {
+    Ok(url) if url.scheme() == "file" => match url.to_file_path() {
+      Ok(path) => path,
+      Err(()) => return Ok(None),
+    },
+    _ => return Ok(None),
+  };
+  let (name, sub_path) = match parse_bare_specifier(specifier) {
+    Some(parsed) => parsed,
+    None => return Ok(None),
+  };
+  let package_dir = referrer_path
+    .ancestors()
+    .skip(1)
+    .map(|dir| dir.join("node_modules").join(name))
+    .find(|dir| dir.is_dir());
+  let package_dir = match package_dir {
+    Some(package_dir) => package_dir,
+    None => return Ok(None),
+  };
+  let entry = resolve_package_entry(&package_dir, sub_path, IMPORT_CONDITIONS)?;
+  let url = Url::from_file_path(&entry).unwrap();
+  Ok(Some(ModuleSpecifier::resolve_url(url.as_str())?))
+}
+
+/// Source of the ES module standing in for the package module at `entry`.
+pub fn wrapper_source(entry: &Path, is_es_module: bool) -> String {
+  let entry_url = Url::from_file_path(entry).unwrap().to_string();
+  let entry_url = serde_json::to_string(&entry_url).unwrap();
+  if is_es_module {
+    return format!(
+      "import * as mod from {url};\n\
+       export * from {url};\n\
+       export default mod.default;\n",
+      url = entry_url,
+    );
+  }
+  let entry_path = serde_json::to_string(&entry.to_string_lossy()).unwrap();
+  format!(
+    "import \"{std}/global.ts\";\n\
+     import {{ createRequire }} from \"{std}/module.ts\";\n\
+     const require = createRequire({url});\n\
+     export default require({path});\n",
+    std = compat::STD_NODE_URL,
+    url = entry_url,
+    path = entry_path,
+  )
+}
+
+#[cfg(unix)]
+fn symlink_dir(target: &Path, link: &Path) -> io::Result<()> {
+  std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(target: &Path, link: &Path) -> io::Result<()> {
+  std::os::windows::fs::symlink_dir(target, link)
+}
+
+/// Makes the package in `target` available as `name` to the package in
+/// `package_dir`.
+fn link_dependency(
+  package_dir: &Path,
+  name: &str,
+  target: &Path,
+) -> io::Result<()> {
+  let link = package_dir.join("node_modules").join(name);
+  if fs::symlink_metadata(&link).is_ok() {
+    return Ok(());
+  }
+  fs::create_dir_all(link.parent().unwrap())?;
+  match symlink_dir(target, &link) {
+    // Another process linked it first.
+    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+    result => result,
+  }
+}
+
+/// On-disk cache of npm packages, in `$DENO_DIR/npm/<registry host>`.
+#[derive(Clone)]
+pub struct NpmCache {
+  location: PathBuf,
+  registry: Url,
+  /// Network access to the registry is checked against these.
+  permissions: Permissions,
+  packuments: Arc<Mutex<HashMap<String, Arc<Packument>>>>,
+}
+
+impl NpmCache {
+  pub fn new(location: &Path, permissions: Permissions) -> Self {
+    let registry = registry_url();
+    let location = location.join(registry.host_str().unwrap_or("registry"));
+    Self {
+      location,
+      registry,
+      permissions,
+      packuments: Arc::new(Mutex::new(HashMap::new())),
+    }
+  }
+
+  fn package_dir(&self, name: &str, version: &Version) -> PathBuf {
+    self.location.join(name).join(version.to_string())
+  }
+
+  /// Newest downloaded version of `name` satisfying `range`.
+  fn cached_version(
+    &self,
+    name: &str,
+    range: &[VersionReq],
+  ) -> Option<Version> {
+    fs::read_dir(self.location.join(name))
+      .ok()?
+      .filter_map(|entry| entry.ok())
+      .filter_map(|entry| Version::parse(entry.file_name().to_str()?).ok())
+      .filter(|version| satisfies(range, version))
+      .max()
+  }
+
+  async fn fetch_bytes(
+    &self,
+    client: &reqwest::Client,
+    url: &Url,
+  ) -> Result<Vec<u8>, ErrBox> {
+    let mut url = url.clone();
+    for _ in 0..10 {
+      // Redirects aren't followed by the client, so every location is
+      // checked before it's requested.
+      self.permissions.check_net_url(&url)?;
+      match http_util::fetch_once(client.clone(), &url, None, None).await? {
+        FetchOnceResult::Code(body, _) => return Ok(body),
+        FetchOnceResult::Redirect(location, _) => url = location,
+        FetchOnceResult::NotModified => unreachable!(),
+      }
+    }
+    Err(OpError::http(format!("Too many redirects fetching {}", url)).into())
+  }
+
+  async fn packument(
+    &self,
+    client: &reqwest::Client,
+    name: &str,
+    cached_only: bool,
+  ) -> Result<Arc<Packument>, ErrBox> {
+    if let Some(packument) = self.packuments.lock().unwrap().get(name) {
+      return Ok(packument.clone());
+    }
+    if cached_only {
+      return Err(
+        OpError::not_found(format!(
+          "npm package \"{}\" is not in the cache, run again without --cached-only",
+          name
+        ))
+        .into(),
+      );
+    }
+    // Scoped names keep their `@` but escape the slash.
+    let url = self.registry.join(&name.replace('/', "%2f"))?;
+    let body = self.fetch_bytes(client, &url).await?;
+    let packument: Packument = serde_json::from_slice(&body).map_err(|e| {
+      OpError::other(format!("Invalid registry response for {}: {}", url, e))
+    })?;
+    let packument = Arc::new(packument);
+    self
+      .packuments
+      .lock()
+      .unwrap()
+      .insert(name.to_string(), packument.clone());
+    Ok(packument)
+  }
+
+  /// Picks the version of `name` to use for `req`, preferring downloaded
+  /// versions over asking the registry.
+  async fn resolve_version(
+    &self,
+    client: &reqwest::Client,
+    name: &str,
+    req: &str,
+    cached_only: bool,
+  ) -> Result<Version, ErrBox> {
+    let range = if req.is_empty() {
+      None
+    } else {
+      parse_range(req)
+    };
+    if let Some(range) = &range {
+      if let Some(version) = self.cached_version(name, range) {
+        return Ok(version);
+      }
+    }
+    let packument = self.packument(client, name, cached_only).await?;
+    let tag = if req.is_empty() { "latest" } else { req };
+    let version = match (packument.dist_tags.get(tag), &range) {
+      (Some(version), _) => Version::parse(version).ok(),
+      (None, Some(range)) => packument
+        .versions
+        .keys()
+        .filter_map(|version| Version::parse(version).ok())
+        .filter(|version| satisfies(range, version))
+        .max(),
+      (None, None) => None,
+    };
+    version.ok_or_else(|| {
+      OpError::not_found(format!(
+        "No version of npm package \"{}\" matches \"{}\"",
+        name, req
+      ))
+      .into()
+    })
+  }
+
+  /// Downloads version `version` of `name` unless it is already cached and
+  /// returns its directory.
+  async fn ensure_package(
+    &self,
+    client: &reqwest::Client,
+    name: &str,
+    version: &Version,
+    cached_only: bool,
+  ) -> Result<PathBuf, ErrBox> {
+    let package_dir = self.package_dir(name, version);
+    if package_dir.is_dir() {
+      return Ok(package_dir);
+    }
+    let packument = self.packument(client, name, cached_only).await?;
+    let dist = &packument
+      .versions
+      .get(&version.to_string())
+      .ok_or_else(|| {
+        OpError::not_found(format!(
+          "npm package {}@{} not found",
+          name, version
+        ))
+      })?
+      .dist;
+    let tarball_url = Url::parse(&dist.tarball)?;
+    info!("{} {}", colors::green("Download".to_string()), tarball_url);
+    let data = self.fetch_bytes(client, &tarball_url).await?;
+    verify_tarball(&data, dist)?;
+
+    // Extract next to the final location first, so that an interrupted
+    // download is never mistaken for a complete package.
+    let parent = package_dir.parent().unwrap();
+    fs::create_dir_all(parent)?;
+    let temp_dir = tempfile::Builder::new()
+      .prefix(".download-")
+      .tempdir_in(parent)?;
+    extract_tarball(&data, temp_dir.path())?;
+    if let Err(e) = fs::rename(temp_dir.path(), &package_dir) {
+      // Another process may have completed the same download.
+      if !package_dir.is_dir() {
+        return Err(e.into());
+      }
+    }
+    Ok(package_dir)
+  }
+
+  /// Installs the package `name@req` and, transitively, its dependencies.
+  /// Returns the package directory.
+  async fn install(
+    &self,
+    client: &reqwest::Client,
+    name: &str,
+    req: &str,
+    cached_only: bool,
+  ) -> Result<PathBuf, ErrBox> {
+    let version = self.resolve_version(client, name, req, cached_only).await?;
+    let root = self
+      .ensure_package(client, name, &version, cached_only)
+      .await?;
+
+    let mut visited = HashSet::new();
+    let mut queue = vec![root.clone()];
+    while let Some(package_dir) = queue.pop() {
+      if !visited.insert(package_dir.clone()) {
+        continue;
+      }
+      let package_json = read_package_json(&package_dir)?;
+      let dependencies = match package_json.get("dependencies") {
+        Some(Value::Object(dependencies)) => dependencies.clone(),
+        _ => continue,
+      };
+      for (dep_name, dep_req) in dependencies {
+        if !is_valid_package_name(&dep_name) {
+          return Err(
+            OpError::other(format!(
+              "Invalid dependency name \"{}\" in {}",
+              dep_name,
+              package_dir.join("package.json").display()
+            ))
+            .into(),
+          );
+        }
+        let dep_req = dep_req.as_str().unwrap_or("");
+        let dep_version = self
+          .resolve_version(client, &dep_name, dep_req, cached_only)
+          .await?;
+        let dep_dir = self
+          .ensure_package(client, &dep_name, &dep_version, cached_only)
+          .await?;
+        link_dependency(&package_dir, &dep_name, &dep_dir)?;
+        queue.push(dep_dir);
+      }
+    }
+    Ok(root)
+  }
+
+  fn source_file(
+    url: &Url,
+    reference: &NpmReference,
+    package_dir: &Path,
+  ) -> Result<SourceFile, ErrBox> {
+    let entry = resolve_package_entry(
+      package_dir,
+      reference.sub_path.as_deref(),
+      REQUIRE_CONDITIONS,
+    )?;
+    let source = wrapper_source(&entry, is_es_module(package_dir, &entry));
+    Ok(SourceFile {
+      url: url.clone(),
+      filename: entry,
+      types_url: None,
+      media_type: msg::MediaType::JavaScript,
+      source_code: source.into_bytes(),
+    })
+  }
+
+  /// Installs the package referenced by the `npm:` URL `url` if needed and
+  /// returns the module standing in for it.
+  pub async fn fetch_source_file(
+    &self,
+    client: &reqwest::Client,
+    url: &Url,
+    cached_only: bool,
+  ) -> Result<SourceFile, ErrBox> {
+    let reference = NpmReference::from_url(url)?;
+    let package_dir = self
+      .install(client, &reference.name, &reference.req, cached_only)
+      .await?;
+    Self::source_file(url, &reference, &package_dir)
+  }
+
+  /// Like `fetch_source_file`, but only looks at downloaded packages.
+  pub fn fetch_cached_source_file(
+    &self,
+    url: &Url,
+  ) -> Result<Option<SourceFile>, ErrBox> {
+    let reference = NpmReference::from_url(url)?;
+    let range = if reference.req.is_empty() {
+      parse_range("*")
+    } else {
+      parse_range(&reference.req)
+    };
+    let version = match range
+      .and_then(|range| self.cached_version(&reference.name, &range))
+    {
+      Some(version) => version,
+      None => return Ok(None),
+    };
+    let package_dir = self.package_dir(&reference.name, &version);
+    Self::source_file(url, &reference, &package_dir).map(Some)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  fn reference(s: &str) -> NpmReference {
+    NpmReference::from_url(&Url::parse(s).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn test_parse_reference() {
+    assert_eq!(
+      reference("npm:express"),
+      NpmReference {
+        name: "express".to_string(),
+        req: "".to_string(),
+        sub_path: None,
+      }
+    );
+    assert_eq!(
+      reference("npm:express@4/lib/router"),
+      NpmReference {
+        name: "express".to_string(),
+        req: "4".to_string(),
+        sub_path: Some("lib/router".to_string()),
+      }
+    );
+    assert_eq!(
+      reference("npm:@types/node@^14.0.0"),
+      NpmReference {
+        name: "@types/node".to_string(),
+        req: "^14.0.0".to_string(),
+        sub_path: None,
+      }
+    );
+    assert_eq!(
+      reference("npm:@scope/pkg/sub"),
+      NpmReference {
+        name: "@scope/pkg".to_string(),
+        req: "".to_string(),
+        sub_path: Some("sub".to_string()),
+      }
+    );
+    assert!(NpmReference::from_url(&Url::parse("npm:@scope").unwrap()).is_err());
+    assert!(NpmReference::from_url(&Url::parse("npm:@1.0.0").unwrap()).is_err());
+    assert!(NpmReference::from_url(&Url::parse("npm:..").unwrap()).is_err());
+    assert!(
+      NpmReference::from_url(&Url::parse("npm:@scope/..").unwrap()).is_err()
+    );
+  }
+
+  #[test]
+  fn test_is_valid_package_name() {
+    assert!(is_valid_package_name("express"));
+    assert!(is_valid_package_name("lodash.merge"));
+    assert!(is_valid_package_name("@types/node"));
+    assert!(!is_valid_package_name(""));
+    assert!(!is_valid_package_name("."));
+    assert!(!is_valid_package_name(".."));
+    assert!(!is_valid_package_name(".hidden"));
+    assert!(!is_valid_package_name("_private"));
+    assert!(!is_valid_package_name("a/b"));
+    assert!(!is_valid_package_name("a\\b"));
+    assert!(!is_valid_package_name("@scope"));
+    assert!(!is_valid_package_name("@scope/"));
+    assert!(!is_valid_package_name("@../pkg"));
+    assert!(!is_valid_package_name("@scope/.."));
+    assert!(!is_valid_package_name("@scope/pkg/sub"));
+    assert!(!is_valid_package_name("with space"));
+  }
+
+  #[test]
+  fn test_parse_range() {
+    let matches = |range: &str, version: &str| {
+      satisfies(
+        &parse_range(range).unwrap(),
+        &Version::parse(version).unwrap(),
+      )
+    };
+    assert!(matches("4", "4.17.1"));
+    assert!(!matches("4", "5.0.0"));
+    assert!(matches("1.2.3", "1.2.3"));
+    assert!(!matches("1.2.3", "1.2.4"));
+    assert!(matches("^1.2.3", "1.9.0"));
+    assert!(matches("~1.2.3", "1.2.9"));
+    assert!(!matches("~1.2.3", "1.3.0"));
+    assert!(matches(">= 1.0.0 < 2", "1.5.0"));
+    assert!(!matches(">=1.0.0 <2", "2.0.0"));
+    assert!(matches("1.x", "1.4.0"));
+    assert!(matches("*", "3.0.0"));
+    assert!(matches("1.0.0 - 1.2.0", "1.1.0"));
+    assert!(matches("^1.0.0 || ^2.0.0", "2.1.0"));
+    assert!(!matches("^1.0.0 || ^2.0.0", "3.0.0"));
+  }
+
+  #[test]
+  fn test_resolve_package_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::write(dir.join("lib/main.js"), "").unwrap();
+    fs::write(dir.join("lib/util.js"), "").unwrap();
+    fs::write(dir.join("index.js"), "").unwrap();
+
+    fs::write(dir.join("package.json"), r#"{"main": "lib/main"}"#).unwrap();
+    let entry = resolve_package_entry(dir, None, REQUIRE_CONDITIONS).unwrap();
+    assert_eq!(entry, dir.join("lib/main.js"));
+    let entry =
+      resolve_package_entry(dir, Some("lib/util"), REQUIRE_CONDITIONS).unwrap();
+    assert_eq!(entry, dir.join("lib/util.js"));
+
+    fs::write(dir.join("package.json"), "{}").unwrap();
+    let entry = resolve_package_entry(dir, None, REQUIRE_CONDITIONS).unwrap();
+    assert_eq!(entry, dir.join("index.js"));
+
+    fs::write(
+      dir.join("package.json"),
+      r#"{
+        "main": "index.js",
+        "exports": {
+          ".": { "import": "./lib/util.js", "require": "./lib/main.js" },
+          "./util": "./lib/util.js"
+        }
+      }"#,
+    )
+    .unwrap();
+    let entry = resolve_package_entry(dir, None, REQUIRE_CONDITIONS).unwrap();
+    assert_eq!(entry, dir.join("lib/main.js"));
+    let entry =
+      resolve_package_entry(dir, Some("util"), REQUIRE_CONDITIONS).unwrap();
+    assert_eq!(entry, dir.join("lib/util.js"));
+    assert!(
+      resolve_package_entry(dir, Some("lib/main"), REQUIRE_CONDITIONS).is_err()
+    );
+
+    let package_dir = dir.join("pkg");
+    fs::create_dir_all(&package_dir).unwrap();
+    fs::write(
+      package_dir.join("package.json"),
+      r#"{ "exports": { ".": "../index.js", "./abs": "/etc/passwd" } }"#,
+    )
+    .unwrap();
+    assert!(
+      resolve_package_entry(&package_dir, None, REQUIRE_CONDITIONS).is_err()
+    );
+    assert!(resolve_package_entry(
+      &package_dir,
+      Some("abs"),
+      REQUIRE_CONDITIONS
+    )
+    .is_err());
+    fs::write(package_dir.join("package.json"), "{}").unwrap();
+    assert!(resolve_package_entry(
+      &package_dir,
+      Some("../index.js"),
+      REQUIRE_CONDITIONS
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn test_is_es_module() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("package.json"), "{}").unwrap();
+    assert!(!is_es_module(dir, &dir.join("index.js")));
+    assert!(is_es_module(dir, &dir.join("index.mjs")));
+    fs::write(dir.join("package.json"), r#"{"type": "module"}"#).unwrap();
+    assert!(is_es_module(dir, &dir.join("index.js")));
+    assert!(!is_es_module(dir, &dir.join("index.cjs")));
+  }
+
+  #[test]
+  fn test_parse_bare_specifier() {
+    assert_eq!(parse_bare_specifier("lodash"), Some(("lodash", None)));
+    assert_eq!(
+      parse_bare_specifier("lodash/fp"),
+      Some(("lodash", Some("fp")))
+    );
+    assert_eq!(
+      parse_bare_specifier("@scope/pkg/a/b"),
+      Some(("@scope/pkg", Some("a/b")))
+    );
+    assert_eq!(parse_bare_specifier("@scope"), None);
+    assert_eq!(parse_bare_specifier("../pkg"), None);
+  }
+
+  #[test]
+  fn test_resolve_bare_specifier() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let package_dir = root.join("node_modules").join("left-pad");
+    fs::create_dir_all(&package_dir).unwrap();
+    fs::write(package_dir.join("package.json"), r#"{"main": "lib.js"}"#)
+      .unwrap();
+    fs::write(package_dir.join("lib.js"), "module.exports = 1;").unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    let referrer = Url::from_file_path(root.join("src").join("main.ts"))
+      .unwrap()
+      .to_string();
+
+    let resolved = resolve_bare_specifier("left-pad", &referrer)
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      resolved.as_url().to_file_path().unwrap(),
+      package_dir.join("lib.js")
+    );
+    assert!(is_commonjs_package_file(&package_dir.join("lib.js")));
+    assert!(!is_commonjs_package_file(&root.join("src").join("main.js")));
+
+    assert!(resolve_bare_specifier("right-pad", &referrer)
+      .unwrap()
+      .is_none());
+    assert!(
+      resolve_bare_specifier("left-pad", "https://example.com/a.js")
+        .unwrap()
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn test_verify_tarball() {
+    let dist = |shasum: Option<&str>, integrity: Option<&str>| Dist {
+      tarball: "https://registry.npmjs.org/a/-/a-1.0.0.tgz".to_string(),
+      shasum: shasum.map(String::from),
+      integrity: integrity.map(String::from),
+    };
+    // sha1("hello")
+    let sha1 = "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d";
+    assert!(verify_tarball(b"hello", &dist(Some(sha1), None)).is_ok());
+    assert!(verify_tarball(b"hello!", &dist(Some(sha1), None)).is_err());
+    let sha512 = base64::encode(digest::digest(&digest::SHA512, b"hello"));
+    let integrity = format!("sha512-{}", sha512);
+    assert!(verify_tarball(b"hello", &dist(None, Some(&integrity))).is_ok());
+    assert!(verify_tarball(b"hello!", &dist(None, Some(&integrity))).is_err());
+  }
+}
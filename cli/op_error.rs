@@ -17,11 +17,13 @@
 use crate::import_map::ImportMapError;
 use deno_core::ErrBox;
 use deno_core::ModuleResolutionError;
+#[cfg(feature = "repl")]
 use rustyline::error::ReadlineError;
 use std::env::VarError;
 use std::error::Error;
 use std::fmt;
 use std::io;
+use trust_dns_resolver::error::ResolveError;
 
 // Warning! The values in this enum are duplicated in js/errors.ts
 // Update carefully!
@@ -51,6 +53,14 @@ pub enum ErrorKind {
   /// https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Error
   Other = 22,
   Busy = 23,
+  /// An op whose cancel handle was aborted, see `ops::cancel`. JS throws a
+  /// `DOMException` named "AbortError" for it.
+  Aborted = 24,
+  /// The operation isn't available on this platform, eg. `chown` on Windows.
+  NotSupported = 25,
+  /// A storage area is full. JS throws a `DOMException` named
+  /// "QuotaExceededError" for it.
+  QuotaExceeded = 26,
 }
 
 #[derive(Debug)]
@@ -72,10 +82,18 @@ impl OpError {
     Self::other("not implemented".to_string())
   }
 
+  pub fn not_supported(msg: String) -> Self {
+    Self::new(ErrorKind::NotSupported, msg)
+  }
+
   pub fn other(msg: String) -> Self {
     Self::new(ErrorKind::Other, msg)
   }
 
+  pub fn invalid_data(msg: String) -> Self {
+    Self::new(ErrorKind::InvalidData, msg)
+  }
+
   pub fn type_error(msg: String) -> Self {
     Self::new(ErrorKind::TypeError, msg)
   }
@@ -105,6 +123,14 @@ impl OpError {
     Self::new(ErrorKind::InvalidData, "invalid utf8".to_string())
   }
 
+  pub fn quota_exceeded(msg: String) -> OpError {
+    Self::new(ErrorKind::QuotaExceeded, msg)
+  }
+
+  pub fn aborted() -> OpError {
+    Self::new(ErrorKind::Aborted, "The operation was aborted".to_string())
+  }
+
   pub fn resource_unavailable() -> OpError {
     Self::new(
       ErrorKind::Busy,
@@ -263,12 +289,14 @@ impl From<&reqwest::Error> for OpError {
   }
 }
 
+#[cfg(feature = "repl")]
 impl From<ReadlineError> for OpError {
   fn from(error: ReadlineError) -> Self {
     OpError::from(&error)
   }
 }
 
+#[cfg(feature = "repl")]
 impl From<&ReadlineError> for OpError {
   fn from(error: &ReadlineError) -> Self {
     use ReadlineError::*;
@@ -381,6 +409,55 @@ impl From<&notify::Error> for OpError {
   }
 }
 
+impl From<ResolveError> for OpError {
+  fn from(error: ResolveError) -> Self {
+    OpError::from(&error)
+  }
+}
+
+impl From<&ResolveError> for OpError {
+  fn from(error: &ResolveError) -> Self {
+    use trust_dns_resolver::error::ResolveErrorKind::*;
+    let kind = match error.kind() {
+      NoRecordsFound { .. } => ErrorKind::NotFound,
+      Io(e) => return e.into(),
+      Timeout => ErrorKind::TimedOut,
+      _ => ErrorKind::Other,
+    };
+
+    Self {
+      kind,
+      msg: error.to_string(),
+    }
+  }
+}
+
+impl From<rusqlite::Error> for OpError {
+  fn from(error: rusqlite::Error) -> Self {
+    OpError::from(&error)
+  }
+}
+
+impl From<&rusqlite::Error> for OpError {
+  fn from(error: &rusqlite::Error) -> Self {
+    use rusqlite::ErrorCode::*;
+    let kind = match error {
+      rusqlite::Error::SqliteFailure(e, _) => match e.code {
+        DatabaseBusy | DatabaseLocked => ErrorKind::Busy,
+        ReadOnly | PermissionDenied => ErrorKind::PermissionDenied,
+        CannotOpen => ErrorKind::NotFound,
+        _ => ErrorKind::Other,
+      },
+      _ => ErrorKind::Other,
+    };
+
+    Self {
+      kind,
+      msg: error.to_string(),
+    }
+  }
+}
+
 impl From<ErrBox> for OpError {
   fn from(error: ErrBox) -> Self {
     #[cfg(unix)]
@@ -393,6 +470,16 @@ impl From<ErrBox> for OpError {
       None
     }
 
+    #[cfg(feature = "repl")]
+    fn readline_error_kind(err: &ErrBox) -> Option<OpError> {
+      err.downcast_ref::<ReadlineError>().map(|e| e.into())
+    }
+
+    #[cfg(not(feature = "repl"))]
+    fn readline_error_kind(_: &ErrBox) -> Option<OpError> {
+      None
+    }
+
     None
       .or_else(|| {
         error
@@ -409,7 +496,7 @@ impl From<ErrBox> for OpError {
       })
       .or_else(|| error.downcast_ref::<url::ParseError>().map(|e| e.into()))
       .or_else(|| error.downcast_ref::<VarError>().map(|e| e.into()))
-      .or_else(|| error.downcast_ref::<ReadlineError>().map(|e| e.into()))
+      .or_else(|| readline_error_kind(&error))
       .or_else(|| {
         error
           .downcast_ref::<serde_json::error::Error>()
@@ -417,6 +504,7 @@ impl From<ErrBox> for OpError {
       })
       .or_else(|| error.downcast_ref::<dlopen::Error>().map(|e| e.into()))
       .or_else(|| error.downcast_ref::<notify::Error>().map(|e| e.into()))
+      .or_else(|| error.downcast_ref::<rusqlite::Error>().map(|e| e.into()))
       .or_else(|| unix_error_kind(&error))
       .unwrap_or_else(|| {
         panic!("Can't downcast {:?} to OpError", error);
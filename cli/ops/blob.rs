@@ -0,0 +1,223 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::io::{StreamResource, StreamResourceHolder};
+use super::stream_resource::StreamResourceReader;
+use crate::blob_store::Blob;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use std::sync::Arc;
+use url::Url;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_blob_create", s.stateful_json_op(op_blob_create));
+  i.register_op("op_blob_slice", s.stateful_json_op(op_blob_slice));
+  i.register_op("op_blob_read", s.stateful_json_op(op_blob_read));
+  i.register_op("op_blob_remove", s.stateful_json_op(op_blob_remove));
+  i.register_op("op_blob_stream", s.stateful_json_op2(op_blob_stream));
+  i.register_op(
+    "op_blob_create_object_url",
+    s.stateful_json_op(op_blob_create_object_url),
+  );
+  i.register_op(
+    "op_blob_revoke_object_url",
+    s.stateful_json_op(op_blob_revoke_object_url),
+  );
+  i.register_op(
+    "op_blob_from_object_url",
+    s.stateful_json_op(op_blob_from_object_url),
+  );
+}
+
+fn get_blob(state: &State, id: u32) -> Result<Arc<Blob>, OpError> {
+  state
+    .borrow()
+    .blobs
+    .get(id)
+    .cloned()
+    .ok_or_else(|| OpError::not_found("Blob not found".to_string()))
+}
+
+#[derive(Deserialize)]
+struct PartArgs {
+  /// The contents of another blob.
+  blob: Option<u32>,
+  /// The next `len` bytes of `zero_copy`.
+  len: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct CreateArgs {
+  #[serde(rename = "type")]
+  media_type: String,
+  parts: Vec<PartArgs>,
+}
+
+fn op_blob_create(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CreateArgs = serde_json::from_value(args)?;
+  let bytes: &[u8] = zero_copy.as_deref().unwrap_or(&[]);
+  let mut blob = Blob::new(args.media_type);
+  let mut offset = 0;
+  for part in args.parts {
+    match (part.blob, part.len) {
+      (Some(id), _) => blob.push_blob(&get_blob(state, id)?),
+      (None, Some(len)) => {
+        let end = offset + len;
+        if end > bytes.len() {
+          return Err(OpError::type_error("Invalid blob part".to_string()));
+        }
+        blob.push_bytes(bytes[offset..end].to_vec());
+        offset = end;
+      }
+      (None, None) => {
+        return Err(OpError::type_error("Invalid blob part".to_string()))
+      }
+    }
+  }
+  let id = state.borrow_mut().blobs.add(blob);
+  Ok(JsonOp::Sync(json!(id)))
+}
+
+#[derive(Deserialize)]
+struct SliceArgs {
+  id: u32,
+  start: usize,
+  end: usize,
+  #[serde(rename = "type")]
+  media_type: String,
+}
+
+fn op_blob_slice(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: SliceArgs = serde_json::from_value(args)?;
+  let slice =
+    get_blob(state, args.id)?.slice(args.start, args.end, args.media_type);
+  let id = state.borrow_mut().blobs.add(slice);
+  Ok(JsonOp::Sync(json!(id)))
+}
+
+#[derive(Deserialize)]
+struct ReadArgs {
+  id: u32,
+  offset: usize,
+}
+
+/// Reads the blob from `offset` into `zero_copy`, returning the number of
+/// bytes read.
+fn op_blob_read(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ReadArgs = serde_json::from_value(args)?;
+  let mut buf = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a buffer".to_string()))?;
+  let nread = get_blob(state, args.id)?.read_at(args.offset, &mut buf);
+  Ok(JsonOp::Sync(json!(nread)))
+}
+
+#[derive(Deserialize)]
+struct IdArgs {
+  id: u32,
+}
+
+fn op_blob_remove(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: IdArgs = serde_json::from_value(args)?;
+  state.borrow_mut().blobs.remove(args.id);
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Returns a stream resource reading the blob.
+fn op_blob_stream(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: IdArgs = serde_json::from_value(args)?;
+  let blob = get_blob(state, args.id)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add(
+    "blobStream",
+    Box::new(StreamResourceHolder::new(StreamResource::ByteStream(
+      Box::new(StreamResourceReader::new(blob.stream())),
+    ))),
+  );
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+fn op_blob_create_object_url(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: IdArgs = serde_json::from_value(args)?;
+  let blob = get_blob(state, args.id)?;
+  let state = state.borrow();
+  let global_state = &state.global_state;
+  // Without `--location`, the origin is opaque.
+  let origin = match &global_state.flags.location {
+    Some(location) => location.origin().ascii_serialization(),
+    None => "null".to_string(),
+  };
+  let url = global_state.blob_store.create_object_url(&origin, blob);
+  Ok(JsonOp::Sync(json!(url)))
+}
+
+#[derive(Deserialize)]
+struct UrlArgs {
+  url: String,
+}
+
+fn op_blob_revoke_object_url(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: UrlArgs = serde_json::from_value(args)?;
+  // Like in browsers, revoking anything but an object URL does nothing.
+  if let Ok(url) = Url::parse(&args.url) {
+    state
+      .borrow()
+      .global_state
+      .blob_store
+      .revoke_object_url(&url);
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Returns the id, size and type of the blob of an object URL, or null if
+/// there is none.
+fn op_blob_from_object_url(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: UrlArgs = serde_json::from_value(args)?;
+  let url = Url::parse(&args.url)?;
+  let mut state = state.borrow_mut();
+  let blob = match state.global_state.blob_store.get(&url) {
+    Some(blob) => blob,
+    None => return Ok(JsonOp::Sync(json!(null))),
+  };
+  let size = blob.size();
+  let media_type = blob.media_type.clone();
+  let id = state.blobs.add_shared(blob);
+  Ok(JsonOp::Sync(json!({
+    "id": id,
+    "size": size,
+    "type": media_type,
+  })))
+}
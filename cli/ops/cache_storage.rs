@@ -0,0 +1,299 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::io::{FileMetadata, StreamResource, StreamResourceHolder};
+use crate::cache_storage::CacheRequest;
+use crate::cache_storage::CacheResponse;
+use crate::cache_storage::OriginCaches;
+use crate::cache_storage::QueryOptions;
+use crate::op_error::OpError;
+use crate::state::State;
+use crate::web_storage;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_cache_storage_open",
+    s.stateful_json_op(op_cache_storage_open),
+  );
+  i.register_op(
+    "op_cache_storage_has",
+    s.stateful_json_op(op_cache_storage_has),
+  );
+  i.register_op(
+    "op_cache_storage_delete",
+    s.stateful_json_op(op_cache_storage_delete),
+  );
+  i.register_op(
+    "op_cache_storage_keys",
+    s.stateful_json_op(op_cache_storage_keys),
+  );
+  i.register_op(
+    "op_cache_put_start",
+    s.stateful_json_op2(op_cache_put_start),
+  );
+  i.register_op(
+    "op_cache_put_write",
+    s.stateful_json_op2(op_cache_put_write),
+  );
+  i.register_op(
+    "op_cache_put_finish",
+    s.stateful_json_op2(op_cache_put_finish),
+  );
+  i.register_op("op_cache_match", s.stateful_json_op2(op_cache_match));
+  i.register_op("op_cache_delete", s.stateful_json_op(op_cache_delete));
+  i.register_op("op_cache_keys", s.stateful_json_op(op_cache_keys));
+}
+
+/// Calls `f` with the caches of the isolate's origin.
+fn with_origin<T>(
+  state: &State,
+  f: impl FnOnce(&mut OriginCaches) -> Result<T, OpError>,
+) -> Result<T, OpError> {
+  let state = state.borrow();
+  let global_state = &state.global_state;
+  let origin = web_storage::origin(
+    global_state.flags.location.as_ref(),
+    &state.main_module,
+  );
+  global_state.cache_storage.with_origin(&origin, f)
+}
+
+#[derive(Deserialize)]
+struct NameArgs {
+  name: String,
+}
+
+/// Returns the id of the named cache, creating it if needed.
+fn op_cache_storage_open(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NameArgs = serde_json::from_value(args)?;
+  let id = with_origin(state, |caches| caches.open_cache(&args.name))?;
+  Ok(JsonOp::Sync(json!(id)))
+}
+
+fn op_cache_storage_has(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NameArgs = serde_json::from_value(args)?;
+  let has = with_origin(state, |caches| caches.has_cache(&args.name))?;
+  Ok(JsonOp::Sync(json!(has)))
+}
+
+fn op_cache_storage_delete(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NameArgs = serde_json::from_value(args)?;
+  let deleted = with_origin(state, |caches| caches.delete_cache(&args.name))?;
+  Ok(JsonOp::Sync(json!(deleted)))
+}
+
+fn op_cache_storage_keys(
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let names = with_origin(state, |caches| caches.cache_names())?;
+  Ok(JsonOp::Sync(json!(names)))
+}
+
+/// An entry being put in a cache, whose body is written to `path` by
+/// `op_cache_put_write`. Until `op_cache_put_finish` moves it into the
+/// cache, closing the resource discards the body.
+struct CachePutResource {
+  cache_id: i64,
+  request: CacheRequest,
+  response: CacheResponse,
+  /// Closed when the body is moved into the cache.
+  file: Option<fs::File>,
+  path: PathBuf,
+}
+
+impl Drop for CachePutResource {
+  fn drop(&mut self) {
+    // Does nothing once the body was moved into the cache.
+    let _ = fs::remove_file(&self.path);
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutStartArgs {
+  cache_id: i64,
+  request: CacheRequest,
+  response: CacheResponse,
+}
+
+fn op_cache_put_start(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: PutStartArgs = serde_json::from_value(args)?;
+  let path = with_origin(state, |caches| Ok(caches.new_body_path()))?;
+  let file = fs::File::create(&path)?;
+  let resource = CachePutResource {
+    cache_id: args.cache_id,
+    request: args.request,
+    response: args.response,
+    file: Some(file),
+    path,
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add("cachePut", Box::new(resource));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct PutArgs {
+  rid: u32,
+}
+
+/// Appends the chunk in `zero_copy` to the body of the entry.
+fn op_cache_put_write(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: PutArgs = serde_json::from_value(args)?;
+  let chunk = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a chunk".to_string()))?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<CachePutResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  resource.file.as_mut().unwrap().write_all(&chunk)?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Adds the entry to its cache, with the body written so far.
+fn op_cache_put_finish(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: PutArgs = serde_json::from_value(args)?;
+  let mut resource = isolate
+    .resource_table
+    .borrow_mut()
+    .remove::<CachePutResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  resource.file.take().unwrap().sync_all()?;
+  with_origin(state, |caches| {
+    caches.put(
+      resource.cache_id,
+      resource.request.clone(),
+      resource.response.clone(),
+      resource.path.clone(),
+    )
+  })?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MatchArgs {
+  /// `None` to look in every cache, like `caches.match()`.
+  cache_id: Option<i64>,
+  request: Option<CacheRequest>,
+  options: QueryOptions,
+  /// Returns only the first match, for `match()`.
+  first: bool,
+}
+
+/// Returns the responses matching the request, each with the rid of a file
+/// resource to read its body from.
+fn op_cache_match(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: MatchArgs = serde_json::from_value(args)?;
+  let mut entries = with_origin(state, |caches| {
+    caches.query(args.cache_id, args.request.as_ref(), args.options)
+  })?;
+  if args.first {
+    entries.truncate(1);
+  }
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let mut responses = vec![];
+  for (_, entry) in entries {
+    let file = tokio::fs::File::from_std(fs::File::open(&entry.body)?);
+    let body_rid = resource_table.add(
+      "fsFile",
+      Box::new(StreamResourceHolder::new(StreamResource::FsFile(Some((
+        file,
+        FileMetadata::default(),
+      ))))),
+    );
+    let response = entry.response;
+    responses.push(json!({
+      "url": response.url,
+      "status": response.status,
+      "statusText": response.status_text,
+      "headers": response.headers,
+      "bodyRid": body_rid,
+    }));
+  }
+  Ok(JsonOp::Sync(json!(responses)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteArgs {
+  cache_id: i64,
+  request: CacheRequest,
+  options: QueryOptions,
+}
+
+fn op_cache_delete(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: DeleteArgs = serde_json::from_value(args)?;
+  let deleted = with_origin(state, |caches| {
+    caches.delete(args.cache_id, &args.request, args.options)
+  })?;
+  Ok(JsonOp::Sync(json!(deleted)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeysArgs {
+  cache_id: i64,
+  request: Option<CacheRequest>,
+  options: QueryOptions,
+}
+
+/// Returns the requests of the entries matching the request, or of every
+/// entry.
+fn op_cache_keys(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: KeysArgs = serde_json::from_value(args)?;
+  let entries = with_origin(state, |caches| {
+    caches.query(Some(args.cache_id), args.request.as_ref(), args.options)
+  })?;
+  let requests: Vec<CacheRequest> = entries
+    .into_iter()
+    .map(|(_, entry)| entry.request)
+    .collect();
+  Ok(JsonOp::Sync(json!(requests)))
+}
@@ -0,0 +1,102 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Cancellation of async ops. JS creates a cancel handle for an `AbortSignal`
+//! and passes its rid along with async ops (`cancelRid` for JSON ops, the
+//! fourth control int for minimal ops). Aborting the handle drops the ops'
+//! futures, which closes whatever they were waiting on, and rejects their
+//! promises with an `AbortError`.
+
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::future::AbortHandle;
+use futures::future::Abortable;
+use futures::future::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_cancel_handle_new",
+    s.stateful_json_op2(op_cancel_handle_new),
+  );
+  i.register_op(
+    "op_cancel_handle_abort",
+    s.stateful_json_op2(op_cancel_handle_abort),
+  );
+}
+
+#[derive(Default)]
+struct CancelHandle {
+  aborted: bool,
+  abort_handles: Vec<AbortHandle>,
+}
+
+impl CancelHandle {
+  fn abort(&mut self) {
+    self.aborted = true;
+    for abort_handle in self.abort_handles.drain(..) {
+      abort_handle.abort();
+    }
+  }
+}
+
+type OpFuture<T> = Pin<Box<dyn Future<Output = Result<T, OpError>>>>;
+
+/// Ties `fut` to the cancel handle `cancel_rid`. If the handle has been
+/// aborted already, `fut` is dropped right away.
+pub fn cancelable<T: 'static>(
+  isolate: &CoreIsolate,
+  cancel_rid: u32,
+  fut: OpFuture<T>,
+) -> OpFuture<T> {
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let handle = match resource_table.get_mut::<CancelHandle>(cancel_rid) {
+    Some(handle) => handle,
+    None => {
+      return futures::future::err(OpError::bad_resource_id()).boxed_local()
+    }
+  };
+  if handle.aborted {
+    return futures::future::err(OpError::aborted()).boxed_local();
+  }
+  let (abort_handle, registration) = AbortHandle::new_pair();
+  handle.abort_handles.push(abort_handle);
+  Abortable::new(fut, registration)
+    .map(|result| result.unwrap_or_else(|_| Err(OpError::aborted())))
+    .boxed_local()
+}
+
+fn op_cancel_handle_new(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid =
+    resource_table.add("cancelHandle", Box::new(CancelHandle::default()));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct CancelHandleArgs {
+  rid: u32,
+}
+
+fn op_cancel_handle_abort(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CancelHandleArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let handle = resource_table
+    .get_mut::<CancelHandle>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  handle.abort();
+  Ok(JsonOp::Sync(json!({})))
+}
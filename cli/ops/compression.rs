@@ -0,0 +1,132 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::compression::Format;
+use crate::compression::Transformer;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_compression_new",
+    s.stateful_json_op2(op_compression_new),
+  );
+  i.register_op(
+    "op_compression_write",
+    s.stateful_json_op2(op_compression_write),
+  );
+  i.register_op(
+    "op_compression_finish",
+    s.stateful_json_op2(op_compression_finish),
+  );
+  i.register_op(
+    "op_compression_read",
+    s.stateful_json_op2(op_compression_read),
+  );
+}
+
+/// A compression or decompression stream. Output produced by a write or
+/// finish is held in `pending` until JS reads it with
+/// `op_compression_read`.
+struct CompressionResource {
+  transformer: Transformer,
+  pending: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct CompressionNewArgs {
+  format: String,
+  decompress: bool,
+}
+
+fn op_compression_new(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CompressionNewArgs = serde_json::from_value(args)?;
+  let format = Format::from_name(&args.format).ok_or_else(|| {
+    OpError::type_error(format!(
+      "Unsupported compression format: \"{}\"",
+      args.format
+    ))
+  })?;
+  let transformer = if args.decompress {
+    Transformer::decompress(format)
+  } else {
+    Transformer::compress(format)
+  };
+  let resource = CompressionResource {
+    transformer,
+    pending: Vec::new(),
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add("compression", Box::new(resource));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct CompressionArgs {
+  rid: u32,
+}
+
+/// Feeds the chunk in `zero_copy` to the stream and returns the number of
+/// output bytes that are ready to be read.
+fn op_compression_write(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CompressionArgs = serde_json::from_value(args)?;
+  let chunk = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a chunk".to_string()))?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<CompressionResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let output = resource.transformer.write(&chunk)?;
+  resource.pending.extend(output);
+  Ok(JsonOp::Sync(json!(resource.pending.len())))
+}
+
+/// Ends the stream; like `op_compression_write` this returns the number of
+/// output bytes to read.
+fn op_compression_finish(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CompressionArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<CompressionResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let output = resource.transformer.finish()?;
+  resource.pending.extend(output);
+  Ok(JsonOp::Sync(json!(resource.pending.len())))
+}
+
+/// Moves pending output into `zero_copy` and returns the number of bytes
+/// copied.
+fn op_compression_read(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CompressionArgs = serde_json::from_value(args)?;
+  let mut buf = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a buffer".to_string()))?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<CompressionResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let n = buf.len().min(resource.pending.len());
+  buf[..n].copy_from_slice(&resource.pending[..n]);
+  resource.pending.drain(..n);
+  Ok(JsonOp::Sync(json!(n)))
+}
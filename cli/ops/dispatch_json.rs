@@ -1,4 +1,5 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::cancel::cancelable;
 use crate::op_error::OpError;
 use deno_core::Buf;
 use deno_core::CoreIsolate;
@@ -42,6 +43,7 @@ fn serialize_result(promise_id: Option<u64>, result: JsonResult) -> Buf {
 #[serde(rename_all = "camelCase")]
 struct AsyncArgs {
   promise_id: Option<u64>,
+  cancel_rid: Option<u32>,
 }
 
 pub fn json_op<D>(
@@ -62,6 +64,7 @@ where
       }
     };
     let promise_id = async_args.promise_id;
+    let cancel_rid = async_args.cancel_rid;
     let is_sync = promise_id.is_none();
 
     let result = serde_json::from_slice(control)
@@ -76,6 +79,10 @@ where
       }
       Ok(JsonOp::Async(fut)) => {
         assert!(promise_id.is_some());
+        let fut = match cancel_rid {
+          Some(rid) => cancelable(isolate, rid, fut),
+          None => fut,
+        };
         let fut2 = fut.then(move |result| {
           futures::future::ready(serialize_result(promise_id, result))
         });
@@ -83,6 +90,10 @@ where
       }
       Ok(JsonOp::AsyncUnref(fut)) => {
         assert!(promise_id.is_some());
+        let fut = match cancel_rid {
+          Some(rid) => cancelable(isolate, rid, fut),
+          None => fut,
+        };
         let fut2 = fut.then(move |result| {
           futures::future::ready(serialize_result(promise_id, result))
         });
@@ -100,6 +111,7 @@ where
   }
 }
 
+/// Run `f` synchronously or, for async ops, on the blocking thread pool.
 pub fn blocking_json<F>(is_sync: bool, f: F) -> Result<JsonOp, OpError>
 where
   F: 'static + Send + FnOnce() -> JsonResult,
@@ -107,7 +119,7 @@ where
   if is_sync {
     Ok(JsonOp::Sync(f()?))
   } else {
-    let fut = async move { tokio::task::spawn_blocking(f).await.unwrap() };
+    let fut = crate::tokio_util::spawn_blocking(f);
     Ok(JsonOp::Async(fut.boxed_local()))
   }
 }
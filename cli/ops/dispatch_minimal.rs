@@ -4,6 +4,7 @@
 //! alternative to flatbuffers using a very simple list of int32s to lay out
 //! messages. The first i32 is used to determine if a message a flatbuffer
 //! message or a "minimal" message.
+use super::cancel::cancelable;
 use crate::op_error::OpError;
 use byteorder::{LittleEndian, WriteBytesExt};
 use deno_core::Buf;
@@ -74,7 +75,9 @@ fn test_error_record() {
   assert_eq!(buf, expected.into_boxed_slice());
 }
 
-pub fn parse_min_record(bytes: &[u8]) -> Option<Record> {
+/// Parses the control buffer of a minimal op. A fourth int, if present and
+/// not negative, is the rid of a cancel handle for async ops.
+pub fn parse_min_record(bytes: &[u8]) -> Option<(Record, Option<u32>)> {
   if bytes.len() % std::mem::size_of::<i32>() != 0 {
     return None;
   }
@@ -83,16 +86,18 @@ pub fn parse_min_record(bytes: &[u8]) -> Option<Record> {
   let p32 = p as *const i32;
   let s = unsafe { std::slice::from_raw_parts(p32, bytes.len() / 4) };
 
-  if s.len() != 3 {
+  if s.len() != 3 && s.len() != 4 {
     return None;
   }
   let ptr = s.as_ptr();
-  let ints = unsafe { std::slice::from_raw_parts(ptr, 3) };
-  Some(Record {
+  let ints = unsafe { std::slice::from_raw_parts(ptr, s.len()) };
+  let record = Record {
     promise_id: ints[0],
     arg: ints[1],
     result: ints[2],
-  })
+  };
+  let cancel_rid = ints.get(3).filter(|rid| **rid >= 0).map(|rid| *rid as u32);
+  Some((record, cancel_rid))
 }
 
 #[test]
@@ -100,13 +105,22 @@ fn test_parse_min_record() {
   let buf = vec![1, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0];
   assert_eq!(
     parse_min_record(&buf),
-    Some(Record {
-      promise_id: 1,
-      arg: 3,
-      result: 4,
-    })
+    Some((
+      Record {
+        promise_id: 1,
+        arg: 3,
+        result: 4,
+      },
+      None
+    ))
   );
 
+  let buf = vec![1, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0];
+  assert_eq!(parse_min_record(&buf).unwrap().1, Some(5));
+
+  let buf = vec![1, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 255, 255, 255, 255];
+  assert_eq!(parse_min_record(&buf).unwrap().1, None);
+
   let buf = vec![];
   assert_eq!(parse_min_record(&buf), None);
 
@@ -123,7 +137,7 @@ where
   move |isolate: &mut CoreIsolate,
         control: &[u8],
         zero_copy: Option<ZeroCopyBuf>| {
-    let mut record = match parse_min_record(control) {
+    let (mut record, cancel_rid) = match parse_min_record(control) {
       Some(r) => r,
       None => {
         let e = OpError::type_error("Unparsable control buffer".to_string());
@@ -157,6 +171,10 @@ where
         }
       }),
       MinimalOp::Async(min_fut) => {
+        let min_fut = match cancel_rid {
+          Some(cancel_rid) => cancelable(isolate, cancel_rid, min_fut),
+          None => min_fut,
+        };
         let fut = async move {
           match min_fut.await {
             Ok(r) => {
@@ -0,0 +1,168 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use encoding_rs::CoderResult;
+use encoding_rs::Decoder;
+use encoding_rs::DecoderResult;
+use encoding_rs::Encoding;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_encoding_normalize_label",
+    s.stateful_json_op(op_encoding_normalize_label),
+  );
+  i.register_op(
+    "op_encoding_decode_single",
+    s.stateful_json_op(op_encoding_decode_single),
+  );
+  i.register_op(
+    "op_encoding_new_decoder",
+    s.stateful_json_op2(op_encoding_new_decoder),
+  );
+  i.register_op(
+    "op_encoding_decode",
+    s.stateful_json_op2(op_encoding_decode),
+  );
+}
+
+#[derive(Deserialize)]
+struct NormalizeLabelArgs {
+  label: String,
+}
+
+/// Resolves an encoding label to the encoding's lowercased name, or `null` if
+/// `TextDecoder` doesn't support the label.
+fn op_encoding_normalize_label(
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: NormalizeLabelArgs = serde_json::from_value(args)?;
+  let name = Encoding::for_label_no_replacement(args.label.as_bytes())
+    .map(|encoding| encoding.name().to_lowercase());
+  Ok(JsonOp::Sync(json!(name)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DecoderArgs {
+  encoding: String,
+  fatal: bool,
+  ignore_bom: bool,
+}
+
+impl DecoderArgs {
+  fn new_decoder(&self) -> Result<TextDecoderResource, OpError> {
+    let encoding =
+      Encoding::for_label(self.encoding.as_bytes()).ok_or_else(|| {
+        OpError::type_error(format!(
+          "Internal decoder ('{}') not found.",
+          self.encoding
+        ))
+      })?;
+    let decoder = if self.ignore_bom {
+      encoding.new_decoder_without_bom_handling()
+    } else {
+      encoding.new_decoder_with_bom_removal()
+    };
+    Ok(TextDecoderResource {
+      decoder,
+      fatal: self.fatal,
+    })
+  }
+}
+
+struct TextDecoderResource {
+  decoder: Decoder,
+  fatal: bool,
+}
+
+impl TextDecoderResource {
+  /// Decodes `input`. Unless `last` is set, a partial sequence at the end of
+  /// `input` is held by the decoder and completed by the next call.
+  fn decode(&mut self, input: &[u8], last: bool) -> Result<String, OpError> {
+    let too_large = || OpError::type_error("Input is too large".to_string());
+    let mut output = String::new();
+    if self.fatal {
+      let max_length = self
+        .decoder
+        .max_utf8_buffer_length_without_replacement(input.len())
+        .ok_or_else(too_large)?;
+      output.reserve(max_length);
+      let (result, _read) = self.decoder.decode_to_string_without_replacement(
+        input,
+        &mut output,
+        last,
+      );
+      match result {
+        DecoderResult::InputEmpty => Ok(output),
+        DecoderResult::Malformed(_, _) => {
+          Err(OpError::type_error("Decoder error.".to_string()))
+        }
+        DecoderResult::OutputFull => unreachable!(),
+      }
+    } else {
+      let max_length = self
+        .decoder
+        .max_utf8_buffer_length(input.len())
+        .ok_or_else(too_large)?;
+      output.reserve(max_length);
+      let (result, _read, _had_replacements) =
+        self.decoder.decode_to_string(input, &mut output, last);
+      assert_eq!(result, CoderResult::InputEmpty);
+      Ok(output)
+    }
+  }
+}
+
+/// Decodes a complete buffer, without keeping any state around.
+fn op_encoding_decode_single(
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: DecoderArgs = serde_json::from_value(args)?;
+  let mut resource = args.new_decoder()?;
+  let input = zero_copy.as_deref().unwrap_or(&[]);
+  let output = resource.decode(input, true)?;
+  Ok(JsonOp::Sync(json!(output)))
+}
+
+/// Creates a decoder for `TextDecoder.decode()` calls with `stream: true`.
+fn op_encoding_new_decoder(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: DecoderArgs = serde_json::from_value(args)?;
+  let resource = args.new_decoder()?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add("textDecoder", Box::new(resource));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct DecodeArgs {
+  rid: u32,
+  stream: bool,
+}
+
+fn op_encoding_decode(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: DecodeArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<TextDecoderResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let input = zero_copy.as_deref().unwrap_or(&[]);
+  let output = resource.decode(input, !args.stream)?;
+  Ok(JsonOp::Sync(json!(output)))
+}
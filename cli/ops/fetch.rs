@@ -1,26 +1,47 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
 use super::io::{StreamResource, StreamResourceHolder};
-use crate::http_util::{create_http_client, HttpBody};
+use super::stream_resource::StreamResourceReader;
+use crate::compression::Format;
+use crate::compression::Transformer;
+use crate::http_util::{
+  create_http_client_with_options, HttpBody, HttpClientOptions,
+};
 use crate::op_error::OpError;
+use crate::resolve_addr::resolve_addr_async;
 use crate::state::State;
 use deno_core::CoreIsolate;
+use deno_core::ResourceTable;
 use deno_core::ZeroCopyBuf;
 use futures::future::FutureExt;
 use http::header::HeaderName;
 use http::header::HeaderValue;
+use http::header::CONTENT_ENCODING;
+use http::header::CONTENT_LENGTH;
+use http::header::HOST;
 use http::Method;
 use std::convert::From;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_fetch", s.stateful_json_op2(op_fetch));
+  i.register_op(
+    "op_create_http_client",
+    s.stateful_json_op2(op_create_http_client),
+  );
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct FetchArgs {
   method: Option<String>,
   url: String,
   headers: Vec<(String, String)>,
+  client_rid: Option<u32>,
+  /// A stream resource to read the body from, instead of `data`.
+  body_rid: Option<u32>,
 }
 
 pub fn op_fetch(
@@ -32,8 +53,26 @@ pub fn op_fetch(
   let args: FetchArgs = serde_json::from_value(args)?;
   let url = args.url;
 
-  let client =
-    create_http_client(state.borrow().global_state.flags.ca_file.clone())?;
+  // Taken out of the table first, so the body stream is cancelled even if
+  // the request fails before it's sent.
+  let body_reader = match args.body_rid {
+    Some(rid) => Some(take_stream_body(
+      &mut isolate.resource_table.borrow_mut(),
+      rid,
+    )?),
+    None => None,
+  };
+
+  let client = match args.client_rid {
+    Some(rid) => {
+      let resource_table = isolate.resource_table.borrow();
+      let resource = resource_table
+        .get::<HttpClientResource>(rid)
+        .ok_or_else(OpError::bad_resource_id)?;
+      resource.client.clone()
+    }
+    None => state.http_client()?,
+  };
 
   let method = match args.method {
     Some(method_str) => Method::from_bytes(method_str.as_bytes())
@@ -52,32 +91,83 @@ pub fn op_fetch(
     )));
   }
 
-  state.check_net_url(&url_)?;
+  // A hostname that isn't whitelisted by name may still resolve into a
+  // whitelisted address range, which is only known after the lookup. reqwest
+  // can't be told which address to connect to, so only `http:` requests,
+  // which can be sent to the checked address directly, are allowed that way.
+  let unresolved_target = match state.check_net_url(&url_) {
+    Ok(()) => None,
+    Err(err) => {
+      let host = url_.host_str().unwrap_or_default().to_string();
+      let port = url_.port_or_known_default().unwrap_or(0);
+      if scheme != "http" || !state.borrow().permissions.has_net_range(port) {
+        return Err(err);
+      }
+      Some((err, host, port))
+    }
+  };
 
-  let mut request = client.request(method, url_);
+  let headers = args.headers;
+  let resource_table = isolate.resource_table.clone();
+  let state = state.clone();
+  let future = async move {
+    let mut url_ = url_;
+    let mut pinned_host = None;
+    if let Some((err, host, port)) = unresolved_target {
+      let addr = resolve_addr_async(host.clone(), port).await?;
+      if state.check_net_addr(&host, &addr).is_err() {
+        return Err(err);
+      }
+      pinned_host = Some(pin_url_to_addr(&mut url_, &addr));
+    }
 
-  if let Some(buf) = data {
-    request = request.body(Vec::from(&*buf));
-  }
+    let mut request = client.request(method, url_);
 
-  for (key, value) in args.headers {
-    let name = HeaderName::from_bytes(key.as_bytes()).unwrap();
-    let v = HeaderValue::from_str(&value).unwrap();
-    request = request.header(name, v);
-  }
-  debug!("Before fetch {}", url);
+    if let Some(reader) = body_reader {
+      let body = reqwest::Body::wrap_stream(reader.into_stream());
+      request = request.body(body);
+    } else if let Some(buf) = data {
+      request = request.body(Vec::from(&*buf));
+    }
+
+    for (key, value) in headers {
+      let name = HeaderName::from_bytes(key.as_bytes()).unwrap();
+      if pinned_host.is_some() && name == HOST {
+        continue;
+      }
+      let v = HeaderValue::from_str(&value).unwrap();
+      request = request.header(name, v);
+    }
+    if let Some(host) = pinned_host {
+      request = request.header(HOST, host);
+    }
+    debug!("Before fetch {}", url);
 
-  let resource_table = isolate.resource_table.clone();
-  let future = async move {
     let res = request.send().await?;
     debug!("Fetch response {}", url);
     let status = res.status();
+    // reqwest decodes gzip and brotli itself and drops the header when it
+    // does; anything still marked as encoded is decoded here.
+    let decoder = res
+      .headers()
+      .get(CONTENT_ENCODING)
+      .and_then(|value| value.to_str().ok())
+      .and_then(Format::from_content_encoding)
+      .map(Transformer::decompress);
     let mut res_headers = Vec::new();
     for (key, val) in res.headers().iter() {
+      if decoder.is_some()
+        && (*key == CONTENT_ENCODING || *key == CONTENT_LENGTH)
+      {
+        continue;
+      }
       res_headers.push((key.to_string(), val.to_str().unwrap().to_owned()));
     }
 
-    let body = HttpBody::from(res);
+    let body = match decoder {
+      Some(decoder) => HttpBody::with_decoder(res, decoder),
+      None => HttpBody::from(res),
+    };
     let mut resource_table = resource_table.borrow_mut();
     let rid = resource_table.add(
       "httpBody",
@@ -98,3 +188,83 @@ pub fn op_fetch(
 
   Ok(JsonOp::Async(future.boxed_local()))
 }
+
+/// Points an `http:` URL at `addr`, which its host was resolved to and
+/// checked against the permissions, so that reqwest connects there instead
+/// of resolving the host again, possibly to another address. Returns the
+/// value of the `Host` header to send instead.
+fn pin_url_to_addr(url: &mut url::Url, addr: &SocketAddr) -> String {
+  let host = match url.port() {
+    Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+    None => url.host_str().unwrap_or_default().to_string(),
+  };
+  url.set_ip_host(addr.ip()).unwrap();
+  url.set_port(Some(addr.port())).unwrap();
+  host
+}
+
+/// Takes the reader of a stream resource, like one made by
+/// `op_stream_resource_open` or `op_multipart_encoder_finish`, out of the
+/// table, to send as a request body.
+fn take_stream_body(
+  resource_table: &mut ResourceTable,
+  rid: u32,
+) -> Result<Box<StreamResourceReader>, OpError> {
+  match resource_table.get_mut::<StreamResourceHolder>(rid) {
+    Some(resource_holder) => match resource_holder.resource {
+      StreamResource::ByteStream(_) => {}
+      _ => return Err(OpError::bad_resource_id()),
+    },
+    None => return Err(OpError::bad_resource_id()),
+  }
+  let mut resource_holder =
+    resource_table.remove::<StreamResourceHolder>(rid).unwrap();
+  match std::mem::replace(
+    &mut resource_holder.resource,
+    StreamResource::FsFile(None),
+  ) {
+    StreamResource::ByteStream(reader) => Ok(reader),
+    _ => unreachable!(),
+  }
+}
+
+struct HttpClientResource {
+  client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateHttpClientArgs {
+  ca_file: Option<String>,
+  pool_max_idle_per_host: Option<usize>,
+  /// In milliseconds.
+  pool_idle_timeout: Option<u64>,
+  #[serde(default)]
+  http2: bool,
+}
+
+fn op_create_http_client(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.createHttpClient")?;
+  let args: CreateHttpClientArgs = serde_json::from_value(args)?;
+
+  if let Some(ca_file) = &args.ca_file {
+    state.check_read(Path::new(ca_file))?;
+  }
+
+  let client = create_http_client_with_options(HttpClientOptions {
+    ca_file: args.ca_file,
+    pool_max_idle_per_host: args.pool_max_idle_per_host,
+    pool_idle_timeout: args.pool_idle_timeout.map(Duration::from_millis),
+    http2_prior_knowledge: args.http2,
+  })?;
+
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid =
+    resource_table.add("httpClient", Box::new(HttpClientResource { client }));
+  Ok(JsonOp::Sync(json!(rid)))
+}
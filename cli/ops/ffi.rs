@@ -0,0 +1,187 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{blocking_json, Deserialize, JsonOp, Value};
+use crate::ffi::read_c_string;
+use crate::ffi::read_pointer;
+use crate::ffi::write_pointer;
+use crate::ffi::DynamicLibraryResource;
+use crate::ffi::ForeignFunctionDef;
+use crate::ffi::NativeType;
+use crate::fs::resolve_from_cwd;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::Path;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_ffi_load", s.stateful_json_op2(op_ffi_load));
+  i.register_op("op_ffi_call", s.stateful_json_op2(op_ffi_call));
+  i.register_op("op_ffi_ptr_of", s.stateful_json_op(op_ffi_ptr_of));
+  i.register_op("op_ffi_ptr_read", s.stateful_json_op(op_ffi_ptr_read));
+  i.register_op("op_ffi_ptr_write", s.stateful_json_op(op_ffi_ptr_write));
+  i.register_op(
+    "op_ffi_ptr_copy_into",
+    s.stateful_json_op(op_ffi_ptr_copy_into),
+  );
+  i.register_op(
+    "op_ffi_ptr_c_string",
+    s.stateful_json_op(op_ffi_ptr_c_string),
+  );
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiLoadArgs {
+  path: String,
+  symbols: HashMap<String, ForeignFunctionDef>,
+}
+
+fn op_ffi_load(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.dlopen")?;
+  let args: FfiLoadArgs = serde_json::from_value(args)?;
+
+  // Bare library names like "libc.so.6" are looked up by the system loader.
+  let path = Path::new(&args.path);
+  let path = if path.components().count() > 1 {
+    resolve_from_cwd(path)?.into_os_string()
+  } else {
+    OsString::from(&args.path)
+  };
+  state.check_ffi(Some(Path::new(&path)))?;
+
+  let resource = DynamicLibraryResource::open(&path, args.symbols)?;
+  let rid = isolate
+    .resource_table
+    .borrow_mut()
+    .add("dynamicLibrary", Box::new(resource));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FfiCallArgs {
+  promise_id: Option<u64>,
+  rid: u32,
+  symbol: String,
+  args: Vec<Value>,
+}
+
+fn op_ffi_call(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let args: FfiCallArgs = serde_json::from_value(args)?;
+  let function = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<DynamicLibraryResource>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource.symbols.get(&args.symbol).cloned().ok_or_else(|| {
+      OpError::type_error(format!("Symbol \"{}\" not found", args.symbol))
+    })?
+  };
+
+  let is_sync = args.promise_id.is_none();
+  let call_args = args.args;
+  let mut zero_copy = zero_copy;
+  blocking_json(is_sync, move || {
+    function.call(&call_args, zero_copy.as_mut().map(|buf| &mut buf[..]))
+  })
+}
+
+fn op_ffi_ptr_of(
+  state: &State,
+  _args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let buf = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a Uint8Array".to_string()))?;
+  Ok(JsonOp::Sync(json!(buf.as_ptr() as usize)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PointerArgs {
+  pointer: usize,
+  #[serde(default)]
+  offset: usize,
+  #[serde(rename = "type")]
+  native_type: Option<NativeType>,
+  value: Option<Value>,
+}
+
+impl PointerArgs {
+  fn address(&self) -> Result<usize, OpError> {
+    if self.pointer == 0 {
+      return Err(OpError::type_error("Null pointer".to_string()));
+    }
+    Ok(self.pointer + self.offset)
+  }
+
+  fn native_type(&self) -> Result<NativeType, OpError> {
+    self
+      .native_type
+      .ok_or_else(|| OpError::type_error("Missing type".to_string()))
+  }
+}
+
+fn op_ffi_ptr_read(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let args: PointerArgs = serde_json::from_value(args)?;
+  let value = unsafe { read_pointer(args.address()?, args.native_type()?)? };
+  Ok(JsonOp::Sync(value))
+}
+
+fn op_ffi_ptr_write(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let args: PointerArgs = serde_json::from_value(args)?;
+  let value = args.value.clone().unwrap_or(Value::Null);
+  unsafe { write_pointer(args.address()?, args.native_type()?, &value)? };
+  Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_ffi_ptr_copy_into(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let args: PointerArgs = serde_json::from_value(args)?;
+  let mut buf = zero_copy
+    .ok_or_else(|| OpError::type_error("Expected a Uint8Array".to_string()))?;
+  let src = args.address()? as *const u8;
+  unsafe {
+    std::ptr::copy_nonoverlapping(src, buf.as_mut_ptr(), buf.len());
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_ffi_ptr_c_string(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_ffi(None)?;
+  let args: PointerArgs = serde_json::from_value(args)?;
+  let s = unsafe { read_c_string(args.address()?) };
+  Ok(JsonOp::Sync(json!(s)))
+}
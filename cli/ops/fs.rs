@@ -13,6 +13,7 @@ use futures::future::FutureExt;
 use std::convert::From;
 use std::env::{current_dir, set_current_dir, temp_dir};
 use std::io;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
@@ -29,6 +30,7 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_chown", s.stateful_json_op(op_chown));
   i.register_op("op_remove", s.stateful_json_op(op_remove));
   i.register_op("op_copy_file", s.stateful_json_op(op_copy_file));
+  i.register_op("op_write_file", s.stateful_json_op(op_write_file));
   i.register_op("op_stat", s.stateful_json_op(op_stat));
   i.register_op("op_realpath", s.stateful_json_op(op_realpath));
   i.register_op("op_read_dir", s.stateful_json_op(op_read_dir));
@@ -214,15 +216,16 @@ fn op_umask(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.umask");
+  state.check_unstable("Deno.umask")?;
   let args: UmaskArgs = serde_json::from_value(args)?;
-  // TODO implement umask for Windows
-  // see https://github.com/nodejs/node/blob/master/src/node_process_methods.cc
-  // and https://docs.microsoft.com/fr-fr/cpp/c-runtime-library/reference/umask?view=vs-2019
+  // Windows has no process umask that applies to files created by the OS
+  // APIs std uses.
   #[cfg(not(unix))]
   {
     let _ = args.mask; // avoid unused warning.
-    Err(OpError::not_implemented())
+    Err(OpError::not_supported(
+      "umask is not supported on Windows".to_string(),
+    ))
   }
   #[cfg(unix)]
   {
@@ -323,12 +326,14 @@ fn op_chmod(
       std::fs::set_permissions(&path, permissions)?;
       Ok(json!({}))
     }
-    // TODO Implement chmod for Windows (#4357)
+    // Windows only has a read-only attribute, which is set when the owner
+    // can't write.
     #[cfg(not(unix))]
     {
-      // Still check file/dir exists on Windows
-      let _metadata = std::fs::metadata(&path)?;
-      Err(OpError::not_implemented())
+      let mut permissions = std::fs::metadata(&path)?.permissions();
+      permissions.set_readonly(mode & 0o200 == 0);
+      std::fs::set_permissions(&path, permissions)?;
+      Ok(json!({}))
     }
   })
 }
@@ -363,12 +368,13 @@ fn op_chown(
       chown(&path, Option::Some(nix_uid), Option::Some(nix_gid))?;
       Ok(json!({}))
     }
-    // TODO Implement chown for Windows
     #[cfg(not(unix))]
     {
       // Still check file/dir exists on Windows
       let _metadata = std::fs::metadata(&path)?;
-      Err(OpError::not_implemented())
+      Err(OpError::not_supported(
+        "chown is not supported on Windows".to_string(),
+      ))
     }
   })
 }
@@ -444,6 +450,149 @@ fn op_copy_file(
   })
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteFileArgs {
+  promise_id: Option<u64>,
+  path: String,
+  mode: Option<u32>,
+  #[serde(default)]
+  append: bool,
+  create: Option<bool>,
+  #[serde(default)]
+  atomic: bool,
+  #[serde(default)]
+  sync: bool,
+}
+
+fn op_write_file(
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: WriteFileArgs = serde_json::from_value(args)?;
+  let path = resolve_from_cwd(Path::new(&args.path))?;
+  let create = args.create.unwrap_or(true);
+  let mode = args.mode.map(|mode| mode & 0o777);
+
+  if args.atomic && args.append {
+    return Err(OpError::type_error(
+      "The atomic and append options can't be combined".to_string(),
+    ));
+  }
+  state.check_write(&path)?;
+
+  let is_sync = args.promise_id.is_none();
+  blocking_json(is_sync, move || {
+    debug!("op_write_file {} atomic={}", path.display(), args.atomic);
+    let data = zero_copy.as_deref().unwrap_or(&[]);
+    if args.atomic {
+      write_file_atomic(&path, data, mode, create, args.sync)?;
+    } else {
+      let mut open_options = std::fs::OpenOptions::new();
+      open_options
+        .write(true)
+        .create(create)
+        .append(args.append)
+        .truncate(!args.append);
+      let mut file = open_options.open(&path)?;
+      // Like chmod, the mode is also applied to files that already exist,
+      // regardless of the umask.
+      #[cfg(unix)]
+      {
+        use std::os::unix::fs::PermissionsExt;
+        if let Some(mode) = mode {
+          file.set_permissions(PermissionsExt::from_mode(mode))?;
+        }
+      }
+      file.write_all(data)?;
+      if args.sync {
+        file.sync_all()?;
+      }
+    }
+    Ok(json!({}))
+  })
+}
+
+/// Writes `data` to a new file next to `path`, then renames it over `path`,
+/// so that readers and crashes only ever see the old or the new contents.
+/// Files that are replaced keep their permissions unless `mode` is given.
+/// With `sync`, the data and the rename are flushed to disk before
+/// returning.
+fn write_file_atomic(
+  path: &Path,
+  data: &[u8],
+  mode: Option<u32>,
+  create: bool,
+  sync: bool,
+) -> Result<(), OpError> {
+  let existing = match std::fs::metadata(path) {
+    Ok(metadata) => Some(metadata),
+    Err(e) if e.kind() == io::ErrorKind::NotFound && create => None,
+    Err(e) => return Err(e.into()),
+  };
+  let dir = path.parent().unwrap_or_else(|| Path::new("."));
+  let name = path.file_name().ok_or_else(|| {
+    OpError::type_error(format!("Can't write to \"{}\"", path.display()))
+  })?;
+
+  let mut rng = thread_rng();
+  let (temp_path, mut file) = loop {
+    let temp_name =
+      format!(".{}.{:08x}.tmp", name.to_string_lossy(), rng.gen::<u32>());
+    let temp_path = dir.join(temp_name);
+    let mut open_options = std::fs::OpenOptions::new();
+    open_options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::OpenOptionsExt;
+      open_options.mode(mode.unwrap_or(0o666));
+    }
+    match open_options.open(&temp_path) {
+      Ok(file) => break (temp_path, file),
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(e.into()),
+    }
+  };
+
+  let result = (|| -> Result<(), OpError> {
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      let permissions = match (mode, &existing) {
+        (Some(mode), _) => Some(PermissionsExt::from_mode(mode)),
+        (None, Some(metadata)) => Some(metadata.permissions()),
+        (None, None) => None,
+      };
+      if let Some(permissions) = permissions {
+        file.set_permissions(permissions)?;
+      }
+    }
+    #[cfg(not(unix))]
+    let _ = (mode, &existing);
+    file.write_all(data)?;
+    if sync {
+      file.sync_all()?;
+    }
+    drop(file);
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+  })();
+  if result.is_err() {
+    let _ = std::fs::remove_file(&temp_path);
+  }
+  result?;
+
+  // The rename is only durable once the directory entry is flushed too.
+  #[cfg(unix)]
+  {
+    if sync {
+      std::fs::File::open(dir)?.sync_all()?;
+    }
+  }
+  Ok(())
+}
+
 fn to_msec(maybe_time: Result<SystemTime, io::Error>) -> serde_json::Value {
   match maybe_time {
     Ok(time) => {
@@ -649,7 +798,7 @@ fn op_link(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.link");
+  state.check_unstable("Deno.link")?;
   let args: LinkArgs = serde_json::from_value(args)?;
   let oldpath = resolve_from_cwd(Path::new(&args.oldpath))?;
   let newpath = resolve_from_cwd(Path::new(&args.newpath))?;
@@ -678,7 +827,7 @@ fn op_symlink(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.symlink");
+  state.check_unstable("Deno.symlink")?;
   let args: SymlinkArgs = serde_json::from_value(args)?;
   let oldpath = resolve_from_cwd(Path::new(&args.oldpath))?;
   let newpath = resolve_from_cwd(Path::new(&args.newpath))?;
@@ -889,7 +1038,7 @@ fn op_utime(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.utime");
+  state.check_unstable("Deno.utime")?;
 
   let args: UtimeArgs = serde_json::from_value(args)?;
   let path = resolve_from_cwd(Path::new(&args.path))?;
@@ -898,8 +1047,8 @@ fn op_utime(
 
   let is_sync = args.promise_id.is_none();
   blocking_json(is_sync, move || {
-    debug!("op_utime {} {} {}", args.path, args.atime, args.mtime);
-    utime::set_file_times(args.path, args.atime, args.mtime)?;
+    debug!("op_utime {} {} {}", path.display(), args.atime, args.mtime);
+    utime::set_file_times(&path, args.atime, args.mtime)?;
     Ok(json!({}))
   })
 }
@@ -0,0 +1,118 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::inspector::LocalInspectorSession;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use std::path::Path;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+static NEXT_COVERAGE_FILE: AtomicUsize = AtomicUsize::new(1);
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_inspector_post", s.stateful_json_op(op_inspector_post));
+  i.register_op(
+    "op_inspector_report",
+    s.stateful_json_op(op_inspector_report),
+  );
+}
+
+fn get_session(state: &State) -> Result<LocalInspectorSession, OpError> {
+  state.borrow().inspector_session.clone().ok_or_else(|| {
+    OpError::other(
+      "The inspector isn't enabled, use --inspect, --coverage or --profile"
+        .to_string(),
+    )
+  })
+}
+
+#[derive(Deserialize)]
+struct InspectorPostArgs {
+  method: String,
+  params: Option<Value>,
+}
+
+/// Posts a DevTools protocol message to the isolate's own inspector. Only
+/// the profiler domains are exposed; other domains, like `Runtime`, would
+/// bypass permissions.
+fn op_inspector_post(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: InspectorPostArgs = serde_json::from_value(args)?;
+  let domain = args.method.split('.').next().unwrap();
+  if domain != "Profiler" && domain != "HeapProfiler" {
+    return Err(OpError::type_error(format!(
+      "Inspector method \"{}\" isn't allowed",
+      args.method
+    )));
+  }
+  let session = get_session(state)?;
+  let params = args.params.unwrap_or_else(|| json!({}));
+  let (result, notifications) = session.post_message(&args.method, params)?;
+  Ok(JsonOp::Sync(json!({
+    "result": result,
+    "notifications": notifications,
+  })))
+}
+
+#[derive(Deserialize)]
+struct InspectorReportArgs {
+  kind: String,
+}
+
+/// Takes the coverage or stops the CPU profile that was started, and writes
+/// it where `--coverage` or `--profile` was given. Returns the written file.
+fn op_inspector_report(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: InspectorReportArgs = serde_json::from_value(args)?;
+  let session = get_session(state)?;
+  let flags = state.borrow().global_state.flags.clone();
+  let (path, json) = match args.kind.as_str() {
+    "coverage" => {
+      let dir = flags.coverage_dir.ok_or_else(|| {
+        OpError::other("Coverage is only written with --coverage".to_string())
+      })?;
+      let (mut result, _) =
+        session.post_message("Profiler.takePreciseCoverage", json!({}))?;
+      session.post_message("Profiler.stopPreciseCoverage", json!({}))?;
+      // Leave out the runtime's own scripts.
+      if let Some(scripts) = result["result"].as_array_mut() {
+        scripts.retain(|script| {
+          let url = script["url"].as_str().unwrap_or("");
+          ["file://", "http://", "https://"]
+            .iter()
+            .any(|scheme| url.starts_with(scheme))
+        });
+      }
+      std::fs::create_dir_all(&dir)?;
+      let file_name = format!(
+        "coverage-{}-{}.json",
+        std::process::id(),
+        NEXT_COVERAGE_FILE.fetch_add(1, Ordering::SeqCst)
+      );
+      (Path::new(&dir).join(file_name), result)
+    }
+    "profile" => {
+      let path = flags.cpu_profile.ok_or_else(|| {
+        OpError::other("Profiles are only written with --profile".to_string())
+      })?;
+      let (mut result, _) = session.post_message("Profiler.stop", json!({}))?;
+      (Path::new(&path).to_path_buf(), result["profile"].take())
+    }
+    kind => {
+      return Err(OpError::type_error(format!(
+        "Unknown report kind \"{}\"",
+        kind
+      )))
+    }
+  };
+  std::fs::write(&path, serde_json::to_string(&json)?)?;
+  Ok(JsonOp::Sync(json!(path)))
+}
@@ -1,4 +1,6 @@
+use super::dispatch_json::{Deserialize, JsonOp, Value};
 use super::dispatch_minimal::MinimalOp;
+use super::stream_resource::StreamResourceReader;
 use crate::http_util::HttpBody;
 use crate::op_error::OpError;
 use crate::state::State;
@@ -63,6 +65,12 @@ lazy_static! {
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_read", s.stateful_minimal_op2(op_read));
   i.register_op("op_write", s.stateful_minimal_op2(op_write));
+  i.register_op(
+    "op_line_reader_open",
+    s.stateful_json_op2(op_line_reader_open),
+  );
+  i.register_op("op_read_lines", s.stateful_json_op2(op_read_lines));
+  i.register_op("op_copy", s.stateful_json_op2(op_copy));
 }
 
 pub fn get_stdio() -> (
@@ -161,6 +169,7 @@ pub enum StreamResource {
   ChildStdin(tokio::process::ChildStdin),
   ChildStdout(tokio::process::ChildStdout),
   ChildStderr(tokio::process::ChildStderr),
+  ByteStream(Box<StreamResourceReader>),
 }
 
 trait UnpinAsyncRead: AsyncRead + Unpin {}
@@ -198,6 +207,7 @@ impl DenoAsyncRead for StreamResource {
       ChildStdout(f) => f,
       ChildStderr(f) => f,
       HttpBody(f) => f,
+      ByteStream(f) => f,
       _ => return Err(OpError::bad_resource_id()).into(),
     };
     let v = ready!(Pin::new(f).poll_read(cx, buf))?;
@@ -269,6 +279,215 @@ pub fn op_read(
   }
 }
 
+/// Size of the chunks a `LineReader` reads from its stream.
+const LINE_READER_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Splits the stream of another resource into lines, keeping what follows
+/// the last line break until the next chunk is read.
+struct LineReader {
+  source_rid: u32,
+  buffer: Vec<u8>,
+  eof: bool,
+}
+
+impl LineReader {
+  /// Removes the complete lines from the buffer. Line breaks are "\n" or
+  /// "\r\n", and invalid UTF-8 is replaced like `TextDecoder` does.
+  fn take_lines(&mut self) -> Vec<String> {
+    let end = match self.buffer.iter().rposition(|b| *b == b'\n') {
+      Some(i) => i + 1,
+      None if self.eof && !self.buffer.is_empty() => self.buffer.len(),
+      None => return vec![],
+    };
+    let rest = self.buffer.split_off(end);
+    let mut complete = std::mem::replace(&mut self.buffer, rest);
+    if complete.last() == Some(&b'\n') {
+      complete.pop();
+    }
+    complete
+      .split(|b| *b == b'\n')
+      .map(|line| {
+        let line = match line.last() {
+          Some(b'\r') => &line[..line.len() - 1],
+          _ => line,
+        };
+        String::from_utf8_lossy(line).into_owned()
+      })
+      .collect()
+  }
+}
+
+#[derive(Deserialize)]
+struct LineReaderOpenArgs {
+  rid: u32,
+}
+
+fn op_line_reader_open(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.readLines")?;
+  let args: LineReaderOpenArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  if !resource_table.has(args.rid) {
+    return Err(OpError::bad_resource_id());
+  }
+  let line_reader = LineReader {
+    source_rid: args.rid,
+    buffer: Vec::new(),
+    eof: false,
+  };
+  let rid = resource_table.add("lineReader", Box::new(line_reader));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct ReadLinesArgs {
+  rid: u32,
+}
+
+/// Resolves with the lines that are complete once at least one is, reading
+/// chunks from the source stream as needed. `done` is set once the stream
+/// ended and all lines were returned.
+fn op_read_lines(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ReadLinesArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+  let resource_table = isolate.resource_table.clone();
+
+  let fut = async move {
+    let mut chunk = vec![0u8; LINE_READER_CHUNK_SIZE];
+    loop {
+      let source_rid = {
+        let mut resource_table = resource_table.borrow_mut();
+        let line_reader = resource_table
+          .get_mut::<LineReader>(rid)
+          .ok_or_else(OpError::bad_resource_id)?;
+        let lines = line_reader.take_lines();
+        if !lines.is_empty() || line_reader.eof {
+          let done = line_reader.eof && line_reader.buffer.is_empty();
+          return Ok(json!({ "lines": lines, "done": done }));
+        }
+        line_reader.source_rid
+      };
+
+      let nread = poll_fn(|cx| {
+        let mut resource_table = resource_table.borrow_mut();
+        let resource_holder = resource_table
+          .get_mut::<StreamResourceHolder>(source_rid)
+          .ok_or_else(OpError::bad_resource_id)?;
+        resource_holder.resource.poll_read(cx, &mut chunk)
+      })
+      .await?;
+
+      let mut resource_table = resource_table.borrow_mut();
+      let line_reader = resource_table
+        .get_mut::<LineReader>(rid)
+        .ok_or_else(OpError::bad_resource_id)?;
+      if nread == 0 {
+        line_reader.eof = true;
+      } else {
+        line_reader.buffer.extend_from_slice(&chunk[..nread]);
+      }
+    }
+  };
+
+  Ok(JsonOp::Async(fut.boxed_local()))
+}
+
+/// Same default as `Deno.copy()` when it copies in JavaScript.
+const DEFAULT_COPY_BUFFER_SIZE: usize = 32 * 1024;
+
+fn get_stream_resource(
+  resource_table: &mut ResourceTable,
+  rid: u32,
+) -> Result<&mut StreamResource, OpError> {
+  resource_table
+    .get_mut::<StreamResourceHolder>(rid)
+    .map(|resource_holder| &mut resource_holder.resource)
+    .ok_or_else(OpError::bad_resource_id)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CopyArgs {
+  src_rid: u32,
+  dst_rid: u32,
+  buf_size: Option<usize>,
+  limit: Option<u64>,
+}
+
+/// Copies from one stream resource to another until the source ends, so the
+/// chunks don't go through JavaScript. With `limit`, resolves once that many
+/// bytes were copied, for the caller to report progress and continue.
+fn op_copy(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: CopyArgs = serde_json::from_value(args)?;
+  let buf_size = args.buf_size.unwrap_or(DEFAULT_COPY_BUFFER_SIZE);
+  if buf_size == 0 {
+    return Err(OpError::type_error("bufSize must be positive".to_string()));
+  }
+  let resource_table = isolate.resource_table.clone();
+
+  let fut = async move {
+    let mut buf = vec![0u8; buf_size];
+    let mut copied: u64 = 0;
+    let mut done = false;
+    while args.limit.map_or(true, |limit| copied < limit) {
+      let len = match args.limit {
+        Some(limit) => std::cmp::min(buf_size as u64, limit - copied) as usize,
+        None => buf_size,
+      };
+      let nread = poll_fn(|cx| {
+        let mut resource_table = resource_table.borrow_mut();
+        get_stream_resource(&mut resource_table, args.src_rid)?
+          .poll_read(cx, &mut buf[..len])
+      })
+      .await?;
+      if nread == 0 {
+        done = true;
+        break;
+      }
+
+      let mut nwritten = 0;
+      while nwritten < nread {
+        let n = poll_fn(|cx| {
+          let mut resource_table = resource_table.borrow_mut();
+          get_stream_resource(&mut resource_table, args.dst_rid)?
+            .poll_write(cx, &buf[nwritten..nread])
+        })
+        .await?;
+        if n == 0 {
+          return Err(OpError::from(std::io::Error::from(
+            std::io::ErrorKind::WriteZero,
+          )));
+        }
+        nwritten += n;
+      }
+      copied += nread as u64;
+    }
+
+    poll_fn(|cx| {
+      let mut resource_table = resource_table.borrow_mut();
+      get_stream_resource(&mut resource_table, args.dst_rid)?.poll_flush(cx)
+    })
+    .await?;
+    Ok(json!({ "copied": copied, "done": done }))
+  };
+
+  Ok(JsonOp::Async(fut.boxed_local()))
+}
+
 /// `DenoAsyncWrite` is the same as the `tokio_io::AsyncWrite` trait
 /// but uses an `OpError` error instead of `std::io:Error`
 pub trait DenoAsyncWrite {
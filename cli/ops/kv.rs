@@ -0,0 +1,235 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{blocking_json, Deserialize, JsonOp, Value};
+use crate::checksum;
+use crate::fs::resolve_from_cwd;
+use crate::kv::Check;
+use crate::kv::Entry;
+use crate::kv::KeyPart;
+use crate::kv::KvStore;
+use crate::kv::Mutation;
+use crate::kv::Selector;
+use crate::op_error::OpError;
+use crate::state::State;
+use crate::web_storage;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::future::select;
+use futures::future::FutureExt;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_kv_open", s.stateful_json_op2(op_kv_open));
+  i.register_op("op_kv_get", s.stateful_json_op2(op_kv_get));
+  i.register_op("op_kv_list", s.stateful_json_op2(op_kv_list));
+  i.register_op("op_kv_atomic", s.stateful_json_op2(op_kv_atomic));
+  i.register_op("op_kv_watch", s.stateful_json_op2(op_kv_watch));
+}
+
+/// An open `Deno.Kv`. Dropping the resource, when JS closes it, drops
+/// `closed` and so ends the watches of the database.
+struct KvResource {
+  store: Arc<KvStore>,
+  #[allow(dead_code)]
+  closed: watch::Sender<()>,
+  closed_rx: watch::Receiver<()>,
+}
+
+fn get_store(isolate: &CoreIsolate, rid: u32) -> Result<Arc<KvStore>, OpError> {
+  let resource_table = isolate.resource_table.borrow();
+  let resource = resource_table
+    .get::<KvResource>(rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  Ok(resource.store.clone())
+}
+
+#[derive(Deserialize)]
+struct OpenArgs {
+  path: Option<String>,
+}
+
+/// Opens the database at `path`, in memory if it's ":memory:". Without a
+/// path, the database of the origin in `$DENO_DIR/kv` is opened; like
+/// `localStorage` that needs no permissions.
+fn op_kv_open(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.openKv")?;
+  let args: OpenArgs = serde_json::from_value(args)?;
+  let path = match args.path.as_deref() {
+    Some(":memory:") => None,
+    Some(path) => {
+      let path = resolve_from_cwd(Path::new(path))?;
+      state.check_read(&path)?;
+      state.check_write(&path)?;
+      Some(path)
+    }
+    None => {
+      let state = state.borrow();
+      let global_state = &state.global_state;
+      let origin = web_storage::origin(
+        global_state.flags.location.as_ref(),
+        &state.main_module,
+      );
+      let file_name = format!("{}.sqlite3", checksum::gen2(&origin));
+      Some(global_state.dir.root.join("kv").join(file_name))
+    }
+  };
+  let store = state.borrow().global_state.kv_stores.open(path)?;
+  let (closed, closed_rx) = watch::channel(());
+  let resource = KvResource {
+    store,
+    closed,
+    closed_rx,
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add("kv", Box::new(resource));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetArgs {
+  promise_id: Option<u64>,
+  rid: u32,
+  keys: Vec<Vec<KeyPart>>,
+}
+
+/// Returns the entries of the keys, `null` for those that don't exist.
+fn op_kv_get(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: GetArgs = serde_json::from_value(args)?;
+  let store = get_store(isolate, args.rid)?;
+  let is_sync = args.promise_id.is_none();
+  blocking_json(is_sync, move || {
+    let entries = store.get(&args.keys)?;
+    Ok(json!(entries))
+  })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListArgs {
+  promise_id: Option<u64>,
+  rid: u32,
+  selector: Selector,
+  limit: u32,
+  cursor: Option<String>,
+  reverse: bool,
+}
+
+fn op_kv_list(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ListArgs = serde_json::from_value(args)?;
+  let store = get_store(isolate, args.rid)?;
+  let is_sync = args.promise_id.is_none();
+  blocking_json(is_sync, move || {
+    let entries = store.list(
+      &args.selector,
+      args.limit,
+      args.cursor.as_deref(),
+      args.reverse,
+    )?;
+    let entries: Vec<Value> = entries
+      .into_iter()
+      .map(|(entry, cursor)| {
+        json!({
+          "key": entry.key,
+          "value": entry.value,
+          "versionstamp": entry.versionstamp,
+          "cursor": cursor,
+        })
+      })
+      .collect();
+    Ok(json!(entries))
+  })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtomicArgs {
+  promise_id: Option<u64>,
+  rid: u32,
+  checks: Vec<Check>,
+  mutations: Vec<Mutation>,
+}
+
+/// Commits the mutations if every check passes. Returns the versionstamp
+/// of the commit, or `null` if a check failed.
+fn op_kv_atomic(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: AtomicArgs = serde_json::from_value(args)?;
+  let store = get_store(isolate, args.rid)?;
+  let is_sync = args.promise_id.is_none();
+  blocking_json(is_sync, move || {
+    let versionstamp = store.atomic(&args.checks, &args.mutations)?;
+    Ok(json!(versionstamp))
+  })
+}
+
+#[derive(Deserialize)]
+struct WatchArgs {
+  rid: u32,
+  keys: Vec<Vec<KeyPart>>,
+  /// The versionstamps of the keys last seen by JS.
+  versionstamps: Vec<Option<String>>,
+}
+
+/// Resolves to the entries of the keys once any of them changed from
+/// `versionstamps`, or to `null` once the database is closed.
+fn op_kv_watch(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: WatchArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+  let (store, mut closed_rx) = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<KvResource>(rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    (resource.store.clone(), resource.closed_rx.clone())
+  };
+  // Subscribed before the first read, so no commit is missed.
+  let mut commits = store.subscribe();
+  let keys = Arc::new(args.keys);
+  let versionstamps = args.versionstamps;
+  let resource_table = isolate.resource_table.clone();
+  let op = async move {
+    loop {
+      if resource_table.borrow().get::<KvResource>(rid).is_none() {
+        return Ok(json!(null));
+      }
+      let (store, keys) = (store.clone(), keys.clone());
+      let entries: Vec<Option<Entry>> =
+        crate::tokio_util::spawn_blocking(move || store.get(&keys)).await?;
+      let changed = entries
+        .iter()
+        .map(|entry| entry.as_ref().map(|entry| &entry.versionstamp))
+        .ne(versionstamps.iter().map(Option::as_ref));
+      if changed {
+        return Ok(json!(entries));
+      }
+      select(Box::pin(commits.recv()), Box::pin(closed_rx.recv())).await;
+    }
+  };
+  Ok(JsonOp::Async(op.boxed_local()))
+}
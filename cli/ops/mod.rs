@@ -8,27 +8,42 @@ pub use dispatch_json::JsonResult;
 pub use dispatch_minimal::minimal_op;
 pub use dispatch_minimal::MinimalOp;
 
+pub mod blob;
+pub mod cache_storage;
+pub mod cancel;
 pub mod compiler;
+pub mod compression;
+pub mod encoding;
 pub mod errors;
 pub mod fetch;
+pub mod ffi;
 pub mod fs;
 pub mod fs_events;
+pub mod inspector;
 pub mod io;
+pub mod kv;
+pub mod multipart;
 pub mod net;
 #[cfg(unix)]
 mod net_unix;
 pub mod os;
+pub mod performance;
 pub mod permissions;
 pub mod plugins;
 pub mod process;
 pub mod random;
+#[cfg(feature = "repl")]
 pub mod repl;
 pub mod resources;
 pub mod runtime;
 pub mod runtime_compiler;
 pub mod signal;
+pub mod stream_resource;
+pub mod testing;
 pub mod timers;
 pub mod tls;
 pub mod tty;
+pub mod url;
+pub mod web_storage;
 pub mod web_worker;
 pub mod worker_host;
@@ -0,0 +1,266 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::io::{DenoAsyncRead, StreamResource, StreamResourceHolder};
+use super::stream_resource::StreamResourceReader;
+use crate::blob_store::Blob;
+use crate::multipart::Encoder;
+use crate::multipart::Entry;
+use crate::multipart::Parser;
+use crate::multipart::Part;
+use crate::multipart::PartBody;
+use crate::multipart::PartFiles;
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::future::poll_fn;
+use futures::future::FutureExt;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_multipart_encoder_open",
+    s.stateful_json_op2(op_multipart_encoder_open),
+  );
+  i.register_op(
+    "op_multipart_encoder_append",
+    s.stateful_json_op2(op_multipart_encoder_append),
+  );
+  i.register_op(
+    "op_multipart_encoder_finish",
+    s.stateful_json_op2(op_multipart_encoder_finish),
+  );
+  i.register_op(
+    "op_multipart_parse",
+    s.stateful_json_op2(op_multipart_parse),
+  );
+  i.register_op(
+    "op_multipart_part_blob",
+    s.stateful_json_op(op_multipart_part_blob),
+  );
+}
+
+/// Calls `f` with the files of the isolate's parsed file parts.
+fn with_part_files<T>(
+  state: &State,
+  f: impl FnOnce(&mut PartFiles) -> T,
+) -> Result<T, OpError> {
+  let mut state = state.borrow_mut();
+  if state.multipart_part_files.is_none() {
+    state.multipart_part_files = Some(PartFiles::new()?);
+  }
+  Ok(f(state.multipart_part_files.as_mut().unwrap()))
+}
+
+fn get_part_file(state: &State, id: usize) -> Result<(PathBuf, u64), OpError> {
+  with_part_files(state, |files| files.get(id).cloned())?
+    .ok_or_else(|| OpError::not_found("File part not found".to_string()))
+}
+
+struct EncoderResource {
+  encoder: Encoder,
+}
+
+#[derive(Deserialize)]
+struct EncoderOpenArgs {
+  /// Generated if not given.
+  boundary: Option<String>,
+}
+
+fn op_multipart_encoder_open(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: EncoderOpenArgs = serde_json::from_value(args)?;
+  let boundary = args
+    .boundary
+    .unwrap_or_else(crate::multipart::generate_boundary);
+  let encoder = Encoder::new(boundary);
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table
+    .add("multipartEncoder", Box::new(EncoderResource { encoder }));
+  Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncoderAppendArgs {
+  rid: u32,
+  name: String,
+  filename: Option<String>,
+  content_type: Option<String>,
+  /// The value of a field. File parts have their contents in the blob
+  /// `blob_id`, or in the parsed file part `part_id`.
+  value: Option<String>,
+  blob_id: Option<u32>,
+  part_id: Option<usize>,
+}
+
+fn op_multipart_encoder_append(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: EncoderAppendArgs = serde_json::from_value(args)?;
+  let body = if let Some(value) = args.value {
+    PartBody::Bytes(value.into_bytes())
+  } else if let Some(id) = args.part_id {
+    let (path, size) = get_part_file(state, id)?;
+    PartBody::File(path, size)
+  } else if let Some(id) = args.blob_id {
+    let blob = state.borrow().blobs.get(id).cloned();
+    PartBody::Blob(
+      blob.ok_or_else(|| OpError::not_found("Blob not found".to_string()))?,
+    )
+  } else {
+    return Err(OpError::type_error("Expected a part body".to_string()));
+  };
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .get_mut::<EncoderResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  resource.encoder.append(Part {
+    name: args.name,
+    filename: args.filename,
+    content_type: args.content_type,
+    body,
+  });
+  Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+struct EncoderFinishArgs {
+  rid: u32,
+}
+
+/// Returns a stream resource reading the body, along with its content type
+/// and length.
+fn op_multipart_encoder_finish(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: EncoderFinishArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let resource = resource_table
+    .remove::<EncoderResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let content_type = resource.encoder.content_type();
+  let (content_length, stream) = resource.encoder.finish();
+  let rid = resource_table.add(
+    "multipartBody",
+    Box::new(StreamResourceHolder::new(StreamResource::ByteStream(
+      Box::new(StreamResourceReader::new(stream)),
+    ))),
+  );
+  Ok(JsonOp::Sync(json!({
+    "rid": rid,
+    "contentType": content_type,
+    "contentLength": content_length,
+  })))
+}
+
+#[derive(Deserialize)]
+struct ParseArgs {
+  /// A resource to read the body from, until its end, instead of
+  /// `zero_copy`.
+  rid: Option<u32>,
+  boundary: String,
+}
+
+/// Parses a `multipart/form-data` body. File parts are written to disk and
+/// returned with the id to read them with `op_multipart_part_blob`.
+fn op_multipart_parse(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ParseArgs = serde_json::from_value(args)?;
+  let dir = with_part_files(state, |files| files.dir().to_owned())?;
+  let mut parser = Parser::new(&args.boundary, dir);
+  let resource = match args.rid {
+    Some(rid) => {
+      let mut resource_table = isolate.resource_table.borrow_mut();
+      if resource_table.get::<StreamResourceHolder>(rid).is_none() {
+        return Err(OpError::bad_resource_id());
+      }
+      let mut resource_holder =
+        resource_table.remove::<StreamResourceHolder>(rid).unwrap();
+      Some(std::mem::replace(
+        &mut resource_holder.resource,
+        StreamResource::FsFile(None),
+      ))
+    }
+    None => None,
+  };
+  let data = zero_copy.map(|buf| buf.to_vec()).unwrap_or_default();
+
+  let state = state.clone();
+  let future = async move {
+    if let Some(mut resource) = resource {
+      let mut buf = vec![0; 64 * 1024];
+      loop {
+        let nread = poll_fn(|cx| resource.poll_read(cx, &mut buf)).await?;
+        if nread == 0 {
+          break;
+        }
+        parser.feed(&buf[..nread])?;
+      }
+    } else {
+      parser.feed(&data)?;
+    }
+    let entries = parser.finish()?;
+    let mut result = vec![];
+    for entry in entries {
+      result.push(match entry {
+        Entry::Field { name, value } => json!({
+          "name": name,
+          "value": value,
+        }),
+        Entry::File {
+          name,
+          filename,
+          content_type,
+          path,
+          size,
+        } => {
+          let part_id = with_part_files(&state, |files| files.add(path, size))?;
+          json!({
+            "name": name,
+            "filename": filename,
+            "contentType": content_type,
+            "partId": part_id,
+            "size": size,
+          })
+        }
+      });
+    }
+    Ok(json!(result))
+  };
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+#[derive(Deserialize)]
+struct PartBlobArgs {
+  id: usize,
+}
+
+/// Reads a parsed file part into a blob, returning the id of the blob.
+fn op_multipart_part_blob(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: PartBlobArgs = serde_json::from_value(args)?;
+  let (path, _) = get_part_file(state, args.id)?;
+  let mut blob = Blob::new(String::new());
+  blob.push_bytes(fs::read(path)?);
+  let id = state.borrow_mut().blobs.add(blob);
+  Ok(JsonOp::Sync(json!(id)))
+}
@@ -3,20 +3,29 @@ use super::dispatch_json::{Deserialize, JsonOp, Value};
 use super::io::{StreamResource, StreamResourceHolder};
 use crate::op_error::OpError;
 use crate::resolve_addr::resolve_addr;
+use crate::resolve_addr::resolve_addr_async;
 use crate::state::State;
 use deno_core::CoreIsolate;
 use deno_core::ResourceTable;
 use deno_core::ZeroCopyBuf;
 use futures::future::poll_fn;
 use futures::future::FutureExt;
+use std::cell::RefCell;
 use std::convert::From;
+use std::net::IpAddr;
 use std::net::Shutdown;
 use std::net::SocketAddr;
+use std::rc::Rc;
 use std::task::Context;
 use std::task::Poll;
 use tokio::net::TcpListener;
 use tokio::net::TcpStream;
 use tokio::net::UdpSocket;
+use trust_dns_resolver::config::NameServerConfig;
+use trust_dns_resolver::config::Protocol;
+use trust_dns_resolver::config::ResolverConfig;
+use trust_dns_resolver::config::ResolverOpts;
+use trust_dns_resolver::TokioAsyncResolver;
 
 #[cfg(unix)]
 use super::net_unix;
@@ -28,6 +37,7 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_listen", s.stateful_json_op2(op_listen));
   i.register_op("op_receive", s.stateful_json_op2(op_receive));
   i.register_op("op_send", s.stateful_json_op2(op_send));
+  i.register_op("op_resolve_dns", s.stateful_json_op(op_resolve_dns));
 }
 
 #[derive(Deserialize)]
@@ -68,7 +78,13 @@ fn accept_tcp(
         }
       }
     });
-    let (tcp_stream, _socket_addr) = accept_fut.await?;
+    let (tcp_stream, _socket_addr) = {
+      let _guard = AcceptTaskGuard {
+        resource_table: resource_table.clone(),
+        rid,
+      };
+      accept_fut.await?
+    };
     let local_addr = tcp_stream.local_addr()?;
     let remote_addr = tcp_stream.peer_addr()?;
     let mut resource_table = resource_table.borrow_mut();
@@ -201,9 +217,13 @@ fn op_send(
       transport,
       transport_args: ArgsEnum::Ip(args),
     } if transport == "udp" => {
-      state.check_net(&args.hostname, args.port)?;
-
+      let check_addr = state.check_net_unresolved(&args.hostname, args.port)?;
+      let state = state.clone();
       let op = async move {
+        let addr = resolve_addr_async(args.hostname.clone(), args.port).await?;
+        if check_addr {
+          state.check_net_addr(&args.hostname, &addr)?;
+        }
         let mut resource_table = resource_table.borrow_mut();
         let resource = resource_table
           .get_mut::<UdpSocketResource>(rid as u32)
@@ -211,7 +231,6 @@ fn op_send(
             OpError::bad_resource("Socket has been closed".to_string())
           })?;
         let socket = &mut resource.socket;
-        let addr = resolve_addr(&args.hostname, args.port)?;
         socket.send_to(&buf, addr).await?;
         Ok(json!({}))
       };
@@ -267,9 +286,13 @@ fn op_connect(
       transport,
       transport_args: ArgsEnum::Ip(args),
     } if transport == "tcp" => {
-      state.check_net(&args.hostname, args.port)?;
+      let check_addr = state.check_net_unresolved(&args.hostname, args.port)?;
+      let state = state.clone();
       let op = async move {
-        let addr = resolve_addr(&args.hostname, args.port)?;
+        let addr = resolve_addr_async(args.hostname.clone(), args.port).await?;
+        if check_addr {
+          state.check_net_addr(&args.hostname, &addr)?;
+        }
         let tcp_stream = TcpStream::connect(&addr).await?;
         let local_addr = tcp_stream.local_addr()?;
         let remote_addr = tcp_stream.peer_addr()?;
@@ -346,7 +369,7 @@ fn op_shutdown(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.shutdown");
+  state.check_unstable("Deno.shutdown")?;
 
   let args: ShutdownArgs = serde_json::from_value(args)?;
 
@@ -427,6 +450,24 @@ impl TcpListenerResource {
   }
 }
 
+/// Stops tracking the accept task if the accept op is dropped before it
+/// completes, e.g. because it was cancelled, so the listener can accept again.
+struct AcceptTaskGuard {
+  resource_table: Rc<RefCell<ResourceTable>>,
+  rid: u32,
+}
+
+impl Drop for AcceptTaskGuard {
+  fn drop(&mut self) {
+    let mut resource_table = self.resource_table.borrow_mut();
+    if let Some(listener_resource) =
+      resource_table.get_mut::<TcpListenerResource>(self.rid)
+    {
+      listener_resource.untrack_task();
+    }
+  }
+}
+
 struct UdpSocketResource {
   socket: UdpSocket,
 }
@@ -495,10 +536,13 @@ fn op_listen(
       transport_args: ArgsEnum::Ip(args),
     } => {
       if transport == "udp" {
-        state.check_unstable("Deno.listenDatagram");
+        state.check_unstable("Deno.listenDatagram")?;
       }
-      state.check_net(&args.hostname, args.port)?;
+      let check_addr = state.check_net_unresolved(&args.hostname, args.port)?;
       let addr = resolve_addr(&args.hostname, args.port)?;
+      if check_addr {
+        state.check_net_addr(&args.hostname, &addr)?;
+      }
       let (rid, local_addr) = if transport == "tcp" {
         listen_tcp(&mut resource_table, addr)?
       } else {
@@ -525,7 +569,7 @@ fn op_listen(
       transport_args: ArgsEnum::Unix(args),
     } if transport == "unix" || transport == "unixpacket" => {
       if transport == "unixpacket" {
-        state.check_unstable("Deno.listenDatagram");
+        state.check_unstable("Deno.listenDatagram")?;
       }
       let address_path = net_unix::Path::new(&args.path);
       state.check_read(&address_path)?;
@@ -552,3 +596,143 @@ fn op_listen(
     _ => Err(OpError::other("Wrong argument format!".to_owned())),
   }
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveDnsArgs {
+  query: String,
+  record_type: String,
+  name_server: Option<NameServerArgs>,
+}
+
+#[derive(Deserialize)]
+struct NameServerArgs {
+  ip: String,
+  #[serde(default = "default_dns_port")]
+  port: u16,
+}
+
+fn default_dns_port() -> u16 {
+  53
+}
+
+fn op_resolve_dns(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.resolveDns")?;
+  let args: ResolveDnsArgs = serde_json::from_value(args)?;
+  state.check_net(&args.query, 53)?;
+
+  let config = match args.name_server {
+    Some(name_server) => {
+      state.check_net(&name_server.ip, name_server.port)?;
+      let ip: IpAddr = name_server.ip.parse().map_err(|_| {
+        OpError::type_error(format!(
+          "Invalid name server address \"{}\"",
+          name_server.ip
+        ))
+      })?;
+      let socket_addr = SocketAddr::new(ip, name_server.port);
+      let mut config = ResolverConfig::new();
+      config.add_name_server(NameServerConfig {
+        socket_addr,
+        protocol: Protocol::Udp,
+        tls_dns_name: None,
+      });
+      config.add_name_server(NameServerConfig {
+        socket_addr,
+        protocol: Protocol::Tcp,
+        tls_dns_name: None,
+      });
+      Some(config)
+    }
+    None => None,
+  };
+
+  let op = async move {
+    let resolver = match config {
+      Some(config) => {
+        TokioAsyncResolver::tokio(config, ResolverOpts::default()).await?
+      }
+      None => TokioAsyncResolver::tokio_from_system_conf().await?,
+    };
+    let query = args.query;
+
+    let records = match args.record_type.as_str() {
+      "A" => resolver
+        .ipv4_lookup(query.as_str())
+        .await?
+        .iter()
+        .map(|ip| json!(ip.to_string()))
+        .collect::<Vec<_>>(),
+      "AAAA" => resolver
+        .ipv6_lookup(query.as_str())
+        .await?
+        .iter()
+        .map(|ip| json!(ip.to_string()))
+        .collect(),
+      "TXT" => resolver
+        .txt_lookup(query.as_str())
+        .await?
+        .iter()
+        .map(|txt| {
+          let strings = txt
+            .txt_data()
+            .iter()
+            .map(|data| String::from_utf8_lossy(data).into_owned())
+            .collect::<Vec<_>>();
+          json!(strings)
+        })
+        .collect(),
+      "MX" => resolver
+        .mx_lookup(query.as_str())
+        .await?
+        .iter()
+        .map(|mx| {
+          json!({
+            "preference": mx.preference(),
+            "exchange": mx.exchange().to_utf8(),
+          })
+        })
+        .collect(),
+      "SRV" => resolver
+        .srv_lookup(query.as_str())
+        .await?
+        .iter()
+        .map(|srv| {
+          json!({
+            "priority": srv.priority(),
+            "weight": srv.weight(),
+            "port": srv.port(),
+            "target": srv.target().to_utf8(),
+          })
+        })
+        .collect(),
+      "PTR" => {
+        let ip: IpAddr = query.parse().map_err(|_| {
+          OpError::type_error(format!(
+            "PTR lookups expect an IP address, got \"{}\"",
+            query
+          ))
+        })?;
+        resolver
+          .reverse_lookup(ip)
+          .await?
+          .iter()
+          .map(|name| json!(name.to_utf8()))
+          .collect()
+      }
+      record_type => {
+        return Err(OpError::type_error(format!(
+          "Unsupported record type \"{}\"",
+          record_type
+        )))
+      }
+    };
+    Ok(json!(records))
+  };
+
+  Ok(JsonOp::Async(op.boxed_local()))
+}
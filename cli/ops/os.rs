@@ -1,16 +1,27 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::flags::DenoSubcommand;
 use crate::op_error::OpError;
+use crate::state::DebugType;
 use crate::state::State;
+use deno_core::v8;
 use deno_core::CoreIsolate;
 use deno_core::ZeroCopyBuf;
+use futures::channel::oneshot;
+use futures::future::FutureExt;
 use std::collections::HashMap;
 use std::env;
-use std::io::{Error, ErrorKind};
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use url::Url;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
-  i.register_op("op_exit", s.stateful_json_op(op_exit));
+  i.register_op("op_exit", s.stateful_json_op2(op_exit));
+  i.register_op(
+    "op_shutdown_on_sigint",
+    s.stateful_json_op2(op_shutdown_on_sigint),
+  );
   i.register_op("op_env", s.stateful_json_op(op_env));
   i.register_op("op_exec_path", s.stateful_json_op(op_exec_path));
   i.register_op("op_set_env", s.stateful_json_op(op_set_env));
@@ -19,6 +30,15 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_hostname", s.stateful_json_op(op_hostname));
   i.register_op("op_loadavg", s.stateful_json_op(op_loadavg));
   i.register_op("op_os_release", s.stateful_json_op(op_os_release));
+  i.register_op(
+    "op_system_memory_info",
+    s.stateful_json_op(op_system_memory_info),
+  );
+  i.register_op("op_memory_usage", s.stateful_json_op2(op_memory_usage));
+  i.register_op(
+    "op_network_interfaces",
+    s.stateful_json_op(op_network_interfaces),
+  );
 }
 
 #[derive(Deserialize)]
@@ -31,7 +51,7 @@ fn op_get_dir(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.dir");
+  state.check_unstable("Deno.dir")?;
   state.check_env()?;
   let args: GetDirArgs = serde_json::from_value(args)?;
 
@@ -143,12 +163,77 @@ struct Exit {
 }
 
 fn op_exit(
-  _s: &State,
+  isolate: &mut CoreIsolate,
+  state: &State,
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
   let args: Exit = serde_json::from_value(args)?;
-  std::process::exit(args.code)
+  let mut state = state.borrow_mut();
+  if state.debug_type != DebugType::Main {
+    // Workers with `Deno` namespace exit the whole process immediately.
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    std::process::exit(args.code)
+  }
+  // Stop executing JS; the embedder notices `exit_code` and performs
+  // graceful shutdown before exiting the process.
+  if state.exit_code.is_none() {
+    state.exit_code = Some(args.code);
+  }
+  isolate
+    .v8_isolate
+    .as_mut()
+    .unwrap()
+    .thread_safe_handle()
+    .terminate_execution();
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Makes Ctrl-C perform graceful shutdown of the main isolate, called once
+/// when the main runtime is bootstrapped. Like `Deno.exit()`, Ctrl-C terminates
+/// execution, which lets `MainWorker::handle_exit` take over even if JS is
+/// busy; the op then completes to wake up the event loop if it's idle.
+/// Pressing Ctrl-C again exits the process right away.
+fn op_shutdown_on_sigint(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let mut state = state.borrow_mut();
+  let is_repl = match state.global_state.flags.subcommand {
+    DenoSubcommand::Repl { .. } => true,
+    _ => false,
+  };
+  // The REPL handles Ctrl-C on its own.
+  if state.debug_type != DebugType::Main || is_repl || state.sigint.is_some() {
+    return Ok(JsonOp::Sync(json!({})));
+  }
+  let sigint = Arc::new(AtomicBool::new(false));
+  state.sigint = Some(sigint.clone());
+  let isolate_handle =
+    isolate.v8_isolate.as_mut().unwrap().thread_safe_handle();
+  let (sender, receiver) = oneshot::channel::<()>();
+  tokio::spawn(async move {
+    let mut sender = Some(sender);
+    while tokio::signal::ctrl_c().await.is_ok() {
+      if sigint.swap(true, Ordering::SeqCst) {
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        std::process::exit(130);
+      }
+      isolate_handle.terminate_execution();
+      if let Some(sender) = sender.take() {
+        let _ = sender.send(());
+      }
+    }
+  });
+  let fut = async move {
+    let _ = receiver.await;
+    Ok::<Value, OpError>(json!({}))
+  };
+  Ok(JsonOp::AsyncUnref(fut.boxed_local()))
 }
 
 fn op_loadavg(
@@ -156,7 +241,7 @@ fn op_loadavg(
   _args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.loadavg");
+  state.check_unstable("Deno.loadavg")?;
   state.check_env()?;
   match sys_info::loadavg() {
     Ok(loadavg) => Ok(JsonOp::Sync(json!([
@@ -173,7 +258,7 @@ fn op_hostname(
   _args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.hostname");
+  state.check_unstable("Deno.hostname")?;
   state.check_env()?;
   let hostname = sys_info::hostname().unwrap_or_else(|_| "".to_string());
   Ok(JsonOp::Sync(json!(hostname)))
@@ -184,8 +269,165 @@ fn op_os_release(
   _args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.osRelease");
+  state.check_unstable("Deno.osRelease")?;
   state.check_env()?;
   let release = sys_info::os_release().unwrap_or_else(|_| "".to_string());
   Ok(JsonOp::Sync(json!(release)))
 }
+
+fn op_system_memory_info(
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.systemMemoryInfo")?;
+  state.check_env()?;
+  let info = sys_info::mem_info()
+    .map_err(|e| OpError::other(format!("Can't get memory info: {}", e)))?;
+  // `sys_info` counts in KiB.
+  Ok(JsonOp::Sync(json!({
+    "total": info.total * 1024,
+    "free": info.free * 1024,
+    "available": info.avail * 1024,
+    "buffers": info.buffers * 1024,
+    "cached": info.cached * 1024,
+    "swapTotal": info.swap_total * 1024,
+    "swapFree": info.swap_free * 1024,
+  })))
+}
+
+fn op_memory_usage(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.memoryUsage")?;
+  let mut stats = v8::HeapStatistics::default();
+  isolate
+    .v8_isolate
+    .as_mut()
+    .unwrap()
+    .get_heap_statistics(&mut stats);
+  Ok(JsonOp::Sync(json!({
+    "rss": rss(),
+    "heapTotal": stats.total_heap_size(),
+    "heapUsed": stats.used_heap_size(),
+    "external": stats.external_memory(),
+  })))
+}
+
+/// The resident set size of this process, in bytes.
+#[cfg(target_os = "linux")]
+fn rss() -> u64 {
+  // The second field of statm is the resident set size in pages.
+  let statm = std::fs::read_to_string("/proc/self/statm").unwrap_or_default();
+  let pages = statm
+    .split_whitespace()
+    .nth(1)
+    .and_then(|pages| pages.parse::<u64>().ok())
+    .unwrap_or(0);
+  let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+  pages * page_size as u64
+}
+
+/// The peak resident set size of this process, in bytes. The current size
+/// isn't available through `libc` on these platforms.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn rss() -> u64 {
+  let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+  if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+    return 0;
+  }
+  let max_rss = usage.ru_maxrss as u64;
+  // macOS counts in bytes, the BSDs in KiB.
+  if cfg!(target_os = "macos") {
+    max_rss
+  } else {
+    max_rss * 1024
+  }
+}
+
+/// The working set size of this process, in bytes.
+#[cfg(windows)]
+fn rss() -> u64 {
+  use winapi::um::processthreadsapi::GetCurrentProcess;
+  use winapi::um::psapi::GetProcessMemoryInfo;
+  use winapi::um::psapi::PROCESS_MEMORY_COUNTERS;
+
+  let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+  let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+  let ok =
+    unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+  if ok == 0 {
+    return 0;
+  }
+  counters.WorkingSetSize as u64
+}
+
+fn op_network_interfaces(
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.networkInterfaces")?;
+  state.check_env()?;
+  Ok(JsonOp::Sync(json!(network_interfaces()?)))
+}
+
+#[cfg(unix)]
+fn network_interfaces() -> Result<Vec<Value>, OpError> {
+  use nix::ifaddrs::getifaddrs;
+  use nix::net::if_::InterfaceFlags;
+  use nix::sys::socket::SockAddr;
+
+  let addrs: Vec<_> = getifaddrs()
+    .map_err(|e| OpError::other(format!("Can't list interfaces: {}", e)))?
+    .collect();
+
+  // Hardware addresses are listed as separate entries of the interface.
+  let mut macs = HashMap::new();
+  for addr in &addrs {
+    if let Some(SockAddr::Link(link)) = addr.address {
+      let mac = link
+        .addr()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":");
+      macs.insert(addr.interface_name.clone(), mac);
+    }
+  }
+
+  let mut interfaces = Vec::new();
+  for addr in addrs {
+    let ip = match addr.address {
+      Some(SockAddr::Inet(inet)) => inet.to_std().ip(),
+      _ => continue,
+    };
+    let netmask = match addr.netmask {
+      Some(SockAddr::Inet(inet)) => inet.to_std().ip().to_string(),
+      _ => String::new(),
+    };
+    let mac = macs
+      .get(&addr.interface_name)
+      .cloned()
+      .unwrap_or_else(|| "00:00:00:00:00:00".to_string());
+    interfaces.push(json!({
+      "name": addr.interface_name,
+      "family": if ip.is_ipv4() { "IPv4" } else { "IPv6" },
+      "address": ip.to_string(),
+      "netmask": netmask,
+      "mac": mac,
+      "internal": addr.flags.contains(InterfaceFlags::IFF_LOOPBACK),
+    }));
+  }
+  Ok(interfaces)
+}
+
+#[cfg(not(unix))]
+fn network_interfaces() -> Result<Vec<Value>, OpError> {
+  Err(OpError::not_supported(
+    "Deno.networkInterfaces isn't supported on this platform".to_string(),
+  ))
+}
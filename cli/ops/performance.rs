@@ -0,0 +1,71 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::op_error::OpError;
+use crate::performance::Entry;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_performance_record",
+    s.stateful_json_op(op_performance_record),
+  );
+  i.register_op(
+    "op_performance_clear",
+    s.stateful_json_op(op_performance_clear),
+  );
+  i.register_op(
+    "op_performance_entries",
+    s.stateful_json_op(op_performance_entries),
+  );
+}
+
+/// Adds a mark or measure to the isolate's timeline.
+fn op_performance_record(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let entry: Entry = serde_json::from_value(args)?;
+  let state = state.borrow();
+  if let Some(timeline_id) = state.timeline_id {
+    state.global_state.performance.record(timeline_id, entry);
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClearArgs {
+  entry_type: String,
+  name: Option<String>,
+}
+
+fn op_performance_clear(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ClearArgs = serde_json::from_value(args)?;
+  let state = state.borrow();
+  if let Some(timeline_id) = state.timeline_id {
+    state.global_state.performance.clear(
+      timeline_id,
+      &args.entry_type,
+      args.name.as_deref(),
+    );
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Returns the marks and measures of all isolates, by isolate.
+fn op_performance_entries(
+  state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.performanceEntries")?;
+  let state = state.borrow();
+  Ok(JsonOp::Sync(state.global_state.performance.to_json()))
+}
@@ -68,6 +68,7 @@ pub fn op_revoke_permission(
     "net" => permissions.allow_net.revoke(),
     "env" => permissions.allow_env.revoke(),
     "plugin" => permissions.allow_plugin.revoke(),
+    "ffi" => permissions.allow_ffi.revoke(),
     "hrtime" => permissions.allow_hrtime.revoke(),
     _ => {}
   };
@@ -100,6 +101,7 @@ pub fn op_request_permission(
     "net" => permissions.request_net(&args.url.as_deref()),
     "env" => Ok(permissions.request_env()),
     "plugin" => Ok(permissions.request_plugin()),
+    "ffi" => Ok(permissions.request_ffi()),
     "hrtime" => Ok(permissions.request_hrtime()),
     n => Err(OpError::other(format!("No such permission name: {}", n))),
   }?;
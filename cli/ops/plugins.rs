@@ -39,26 +39,23 @@ pub fn op_open_plugin(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.openPlugin");
-  let args: OpenPluginArgs = serde_json::from_value(args).unwrap();
+  state.check_unstable("Deno.openPlugin")?;
+  let args: OpenPluginArgs = serde_json::from_value(args)?;
   let filename = deno_fs::resolve_from_cwd(Path::new(&args.filename))?;
 
   state.check_plugin(&filename)?;
 
-  let lib = open_plugin(filename).unwrap();
-  let plugin_resource = PluginResource { lib };
-
-  let mut resource_table = isolate.resource_table.borrow_mut();
-  let rid = resource_table.add("plugin", Box::new(plugin_resource));
-  let plugin_resource = resource_table.get::<PluginResource>(rid).unwrap();
+  // Failing to open the library or to find the init function is reported
+  // to JS rather than bringing the whole process down.
+  let lib = open_plugin(filename)?;
+  let deno_plugin_init =
+    *unsafe { lib.symbol::<PluginInitFn>("deno_plugin_init") }?;
 
-  let deno_plugin_init = *unsafe {
-    plugin_resource
-      .lib
-      .symbol::<PluginInitFn>("deno_plugin_init")
-  }
-  .unwrap();
-  drop(resource_table);
+  let plugin_resource = PluginResource { lib };
+  let rid = isolate
+    .resource_table
+    .borrow_mut()
+    .add("plugin", Box::new(plugin_resource));
 
   deno_plugin_init(isolate);
 
@@ -1,6 +1,8 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
-use super::io::{std_file_resource, StreamResource, StreamResourceHolder};
+use super::io::{
+  std_file_resource, DenoAsyncRead, StreamResource, StreamResourceHolder,
+};
 use crate::op_error::OpError;
 use crate::signal::kill;
 use crate::state::State;
@@ -10,7 +12,10 @@ use deno_core::ZeroCopyBuf;
 use futures::future::poll_fn;
 use futures::future::FutureExt;
 use futures::TryFutureExt;
+use std::cell::RefCell;
 use std::convert::From;
+use std::process::ExitStatus;
+use std::rc::Rc;
 use tokio::process::Command;
 
 #[cfg(unix)]
@@ -19,6 +24,11 @@ use std::os::unix::process::ExitStatusExt;
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_run", s.stateful_json_op2(op_run));
   i.register_op("op_run_status", s.stateful_json_op2(op_run_status));
+  i.register_op("op_run_output", s.stateful_json_op2(op_run_output));
+  i.register_op(
+    "op_run_output_take",
+    s.stateful_json_op2(op_run_output_take),
+  );
   i.register_op("op_kill", s.stateful_json_op(op_kill));
 }
 
@@ -53,6 +63,10 @@ struct RunArgs {
   stdin_rid: u32,
   stdout_rid: u32,
   stderr_rid: u32,
+  detached: bool,
+  uid: Option<u32>,
+  gid: Option<u32>,
+  pgid: Option<i32>,
 }
 
 struct ChildResource {
@@ -109,8 +123,60 @@ fn op_run(
     c.stderr(subprocess_stdio_map(run_args.stderr.as_ref()));
   }
 
-  // We want to kill child when it's closed
-  c.kill_on_drop(true);
+  // Kill the child when its resource is closed, either explicitly or when
+  // the isolate is dropped, unless it was asked to outlive the program.
+  c.kill_on_drop(!run_args.detached);
+
+  if run_args.detached && run_args.pgid.is_some() {
+    return Err(OpError::type_error(
+      "The detached and pgid options can't be combined".to_string(),
+    ));
+  }
+  #[cfg(unix)]
+  {
+    if let Some(uid) = run_args.uid {
+      c.uid(uid);
+    }
+    if let Some(gid) = run_args.gid {
+      c.gid(gid);
+    }
+    let detached = run_args.detached;
+    let pgid = run_args.pgid;
+    if detached || pgid.is_some() {
+      // Runs in the child between fork and exec, so it only makes syscalls.
+      unsafe {
+        c.pre_exec(move || {
+          // A new session also detaches the child from the terminal, whose
+          // hangup would otherwise end it with the parent.
+          let result = match pgid {
+            Some(pgid) => libc::setpgid(0, pgid),
+            None => libc::setsid(),
+          };
+          if result == -1 {
+            return Err(std::io::Error::last_os_error());
+          }
+          Ok(())
+        });
+      }
+    }
+  }
+  #[cfg(windows)]
+  {
+    if run_args.uid.is_some() || run_args.gid.is_some() {
+      return Err(OpError::not_supported(
+        "The uid and gid options are not supported on Windows".to_string(),
+      ));
+    }
+    if run_args.pgid.is_some() {
+      return Err(OpError::not_supported(
+        "The pgid option is not supported on Windows".to_string(),
+      ));
+    }
+    if run_args.detached {
+      use winapi::um::winbase::{CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS};
+      c.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+  }
 
   // Spawn the command.
   let mut child = c.spawn()?;
@@ -186,38 +252,189 @@ fn op_run_status(
   let resource_table = isolate.resource_table.clone();
 
   let future = async move {
-    let run_status = poll_fn(|cx| {
-      let mut resource_table = resource_table.borrow_mut();
-      let child_resource = resource_table
-        .get_mut::<ChildResource>(rid)
-        .ok_or_else(OpError::bad_resource_id)?;
-      let child = &mut child_resource.child;
-      child.map_err(OpError::from).poll_unpin(cx)
-    })
-    .await?;
+    let run_status = wait_child(resource_table, rid).await?;
+    Ok(run_status_json(run_status))
+  };
+
+  Ok(JsonOp::Async(future.boxed_local()))
+}
+
+async fn wait_child(
+  resource_table: Rc<RefCell<ResourceTable>>,
+  rid: u32,
+) -> Result<ExitStatus, OpError> {
+  poll_fn(|cx| {
+    let mut resource_table = resource_table.borrow_mut();
+    let child_resource = resource_table
+      .get_mut::<ChildResource>(rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    let child = &mut child_resource.child;
+    child.map_err(OpError::from).poll_unpin(cx)
+  })
+  .await
+}
+
+fn run_status_json(run_status: ExitStatus) -> Value {
+  let code = run_status.code();
+
+  #[cfg(unix)]
+  let signal = run_status.signal();
+  #[cfg(not(unix))]
+  let signal = None;
+
+  code
+    .or(signal)
+    .expect("Should have either an exit code or a signal.");
+  let got_signal = signal.is_some();
+
+  json!({
+     "gotSignal": got_signal,
+     "exitCode": code.unwrap_or(-1),
+     "exitSignal": signal.unwrap_or(-1),
+  })
+}
+
+/// The drained stdout and stderr of a child, until JS takes them with
+/// `op_run_output_take`.
+struct ChildOutputResource {
+  stdout: Vec<u8>,
+  stderr: Vec<u8>,
+}
+
+/// Takes a piped stdout or stderr of a child out of the resource table, so
+/// it can be read without holding on to the table.
+fn take_child_pipe(
+  resource_table: &mut ResourceTable,
+  rid: Option<u32>,
+) -> Result<Option<StreamResource>, OpError> {
+  let rid = match rid {
+    Some(rid) => rid,
+    None => return Ok(None),
+  };
+  match resource_table.get_mut::<StreamResourceHolder>(rid) {
+    Some(resource_holder) => match resource_holder.resource {
+      StreamResource::ChildStdout(_) | StreamResource::ChildStderr(_) => {}
+      _ => return Err(OpError::bad_resource_id()),
+    },
+    None => return Err(OpError::bad_resource_id()),
+  }
+  let mut resource_holder =
+    resource_table.remove::<StreamResourceHolder>(rid).unwrap();
+  let resource = std::mem::replace(
+    &mut resource_holder.resource,
+    StreamResource::FsFile(None),
+  );
+  Ok(Some(resource))
+}
 
-    let code = run_status.code();
+async fn read_child_pipe(
+  pipe: Option<StreamResource>,
+) -> Result<Vec<u8>, OpError> {
+  let mut output = Vec::new();
+  if let Some(mut pipe) = pipe {
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+      let nread = poll_fn(|cx| pipe.poll_read(cx, &mut buf)).await?;
+      if nread == 0 {
+        break;
+      }
+      output.extend_from_slice(&buf[..nread]);
+    }
+  }
+  Ok(output)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunOutputArgs {
+  rid: u32,
+  stdout_rid: Option<u32>,
+  stderr_rid: Option<u32>,
+}
 
-    #[cfg(unix)]
-    let signal = run_status.signal();
-    #[cfg(not(unix))]
-    let signal = None;
+/// Reads the piped stdout and stderr of a child to the end while waiting for
+/// it to exit. Reading both at once means a child that fills one pipe while
+/// its parent waits on the other doesn't deadlock. Resolves to the status,
+/// the lengths of the outputs, and the rid to take them from.
+fn op_run_output(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RunOutputArgs = serde_json::from_value(args)?;
+  state.check_run()?;
 
-    code
-      .or(signal)
-      .expect("Should have either an exit code or a signal.");
-    let got_signal = signal.is_some();
+  let resource_table = isolate.resource_table.clone();
+  let (stdout, stderr) = {
+    let mut resource_table = resource_table.borrow_mut();
+    if !resource_table.has(args.rid) {
+      return Err(OpError::bad_resource_id());
+    }
+    let stdout = take_child_pipe(&mut resource_table, args.stdout_rid)?;
+    let stderr = take_child_pipe(&mut resource_table, args.stderr_rid)?;
+    (stdout, stderr)
+  };
 
+  let future = async move {
+    let (stdout, stderr, run_status) = futures::future::try_join3(
+      read_child_pipe(stdout),
+      read_child_pipe(stderr),
+      wait_child(resource_table.clone(), args.rid),
+    )
+    .await?;
+    let stdout_len = stdout.len();
+    let stderr_len = stderr.len();
+    let output_rid = resource_table.borrow_mut().add(
+      "childOutput",
+      Box::new(ChildOutputResource { stdout, stderr }),
+    );
     Ok(json!({
-       "gotSignal": got_signal,
-       "exitCode": code.unwrap_or(-1),
-       "exitSignal": signal.unwrap_or(-1),
+      "status": run_status_json(run_status),
+      "outputRid": output_rid,
+      "stdoutLength": stdout_len,
+      "stderrLength": stderr_len,
     }))
   };
 
   Ok(JsonOp::Async(future.boxed_local()))
 }
 
+#[derive(Deserialize)]
+struct RunOutputTakeArgs {
+  rid: u32,
+}
+
+/// Copies the outputs collected by `op_run_output` into the buffer, stdout
+/// followed by stderr, and closes their resource.
+fn op_run_output_take(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: RunOutputTakeArgs = serde_json::from_value(args)?;
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let output = resource_table
+    .remove::<ChildOutputResource>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  let len = output.stdout.len() + output.stderr.len();
+  if len == 0 {
+    return Ok(JsonOp::Sync(json!({})));
+  }
+  let mut buf = zero_copy
+    .ok_or_else(|| OpError::type_error("no buffer specified".to_string()))?;
+  if buf.len() != len {
+    return Err(OpError::type_error(format!(
+      "Expected a buffer of {} bytes",
+      len
+    )));
+  }
+  buf[..output.stdout.len()].copy_from_slice(&output.stdout);
+  buf[output.stdout.len()..].copy_from_slice(&output.stderr);
+  Ok(JsonOp::Sync(json!({})))
+}
+
 #[derive(Deserialize)]
 struct KillArgs {
   pid: i32,
@@ -229,7 +446,7 @@ fn op_kill(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.kill");
+  state.check_unstable("Deno.kill")?;
   state.check_run()?;
 
   let args: KillArgs = serde_json::from_value(args)?;
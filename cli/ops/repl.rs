@@ -1,20 +1,37 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-use super::dispatch_json::{blocking_json, Deserialize, JsonOp, Value};
+use super::dispatch_json::{Deserialize, JsonOp, JsonResult, Value};
+use crate::colors;
+use crate::flags::DenoSubcommand;
 use crate::op_error::OpError;
 use crate::repl;
 use crate::repl::Repl;
 use crate::state::State;
+use crate::tsc::runtime_compile;
+use crate::tsc::runtime_transpile;
 use deno_core::CoreIsolate;
 use deno_core::ZeroCopyBuf;
+use futures::future;
+use futures::future::AbortHandle;
+use futures::future::FutureExt;
+use std::collections::HashMap;
+use std::env;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_repl_start", s.stateful_json_op2(op_repl_start));
   i.register_op("op_repl_readline", s.stateful_json_op2(op_repl_readline));
+  i.register_op("op_repl_check", s.stateful_json_op(op_repl_check));
 }
 
-struct ReplResource(Arc<Mutex<Repl>>);
+struct ReplResource(Arc<Mutex<Repl>>, AbortHandle);
+
+impl Drop for ReplResource {
+  fn drop(&mut self) {
+    // Stop listening for Ctrl-C.
+    self.1.abort();
+  }
+}
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -30,13 +47,51 @@ fn op_repl_start(
 ) -> Result<JsonOp, OpError> {
   let args: ReplStartArgs = serde_json::from_value(args)?;
   debug!("op_repl_start {}", args.history_file);
-  let history_path =
-    repl::history_path(&state.borrow().global_state.dir, &args.history_file);
-  let repl = repl::Repl::new(history_path);
-  let resource = ReplResource(Arc::new(Mutex::new(repl)));
+  let global_state = state.borrow().global_state.clone();
+  let (history_file, history_size, history_ignore_space, check) =
+    match global_state.flags.subcommand {
+      DenoSubcommand::Repl {
+        ref history_file,
+        history_size,
+        history_ignore_space,
+        check,
+      } => (
+        history_file.clone(),
+        history_size,
+        history_ignore_space,
+        check,
+      ),
+      _ => (None, None, false, false),
+    };
+  let history_path = repl::history_path(
+    &global_state.dir,
+    &args.history_file,
+    history_file,
+    env::var_os("DENO_REPL_HISTORY"),
+  );
+  let repl = repl::Repl::new(
+    history_path,
+    history_size.unwrap_or(repl::DEFAULT_HISTORY_SIZE),
+    history_ignore_space,
+  );
+  // While input is read the terminal is in raw mode and Ctrl-C is handled
+  // by `readline`; otherwise it interrupts code that is being evaluated, if
+  // any.
+  let interrupt_handle = isolate.eval_interrupt_handle();
+  let (interrupt_listener, abort_handle) = future::abortable(async move {
+    while tokio::signal::ctrl_c().await.is_ok() {
+      interrupt_handle.interrupt();
+    }
+  });
+  tokio::spawn(interrupt_listener);
+  let resource = ReplResource(Arc::new(Mutex::new(repl)), abort_handle);
   let mut resource_table = isolate.resource_table.borrow_mut();
   let rid = resource_table.add("repl", Box::new(resource));
-  Ok(JsonOp::Sync(json!(rid)))
+  Ok(JsonOp::Sync(json!({
+    "rid": rid,
+    "check": check,
+    "colors": colors::use_color(),
+  })))
 }
 
 #[derive(Deserialize)]
@@ -61,8 +116,59 @@ fn op_repl_readline(
     .ok_or_else(OpError::bad_resource_id)?;
   let repl = resource.0.clone();
 
-  blocking_json(false, move || {
-    let line = repl.lock().unwrap().readline(&prompt)?;
-    Ok(json!(line))
-  })
+  // Waiting for user input can take arbitrarily long, so it's not run on
+  // the bounded blocking pool used by `blocking_json`.
+  let fut = async move {
+    tokio::task::spawn_blocking(move || -> JsonResult {
+      let line = repl.lock().unwrap().readline(&prompt)?;
+      Ok(json!(line))
+    })
+    .await
+    .unwrap()
+  };
+  Ok(JsonOp::Async(fut.boxed_local()))
+}
+
+/// Name of the file REPL input is type checked as.
+const REPL_CHECK_FILE: &str = "/$deno$repl.ts";
+
+#[derive(Deserialize)]
+struct ReplCheckArgs {
+  // Input evaluated so far followed by the new input.
+  source: String,
+  input: String,
+}
+
+/// Type checks the new input of `deno repl --check` in the context of
+/// previous input and transpiles it to JavaScript that can be evaluated.
+fn op_repl_check(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ReplCheckArgs = serde_json::from_value(args)?;
+  let global_state = state.borrow().global_state.clone();
+  let fut = async move {
+    let mut sources = HashMap::new();
+    sources.insert(REPL_CHECK_FILE.to_string(), args.source);
+    let mut input = HashMap::new();
+    input.insert(REPL_CHECK_FILE.to_string(), args.input);
+    let (compiled, transpiled) = future::try_join(
+      runtime_compile(
+        global_state.clone(),
+        REPL_CHECK_FILE,
+        &Some(sources),
+        false,
+        &None,
+        true,
+      ),
+      runtime_transpile(global_state, &input, &None),
+    )
+    .await?;
+    Ok(json!({
+      "diagnostics": compiled["diagnostics"],
+      "source": transpiled[REPL_CHECK_FILE]["source"],
+    }))
+  };
+  Ok(JsonOp::Async(fut.boxed_local()))
 }
@@ -1,5 +1,5 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
-use super::dispatch_json::{JsonOp, Value};
+use super::dispatch_json::{Deserialize, JsonOp, Value};
 use crate::colors;
 use crate::op_error::OpError;
 use crate::state::State;
@@ -11,27 +11,43 @@ use std::env;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_start", s.stateful_json_op(op_start));
-  i.register_op("op_metrics", s.stateful_json_op(op_metrics));
+  i.register_op("op_metrics", s.stateful_json_op2(op_metrics));
+}
+
+#[derive(Deserialize)]
+struct StartArgs {
+  /// Name of the isolate's performance timeline; "main" if not given.
+  name: Option<String>,
 }
 
 fn op_start(
   state: &State,
-  _args: Value,
+  args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  let state = state.borrow();
-  let gs = &state.global_state;
+  let args: StartArgs = serde_json::from_value(args)?;
+  let mut state = state.borrow_mut();
+  let gs = state.global_state.clone();
+  let time_origin = gs.performance.time_origin(state.start_time);
+  if state.timeline_id.is_none() {
+    let name = args.name.unwrap_or_else(|| "main".to_string());
+    let timeline_id = gs.performance.add_timeline(name, time_origin);
+    state.timeline_id = Some(timeline_id);
+  }
 
   Ok(JsonOp::Sync(json!({
     // TODO(bartlomieju): `cwd` field is not used in JS, remove?
     "args": gs.flags.argv.clone(),
+    "cpuCount": sys_info::cpu_num().unwrap_or(1),
     "cwd": &env::current_dir().unwrap(),
-    "debugFlag": gs.flags.log_level.map_or(false, |l| l == log::Level::Debug),
+    "debugFlag": gs.flags.log_level.map_or(false, |l| l >= log::Level::Debug),
     "denoVersion": version::DENO,
-    "noColor": !colors::use_color(),
+    "mainModule": state.main_module.to_string(),
+    "noColor": colors::no_color(),
     "pid": std::process::id(),
-    "repl": gs.flags.subcommand == DenoSubcommand::Repl,
+    "repl": matches!(gs.flags.subcommand, DenoSubcommand::Repl { .. }),
     "target": env!("TARGET"),
+    "timeOrigin": time_origin,
     "tsVersion": version::TYPESCRIPT,
     "unstableFlag": gs.flags.unstable,
     "v8Version": version::v8(),
@@ -40,24 +56,11 @@ fn op_start(
 }
 
 fn op_metrics(
+  isolate: &mut CoreIsolate,
   state: &State,
   _args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
   let state = state.borrow();
-  let m = &state.metrics;
-
-  Ok(JsonOp::Sync(json!({
-    "opsDispatched": m.ops_dispatched,
-    "opsDispatchedSync": m.ops_dispatched_sync,
-    "opsDispatchedAsync": m.ops_dispatched_async,
-    "opsDispatchedAsyncUnref": m.ops_dispatched_async_unref,
-    "opsCompleted": m.ops_completed,
-    "opsCompletedSync": m.ops_completed_sync,
-    "opsCompletedAsync": m.ops_completed_async,
-    "opsCompletedAsyncUnref": m.ops_completed_async_unref,
-    "bytesSentControl": m.bytes_sent_control,
-    "bytesSentData": m.bytes_sent_data,
-    "bytesReceived": m.bytes_received
-  })))
+  Ok(JsonOp::Sync(state.metrics.to_json(&isolate.op_registry)))
 }
@@ -4,6 +4,7 @@ use crate::futures::FutureExt;
 use crate::op_error::OpError;
 use crate::state::State;
 use crate::tsc::runtime_compile;
+use crate::tsc::runtime_graph;
 use crate::tsc::runtime_transpile;
 use deno_core::CoreIsolate;
 use deno_core::ZeroCopyBuf;
@@ -12,6 +13,8 @@ use std::collections::HashMap;
 pub fn init(i: &mut CoreIsolate, s: &State) {
   i.register_op("op_compile", s.stateful_json_op(op_compile));
   i.register_op("op_transpile", s.stateful_json_op(op_transpile));
+  i.register_op("op_emit", s.stateful_json_op(op_emit));
+  i.register_op("op_graph", s.stateful_json_op(op_graph));
 }
 
 #[derive(Deserialize, Debug)]
@@ -28,7 +31,7 @@ fn op_compile(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.compile");
+  state.check_unstable("Deno.compile")?;
   let args: CompileArgs = serde_json::from_value(args)?;
   let global_state = state.borrow().global_state.clone();
   let fut = async move {
@@ -38,6 +41,7 @@ fn op_compile(
       &args.sources,
       args.bundle,
       &args.options,
+      true,
     )
     .await
   }
@@ -45,6 +49,61 @@ fn op_compile(
   Ok(JsonOp::Async(fut))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct EmitArgs {
+  root_name: String,
+  sources: Option<HashMap<String, String>>,
+  bundle: bool,
+  check: bool,
+  options: Option<String>,
+}
+
+fn op_emit(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.emit")?;
+  let args: EmitArgs = serde_json::from_value(args)?;
+  let global_state = state.borrow().global_state.clone();
+  let fut = async move {
+    runtime_compile(
+      global_state,
+      &args.root_name,
+      &args.sources,
+      args.bundle,
+      &args.options,
+      args.check,
+    )
+    .await
+  }
+  .boxed_local();
+  Ok(JsonOp::Async(fut))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GraphArgs {
+  root_name: String,
+  sources: Option<HashMap<String, String>>,
+}
+
+fn op_graph(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.graph")?;
+  let args: GraphArgs = serde_json::from_value(args)?;
+  let global_state = state.borrow().global_state.clone();
+  let fut = async move {
+    runtime_graph(global_state, &args.root_name, &args.sources).await
+  }
+  .boxed_local();
+  Ok(JsonOp::Async(fut))
+}
+
 #[derive(Deserialize, Debug)]
 struct TranspileArgs {
   sources: HashMap<String, String>,
@@ -56,7 +115,7 @@ fn op_transpile(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.transpile");
+  state.check_unstable("Deno.transpile")?;
   let args: TranspileArgs = serde_json::from_value(args)?;
   let global_state = state.borrow().global_state.clone();
   let fut = async move {
@@ -0,0 +1,172 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+//! Resources reading from a stream of chunks. Such a stream can come from
+//! JavaScript: JS writes the chunks of a `ReadableStream` into the sink half,
+//! and Rust reads them from the reader half like from any other stream
+//! resource, e.g. with `op_read` or as the body of a fetch request.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use super::io::{StreamResource, StreamResourceHolder};
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use futures::channel::mpsc;
+use futures::future::FutureExt;
+use futures::sink::SinkExt;
+use futures::stream::Stream;
+use futures::stream::StreamExt;
+use std::cmp::min;
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use tokio::io::AsyncRead;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_stream_resource_open",
+    s.stateful_json_op2(op_stream_resource_open),
+  );
+  i.register_op(
+    "op_stream_resource_write",
+    s.stateful_json_op2(op_stream_resource_write),
+  );
+}
+
+/// A chunk written by JS, or the error the JS stream errored with.
+type Chunk = Result<Vec<u8>, String>;
+
+type ByteStream =
+  Pin<Box<dyn Stream<Item = Result<Vec<u8>, io::Error>> + Send + Sync>>;
+
+/// A resource reading from a stream of chunks, like the reading half of a
+/// resource opened with `op_stream_resource_open`.
+pub struct StreamResourceReader {
+  stream: ByteStream,
+  chunk: Vec<u8>,
+  pos: usize,
+}
+
+impl StreamResourceReader {
+  pub fn new<S>(stream: S) -> Self
+  where
+    S: Stream<Item = Result<Vec<u8>, io::Error>> + Send + Sync + 'static,
+  {
+    Self {
+      stream: Box::pin(stream),
+      chunk: vec![],
+      pos: 0,
+    }
+  }
+
+  /// The chunks not read yet, as a stream.
+  pub fn into_stream(
+    self,
+  ) -> impl Stream<Item = Result<Vec<u8>, io::Error>> + Send + Sync {
+    let rest = self.chunk[self.pos..].to_vec();
+    let rest = if rest.is_empty() {
+      vec![]
+    } else {
+      vec![Ok(rest)]
+    };
+    futures::stream::iter(rest).chain(self.stream)
+  }
+}
+
+impl AsyncRead for StreamResourceReader {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+    buf: &mut [u8],
+  ) -> Poll<Result<usize, io::Error>> {
+    let inner = self.get_mut();
+    while inner.pos == inner.chunk.len() {
+      match futures::ready!(inner.stream.poll_next_unpin(cx)) {
+        Some(Ok(chunk)) => {
+          inner.chunk = chunk;
+          inner.pos = 0;
+        }
+        Some(Err(e)) => return Poll::Ready(Err(e)),
+        None => return Poll::Ready(Ok(0)),
+      }
+    }
+    let n = min(buf.len(), inner.chunk.len() - inner.pos);
+    buf[..n].copy_from_slice(&inner.chunk[inner.pos..inner.pos + n]);
+    inner.pos += n;
+    Poll::Ready(Ok(n))
+  }
+}
+
+/// The writing half, kept by JS. Closing it ends the reader.
+struct StreamResourceSink {
+  sender: mpsc::Sender<Chunk>,
+}
+
+/// Returns the rids of a new reader and of the sink writing to it.
+fn op_stream_resource_open(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  _args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  // A single chunk is buffered, so writes wait for the reader.
+  let (sender, receiver) = mpsc::channel::<Chunk>(1);
+  let reader = StreamResourceReader::new(receiver.map(|chunk| {
+    chunk.map_err(|msg| io::Error::new(io::ErrorKind::Other, msg))
+  }));
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add(
+    "streamResource",
+    Box::new(StreamResourceHolder::new(StreamResource::ByteStream(
+      Box::new(reader),
+    ))),
+  );
+  let sink_rid = resource_table.add(
+    "streamResourceSink",
+    Box::new(StreamResourceSink { sender }),
+  );
+  Ok(JsonOp::Sync(json!({
+    "rid": rid,
+    "sinkRid": sink_rid,
+  })))
+}
+
+#[derive(Deserialize)]
+struct WriteArgs {
+  rid: u32,
+  /// Errors the reader instead of writing a chunk.
+  error: Option<String>,
+}
+
+/// Writes the chunk in `zero_copy` to the sink, resolving once the reader
+/// has room for it. Fails with `BrokenPipe` once the reader is closed.
+fn op_stream_resource_write(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: WriteArgs = serde_json::from_value(args)?;
+  let chunk = match args.error {
+    Some(msg) => Err(msg),
+    None => Ok(
+      zero_copy
+        .map(|buf| buf.to_vec())
+        .ok_or_else(|| OpError::type_error("Expected a chunk".to_string()))?,
+    ),
+  };
+  let mut sender = {
+    let resource_table = isolate.resource_table.borrow();
+    let resource = resource_table
+      .get::<StreamResourceSink>(args.rid)
+      .ok_or_else(OpError::bad_resource_id)?;
+    resource.sender.clone()
+  };
+  let future = async move {
+    sender
+      .send(chunk)
+      .await
+      .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+    Ok(json!({}))
+  };
+  Ok(JsonOp::Async(future.boxed_local()))
+}
@@ -0,0 +1,26 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{JsonOp, Value};
+use crate::op_error::OpError;
+use crate::state::State;
+use crate::test_reporter::TestEvent;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_test_event", s.stateful_json_op(op_test_event));
+}
+
+fn op_test_event(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let event: TestEvent = serde_json::from_value(args)?;
+  let global_state = state.borrow().global_state.clone();
+  // Events are dropped unless tests are run by `deno test`.
+  if let Some(collector) = global_state.test_collector.lock().unwrap().as_mut()
+  {
+    collector.visit_event(event);
+  }
+  Ok(JsonOp::Sync(json!({})))
+}
@@ -9,42 +9,27 @@ use std::time::Duration;
 use std::time::Instant;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
-  i.register_op(
-    "op_global_timer_stop",
-    s.stateful_json_op(op_global_timer_stop),
-  );
   i.register_op("op_global_timer", s.stateful_json_op(op_global_timer));
   i.register_op("op_now", s.stateful_json_op(op_now));
 }
 
-fn op_global_timer_stop(
-  state: &State,
-  _args: Value,
-  _zero_copy: Option<ZeroCopyBuf>,
-) -> Result<JsonOp, OpError> {
-  let mut state = state.borrow_mut();
-  state.global_timer.cancel();
-  Ok(JsonOp::Sync(json!({})))
-}
-
 #[derive(Deserialize)]
 struct GlobalTimerArgs {
   timeout: u64,
 }
 
+/// Resolves after `timeout` milliseconds. JS only keeps one of these pending,
+/// for the timer that is due first, and stops it through a cancel handle.
 fn op_global_timer(
-  state: &State,
+  _state: &State,
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
   let args: GlobalTimerArgs = serde_json::from_value(args)?;
   let val = args.timeout;
 
-  let mut state = state.borrow_mut();
   let deadline = Instant::now() + Duration::from_millis(val);
-  let f = state
-    .global_timer
-    .new_timeout(deadline)
+  let f = tokio::time::delay_until(deadline.into())
     .then(move |_| futures::future::ok(json!({})));
 
   Ok(JsonOp::Async(f.boxed_local()))
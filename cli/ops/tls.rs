@@ -3,16 +3,20 @@ use super::dispatch_json::{Deserialize, JsonOp, Value};
 use super::io::{StreamResource, StreamResourceHolder};
 use crate::op_error::OpError;
 use crate::resolve_addr::resolve_addr;
+use crate::resolve_addr::resolve_addr_async;
 use crate::state::State;
 use deno_core::CoreIsolate;
+use deno_core::ResourceTable;
 use deno_core::ZeroCopyBuf;
 use futures::future::poll_fn;
 use futures::future::FutureExt;
+use std::cell::RefCell;
 use std::convert::From;
 use std::fs::File;
 use std::io::BufReader;
 use std::net::SocketAddr;
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
@@ -58,7 +62,7 @@ pub fn op_start_tls(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.startTls");
+  state.check_unstable("Deno.startTls")?;
   let args: StartTLSArgs = serde_json::from_value(args)?;
   let rid = args.rid as u32;
   let cert_file = args.cert_file.clone();
@@ -140,7 +144,6 @@ pub fn op_connect_tls(
   let args: ConnectTLSArgs = serde_json::from_value(args)?;
   let cert_file = args.cert_file.clone();
   let resource_table = isolate.resource_table.clone();
-  state.check_net(&args.hostname, args.port)?;
   if let Some(path) = cert_file.clone() {
     state.check_read(Path::new(&path))?;
   }
@@ -150,8 +153,13 @@ pub fn op_connect_tls(
     domain.push_str("localhost");
   }
 
+  let check_addr = state.check_net_unresolved(&args.hostname, args.port)?;
+  let state = state.clone();
   let op = async move {
-    let addr = resolve_addr(&args.hostname, args.port)?;
+    let addr = resolve_addr_async(args.hostname.clone(), args.port).await?;
+    if check_addr {
+      state.check_net_addr(&args.hostname, &addr)?;
+    }
     let tcp_stream = TcpStream::connect(&addr).await?;
     let local_addr = tcp_stream.local_addr()?;
     let remote_addr = tcp_stream.peer_addr()?;
@@ -319,7 +327,11 @@ fn op_listen_tls(
   let cert_file = args.cert_file;
   let key_file = args.key_file;
 
-  state.check_net(&args.hostname, args.port)?;
+  let check_addr = state.check_net_unresolved(&args.hostname, args.port)?;
+  let addr = resolve_addr(&args.hostname, args.port)?;
+  if check_addr {
+    state.check_net_addr(&args.hostname, &addr)?;
+  }
   state.check_read(Path::new(&cert_file))?;
   state.check_read(Path::new(&key_file))?;
 
@@ -328,7 +340,6 @@ fn op_listen_tls(
     .set_single_cert(load_certs(&cert_file)?, load_keys(&key_file)?.remove(0))
     .expect("invalid key or certificate");
   let tls_acceptor = TlsAcceptor::from(Arc::new(config));
-  let addr = resolve_addr(&args.hostname, args.port)?;
   let std_listener = std::net::TcpListener::bind(&addr)?;
   let listener = TcpListener::from_std(std_listener)?;
   let local_addr = listener.local_addr()?;
@@ -357,6 +368,24 @@ struct AcceptTlsArgs {
   rid: i32,
 }
 
+/// Stops tracking the accept task if the accept op is dropped before it
+/// completes, e.g. because it was cancelled, so the listener can accept again.
+struct AcceptTaskGuard {
+  resource_table: Rc<RefCell<ResourceTable>>,
+  rid: u32,
+}
+
+impl Drop for AcceptTaskGuard {
+  fn drop(&mut self) {
+    let mut resource_table = self.resource_table.borrow_mut();
+    if let Some(listener_resource) =
+      resource_table.get_mut::<TlsListenerResource>(self.rid)
+    {
+      listener_resource.untrack_task();
+    }
+  }
+}
+
 fn op_accept_tls(
   isolate: &mut CoreIsolate,
   _state: &State,
@@ -390,7 +419,13 @@ fn op_accept_tls(
         }
       }
     });
-    let (tcp_stream, _socket_addr) = accept_fut.await?;
+    let (tcp_stream, _socket_addr) = {
+      let _guard = AcceptTaskGuard {
+        resource_table: resource_table.clone(),
+        rid,
+      };
+      accept_fut.await?
+    };
     let local_addr = tcp_stream.local_addr()?;
     let remote_addr = tcp_stream.peer_addr()?;
     let tls_acceptor = {
@@ -51,7 +51,7 @@ pub fn op_set_raw(
   args: Value,
   _zero_copy: Option<ZeroCopyBuf>,
 ) -> Result<JsonOp, OpError> {
-  state.check_unstable("Deno.setRaw");
+  state.check_unstable("Deno.setRaw")?;
   let args: SetRawArgs = serde_json::from_value(args)?;
   let rid = args.rid;
   let is_raw = args.mode;
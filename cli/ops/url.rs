@@ -0,0 +1,140 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::op_error::OpError;
+use crate::state::State;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+use url::form_urlencoded;
+use url::quirks;
+use url::Url;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op("op_url_parse", s.stateful_json_op(op_url_parse));
+  i.register_op(
+    "op_url_parse_search_params",
+    s.stateful_json_op(op_url_parse_search_params),
+  );
+  i.register_op(
+    "op_url_stringify_search_params",
+    s.stateful_json_op(op_url_stringify_search_params),
+  );
+}
+
+/// The URL to parse, and optionally a component to set afterwards with the
+/// corresponding `URL` setter.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UrlParseArgs {
+  href: String,
+  base_href: Option<String>,
+  hash: Option<String>,
+  host: Option<String>,
+  hostname: Option<String>,
+  password: Option<String>,
+  pathname: Option<String>,
+  port: Option<String>,
+  protocol: Option<String>,
+  search: Option<String>,
+  username: Option<String>,
+}
+
+/// Parses a URL the way the WHATWG URL standard says, using the same parser
+/// that resolves module specifiers, and returns its components as the `URL`
+/// getters serialize them.
+fn op_url_parse(
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: UrlParseArgs = serde_json::from_value(args)?;
+  let base_url = match &args.base_href {
+    Some(base_href) => Some(
+      Url::parse(base_href)
+        .map_err(|_| OpError::type_error("Invalid base URL.".to_string()))?,
+    ),
+    None => None,
+  };
+  let mut url = Url::options()
+    .base_url(base_url.as_ref())
+    .parse(&args.href)
+    .map_err(|_| OpError::type_error("Invalid URL.".to_string()))?;
+
+  // Setters ignore values they can't apply, as the standard says.
+  if let Some(hash) = &args.hash {
+    quirks::set_hash(&mut url, hash);
+  }
+  if let Some(host) = &args.host {
+    let _ = quirks::set_host(&mut url, host);
+  }
+  if let Some(hostname) = &args.hostname {
+    let _ = quirks::set_hostname(&mut url, hostname);
+  }
+  if let Some(password) = &args.password {
+    let _ = quirks::set_password(&mut url, password);
+  }
+  if let Some(pathname) = &args.pathname {
+    quirks::set_pathname(&mut url, pathname);
+  }
+  if let Some(port) = &args.port {
+    let _ = quirks::set_port(&mut url, port);
+  }
+  if let Some(protocol) = &args.protocol {
+    let _ = quirks::set_protocol(&mut url, protocol);
+  }
+  if let Some(search) = &args.search {
+    quirks::set_search(&mut url, search);
+  }
+  if let Some(username) = &args.username {
+    let _ = quirks::set_username(&mut url, username);
+  }
+
+  Ok(JsonOp::Sync(json!({
+    "href": quirks::href(&url),
+    "hash": quirks::hash(&url),
+    "host": quirks::host(&url),
+    "hostname": quirks::hostname(&url),
+    "origin": quirks::origin(&url),
+    "password": quirks::password(&url),
+    "pathname": quirks::pathname(&url),
+    "port": quirks::port(&url),
+    "protocol": quirks::protocol(&url),
+    "search": quirks::search(&url),
+    "username": quirks::username(&url),
+  })))
+}
+
+#[derive(Deserialize)]
+struct ParseSearchParamsArgs {
+  query: String,
+}
+
+/// Parses `application/x-www-form-urlencoded` data into name-value pairs.
+fn op_url_parse_search_params(
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ParseSearchParamsArgs = serde_json::from_value(args)?;
+  let pairs: Vec<(String, String)> =
+    form_urlencoded::parse(args.query.as_bytes())
+      .into_owned()
+      .collect();
+  Ok(JsonOp::Sync(json!(pairs)))
+}
+
+#[derive(Deserialize)]
+struct StringifySearchParamsArgs {
+  pairs: Vec<(String, String)>,
+}
+
+fn op_url_stringify_search_params(
+  _state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: StringifySearchParamsArgs = serde_json::from_value(args)?;
+  let query = form_urlencoded::Serializer::new(String::new())
+    .extend_pairs(args.pairs)
+    .finish();
+  Ok(JsonOp::Sync(json!(query)))
+}
@@ -0,0 +1,143 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::op_error::OpError;
+use crate::state::State;
+use crate::web_storage;
+use crate::web_storage::StorageArea;
+use deno_core::CoreIsolate;
+use deno_core::ZeroCopyBuf;
+
+pub fn init(i: &mut CoreIsolate, s: &State) {
+  i.register_op(
+    "op_webstorage_length",
+    s.stateful_json_op(op_webstorage_length),
+  );
+  i.register_op("op_webstorage_key", s.stateful_json_op(op_webstorage_key));
+  i.register_op("op_webstorage_keys", s.stateful_json_op(op_webstorage_keys));
+  i.register_op("op_webstorage_get", s.stateful_json_op(op_webstorage_get));
+  i.register_op("op_webstorage_set", s.stateful_json_op(op_webstorage_set));
+  i.register_op(
+    "op_webstorage_remove",
+    s.stateful_json_op(op_webstorage_remove),
+  );
+  i.register_op(
+    "op_webstorage_clear",
+    s.stateful_json_op(op_webstorage_clear),
+  );
+}
+
+/// Calls `f` with the storage area of the isolate's origin: its
+/// `localStorage` area if `persistent`, else its `sessionStorage` area.
+fn with_area<T>(
+  state: &State,
+  persistent: bool,
+  f: impl FnOnce(&mut StorageArea) -> Result<T, OpError>,
+) -> Result<T, OpError> {
+  let state = state.borrow();
+  let global_state = &state.global_state;
+  let origin = web_storage::origin(
+    global_state.flags.location.as_ref(),
+    &state.main_module,
+  );
+  global_state.web_storage.with_area(&origin, persistent, f)
+}
+
+#[derive(Deserialize)]
+struct StorageArgs {
+  persistent: bool,
+}
+
+fn op_webstorage_length(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: StorageArgs = serde_json::from_value(args)?;
+  let length = with_area(state, args.persistent, |area| Ok(area.len()))?;
+  Ok(JsonOp::Sync(json!(length)))
+}
+
+#[derive(Deserialize)]
+struct KeyArgs {
+  persistent: bool,
+  index: usize,
+}
+
+fn op_webstorage_key(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: KeyArgs = serde_json::from_value(args)?;
+  let key = with_area(state, args.persistent, |area| {
+    Ok(area.key(args.index).map(String::from))
+  })?;
+  Ok(JsonOp::Sync(json!(key)))
+}
+
+fn op_webstorage_keys(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: StorageArgs = serde_json::from_value(args)?;
+  let keys = with_area(state, args.persistent, |area| Ok(area.keys()))?;
+  Ok(JsonOp::Sync(json!(keys)))
+}
+
+#[derive(Deserialize)]
+struct ItemArgs {
+  persistent: bool,
+  key: String,
+}
+
+fn op_webstorage_get(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ItemArgs = serde_json::from_value(args)?;
+  let value = with_area(state, args.persistent, |area| {
+    Ok(area.get(&args.key).map(String::from))
+  })?;
+  Ok(JsonOp::Sync(json!(value)))
+}
+
+#[derive(Deserialize)]
+struct SetArgs {
+  persistent: bool,
+  key: String,
+  value: String,
+}
+
+fn op_webstorage_set(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: SetArgs = serde_json::from_value(args)?;
+  with_area(state, args.persistent, |area| {
+    area.set(args.key, args.value)
+  })?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_webstorage_remove(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: ItemArgs = serde_json::from_value(args)?;
+  with_area(state, args.persistent, |area| area.remove(&args.key))?;
+  Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_webstorage_clear(
+  state: &State,
+  args: Value,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: StorageArgs = serde_json::from_value(args)?;
+  with_area(state, args.persistent, |area| area.clear())?;
+  Ok(JsonOp::Sync(json!({})))
+}
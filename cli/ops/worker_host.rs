@@ -1,13 +1,16 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::broadcast_channel::Subscription;
 use crate::fmt_errors::JSError;
 use crate::global_state::GlobalState;
 use crate::op_error::OpError;
 use crate::ops::io::get_stdio;
 use crate::permissions::Permissions;
+use crate::permissions::PermissionsSubset;
 use crate::startup_data;
 use crate::state::State;
 use crate::tokio_util::create_basic_runtime;
+use crate::tsc::TargetLib;
 use crate::web_worker::WebWorker;
 use crate::web_worker::WebWorkerHandle;
 use crate::worker::WorkerEvent;
@@ -15,8 +18,11 @@ use deno_core::CoreIsolate;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
 use deno_core::ZeroCopyBuf;
+use futures::future::poll_fn;
 use futures::future::FutureExt;
+use futures::stream::StreamExt;
 use std::convert::From;
+use std::sync::atomic::Ordering;
 use std::thread::JoinHandle;
 
 pub fn init(i: &mut CoreIsolate, s: &State) {
@@ -33,6 +39,24 @@ pub fn init(i: &mut CoreIsolate, s: &State) {
     "op_host_get_message",
     s.stateful_json_op(op_host_get_message),
   );
+  i.register_op(
+    "op_host_get_workers",
+    s.stateful_json_op(op_host_get_workers),
+  );
+  i.register_op(
+    "op_broadcast_subscribe",
+    s.stateful_json_op2(op_broadcast_subscribe),
+  );
+  i.register_op("op_broadcast_post", s.stateful_json_op2(op_broadcast_post));
+  i.register_op("op_broadcast_recv", s.stateful_json_op2(op_broadcast_recv));
+}
+
+/// Corresponds to the `type` option of `WorkerOptions`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum WorkerType {
+  Classic,
+  Module,
 }
 
 fn create_web_worker(
@@ -80,6 +104,7 @@ fn run_worker_thread(
   permissions: Permissions,
   specifier: ModuleSpecifier,
   has_deno_namespace: bool,
+  worker_type: WorkerType,
   maybe_source_code: Option<String>,
 ) -> Result<(JoinHandle<()>, WebWorkerHandle), ErrBox> {
   let (handle_sender, handle_receiver) =
@@ -126,18 +151,34 @@ fn run_worker_thread(
 
     // TODO: run with using select with terminate
 
-    // Execute provided source code immediately
-    let result = if let Some(source_code) = maybe_source_code {
-      worker.execute(&source_code)
-    } else {
-      // TODO(bartlomieju): add "type": "classic", ie. ability to load
-      // script instead of module
-      let load_future = worker.execute_module(&specifier).boxed_local();
-
-      rt.block_on(load_future)
+    let result = match (worker_type, maybe_source_code) {
+      // Source code was provided inline (`blob:` or `data:` URL)
+      (WorkerType::Classic, Some(source_code)) => {
+        worker.execute2(&specifier.to_string(), &source_code)
+      }
+      (WorkerType::Module, Some(source_code)) => {
+        let load_future = worker
+          .execute_module_from_code(&specifier, source_code)
+          .boxed_local();
+        rt.block_on(load_future)
+      }
+      (WorkerType::Classic, None) => {
+        let global_state = worker.state.borrow().global_state.clone();
+        let fetch_future = global_state
+          .fetch_compiled_module(specifier.clone(), None, TargetLib::Worker)
+          .boxed_local();
+        rt.block_on(fetch_future).and_then(|compiled_module| {
+          worker.execute2(&compiled_module.name, &compiled_module.code)
+        })
+      }
+      (WorkerType::Module, None) => {
+        let load_future = worker.execute_module(&specifier).boxed_local();
+        rt.block_on(load_future)
+      }
     };
 
     if let Err(e) = result {
+      worker.thread_safe_handle().set_errored();
       let mut sender = worker.internal_channels.sender.clone();
       sender
         .try_send(WorkerEvent::TerminalError(e))
@@ -166,6 +207,9 @@ struct CreateWorkerArgs {
   has_source_code: bool,
   source_code: String,
   use_deno_namespace: bool,
+  #[serde(rename = "type")]
+  worker_type: WorkerType,
+  permissions: Option<PermissionsSubset>,
 }
 
 /// Create worker as the host
@@ -184,13 +228,20 @@ fn op_create_worker(
   };
   let args_name = args.name;
   let use_deno_namespace = args.use_deno_namespace;
+  let worker_type = args.worker_type;
+  if args.permissions.is_some() {
+    state.check_unstable("Deno.test.permissions")?;
+  }
   if use_deno_namespace {
-    state.check_unstable("Worker.deno");
+    state.check_unstable("Worker.deno")?;
   }
   let parent_state = state.clone();
   let mut state = state.borrow_mut();
   let global_state = state.global_state.clone();
-  let permissions = state.permissions.clone();
+  let permissions = match &args.permissions {
+    Some(subset) => state.permissions.restrict(subset)?,
+    None => state.permissions.clone(),
+  };
   let referrer = state.main_module.to_string();
   let worker_id = state.next_worker_id;
   state.next_worker_id += 1;
@@ -207,6 +258,7 @@ fn op_create_worker(
     permissions,
     module_specifier,
     use_deno_namespace,
+    worker_type,
     maybe_source_code,
   )
   .map_err(|e| OpError::other(e.to_string()))?;
@@ -358,3 +410,112 @@ fn op_host_post_message(
     .map_err(|e| OpError::other(e.to_string()))?;
   Ok(JsonOp::Sync(json!({})))
 }
+
+/// List workers created by this isolate that weren't yet removed from
+/// the worker table
+fn op_host_get_workers(
+  state: &State,
+  _args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  state.check_unstable("Deno.workers")?;
+  let state = state.borrow();
+  let mut ids: Vec<&u32> = state.workers.keys().collect();
+  ids.sort();
+  let workers: Vec<Value> = ids
+    .into_iter()
+    .map(|id| {
+      let (_join_handle, worker_handle) = &state.workers[id];
+      let info = &worker_handle.info;
+      json!({
+        "id": id,
+        "name": info.name,
+        "specifier": info.specifier,
+        "status": worker_handle.status().as_str(),
+        "queuedMessages": info.queued_messages.load(Ordering::SeqCst),
+        "uptime": info.created_at.elapsed().as_millis() as u64,
+      })
+    })
+    .collect();
+  Ok(JsonOp::Sync(json!(workers)))
+}
+
+#[derive(Deserialize)]
+struct BroadcastSubscribeArgs {
+  name: String,
+}
+
+/// Subscribe to named `BroadcastChannel`; subscription is shared with
+/// all other isolates through `GlobalState`.
+fn op_broadcast_subscribe(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: BroadcastSubscribeArgs = serde_json::from_value(args)?;
+  let subscription = state
+    .borrow()
+    .global_state
+    .broadcast_channels
+    .subscribe(args.name);
+  let mut resource_table = isolate.resource_table.borrow_mut();
+  let rid = resource_table.add("broadcastChannel", Box::new(subscription));
+  Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+#[derive(Deserialize)]
+struct BroadcastArgs {
+  rid: u32,
+}
+
+/// Post message to all other subscribers of the channel
+fn op_broadcast_post(
+  isolate: &mut CoreIsolate,
+  state: &State,
+  args: Value,
+  data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: BroadcastArgs = serde_json::from_value(args)?;
+  let data = data.ok_or_else(|| {
+    OpError::type_error("Message data is required".to_string())
+  })?;
+  let resource_table = isolate.resource_table.borrow();
+  let subscription = resource_table
+    .get::<Subscription>(args.rid)
+    .ok_or_else(OpError::bad_resource_id)?;
+  state
+    .borrow()
+    .global_state
+    .broadcast_channels
+    .post(subscription.id, data.as_ref());
+  Ok(JsonOp::Sync(json!({})))
+}
+
+/// Get next message posted to the channel; resolves to `null` once the
+/// subscription resource is closed. Messages are JSON, so like messages to
+/// workers they're passed back as a string and deserialized in JS.
+fn op_broadcast_recv(
+  isolate: &mut CoreIsolate,
+  _state: &State,
+  args: Value,
+  _data: Option<ZeroCopyBuf>,
+) -> Result<JsonOp, OpError> {
+  let args: BroadcastArgs = serde_json::from_value(args)?;
+  let rid = args.rid;
+  let resource_table = isolate.resource_table.clone();
+  let op = poll_fn(move |cx| {
+    let mut resource_table = resource_table.borrow_mut();
+    let subscription = match resource_table.get_mut::<Subscription>(rid) {
+      Some(subscription) => subscription,
+      // Resource was closed while waiting for a message
+      None => return std::task::Poll::Ready(Ok(json!(null))),
+    };
+    subscription.receiver.poll_next_unpin(cx).map(|maybe_buf| {
+      let maybe_msg =
+        maybe_buf.map(|buf| String::from_utf8_lossy(&buf).into_owned());
+      Ok(json!(maybe_msg))
+    })
+  });
+  Ok(JsonOp::Async(op.boxed_local()))
+}
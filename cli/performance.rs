@@ -0,0 +1,187 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Time origins and User Timing entries of all isolates.
+//!
+//! The time origin of every isolate is derived from the same monotonic clock,
+//! started along with the program, so `performance.timeOrigin +
+//! performance.now()` can be compared across the main isolate and workers.
+//!
+//! Isolates record the marks and measures they create here too, in a
+//! timeline of their own, so that the main isolate can collect the entries of
+//! every worker with `Deno.performanceEntries()`.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// A `PerformanceMark` or `PerformanceMeasure`. Times are in milliseconds,
+/// relative to the time origin of the isolate that created it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+  pub name: String,
+  pub entry_type: String,
+  pub start_time: f64,
+  pub duration: f64,
+}
+
+struct Timeline {
+  name: String,
+  time_origin: f64,
+  entries: Vec<Entry>,
+}
+
+/// The timelines of all isolates; there's one instance per process, held by
+/// `GlobalState`.
+pub struct Performance {
+  start: Instant,
+  /// When `start` was, in milliseconds since the Unix epoch.
+  start_epoch_ms: f64,
+  timelines: Mutex<BTreeMap<u32, Timeline>>,
+}
+
+impl Default for Performance {
+  fn default() -> Self {
+    let since_epoch = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default();
+    Self {
+      start: Instant::now(),
+      start_epoch_ms: since_epoch.as_secs_f64() * 1000.0,
+      timelines: Mutex::new(BTreeMap::new()),
+    }
+  }
+}
+
+impl Performance {
+  /// The time origin of an isolate started at `start_time`, in milliseconds
+  /// since the Unix epoch.
+  pub fn time_origin(&self, start_time: Instant) -> f64 {
+    let offset = start_time
+      .checked_duration_since(self.start)
+      .unwrap_or_default();
+    self.start_epoch_ms + offset.as_secs_f64() * 1000.0
+  }
+
+  /// Adds the timeline of an isolate, returning its id. Timelines are never
+  /// removed.
+  pub fn add_timeline(&self, name: String, time_origin: f64) -> u32 {
+    let mut timelines = self.timelines.lock().unwrap();
+    let id = timelines.len() as u32;
+    timelines.insert(
+      id,
+      Timeline {
+        name,
+        time_origin,
+        entries: vec![],
+      },
+    );
+    id
+  }
+
+  pub fn record(&self, timeline_id: u32, entry: Entry) {
+    let mut timelines = self.timelines.lock().unwrap();
+    if let Some(timeline) = timelines.get_mut(&timeline_id) {
+      timeline.entries.push(entry);
+    }
+  }
+
+  /// Removes the entries of type `entry_type` from a timeline, or only those
+  /// named `name` if given, like `performance.clearMarks()`.
+  pub fn clear(&self, timeline_id: u32, entry_type: &str, name: Option<&str>) {
+    let mut timelines = self.timelines.lock().unwrap();
+    if let Some(timeline) = timelines.get_mut(&timeline_id) {
+      timeline.entries.retain(|entry| {
+        entry.entry_type != entry_type
+          || name.map_or(false, |name| entry.name != name)
+      });
+    }
+  }
+
+  /// The timelines that have entries, in the order the isolates started.
+  /// Entries of terminated workers are included, so they can be collected
+  /// after the workers are done.
+  pub fn to_json(&self) -> serde_json::Value {
+    let timelines = self.timelines.lock().unwrap();
+    let timelines: Vec<serde_json::Value> = timelines
+      .iter()
+      .filter(|(_, timeline)| !timeline.entries.is_empty())
+      .map(|(id, timeline)| {
+        json!({
+          "id": id,
+          "name": timeline.name,
+          "timeOrigin": timeline.time_origin,
+          "entries": timeline.entries,
+        })
+      })
+      .collect();
+    json!(timelines)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn mark(name: &str, start_time: f64) -> Entry {
+    Entry {
+      name: name.to_string(),
+      entry_type: "mark".to_string(),
+      start_time,
+      duration: 0.0,
+    }
+  }
+
+  #[test]
+  fn time_origin() {
+    let performance = Performance::default();
+    let origin = performance.time_origin(performance.start);
+    assert_eq!(origin, performance.start_epoch_ms);
+    let later = performance.start + Duration::from_millis(1500);
+    assert_eq!(performance.time_origin(later), origin + 1500.0);
+  }
+
+  #[test]
+  fn timelines() {
+    let performance = Performance::default();
+    let main = performance.add_timeline("main".to_string(), 1.0);
+    let worker = performance.add_timeline("worker-0".to_string(), 2.0);
+    let empty = performance.add_timeline("worker-1".to_string(), 3.0);
+    assert_eq!((main, worker, empty), (0, 1, 2));
+
+    performance.record(main, mark("a", 1.0));
+    performance.record(main, mark("b", 2.0));
+    performance.record(
+      main,
+      Entry {
+        name: "a".to_string(),
+        entry_type: "measure".to_string(),
+        start_time: 1.0,
+        duration: 1.0,
+      },
+    );
+    performance.record(worker, mark("c", 3.0));
+    performance.clear(main, "mark", Some("a"));
+
+    let json = performance.to_json();
+    let timelines = json.as_array().unwrap();
+    assert_eq!(timelines.len(), 2);
+    assert_eq!(timelines[0]["name"], "main");
+    assert_eq!(timelines[0]["timeOrigin"], 1.0);
+    let entries = timelines[0]["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["name"], "b");
+    assert_eq!(entries[1]["entryType"], "measure");
+    assert_eq!(timelines[1]["id"], 1);
+    assert_eq!(timelines[1]["entries"][0]["startTime"], 3.0);
+
+    performance.clear(main, "mark", None);
+    performance.clear(main, "measure", None);
+    assert_eq!(performance.to_json().as_array().unwrap().len(), 1);
+  }
+}
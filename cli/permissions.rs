@@ -1,11 +1,15 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::colors;
 use crate::flags::Flags;
+use crate::fs::canonicalize_path_lenient;
+use crate::fs::resolve_from_cwd;
 use crate::op_error::OpError;
+use serde::Deserialize;
 use std::collections::HashSet;
 use std::fmt;
 #[cfg(not(test))]
 use std::io;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 #[cfg(test)]
 use std::sync::atomic::AtomicBool;
@@ -108,9 +112,31 @@ pub struct Permissions {
   pub allow_env: PermissionState,
   pub allow_run: PermissionState,
   pub allow_plugin: PermissionState,
+  pub allow_ffi: PermissionState,
   pub allow_hrtime: PermissionState,
 }
 
+/// A subset of the current permissions requested for a worker, as given by
+/// the `permissions` option of `Deno.test()`. Omitted fields are inherited.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct PermissionsSubset {
+  pub read: Option<PermissionSubsetArg>,
+  pub write: Option<PermissionSubsetArg>,
+  pub net: Option<PermissionSubsetArg>,
+  pub env: Option<bool>,
+  pub run: Option<bool>,
+  pub plugin: Option<bool>,
+  pub ffi: Option<bool>,
+  pub hrtime: Option<bool>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum PermissionSubsetArg {
+  All(bool),
+  Whitelist(Vec<String>),
+}
+
 impl Permissions {
   pub fn from_flags(flags: &Flags) -> Self {
     // assert each whitelist path is absolute, since the cwd may change.
@@ -122,14 +148,15 @@ impl Permissions {
     }
     Self {
       allow_read: PermissionState::from(flags.allow_read),
-      read_whitelist: flags.read_whitelist.iter().cloned().collect(),
+      read_whitelist: canonicalize_white_list(&flags.read_whitelist),
       allow_write: PermissionState::from(flags.allow_write),
-      write_whitelist: flags.write_whitelist.iter().cloned().collect(),
+      write_whitelist: canonicalize_white_list(&flags.write_whitelist),
       allow_net: PermissionState::from(flags.allow_net),
       net_whitelist: flags.net_whitelist.iter().cloned().collect(),
       allow_env: PermissionState::from(flags.allow_env),
       allow_run: PermissionState::from(flags.allow_run),
       allow_plugin: PermissionState::from(flags.allow_plugin),
+      allow_ffi: PermissionState::from(flags.allow_ffi),
       allow_hrtime: PermissionState::from(flags.allow_hrtime),
     }
   }
@@ -197,6 +224,59 @@ impl Permissions {
     )
   }
 
+  /// Checks a connection to `hostname` once it has been resolved to `addr`,
+  /// so that CIDR and `localhost` entries apply to the actual target.
+  pub fn check_net_addr(
+    &self,
+    hostname: &str,
+    addr: &SocketAddr,
+  ) -> Result<(), OpError> {
+    if check_addr_whitelist(addr, &self.net_whitelist) {
+      return Ok(());
+    }
+    self.check_net(hostname, addr.port())
+  }
+
+  /// Checks a connection to `hostname` before it is resolved. Returns `true`
+  /// if the name itself isn't whitelisted but an address range could still
+  /// allow the address it resolves to, which the caller then checks with
+  /// `check_net_addr`. Anything else fails here, before any DNS lookup.
+  pub fn check_net_unresolved(
+    &self,
+    hostname: &str,
+    port: u16,
+  ) -> Result<bool, OpError> {
+    if let Ok(ip) = hostname.parse::<IpAddr>() {
+      return self
+        .check_net_addr(hostname, &SocketAddr::new(ip, port))
+        .map(|()| false);
+    }
+    match self.check_net(hostname, port) {
+      Ok(()) => Ok(false),
+      Err(_) if self.has_net_range(port) => Ok(true),
+      Err(err) => Err(err),
+    }
+  }
+
+  /// Whether the whitelist has an entry for `port` that covers addresses
+  /// rather than names: a CIDR range, or `localhost`, which covers every
+  /// loopback address.
+  pub fn has_net_range(&self, port: u16) -> bool {
+    let localhost = format!("localhost:{}", port);
+    self.net_whitelist.iter().any(|entry| {
+      if entry == "localhost" || *entry == localhost {
+        return true;
+      }
+      match split_net_pattern(entry) {
+        Some((pattern, entry_port)) => {
+          parse_cidr(pattern).is_some()
+            && entry_port.map_or(true, |entry_port| entry_port == port)
+        }
+        None => false,
+      }
+    })
+  }
+
   pub fn check_net_url(&self, url: &url::Url) -> Result<(), OpError> {
     let host = url
       .host_str()
@@ -219,6 +299,137 @@ impl Permissions {
     )
   }
 
+  pub fn check_ffi(&self, path: Option<&Path>) -> Result<(), OpError> {
+    let msg = match path {
+      Some(path) => {
+        format!("access to open a dynamic library: {}", path.display())
+      }
+      None => "access to foreign functions and pointers".to_string(),
+    };
+    self.allow_ffi.check(&msg, "--allow-ffi")
+  }
+
+  /// Returns these permissions narrowed down to `subset`. Permissions that
+  /// are turned off are denied rather than reset to prompt, so they can't be
+  /// requested back. Fails if `subset` asks for anything that isn't granted
+  /// here.
+  pub fn restrict(
+    &self,
+    subset: &PermissionsSubset,
+  ) -> Result<Permissions, OpError> {
+    let mut permissions = self.clone();
+
+    match &subset.read {
+      Some(PermissionSubsetArg::All(allow)) => {
+        permissions.allow_read =
+          restrict_state(self.allow_read, *allow, "read")?;
+        permissions.read_whitelist.clear();
+      }
+      Some(PermissionSubsetArg::Whitelist(paths)) => {
+        permissions.allow_read = PermissionState::Deny;
+        permissions.read_whitelist.clear();
+        for path in paths {
+          let path = resolve_from_cwd(Path::new(path))?;
+          if !self.get_state_read(&Some(&path)).is_allow() {
+            return Err(escalation_error(&format!(
+              "read access to \"{}\"",
+              path.display()
+            )));
+          }
+          permissions
+            .read_whitelist
+            .insert(canonicalize_path_lenient(&path));
+        }
+      }
+      None => {}
+    }
+
+    match &subset.write {
+      Some(PermissionSubsetArg::All(allow)) => {
+        permissions.allow_write =
+          restrict_state(self.allow_write, *allow, "write")?;
+        permissions.write_whitelist.clear();
+      }
+      Some(PermissionSubsetArg::Whitelist(paths)) => {
+        permissions.allow_write = PermissionState::Deny;
+        permissions.write_whitelist.clear();
+        for path in paths {
+          let path = resolve_from_cwd(Path::new(path))?;
+          if !self.get_state_write(&Some(&path)).is_allow() {
+            return Err(escalation_error(&format!(
+              "write access to \"{}\"",
+              path.display()
+            )));
+          }
+          permissions
+            .write_whitelist
+            .insert(canonicalize_path_lenient(&path));
+        }
+      }
+      None => {}
+    }
+
+    match &subset.net {
+      Some(PermissionSubsetArg::All(allow)) => {
+        permissions.allow_net = restrict_state(self.allow_net, *allow, "net")?;
+        permissions.net_whitelist.clear();
+      }
+      Some(PermissionSubsetArg::Whitelist(entries)) => {
+        permissions.allow_net = PermissionState::Deny;
+        permissions.net_whitelist.clear();
+        for entry in entries {
+          if !self.is_net_entry_granted(entry) {
+            return Err(escalation_error(&format!(
+              "network access to \"{}\"",
+              entry
+            )));
+          }
+          permissions.net_whitelist.insert(entry.clone());
+        }
+      }
+      None => {}
+    }
+
+    if let Some(allow) = subset.env {
+      permissions.allow_env = restrict_state(self.allow_env, allow, "env")?;
+    }
+    if let Some(allow) = subset.run {
+      permissions.allow_run = restrict_state(self.allow_run, allow, "run")?;
+    }
+    if let Some(allow) = subset.plugin {
+      permissions.allow_plugin =
+        restrict_state(self.allow_plugin, allow, "plugin")?;
+    }
+    if let Some(allow) = subset.ffi {
+      permissions.allow_ffi = restrict_state(self.allow_ffi, allow, "ffi")?;
+    }
+    if let Some(allow) = subset.hrtime {
+      permissions.allow_hrtime =
+        restrict_state(self.allow_hrtime, allow, "hrtime")?;
+    }
+
+    Ok(permissions)
+  }
+
+  /// Whether every address covered by a `--allow-net` style entry is also
+  /// covered by these permissions.
+  fn is_net_entry_granted(&self, entry: &str) -> bool {
+    if self.allow_net.is_allow() || self.net_whitelist.contains(entry) {
+      return true;
+    }
+    // Patterns can only be granted by the same pattern or by full access.
+    if entry.contains('/') || entry.contains('*') {
+      return false;
+    }
+    match Url::parse(&format!("http://{}", entry)) {
+      Ok(url) => match url.host_str() {
+        Some(host) => self.get_state_net(host, url.port()).is_allow(),
+        None => false,
+      },
+      Err(_) => false,
+    }
+  }
+
   pub fn request_run(&mut self) -> PermissionState {
     self
       .allow_run
@@ -278,6 +489,12 @@ impl Permissions {
     self.allow_plugin.request("Deno requests to open plugins")
   }
 
+  pub fn request_ffi(&mut self) -> PermissionState {
+    self
+      .allow_ffi
+      .request("Deno requests to call foreign functions")
+  }
+
   pub fn get_permission_state(
     &self,
     name: &str,
@@ -291,6 +508,7 @@ impl Permissions {
       "net" => self.get_state_net_url(url),
       "env" => Ok(self.allow_env),
       "plugin" => Ok(self.allow_plugin),
+      "ffi" => Ok(self.allow_ffi),
       "hrtime" => Ok(self.allow_hrtime),
       n => Err(OpError::other(format!("No such permission name: {}", n))),
     }
@@ -359,8 +577,38 @@ fn log_perm_access(message: &str) {
   );
 }
 
+fn restrict_state(
+  state: PermissionState,
+  allow: bool,
+  name: &str,
+) -> Result<PermissionState, OpError> {
+  if !allow {
+    Ok(PermissionState::Deny)
+  } else if state.is_allow() {
+    Ok(PermissionState::Allow)
+  } else {
+    Err(escalation_error(&format!("\"{}\" permission", name)))
+  }
+}
+
+fn escalation_error(what: &str) -> OpError {
+  OpError::permission_denied(format!(
+    "Can't grant {} to a worker, it isn't granted to its parent",
+    what
+  ))
+}
+
+fn canonicalize_white_list(paths: &[PathBuf]) -> HashSet<PathBuf> {
+  paths
+    .iter()
+    .map(|path| canonicalize_path_lenient(path))
+    .collect()
+}
+
+/// Paths are compared after resolving symlinks and `..`, so a whitelisted
+/// directory can't be escaped through a link inside it.
 fn check_path_white_list(path: &Path, white_list: &HashSet<PathBuf>) -> bool {
-  let mut path_buf = PathBuf::from(path);
+  let mut path_buf = canonicalize_path_lenient(path);
   loop {
     if white_list.contains(&path_buf) {
       return true;
@@ -380,6 +628,108 @@ fn check_host_and_port_whitelist(
   whitelist.contains(host)
     || (port.is_some()
       && whitelist.contains(&format!("{}:{}", host, port.unwrap())))
+    || whitelist
+      .iter()
+      .any(|entry| check_net_pattern(entry, host, port))
+}
+
+/// Checks the address a hostname resolved to. Besides plain and CIDR entries,
+/// a `localhost` entry covers every loopback address.
+fn check_addr_whitelist(
+  addr: &SocketAddr,
+  whitelist: &HashSet<String>,
+) -> bool {
+  let ip = addr.ip().to_string();
+  let port = Some(addr.port());
+  check_host_and_port_whitelist(&ip, port, whitelist)
+    || (addr.ip().is_loopback()
+      && check_host_and_port_whitelist("localhost", port, whitelist))
+}
+
+/// Matches `--allow-net` entries that aren't plain hostnames: wildcard
+/// subdomains (`*.example.com`) and CIDR ranges (`10.0.0.0/8`), both with an
+/// optional port (`*.example.com:443`, `10.0.0.0/8:5432`).
+fn check_net_pattern(entry: &str, host: &str, port: Option<u16>) -> bool {
+  let (pattern, entry_port) = match split_net_pattern(entry) {
+    Some(parts) => parts,
+    None => return false,
+  };
+  if entry_port.is_some() && entry_port != port {
+    return false;
+  }
+  if pattern.starts_with("*.") {
+    let suffix = &pattern[1..];
+    return host.len() > suffix.len()
+      && host
+        .to_ascii_lowercase()
+        .ends_with(&suffix.to_ascii_lowercase());
+  }
+  let host = host.trim_start_matches('[').trim_end_matches(']');
+  match (host.parse::<IpAddr>(), parse_cidr(pattern)) {
+    (Ok(ip), Some((network, prefix))) => ip_in_network(ip, network, prefix),
+    _ => false,
+  }
+}
+
+/// Splits a wildcard or CIDR entry into the pattern and its optional port.
+/// Returns `None` for anything else, including malformed ports.
+fn split_net_pattern(entry: &str) -> Option<(&str, Option<u16>)> {
+  let (pattern, port) = if entry.starts_with("*.") {
+    match entry.rfind(':') {
+      Some(i) => (&entry[..i], Some(&entry[i + 1..])),
+      None => (entry, None),
+    }
+  } else if let Some(slash) = entry.find('/') {
+    // The port, if any, follows the prefix length: "fd00::/8:443".
+    match entry[slash..].find(':') {
+      Some(i) => (&entry[..slash + i], Some(&entry[slash + i + 1..])),
+      None => (entry, None),
+    }
+  } else {
+    return None;
+  };
+  match port {
+    Some(port) => port.parse().ok().map(|port| (pattern, Some(port))),
+    None => Some((pattern, None)),
+  }
+}
+
+fn parse_cidr(pattern: &str) -> Option<(IpAddr, u8)> {
+  let slash = pattern.find('/')?;
+  let network: IpAddr = pattern[..slash].parse().ok()?;
+  let prefix: u8 = pattern[slash + 1..].parse().ok()?;
+  let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+  if prefix > max_prefix {
+    return None;
+  }
+  Some((network, prefix))
+}
+
+fn ip_in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+  match (ip, network) {
+    (IpAddr::V4(ip), IpAddr::V4(network)) => {
+      let mask = u32::max_value()
+        .checked_shl(32 - prefix as u32)
+        .unwrap_or(0);
+      u32::from(ip) & mask == u32::from(network) & mask
+    }
+    (IpAddr::V6(ip), IpAddr::V6(network)) => {
+      let mask = u128::max_value()
+        .checked_shl(128 - prefix as u32)
+        .unwrap_or(0);
+      u128::from(ip) & mask == u128::from(network) & mask
+    }
+    // IPv4-mapped addresses (::ffff:a.b.c.d), as reported by dual-stack
+    // sockets.
+    (IpAddr::V6(ip), IpAddr::V4(_)) => match ip.segments() {
+      [0, 0, 0, 0, 0, 0xffff, _, _] => {
+        let ip = IpAddr::V4(ip.to_ipv4().unwrap());
+        ip_in_network(ip, network, prefix)
+      }
+      _ => false,
+    },
+    (IpAddr::V4(_), IpAddr::V6(_)) => false,
+  }
 }
 
 #[cfg(test)]
@@ -444,6 +794,34 @@ mod tests {
     assert!(perms.check_write(Path::new("/a/b")).is_err());
   }
 
+  #[cfg(unix)]
+  #[test]
+  fn check_paths_through_symlinks() {
+    use std::os::unix::fs::symlink;
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let dir = temp_dir.path().canonicalize().unwrap();
+    let data = dir.join("data");
+    std::fs::create_dir(&data).unwrap();
+    std::fs::create_dir(dir.join("secret")).unwrap();
+    symlink(dir.join("secret"), data.join("link")).unwrap();
+    symlink(dir.join("secret/new.txt"), data.join("dangling")).unwrap();
+    symlink(data.join("inner"), dir.join("outer")).unwrap();
+
+    let perms = Permissions::from_flags(&Flags {
+      read_whitelist: vec![data.clone()],
+      write_whitelist: vec![data.clone()],
+      ..Default::default()
+    });
+
+    assert!(perms.check_read(&data.join("file.txt")).is_ok());
+    assert!(perms.check_write(&data.join("new/file.txt")).is_ok());
+    assert!(perms.check_read(&dir.join("outer/file.txt")).is_ok());
+    assert!(perms.check_read(&data.join("link/passwd")).is_err());
+    assert!(perms.check_write(&data.join("link/new.txt")).is_err());
+    assert!(perms.check_write(&data.join("dangling")).is_err());
+    assert!(perms.check_read(&data.join("../secret")).is_err());
+  }
+
   #[test]
   fn test_check_net() {
     let perms = Permissions::from_flags(&Flags {
@@ -527,6 +905,182 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_check_net_patterns() {
+    let perms = Permissions::from_flags(&Flags {
+      net_whitelist: svec![
+        "*.deno.land",
+        "*.example.com:443",
+        "10.0.0.0/8",
+        "192.168.1.0/24:5432",
+        "fd00::/8"
+      ],
+      ..Default::default()
+    });
+
+    let domain_tests = vec![
+      ("deno.land", 0, false),
+      ("doc.deno.land", 0, true),
+      ("a.b.deno.land", 3000, true),
+      ("DOC.DENO.LAND", 0, true),
+      ("notdeno.land", 0, false),
+      ("www.example.com", 443, true),
+      ("www.example.com", 80, false),
+      ("example.com", 443, false),
+      ("10.0.0.1", 0, true),
+      ("10.255.255.255", 8080, true),
+      ("11.0.0.1", 0, false),
+      ("192.168.1.20", 5432, true),
+      ("192.168.1.20", 5433, false),
+      ("192.168.2.20", 5432, false),
+      ("fd12::1", 80, true),
+      ("fe80::1", 80, false),
+    ];
+
+    for (host, port, is_ok) in domain_tests.iter() {
+      assert_eq!(*is_ok, perms.check_net(host, *port).is_ok(), "{}", host);
+    }
+
+    let url_tests = vec![
+      ("https://doc.deno.land/", true),
+      ("https://deno.land/", false),
+      ("http://10.1.2.3:8000/", true),
+      ("http://[fd00::1]/", true),
+      ("http://[fe80::1]/", false),
+    ];
+
+    for (url_str, is_ok) in url_tests.iter() {
+      let u = url::Url::parse(url_str).unwrap();
+      assert_eq!(*is_ok, perms.check_net_url(&u).is_ok(), "{}", url_str);
+    }
+  }
+
+  #[test]
+  fn test_check_net_addr() {
+    let perms = Permissions::from_flags(&Flags {
+      net_whitelist: svec!["localhost:8000", "10.0.0.0/8", "deno.land"],
+      ..Default::default()
+    });
+
+    let addr_tests = vec![
+      // The resolved address decides for names that aren't whitelisted.
+      ("internal.corp", "10.1.2.3:80", true),
+      ("internal.corp", "11.1.2.3:80", false),
+      ("internal.corp", "[::ffff:10.1.2.3]:80", true),
+      // "localhost" covers every loopback address.
+      ("localhost", "127.0.0.1:8000", true),
+      ("127.0.0.2", "127.0.0.2:8000", true),
+      ("::1", "[::1]:8000", true),
+      ("localhost", "127.0.0.1:8001", false),
+      // Whitelisted names are allowed wherever they resolve to.
+      ("deno.land", "104.18.0.1:443", true),
+    ];
+
+    for (host, addr, is_ok) in addr_tests.iter() {
+      let addr: SocketAddr = addr.parse().unwrap();
+      assert_eq!(
+        *is_ok,
+        perms.check_net_addr(host, &addr).is_ok(),
+        "{}",
+        host
+      );
+    }
+  }
+
+  #[test]
+  fn test_check_net_unresolved() {
+    let perms = Permissions::from_flags(&Flags {
+      net_whitelist: svec!["deno.land", "10.0.0.0/8:5432"],
+      ..Default::default()
+    });
+    assert!(!perms.check_net_unresolved("deno.land", 443).unwrap());
+    assert!(perms.check_net_unresolved("db.corp", 5432).unwrap());
+    assert!(perms.check_net_unresolved("db.corp", 80).is_err());
+    assert!(!perms.check_net_unresolved("10.1.2.3", 5432).unwrap());
+    assert!(perms.check_net_unresolved("10.1.2.3", 80).is_err());
+
+    let perms = Permissions::from_flags(&Flags {
+      net_whitelist: svec!["deno.land"],
+      ..Default::default()
+    });
+    assert!(perms.check_net_unresolved("example.com", 443).is_err());
+
+    let perms = Permissions::from_flags(&Flags {
+      net_whitelist: svec!["localhost:8000"],
+      ..Default::default()
+    });
+    assert!(perms.check_net_unresolved("dev.test", 8000).unwrap());
+    assert!(!perms.check_net_unresolved("127.0.0.2", 8000).unwrap());
+    assert!(perms.check_net_unresolved("dev.test", 8001).is_err());
+    assert!(Permissions::default()
+      .check_net_unresolved("example.com", 443)
+      .is_err());
+  }
+
+  #[test]
+  fn test_restrict() {
+    let perms = Permissions::from_flags(&Flags {
+      read_whitelist: vec![PathBuf::from("/a/specific/dir")],
+      allow_write: true,
+      net_whitelist: svec!["localhost:8000", "10.0.0.0/8"],
+      allow_env: true,
+      ..Default::default()
+    });
+
+    let restricted = perms
+      .restrict(&PermissionsSubset {
+        read: Some(PermissionSubsetArg::Whitelist(svec![
+          "/a/specific/dir/fixtures"
+        ])),
+        write: Some(PermissionSubsetArg::All(false)),
+        net: Some(PermissionSubsetArg::Whitelist(svec![
+          "localhost:8000",
+          "10.0.0.0/8"
+        ])),
+        ..Default::default()
+      })
+      .unwrap();
+    assert!(restricted
+      .check_read(Path::new("/a/specific/dir/fixtures/file.txt"))
+      .is_ok());
+    assert!(restricted
+      .check_read(Path::new("/a/specific/dir/other.txt"))
+      .is_err());
+    assert!(restricted.check_write(Path::new("/tmp/file.txt")).is_err());
+    assert_eq!(restricted.allow_write, PermissionState::Deny);
+    assert!(restricted.check_net("localhost", 8000).is_ok());
+    assert!(restricted.check_net("localhost", 8001).is_err());
+    // Omitted permissions are inherited.
+    assert!(restricted.check_env().is_ok());
+    assert!(restricted.check_run().is_err());
+
+    let escalations = vec![
+      PermissionsSubset {
+        read: Some(PermissionSubsetArg::All(true)),
+        ..Default::default()
+      },
+      PermissionsSubset {
+        read: Some(PermissionSubsetArg::Whitelist(svec!["/a/specific"])),
+        ..Default::default()
+      },
+      PermissionsSubset {
+        net: Some(PermissionSubsetArg::Whitelist(svec!["localhost"])),
+        ..Default::default()
+      },
+      PermissionsSubset {
+        net: Some(PermissionSubsetArg::Whitelist(svec!["11.0.0.0/8"])),
+        ..Default::default()
+      },
+      PermissionsSubset {
+        run: Some(true),
+        ..Default::default()
+      },
+    ];
+    for subset in escalations.iter() {
+      assert!(perms.restrict(subset).is_err(), "{:?}", subset);
+    }
+  }
+
   #[test]
   fn test_permissions_request_run() {
     let guard = PERMISSION_PROMPT_GUARD.lock().unwrap();
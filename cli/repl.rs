@@ -1,20 +1,174 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::colors;
 use crate::deno_dir::DenoDir;
 use crate::op_error::OpError;
+use crate::swc_ecma_parser::token::Token;
+use crate::swc_ecma_parser::token::Word;
+use crate::swc_util::AstParser;
 use deno_core::ErrBox;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::Config;
 use rustyline::Editor;
+use std::borrow::Cow;
+use std::ffi::OsString;
 use std::fs;
+use std::ops::Range;
 use std::path::PathBuf;
 
+/// Colorizes input as the user types it, using the swc lexer, and
+/// highlights the bracket matching the one next to the cursor.
+struct ReplHelper;
+
+impl Completer for ReplHelper {
+  type Candidate = String;
+}
+
+impl Hinter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl rustyline::Helper for ReplHelper {}
+
+impl Highlighter for ReplHelper {
+  fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+    Cow::Owned(highlight(line, pos))
+  }
+
+  fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+    // Bracket matching depends on cursor position, so redraw on every
+    // change.
+    true
+  }
+}
+
+fn is_open_bracket(token: &Token) -> bool {
+  match token {
+    Token::LParen | Token::LBracket | Token::LBrace | Token::DollarLBrace => {
+      true
+    }
+    _ => false,
+  }
+}
+
+fn is_close_bracket(token: &Token) -> bool {
+  match token {
+    Token::RParen | Token::RBracket | Token::RBrace => true,
+    _ => false,
+  }
+}
+
+/// Returns indexes of the pair of bracket tokens where one of them is
+/// right before or at the cursor position.
+fn find_matching_brackets(
+  tokens: &[(Token, Range<usize>)],
+  pos: usize,
+) -> Option<(usize, usize)> {
+  let mut stack = Vec::new();
+  let mut pairs = Vec::new();
+  for (i, (token, _)) in tokens.iter().enumerate() {
+    if is_open_bracket(token) {
+      stack.push(i);
+    } else if is_close_bracket(token) {
+      if let Some(open) = stack.pop() {
+        pairs.push((open, i));
+      }
+    }
+  }
+  let is_at = |i: usize, at: usize| tokens[i].1.start == at;
+  let before_cursor = pos.checked_sub(1);
+  before_cursor
+    .and_then(|at| {
+      pairs
+        .iter()
+        .find(|(open, close)| is_at(*open, at) || is_at(*close, at))
+    })
+    .or_else(|| {
+      pairs
+        .iter()
+        .find(|(open, close)| is_at(*open, pos) || is_at(*close, pos))
+    })
+    .copied()
+}
+
+fn highlight_token(token: &Token, text: &str) -> String {
+  let text = text.to_string();
+  match token {
+    Token::Word(Word::Keyword(_)) => colors::cyan(text).to_string(),
+    Token::Word(Word::Null)
+    | Token::Word(Word::True)
+    | Token::Word(Word::False) => colors::yellow(text).to_string(),
+    Token::Num(_) | Token::BigInt(_) => colors::yellow(text).to_string(),
+    Token::Str { .. } | Token::Template { .. } | Token::BackQuote => {
+      colors::green(text).to_string()
+    }
+    Token::Regex(..) => colors::red(text).to_string(),
+    _ => text,
+  }
+}
+
+fn highlight(line: &str, pos: usize) -> String {
+  let tokens = AstParser::new().tokenize("repl", line);
+  let matching = find_matching_brackets(&tokens, pos);
+  let mut out = String::with_capacity(line.len());
+  let mut last_end = 0;
+  for (i, (token, range)) in tokens.iter().enumerate() {
+    // Lexer might not produce a token for invalid input.
+    if range.start < last_end || range.end > line.len() {
+      continue;
+    }
+    out.push_str(&highlight_gap(&line[last_end..range.start]));
+    let text = &line[range.clone()];
+    match matching {
+      Some((open, close)) if i == open || i == close => {
+        out.push_str(&colors::bold(text.to_string()).to_string())
+      }
+      _ => out.push_str(&highlight_token(token, text)),
+    }
+    last_end = range.end;
+  }
+  out.push_str(&highlight_gap(&line[last_end..]));
+  out
+}
+
+/// Text between tokens is either whitespace or a comment.
+fn highlight_gap(gap: &str) -> String {
+  if gap.trim().is_empty() {
+    gap.to_string()
+  } else {
+    colors::gray(gap.to_string()).to_string()
+  }
+}
+
+/// Maximum number of history entries unless configured otherwise using
+/// `--history-size`.
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+
 pub struct Repl {
-  editor: Editor<()>,
-  history_file: PathBuf,
+  editor: Editor<ReplHelper>,
+  /// History is not persisted if `None`.
+  history_file: Option<PathBuf>,
 }
 
 impl Repl {
-  pub fn new(history_file: PathBuf) -> Self {
+  pub fn new(
+    history_file: Option<PathBuf>,
+    history_size: usize,
+    history_ignore_space: bool,
+  ) -> Self {
+    let config = Config::builder()
+      .max_history_size(history_size)
+      .history_ignore_dups(true)
+      .history_ignore_space(history_ignore_space)
+      .build();
+    let mut editor = Editor::<ReplHelper>::with_config(config);
+    if colors::use_color() {
+      editor.set_helper(Some(ReplHelper));
+    }
     let mut repl = Self {
-      editor: Editor::<()>::new(),
+      editor,
       history_file,
     };
 
@@ -23,50 +177,127 @@ impl Repl {
   }
 
   fn load_history(&mut self) {
-    debug!("Loading REPL history: {:?}", self.history_file);
+    let history_file = match self.history_file {
+      Some(ref history_file) => history_file,
+      None => return,
+    };
+    debug!("Loading REPL history: {:?}", history_file);
     self
       .editor
-      .load_history(&self.history_file.to_str().unwrap())
+      .load_history(history_file)
       .map_err(|e| {
-        debug!("Unable to load history file: {:?} {}", self.history_file, e)
+        debug!("Unable to load history file: {:?} {}", history_file, e)
       })
       // ignore this error (e.g. it occurs on first load)
       .unwrap_or(())
   }
 
   fn save_history(&mut self) -> Result<(), ErrBox> {
-    fs::create_dir_all(self.history_file.parent().unwrap())?;
-    self
-      .editor
-      .save_history(&self.history_file.to_str().unwrap())
-      .map(|_| debug!("Saved REPL history to: {:?}", self.history_file))
-      .map_err(|e| {
-        eprintln!("Unable to save REPL history: {:?} {}", self.history_file, e);
-        ErrBox::from(e)
-      })
+    let history_file = match self.history_file {
+      Some(ref history_file) => history_file,
+      None => return Ok(()),
+    };
+    if let Some(parent) = history_file.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    self.editor.save_history(history_file)?;
+    debug!("Saved REPL history to: {:?}", history_file);
+    Ok(())
   }
 
   pub fn readline(&mut self, prompt: &str) -> Result<String, OpError> {
-    self
-      .editor
-      .readline(&prompt)
-      .map(|line| {
-        self.editor.add_history_entry(line.clone());
-        line
-      })
-      .map_err(OpError::from)
+    let line = self.editor.readline(&prompt).map_err(OpError::from)?;
     // Forward error to TS side for processing
+    if self.editor.add_history_entry(line.clone()) {
+      // History is saved after every entry, so it's not lost if the process
+      // is interrupted while evaluating code.
+      if let Err(e) = self.save_history() {
+        debug!("Unable to save REPL history: {}", e);
+      }
+    }
+    Ok(line)
   }
 }
 
 impl Drop for Repl {
   fn drop(&mut self) {
-    self.save_history().unwrap();
+    if let Err(e) = self.save_history() {
+      eprintln!("Unable to save REPL history: {:?} {}", self.history_file, e);
+    }
+  }
+}
+
+/// Returns path of the history file: `--history-file` flag takes precedence
+/// over `DENO_REPL_HISTORY` env variable, which defaults to `history_file`
+/// in `DENO_DIR`. History is not persisted if the env variable is empty.
+pub fn history_path(
+  dir: &DenoDir,
+  history_file: &str,
+  flag: Option<PathBuf>,
+  env_var: Option<OsString>,
+) -> Option<PathBuf> {
+  if flag.is_some() {
+    return flag;
+  }
+  match env_var {
+    Some(path) if path.is_empty() => None,
+    Some(path) => Some(PathBuf::from(path)),
+    None => Some(dir.root.join(history_file)),
   }
 }
 
-pub fn history_path(dir: &DenoDir, history_file: &str) -> PathBuf {
-  let mut p: PathBuf = dir.root.clone();
-  p.push(history_file);
-  p
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn history_path_override() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let dir = DenoDir::new(Some(temp_dir.path().to_path_buf())).unwrap();
+    assert_eq!(
+      history_path(&dir, "deno_history.txt", None, None),
+      Some(temp_dir.path().join("deno_history.txt"))
+    );
+    assert_eq!(
+      history_path(&dir, "deno_history.txt", None, Some("/h.txt".into())),
+      Some(PathBuf::from("/h.txt"))
+    );
+    assert_eq!(
+      history_path(&dir, "deno_history.txt", None, Some("".into())),
+      None
+    );
+    assert_eq!(
+      history_path(
+        &dir,
+        "deno_history.txt",
+        Some(PathBuf::from("/flag.txt")),
+        Some("/h.txt".into())
+      ),
+      Some(PathBuf::from("/flag.txt"))
+    );
+  }
+
+  #[test]
+  fn highlight_preserves_text() {
+    let line = "const a = /re/g.test(`${1 + 2}`) // comment";
+    assert_eq!(colors::strip_ansi_codes(&highlight(line, 0)), line);
+  }
+
+  #[test]
+  fn matching_brackets() {
+    let line = "f(a[0], { b })";
+    let tokens = AstParser::new().tokenize("repl", line);
+    let text = |(open, close): (usize, usize)| {
+      (&line[tokens[open].1.clone()], tokens[close].1.start)
+    };
+    // Cursor right after "("
+    assert_eq!(
+      find_matching_brackets(&tokens, 2).map(text),
+      Some(("(", 13))
+    );
+    // Cursor at "["
+    assert_eq!(find_matching_brackets(&tokens, 3).map(text), Some(("[", 5)));
+    assert_eq!(find_matching_brackets(&tokens, 11), None);
+  }
 }
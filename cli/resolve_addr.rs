@@ -1,5 +1,6 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 use crate::op_error::OpError;
+use crate::tokio_util::spawn_blocking;
 use std::net::SocketAddr;
 use std::net::ToSocketAddrs;
 
@@ -25,6 +26,15 @@ pub fn resolve_addr(hostname: &str, port: u16) -> Result<SocketAddr, OpError> {
   Ok(iter.next().unwrap())
 }
 
+/// Same as `resolve_addr` but the lookup is performed on the blocking pool,
+/// so slow DNS queries don't stall the event loop.
+pub async fn resolve_addr_async(
+  hostname: String,
+  port: u16,
+) -> Result<SocketAddr, OpError> {
+  spawn_blocking(move || resolve_addr(&hostname, port)).await
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -33,6 +43,18 @@ mod tests {
   use std::net::SocketAddrV4;
   use std::net::SocketAddrV6;
 
+  #[test]
+  fn resolve_addr_async1() {
+    let expected =
+      SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 80));
+    let actual = crate::tokio_util::run_basic(resolve_addr_async(
+      "127.0.0.1".to_string(),
+      80,
+    ))
+    .unwrap();
+    assert_eq!(actual, expected);
+  }
+
   #[test]
   fn resolve_addr1() {
     let expected =
@@ -31,12 +31,15 @@ fn builtin_source_map(_: &str) -> Option<Vec<u8>> {
 #[cfg(not(feature = "check-only"))]
 fn builtin_source_map(file_name: &str) -> Option<Vec<u8>> {
   if file_name.ends_with("CLI_SNAPSHOT.js") {
-    Some(crate::js::CLI_SNAPSHOT_MAP.to_vec())
-  } else if file_name.ends_with("COMPILER_SNAPSHOT.js") {
-    Some(crate::js::COMPILER_SNAPSHOT_MAP.to_vec())
-  } else {
-    None
+    return Some(crate::js::CLI_SNAPSHOT_MAP.to_vec());
   }
+  #[cfg(feature = "tsc")]
+  {
+    if file_name.ends_with("COMPILER_SNAPSHOT.js") {
+      return Some(crate::js::COMPILER_SNAPSHOT_MAP.to_vec());
+    }
+  }
+  None
 }
 
 /// Apply a source map to a deno_core::JSError, returning a JSError where file
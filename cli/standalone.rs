@@ -0,0 +1,180 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Standalone binaries produced by `deno compile`.
+//!
+//! A standalone binary is a copy of the deno executable with a payload
+//! appended to it:
+//!
+//! ```text
+//! | deno executable | bundle | metadata (JSON) | trailer |
+//! ```
+//!
+//! The trailer holds a magic string followed by the offsets of the bundle and
+//! the metadata, as big endian `u64`s. On startup deno checks its own
+//! executable for the trailer and, if found, runs the embedded bundle instead
+//! of parsing the command line.
+
+use crate::flags::Flags;
+use deno_core::ErrBox;
+use serde::Deserialize;
+use serde::Serialize;
+use std::convert::TryInto;
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+#[cfg(not(windows))]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+const MAGIC_TRAILER: &[u8; 8] = b"d3n0l4nd";
+const TRAILER_SIZE: usize = 24;
+
+/// Flags baked into the binary at compile time.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct Metadata {
+  /// Arguments passed to `deno run`, eg. permission flags and `--unstable`.
+  pub args: Vec<String>,
+}
+
+impl Metadata {
+  pub fn from_flags(flags: &Flags) -> Self {
+    let mut args = flags.to_permission_args();
+    if flags.unstable {
+      args.push("--unstable".to_string());
+    }
+    Self { args }
+  }
+}
+
+pub struct Standalone {
+  pub bundle: String,
+  pub metadata: Metadata,
+}
+
+fn u64_be(bytes: &[u8]) -> u64 {
+  u64::from_be_bytes(bytes.try_into().unwrap())
+}
+
+/// Returns the payload of the executable at `exe_path`, or `None` if it's not
+/// a standalone binary.
+pub fn extract_standalone(
+  exe_path: &Path,
+) -> Result<Option<Standalone>, ErrBox> {
+  let mut file = File::open(exe_path)?;
+  let len = file.seek(SeekFrom::End(0))?;
+  if len < TRAILER_SIZE as u64 {
+    return Ok(None);
+  }
+  let mut trailer = [0; TRAILER_SIZE];
+  file.seek(SeekFrom::End(-(TRAILER_SIZE as i64)))?;
+  file.read_exact(&mut trailer)?;
+  if &trailer[0..8] != MAGIC_TRAILER {
+    return Ok(None);
+  }
+
+  let bundle_pos = u64_be(&trailer[8..16]);
+  let metadata_pos = u64_be(&trailer[16..24]);
+  let trailer_pos = len - TRAILER_SIZE as u64;
+  if bundle_pos > metadata_pos || metadata_pos > trailer_pos {
+    return Err(ErrBox::from(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      "Corrupted standalone binary",
+    )));
+  }
+
+  file.seek(SeekFrom::Start(bundle_pos))?;
+  let mut bundle = String::new();
+  file
+    .by_ref()
+    .take(metadata_pos - bundle_pos)
+    .read_to_string(&mut bundle)?;
+  let mut metadata = String::new();
+  file
+    .take(trailer_pos - metadata_pos)
+    .read_to_string(&mut metadata)?;
+  let metadata: Metadata = serde_json::from_str(&metadata)?;
+
+  Ok(Some(Standalone { bundle, metadata }))
+}
+
+/// Appends `bundle` and `metadata` to a copy of the executable at
+/// `original_exe` and writes the result to `output`.
+pub fn create_standalone_binary(
+  original_exe: &Path,
+  bundle: &str,
+  metadata: &Metadata,
+  output: &Path,
+) -> Result<(), ErrBox> {
+  let mut binary = fs::read(original_exe)?;
+  // Compiling with a standalone binary would nest the payloads.
+  if binary.len() >= TRAILER_SIZE
+    && &binary[binary.len() - TRAILER_SIZE..][0..8] == MAGIC_TRAILER
+  {
+    let bundle_pos = u64_be(&binary[binary.len() - 16..binary.len() - 8]);
+    binary.truncate(bundle_pos as usize);
+  }
+  let metadata = serde_json::to_string(metadata)?;
+
+  let bundle_pos = binary.len() as u64;
+  let metadata_pos = bundle_pos + bundle.len() as u64;
+  binary.extend_from_slice(bundle.as_bytes());
+  binary.extend_from_slice(metadata.as_bytes());
+  binary.extend_from_slice(MAGIC_TRAILER);
+  binary.extend_from_slice(&bundle_pos.to_be_bytes());
+  binary.extend_from_slice(&metadata_pos.to_be_bytes());
+
+  fs::write(output, binary)?;
+  #[cfg(not(windows))]
+  fs::set_permissions(output, fs::Permissions::from_mode(0o755))?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::TempDir;
+
+  #[test]
+  fn create_and_extract() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let original = temp_dir.path().join("deno");
+    fs::write(&original, b"not really an executable").unwrap();
+    assert!(extract_standalone(&original).unwrap().is_none());
+
+    let metadata = Metadata {
+      args: vec!["--allow-net".to_string()],
+    };
+    let output = temp_dir.path().join("hello");
+    create_standalone_binary(
+      &original,
+      "console.log('hello');",
+      &metadata,
+      &output,
+    )
+    .unwrap();
+    let standalone = extract_standalone(&output).unwrap().unwrap();
+    assert_eq!(standalone.bundle, "console.log('hello');");
+    assert_eq!(standalone.metadata, metadata);
+
+    // Compiling with a standalone binary replaces its payload.
+    let output2 = temp_dir.path().join("bye");
+    create_standalone_binary(
+      &output,
+      "console.log('bye');",
+      &Metadata { args: vec![] },
+      &output2,
+    )
+    .unwrap();
+    let standalone = extract_standalone(&output2).unwrap().unwrap();
+    assert_eq!(standalone.bundle, "console.log('bye');");
+    assert!(standalone.metadata.args.is_empty());
+    let payload_len = "console.log('bye');".len() + r#"{"args":[]}"#.len();
+    assert_eq!(
+      fs::metadata(&output2).unwrap().len(),
+      fs::metadata(&original).unwrap().len()
+        + (payload_len + TRAILER_SIZE) as u64
+    );
+  }
+}
@@ -3,6 +3,7 @@
 use deno_core::Script;
 
 use crate::js::CLI_SNAPSHOT;
+#[cfg(feature = "tsc")]
 use crate::js::COMPILER_SNAPSHOT;
 use deno_core::Snapshot;
 use deno_core::StartupData;
@@ -33,7 +34,7 @@ pub fn deno_isolate_init() -> StartupData<'static> {
   StartupData::Snapshot(Snapshot::Static(data))
 }
 
-#[cfg(feature = "no-snapshot-init")]
+#[cfg(all(feature = "tsc", feature = "no-snapshot-init"))]
 pub fn compiler_isolate_init() -> StartupData<'static> {
   debug!("Compiler isolate init without snapshots.");
   #[cfg(not(feature = "check-only"))]
@@ -48,11 +49,11 @@ pub fn compiler_isolate_init() -> StartupData<'static> {
   })
 }
 
-#[cfg(not(feature = "no-snapshot-init"))]
+#[cfg(all(feature = "tsc", not(feature = "no-snapshot-init")))]
 pub fn compiler_isolate_init() -> StartupData<'static> {
   debug!("Deno isolate init with snapshots.");
   #[cfg(not(feature = "check-only"))]
-  let data = COMPILER_SNAPSHOT;
+  let data = &COMPILER_SNAPSHOT;
   #[cfg(feature = "check-only")]
   let data = b"";
 
@@ -0,0 +1,119 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Durations of the phases of starting a program, up to evaluation of the
+//! main module. They're printed with `deno run --startup-timing`, or logged
+//! with `--log-level=debug`, to tell which phase makes a cold start slow.
+
+use std::fmt::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+  /// Creating the main isolate, which deserializes the snapshot.
+  Isolate,
+  Bootstrap,
+  Resolve,
+  Fetch,
+  Compile,
+  Evaluate,
+}
+
+const PHASE_COUNT: usize = 6;
+
+const PHASES: [(Phase, &str); PHASE_COUNT] = [
+  (Phase::Isolate, "snapshot load and isolate creation"),
+  (Phase::Bootstrap, "bootstrap"),
+  (Phase::Resolve, "module resolution"),
+  (Phase::Fetch, "module fetch"),
+  (Phase::Compile, "module compile"),
+  (Phase::Evaluate, "module evaluation"),
+];
+
+#[derive(Clone, Copy, Default)]
+struct PhaseTiming {
+  duration: Duration,
+  count: usize,
+}
+
+pub struct StartupTiming {
+  start: Instant,
+  phases: Mutex<[PhaseTiming; PHASE_COUNT]>,
+  finished: AtomicBool,
+}
+
+impl Default for StartupTiming {
+  fn default() -> Self {
+    Self {
+      start: Instant::now(),
+      phases: Mutex::new([PhaseTiming::default(); PHASE_COUNT]),
+      finished: AtomicBool::new(false),
+    }
+  }
+}
+
+impl StartupTiming {
+  /// Adds `duration` to `phase`; phases that happen once per module add up
+  /// the time spent on every module. Does nothing once startup finished.
+  pub fn record(&self, phase: Phase, duration: Duration) {
+    if self.finished.load(Ordering::SeqCst) {
+      return;
+    }
+    let mut phases = self.phases.lock().unwrap();
+    let timing = &mut phases[phase as usize];
+    timing.duration += duration;
+    timing.count += 1;
+  }
+
+  /// Ends startup and returns the report of all phases.
+  pub fn finish(&self) -> String {
+    self.finished.store(true, Ordering::SeqCst);
+    let total = self.start.elapsed();
+    let phases = self.phases.lock().unwrap();
+    let mut report = "Startup timing:\n".to_string();
+    for (phase, name) in PHASES.iter() {
+      let timing = phases[*phase as usize];
+      let _ =
+        write!(report, "  {:<36}{:>10}", name, format_ms(timing.duration));
+      if timing.count > 1 {
+        let _ = write!(report, " ({} modules)", timing.count);
+      }
+      report.push('\n');
+    }
+    let _ = write!(report, "  {:<36}{:>10}", "total", format_ms(total));
+    report
+  }
+}
+
+fn format_ms(duration: Duration) -> String {
+  format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn startup_timing_report() {
+    let timing = StartupTiming::default();
+    timing.record(Phase::Isolate, Duration::from_millis(12));
+    timing.record(Phase::Fetch, Duration::from_millis(1));
+    timing.record(Phase::Fetch, Duration::from_millis(2));
+    let report = timing.finish();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), PHASE_COUNT + 2);
+    assert_eq!(lines[0], "Startup timing:");
+    assert!(lines[1].starts_with("  snapshot load and isolate creation"));
+    assert!(lines[1].ends_with("12.00ms"));
+    assert!(lines[4].ends_with("3.00ms (2 modules)"));
+    assert!(lines[6].ends_with("0.00ms"));
+    assert!(lines[7].starts_with("  total"));
+
+    // Phases recorded after startup are ignored.
+    timing.record(Phase::Evaluate, Duration::from_secs(1));
+    assert_eq!(timing.finish().lines().nth(6), Some(lines[6]));
+  }
+}
@@ -1,19 +1,26 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::blob_store::Blobs;
 use crate::file_fetcher::SourceFileFetcher;
 use crate::global_state::GlobalState;
-use crate::global_timer::GlobalTimer;
+use crate::http_util::create_http_client;
 use crate::import_map::ImportMap;
+use crate::inspector::LocalInspectorSession;
 use crate::metrics::Metrics;
+use crate::multipart::PartFiles;
+use crate::npm;
 use crate::op_error::OpError;
 use crate::ops::JsonOp;
 use crate::ops::MinimalOp;
 use crate::permissions::Permissions;
+use crate::startup_timing::Phase;
+use crate::trace_events::OpTrace;
 use crate::tsc::TargetLib;
 use crate::web_worker::WebWorkerHandle;
 use deno_core::Buf;
 use deno_core::ErrBox;
 use deno_core::ModuleLoadId;
 use deno_core::ModuleLoader;
+use deno_core::ModuleResolutionError;
 use deno_core::ModuleSpecifier;
 use deno_core::Op;
 use deno_core::ZeroCopyBuf;
@@ -24,11 +31,14 @@ use rand::SeedableRng;
 use serde_json::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::path::Path;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::str;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use std::thread::JoinHandle;
 use std::time::Instant;
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -60,13 +70,35 @@ pub struct StateInner {
   /// import map file will be resolved and set.
   pub import_map: Option<ImportMap>,
   pub metrics: Metrics,
-  pub global_timer: GlobalTimer,
   pub workers: HashMap<u32, (JoinHandle<()>, WebWorkerHandle)>,
   pub next_worker_id: u32,
   pub start_time: Instant,
   pub seeded_rng: Option<StdRng>,
   pub target_lib: TargetLib,
   pub debug_type: DebugType,
+  /// Exit code requested using `Deno.exit()` in the main isolate; the
+  /// process exits once `MainWorker::handle_exit` performs shutdown.
+  pub exit_code: Option<i32>,
+  /// Set during graceful shutdown; async ops dispatched afterwards don't
+  /// keep the event loop alive.
+  pub is_shutting_down: bool,
+  /// Set once Ctrl-C performs graceful shutdown, ie. after the main runtime
+  /// was bootstrapped; the flag is raised when Ctrl-C is pressed.
+  pub sigint: Option<Arc<AtomicBool>>,
+  /// Client shared by `fetch()` calls that don't pass their own, so that
+  /// connections are pooled; created on first use.
+  pub http_client: Option<reqwest::Client>,
+  /// Session of the isolate's inspector that ops post DevTools protocol
+  /// messages to; set if the worker has an inspector.
+  pub inspector_session: Option<LocalInspectorSession>,
+  /// Files of the file parts of `multipart/form-data` bodies parsed by the
+  /// isolate, deleted along with it; created on first use.
+  pub multipart_part_files: Option<PartFiles>,
+  /// Contents of the isolate's `Blob` objects.
+  pub blobs: Blobs,
+  /// The isolate's timeline in `GlobalState::performance`, added when the
+  /// runtime is bootstrapped.
+  pub timeline_id: Option<u32>,
 }
 
 impl State {
@@ -113,31 +145,81 @@ impl State {
           control: &[u8],
           zero_copy: Option<ZeroCopyBuf>|
           -> Op {
+      let op_id = isolate.current_op_id();
       let bytes_sent_control = control.len() as u64;
       let bytes_sent_zero_copy =
         zero_copy.as_ref().map(|b| b.len()).unwrap_or(0) as u64;
 
+      // Async op latency is only measured with `--warn-slow-ops`.
+      let dispatched_at = state
+        .borrow()
+        .global_state
+        .flags
+        .warn_slow_ops
+        .map(|_| Instant::now());
+
+      // Ops are traced with `--trace-ops`.
+      let trace_events = state.borrow().global_state.trace_events.clone();
+      let op_trace = trace_events.map(|trace_events| {
+        let name = op_id.and_then(|op_id| isolate.op_registry.name(op_id));
+        OpTrace::new(&trace_events, name.unwrap_or("unknown"))
+      });
+
       let op = dispatcher(isolate, control, zero_copy);
 
       match op {
         Op::Sync(buf) => {
           let mut state_ = state.borrow_mut();
           state_.metrics.op_sync(
+            op_id,
             bytes_sent_control,
             bytes_sent_zero_copy,
             buf.len() as u64,
           );
+          if let Some(op_trace) = op_trace {
+            op_trace.sync_completed();
+          }
           Op::Sync(buf)
         }
+        // Once shutdown began new async ops are not allowed to extend it.
+        Op::Async(fut) if state.borrow().is_shutting_down => {
+          let mut state_ = state.borrow_mut();
+          state_.metrics.op_dispatched_async_unref(
+            op_id,
+            bytes_sent_control,
+            bytes_sent_zero_copy,
+          );
+          let op_trace = op_trace.map(OpTrace::async_dispatched);
+          let state = state.clone();
+          let result_fut = fut.map(move |buf: Buf| {
+            if let Some(op_trace) = op_trace {
+              op_trace.completed();
+            }
+            let mut state_ = state.borrow_mut();
+            state_
+              .metrics
+              .op_completed_async_unref(op_id, buf.len() as u64);
+            state_.metrics.record_op_async_duration(dispatched_at);
+            buf
+          });
+          Op::AsyncUnref(result_fut.boxed_local())
+        }
         Op::Async(fut) => {
           let mut state_ = state.borrow_mut();
-          state_
-            .metrics
-            .op_dispatched_async(bytes_sent_control, bytes_sent_zero_copy);
+          state_.metrics.op_dispatched_async(
+            op_id,
+            bytes_sent_control,
+            bytes_sent_zero_copy,
+          );
+          let op_trace = op_trace.map(OpTrace::async_dispatched);
           let state = state.clone();
           let result_fut = fut.map(move |buf: Buf| {
+            if let Some(op_trace) = op_trace {
+              op_trace.completed();
+            }
             let mut state_ = state.borrow_mut();
-            state_.metrics.op_completed_async(buf.len() as u64);
+            state_.metrics.op_completed_async(op_id, buf.len() as u64);
+            state_.metrics.record_op_async_duration(dispatched_at);
             buf
           });
           Op::Async(result_fut.boxed_local())
@@ -145,13 +227,21 @@ impl State {
         Op::AsyncUnref(fut) => {
           let mut state_ = state.borrow_mut();
           state_.metrics.op_dispatched_async_unref(
+            op_id,
             bytes_sent_control,
             bytes_sent_zero_copy,
           );
+          let op_trace = op_trace.map(OpTrace::async_dispatched);
           let state = state.clone();
           let result_fut = fut.map(move |buf: Buf| {
+            if let Some(op_trace) = op_trace {
+              op_trace.completed();
+            }
             let mut state_ = state.borrow_mut();
-            state_.metrics.op_completed_async_unref(buf.len() as u64);
+            state_
+              .metrics
+              .op_completed_async_unref(op_id, buf.len() as u64);
+            state_.metrics.record_op_async_duration(dispatched_at);
             buf
           });
           Op::AsyncUnref(result_fut.boxed_local())
@@ -233,16 +323,18 @@ impl State {
     }
   }
 
-  /// Quits the process if the --unstable flag was not provided.
-  ///
-  /// This is intentionally a non-recoverable check so that people cannot probe
-  /// for unstable APIs from stable programs.
-  pub fn check_unstable(&self, api_name: &str) {
-    // TODO(ry) Maybe use IsolateHandle::terminate_execution here to provide a
-    // stack trace in JS.
+  /// Errors if the --unstable flag was not provided. Ops of unstable APIs
+  /// call this first, so they can't be dispatched directly through
+  /// `Deno.core` by stable programs either.
+  pub fn check_unstable(&self, api_name: &str) -> Result<(), OpError> {
     let s = self.0.borrow();
-    if !s.global_state.flags.unstable {
-      exit_unstable(api_name);
+    if s.global_state.flags.unstable {
+      Ok(())
+    } else {
+      Err(OpError::other(format!(
+        "{} is unstable and requires --unstable",
+        api_name
+      )))
     }
   }
 }
@@ -262,18 +354,12 @@ impl ModuleLoader for State {
     referrer: &str,
     is_main: bool,
   ) -> Result<ModuleSpecifier, ErrBox> {
-    if !is_main {
-      if let Some(import_map) = &self.borrow().import_map {
-        let result = import_map.resolve(specifier, referrer)?;
-        if let Some(r) = result {
-          return Ok(r);
-        }
-      }
-    }
-    let module_specifier =
-      ModuleSpecifier::resolve_import(specifier, referrer)?;
-
-    Ok(module_specifier)
+    let start = Instant::now();
+    let result = self.resolve_specifier(specifier, referrer, is_main);
+    let state = self.borrow();
+    let startup_timing = &state.global_state.startup_timing;
+    startup_timing.record(Phase::Resolve, start.elapsed());
+    result
   }
 
   /// Given an absolute url, load its source code.
@@ -353,6 +439,35 @@ impl ModuleLoader for State {
 }
 
 impl State {
+  /// Resolves an import specifier, using the import map and, with
+  /// `--compat`, `node_modules`.
+  fn resolve_specifier(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    is_main: bool,
+  ) -> Result<ModuleSpecifier, ErrBox> {
+    if !is_main {
+      if let Some(import_map) = &self.borrow().import_map {
+        let result = import_map.resolve(specifier, referrer)?;
+        if let Some(r) = result {
+          return Ok(r);
+        }
+      }
+    }
+    let result = ModuleSpecifier::resolve_import(specifier, referrer);
+    if let Err(ModuleResolutionError::ImportPrefixMissing(..)) = result {
+      if self.borrow().global_state.flags.compat {
+        if let Some(r) = npm::resolve_bare_specifier(specifier, referrer)? {
+          return Ok(r);
+        }
+      }
+    }
+    let module_specifier = result?;
+
+    Ok(module_specifier)
+  }
+
   /// If `shared_permission` is None then permissions from globa state are used.
   pub fn new(
     global_state: GlobalState,
@@ -388,13 +503,20 @@ impl State {
       permissions,
       import_map,
       metrics: Metrics::default(),
-      global_timer: GlobalTimer::new(),
       workers: HashMap::new(),
       next_worker_id: 0,
       start_time: Instant::now(),
       seeded_rng,
       target_lib: TargetLib::Main,
       debug_type,
+      exit_code: None,
+      is_shutting_down: false,
+      sigint: None,
+      http_client: None,
+      inspector_session: None,
+      multipart_part_files: None,
+      blobs: Blobs::default(),
+      timeline_id: None,
     }));
 
     Ok(Self(state))
@@ -423,18 +545,37 @@ impl State {
       permissions,
       import_map: None,
       metrics: Metrics::default(),
-      global_timer: GlobalTimer::new(),
       workers: HashMap::new(),
       next_worker_id: 0,
       start_time: Instant::now(),
       seeded_rng,
       target_lib: TargetLib::Worker,
       debug_type: DebugType::Dependent,
+      exit_code: None,
+      is_shutting_down: false,
+      sigint: None,
+      http_client: None,
+      inspector_session: None,
+      multipart_part_files: None,
+      blobs: Blobs::default(),
+      timeline_id: None,
     }));
 
     Ok(Self(state))
   }
 
+  /// Returns the isolate's shared HTTP client, creating it on first use.
+  pub fn http_client(&self) -> Result<reqwest::Client, OpError> {
+    let mut state = self.borrow_mut();
+    if let Some(client) = &state.http_client {
+      return Ok(client.clone());
+    }
+    let ca_file = state.global_state.flags.ca_file.clone();
+    let client = create_http_client(ca_file)?;
+    state.http_client = Some(client.clone());
+    Ok(client)
+  }
+
   #[inline]
   pub fn check_read(&self, path: &Path) -> Result<(), OpError> {
     self.borrow().permissions.check_read(path)
@@ -455,6 +596,27 @@ impl State {
     self.borrow().permissions.check_net(hostname, port)
   }
 
+  #[inline]
+  pub fn check_net_addr(
+    &self,
+    hostname: &str,
+    addr: &SocketAddr,
+  ) -> Result<(), OpError> {
+    self.borrow().permissions.check_net_addr(hostname, addr)
+  }
+
+  #[inline]
+  pub fn check_net_unresolved(
+    &self,
+    hostname: &str,
+    port: u16,
+  ) -> Result<bool, OpError> {
+    self
+      .borrow()
+      .permissions
+      .check_net_unresolved(hostname, port)
+  }
+
   #[inline]
   pub fn check_net_url(&self, url: &url::Url) -> Result<(), OpError> {
     self.borrow().permissions.check_net_url(url)
@@ -470,6 +632,11 @@ impl State {
     self.borrow().permissions.check_plugin(filename)
   }
 
+  #[inline]
+  pub fn check_ffi(&self, filename: Option<&Path>) -> Result<(), OpError> {
+    self.borrow().permissions.check_ffi(filename)
+  }
+
   pub fn check_dyn_import(
     &self,
     module_specifier: &ModuleSpecifier,
@@ -494,6 +661,10 @@ impl State {
         self.check_read(Path::new(&path))?;
         Ok(())
       }
+      // Downloads are checked against `--allow-net` by the npm cache.
+      "npm" => Ok(()),
+      // Object URLs only give access to blobs the program made itself.
+      "blob" => Ok(()),
       _ => unreachable!(),
     }
   }
@@ -12,6 +12,8 @@ use crate::swc_common::SourceMap;
 use crate::swc_common::Span;
 use crate::swc_ecma_ast;
 use crate::swc_ecma_parser::lexer::Lexer;
+use crate::swc_ecma_parser::token::Token;
+use crate::swc_ecma_parser::EsConfig;
 use crate::swc_ecma_parser::JscTarget;
 use crate::swc_ecma_parser::Parser;
 use crate::swc_ecma_parser::Session;
@@ -23,6 +25,7 @@ use swc_ecma_visit::Visit;
 
 use std::error::Error;
 use std::fmt;
+use std::ops::Range;
 use std::sync::Arc;
 use std::sync::RwLock;
 
@@ -126,6 +129,7 @@ impl AstParser {
 
       let mut ts_config = TsConfig::default();
       ts_config.dynamic_import = true;
+      ts_config.decorators = true;
       let syntax = Syntax::Typescript(ts_config);
 
       let lexer = Lexer::new(
@@ -150,6 +154,40 @@ impl AstParser {
     })
   }
 
+  /// Splits JavaScript source code into tokens, paired with their byte
+  /// ranges in `source_code`. Comments and whitespace are not included.
+  pub fn tokenize(
+    &self,
+    file_name: &str,
+    source_code: &str,
+  ) -> Vec<(Token, Range<usize>)> {
+    swc_common::GLOBALS.set(&self.globals, || {
+      let swc_source_file = self.source_map.new_source_file(
+        FileName::Custom(file_name.to_string()),
+        source_code.to_string(),
+      );
+      let session = Session {
+        handler: &self.handler,
+      };
+      let lexer = Lexer::new(
+        session,
+        Syntax::Es(EsConfig::default()),
+        JscTarget::Es2019,
+        SourceFileInput::from(&*swc_source_file),
+        None,
+      );
+      let start_pos = swc_source_file.start_pos.0 as usize;
+      lexer
+        .map(|token_and_span| {
+          let span = token_and_span.span;
+          let lo = span.lo().0 as usize - start_pos;
+          let hi = span.hi().0 as usize - start_pos;
+          (token_and_span.token, lo..hi)
+        })
+        .collect()
+    })
+  }
+
   pub fn get_span_location(&self, span: Span) -> swc_common::Loc {
     self.source_map.lookup_char_pos(span.lo())
   }
@@ -317,3 +355,19 @@ const a = await import("./" + "buzz.ts");
     ]
   );
 }
+
+#[test]
+fn test_analyze_dependencies_decorators() {
+  let source = r#"
+import { Component } from "./framework.ts";
+
+@Component({ selector: "app" })
+export class App {
+  @Input() name = "";
+}
+"#;
+
+  let dependencies =
+    analyze_dependencies(source, false).expect("Failed to parse");
+  assert_eq!(dependencies, vec!["./framework.ts".to_string()]);
+}
@@ -0,0 +1,362 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! `deno task` runs command lines defined in the `tasks` map of `deno.json`.
+//!
+//! Command lines are executed by a small interpreter rather than the system
+//! shell, so that tasks behave the same on every platform. It supports:
+//!
+//! - sequencing commands with `&&`, `||` and `;`
+//! - single and double quotes, and backslash escapes
+//! - expansion of environment variables (`$NAME`, `${NAME}`)
+//! - setting environment variables (`NAME=value cmd`, or `NAME=value` alone
+//!   for the rest of the task)
+
+use crate::op_error::OpError;
+use deno_core::ErrBox;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+pub const CONFIG_FILE_NAME: &str = "deno.json";
+
+/// Looks for `deno.json` in `dir` and its ancestors.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+  dir
+    .ancestors()
+    .map(|dir| dir.join(CONFIG_FILE_NAME))
+    .find(|path| path.is_file())
+}
+
+/// Reads the `tasks` map of the config file, in declaration order.
+pub fn read_tasks(
+  config_path: &Path,
+) -> Result<IndexMap<String, String>, ErrBox> {
+  let source = fs::read_to_string(config_path)?;
+  let config: serde_json::Value = serde_json::from_str(&source)?;
+  let tasks = match config.get("tasks") {
+    Some(serde_json::Value::Object(tasks)) => tasks,
+    Some(_) => {
+      return Err(
+        OpError::type_error(format!(
+          "\"tasks\" in {} must be an object",
+          config_path.display()
+        ))
+        .into(),
+      )
+    }
+    None => return Ok(IndexMap::new()),
+  };
+  let mut result = IndexMap::new();
+  for (name, script) in tasks {
+    match script {
+      serde_json::Value::String(script) => {
+        result.insert(name.to_string(), script.to_string());
+      }
+      _ => {
+        return Err(
+          OpError::type_error(format!(
+            "Task \"{}\" in {} must be a string",
+            name,
+            config_path.display()
+          ))
+          .into(),
+        )
+      }
+    }
+  }
+  Ok(result)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+  /// `;`, run the next command unconditionally.
+  Sequence,
+  /// `&&`, run the next command if the previous one succeeded.
+  And,
+  /// `||`, run the next command if the previous one failed.
+  Or,
+}
+
+/// Splits a command line into commands and the operators preceding them.
+/// Words are split later, so variables set by earlier commands are expanded.
+fn split_commands(line: &str) -> Result<Vec<(Operator, String)>, ErrBox> {
+  let mut commands = vec![];
+  let mut operator = Operator::Sequence;
+  let mut current = String::new();
+  let mut chars = line.chars().peekable();
+  let mut quote: Option<char> = None;
+
+  while let Some(c) = chars.next() {
+    match (c, quote) {
+      ('\\', q) if q != Some('\'') => {
+        current.push(c);
+        if let Some(next) = chars.next() {
+          current.push(next);
+        }
+      }
+      ('\'', None) | ('"', None) => {
+        quote = Some(c);
+        current.push(c);
+      }
+      (c, Some(q)) if c == q => {
+        quote = None;
+        current.push(c);
+      }
+      ('&', None) | ('|', None) | (';', None) => {
+        let next_operator = match c {
+          ';' => Operator::Sequence,
+          _ if chars.peek() == Some(&c) => {
+            chars.next();
+            if c == '&' {
+              Operator::And
+            } else {
+              Operator::Or
+            }
+          }
+          _ => {
+            return Err(
+              OpError::other(format!("Unsupported operator '{}'", c)).into(),
+            )
+          }
+        };
+        if current.trim().is_empty() {
+          return Err(
+            OpError::other(format!("Missing command before '{}'", c)).into(),
+          );
+        }
+        commands.push((operator, current.trim().to_string()));
+        current.clear();
+        operator = next_operator;
+      }
+      _ => current.push(c),
+    }
+  }
+
+  if quote.is_some() {
+    return Err(OpError::other("Unterminated quote".to_string()).into());
+  }
+  if !current.trim().is_empty() {
+    commands.push((operator, current.trim().to_string()));
+  } else if !commands.is_empty() && operator != Operator::Sequence {
+    return Err(
+      OpError::other("Missing command at the end".to_string()).into(),
+    );
+  }
+  Ok(commands)
+}
+
+fn is_name_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Splits a single command into words, removing quotes and expanding
+/// variables from `vars`.
+fn split_words(
+  command: &str,
+  vars: &HashMap<String, String>,
+) -> Result<Vec<String>, ErrBox> {
+  let mut words = vec![];
+  let mut current = String::new();
+  // Distinguishes an empty quoted word from no word at all.
+  let mut in_word = false;
+  let mut quote: Option<char> = None;
+  let mut chars = command.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match (c, quote) {
+      (c, None) if c.is_whitespace() => {
+        if in_word {
+          words.push(std::mem::take(&mut current));
+          in_word = false;
+        }
+      }
+      ('\'', None) | ('"', None) => {
+        quote = Some(c);
+        in_word = true;
+      }
+      (c, Some(q)) if c == q => quote = None,
+      ('\\', q) if q != Some('\'') => {
+        if let Some(next) = chars.next() {
+          current.push(next);
+        }
+        in_word = true;
+      }
+      ('$', q) if q != Some('\'') => {
+        let mut name = String::new();
+        if chars.peek() == Some(&'{') {
+          chars.next();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => name.push(c),
+              None => {
+                return Err(
+                  OpError::other("Unterminated variable".to_string()).into(),
+                )
+              }
+            }
+          }
+        } else {
+          while let Some(&c) = chars.peek() {
+            if !is_name_char(c) {
+              break;
+            }
+            name.push(c);
+            chars.next();
+          }
+        }
+        if name.is_empty() {
+          current.push('$');
+        } else if let Some(value) = vars.get(&name) {
+          current.push_str(value);
+        }
+        in_word = true;
+      }
+      _ => {
+        current.push(c);
+        in_word = true;
+      }
+    }
+  }
+  if in_word {
+    words.push(current);
+  }
+  Ok(words)
+}
+
+/// Returns the `NAME=value` assignment of the word, if it's one.
+fn parse_assignment(word: &str) -> Option<(String, String)> {
+  let eq = word.find('=')?;
+  let name = &word[..eq];
+  if name.is_empty()
+    || name.starts_with(|c: char| c.is_ascii_digit())
+    || !name.chars().all(is_name_char)
+  {
+    return None;
+  }
+  Some((name.to_string(), word[eq + 1..].to_string()))
+}
+
+/// Runs the command line of a task in `cwd`; `args` are appended to the last
+/// command. Returns the exit code of the last command that ran.
+pub fn run_task(
+  script: &str,
+  cwd: &Path,
+  args: &[String],
+) -> Result<i32, ErrBox> {
+  let commands = split_commands(script)?;
+  let mut vars: HashMap<String, String> = env::vars().collect();
+  let mut exit_code = 0;
+
+  for (i, (operator, command)) in commands.iter().enumerate() {
+    let skip = match operator {
+      Operator::Sequence => false,
+      Operator::And => exit_code != 0,
+      Operator::Or => exit_code == 0,
+    };
+    if skip {
+      continue;
+    }
+
+    let mut words = split_words(command, &vars)?;
+    if i == commands.len() - 1 {
+      words.extend_from_slice(args);
+    }
+    let assignments_len = words
+      .iter()
+      .take_while(|word| parse_assignment(word).is_some())
+      .count();
+    let assignments: Vec<(String, String)> = words
+      .drain(..assignments_len)
+      .filter_map(|word| parse_assignment(&word))
+      .collect();
+
+    if words.is_empty() {
+      // Assignments without a command apply to the rest of the task.
+      vars.extend(assignments);
+      exit_code = 0;
+      continue;
+    }
+
+    let mut command_vars = vars.clone();
+    command_vars.extend(assignments);
+    let status = Command::new(&words[0])
+      .args(&words[1..])
+      .current_dir(cwd)
+      .env_clear()
+      .envs(&command_vars)
+      .status()
+      .map_err(|e| {
+        OpError::other(format!("Failed to run \"{}\": {}", words[0], e))
+      })?;
+    exit_code = status.code().unwrap_or(1);
+  }
+
+  Ok(exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn split_commands_operators() {
+    assert_eq!(
+      split_commands("a 1 && b 'x && y' || c; d").unwrap(),
+      vec![
+        (Operator::Sequence, "a 1".to_string()),
+        (Operator::And, "b 'x && y'".to_string()),
+        (Operator::Or, "c".to_string()),
+        (Operator::Sequence, "d".to_string()),
+      ]
+    );
+    assert!(split_commands("a | b").is_err());
+    assert!(split_commands("&& b").is_err());
+    assert!(split_commands("a &&").is_err());
+    assert!(split_commands("echo 'a").is_err());
+  }
+
+  #[test]
+  fn split_words_quotes_and_vars() {
+    let mut vars = HashMap::new();
+    vars.insert("NAME".to_string(), "deno land".to_string());
+    assert_eq!(
+      split_words(r#"echo "hello $NAME" '$NAME' ${NAME}! \$x """#, &vars)
+        .unwrap(),
+      vec!["echo", "hello deno land", "$NAME", "deno land!", "$x", ""]
+    );
+    assert_eq!(split_words("echo $MISSING", &vars).unwrap(), vec!["echo"]);
+  }
+
+  #[test]
+  fn assignments() {
+    assert_eq!(
+      parse_assignment("A_1=b=c"),
+      Some(("A_1".to_string(), "b=c".to_string()))
+    );
+    assert_eq!(parse_assignment("=b"), None);
+    assert_eq!(parse_assignment("1A=b"), None);
+    assert_eq!(parse_assignment("--flag=b"), None);
+  }
+
+  #[test]
+  fn read_tasks_in_order() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let config_path = temp_dir.path().join(CONFIG_FILE_NAME);
+    fs::write(
+      &config_path,
+      r#"{ "tasks": { "b": "echo b", "a": "echo a" } }"#,
+    )
+    .unwrap();
+    let tasks = read_tasks(&config_path).unwrap();
+    assert_eq!(tasks.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+
+    let sub_dir = temp_dir.path().join("sub");
+    fs::create_dir(&sub_dir).unwrap();
+    assert_eq!(find_config(&sub_dir), Some(config_path));
+  }
+}
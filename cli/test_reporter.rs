@@ -0,0 +1,310 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Reporting of `deno test` results.
+//!
+//! Test workers send `TestEvent`s through `op_test_event`; all of them are
+//! collected by a single `TestCollector`, so results of tests run in
+//! parallel (`--jobs`) end up in one report.
+
+use crate::colors;
+use serde::Deserialize;
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+  Passed,
+  Failed,
+  Ignored,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TestResult {
+  pub name: String,
+  pub status: TestStatus,
+  /// Duration in milliseconds.
+  pub duration: u64,
+  /// Formatted error, set if the test failed.
+  pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TestEvent {
+  /// Sent by each test worker before running its tests.
+  Plan {
+    pending: usize,
+    filtered: usize,
+  },
+  Result(TestResult),
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TestSummary {
+  pub passed: usize,
+  pub failed: usize,
+  pub ignored: usize,
+  pub filtered: usize,
+  #[serde(skip)]
+  pub results: Vec<TestResult>,
+}
+
+impl TestSummary {
+  fn record(&mut self, event: &TestEvent) {
+    match event {
+      TestEvent::Plan { filtered, .. } => self.filtered += filtered,
+      TestEvent::Result(result) => {
+        match result.status {
+          TestStatus::Passed => self.passed += 1,
+          TestStatus::Failed => self.failed += 1,
+          TestStatus::Ignored => self.ignored += 1,
+        }
+        self.results.push(result.clone());
+      }
+    }
+  }
+
+  fn failures(&self) -> impl Iterator<Item = &TestResult> {
+    self
+      .results
+      .iter()
+      .filter(|result| result.status == TestStatus::Failed)
+  }
+}
+
+pub trait TestReporter: Send {
+  fn visit_event(&mut self, event: &TestEvent);
+  fn done(&mut self, summary: &TestSummary, elapsed: Duration);
+}
+
+/// Returns reporter for the `--reporter` flag value.
+pub fn create_reporter(kind: &str, quiet: bool) -> Box<dyn TestReporter> {
+  match kind {
+    "json" => Box::new(JsonTestReporter),
+    "junit" => Box::new(JunitTestReporter),
+    _ => Box::new(PrettyTestReporter { quiet }),
+  }
+}
+
+pub struct TestCollector {
+  summary: TestSummary,
+  reporter: Box<dyn TestReporter>,
+}
+
+impl TestCollector {
+  pub fn new(reporter: Box<dyn TestReporter>) -> Self {
+    Self {
+      summary: TestSummary::default(),
+      reporter,
+    }
+  }
+
+  pub fn visit_event(&mut self, event: TestEvent) {
+    self.summary.record(&event);
+    self.reporter.visit_event(&event);
+  }
+
+  pub fn done(mut self, elapsed: Duration) -> TestSummary {
+    self.reporter.done(&self.summary, elapsed);
+    self.summary
+  }
+}
+
+fn format_duration(duration: u64) -> String {
+  colors::italic_gray(format!("({}ms)", duration)).to_string()
+}
+
+/// Human readable output; nothing is printed if `quiet` is set.
+struct PrettyTestReporter {
+  quiet: bool,
+}
+
+impl TestReporter for PrettyTestReporter {
+  fn visit_event(&mut self, event: &TestEvent) {
+    if self.quiet {
+      return;
+    }
+    match event {
+      TestEvent::Plan { pending, .. } => println!("running {} tests", pending),
+      TestEvent::Result(result) => {
+        let status = match result.status {
+          TestStatus::Passed => colors::green("ok".to_string()).to_string(),
+          TestStatus::Failed => colors::red("FAILED".to_string()).to_string(),
+          TestStatus::Ignored => {
+            colors::yellow("ignored".to_string()).to_string()
+          }
+        };
+        println!(
+          "test {} ... {} {}",
+          result.name,
+          status,
+          format_duration(result.duration)
+        );
+      }
+    }
+  }
+
+  fn done(&mut self, summary: &TestSummary, elapsed: Duration) {
+    if self.quiet {
+      return;
+    }
+    if summary.failed > 0 {
+      println!("\nfailures:\n");
+      for result in summary.failures() {
+        println!("{}", result.name);
+        println!("{}", result.error.as_deref().unwrap_or(""));
+        println!();
+      }
+      println!("failures:\n");
+      for result in summary.failures() {
+        println!("\t{}", result.name);
+      }
+    }
+    let status = if summary.failed > 0 {
+      colors::red("FAILED".to_string()).to_string()
+    } else {
+      colors::green("ok".to_string()).to_string()
+    };
+    println!(
+      "\ntest result: {}. {} passed; {} failed; {} ignored; 0 measured; {} filtered out {}\n",
+      status,
+      summary.passed,
+      summary.failed,
+      summary.ignored,
+      summary.filtered,
+      format_duration(elapsed.as_millis() as u64),
+    );
+  }
+}
+
+/// Prints every event, followed by the summary, as a line of JSON.
+struct JsonTestReporter;
+
+impl TestReporter for JsonTestReporter {
+  fn visit_event(&mut self, event: &TestEvent) {
+    println!("{}", json!(event));
+  }
+
+  fn done(&mut self, summary: &TestSummary, elapsed: Duration) {
+    let mut summary = json!(summary);
+    summary["duration"] = json!(elapsed.as_millis() as u64);
+    println!("{}", json!({ "summary": summary }));
+  }
+}
+
+/// Prints JUnit XML report once all tests finished.
+struct JunitTestReporter;
+
+impl TestReporter for JunitTestReporter {
+  fn visit_event(&mut self, _event: &TestEvent) {}
+
+  fn done(&mut self, summary: &TestSummary, elapsed: Duration) {
+    print!("{}", render_junit(summary, elapsed));
+  }
+}
+
+fn escape_xml(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+fn render_junit(summary: &TestSummary, elapsed: Duration) -> String {
+  let tests = summary.results.len();
+  let attrs = format!(
+    "name=\"deno test\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\"",
+    tests,
+    summary.failed,
+    summary.ignored,
+    elapsed.as_secs_f64()
+  );
+  let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  out.push_str(&format!("<testsuites {}>\n", attrs));
+  out.push_str(&format!("  <testsuite {}>\n", attrs));
+  for result in &summary.results {
+    let testcase = format!(
+      "    <testcase name=\"{}\" time=\"{:.3}\"",
+      escape_xml(&result.name),
+      result.duration as f64 / 1000.0
+    );
+    match result.status {
+      TestStatus::Passed => out.push_str(&format!("{}/>\n", testcase)),
+      TestStatus::Ignored => {
+        out.push_str(&format!("{}>\n      <skipped/>\n", testcase));
+        out.push_str("    </testcase>\n");
+      }
+      TestStatus::Failed => {
+        let error = result.error.as_deref().unwrap_or("");
+        let message = error.lines().next().unwrap_or("");
+        out.push_str(&format!(
+          "{}>\n      <failure message=\"{}\">{}</failure>\n",
+          testcase,
+          escape_xml(message),
+          escape_xml(error)
+        ));
+        out.push_str("    </testcase>\n");
+      }
+    }
+  }
+  out.push_str("  </testsuite>\n</testsuites>\n");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(name: &str, status: TestStatus, error: Option<&str>) -> TestEvent {
+    TestEvent::Result(TestResult {
+      name: name.to_string(),
+      status,
+      duration: 5,
+      error: error.map(String::from),
+    })
+  }
+
+  #[test]
+  fn deserialize_event() {
+    let event: TestEvent = serde_json::from_value(json!({
+      "result": { "name": "a", "status": "failed", "duration": 1, "error": "e" }
+    }))
+    .unwrap();
+    assert_eq!(
+      event,
+      TestEvent::Result(TestResult {
+        name: "a".to_string(),
+        status: TestStatus::Failed,
+        duration: 1,
+        error: Some("e".to_string()),
+      })
+    );
+  }
+
+  #[test]
+  fn junit_report() {
+    let mut summary = TestSummary::default();
+    summary.record(&TestEvent::Plan {
+      pending: 3,
+      filtered: 1,
+    });
+    summary.record(&result("a", TestStatus::Passed, None));
+    summary.record(&result("<b>", TestStatus::Failed, Some("Error: x\n  at")));
+    summary.record(&result("c", TestStatus::Ignored, None));
+    assert_eq!(summary.passed, 1);
+    assert_eq!(summary.failed, 1);
+    assert_eq!(summary.ignored, 1);
+    assert_eq!(summary.filtered, 1);
+
+    let xml = render_junit(&summary, Duration::from_millis(1500));
+    assert!(xml.contains(
+      "<testsuites name=\"deno test\" tests=\"3\" failures=\"1\" skipped=\"1\" time=\"1.500\">"
+    ));
+    assert!(xml.contains("<testcase name=\"a\" time=\"0.005\"/>"));
+    assert!(xml.contains("<testcase name=\"&lt;b&gt;\" time=\"0.005\">"));
+    assert!(xml.contains("<failure message=\"Error: x\">Error: x\n  at"));
+    assert!(xml.contains("<skipped/>"));
+  }
+}
@@ -1,56 +1,65 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
-use crate::fs as deno_fs;
+use crate::file_collector::FileCollector;
 use crate::installer::is_remote_url;
 use deno_core::ErrBox;
 use std::path::Path;
 use std::path::PathBuf;
 use url::Url;
 
-fn is_supported(p: &Path) -> bool {
+/// Checks if file name matches `{*_,*.,}<name>.{js,ts,jsx,tsx}`.
+fn is_module_named(p: &Path, name: &str) -> bool {
   use std::path::Component;
   if let Some(Component::Normal(basename_os_str)) = p.components().next_back() {
     let basename = basename_os_str.to_string_lossy();
-    basename.ends_with("_test.ts")
-      || basename.ends_with("_test.tsx")
-      || basename.ends_with("_test.js")
-      || basename.ends_with("_test.jsx")
-      || basename.ends_with(".test.ts")
-      || basename.ends_with(".test.tsx")
-      || basename.ends_with(".test.js")
-      || basename.ends_with(".test.jsx")
-      || basename == "test.ts"
-      || basename == "test.tsx"
-      || basename == "test.js"
-      || basename == "test.jsx"
+    ["ts", "tsx", "js", "jsx"].iter().any(|ext| {
+      let file_name = format!("{}.{}", name, ext);
+      basename == file_name
+        || basename.ends_with(&format!("_{}", file_name))
+        || basename.ends_with(&format!(".{}", file_name))
+    })
   } else {
     false
   }
 }
 
+fn is_supported(p: &Path) -> bool {
+  is_module_named(p, "test")
+}
+
+fn is_supported_bench(p: &Path) -> bool {
+  is_module_named(p, "bench")
+}
+
 pub fn prepare_test_modules_urls(
   include: Vec<String>,
   root_path: &PathBuf,
+) -> Result<Vec<Url>, ErrBox> {
+  prepare_modules_urls(include, root_path, is_supported)
+}
+
+pub fn prepare_bench_modules_urls(
+  include: Vec<String>,
+  root_path: &PathBuf,
+) -> Result<Vec<Url>, ErrBox> {
+  prepare_modules_urls(include, root_path, is_supported_bench)
+}
+
+/// Expands directories and globs in `include` to files matching
+/// `is_supported`.
+fn prepare_modules_urls(
+  include: Vec<String>,
+  root_path: &PathBuf,
+  is_supported: fn(&Path) -> bool,
 ) -> Result<Vec<Url>, ErrBox> {
   let (include_paths, include_urls): (Vec<String>, Vec<String>) =
     include.into_iter().partition(|n| !is_remote_url(n));
 
-  let mut prepared = vec![];
-
-  for path in include_paths {
-    let p = deno_fs::normalize_path(&root_path.join(path));
-    if p.is_dir() {
-      let test_files = crate::fs::files_in_subtree(p, is_supported);
-      let test_files_as_urls = test_files
-        .iter()
-        .map(|f| Url::from_file_path(f).unwrap())
-        .collect::<Vec<Url>>();
-      prepared.extend(test_files_as_urls);
-    } else {
-      let url = Url::from_file_path(p).unwrap();
-      prepared.push(url);
-    }
-  }
+  let mut prepared: Vec<Url> = FileCollector::new(is_supported)
+    .collect(root_path, &include_paths)?
+    .iter()
+    .map(|f| Url::from_file_path(f).unwrap())
+    .collect();
 
   for remote_url in include_urls {
     let url = Url::parse(&remote_url)?;
@@ -64,6 +73,8 @@ pub fn render_test_file(
   modules: Vec<Url>,
   fail_fast: bool,
   quiet: bool,
+  trace_ops: bool,
+  coverage: bool,
   filter: Option<String>,
 ) -> String {
   let mut test_file = "".to_string();
@@ -72,11 +83,19 @@ pub fn render_test_file(
     test_file.push_str(&format!("import \"{}\";\n", module.to_string()));
   }
 
-  let options = if let Some(filter) = filter {
-    json!({ "failFast": fail_fast, "reportToConsole": !quiet, "disableLog": quiet, "filter": filter })
-  } else {
-    json!({ "failFast": fail_fast, "reportToConsole": !quiet, "disableLog": quiet })
-  };
+  // Results are sent to `TestCollector` instead of being printed by JS.
+  let mut options = json!({
+    "failFast": fail_fast,
+    "exitOnFail": false,
+    "reportToConsole": false,
+    "reportToRunner": true,
+    "disableLog": quiet,
+    "traceOps": trace_ops,
+    "coverage": coverage,
+  });
+  if let Some(filter) = filter {
+    options["filter"] = json!(filter);
+  }
 
   let run_tests_cmd = format!(
     "// @ts-ignore\nDeno[Deno.internal].runTests({});\n",
@@ -87,6 +106,31 @@ pub fn render_test_file(
   test_file
 }
 
+pub fn render_bench_file(
+  modules: Vec<Url>,
+  profile: bool,
+  filter: Option<String>,
+) -> String {
+  let mut bench_file = "".to_string();
+
+  for module in modules {
+    bench_file.push_str(&format!("import \"{}\";\n", module.to_string()));
+  }
+
+  let mut options = json!({ "profile": profile });
+  if let Some(filter) = filter {
+    options["filter"] = json!(filter);
+  }
+
+  let run_benchmarks_cmd = format!(
+    "// @ts-ignore\nDeno[Deno.internal].runBenchmarks({});\n",
+    options
+  );
+  bench_file.push_str(&run_benchmarks_cmd);
+
+  bench_file
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -143,6 +187,15 @@ mod tests {
     assert!(!is_supported(Path::new("NotAtest.ts")));
   }
 
+  #[test]
+  fn test_is_supported_bench() {
+    assert!(is_supported_bench(Path::new("tests/subdir/foo_bench.ts")));
+    assert!(is_supported_bench(Path::new("bar/foo.bench.js")));
+    assert!(is_supported_bench(Path::new("foo/bar/bench.tsx")));
+    assert!(!is_supported_bench(Path::new("foo/bar/foo_test.ts")));
+    assert!(!is_supported_bench(Path::new("foobench.ts")));
+  }
+
   #[test]
   fn supports_dirs() {
     let root = test_util::root_path().join("std").join("http");
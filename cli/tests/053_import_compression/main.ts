@@ -11,3 +11,8 @@ console.log(
     "http://127.0.0.1:4545/cli/tests/053_import_compression/brotli"
   ).then((res) => res.text())
 );
+console.log(
+  await fetch(
+    "http://127.0.0.1:4545/cli/tests/053_import_compression/deflated"
+  ).then((res) => res.text())
+);
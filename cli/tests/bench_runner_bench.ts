@@ -0,0 +1,15 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+Deno.bench({
+  name: "noop",
+  group: "noop",
+  fn() {},
+});
+
+Deno.bench({
+  name: "async noop",
+  group: "noop",
+  async fn() {},
+});
+
+Deno.bench({ name: "ignored", ignore: true, fn() {} });
@@ -0,0 +1,11 @@
+/** Doubles the value, the only export `mod.ts` uses. */
+export function double(value: number): string {
+  const doubledValue = value * 2;
+  return `doubled: ${doubledValue}`;
+}
+
+export function unusedHelper(): string {
+  return "unused helper";
+}
+
+export const unusedConstant = "unused constant";
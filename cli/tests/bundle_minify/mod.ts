@@ -0,0 +1,3 @@
+import { double } from "./lib.ts";
+
+console.log(double(21));
@@ -1,7 +1,7 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
 
 import { assert, assertEquals } from "../../std/testing/asserts.ts";
-const { compile, transpileOnly, bundle, test } = Deno;
+const { compile, transpileOnly, bundle, emit, graph, test } = Deno;
 
 test("compilerApiCompileSources", async function () {
   const [diagnostics, actual] = await compile("/foo.ts", {
@@ -102,6 +102,19 @@ test("transpileOnlyApiConfig", async function () {
   assert(actual["foo.ts"].map == null);
 });
 
+test("transpileOnlyApiDecorators", async function () {
+  const actual = await transpileOnly(
+    {
+      "foo.ts": `function a(_target: unknown) {}\n@a\nexport class A {}\n`,
+    },
+    {
+      experimentalDecorators: true,
+    }
+  );
+  assert(actual);
+  assert(actual["foo.ts"].source.includes("__decorate(["));
+});
+
 test("bundleApiSources", async function () {
   const [diagnostics, actual] = await bundle("/foo.ts", {
     "/foo.ts": `export * from "./bar.ts";\n`,
@@ -150,3 +163,69 @@ test("diagnosticsTest", async function () {
   assert(Array.isArray(diagnostics));
   assert(diagnostics.length === 1);
 });
+
+test("emitApiSources", async function () {
+  const { diagnostics, files } = await emit("/foo.ts", {
+    sources: {
+      "/foo.ts": `import * as bar from "./bar.ts";\n\nconsole.log(bar);\n`,
+      "/bar.ts": `export const bar = "bar";\n`,
+    },
+  });
+  assertEquals(diagnostics, []);
+  assertEquals(Object.keys(files), [
+    "/bar.js.map",
+    "/bar.js",
+    "/foo.js.map",
+    "/foo.js",
+  ]);
+});
+
+test("emitApiBundle", async function () {
+  const { diagnostics, files } = await emit("/foo.ts", {
+    bundle: "esm",
+    sources: {
+      "/foo.ts": `export * from "./bar.ts";\n`,
+      "/bar.ts": `export const bar = "bar";\n`,
+    },
+  });
+  assertEquals(diagnostics, []);
+  assertEquals(Object.keys(files), ["deno:///bundle.js"]);
+  assert(files["deno:///bundle.js"].includes(`__instantiate("foo")`));
+});
+
+test("emitApiCheck", async function () {
+  const sources = { "/foo.ts": `const a: string = 1;\n` };
+  const { diagnostics } = await emit("/foo.ts", { sources });
+  assertEquals(diagnostics.length, 1);
+
+  const { diagnostics: unchecked, files } = await emit("/foo.ts", {
+    check: false,
+    compilerOptions: { sourceMap: false },
+    sources,
+  });
+  assertEquals(unchecked, []);
+  assertEquals(Object.keys(files), ["/foo.js"]);
+});
+
+test("graphApiSources", async function () {
+  const { root, modules } = await graph("/foo.ts", {
+    sources: {
+      "/foo.ts": `export * from "./bar.ts";\n`,
+      "/bar.ts": `import "./baz.js";\nexport const bar = "bar";\n`,
+      "/baz.js": `console.log("baz");\n`,
+    },
+  });
+  assertEquals(root, "/foo.ts");
+  assertEquals(modules, {
+    "/foo.ts": { dependencies: ["/bar.ts"] },
+    "/bar.ts": { dependencies: ["/baz.js"] },
+    "/baz.js": { dependencies: [] },
+  });
+});
+
+test("graphApiNoSources", async function () {
+  const { root, modules } = await graph("./subdir/mod1.ts");
+  assert(root.endsWith("/subdir/mod1.ts"));
+  assertEquals(modules[root].dependencies.length, 1);
+  assert(modules[root].dependencies[0].endsWith("/subdir/subdir2/mod2.ts"));
+});
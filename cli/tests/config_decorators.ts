@@ -0,0 +1,20 @@
+function log(
+  _target: unknown,
+  key: string,
+  descriptor: PropertyDescriptor
+): void {
+  const method = descriptor.value;
+  descriptor.value = function (...args: unknown[]): unknown {
+    console.log(`calling ${key}`);
+    return method.apply(this, args);
+  };
+}
+
+class A {
+  @log
+  hello(): void {
+    console.log("hello");
+  }
+}
+
+new A().hello();
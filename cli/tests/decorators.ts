@@ -0,0 +1,39 @@
+// A stand-in for the `reflect-metadata` package.
+const metadata = new Map<string, unknown>();
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+(Reflect as any).metadata = (key: string, value: unknown) => (
+  _target: unknown,
+  property: string
+): void => {
+  metadata.set(`${property}:${key}`, value);
+};
+
+const fields: string[] = [];
+
+function field(_target: unknown, property: string): void {
+  fields.push(property);
+}
+
+function logged(name: string) {
+  return function <T extends { new (...args: any[]): {} }>(constructor: T): T {
+    return class extends constructor {
+      constructor(...args: any[]) {
+        super(...args);
+        console.log(`created ${name}`);
+      }
+    };
+  };
+}
+
+@logged("user")
+class User {
+  // Initialized fields are assigned in the constructor, after the decorators
+  // of the class ran.
+  @field
+  name: string = "deno";
+}
+
+const user = new User();
+console.log(user.name);
+console.log(fields);
+console.log(metadata.get("name:design:type") === String);
@@ -0,0 +1,9 @@
+declare const DEBUG: boolean;
+
+if (DEBUG) {
+  console.log("debug build");
+} else {
+  console.log("release build");
+}
+console.log(DENO_ENV === "production" ? "production" : "development");
+console.log(process.env.NODE_ENV);
@@ -0,0 +1,14 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+/**
+ * Adds two numbers.
+ *
+ * ```ts
+ * if (add(1, 2) !== 3) {
+ *   throw new Error("add(1, 2) should be 3");
+ * }
+ * ```
+ */
+export function add(a: number, b: number): number {
+  return a + b;
+}
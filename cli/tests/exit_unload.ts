@@ -0,0 +1,9 @@
+window.addEventListener("unload", () => {
+  console.log("unload");
+  // Exit code of the first call wins.
+  Deno.exit(1);
+});
+setTimeout(() => console.log("timeout"), 10000);
+console.log("before");
+Deno.exit(3);
+console.log("after");
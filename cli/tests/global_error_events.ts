@@ -0,0 +1,25 @@
+addEventListener("error", (e: Event): void => {
+  console.log(`error: ${(e as ErrorEvent).message}`);
+  e.preventDefault();
+});
+
+onunhandledrejection = (e: PromiseRejectionEvent): void => {
+  console.log(`unhandledrejection: ${e.reason}`);
+  e.preventDefault();
+};
+
+onunload = (): void => {
+  console.log("unload");
+};
+
+addEventListener("load", (): void => {
+  throw new Error("thrown by a listener");
+});
+
+setTimeout((): void => {
+  throw new Error("thrown by a timer");
+});
+
+Promise.reject("rejected");
+// A rejection that gets a handler in the same tick isn't dispatched.
+Promise.reject("caught").catch((): void => {});
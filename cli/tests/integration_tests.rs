@@ -50,6 +50,20 @@ fn no_color() {
   let stdout_str = std::str::from_utf8(&output.stdout).unwrap().trim();
   assert_eq!("noColor true", stdout_str);
 
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("run")
+    .arg("--no-color")
+    .arg("cli/tests/no_color.js")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap().trim();
+  assert_eq!("noColor true", stdout_str);
+
   let output = util::deno_cmd()
     .current_dir(util::root_path())
     .arg("run")
@@ -115,6 +129,28 @@ pub fn test_raw_tty() {
   }
 }
 
+#[test]
+fn web_storage_persists_per_origin() {
+  let deno_dir = TempDir::new().expect("tempdir fail");
+  let run = |location: &str| {
+    let output = util::deno_cmd()
+      .env("DENO_DIR", deno_dir.path())
+      .current_dir(util::root_path())
+      .arg("run")
+      .arg("--location")
+      .arg(location)
+      .arg("cli/tests/web_storage.ts")
+      .output()
+      .expect("failed to spawn script");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+  };
+  assert_eq!(run("https://example.com/"), "visits: 1\n");
+  // Only `localStorage` is kept across runs, per origin.
+  assert_eq!(run("https://example.com/app/"), "visits: 2\n");
+  assert_eq!(run("https://example.org/"), "visits: 1\n");
+}
+
 #[test]
 fn test_pattern_match() {
   assert!(util::pattern_match("foo[BAR]baz", "foobarbaz", "[BAR]"));
@@ -222,6 +258,115 @@ fn fmt_stdin_error() {
   assert!(!output.status.success());
 }
 
+#[test]
+fn dump_metrics() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let metrics_path = temp_dir.path().join("metrics.json");
+  let status = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("run")
+    .arg(format!("--dump-metrics={}", metrics_path.display()))
+    .arg("cli/tests/dump_metrics.js")
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let metrics: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&metrics_path).unwrap())
+      .unwrap();
+  let op_write = &metrics["ops"]["op_write"];
+  assert_eq!(op_write["opsDispatchedAsync"], 1);
+  assert_eq!(op_write["opsCompletedAsync"], 1);
+  assert_eq!(op_write["bytesSentData"], 6);
+  assert!(metrics["opsDispatched"].as_u64().unwrap() > 1);
+}
+
+#[test]
+fn trace_ops() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let trace_path = temp_dir.path().join("trace.json");
+  let status = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("run")
+    .arg(format!("--trace-ops={}", trace_path.display()))
+    .arg("cli/tests/trace_ops.js")
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let trace: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&trace_path).unwrap())
+      .unwrap();
+  let events = trace["traceEvents"].as_array().unwrap();
+  let has_event = |cat: &str, name: &str, ph: &str| {
+    events
+      .iter()
+      .any(|e| e["cat"] == cat && e["name"] == name && e["ph"] == ph)
+  };
+  assert!(has_event("op", "op_write", "b"));
+  assert!(has_event("op", "op_write", "e"));
+  assert!(has_event("event_loop", "tick", "X"));
+  assert!(has_event("module", "fetch", "b"));
+  assert!(has_event("module", "evaluate", "X"));
+}
+
+#[test]
+fn test_coverage() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let coverage_dir = temp_dir.path().join("cov");
+  let status = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("test")
+    .arg(format!("--coverage={}", coverage_dir.display()))
+    .arg("test_runner_pass_test.ts")
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let files: Vec<_> = std::fs::read_dir(&coverage_dir).unwrap().collect();
+  assert_eq!(files.len(), 1);
+  let path = files[0].as_ref().unwrap().path();
+  let coverage: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+  let scripts = coverage["result"].as_array().unwrap();
+  assert!(scripts
+    .iter()
+    .all(|s| s["url"].as_str().unwrap().starts_with("file://")));
+  assert!(scripts.iter().any(|s| s["url"]
+    .as_str()
+    .unwrap()
+    .ends_with("test_runner_pass_test.ts")));
+}
+
+#[test]
+fn bench_profile() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let profile_path = temp_dir.path().join("bench.cpuprofile");
+  let status = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("bench")
+    .arg("--unstable")
+    .arg(format!("--profile={}", profile_path.display()))
+    .arg("bench_runner_bench.ts")
+    .stdout(std::process::Stdio::null())
+    .spawn()
+    .unwrap()
+    .wait()
+    .unwrap();
+  assert!(status.success());
+  let profile: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&profile_path).unwrap())
+      .unwrap();
+  assert!(!profile["nodes"].as_array().unwrap().is_empty());
+  assert!(profile["endTime"].as_f64().unwrap() > 0.0);
+}
+
 // Warning: this test requires internet access.
 #[test]
 fn upgrade_in_tmpdir() {
@@ -316,6 +461,224 @@ fn installer_test_local_module_run() {
   drop(temp_dir);
 }
 
+#[test]
+fn compile_standalone_run() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let mut exe_path = temp_dir.path().join("echo");
+  if cfg!(windows) {
+    exe_path = exe_path.with_extension("exe");
+  }
+  let status = Command::new(util::deno_exe_path())
+    .current_dir(util::root_path())
+    .arg("compile")
+    .arg("--output")
+    .arg(temp_dir.path().join("echo"))
+    .arg("cli/tests/echo.ts")
+    .spawn()
+    .expect("failed to spawn script")
+    .wait()
+    .expect("failed to wait for child process");
+  assert!(status.success());
+  assert!(exe_path.exists());
+
+  let output = Command::new(exe_path)
+    .current_dir(temp_dir.path())
+    .arg("foo")
+    .arg("--allow-net")
+    .output()
+    .expect("failed to spawn script");
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  // Arguments are passed to the script, not parsed as flags.
+  assert_eq!(stdout_str, "foo, --allow-net");
+  drop(temp_dir);
+}
+
+#[test]
+fn task_runner() {
+  let output = Command::new(util::deno_exe_path())
+    .current_dir(util::tests_path())
+    .env("PATH", util::target_dir())
+    .arg("task")
+    .arg("--config")
+    .arg("task/deno.json")
+    .arg("greet")
+    .arg("extra")
+    .output()
+    .expect("failed to spawn script");
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap().trim();
+  assert_eq!(stdout_str, r#"hello [ "hello world", "extra" ]"#);
+
+  let status = Command::new(util::deno_exe_path())
+    .current_dir(util::tests_path().join("task"))
+    .env("PATH", util::target_dir())
+    .arg("task")
+    .arg("fail")
+    .output()
+    .expect("failed to spawn script")
+    .status;
+  assert_eq!(status.code(), Some(3));
+}
+
+#[test]
+fn lint_config_and_ignore_directives() {
+  let output = Command::new(util::deno_exe_path())
+    .current_dir(util::tests_path().join("lint"))
+    .env("NO_COLOR", "1")
+    .arg("lint")
+    .output()
+    .expect("failed to spawn script");
+  assert!(!output.status.success());
+  let stderr_str = std::str::from_utf8(&output.stderr).unwrap();
+  assert!(stderr_str.contains("(no-var) `var` keyword is not allowed"));
+  assert!(stderr_str.contains("main.ts:4:2"));
+  assert!(stderr_str.contains("(eqeqeq)"));
+  assert!(stderr_str.contains("main.ts:9:9"));
+  assert!(!stderr_str.contains("no-debugger"));
+  assert!(!stderr_str.contains("no-explicit-any"));
+  assert!(!stderr_str.contains("bad.ts"));
+  assert!(stderr_str.contains("Found 2 problems"));
+
+  let output = Command::new(util::deno_exe_path())
+    .current_dir(util::tests_path().join("lint"))
+    .env("NO_COLOR", "1")
+    .arg("lint")
+    .arg("--rules")
+    .output()
+    .expect("failed to spawn script");
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  assert!(stdout_str.contains("- eqeqeq (enabled, fixable)"));
+  assert!(stdout_str.contains("- no-explicit-any (recoverable, recommended)"));
+  assert!(stdout_str.contains("https://deno.land/manual/tools/linter#no-var"));
+}
+
+#[test]
+fn lint_fix() {
+  let temp_dir = TempDir::new().expect("tempdir fail");
+  let file_path = temp_dir.path().join("fix.ts");
+  std::fs::write(
+    &file_path,
+    "import { a, b } from \"./a.ts\";\nlet c = a == 1;\nconsole.log(c);\n",
+  )
+  .unwrap();
+  let status = Command::new(util::deno_exe_path())
+    .current_dir(temp_dir.path())
+    .arg("lint")
+    .arg("--fix")
+    .arg("fix.ts")
+    .spawn()
+    .expect("failed to spawn script")
+    .wait()
+    .expect("failed to wait for the child process");
+  assert!(status.success());
+  assert_eq!(
+    std::fs::read_to_string(&file_path).unwrap(),
+    "import { a } from \"./a.ts\";\nconst c = a === 1;\nconsole.log(c);\n"
+  );
+}
+
+#[test]
+fn lsp_initialize_diagnostics_shutdown() {
+  use std::io::Write;
+
+  let uri = url::Url::from_file_path(util::tests_path().join("lsp_test.ts"))
+    .unwrap()
+    .to_string();
+  let messages = vec![
+    r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+    format!(
+      r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"{}","languageId":"typescript","version":1,"text":"const a: number = \"a\";\n"}}}}}}"#,
+      uri
+    ),
+    r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#.to_string(),
+    r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+  ];
+
+  let mut child = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("lsp")
+    .stdin(std::process::Stdio::piped())
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let stdin = child.stdin.as_mut().unwrap();
+  for message in messages {
+    write!(
+      stdin,
+      "Content-Length: {}\r\n\r\n{}",
+      message.len(),
+      message
+    )
+    .unwrap();
+  }
+  let output = child.wait_with_output().unwrap();
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  assert!(stdout_str.contains(r#""name":"deno-language-server""#));
+  assert!(stdout_str.contains("textDocument/publishDiagnostics"));
+  assert!(stdout_str.contains(r#""code":2322"#));
+  assert!(stdout_str.contains(r#"{"jsonrpc":"2.0","id":2,"result":null}"#));
+}
+
+#[test]
+fn types_lib() {
+  let output = util::deno_cmd()
+    .arg("types")
+    .arg("--lib")
+    .arg("deno.worker")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  assert!(stdout_str.contains("// lib.deno.worker.d.ts"));
+  assert!(stdout_str.contains("// lib.esnext.d.ts"));
+  assert!(!stdout_str.contains("// lib.deno.window.d.ts"));
+
+  let output = util::deno_cmd()
+    .arg("types")
+    .arg("--lib")
+    .arg("foo")
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr_str = std::str::from_utf8(&output.stderr).unwrap();
+  assert!(stderr_str.contains("Unknown lib \"foo\""));
+}
+
+#[test]
+fn types_unstable() {
+  let output = util::deno_cmd()
+    .arg("types")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  assert!(!stdout_str.contains("export function loadavg()"));
+
+  let output = util::deno_cmd()
+    .arg("types")
+    .arg("--unstable")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let stdout_str = std::str::from_utf8(&output.stdout).unwrap();
+  assert!(stdout_str.contains("export function loadavg()"));
+}
+
 #[test]
 fn installer_test_remote_module_run() {
   let g = util::http_server();
@@ -412,6 +775,78 @@ fn bundle_exports() {
   assert_eq!(output.stderr, b"");
 }
 
+#[test]
+fn bundle_minify() {
+  let mod_ts = util::root_path().join("cli/tests/bundle_minify/mod.ts");
+  assert!(mod_ts.is_file());
+  let t = TempDir::new().expect("tempdir fail");
+  let bundle = t.path().join("mod.bundle.js");
+  let status = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("bundle")
+    .arg("--minify")
+    .arg(mod_ts)
+    .arg(&bundle)
+    .spawn()
+    .expect("failed to spawn script")
+    .wait()
+    .expect("failed to wait for the child process");
+  assert!(status.success());
+  let output = std::fs::read_to_string(&bundle).unwrap();
+  // Unused exports, comments and local names are left out.
+  assert!(!output.contains("unused helper"));
+  assert!(!output.contains("unused constant"));
+  assert!(!output.contains("Doubles the value"));
+  assert!(!output.contains("doubledValue"));
+  assert!(output.ends_with("//# sourceMappingURL=mod.bundle.js.map\n"));
+  let source_map = std::fs::read_to_string(t.path().join("mod.bundle.js.map"))
+    .expect("source map not written");
+  let source_map: serde_json::Value =
+    serde_json::from_str(&source_map).unwrap();
+  assert!(source_map["mappings"].as_str().unwrap().starts_with(';'));
+
+  let output = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("run")
+    .arg(&bundle)
+    .output()
+    .expect("failed to spawn script");
+  assert_eq!(
+    std::str::from_utf8(&output.stdout).unwrap(),
+    "doubled: 42\n"
+  );
+  assert_eq!(output.stderr, b"");
+}
+
+#[test]
+fn bundle_define() {
+  let define = util::root_path().join("cli/tests/define.ts");
+  assert!(define.is_file());
+  let t = TempDir::new().expect("tempdir fail");
+  let bundle = t.path().join("define.bundle.js");
+  let status = util::deno_cmd()
+    .current_dir(util::root_path())
+    .arg("bundle")
+    .arg("--define")
+    .arg("DEBUG=false")
+    .arg("--define")
+    .arg("DENO_ENV=production")
+    .arg("--define")
+    .arg("process.env.NODE_ENV=production")
+    .arg(define)
+    .arg(&bundle)
+    .spawn()
+    .expect("failed to spawn script")
+    .wait()
+    .expect("failed to wait for the child process");
+  assert!(status.success());
+  let output = std::fs::read_to_string(&bundle).unwrap();
+  // The branches that can't run are left out.
+  assert!(output.contains("release build"));
+  assert!(!output.contains("debug build"));
+  assert!(!output.contains("development"));
+}
+
 #[test]
 fn bundle_circular() {
   // First we have to generate a bundle of some module that has exports.
@@ -651,7 +1086,7 @@ fn repl_test_eof() {
   assert!(err.is_empty());
 }
 
-const REPL_MSG: &str = "exit using ctrl+d or close()\n";
+const REPL_MSG: &str = "exit using ctrl+d, ctrl+c twice or close()\n";
 
 #[test]
 fn repl_test_close_command() {
@@ -691,6 +1126,84 @@ fn repl_test_multiline() {
   assert!(err.is_empty());
 }
 
+#[test]
+fn repl_test_multiline_template_substitution() {
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl",
+    Some(vec!["`${", "1 + 2}`"]),
+    None,
+    false,
+  );
+  assert!(out.ends_with("3\n"));
+  assert!(err.is_empty());
+}
+
+#[test]
+fn repl_test_multiline_brackets_in_strings_and_comments() {
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl",
+    Some(vec!["[\"{\", /* ( */ '[', /[(]/.source, // {", "].length"]),
+    None,
+    false,
+  );
+  assert!(out.ends_with("3\n"));
+  assert!(err.is_empty());
+}
+
+#[test]
+fn repl_test_redeclare_const() {
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl",
+    Some(vec![
+      "const x = 1",
+      "const x = 2; let y = 'const z'",
+      "x + y",
+    ]),
+    None,
+    false,
+  );
+  assert!(out.ends_with("2const z\n"));
+  assert!(err.is_empty());
+}
+
+#[test]
+fn repl_test_redeclare_class() {
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl",
+    Some(vec![
+      "class A {}",
+      "class A { static f() { return 3 } }",
+      "A.f()",
+    ]),
+    None,
+    false,
+  );
+  assert!(out.ends_with("3\n"));
+  assert!(err.is_empty());
+}
+
+#[test]
+fn repl_test_check() {
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl --check",
+    Some(vec![
+      "const a: number = \"a\";",
+      "interface Point { x: number }",
+      "const p: Point = { x: 2 }; p.x",
+    ]),
+    None,
+    false,
+  );
+  assert!(out.ends_with("2\n"));
+  // Diagnostics of previous input aren't reported again.
+  assert_eq!(err.matches("TS2322").count(), 1);
+}
+
 #[test]
 fn repl_test_eval_unterminated() {
   let (out, err) = util::run_and_collect_output(
@@ -879,6 +1392,41 @@ macro_rules! itest_ignore(
   }
 );
 
+itest!(v8_flags_help {
+  args: "run --v8-flags=--help 002_hello.ts",
+  output: "v8_flags_help.out",
+});
+
+itest!(v8_flags_unrecognized {
+  args: "run --v8-flags=--foo,--expose-gc,--bar 002_hello.ts",
+  output: "v8_flags_unrecognized.out",
+  check_stderr: true,
+  exit_code: 1,
+});
+
+itest!(uncaught_exit_code {
+  args: "run --reload --uncaught-exit-code=3 uncaught_exit_code.ts",
+  output: "uncaught_exit_code.ts.out",
+  exit_code: 3,
+});
+
+itest!(uncaught_rejection_exit_code {
+  args: "run --reload --uncaught-exit-code=3 uncaught_rejection_exit_code.ts",
+  output: "uncaught_rejection_exit_code.ts.out",
+  exit_code: 3,
+});
+
+itest!(global_error_events {
+  args: "run --reload global_error_events.ts",
+  output: "global_error_events.ts.out",
+});
+
+itest!(listener_error_uncaught {
+  args: "run --reload --uncaught-exit-code=3 listener_error_uncaught.ts",
+  output: "listener_error_uncaught.ts.out",
+  exit_code: 3,
+});
+
 itest!(_001_hello {
   args: "run --reload 001_hello.js",
   output: "001_hello.js.out",
@@ -910,6 +1458,28 @@ itest!(_006_url_imports {
   http_server: true,
 });
 
+itest!(remote_type_error {
+  args: "run --reload no_remote_check.ts",
+  output: "remote_type_error.ts.out",
+  check_stderr: true,
+  exit_code: 1,
+  http_server: true,
+});
+
+itest!(no_remote_check {
+  args: "run --reload --no-remote-check no_remote_check.ts",
+  output: "no_remote_check.ts.out",
+  http_server: true,
+});
+
+itest!(no_remote_check_local_error {
+  args: "run --reload --no-remote-check no_remote_check_local_error.ts",
+  output: "no_remote_check_local_error.ts.out",
+  check_stderr: true,
+  exit_code: 1,
+  http_server: true,
+});
+
 itest!(_012_async {
   args: "run --reload 012_async.ts",
   output: "012_async.ts.out",
@@ -999,6 +1569,11 @@ itest!(_026_redirect_javascript {
   http_server: true,
 });
 
+itest!(define {
+  args: "run --reload --define DEBUG=false --define DENO_ENV=production --define process.env.NODE_ENV=production define.ts",
+  output: "define.ts.out",
+});
+
 itest!(deno_test_fail_fast {
   args: "test --failfast test_runner_test.ts",
   exit_code: 1,
@@ -1011,6 +1586,39 @@ itest!(deno_test {
   output: "deno_test.out",
 });
 
+itest!(deno_bench {
+  args: "bench --unstable bench_runner_bench.ts",
+  output: "deno_bench.out",
+});
+
+itest!(deno_test_jobs {
+  args: "test --jobs=2 test_runner_test.ts test_runner_pass_test.ts",
+  exit_code: 1,
+  output: "deno_test_jobs.out",
+});
+
+itest!(deno_test_junit {
+  args: "test --reporter=junit test_runner_pass_test.ts",
+  output: "deno_test_junit.out",
+});
+
+itest!(deno_test_doc {
+  args: "test --doc doc_test/",
+  output: "deno_test_doc.out",
+});
+
+itest!(deno_test_leaks {
+  args: "test --trace-ops test_leaks_test.ts",
+  exit_code: 1,
+  output: "deno_test_leaks.out",
+});
+
+itest!(deno_test_permissions {
+  args: "test --unstable test_permissions_test.ts",
+  exit_code: 1,
+  output: "deno_test_permissions.out",
+});
+
 #[test]
 fn workers() {
   let g = util::http_server();
@@ -1050,6 +1658,16 @@ itest!(_027_redirect_typescript {
   http_server: true,
 });
 
+itest!(open_plugin_missing {
+  args: "run --unstable --allow-plugin open_plugin_missing.js",
+  output: "open_plugin_missing.js.out",
+});
+
+itest!(main_module {
+  args: "run main_module.ts",
+  output: "main_module.ts.out",
+});
+
 itest!(_028_args {
   args: "run --reload 028_args.ts --arg1 val1 --arg2=val2 -- arg3 arg4",
   output: "028_args.ts.out",
@@ -1066,6 +1684,43 @@ itest!(_030_eval_ts {
   output: "030_eval_ts.out",
 });
 
+itest!(eval_print {
+  args: "eval -p 1+2",
+  output_str: Some("3\n"),
+});
+
+itest!(eval_print_statements {
+  args: "eval -p -",
+  input: Some("const a = 1;\nconst b = 2;\nif (a < b) { a + b; }\n"),
+  output_str: Some("3\n"),
+});
+
+itest!(eval_print_ts {
+  args: "eval -T -p -",
+  input: Some("const a: number = 1;\na + 1;\n"),
+  output_str: Some("2\n"),
+});
+
+itest!(eval_stdin {
+  args: "eval -",
+  input: Some("console.log(\"hello from stdin\");\n"),
+  output_str: Some("hello from stdin\n"),
+});
+
+itest!(run_stdin {
+  args: "run -",
+  input: Some(
+    "import { printHello } from './subdir/print_hello.ts';\nprintHello();\n"
+  ),
+  output_str: Some("Hello\n"),
+});
+
+itest!(run_stdin_ts {
+  args: "run --ext=ts -",
+  input: Some("const n: number = 3;\nconsole.log(n);\n"),
+  output_str: Some("3\n"),
+});
+
 itest!(_033_import_map {
   args:
     "run --reload --importmap=importmaps/import_map.json --unstable importmaps/test.ts",
@@ -1125,6 +1780,11 @@ itest!(_041_info_flag {
   output: "041_info_flag.out",
 });
 
+itest!(info_json {
+  args: "info --json",
+  output: "info_json.out",
+});
+
 itest!(_042_dyn_import_evalcontext {
   args: "run --allow-read --reload 042_dyn_import_evalcontext.ts",
   output: "042_dyn_import_evalcontext.ts.out",
@@ -1234,6 +1894,20 @@ itest!(lock_check_err {
   http_server: true,
 });
 
+itest!(cache_reload_lock_check_ok {
+  args: "cache --reload --lock=lock_check_ok.json http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+  output_str: Some(""),
+  http_server: true,
+});
+
+itest!(cache_reload_lock_check_err {
+  args: "cache --reload --lock=lock_check_err.json http://127.0.0.1:4545/cli/tests/003_relative_import.ts",
+  output: "lock_check_err.out",
+  check_stderr: true,
+  exit_code: 10,
+  http_server: true,
+});
+
 // TODO(ry) Re-enable flaky test https://github.com/denoland/deno/issues/4049
 itest_ignore!(lock_check_err2 {
   args: "run --lock=lock_check_err2.json 019_media_types.ts",
@@ -1285,6 +1959,18 @@ itest!(config {
   output: "config.ts.out",
 });
 
+itest!(config_decorators {
+  args:
+    "run --reload --config config_decorators.tsconfig.json config_decorators.ts",
+  check_stderr: true,
+  output: "config_decorators.ts.out",
+});
+
+itest!(decorators {
+  args: "run --reload --config decorators.tsconfig.json decorators.ts",
+  output: "decorators.ts.out",
+});
+
 itest!(error_001 {
   args: "run --reload error_001.ts",
   check_stderr: true,
@@ -1292,6 +1978,20 @@ itest!(error_001 {
   output: "error_001.ts.out",
 });
 
+itest!(error_001_inline_source_maps {
+  args: "run --reload --source-maps=inline error_001.ts",
+  check_stderr: true,
+  exit_code: 1,
+  output: "error_001.ts.out",
+});
+
+itest!(error_001_no_source_maps {
+  args: "run --reload --source-maps=none error_001.ts",
+  check_stderr: true,
+  exit_code: 1,
+  output: "error_001_no_source_maps.out",
+});
+
 itest!(error_002 {
   args: "run --reload error_002.ts",
   check_stderr: true,
@@ -1501,6 +2201,48 @@ itest!(exit_error42 {
   output: "exit_error42.ts.out",
 });
 
+itest!(exit_unload {
+  exit_code: 3,
+  args: "run --reload exit_unload.ts",
+  output: "exit_unload.ts.out",
+});
+
+#[cfg(unix)]
+#[test]
+fn sigint_unload() {
+  use nix::sys::signal::{kill, Signal};
+  use nix::unistd::Pid;
+
+  let mut child = util::deno_cmd()
+    .current_dir(util::tests_path())
+    .arg("run")
+    .arg("--reload")
+    .arg("sigint_unload.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let stdout = child.stdout.as_mut().unwrap();
+  let mut stdout_lines =
+    std::io::BufReader::new(stdout).lines().map(|r| r.unwrap());
+  assert_eq!(stdout_lines.next().unwrap(), "ready");
+  kill(Pid::from_raw(child.id() as i32), Signal::SIGINT).unwrap();
+  assert_eq!(stdout_lines.next().unwrap(), "unload");
+  let status = child.wait().unwrap();
+  assert_eq!(status.code(), Some(130));
+}
+
+itest!(warn_slow_ops {
+  args: "run --reload --warn-slow-ops=50 warn_slow_ops.js",
+  check_stderr: true,
+  output: "warn_slow_ops.js.out",
+});
+
+itest!(startup_timing {
+  args: "run --reload --startup-timing 001_hello.js",
+  check_stderr: true,
+  output: "startup_timing.out",
+});
+
 itest!(https_import {
   args: "run --reload https_import.ts",
   output: "https_import.ts.out",
@@ -1646,6 +2388,11 @@ itest!(unstable_enabled_js {
   output: "unstable_enabled_js.out",
 });
 
+itest!(unstable_op_disabled {
+  args: "run --reload unstable_op_disabled.js",
+  output: "unstable_op_disabled.out",
+});
+
 itest!(_053_import_compression {
   args: "run --reload --allow-net 053_import_compression/main.ts",
   output: "053_import_compression.out",
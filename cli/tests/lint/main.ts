@@ -0,0 +1,10 @@
+// deno-lint-ignore-file no-debugger
+
+export function isAnswer(value: any): boolean {
+  var answer = 42;
+  // deno-lint-ignore eqeqeq
+  if (value == "42") {
+    debugger;
+  }
+  return value == answer;
+}
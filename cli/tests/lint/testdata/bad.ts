@@ -0,0 +1 @@
+var excluded: any = 1;
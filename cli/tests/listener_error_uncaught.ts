@@ -0,0 +1,11 @@
+addEventListener("load", (): void => {
+  throw new Error("boom");
+});
+
+addEventListener("load", (): void => {
+  console.log("later listeners still run");
+});
+
+onunload = (): void => {
+  console.log("unload");
+};
@@ -0,0 +1 @@
+console.log("main_module", Deno.mainModule);
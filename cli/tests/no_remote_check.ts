@@ -0,0 +1,2 @@
+import { a } from "http://localhost:4545/cli/tests/subdir/type_error.ts";
+console.log(typeof a);
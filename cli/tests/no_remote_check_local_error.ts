@@ -0,0 +1,3 @@
+import { a } from "http://localhost:4545/cli/tests/subdir/type_error.ts";
+const b: boolean = a;
+console.log(b);
@@ -0,0 +1,5 @@
+try {
+  Deno.openPlugin("./does_not_exist.so");
+} catch (e) {
+  console.log("caught", e.name);
+}
@@ -0,0 +1,4 @@
+window.addEventListener("unload", () => console.log("unload"));
+setInterval(() => {}, 1000);
+// Give the runtime time to install the Ctrl-C handler.
+setTimeout(() => console.log("ready"), 100);
@@ -0,0 +1,13 @@
+const channel = new BroadcastChannel("workers");
+
+channel.onmessage = (e) => {
+  // Ignore replies from other workers
+  if (e.data !== "ping") {
+    return;
+  }
+  channel.postMessage(`${self.name}: ${e.data}`);
+  channel.close();
+  close();
+};
+
+postMessage("ready");
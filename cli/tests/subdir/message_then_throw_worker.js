@@ -0,0 +1,4 @@
+onmessage = function (e) {
+  postMessage(e.data);
+  throw new Error("error after message");
+};
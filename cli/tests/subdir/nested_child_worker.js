@@ -0,0 +1,3 @@
+setInterval(() => {
+  postMessage("tick");
+}, 10);
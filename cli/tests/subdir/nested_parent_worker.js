@@ -0,0 +1,10 @@
+// Spawns a child worker that keeps ticking until it's terminated together
+// with this worker.
+const child = new Worker("./nested_child_worker.js", {
+  type: "module",
+  name: "child",
+});
+
+child.onmessage = (e) => {
+  postMessage(e.data);
+};
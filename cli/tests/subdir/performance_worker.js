@@ -0,0 +1,4 @@
+onmessage = function () {
+  performance.mark("worker mark");
+  postMessage(performance.timeOrigin);
+};
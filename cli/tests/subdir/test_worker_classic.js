@@ -0,0 +1,12 @@
+// Classic worker scripts are evaluated as scripts, so top level
+// declarations end up on the global scope.
+var greeting = "Hello World";
+
+if (globalThis.greeting !== greeting) {
+  throw Error("Expected classic worker to run in script scope");
+}
+
+onmessage = function (e) {
+  postMessage(e.data === "ping" ? greeting : e.data);
+  close();
+};
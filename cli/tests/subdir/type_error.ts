@@ -0,0 +1,3 @@
+// This module has a type error, it's imported over the network by the
+// `--no-remote-check` tests.
+export const a: number = "not a number";
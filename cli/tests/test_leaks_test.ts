@@ -0,0 +1,9 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+Deno.test("leakingFile", function () {
+  Deno.openSync("test_leaks_test.ts");
+});
+
+Deno.test("leakingTimer", function () {
+  setTimeout(() => {}, 100);
+});
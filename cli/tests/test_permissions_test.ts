@@ -0,0 +1,31 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+Deno.test({
+  name: "readWhitelisted",
+  permissions: { read: ["./subdir"] },
+  fn() {
+    Deno.readTextFileSync("subdir/config.json");
+  },
+});
+
+Deno.test({
+  name: "readOutsideWhitelist",
+  permissions: { read: ["./subdir"] },
+  fn() {
+    try {
+      Deno.readTextFileSync("test_permissions_test.ts");
+    } catch (err) {
+      if (err instanceof Deno.errors.PermissionDenied) {
+        return;
+      }
+      throw err;
+    }
+    throw new Error("Expected read to be denied");
+  },
+});
+
+Deno.test({
+  name: "escalateNet",
+  permissions: { net: true },
+  fn() {},
+});
@@ -0,0 +1,5 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+Deno.test("pass1", function () {});
+
+Deno.test({ name: "ignored1", ignore: true, fn() {} });
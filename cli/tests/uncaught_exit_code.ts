@@ -0,0 +1,5 @@
+window.addEventListener("unload", (): void => {
+  console.log("unload");
+});
+
+throw new Error("boom");
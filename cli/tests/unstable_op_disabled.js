@@ -0,0 +1,3 @@
+const { op_loadavg: opLoadavg } = Deno.core.ops();
+const response = Deno.core.dispatch(opLoadavg, Deno.core.encode("{}"));
+console.log(JSON.parse(Deno.core.decode(response)).err.message);
@@ -0,0 +1,4 @@
+setTimeout(() => {
+  const start = Date.now();
+  while (Date.now() - start < 100);
+}, 0);
@@ -0,0 +1,4 @@
+const visits = Number(localStorage.getItem("visits") ?? 0) + 1;
+localStorage.setItem("visits", String(visits));
+sessionStorage.setItem("visits", String(visits));
+console.log(`visits: ${visits}`);
@@ -307,3 +307,212 @@ Deno.test({
     w.terminate();
   },
 });
+
+Deno.test({
+  name: "worker classic",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const w = new Worker("../tests/subdir/test_worker_classic.js", {
+      type: "classic",
+    });
+    w.onmessage = (e): void => {
+      assertEquals(e.data, "Hello World");
+      promise.resolve();
+    };
+    w.postMessage("ping");
+    await promise;
+    w.terminate();
+  },
+});
+
+Deno.test({
+  name: "worker from blob URL",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const blob = new Blob(["onmessage = (e) => postMessage(e.data + 1);"], {
+      type: "application/javascript",
+    });
+    const url = URL.createObjectURL(blob);
+    const w = new Worker(url, { type: "module" });
+    w.onmessage = (e): void => {
+      assertEquals(e.data, 42);
+      promise.resolve();
+    };
+    w.postMessage(41);
+    await promise;
+    w.terminate();
+    URL.revokeObjectURL(url);
+  },
+});
+
+Deno.test({
+  name: "worker from data URL",
+  fn: async function (): Promise<void> {
+    const promise1 = createResolvable();
+    const promise2 = createResolvable();
+    const source = "onmessage = (e) => postMessage(e.data.toUpperCase());";
+
+    const plain = new Worker(
+      `data:application/javascript,${encodeURIComponent(source)}`,
+      { type: "classic" }
+    );
+    const base64 = new Worker(
+      `data:application/javascript;base64,${btoa(source)}`,
+      { type: "module" }
+    );
+    plain.onmessage = (e): void => {
+      assertEquals(e.data, "PLAIN");
+      promise1.resolve();
+    };
+    base64.onmessage = (e): void => {
+      assertEquals(e.data, "BASE64");
+      promise2.resolve();
+    };
+    plain.postMessage("plain");
+    base64.postMessage("base64");
+    await promise1;
+    await promise2;
+    plain.terminate();
+    base64.terminate();
+  },
+});
+
+Deno.test({
+  name: "worker error event is dispatched after queued messages",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const events: string[] = [];
+
+    const w = new Worker("../tests/subdir/message_then_throw_worker.js", {
+      type: "module",
+    });
+    w.addEventListener("message", (e: Event): void => {
+      events.push(`message:${(e as MessageEvent).data}`);
+    });
+    w.addEventListener("error", (e: Event): void => {
+      assert(e.cancelable);
+      assert(/error after message/.test((e as ErrorEvent).message));
+      e.preventDefault();
+      events.push("error");
+      promise.resolve();
+    });
+
+    w.postMessage("hello");
+    await promise;
+    assertEquals(events, ["message:hello", "error"]);
+    w.terminate();
+  },
+});
+
+Deno.test({
+  name: "worker terminate cascades to nested workers",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+
+    const parent = new Worker("../tests/subdir/nested_parent_worker.js", {
+      type: "module",
+      name: "parent",
+    });
+
+    parent.onmessage = (e): void => {
+      assertEquals(e.data, "tick");
+      parent.onmessage = undefined;
+      promise.resolve();
+    };
+
+    await promise;
+    // Joins both parent and child worker threads; this would hang if the
+    // child outlived its parent.
+    parent.terminate();
+  },
+});
+
+Deno.test({
+  name: "worker BroadcastChannel",
+  fn: async function (): Promise<void> {
+    const ready = createResolvable();
+    const promise = createResolvable();
+    const received: string[] = [];
+
+    const w1 = new Worker("../tests/subdir/broadcast_worker.js", {
+      type: "module",
+      name: "w1",
+    });
+    const w2 = new Worker("../tests/subdir/broadcast_worker.js", {
+      type: "module",
+      name: "w2",
+    });
+    let readyCount = 0;
+    const onReady = (): void => {
+      if (++readyCount === 2) ready.resolve();
+    };
+    w1.onmessage = onReady;
+    w2.onmessage = onReady;
+    await ready;
+
+    const channel = new BroadcastChannel("workers");
+    channel.onmessage = (e): void => {
+      received.push(e.data);
+      if (received.length === 2) promise.resolve();
+    };
+    channel.postMessage("ping");
+    await promise;
+    channel.close();
+
+    received.sort();
+    assertEquals(received, ["w1: ping", "w2: ping"]);
+  },
+});
+
+Deno.test({
+  name: "Deno.workers lists live workers",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable();
+    const w = new Worker("../tests/subdir/event_worker.js", {
+      type: "module",
+      name: "listed",
+    });
+
+    const info = Deno.workers().find((i) => i.name === "listed");
+    assert(info);
+    assertEquals(info.status, "running");
+    assert(info.specifier.endsWith("/subdir/event_worker.js"));
+    assert(info.uptime >= 0);
+
+    w.onmessage = (): void => {
+      promise.resolve();
+    };
+    w.postMessage("hello");
+    await promise;
+    w.terminate();
+    assert(!Deno.workers().some((i) => i.name === "listed"));
+  },
+});
+
+Deno.test({
+  name: "Deno.performanceEntries collects entries of workers",
+  fn: async function (): Promise<void> {
+    const promise = createResolvable<number>();
+    const w = new Worker("../tests/subdir/performance_worker.js", {
+      type: "module",
+      name: "profiled",
+    });
+    w.onmessage = (e): void => {
+      promise.resolve(e.data);
+    };
+    w.postMessage(null);
+    const workerTimeOrigin = await promise;
+    w.terminate();
+
+    // The worker started after the main thread.
+    assert(workerTimeOrigin > performance.timeOrigin);
+    const timeline = Deno.performanceEntries().find(
+      (t) => t.name === "profiled"
+    );
+    assert(timeline);
+    assertEquals(timeline.timeOrigin, workerTimeOrigin);
+    assertEquals(timeline.entries.length, 1);
+    assertEquals(timeline.entries[0].name, "worker mark");
+    assertEquals(timeline.entries[0].entryType, "mark");
+  },
+});
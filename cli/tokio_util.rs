@@ -1,4 +1,74 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use deno_core::ErrBox;
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::executor::ThreadPool;
+use futures::stream::StreamExt;
+use std::future::Future;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// Number of threads in the blocking pool unless configured otherwise using
+/// `--blocking-threads`.
+///
+/// The pool doesn't grow: once all threads are busy, further blocking ops
+/// (file system access, DNS lookups, ...) are queued until a thread is free,
+/// so a program keeping many slow operations in flight (eg. opening FIFOs
+/// or files on a network share) should raise the limit.
+pub const DEFAULT_BLOCKING_THREADS: usize = 64;
+
+static BLOCKING_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+  static ref BLOCKING_POOL: ThreadPool = {
+    let size = match BLOCKING_THREADS.load(Ordering::SeqCst) {
+      0 => DEFAULT_BLOCKING_THREADS,
+      n => n,
+    };
+    ThreadPool::builder()
+      .pool_size(size)
+      .name_prefix("deno-blocking-")
+      .create()
+      .expect("Failed to create blocking pool")
+  };
+}
+
+/// Set size of the blocking pool. Has no effect once the pool was created,
+/// ie. it must be called before the first call to `spawn_blocking`.
+pub fn set_blocking_threads(threads: usize) {
+  BLOCKING_THREADS.store(threads, Ordering::SeqCst);
+}
+
+/// Run blocking function on a dedicated, process-wide thread pool.
+///
+/// Unlike `tokio::task::spawn_blocking` the pool is shared by all isolates
+/// (main and workers), has fixed size and doesn't depend on the tokio
+/// runtime driving the future, so heavy file system work can't starve
+/// timers and sockets.
+///
+/// If `f` panics, the panic is caught on the pool thread, which stays alive,
+/// and resumed when the returned future is polled.
+pub fn spawn_blocking<F, R>(f: F) -> impl Future<Output = R>
+where
+  F: FnOnce() -> R + Send + 'static,
+  R: Send + 'static,
+{
+  let (tx, rx) = oneshot::channel::<std::thread::Result<R>>();
+  BLOCKING_POOL.spawn_ok(async move {
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    // Receiver might have been dropped if the op was cancelled.
+    let _ = tx.send(result);
+  });
+  async move {
+    match rx.await.expect("Blocking pool shut down") {
+      Ok(value) => value,
+      Err(payload) => panic::resume_unwind(payload),
+    }
+  }
+}
 
 pub fn create_basic_runtime() -> tokio::runtime::Runtime {
   tokio::runtime::Builder::new()
@@ -9,6 +79,99 @@ pub fn create_basic_runtime() -> tokio::runtime::Runtime {
     .unwrap()
 }
 
+/// Create multi-threaded runtime with `threads` worker threads.
+///
+/// Note that `block_on` still polls the passed future on the calling thread,
+/// the worker threads drive I/O and spawned tasks.
+pub fn create_threadpool_runtime(
+  threads: usize,
+  stack_size: Option<usize>,
+) -> tokio::runtime::Runtime {
+  let mut builder = tokio::runtime::Builder::new();
+  builder
+    .threaded_scheduler()
+    .core_threads(threads)
+    // tokio 0.2 doesn't support per-thread names, so all worker threads
+    // share the same name.
+    .thread_name("deno-tokio")
+    .enable_io()
+    .enable_time();
+  if let Some(stack_size) = stack_size {
+    builder.thread_stack_size(stack_size);
+  }
+  builder.build().unwrap()
+}
+
+/// Create runtime for the main isolate as configured by `--runtime-threads`
+/// and `--runtime-stack-size`. Unless more than one thread is requested the
+/// current-thread (basic) scheduler is used.
+pub fn create_main_runtime(
+  threads: Option<usize>,
+  stack_size: Option<usize>,
+) -> tokio::runtime::Runtime {
+  match threads {
+    Some(threads) if threads > 1 => {
+      create_threadpool_runtime(threads, stack_size)
+    }
+    _ => create_basic_runtime(),
+  }
+}
+
+/// Stack size of the thread running the main isolate; matches the usual
+/// size of the main thread stack.
+const ISOLATE_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Messages sent from the isolate thread to the thread driving the runtime.
+enum IsolateThreadEvent {
+  Finished(Result<(), ErrBox>),
+}
+
+/// Runs future created by `create_future` on a dedicated "deno-isolate"
+/// thread, while the calling thread drives `rt` (I/O, timers and spawned
+/// tasks) until the isolate thread reports that the future finished.
+///
+/// V8 execution thus never blocks the reactor, so I/O readiness, signals and
+/// the inspector server are serviced even while JavaScript is CPU-bound.
+///
+/// The isolate is created by `create_future` on the isolate thread and never
+/// leaves it, so neither the bindings nor ops need a `v8::Locker`; the two
+/// threads only exchange `Send` values like I/O resources and channels.
+pub fn run_on_isolate_thread<F>(
+  rt: &mut tokio::runtime::Runtime,
+  create_future: F,
+) -> Result<(), ErrBox>
+where
+  F: FnOnce() -> Pin<Box<dyn Future<Output = Result<(), ErrBox>>>>
+    + Send
+    + 'static,
+{
+  let (sender, mut receiver) = mpsc::unbounded::<IsolateThreadEvent>();
+  let handle = rt.handle().clone();
+  let join_handle = std::thread::Builder::new()
+    .name("deno-isolate".to_string())
+    .stack_size(ISOLATE_THREAD_STACK_SIZE)
+    .spawn(move || {
+      // Entering the runtime context lets ops register I/O resources and
+      // timers with the reactor, which is driven by the other thread.
+      let result =
+        handle.enter(|| futures::executor::block_on(create_future()));
+      sender
+        .unbounded_send(IsolateThreadEvent::Finished(result))
+        .unwrap();
+    })?;
+  match rt.block_on(receiver.next()) {
+    Some(IsolateThreadEvent::Finished(result)) => {
+      join_handle.join().unwrap();
+      result
+    }
+    // Channel is closed only if the isolate thread panicked.
+    None => match join_handle.join() {
+      Err(panic) => std::panic::resume_unwind(panic),
+      Ok(()) => unreachable!(),
+    },
+  }
+}
+
 // TODO(ry) rename to run_local ?
 pub fn run_basic<F, R>(future: F) -> R
 where
@@ -17,3 +180,61 @@ where
   let mut rt = create_basic_runtime();
   rt.block_on(future)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::future::FutureExt;
+
+  #[test]
+  fn threadpool_runtime_block_on() {
+    let mut rt = create_main_runtime(Some(2), Some(2 * 1024 * 1024));
+    let name = rt.block_on(async {
+      tokio::spawn(async { std::thread::current().name().map(String::from) })
+        .await
+        .unwrap()
+    });
+    assert_eq!(name.as_deref(), Some("deno-tokio"));
+  }
+
+  #[test]
+  fn run_on_isolate_thread_uses_runtime() {
+    let mut rt = create_basic_runtime();
+    let result = run_on_isolate_thread(&mut rt, || {
+      async {
+        let name = std::thread::current().name().map(String::from);
+        assert_eq!(name.as_deref(), Some("deno-isolate"));
+        // Timers are driven by the runtime on the calling thread.
+        tokio::time::delay_for(std::time::Duration::from_millis(1)).await;
+        Err(ErrBox::from(std::io::Error::from(
+          std::io::ErrorKind::Other,
+        )))
+      }
+      .boxed_local()
+    });
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn spawn_blocking_returns_value() {
+    let result = run_basic(async {
+      let a = spawn_blocking(|| 1 + 1);
+      let b =
+        spawn_blocking(|| std::thread::current().name().map(String::from));
+      (a.await, b.await)
+    });
+    assert_eq!(result.0, 2);
+    assert!(result.1.unwrap().starts_with("deno-blocking-"));
+  }
+
+  #[test]
+  fn spawn_blocking_resumes_panic() {
+    let result = std::panic::catch_unwind(|| {
+      run_basic(spawn_blocking(|| -> usize { panic!("blocking panic") }))
+    });
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"blocking panic"));
+    // The pool thread survived the panic.
+    assert_eq!(run_basic(spawn_blocking(|| 1 + 1)), 2);
+  }
+}
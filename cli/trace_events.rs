@@ -0,0 +1,232 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Recording of op dispatches, event loop ticks and module loading for
+//! `deno run --trace-ops=FILE`. Events are written in the Trace Event Format,
+//! so the file can be opened with chrome://tracing or Perfetto.
+//!
+//! Spans that can overlap on one thread, like async ops or module fetches,
+//! are recorded as async events; synchronous work as complete events.
+
+use deno_core::ErrBox;
+use serde_json::Value;
+use std::cell::Cell;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+  static THREAD_ID: Cell<Option<u64>> = Cell::new(None);
+}
+
+/// Events recorded by all isolates; there's one instance per process, held
+/// by `GlobalState`.
+pub struct TraceEvents {
+  start: Instant,
+  events: Mutex<Vec<Value>>,
+  next_async_id: AtomicU64,
+}
+
+impl Default for TraceEvents {
+  fn default() -> Self {
+    Self {
+      start: Instant::now(),
+      events: Mutex::new(Vec::new()),
+      next_async_id: AtomicU64::new(1),
+    }
+  }
+}
+
+impl TraceEvents {
+  /// Microseconds since tracing started.
+  fn timestamp(&self, instant: Instant) -> f64 {
+    instant.duration_since(self.start).as_secs_f64() * 1_000_000.0
+  }
+
+  /// Trace viewers need integer thread ids; threads are numbered in the
+  /// order they record their first event, which also records their name.
+  fn thread_id(&self) -> u64 {
+    THREAD_ID.with(|id| {
+      if let Some(tid) = id.get() {
+        return tid;
+      }
+      let tid = NEXT_THREAD_ID.fetch_add(1, Ordering::SeqCst);
+      id.set(Some(tid));
+      let thread = std::thread::current();
+      self.events.lock().unwrap().push(json!({
+        "name": "thread_name",
+        "ph": "M",
+        "pid": std::process::id(),
+        "tid": tid,
+        "args": { "name": thread.name().unwrap_or("deno") },
+      }));
+      tid
+    })
+  }
+
+  fn push(
+    &self,
+    phase: &str,
+    category: &str,
+    name: &str,
+    instant: Instant,
+    mut event: Value,
+  ) {
+    let object = event.as_object_mut().unwrap();
+    object.insert("name".to_string(), json!(name));
+    object.insert("cat".to_string(), json!(category));
+    object.insert("ph".to_string(), json!(phase));
+    object.insert("ts".to_string(), json!(self.timestamp(instant)));
+    object.insert("pid".to_string(), json!(std::process::id()));
+    object.insert("tid".to_string(), json!(self.thread_id()));
+    self.events.lock().unwrap().push(event);
+  }
+
+  /// Records synchronous work that began at `start` and ends now.
+  pub fn complete(
+    &self,
+    category: &str,
+    name: &str,
+    start: Instant,
+    args: Value,
+  ) {
+    let duration = start.elapsed().as_secs_f64() * 1_000_000.0;
+    let event = json!({ "dur": duration, "args": args });
+    self.push("X", category, name, start, event);
+  }
+
+  /// Records the beginning of an async span and returns the id to end it
+  /// with.
+  pub fn async_begin(
+    &self,
+    category: &str,
+    name: &str,
+    start: Instant,
+    args: Value,
+  ) -> u64 {
+    let id = self.next_async_id.fetch_add(1, Ordering::SeqCst);
+    let event = json!({ "id": id, "args": args });
+    self.push("b", category, name, start, event);
+    id
+  }
+
+  pub fn async_end(&self, category: &str, name: &str, id: u64) {
+    let event = json!({ "id": id });
+    self.push("e", category, name, Instant::now(), event);
+  }
+
+  /// Records an async span that began at `start` and ends now.
+  pub fn async_span(
+    &self,
+    category: &str,
+    name: &str,
+    start: Instant,
+    args: Value,
+  ) {
+    let id = self.async_begin(category, name, start, args);
+    self.async_end(category, name, id);
+  }
+
+  pub fn to_json(&self) -> Value {
+    let events = self.events.lock().unwrap();
+    json!({
+      "traceEvents": *events,
+      "displayTimeUnit": "ms",
+    })
+  }
+
+  pub fn write(&self, path: &Path) -> Result<(), ErrBox> {
+    let json = serde_json::to_string(&self.to_json())?;
+    std::fs::write(path, json)?;
+    Ok(())
+  }
+}
+
+/// An op that is being traced, created right before it's dispatched.
+pub struct OpTrace {
+  trace_events: Arc<TraceEvents>,
+  name: String,
+  start: Instant,
+}
+
+impl OpTrace {
+  pub fn new(trace_events: &Arc<TraceEvents>, name: &str) -> Self {
+    Self {
+      trace_events: trace_events.clone(),
+      name: name.to_string(),
+      start: Instant::now(),
+    }
+  }
+
+  /// Records a sync op, which completed when its dispatcher returned.
+  pub fn sync_completed(self) {
+    self
+      .trace_events
+      .complete("op", &self.name, self.start, json!({}));
+  }
+
+  /// Records dispatch of an async op; its completion is recorded with the
+  /// returned trace.
+  pub fn async_dispatched(self) -> AsyncOpTrace {
+    let id =
+      self
+        .trace_events
+        .async_begin("op", &self.name, self.start, json!({}));
+    AsyncOpTrace {
+      trace_events: self.trace_events,
+      name: self.name,
+      id,
+    }
+  }
+}
+
+pub struct AsyncOpTrace {
+  trace_events: Arc<TraceEvents>,
+  name: String,
+  id: u64,
+}
+
+impl AsyncOpTrace {
+  pub fn completed(self) {
+    self.trace_events.async_end("op", &self.name, self.id);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn trace_events_format() {
+    let trace_events = Arc::new(TraceEvents::default());
+    OpTrace::new(&trace_events, "op_sync").sync_completed();
+    let op_trace = OpTrace::new(&trace_events, "op_async").async_dispatched();
+    trace_events.async_span(
+      "module",
+      "fetch",
+      Instant::now(),
+      json!({ "specifier": "file:///a.ts" }),
+    );
+    op_trace.completed();
+
+    let json = trace_events.to_json();
+    let events = json["traceEvents"].as_array().unwrap();
+    let phases: Vec<&str> =
+      events.iter().map(|e| e["ph"].as_str().unwrap()).collect();
+    assert_eq!(phases, vec!["M", "X", "b", "b", "e", "e"]);
+    assert_eq!(events[1]["name"], "op_sync");
+    assert_eq!(events[1]["cat"], "op");
+    assert!(events[1]["dur"].as_f64().unwrap() >= 0.0);
+    // Begin and end of an async span share their id.
+    assert_eq!(events[2]["name"], "op_async");
+    assert_eq!(events[5]["name"], "op_async");
+    assert_eq!(events[2]["id"], events[5]["id"]);
+    assert_eq!(events[3]["args"]["specifier"], "file:///a.ts");
+    let tid = &events[0]["tid"];
+    assert!(events.iter().all(|e| &e["tid"] == tid));
+  }
+}
@@ -5,6 +5,8 @@ use crate::diagnostics::DiagnosticItem;
 use crate::disk_cache::DiskCache;
 use crate::file_fetcher::SourceFile;
 use crate::file_fetcher::SourceFileFetcher;
+use crate::flags::Flags;
+#[cfg(feature = "tools")]
 use crate::fmt;
 use crate::fs as deno_fs;
 use crate::global_state::GlobalState;
@@ -12,20 +14,32 @@ use crate::msg;
 use crate::op_error::OpError;
 use crate::ops;
 use crate::source_maps::SourceMapGetter;
+#[cfg(feature = "tsc")]
 use crate::startup_data;
+#[cfg(feature = "tsc")]
 use crate::state::State;
+#[cfg(feature = "tsc")]
 use crate::state::*;
+use crate::task_runner;
+#[cfg(feature = "tsc")]
 use crate::tokio_util;
 use crate::version;
+#[cfg(feature = "tsc")]
 use crate::web_worker::WebWorker;
+#[cfg(feature = "tsc")]
 use crate::web_worker::WebWorkerHandle;
+#[cfg(feature = "tsc")]
 use crate::worker::WorkerEvent;
+#[cfg(feature = "tsc")]
 use core::task::Context;
 use deno_core::Buf;
 use deno_core::ErrBox;
 use deno_core::ModuleSpecifier;
+#[cfg(feature = "tsc")]
 use deno_core::StartupData;
+#[cfg(feature = "tsc")]
 use futures::future::Future;
+#[cfg(feature = "tsc")]
 use futures::future::FutureExt;
 use log::info;
 use regex::Regex;
@@ -37,15 +51,22 @@ use std::collections::HashSet;
 use std::fs;
 use std::hash::BuildHasher;
 use std::io;
+#[cfg(feature = "tsc")]
 use std::ops::Deref;
+#[cfg(feature = "tsc")]
 use std::ops::DerefMut;
 use std::path::PathBuf;
+#[cfg(feature = "tsc")]
 use std::pin::Pin;
 use std::str;
+#[cfg(feature = "tsc")]
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
+#[cfg(feature = "tsc")]
 use std::task::Poll;
+#[cfg(feature = "tsc")]
+use tokio::sync::Mutex as AsyncMutex;
 use url::Url;
 
 #[derive(Debug, Clone)]
@@ -54,8 +75,10 @@ pub struct CompiledModule {
   pub name: String,
 }
 
+#[cfg(feature = "tsc")]
 pub struct CompilerWorker(WebWorker);
 
+#[cfg(feature = "tsc")]
 impl CompilerWorker {
   pub fn new(name: String, startup_data: StartupData, state: State) -> Self {
     let state_ = state.clone();
@@ -68,6 +91,7 @@ impl CompilerWorker {
   }
 }
 
+#[cfg(feature = "tsc")]
 impl Deref for CompilerWorker {
   type Target = WebWorker;
   fn deref(&self) -> &Self::Target {
@@ -75,12 +99,14 @@ impl Deref for CompilerWorker {
   }
 }
 
+#[cfg(feature = "tsc")]
 impl DerefMut for CompilerWorker {
   fn deref_mut(&mut self) -> &mut Self::Target {
     &mut self.0
   }
 }
 
+#[cfg(feature = "tsc")]
 impl Future for CompilerWorker {
   type Output = Result<(), ErrBox>;
 
@@ -219,22 +245,32 @@ impl CompiledFileMetadata {
   }
 }
 /// Creates the JSON message send to compiler.ts's onmessage.
+#[allow(clippy::too_many_arguments)]
 fn req(
   request_type: msg::CompilerRequestType,
   root_names: Vec<String>,
   compiler_config: CompilerConfig,
   target: &str,
   bundle: bool,
-  unstable: bool,
+  minify: bool,
+  flags: &Flags,
+  defines: &serde_json::Map<String, Value>,
 ) -> Buf {
   let cwd = std::env::current_dir().unwrap();
+  let unstable = flags.unstable;
+  let no_remote_check = flags.no_remote_check;
+  let source_maps = source_maps_mode(flags.source_maps.as_deref());
   let j = match (compiler_config.path, compiler_config.content) {
     (Some(config_path), Some(config_data)) => json!({
       "type": request_type as i32,
       "target": target,
       "rootNames": root_names,
       "bundle": bundle,
+      "minify": minify,
       "unstable": unstable,
+      "noRemoteCheck": no_remote_check,
+      "sourceMaps": source_maps,
+      "defines": defines,
       "configPath": config_path,
       "config": str::from_utf8(&config_data).unwrap(),
       "cwd": cwd,
@@ -244,7 +280,11 @@ fn req(
       "target": target,
       "rootNames": root_names,
       "bundle": bundle,
+      "minify": minify,
       "unstable": unstable,
+      "noRemoteCheck": no_remote_check,
+      "sourceMaps": source_maps,
+      "defines": defines,
       "cwd": cwd,
     }),
   };
@@ -252,6 +292,46 @@ fn req(
   j.to_string().into_boxed_str().into_boxed_bytes()
 }
 
+/// The `--source-maps` mode, external when it isn't given.
+fn source_maps_mode(source_maps: Option<&str>) -> &str {
+  source_maps.unwrap_or("external")
+}
+
+/// The constants substituted when compiling: the `define` section of
+/// `deno.json`, overridden by `--define`.
+fn load_defines(
+  flag_defines: &[(String, String)],
+) -> Result<serde_json::Map<String, Value>, ErrBox> {
+  let mut defines = serde_json::Map::new();
+  let cwd = std::env::current_dir()?;
+  if let Some(config_path) = task_runner::find_config(&cwd) {
+    let source = std::fs::read_to_string(&config_path)?;
+    let config: Value = serde_json::from_str(&source)?;
+    let invalid = || {
+      OpError::type_error(format!(
+        "\"define\" in {} must map names to JSON literals",
+        config_path.display()
+      ))
+    };
+    match config.get("define") {
+      None => {}
+      Some(Value::Object(define)) => {
+        for (name, value) in define {
+          if value.is_array() || value.is_object() {
+            return Err(invalid().into());
+          }
+          defines.insert(name.clone(), value.clone());
+        }
+      }
+      Some(_) => return Err(invalid().into()),
+    }
+  }
+  for (name, value) in flag_defines {
+    defines.insert(name.clone(), serde_json::from_str(value)?);
+  }
+  Ok(defines)
+}
+
 /// Emit a SHA256 hash based on source code, deno version and TS config.
 /// Used to check if a recompilation for source code is needed.
 pub fn source_code_version_hash(
@@ -274,6 +354,14 @@ pub struct TsCompilerInner {
   pub use_disk_cache: bool,
   /// This setting is controlled by `compilerOptions.checkJs`
   pub compile_js: bool,
+  /// How source maps are emitted, set by `--source-maps`.
+  pub source_maps: String,
+  /// Constants substituted when compiling, by name.
+  pub defines: serde_json::Map<String, Value>,
+  /// The compiler worker, once a request started it. Locking it queues
+  /// requests to the worker.
+  #[cfg(feature = "tsc")]
+  worker_handle: AsyncMutex<Option<WebWorkerHandle>>,
 }
 
 #[derive(Clone)]
@@ -298,6 +386,7 @@ struct EmittedSource {
 struct BundleResponse {
   diagnostics: Diagnostic,
   bundle_output: String,
+  bundle_source_map: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -329,8 +418,12 @@ impl TsCompiler {
     disk_cache: DiskCache,
     use_disk_cache: bool,
     config_path: Option<String>,
+    source_maps: Option<String>,
+    defines: &[(String, String)],
   ) -> Result<Self, ErrBox> {
     let config = CompilerConfig::load(config_path)?;
+    let source_maps = source_maps_mode(source_maps.as_deref()).to_string();
+    let defines = load_defines(defines)?;
     Ok(TsCompiler(Arc::new(TsCompilerInner {
       file_fetcher,
       disk_cache,
@@ -338,11 +431,16 @@ impl TsCompiler {
       config,
       compiled: Mutex::new(HashSet::new()),
       use_disk_cache,
+      source_maps,
+      defines,
+      #[cfg(feature = "tsc")]
+      worker_handle: AsyncMutex::new(None),
     })))
   }
 
   /// Create a new V8 worker with snapshot of TS compiler and setup compiler's
   /// runtime.
+  #[cfg(feature = "tsc")]
   fn setup_worker(global_state: GlobalState) -> CompilerWorker {
     let entry_point =
       ModuleSpecifier::resolve_url_or_path("./__$deno$ts_compiler.ts").unwrap();
@@ -362,17 +460,69 @@ impl TsCompiler {
     worker
   }
 
+  /// Bundles the module and its dependencies, to `out_file` or to stdout.
+  /// Minified bundles written to a file get a source map next to them.
   pub async fn bundle(
     &self,
     global_state: GlobalState,
     module_name: String,
     out_file: Option<PathBuf>,
+    minify: bool,
   ) -> Result<(), ErrBox> {
+    let (mut output_string, source_map) =
+      self.emit_bundle(global_state, module_name, minify).await?;
+
+    if let Some(out_file_) = out_file.as_ref() {
+      info!("Emitting bundle to {:?}", out_file_);
+
+      if let Some(source_map) = source_map {
+        let mut map_file = out_file_.clone().into_os_string();
+        map_file.push(".map");
+        let map_file = PathBuf::from(map_file);
+        deno_fs::write_file(&map_file, source_map.as_bytes(), 0o666)?;
+        output_string.push_str(&format!(
+          "//# sourceMappingURL={}\n",
+          map_file.file_name().unwrap().to_string_lossy()
+        ));
+      }
+
+      let output_bytes = output_string.as_bytes();
+      let output_len = output_bytes.len();
+
+      deno_fs::write_file(out_file_, output_bytes, 0o666)?;
+      // TODO(bartlomieju): add "humanFileSize" method
+      info!("{} bytes emmited.", output_len);
+    } else {
+      println!("{}", output_string);
+    }
+
+    Ok(())
+  }
+
+  /// Bundles the module and its dependencies and returns the formatted
+  /// bundle, instead of emitting it.
+  pub async fn bundle_to_string(
+    &self,
+    global_state: GlobalState,
+    module_name: String,
+  ) -> Result<String, ErrBox> {
+    let (output_string, _) =
+      self.emit_bundle(global_state, module_name, false).await?;
+    Ok(output_string)
+  }
+
+  /// Returns the bundle, and the source map of minified bundles.
+  async fn emit_bundle(
+    &self,
+    global_state: GlobalState,
+    module_name: String,
+    minify: bool,
+  ) -> Result<(String, Option<String>), ErrBox> {
     debug!(
       "Invoking the compiler to bundle. module_name: {}",
       module_name
     );
-    eprintln!("Bundling {}", module_name);
+    info!("Bundling {}", module_name);
 
     let root_names = vec![module_name];
     let req_msg = req(
@@ -381,7 +531,9 @@ impl TsCompiler {
       self.config.clone(),
       "main",
       true,
-      global_state.flags.unstable,
+      minify,
+      &global_state.flags,
+      &self.defines,
     );
 
     let msg = execute_in_thread(global_state.clone(), req_msg).await?;
@@ -394,22 +546,38 @@ impl TsCompiler {
       return Err(ErrBox::from(bundle_response.diagnostics));
     }
 
+    // Minified bundles aren't formatted, which would undo the minification
+    // and invalidate the source map.
+    if minify {
+      return Ok((
+        bundle_response.bundle_output,
+        bundle_response.bundle_source_map,
+      ));
+    }
+    // Builds without `deno fmt` emit the bundle as the compiler printed it.
+    #[cfg(feature = "tools")]
     let output_string = fmt::format_text(&bundle_response.bundle_output)?;
+    #[cfg(not(feature = "tools"))]
+    let output_string = bundle_response.bundle_output;
+    Ok((output_string, None))
+  }
 
-    if let Some(out_file_) = out_file.as_ref() {
-      eprintln!("Emitting bundle to {:?}", out_file_);
-
-      let output_bytes = output_string.as_bytes();
-      let output_len = output_bytes.len();
-
-      deno_fs::write_file(out_file_, output_bytes, 0o666)?;
-      // TODO(bartlomieju): add "humanFileSize" method
-      eprintln!("{} bytes emmited.", output_len);
-    } else {
-      println!("{}", output_string);
+  /// The hash of the options that the compiled code depends on. Source maps
+  /// and defines are only part of it when they aren't the defaults, so
+  /// modules cached before these options existed stay valid.
+  fn options_hash(&self) -> Vec<u8> {
+    let mut hash = self.config.hash.clone();
+    if self.source_maps != "external" {
+      hash.extend_from_slice(b"source-maps=");
+      hash.extend_from_slice(self.source_maps.as_bytes());
     }
-
-    Ok(())
+    if !self.defines.is_empty() {
+      hash.extend_from_slice(b"defines=");
+      hash.extend_from_slice(
+        Value::Object(self.defines.clone()).to_string().as_bytes(),
+      );
+    }
+    hash
   }
 
   /// Mark given module URL as compiled to avoid multiple compilations of same
@@ -454,7 +622,7 @@ impl TsCompiler {
         let version_hash_to_validate = source_code_version_hash(
           &source_file.source_code,
           version::DENO,
-          &self.config.hash,
+          &self.options_hash(),
         );
 
         if metadata.version_hash == version_hash_to_validate {
@@ -481,7 +649,9 @@ impl TsCompiler {
       self.config.clone(),
       target,
       false,
-      global_state.flags.unstable,
+      false,
+      &global_state.flags,
+      &self.defines,
     );
 
     let ts_compiler = self.clone();
@@ -613,10 +783,19 @@ impl TsCompiler {
     self.disk_cache.set(&js_key, contents.as_bytes())?;
     self.mark_compiled(module_specifier.as_url());
 
+    // A map from an earlier compile with external source maps would be used
+    // for the new code.
+    if self.source_maps != "external" {
+      let source_map_key = self
+        .disk_cache
+        .get_cache_filename_with_extension(module_specifier.as_url(), "js.map");
+      let _ = self.disk_cache.remove(&source_map_key);
+    }
+
     let version_hash = source_code_version_hash(
       &source_file.source_code,
       version::DENO,
-      &self.config.hash,
+      &self.options_hash(),
     );
 
     let compiled_file_metadata = CompiledFileMetadata {
@@ -684,9 +863,17 @@ impl TsCompiler {
 
 impl SourceMapGetter for TsCompiler {
   fn get_source_map(&self, script_name: &str) -> Option<Vec<u8>> {
-    self
-      .try_to_resolve_and_get_source_map(script_name)
-      .map(|out| out.source_code)
+    if let Some(source_map_file) =
+      self.try_to_resolve_and_get_source_map(script_name)
+    {
+      return Some(source_map_file.source_code);
+    }
+    // Modules compiled with `--source-maps=inline` carry their source map.
+    let module_specifier = self.try_to_resolve(script_name)?;
+    let compiled_file = self
+      .get_compiled_source_file(module_specifier.as_url())
+      .ok()?;
+    inline_source_map(&compiled_file.source_code)
   }
 
   fn get_source_line(&self, script_name: &str, line: usize) -> Option<String> {
@@ -704,6 +891,18 @@ impl SourceMapGetter for TsCompiler {
   }
 }
 
+const INLINE_SOURCE_MAP_PREFIX: &str =
+  "//# sourceMappingURL=data:application/json;base64,";
+
+/// Decodes the source map that is inlined at the end of `code`, if any.
+fn inline_source_map(code: &[u8]) -> Option<Vec<u8>> {
+  let code = str::from_utf8(code).ok()?;
+  let start =
+    code.rfind(INLINE_SOURCE_MAP_PREFIX)? + INLINE_SOURCE_MAP_PREFIX.len();
+  let encoded = code[start..].lines().next()?;
+  base64::decode(encoded.trim()).ok()
+}
+
 // `SourceMapGetter` related methods
 impl TsCompiler {
   fn try_to_resolve(&self, script_name: &str) -> Option<ModuleSpecifier> {
@@ -741,32 +940,74 @@ impl TsCompiler {
   }
 }
 
-async fn execute_in_thread(
+/// Starts a compiler worker on its own thread. The thread runs until the
+/// worker is terminated.
+#[cfg(feature = "tsc")]
+fn start_compiler_thread(
   global_state: GlobalState,
-  req: Buf,
-) -> Result<Buf, ErrBox> {
+) -> Result<WebWorkerHandle, ErrBox> {
   let (handle_sender, handle_receiver) =
-    std::sync::mpsc::sync_channel::<Result<WebWorkerHandle, ErrBox>>(1);
+    std::sync::mpsc::sync_channel::<WebWorkerHandle>(1);
   let builder =
     std::thread::Builder::new().name("deno-ts-compiler".to_string());
-  let join_handle = builder.spawn(move || {
-    let worker = TsCompiler::setup_worker(global_state.clone());
-    handle_sender.send(Ok(worker.thread_safe_handle())).unwrap();
+  builder.spawn(move || {
+    let worker = TsCompiler::setup_worker(global_state);
+    handle_sender.send(worker.thread_safe_handle()).unwrap();
     drop(handle_sender);
     tokio_util::run_basic(worker).expect("Panic in event loop");
   })?;
-  let handle = handle_receiver.recv().unwrap()?;
-  handle.post_message(req)?;
-  let event = handle.get_event().await.expect("Compiler didn't respond");
-  let buf = match event {
-    WorkerEvent::Message(buf) => Ok(buf),
-    WorkerEvent::Error(error) => Err(error),
-    WorkerEvent::TerminalError(error) => Err(error),
-  }?;
-  // Shutdown worker and wait for thread to finish
-  handle.terminate();
-  join_handle.join().unwrap();
-  Ok(buf)
+  Ok(handle_receiver.recv().unwrap())
+}
+
+/// Sends `req` to the compiler worker of the program and resolves to its
+/// response. The worker is started by the first request and then serves all
+/// later ones, so the snapshot and the type libraries are only loaded once.
+/// The worker handles one request at a time; concurrent requests, eg. from
+/// dynamic imports and workers, wait for their turn in the order they came.
+#[cfg(feature = "tsc")]
+async fn execute_in_thread(
+  global_state: GlobalState,
+  req: Buf,
+) -> Result<Buf, ErrBox> {
+  let mut maybe_handle = global_state.ts_compiler.worker_handle.lock().await;
+  let handle = match maybe_handle.take() {
+    Some(handle) => handle,
+    None => start_compiler_thread(global_state.clone())?,
+  };
+  if let Err(error) = handle.post_message(req) {
+    handle.terminate();
+    return Err(error);
+  }
+  let event = match handle.get_event().await {
+    Some(event) => event,
+    None => {
+      let message = "The compiler worker exited without responding";
+      return Err(OpError::other(message.to_string()).into());
+    }
+  };
+  match event {
+    WorkerEvent::Message(buf) => {
+      *maybe_handle = Some(handle);
+      Ok(buf)
+    }
+    // The worker may be left in a bad state, the next request starts a new
+    // one.
+    WorkerEvent::Error(error) | WorkerEvent::TerminalError(error) => {
+      handle.terminate();
+      Err(error)
+    }
+  }
+}
+
+/// Without the "tsc" feature there's no compiler snapshot to start a compiler
+/// worker from, so every request to the compiler fails.
+#[cfg(not(feature = "tsc"))]
+async fn execute_in_thread(
+  _global_state: GlobalState,
+  _req: Buf,
+) -> Result<Buf, ErrBox> {
+  let message = "This build of Deno doesn't include the TypeScript compiler";
+  Err(OpError::other(message.to_string()).into())
 }
 
 /// This function is used by `Deno.compile()` and `Deno.bundle()` APIs.
@@ -776,6 +1017,7 @@ pub async fn runtime_compile<S: BuildHasher>(
   sources: &Option<HashMap<String, String, S>>,
   bundle: bool,
   options: &Option<String>,
+  check: bool,
 ) -> Result<Value, OpError> {
   let req_msg = json!({
     "type": msg::CompilerRequestType::RuntimeCompile as i32,
@@ -784,6 +1026,7 @@ pub async fn runtime_compile<S: BuildHasher>(
     "sources": sources,
     "options": options,
     "bundle": bundle,
+    "check": check,
     "unstable": global_state.flags.unstable,
   })
   .to_string()
@@ -803,7 +1046,8 @@ pub async fn runtime_compile<S: BuildHasher>(
 
   let response: RuntimeCompileResponse = serde_json::from_str(json_str)?;
 
-  if response.diagnostics.is_empty() && sources.is_none() {
+  // Without type checking, there may be errors the diagnostics don't show.
+  if check && response.diagnostics.is_empty() && sources.is_none() {
     compiler.cache_emitted_files(response.emit_map)?;
   }
 
@@ -813,6 +1057,64 @@ pub async fn runtime_compile<S: BuildHasher>(
   Ok(serde_json::from_str::<Value>(json_str).unwrap())
 }
 
+/// This function is used by `Deno.graph()` API. Returns the modules
+/// `root_name` depends on, directly or not, with their dependencies.
+pub async fn runtime_graph<S: BuildHasher>(
+  global_state: GlobalState,
+  root_name: &str,
+  sources: &Option<HashMap<String, String, S>>,
+) -> Result<Value, OpError> {
+  let req_msg = json!({
+    "type": msg::CompilerRequestType::RuntimeGraph as i32,
+    "target": "runtime",
+    "rootName": root_name,
+    "sources": sources,
+  })
+  .to_string()
+  .into_boxed_str()
+  .into_boxed_bytes();
+
+  let msg = execute_in_thread(global_state, req_msg).await?;
+  let json_str = std::str::from_utf8(&msg).unwrap();
+  Ok(serde_json::from_str::<Value>(json_str)?)
+}
+
+/// This function is used by the language server (`deno lsp`) to query the
+/// TypeScript language service about `specifier`. `sources` holds contents of
+/// the documents open in the editor.
+pub async fn language_service(
+  global_state: GlobalState,
+  method: &str,
+  specifier: &str,
+  sources: &HashMap<String, String>,
+  position: Option<Value>,
+) -> Result<Value, ErrBox> {
+  let mut req_msg = json!({
+    "type": msg::CompilerRequestType::LanguageService as i32,
+    "target": "main",
+    "method": method,
+    "specifier": specifier,
+    "sources": sources,
+    "position": position,
+    "unstable": global_state.flags.unstable,
+  });
+  // The editor checks with the compiler options of `--config`, eg. to allow
+  // decorators.
+  let config = &global_state.ts_compiler.config;
+  if let (Some(config_path), Some(config_data)) =
+    (&config.path, &config.content)
+  {
+    req_msg["configPath"] = json!(config_path);
+    req_msg["config"] = json!(str::from_utf8(config_data).unwrap());
+    req_msg["cwd"] = json!(std::env::current_dir().unwrap());
+  }
+  let req_msg = req_msg.to_string().into_boxed_str().into_boxed_bytes();
+
+  let msg = execute_in_thread(global_state, req_msg).await?;
+  let json_str = std::str::from_utf8(&msg).unwrap();
+  Ok(serde_json::from_str::<Value>(json_str)?)
+}
+
 /// This function is used by `Deno.transpileOnly()` API.
 pub async fn runtime_transpile<S: BuildHasher>(
   global_state: GlobalState,
@@ -891,11 +1193,40 @@ mod tests {
 
     let result = state
       .ts_compiler
-      .bundle(state.clone(), module_name, None)
+      .bundle(state.clone(), module_name, None, false)
       .await;
     assert!(result.is_ok());
   }
 
+  #[cfg(feature = "tsc")]
+  #[tokio::test]
+  async fn test_compiler_worker_is_reused() {
+    let state =
+      GlobalState::mock(vec![String::from("deno"), String::from("hello.js")]);
+    let mut sources = HashMap::new();
+    sources.insert(
+      "/foo.ts".to_string(),
+      "export const a: number = 1;".to_string(),
+    );
+    let options = None;
+    let (a, b) = futures::join!(
+      runtime_transpile(state.clone(), &sources, &options),
+      runtime_transpile(state.clone(), &sources, &options)
+    );
+    assert_eq!(a.unwrap(), b.unwrap());
+    runtime_transpile(state.clone(), &sources, &options)
+      .await
+      .unwrap();
+    assert_eq!(state.compiler_starts.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn test_inline_source_map() {
+    let code = b"console.log(1);\n//# sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozfQ==\n";
+    assert_eq!(inline_source_map(code), Some(br#"{"version":3}"#.to_vec()));
+    assert_eq!(inline_source_map(b"console.log(1);\n"), None);
+  }
+
   #[test]
   fn test_source_code_version_hash() {
     assert_eq!(
@@ -0,0 +1,126 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! `deno types --lib` composes the declarations of the requested libs into a
+//! single file, for type checking Deno code with tools other than deno.
+//!
+//! Libs are named like in the `lib` compiler option, eg. `dom` or `esnext`,
+//! plus the Deno libs: `deno.ns`, `deno.shared_globals`, `deno.window`,
+//! `deno.worker` and `deno.unstable`. Libs referenced with
+//! `/// <reference lib="..." />` are inlined before the lib referencing them,
+//! so the output doesn't depend on the libs bundled with the type checker.
+
+use crate::js;
+use crate::op_error::OpError;
+use deno_core::ErrBox;
+use std::collections::HashSet;
+
+fn get_lib(name: &str) -> Option<&'static str> {
+  match name {
+    "deno.ns" => Some(js::DENO_NS_LIB),
+    "deno.shared_globals" => Some(js::SHARED_GLOBALS_LIB),
+    "deno.window" => Some(js::WINDOW_LIB),
+    "deno.worker" => Some(js::WORKER_LIB),
+    "deno.unstable" => Some(js::UNSTABLE_NS_LIB),
+    _ => deno_typescript::get_asset(&format!("lib.{}.d.ts", name)),
+  }
+}
+
+enum Directive {
+  /// `/// <reference lib="..." />`
+  Lib(String),
+  /// `/// <reference no-default-lib="true" />`
+  NoDefaultLib,
+}
+
+fn parse_directive(line: &str) -> Option<Directive> {
+  let line = line.trim();
+  if !line.starts_with("///") || !line.contains("<reference") {
+    return None;
+  }
+  if line.contains("no-default-lib=") {
+    return Some(Directive::NoDefaultLib);
+  }
+  let start = line.find("lib=\"")? + "lib=\"".len();
+  let end = start + line[start..].find('"')?;
+  Some(Directive::Lib(line[start..end].to_lowercase()))
+}
+
+fn add_lib(
+  name: &str,
+  added: &mut HashSet<String>,
+  output: &mut String,
+) -> Result<(), ErrBox> {
+  if !added.insert(name.to_string()) {
+    return Ok(());
+  }
+  let source = get_lib(name)
+    .ok_or_else(|| OpError::other(format!("Unknown lib \"{}\"", name)))?;
+
+  let mut body = String::new();
+  for line in source.lines() {
+    match parse_directive(line) {
+      Some(Directive::Lib(dependency)) => add_lib(&dependency, added, output)?,
+      Some(Directive::NoDefaultLib) => {}
+      None => {
+        body.push_str(line);
+        body.push('\n');
+      }
+    }
+  }
+  output.push_str(&format!("// lib.{}.d.ts\n", name));
+  output.push_str(&body);
+  output.push('\n');
+  Ok(())
+}
+
+/// Returns the declarations of `libs` and the libs they reference, each lib
+/// appearing once. `unstable` adds `deno.unstable`.
+pub fn compose_libs(libs: &[String], unstable: bool) -> Result<String, ErrBox> {
+  let mut names: Vec<String> =
+    libs.iter().map(|lib| lib.trim().to_lowercase()).collect();
+  if unstable {
+    names.push("deno.unstable".to_string());
+  }
+
+  let mut added = HashSet::new();
+  let mut output = "/// <reference no-default-lib=\"true\" />\n\n".to_string();
+  for name in names {
+    add_lib(&name, &mut added, &mut output)?;
+  }
+  Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn compose_deno_window() {
+    let types = compose_libs(&["Deno.Window".to_string()], false).unwrap();
+    assert!(types.starts_with("/// <reference no-default-lib=\"true\" />\n"));
+    assert_eq!(types.matches("no-default-lib").count(), 1);
+    assert!(!types.contains("<reference lib="));
+    // Referenced libs come before the lib referencing them.
+    let esnext = types.find("// lib.esnext.d.ts\n").unwrap();
+    let ns = types.find("// lib.deno.ns.d.ts\n").unwrap();
+    let window = types.find("// lib.deno.window.d.ts\n").unwrap();
+    assert!(esnext < ns && ns < window);
+    assert!(!types.contains("// lib.deno.unstable.d.ts\n"));
+  }
+
+  #[test]
+  fn compose_dedups_libs() {
+    let libs = vec!["deno.ns".to_string(), "dom".to_string()];
+    let types = compose_libs(&libs, true).unwrap();
+    assert_eq!(types.matches("// lib.deno.ns.d.ts\n").count(), 1);
+    assert_eq!(types.matches("// lib.es5.d.ts\n").count(), 1);
+    assert!(types.contains("// lib.dom.d.ts\n"));
+    assert!(types.contains("// lib.deno.unstable.d.ts\n"));
+  }
+
+  #[test]
+  fn compose_unknown_lib() {
+    let err = compose_libs(&["deno.foo".to_string()], false).unwrap_err();
+    assert_eq!(err.to_string(), "Unknown lib \"deno.foo\"");
+  }
+}
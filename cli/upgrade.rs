@@ -7,12 +7,15 @@
 //! the same functions as ops available in JS runtime.
 
 extern crate semver_parser;
+use crate::colors;
 use crate::futures::FutureExt;
 use crate::http_util::fetch_once;
 use crate::http_util::FetchOnceResult;
 use crate::op_error::OpError;
 use crate::ErrBox;
 use regex::Regex;
+use reqwest::header::LOCATION;
+use reqwest::StatusCode;
 use reqwest::{redirect::Policy, Client};
 use semver_parser::version::parse as semver_parse;
 use semver_parser::version::Version;
@@ -36,6 +39,14 @@ const ARCHIVE_NAME: &str = "deno-x86_64-apple-darwin.zip";
 #[cfg(target_os = "linux")]
 const ARCHIVE_NAME: &str = "deno-x86_64-unknown-linux-gnu.zip";
 
+const CANARY_URL: &str = "https://dl.deno.land/canary";
+const CANARY_LATEST_URL: &str = "https://dl.deno.land/canary-latest.txt";
+
+/// Releases before this one don't have a checksum published next to their
+/// archives.
+const FIRST_VERSION_WITH_CHECKSUM: &str = "1.0.0";
+const MAX_REDIRECTS: usize = 10;
+
 async fn get_latest_version(client: &Client) -> Result<Version, ErrBox> {
   println!("Checking for latest version");
   let body = client
@@ -50,16 +61,43 @@ async fn get_latest_version(client: &Client) -> Result<Version, ErrBox> {
   Ok(semver_parse(&v).unwrap())
 }
 
+async fn get_latest_canary_version(client: &Client) -> Result<String, ErrBox> {
+  println!("Checking for latest canary version");
+  let body = client
+    .get(Url::parse(CANARY_LATEST_URL)?)
+    .send()
+    .await?
+    .text()
+    .await?;
+  Ok(body.trim().to_string())
+}
+
 /// Asynchronously updates deno executable to greatest version
 /// if greatest version is available.
+///
+/// If `output` is given, the new executable is written there and the current
+/// one is left untouched.
 pub async fn upgrade_command(
   dry_run: bool,
   force: bool,
   version: Option<String>,
+  output: Option<PathBuf>,
+  canary: bool,
 ) -> Result<(), ErrBox> {
   let client = Client::builder().redirect(Policy::none()).build()?;
-  let current_version = semver_parse(crate::version::DENO).unwrap();
 
+  if canary {
+    // Canary builds are identified by the hash of their commit.
+    let install_version = match version {
+      Some(passed_version) => passed_version,
+      None => get_latest_canary_version(&client).await?,
+    };
+    println!("Deno is upgrading to canary build {}", &install_version);
+    let archive_url = compose_canary_url_to_exec(&install_version)?;
+    return install_archive(&archive_url, client, None, dry_run, output).await;
+  }
+
+  let current_version = semver_parse(crate::version::DENO).unwrap();
   let install_version = match version {
     Some(passed_version) => match semver_parse(&passed_version) {
       Ok(ver) => {
@@ -95,17 +133,43 @@ pub async fn upgrade_command(
     &install_version
   );
 
-  let archive_data =
-    download_package(&compose_url_to_exec(&install_version)?, client).await?;
+  let archive_url = compose_url_to_exec(&install_version)?;
+  install_archive(
+    &archive_url,
+    client,
+    Some(&install_version),
+    dry_run,
+    output,
+  )
+  .await
+}
+
+/// Downloads and verifies the archive, checks the executable in it reports
+/// `expected_version`, and installs it unless `dry_run` is set.
+async fn install_archive(
+  archive_url: &Url,
+  client: Client,
+  expected_version: Option<&Version>,
+  dry_run: bool,
+  output: Option<PathBuf>,
+) -> Result<(), ErrBox> {
+  let archive_data = download_package(archive_url, client.clone()).await?;
+  verify_checksum(archive_url, &archive_data, expected_version, client).await?;
 
   let old_exe_path = std::env::current_exe()?;
   let new_exe_path = unpack(archive_data)?;
   let permissions = fs::metadata(&old_exe_path)?.permissions();
   fs::set_permissions(&new_exe_path, permissions)?;
-  check_exe(&new_exe_path, &install_version)?;
+  check_exe(&new_exe_path, expected_version)?;
 
   if !dry_run {
-    replace_exe(&new_exe_path, &old_exe_path)?;
+    match output {
+      Some(output) => {
+        fs::copy(&new_exe_path, &output)?;
+        println!("New executable written to {}", output.display());
+      }
+      None => replace_exe(&new_exe_path, &old_exe_path)?,
+    }
   }
 
   println!("Upgrade done successfully");
@@ -113,6 +177,109 @@ pub async fn upgrade_command(
   Ok(())
 }
 
+/// Compares the archive with the checksum published next to it, in
+/// `<archive>.sha256sum`. Verification fails if the checksum can't be
+/// downloaded, except for releases older than `FIRST_VERSION_WITH_CHECKSUM`
+/// for which the server confirms there's no checksum.
+async fn verify_checksum(
+  archive_url: &Url,
+  archive_data: &[u8],
+  expected_version: Option<&Version>,
+  client: Client,
+) -> Result<(), ErrBox> {
+  let checksum_url = Url::parse(&format!("{}.sha256sum", archive_url))?;
+  let checksum_file = match download_checksum(&checksum_url, client).await? {
+    Some(checksum_file) => checksum_file,
+    None if predates_checksums(expected_version) => {
+      eprintln!(
+        "{}: no checksum is published for {}, the archive is NOT verified",
+        colors::red_bold("Warning".to_string()),
+        archive_url
+      );
+      return Ok(());
+    }
+    None => {
+      return Err(
+        OpError::other(format!(
+          "Checksum not found for {}, refusing to upgrade",
+          archive_url
+        ))
+        .into(),
+      )
+    }
+  };
+  let checksum_file = String::from_utf8(checksum_file)?;
+  let expected = parse_checksum(&checksum_file)?;
+  let actual = crate::checksum::gen(vec![archive_data]);
+  if expected != actual {
+    return Err(
+      OpError::other(format!(
+        "Checksum mismatch for {}: expected {}, got {}",
+        archive_url, expected, actual
+      ))
+      .into(),
+    );
+  }
+  println!("Checksum verified");
+  Ok(())
+}
+
+/// Whether `version` is a release from before checksums were published.
+/// Canary builds always have one.
+fn predates_checksums(version: Option<&Version>) -> bool {
+  let first = semver_parse(FIRST_VERSION_WITH_CHECKSUM).unwrap();
+  version.map_or(false, |version| *version < first)
+}
+
+/// Downloads a checksum file, following redirects. Returns `None` only if
+/// the server responds with 404; any other failure is an error.
+async fn download_checksum(
+  url: &Url,
+  client: Client,
+) -> Result<Option<Vec<u8>>, ErrBox> {
+  println!("downloading {}", url);
+  let mut url = url.clone();
+  for _ in 0..MAX_REDIRECTS {
+    let response = client.get(url.clone()).send().await?;
+    let status = response.status();
+    if status.is_redirection() {
+      let location = response
+        .headers()
+        .get(LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .ok_or_else(|| {
+          OpError::other(format!("Redirect without location from {}", url))
+        })?;
+      url = url.join(location)?;
+      continue;
+    }
+    if status == StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+    if !status.is_success() {
+      return Err(
+        OpError::other(format!("Download of {} failed: {}", url, status))
+          .into(),
+      );
+    }
+    return Ok(Some(response.bytes().await?.to_vec()));
+  }
+  Err(OpError::other(format!("Too many redirects for {}", url)).into())
+}
+
+/// Parses checksum in the `sha256sum` format: `<hex digest>  <file name>`.
+fn parse_checksum(checksum_file: &str) -> Result<String, ErrBox> {
+  match checksum_file.split_whitespace().next() {
+    Some(digest)
+      if digest.len() == 64
+        && digest.chars().all(|c| c.is_ascii_hexdigit()) =>
+    {
+      Ok(digest.to_lowercase())
+    }
+    _ => Err(OpError::other("Invalid checksum file".to_string()).into()),
+  }
+}
+
 fn download_package(
   url: &Url,
   client: Client,
@@ -120,7 +287,7 @@ fn download_package(
   println!("downloading {}", url);
   let url = url.clone();
   let fut = async move {
-    match fetch_once(client.clone(), &url, None).await? {
+    match fetch_once(client.clone(), &url, None, None).await? {
       FetchOnceResult::Code(source, _) => Ok(source),
       FetchOnceResult::NotModified => unreachable!(),
       FetchOnceResult::Redirect(_url, _) => {
@@ -139,6 +306,11 @@ fn compose_url_to_exec(version: &Version) -> Result<Url, ErrBox> {
   Ok(Url::parse(&s)?)
 }
 
+fn compose_canary_url_to_exec(commit_hash: &str) -> Result<Url, ErrBox> {
+  let s = format!("{}/{}/{}", CANARY_URL, commit_hash, ARCHIVE_NAME);
+  Ok(Url::parse(&s)?)
+}
+
 fn find_version(text: &str) -> Result<String, ErrBox> {
   let re = Regex::new(r#"v([^\?]+)?""#)?;
   if let Some(_mat) = re.find(text) {
@@ -203,22 +375,29 @@ fn unpack(archive_data: Vec<u8>) -> Result<PathBuf, ErrBox> {
 }
 
 fn replace_exe(new: &Path, old: &Path) -> Result<(), ErrBox> {
+  // The new executable is first copied next to the old one, as renaming
+  // doesn't work across device boundaries, and then renamed over it. That
+  // way the old executable is replaced atomically and never left half
+  // written.
+  let staged = old.with_extension("new");
+  fs::copy(new, &staged)?;
   if cfg!(windows) {
     // On windows you cannot replace the currently running executable.
     // so first we rename it to deno.old.exe
-    fs::rename(old, old.with_extension("old.exe"))?;
-  } else {
-    fs::remove_file(old)?;
+    let backup = old.with_extension("old.exe");
+    let _ = fs::remove_file(&backup);
+    fs::rename(old, &backup)?;
+  }
+  if let Err(err) = fs::rename(&staged, old) {
+    let _ = fs::remove_file(&staged);
+    return Err(err.into());
   }
-  // Windows cannot rename files across device boundaries, so if rename fails,
-  // we try again with copy.
-  fs::rename(new, old).or_else(|_| fs::copy(new, old).map(|_| ()))?;
   Ok(())
 }
 
 fn check_exe(
   exe_path: &Path,
-  expected_version: &Version,
+  expected_version: Option<&Version>,
 ) -> Result<(), ErrBox> {
   let output = Command::new(exe_path)
     .arg("-V")
@@ -226,7 +405,9 @@ fn check_exe(
     .output()?;
   let stdout = String::from_utf8(output.stdout)?;
   assert!(output.status.success());
-  assert_eq!(stdout.trim(), format!("deno {}", expected_version));
+  if let Some(expected_version) = expected_version {
+    assert_eq!(stdout.trim(), format!("deno {}", expected_version));
+  }
   Ok(())
 }
 
@@ -250,3 +431,45 @@ fn test_compose_url_to_exec() {
   #[cfg(target_os = "linux")]
   assert_eq!(url.as_str(), "https://github.com/denoland/deno/releases/download/v0.0.1/deno-x86_64-unknown-linux-gnu.zip");
 }
+
+#[test]
+fn test_compose_canary_url_to_exec() {
+  let url = compose_canary_url_to_exec("abc123").unwrap();
+  assert!(url
+    .as_str()
+    .starts_with("https://dl.deno.land/canary/abc123/deno-x86_64-"));
+}
+
+#[test]
+fn test_parse_checksum() {
+  let digest = "A".repeat(64);
+  assert_eq!(
+    parse_checksum(&format!("{}  {}\n", digest, ARCHIVE_NAME)).unwrap(),
+    "a".repeat(64)
+  );
+  assert!(parse_checksum("").is_err());
+  assert!(parse_checksum("not a checksum").is_err());
+}
+
+#[test]
+fn test_predates_checksums() {
+  let old = semver_parse("0.42.0").unwrap();
+  let rc = semver_parse("1.0.0-rc2").unwrap();
+  let new = semver_parse("1.0.0").unwrap();
+  assert!(predates_checksums(Some(&old)));
+  assert!(predates_checksums(Some(&rc)));
+  assert!(!predates_checksums(Some(&new)));
+  assert!(!predates_checksums(None));
+}
+
+#[test]
+fn test_replace_exe() {
+  let temp_dir = TempDir::new().unwrap();
+  let old = temp_dir.path().join("deno");
+  let new = temp_dir.path().join("deno_new");
+  fs::write(&old, "old").unwrap();
+  fs::write(&new, "new").unwrap();
+  replace_exe(&new, &old).unwrap();
+  assert_eq!(fs::read_to_string(&old).unwrap(), "new");
+  assert!(!old.with_extension("new").exists());
+}
@@ -0,0 +1,238 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Storage areas behind the `localStorage` and `sessionStorage` Web APIs.
+//!
+//! Each origin has its own areas. `localStorage` areas are kept in JSON files
+//! under `$DENO_DIR/web_storage`, named by the hash of the origin, while
+//! `sessionStorage` areas only last as long as the process.
+
+use crate::checksum;
+use crate::op_error::OpError;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use url::Url;
+
+/// Maximum size of an area, counted in UTF-16 code units of its keys and
+/// values like browsers do.
+pub const QUOTA: usize = 10 * 1024 * 1024;
+
+/// The origin storage belongs to: the origin of `--location` if given, else
+/// the origin of a remote main module, else the URL of the main module, so
+/// that local scripts don't share storage.
+pub fn origin(location: Option<&Url>, main_module: &ModuleSpecifier) -> String {
+  let url = location.unwrap_or_else(|| main_module.as_url());
+  match url.scheme() {
+    "http" | "https" => url.origin().ascii_serialization(),
+    _ => url.to_string(),
+  }
+}
+
+fn utf16_len(s: &str) -> usize {
+  s.encode_utf16().count()
+}
+
+#[derive(Default)]
+pub struct StorageArea {
+  /// File the area is saved to, for `localStorage`.
+  path: Option<PathBuf>,
+  items: IndexMap<String, String>,
+  size: usize,
+}
+
+impl StorageArea {
+  fn load(path: PathBuf) -> Result<Self, OpError> {
+    let mut area = StorageArea {
+      path: Some(path),
+      ..StorageArea::default()
+    };
+    let source = match fs::read_to_string(area.path.as_ref().unwrap()) {
+      Ok(source) => source,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(area),
+      Err(e) => return Err(e.into()),
+    };
+    let items: serde_json::Map<String, serde_json::Value> =
+      serde_json::from_str(&source)?;
+    for (key, value) in items {
+      if let serde_json::Value::String(value) = value {
+        area.size += utf16_len(&key) + utf16_len(&value);
+        area.items.insert(key, value);
+      }
+    }
+    Ok(area)
+  }
+
+  fn save(&self) -> Result<(), OpError> {
+    let path = match &self.path {
+      Some(path) => path,
+      None => return Ok(()),
+    };
+    fs::create_dir_all(path.parent().unwrap())?;
+    // Written to a temporary file first, so the area isn't lost if the
+    // process dies while writing.
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string(&self.items)?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+  }
+
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn key(&self, index: usize) -> Option<&str> {
+    self.items.get_index(index).map(|(key, _)| key.as_str())
+  }
+
+  pub fn keys(&self) -> Vec<String> {
+    self.items.keys().cloned().collect()
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.items.get(key).map(String::as_str)
+  }
+
+  /// Errors with "QuotaExceededError" if the area would grow past `QUOTA`.
+  pub fn set(&mut self, key: String, value: String) -> Result<(), OpError> {
+    let old_size = self
+      .items
+      .get(&key)
+      .map_or(0, |old| utf16_len(&key) + utf16_len(old));
+    let size = self.size - old_size + utf16_len(&key) + utf16_len(&value);
+    if size > QUOTA {
+      return Err(OpError::quota_exceeded(format!(
+        "Exceeded the storage quota of {} bytes",
+        QUOTA
+      )));
+    }
+    self.items.insert(key, value);
+    self.size = size;
+    self.save()
+  }
+
+  pub fn remove(&mut self, key: &str) -> Result<(), OpError> {
+    if let Some(value) = self.items.shift_remove(key) {
+      self.size -= utf16_len(key) + utf16_len(&value);
+      self.save()?;
+    }
+    Ok(())
+  }
+
+  pub fn clear(&mut self) -> Result<(), OpError> {
+    self.items.clear();
+    self.size = 0;
+    self.save()
+  }
+}
+
+/// The storage areas of all origins, loaded on first use. It is stored in
+/// `GlobalState`.
+#[derive(Clone)]
+pub struct WebStorage(Arc<Mutex<WebStorageInner>>);
+
+struct WebStorageInner {
+  dir: PathBuf,
+  /// Areas by origin, and whether they're persistent (`localStorage`).
+  areas: HashMap<(String, bool), StorageArea>,
+}
+
+impl WebStorage {
+  pub fn new(dir: PathBuf) -> Self {
+    WebStorage(Arc::new(Mutex::new(WebStorageInner {
+      dir,
+      areas: HashMap::new(),
+    })))
+  }
+
+  /// Calls `f` with the `localStorage` area of the origin if `persistent`,
+  /// else with its `sessionStorage` area.
+  pub fn with_area<T>(
+    &self,
+    origin: &str,
+    persistent: bool,
+    f: impl FnOnce(&mut StorageArea) -> Result<T, OpError>,
+  ) -> Result<T, OpError> {
+    let mut inner = self.0.lock().unwrap();
+    let key = (origin.to_string(), persistent);
+    if !inner.areas.contains_key(&key) {
+      let area = if persistent {
+        let file_name = format!("{}.json", checksum::gen2(origin));
+        StorageArea::load(inner.dir.join(file_name))?
+      } else {
+        StorageArea::default()
+      };
+      inner.areas.insert(key.clone(), area);
+    }
+    f(inner.areas.get_mut(&key).unwrap())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn storage_origin() {
+    let main_module =
+      ModuleSpecifier::resolve_url("https://deno.land/std/mod.ts").unwrap();
+    assert_eq!(origin(None, &main_module), "https://deno.land");
+    let location = Url::parse("http://localhost:8000/app/").unwrap();
+    assert_eq!(
+      origin(Some(&location), &main_module),
+      "http://localhost:8000"
+    );
+    let main_module =
+      ModuleSpecifier::resolve_url("file:///project/main.ts").unwrap();
+    assert_eq!(origin(None, &main_module), "file:///project/main.ts");
+  }
+
+  #[test]
+  fn persist_local_storage() {
+    let temp_dir = tempfile::TempDir::new().expect("tempdir fail");
+    let storage = WebStorage::new(temp_dir.path().to_path_buf());
+    storage
+      .with_area("https://deno.land", true, |area| {
+        area.set("a".to_string(), "1".to_string())?;
+        area.set("b".to_string(), "2".to_string())?;
+        area.remove("a")
+      })
+      .unwrap();
+    storage
+      .with_area("https://deno.land", false, |area| {
+        area.set("session".to_string(), "1".to_string())
+      })
+      .unwrap();
+
+    // A new process sees what was saved, except `sessionStorage`.
+    let storage = WebStorage::new(temp_dir.path().to_path_buf());
+    let keys = storage
+      .with_area("https://deno.land", true, |area| Ok(area.keys()))
+      .unwrap();
+    assert_eq!(keys, vec!["b".to_string()]);
+    let len = storage
+      .with_area("https://deno.land", false, |area| Ok(area.len()))
+      .unwrap();
+    assert_eq!(len, 0);
+    let len = storage
+      .with_area("https://example.com", true, |area| Ok(area.len()))
+      .unwrap();
+    assert_eq!(len, 0);
+  }
+
+  #[test]
+  fn quota() {
+    let mut area = StorageArea::default();
+    let value = "x".repeat(QUOTA / 2);
+    area.set("a".to_string(), value.clone()).unwrap();
+    assert!(area.set("b".to_string(), value.clone()).is_err());
+    assert_eq!(area.get("b"), None);
+    // Replacing a value only counts the difference.
+    area.set("a".to_string(), value.clone()).unwrap();
+    area.clear().unwrap();
+    area.set("b".to_string(), value).unwrap();
+  }
+}
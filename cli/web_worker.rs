@@ -5,6 +5,7 @@ use crate::worker::Worker;
 use crate::worker::WorkerEvent;
 use crate::worker::WorkerHandle;
 use deno_core::v8;
+use deno_core::Buf;
 use deno_core::ErrBox;
 use deno_core::StartupData;
 use futures::channel::mpsc;
@@ -15,10 +16,40 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::pin::Pin;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WebWorkerStatus {
+  Running,
+  Terminated,
+  Errored,
+}
+
+impl WebWorkerStatus {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      WebWorkerStatus::Running => "running",
+      WebWorkerStatus::Terminated => "terminated",
+      WebWorkerStatus::Errored => "errored",
+    }
+  }
+}
+
+/// Bookkeeping information about a worker that can be inspected by the host,
+/// used to list live workers (`Deno.workers()`).
+pub struct WebWorkerInfo {
+  pub name: String,
+  pub specifier: String,
+  pub created_at: Instant,
+  /// Number of messages posted by host that worker hasn't processed yet.
+  pub queued_messages: AtomicUsize,
+  errored: AtomicBool,
+}
 
 /// Wrapper for `WorkerHandle` that adds functionality
 /// for terminating workers.
@@ -33,6 +64,7 @@ pub struct WebWorkerHandle {
   terminate_tx: mpsc::Sender<()>,
   terminated: Arc<AtomicBool>,
   isolate_handle: v8::IsolateHandle,
+  pub info: Arc<WebWorkerInfo>,
 }
 
 impl Deref for WebWorkerHandle {
@@ -49,6 +81,33 @@ impl DerefMut for WebWorkerHandle {
 }
 
 impl WebWorkerHandle {
+  /// Post message to worker as a host.
+  pub fn post_message(&self, buf: Buf) -> Result<(), ErrBox> {
+    // Count the message before sending it, otherwise the worker could
+    // receive it and decrement the counter first.
+    self.info.queued_messages.fetch_add(1, Ordering::SeqCst);
+    let result = self.worker_handle.post_message(buf);
+    if result.is_err() {
+      self.info.queued_messages.fetch_sub(1, Ordering::SeqCst);
+    }
+    result
+  }
+
+  /// Mark worker as failed with a terminal error.
+  pub fn set_errored(&self) {
+    self.info.errored.store(true, Ordering::SeqCst);
+  }
+
+  pub fn status(&self) -> WebWorkerStatus {
+    if self.info.errored.load(Ordering::SeqCst) {
+      WebWorkerStatus::Errored
+    } else if self.terminated.load(Ordering::Relaxed) {
+      WebWorkerStatus::Terminated
+    } else {
+      WebWorkerStatus::Running
+    }
+  }
+
   pub fn terminate(&self) {
     // This function can be called multiple times by whomever holds
     // the handle. However only a single "termination" should occur so
@@ -58,9 +117,13 @@ impl WebWorkerHandle {
     if !already_terminated {
       self.isolate_handle.terminate_execution();
       let mut sender = self.terminate_tx.clone();
-      // This call should be infallible hence the `expect`.
-      // This might change in the future.
-      sender.try_send(()).expect("Failed to terminate");
+      // Each sender has a guaranteed slot in the channel, so the only way
+      // for this call to fail is when the worker has already shut down
+      // and dropped the receiving end - in that case there's nothing
+      // left to terminate.
+      if let Err(err) = sender.try_send(()) {
+        assert!(err.is_disconnected(), "Failed to terminate");
+      }
     }
   }
 }
@@ -98,12 +161,20 @@ impl WebWorker {
       .unwrap()
       .thread_safe_handle();
     let (terminate_tx, terminate_rx) = mpsc::channel::<()>(1);
+    let info = WebWorkerInfo {
+      name: worker.name.clone(),
+      specifier: state.borrow().main_module.to_string(),
+      created_at: Instant::now(),
+      queued_messages: AtomicUsize::new(0),
+      errored: AtomicBool::new(false),
+    };
 
     let handle = WebWorkerHandle {
       worker_handle: worker.thread_safe_handle(),
       terminated,
       isolate_handle,
       terminate_tx,
+      info: Arc::new(info),
     };
 
     let mut web_worker = Self {
@@ -128,15 +199,26 @@ impl WebWorker {
       ops::worker_host::init(isolate, &state);
       ops::io::init(isolate, &state);
       ops::resources::init(isolate, &state);
+      ops::blob::init(isolate, &state);
+      ops::cache_storage::init(isolate, &state);
+      ops::cancel::init(isolate, &state);
       ops::errors::init(isolate, &state);
       ops::timers::init(isolate, &state);
+      ops::performance::init(isolate, &state);
       ops::fetch::init(isolate, &state);
+      ops::stream_resource::init(isolate, &state);
+      ops::multipart::init(isolate, &state);
+      ops::compression::init(isolate, &state);
+      ops::encoding::init(isolate, &state);
+      ops::url::init(isolate, &state);
 
       if has_deno_namespace {
         ops::runtime_compiler::init(isolate, &state);
         ops::fs::init(isolate, &state);
         ops::fs_events::init(isolate, &state);
+        ops::kv::init(isolate, &state);
         ops::plugins::init(isolate, &state);
+        ops::ffi::init(isolate, &state);
         ops::net::init(isolate, &state);
         ops::tls::init(isolate, &state);
         ops::os::init(isolate, &state);
@@ -159,6 +241,20 @@ impl WebWorker {
   }
 }
 
+impl Drop for WebWorker {
+  fn drop(&mut self) {
+    // Terminating a worker cascades to all workers it spawned; this
+    // recurses down the worker tree as each child is dropped on its own
+    // thread before `join()` returns.
+    let workers = std::mem::take(&mut self.worker.state.borrow_mut().workers);
+    for (id, (join_handle, worker_handle)) in workers {
+      debug!("Terminating nested worker {} of {}", id, self.worker.name);
+      worker_handle.terminate();
+      join_handle.join().expect("Panic in worker thread");
+    }
+  }
+}
+
 impl Deref for WebWorker {
   type Target = Worker;
   fn deref(&self) -> &Self::Target {
@@ -216,9 +312,20 @@ impl Future for WebWorker {
     {
       match r {
         Some(msg) => {
-          let msg = String::from_utf8(msg.to_vec()).unwrap();
+          inner
+            .handle
+            .info
+            .queued_messages
+            .fetch_sub(1, Ordering::SeqCst);
+          // Message is passed as a string literal and deserialized in JS,
+          // so a malformed payload results in a `messageerror` event instead
+          // of a syntax error in the callback script.
+          let msg = String::from_utf8_lossy(&msg).to_string();
           debug!("received message from host: {}", msg);
-          let script = format!("workerMessageRecvCallback({})", msg);
+          let script = format!(
+            "workerMessageRecvCallback({})",
+            serde_json::to_string(&msg).unwrap()
+          );
 
           if let Err(e) = worker.execute(&script) {
             // If execution was terminated during message callback then
@@ -302,6 +409,8 @@ mod tests {
 
       let maybe_msg = handle.get_event().await;
       assert!(maybe_msg.is_some());
+      assert_eq!(handle.info.queued_messages.load(Ordering::SeqCst), 0);
+      assert_eq!(handle.status(), WebWorkerStatus::Running);
 
       let r = handle.post_message(msg.clone());
       assert!(r.is_ok());
@@ -323,6 +432,7 @@ mod tests {
       assert!(r.is_ok());
       let event = handle.get_event().await;
       assert!(event.is_none());
+      assert_eq!(handle.status(), WebWorkerStatus::Terminated);
       handle.sender.close_channel();
     });
     join_handle.join().expect("Failed to join worker thread");
@@ -1,9 +1,12 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::colors;
 use crate::fmt_errors::JSError;
 use crate::inspector::DenoInspector;
 use crate::ops;
+use crate::startup_timing::Phase;
 use crate::state::DebugType;
 use crate::state::State;
+use crate::trace_events::TraceEvents;
 use deno_core::Buf;
 use deno_core::ErrBox;
 use deno_core::ModuleId;
@@ -15,13 +18,18 @@ use futures::stream::StreamExt;
 use futures::task::AtomicWaker;
 use std::env;
 use std::future::Future;
+use std::io::Write;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::path::Path;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::Mutex as AsyncMutex;
 use url::Url;
 
@@ -93,6 +101,10 @@ pub struct Worker {
   pub(crate) internal_channels: WorkerChannelsInternal,
   external_channels: WorkerHandle,
   pub(crate) inspector: Option<Box<DenoInspector>>,
+  // Set by `--inspect-brk` for the main worker, until the main module runs.
+  should_break_on_first_statement: bool,
+  warn_slow_ops: Option<u64>,
+  trace_events: Option<Arc<TraceEvents>>,
 }
 
 impl Worker {
@@ -102,18 +114,58 @@ impl Worker {
 
     let global_state = state.borrow().global_state.clone();
 
-    let inspect = global_state.flags.inspect.as_ref();
-    let inspect_brk = global_state.flags.inspect_brk.as_ref();
-    let inspector = inspect
-      .or(inspect_brk)
-      .and_then(|host| match state.borrow().debug_type {
-        DebugType::Main if inspect_brk.is_some() => Some((host, true)),
-        DebugType::Main | DebugType::Dependent => Some((host, false)),
-        DebugType::Internal => None,
-      })
-      .map(|(host, wait_for_debugger)| {
-        DenoInspector::new(&mut isolate, *host, wait_for_debugger)
+    let flags = &global_state.flags;
+    let inspect_brk = flags.inspect_brk.as_ref();
+    let inspect_host = flags.inspect.as_ref().or(inspect_brk).copied();
+    // Coverage and CPU profiles are collected through the inspector, with no
+    // debugger front-end involved.
+    let inspect_locally =
+      flags.coverage_dir.is_some() || flags.cpu_profile.is_some();
+    let debug_type = state.borrow().debug_type;
+    let has_inspector = match debug_type {
+      DebugType::Main => inspect_host.is_some() || inspect_locally,
+      DebugType::Dependent => inspect_host.is_some(),
+      DebugType::Internal => false,
+    };
+    let inspector = if has_inspector {
+      let inspector = DenoInspector::new(&mut isolate, inspect_host);
+      state.borrow_mut().inspector_session = Some(inspector.local_session());
+      Some(inspector)
+    } else {
+      None
+    };
+    let should_break_on_first_statement = inspector.is_some()
+      && inspect_brk.is_some()
+      && debug_type == DebugType::Main;
+    // Coverage starts before any module is evaluated; `deno test` takes it
+    // once the tests finished.
+    if debug_type == DebugType::Main && flags.coverage_dir.is_some() {
+      let session = state.borrow().inspector_session.clone().unwrap();
+      session
+        .post_message("Profiler.enable", json!({}))
+        .and_then(|_| {
+          let params = json!({ "callCount": true, "detailed": true });
+          session.post_message("Profiler.startPreciseCoverage", params)
+        })
+        .expect("Failed to start coverage");
+    }
+
+    let warn_slow_ops = global_state.flags.warn_slow_ops;
+    if let Some(threshold) = warn_slow_ops {
+      let state_ = state.clone();
+      isolate.set_sync_op_timing_fn(move |name, duration| {
+        state_
+          .borrow_mut()
+          .metrics
+          .op_sync_duration
+          .record(duration);
+        if duration.as_millis() > threshold as u128 {
+          warn_slow(&format!("sync op \"{}\"", name), duration, threshold);
+        }
       });
+    }
+
+    let trace_events = global_state.trace_events.clone();
 
     isolate.set_js_error_create_fn(move |core_js_error| {
       JSError::create(core_js_error, &global_state.ts_compiler)
@@ -129,6 +181,9 @@ impl Worker {
       internal_channels,
       external_channels,
       inspector,
+      should_break_on_first_statement,
+      warn_slow_ops,
+      trace_events,
     }
   }
 
@@ -163,7 +218,8 @@ impl Worker {
     module_specifier: &ModuleSpecifier,
   ) -> Result<(), ErrBox> {
     let id = self.preload_module(module_specifier).await?;
-    self.isolate.mod_evaluate(id)
+    self.wait_for_inspector_session();
+    self.evaluate_module(id, module_specifier)
   }
 
   /// Loads, instantiates and executes provided source code
@@ -177,7 +233,42 @@ impl Worker {
       .isolate
       .load_module(module_specifier, Some(code))
       .await?;
-    self.isolate.mod_evaluate(id)
+    self.wait_for_inspector_session();
+    self.evaluate_module(id, module_specifier)
+  }
+
+  fn evaluate_module(
+    &mut self,
+    id: ModuleId,
+    module_specifier: &ModuleSpecifier,
+  ) -> Result<(), ErrBox> {
+    let start = Instant::now();
+    let result = self.isolate.mod_evaluate(id);
+    {
+      let state = self.state.borrow();
+      if state.debug_type == DebugType::Main {
+        let startup_timing = &state.global_state.startup_timing;
+        startup_timing.record(Phase::Evaluate, start.elapsed());
+      }
+    }
+    if let Some(trace_events) = &self.trace_events {
+      let args = json!({ "specifier": module_specifier.to_string() });
+      trace_events.complete("module", "evaluate", start, args);
+    }
+    result
+  }
+
+  /// With `--inspect-brk`, waits for a debugger to connect before the main
+  /// module is evaluated, and pauses on its first statement.
+  fn wait_for_inspector_session(&mut self) {
+    if self.should_break_on_first_statement {
+      self.should_break_on_first_statement = false;
+      self
+        .inspector
+        .as_mut()
+        .unwrap()
+        .wait_for_session_and_break_on_next_statement();
+    }
   }
 
   /// Returns a way to communicate with the Worker from other threads.
@@ -205,10 +296,40 @@ impl Future for Worker {
       let _ = deno_inspector.poll_unpin(cx);
     }
     inner.waker.register(cx.waker());
-    inner.isolate.poll_unpin(cx)
+    if inner.warn_slow_ops.is_none() && inner.trace_events.is_none() {
+      return inner.isolate.poll_unpin(cx);
+    }
+    // A single poll of the isolate runs all JS callbacks of one tick.
+    let start = Instant::now();
+    let poll = inner.isolate.poll_unpin(cx);
+    if let Some(trace_events) = &inner.trace_events {
+      trace_events.complete("event_loop", "tick", start, json!({}));
+    }
+    if let Some(threshold) = inner.warn_slow_ops {
+      let duration = start.elapsed();
+      inner
+        .state
+        .borrow_mut()
+        .metrics
+        .tick_duration
+        .record(duration);
+      if duration.as_millis() > threshold as u128 {
+        warn_slow("event loop tick", duration, threshold);
+      }
+    }
+    poll
   }
 }
 
+fn warn_slow(what: &str, duration: Duration, threshold: u64) {
+  warn!(
+    "{} took {}ms (threshold: {}ms)",
+    what,
+    duration.as_millis(),
+    threshold
+  );
+}
+
 impl Deref for Worker {
   type Target = deno_core::EsIsolate;
   fn deref(&self) -> &Self::Target {
@@ -238,27 +359,221 @@ impl MainWorker {
       let isolate = &mut worker.isolate;
       ops::runtime::init(isolate, &state);
       ops::runtime_compiler::init(isolate, &state);
+      ops::blob::init(isolate, &state);
+      ops::cache_storage::init(isolate, &state);
+      ops::cancel::init(isolate, &state);
+      ops::compression::init(isolate, &state);
+      ops::encoding::init(isolate, &state);
       ops::errors::init(isolate, &state);
       ops::fetch::init(isolate, &state);
       ops::fs::init(isolate, &state);
       ops::fs_events::init(isolate, &state);
+      ops::inspector::init(isolate, &state);
       ops::io::init(isolate, &state);
+      ops::kv::init(isolate, &state);
+      ops::multipart::init(isolate, &state);
       ops::plugins::init(isolate, &state);
+      ops::ffi::init(isolate, &state);
       ops::net::init(isolate, &state);
       ops::tls::init(isolate, &state);
       ops::os::init(isolate, &state);
+      ops::performance::init(isolate, &state);
       ops::permissions::init(isolate, &state);
       ops::process::init(isolate, &state);
       ops::random::init(isolate, &state);
+      #[cfg(feature = "repl")]
       ops::repl::init(isolate, &state);
       ops::resources::init(isolate, &state);
       ops::signal::init(isolate, &state);
+      ops::stream_resource::init(isolate, &state);
+      ops::testing::init(isolate, &state);
       ops::timers::init(isolate, &state);
       ops::tty::init(isolate, &state);
+      ops::url::init(isolate, &state);
+      ops::web_storage::init(isolate, &state);
       ops::worker_host::init(isolate, &state);
     }
     Self(worker)
   }
+
+  /// Ends startup timing once the main module was evaluated, printing it
+  /// with `--startup-timing` or as debug log.
+  pub fn finish_startup(&self) {
+    let global_state = self.state.borrow().global_state.clone();
+    let report = global_state.startup_timing.finish();
+    if global_state.flags.startup_timing {
+      eprintln!("{}", report);
+    } else {
+      debug!("{}", report);
+    }
+  }
+
+  /// Exit code requested by `Deno.exit()`, if it was called, or 130 if
+  /// Ctrl-C was pressed while it performs graceful shutdown.
+  pub fn exit_code(&self) -> Option<i32> {
+    let state = self.state.borrow();
+    let interrupted = state
+      .sigint
+      .as_ref()
+      .map_or(false, |sigint| sigint.load(Ordering::SeqCst));
+    state
+      .exit_code
+      .or_else(|| if interrupted { Some(130) } else { None })
+  }
+
+  /// Exit code for uncaught exceptions and unhandled promise rejections,
+  /// set with `--uncaught-exit-code`.
+  fn uncaught_exit_code(&self) -> i32 {
+    let state = self.state.borrow();
+    state.global_state.flags.uncaught_exit_code.unwrap_or(1)
+  }
+
+  /// Handles result of running the main module.
+  ///
+  /// If `Deno.exit()` was called or Ctrl-C was pressed, the error produced by
+  /// terminating the isolate is swallowed and the process exits after
+  /// graceful shutdown.
+  /// Uncaught exceptions and unhandled promise rejections are printed, and
+  /// the process exits with the uncaught exit code after graceful shutdown.
+  /// Other errors, eg. failing to load the module, are returned as is.
+  pub async fn handle_exit(
+    &mut self,
+    result: Result<(), ErrBox>,
+  ) -> Result<(), ErrBox> {
+    if let Some(code) = self.exit_code() {
+      self.shutdown().await;
+      std::process::exit(code)
+    }
+    match result {
+      Err(err) if err.downcast_ref::<JSError>().is_some() => {
+        eprintln!(
+          "{}: {}",
+          colors::red_bold("error".to_string()),
+          err.to_string()
+        );
+        self.shutdown().await;
+        // An `unload` listener may have called `Deno.exit()`.
+        let code = self
+          .exit_code()
+          .unwrap_or_else(|| self.uncaught_exit_code());
+        std::process::exit(code)
+      }
+      result => result,
+    }
+  }
+
+  /// Graceful shutdown sequence, performed on `Deno.exit()`, on uncaught
+  /// errors and on Ctrl-C:
+  ///  1. stop keeping event loop alive with newly dispatched async ops,
+  ///  2. dispatch `unload` event,
+  ///  3. let pending ops settle for at most `--shutdown-timeout` ms,
+  ///  4. close remaining resources, eg. files and sockets,
+  ///  5. write metrics and traces requested with `--dump-metrics` and
+  ///     `--trace-ops`,
+  ///  6. flush stdio.
+  async fn shutdown(&mut self) {
+    self.state.borrow_mut().is_shutting_down = true;
+    // `Deno.exit()` or Ctrl-C terminated execution; JS must be able to run
+    // again for `unload` listeners.
+    self
+      .isolate
+      .v8_isolate
+      .as_mut()
+      .unwrap()
+      .thread_safe_handle()
+      .cancel_terminate_execution();
+    let global_state = self.state.borrow().global_state.clone();
+    // Calling `Deno.exit()` again from an `unload` listener is ignored,
+    // the first exit code wins.
+    if let Err(err) = self.execute("window.dispatchEvent(new Event('unload'))")
+    {
+      if !self.isolate.is_execution_terminated() {
+        eprintln!("{}", err.to_string());
+      }
+    }
+    let timeout = global_state.flags.shutdown_timeout.unwrap_or(0);
+    if timeout > 0 {
+      let _ = tokio::time::timeout(
+        std::time::Duration::from_millis(timeout),
+        &mut self.0,
+      )
+      .await;
+    }
+    self.close_resources();
+    self.write_reports();
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+  }
+
+  /// Closes all resources except stdio, so they're released through their
+  /// `Drop` implementations rather than by the OS on exit.
+  fn close_resources(&mut self) {
+    let mut resource_table = self.isolate.resource_table.borrow_mut();
+    for (rid, name) in resource_table.entries() {
+      if !["stdin", "stdout", "stderr"].contains(&name.as_str()) {
+        resource_table.close(rid);
+      }
+    }
+  }
+
+  /// Dispatches `load` event after the main module was evaluated. An
+  /// exception thrown by a listener and not handled by an `error` listener
+  /// is returned.
+  pub fn dispatch_load(&mut self) -> Result<(), ErrBox> {
+    self.execute("window.dispatchEvent(new Event('load'))")
+  }
+
+  /// Dispatches `unload` event after event loop finished normally.
+  ///
+  /// `Deno.exit()` called from an `unload` listener exits the process
+  /// right away, as the event was already dispatched.
+  pub fn dispatch_unload(&mut self) -> Result<(), ErrBox> {
+    let result = self.execute("window.dispatchEvent(new Event('unload'))");
+    self.write_reports();
+    if let Some(code) = self.exit_code() {
+      let _ = std::io::stdout().flush();
+      let _ = std::io::stderr().flush();
+      std::process::exit(code)
+    }
+    result
+  }
+
+  fn write_reports(&self) {
+    self.dump_metrics();
+    self.write_trace_events();
+  }
+
+  /// Writes op traces to the file given with `--trace-ops`.
+  fn write_trace_events(&self) {
+    let trace_events = match &self.trace_events {
+      Some(trace_events) => trace_events,
+      None => return,
+    };
+    let global_state = self.state.borrow().global_state.clone();
+    let path = global_state.flags.trace_ops_file.as_ref().unwrap();
+    if let Err(err) = trace_events.write(Path::new(path)) {
+      eprintln!("Failed to write trace: {}", err);
+    }
+  }
+
+  /// Writes `Deno.metrics()` as JSON to the file given with
+  /// `--dump-metrics`, or to stderr.
+  fn dump_metrics(&self) {
+    let state = self.state.borrow();
+    let target = match &state.global_state.flags.dump_metrics {
+      Some(target) => target,
+      None => return,
+    };
+    let json = state.metrics.to_json(&self.isolate.op_registry);
+    let json = serde_json::to_string_pretty(&json).unwrap();
+    let result = match target {
+      Some(path) => std::fs::write(path, json),
+      None => writeln!(std::io::stderr(), "{}", json),
+    };
+    if let Err(err) = result {
+      eprintln!("Failed to write metrics: {}", err);
+    }
+  }
 }
 
 impl Deref for MainWorker {
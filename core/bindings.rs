@@ -2,6 +2,7 @@
 
 use crate::es_isolate::EsIsolate;
 use crate::isolate::CoreIsolate;
+use crate::isolate::PendingPromiseException;
 use crate::isolate::ZeroCopyBuf;
 use crate::js_errors::JSError;
 
@@ -28,6 +29,12 @@ lazy_static! {
       v8::ExternalReference {
         function: set_macrotask_callback.map_fn_to()
       },
+      v8::ExternalReference {
+        function: set_promise_reject_callback.map_fn_to()
+      },
+      v8::ExternalReference {
+        function: report_uncaught_exception.map_fn_to()
+      },
       v8::ExternalReference {
         function: eval_context.map_fn_to()
       },
@@ -165,6 +172,32 @@ pub fn initialize_context<'s>(
     set_macrotask_callback_val.into(),
   );
 
+  let mut set_promise_reject_callback_tmpl =
+    v8::FunctionTemplate::new(scope, set_promise_reject_callback);
+  let set_promise_reject_callback_val = set_promise_reject_callback_tmpl
+    .get_function(scope, context)
+    .unwrap();
+  core_val.set(
+    context,
+    v8::String::new(scope, "setPromiseRejectCallback")
+      .unwrap()
+      .into(),
+    set_promise_reject_callback_val.into(),
+  );
+
+  let mut report_uncaught_exception_tmpl =
+    v8::FunctionTemplate::new(scope, report_uncaught_exception);
+  let report_uncaught_exception_val = report_uncaught_exception_tmpl
+    .get_function(scope, context)
+    .unwrap();
+  core_val.set(
+    context,
+    v8::String::new(scope, "reportUncaughtException")
+      .unwrap()
+      .into(),
+    report_uncaught_exception_val.into(),
+  );
+
   let mut eval_context_tmpl = v8::FunctionTemplate::new(scope, eval_context);
   let eval_context_val =
     eval_context_tmpl.get_function(scope, context).unwrap();
@@ -210,6 +243,29 @@ pub fn initialize_context<'s>(
     get_promise_details_val.into(),
   );
 
+  let mut get_map_entries_tmpl =
+    v8::FunctionTemplate::new(scope, get_map_entries);
+  let get_map_entries_val =
+    get_map_entries_tmpl.get_function(scope, context).unwrap();
+  core_val.set(
+    context,
+    v8::String::new(scope, "getMapEntries").unwrap().into(),
+    get_map_entries_val.into(),
+  );
+
+  let mut get_typed_array_length_tmpl =
+    v8::FunctionTemplate::new(scope, get_typed_array_length);
+  let get_typed_array_length_val = get_typed_array_length_tmpl
+    .get_function(scope, context)
+    .unwrap();
+  core_val.set(
+    context,
+    v8::String::new(scope, "getTypedArrayLength")
+      .unwrap()
+      .into(),
+    get_typed_array_length_val.into(),
+  );
+
   core_val.set_accessor(
     context,
     v8::String::new(scope, "shared").unwrap().into(),
@@ -333,17 +389,21 @@ pub extern "C" fn promise_reject_callback(message: v8::PromiseRejectMessage) {
   match message.get_event() {
     v8::PromiseRejectEvent::PromiseRejectWithNoHandler => {
       let error = message.get_value();
-      let mut error_global = v8::Global::<v8::Value>::new();
-      error_global.set(scope, error);
+      let mut pending = PendingPromiseException {
+        promise: v8::Global::<v8::Promise>::new(),
+        reason: v8::Global::<v8::Value>::new(),
+      };
+      pending.promise.set(scope, promise);
+      pending.reason.set(scope, error);
       core_isolate
         .pending_promise_exceptions
-        .insert(promise_id, error_global);
+        .insert(promise_id, pending);
     }
     v8::PromiseRejectEvent::PromiseHandlerAddedAfterReject => {
-      if let Some(mut handle) =
+      if let Some(mut pending) =
         core_isolate.pending_promise_exceptions.remove(&promise_id)
       {
-        handle.reset(scope);
+        pending.reset(scope);
       }
     }
     v8::PromiseRejectEvent::PromiseRejectAfterResolved => {}
@@ -502,6 +562,57 @@ fn set_macrotask_callback(
   core_isolate.js_macrotask_cb.set(scope, macrotask_cb_fn);
 }
 
+fn set_promise_reject_callback(
+  scope: v8::FunctionCallbackScope,
+  args: v8::FunctionCallbackArguments,
+  _rv: v8::ReturnValue,
+) {
+  let core_isolate: &mut CoreIsolate =
+    unsafe { &mut *(scope.isolate().get_data(0) as *mut CoreIsolate) };
+
+  if !core_isolate.js_promise_reject_cb.is_empty() {
+    let msg = v8::String::new(
+      scope,
+      "Deno.core.setPromiseRejectCallback already called.",
+    )
+    .unwrap();
+    scope.isolate().throw_exception(msg.into());
+    return;
+  }
+
+  let promise_reject_cb_fn =
+    match v8::Local::<v8::Function>::try_from(args.get(0)) {
+      Ok(f) => f,
+      Err(_) => {
+        let msg = v8::String::new(scope, "Invalid argument").unwrap();
+        let exception = v8::Exception::type_error(scope, msg);
+        scope.isolate().throw_exception(exception);
+        return;
+      }
+    };
+  core_isolate
+    .js_promise_reject_cb
+    .set(scope, promise_reject_cb_fn);
+}
+
+/// Makes the exception uncaught, as if it was thrown to the isolate: the
+/// current `execute()` or poll of the event loop returns it as an error.
+/// Only the first reported exception is kept.
+fn report_uncaught_exception(
+  scope: v8::FunctionCallbackScope,
+  args: v8::FunctionCallbackArguments,
+  _rv: v8::ReturnValue,
+) {
+  let core_isolate: &mut CoreIsolate =
+    unsafe { &mut *(scope.isolate().get_data(0) as *mut CoreIsolate) };
+
+  if core_isolate.pending_uncaught_exception.is_empty() {
+    core_isolate
+      .pending_uncaught_exception
+      .set(scope, args.get(0));
+  }
+}
+
 fn eval_context(
   scope: v8::FunctionCallbackScope,
   args: v8::FunctionCallbackArguments,
@@ -582,7 +693,67 @@ fn eval_context(
     return;
   }
 
+  core_isolate.eval_state.lock().unwrap().depth += 1;
   let result = maybe_script.unwrap().run(scope, context);
+  let interrupted = {
+    let mut eval_state = core_isolate.eval_state.lock().unwrap();
+    eval_state.depth -= 1;
+    std::mem::replace(&mut eval_state.interrupted, false)
+  };
+
+  if interrupted {
+    // Evaluation was interrupted through `EvalInterruptHandle`; let the
+    // caller continue running. The termination is cancelled even if the
+    // script finished before it took effect, so it doesn't hit later code.
+    // TODO(piscisaureus): in rusty_v8, `cancel_terminate_execution()` should
+    // also be implemented on `struct Isolate`.
+    scope
+      .isolate()
+      .thread_safe_handle()
+      .cancel_terminate_execution();
+  }
+
+  if result.is_none() && interrupted {
+    output.set(
+      context,
+      v8::Integer::new(scope, 0).into(),
+      v8::null(scope).into(),
+    );
+
+    let errinfo_obj = v8::Object::new(scope);
+    errinfo_obj.set(
+      context,
+      v8::String::new(scope, "isCompileError").unwrap().into(),
+      v8::Boolean::new(scope, false).into(),
+    );
+
+    errinfo_obj.set(
+      context,
+      v8::String::new(scope, "isNativeError").unwrap().into(),
+      v8::Boolean::new(scope, false).into(),
+    );
+
+    errinfo_obj.set(
+      context,
+      v8::String::new(scope, "isInterrupted").unwrap().into(),
+      v8::Boolean::new(scope, true).into(),
+    );
+
+    errinfo_obj.set(
+      context,
+      v8::String::new(scope, "thrown").unwrap().into(),
+      v8::undefined(scope).into(),
+    );
+
+    output.set(
+      context,
+      v8::Integer::new(scope, 1).into(),
+      errinfo_obj.into(),
+    );
+
+    rv.set(output.into());
+    return;
+  }
 
   if result.is_none() {
     assert!(tc.has_caught());
@@ -864,3 +1035,61 @@ fn get_promise_details(
     }
   }
 }
+
+// Returns the entries of a Map as an array of keys and values,
+// [key0, value0, key1, value1, ...], read by V8 rather than by calling the
+// (overridable) iterator methods. Throws TypeError if the argument isn't a
+// Map.
+fn get_map_entries(
+  scope: v8::FunctionCallbackScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let map = match v8::Local::<v8::Map>::try_from(args.get(0)) {
+    Ok(map) => map,
+    Err(_) => {
+      let msg = v8::String::new(scope, "Invalid argument").unwrap();
+      let exception = v8::Exception::type_error(scope, msg);
+      scope.isolate().throw_exception(exception);
+      return;
+    }
+  };
+  rv.set(map.as_array(scope).into());
+}
+
+// Returns the number of elements of a TypedArray, computed from the length
+// of its view of the buffer rather than read from the (overridable) `length`
+// getter. Throws TypeError if the argument isn't a TypedArray.
+fn get_typed_array_length(
+  scope: v8::FunctionCallbackScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let value = args.get(0);
+  let element_size = if value.is_int8_array()
+    || value.is_uint8_array()
+    || value.is_uint8_clamped_array()
+  {
+    1
+  } else if value.is_int16_array() || value.is_uint16_array() {
+    2
+  } else if value.is_int32_array()
+    || value.is_uint32_array()
+    || value.is_float32_array()
+  {
+    4
+  } else if value.is_float64_array()
+    || value.is_big_int64_array()
+    || value.is_big_uint64_array()
+  {
+    8
+  } else {
+    let msg = v8::String::new(scope, "Invalid argument").unwrap();
+    let exception = v8::Exception::type_error(scope, msg);
+    scope.isolate().throw_exception(exception);
+    return;
+  };
+  let view = v8::Local::<v8::ArrayBufferView>::try_from(value).unwrap();
+  let length = view.byte_length() / element_size;
+  rv.set(v8::Number::new(scope, length as f64).into());
+}
@@ -267,10 +267,10 @@ impl EsIsolate {
         let promise = v8::Local::<v8::Promise>::try_from(value)
           .expect("Expected to get promise as module evaluation result");
         let promise_id = promise.get_identity_hash();
-        if let Some(mut handle) =
+        if let Some(mut pending) =
           core_isolate.pending_promise_exceptions.remove(&promise_id)
         {
-          handle.reset(scope);
+          pending.reset(scope);
         }
       } else {
         assert!(status == v8::ModuleStatus::Errored);
@@ -34,6 +34,8 @@ use std::rc::Rc;
 use std::sync::{Arc, Mutex, Once};
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
 
 type PendingOpFuture = Pin<Box<dyn Future<Output = (OpId, Buf)>>>;
 
@@ -139,6 +141,59 @@ pub enum StartupData<'a> {
 
 type JSErrorCreateFn = dyn Fn(JSError) -> ErrBox;
 type IsolateErrorHandleFn = dyn FnMut(ErrBox) -> Result<(), ErrBox>;
+type SyncOpTimingFn = dyn Fn(&str, Duration);
+
+/// Thread-safe handle that interrupts code run by `Deno.core.evalContext()`.
+///
+/// Unlike terminating the isolate, an interrupted `evalContext()` call
+/// returns to its caller with `isInterrupted` set in the error info, so
+/// embedders like the REPL can abort long-running evaluations and carry on.
+/// If the isolate isn't inside `evalContext()`, nothing happens.
+#[derive(Clone)]
+pub struct EvalInterruptHandle {
+  isolate_handle: v8::IsolateHandle,
+  state: Arc<Mutex<EvalState>>,
+}
+
+impl EvalInterruptHandle {
+  /// Returns false if no evaluation is running or the isolate has been
+  /// disposed.
+  pub fn interrupt(&self) -> bool {
+    // The lock is held while execution is terminated, so the evaluation
+    // can't finish in between.
+    let mut state = self.state.lock().unwrap();
+    if state.depth == 0 {
+      return false;
+    }
+    state.interrupted = true;
+    self.isolate_handle.terminate_execution()
+  }
+}
+
+/// State of `Deno.core.evalContext()` calls, shared with
+/// `EvalInterruptHandle`.
+#[derive(Default)]
+pub(crate) struct EvalState {
+  /// Number of `evalContext()` calls in progress.
+  pub depth: usize,
+  /// Set by `EvalInterruptHandle::interrupt()` until the evaluation that
+  /// was interrupted returns.
+  pub interrupted: bool,
+}
+
+/// A promise that was rejected without a handler. It's kept until the end of
+/// the tick, in case a handler is added.
+pub(crate) struct PendingPromiseException {
+  pub promise: v8::Global<v8::Promise>,
+  pub reason: v8::Global<v8::Value>,
+}
+
+impl PendingPromiseException {
+  pub fn reset(&mut self, scope: &mut impl v8::InIsolate) {
+    self.promise.reset(scope);
+    self.reason.reset(scope);
+  }
+}
 
 /// A single execution context of JavaScript. Corresponds roughly to the "Web
 /// Worker" concept in the DOM. An CoreIsolate is a Future that can be used with
@@ -158,7 +213,9 @@ pub struct CoreIsolate {
   pub(crate) shared_ab: v8::Global<v8::SharedArrayBuffer>,
   pub(crate) js_recv_cb: v8::Global<v8::Function>,
   pub(crate) js_macrotask_cb: v8::Global<v8::Function>,
-  pub(crate) pending_promise_exceptions: HashMap<i32, v8::Global<v8::Value>>,
+  pub(crate) js_promise_reject_cb: v8::Global<v8::Function>,
+  pub(crate) pending_promise_exceptions: HashMap<i32, PendingPromiseException>,
+  pub(crate) pending_uncaught_exception: v8::Global<v8::Value>,
   shared_isolate_handle: Arc<Mutex<Option<*mut v8::Isolate>>>,
   pub(crate) js_error_create_fn: Box<JSErrorCreateFn>,
   needs_init: bool,
@@ -170,10 +227,18 @@ pub struct CoreIsolate {
   pub op_registry: OpRegistry,
   waker: AtomicWaker,
   error_handler: Option<Box<IsolateErrorHandleFn>>,
+  sync_op_timing_fn: Option<Rc<SyncOpTimingFn>>,
+  current_op_id: Option<OpId>,
+  pub(crate) eval_state: Arc<Mutex<EvalState>>,
 }
 
 impl Drop for CoreIsolate {
   fn drop(&mut self) {
+    // The table is shared with op futures and embedders, so it may outlive
+    // the isolate; close resources (files, sockets, child processes) now
+    // rather than leaking them until the last reference goes away.
+    self.resource_table.borrow_mut().close_all();
+
     if let Some(creator) = self.snapshot_creator.take() {
       // TODO(ry): in rusty_v8, `SnapShotCreator::get_owned_isolate()` returns
       // a `struct OwnedIsolate` which is not actually owned, hence the need
@@ -209,6 +274,8 @@ pub unsafe fn v8_init() {
     "".to_string(),
     "--no-wasm-async-compilation".to_string(),
     "--harmony-top-level-await".to_string(),
+    // Enables `WeakRef` and `FinalizationRegistry`.
+    "--harmony-weak-refs".to_string(),
   ];
   v8::V8::set_flags_from_command_line(argv);
 }
@@ -287,6 +354,8 @@ impl CoreIsolate {
       shared_ab: v8::Global::<v8::SharedArrayBuffer>::new(),
       js_recv_cb: v8::Global::<v8::Function>::new(),
       js_macrotask_cb: v8::Global::<v8::Function>::new(),
+      js_promise_reject_cb: v8::Global::<v8::Function>::new(),
+      pending_uncaught_exception: v8::Global::<v8::Value>::new(),
       snapshot_creator: maybe_snapshot_creator,
       has_snapshotted: false,
       shared_isolate_handle: Arc::new(Mutex::new(None)),
@@ -300,6 +369,9 @@ impl CoreIsolate {
       op_registry: OpRegistry::new(),
       waker: AtomicWaker::new(),
       error_handler: None,
+      sync_op_timing_fn: None,
+      current_op_id: None,
+      eval_state: Arc::new(Mutex::new(EvalState::default())),
     };
 
     let mut boxed_isolate = Box::new(core_isolate);
@@ -344,6 +416,33 @@ impl CoreIsolate {
     self.js_error_create_fn = Box::new(f);
   }
 
+  /// Allows a callback to be set that is called with the name and the
+  /// duration of every sync op after it was dispatched. Used by embedders
+  /// to collect op timings; by default no timing is measured.
+  pub fn set_sync_op_timing_fn(
+    &mut self,
+    f: impl Fn(&str, Duration) + 'static,
+  ) {
+    self.sync_op_timing_fn = Some(Rc::new(f));
+  }
+
+  /// Returns id of the op whose dispatcher is running. Lets dispatchers
+  /// shared by many ops, eg. ones collecting metrics, tell them apart.
+  pub fn current_op_id(&self) -> Option<OpId> {
+    self.current_op_id
+  }
+
+  /// Returns a handle that can be used from other threads to interrupt
+  /// `Deno.core.evalContext()` calls.
+  pub fn eval_interrupt_handle(&mut self) -> EvalInterruptHandle {
+    // TODO(piscisaureus): in rusty_v8, the `thread_safe_handle()` method
+    // should not require a mutable reference to `struct rusty_v8::Isolate`.
+    EvalInterruptHandle {
+      isolate_handle: self.v8_isolate.as_mut().unwrap().thread_safe_handle(),
+      state: self.eval_state.clone(),
+    }
+  }
+
   /// Executes a bit of built-in JavaScript to provide Deno.sharedQueue.
   pub(crate) fn shared_init(&mut self) {
     if self.needs_init {
@@ -366,7 +465,21 @@ impl CoreIsolate {
     zero_copy_buf: Option<ZeroCopyBuf>,
   ) -> Option<(OpId, Box<[u8]>)> {
     let op = if let Some(dispatcher) = self.op_registry.get(op_id) {
-      dispatcher(self, control_buf, zero_copy_buf)
+      self.current_op_id = Some(op_id);
+      let op = match self.sync_op_timing_fn.clone() {
+        Some(timing_fn) => {
+          let start = Instant::now();
+          let op = dispatcher(self, control_buf, zero_copy_buf);
+          if let Op::Sync(_) = op {
+            let name = self.op_registry.name(op_id).unwrap();
+            timing_fn(name, start.elapsed());
+          }
+          op
+        }
+        None => dispatcher(self, control_buf, zero_copy_buf),
+      };
+      self.current_op_id = None;
+      op
     } else {
       let message =
         v8::String::new(scope, &format!("Unknown op id: {}", op_id)).unwrap();
@@ -398,6 +511,19 @@ impl CoreIsolate {
     }
   }
 
+  /// Whether execution was terminated, eg. with
+  /// `v8::IsolateHandle::terminate_execution()`, and not resumed since. Errors
+  /// returned while this is true were caused by the termination rather than
+  /// by an exception thrown by JavaScript.
+  pub fn is_execution_terminated(&mut self) -> bool {
+    self
+      .v8_isolate
+      .as_mut()
+      .unwrap()
+      .thread_safe_handle()
+      .is_execution_terminating()
+  }
+
   /// Executes traditional JavaScript code (traditional = not ES modules)
   ///
   /// ErrBox can be downcast to a type that exposes additional information about
@@ -411,6 +537,7 @@ impl CoreIsolate {
     self.shared_init();
 
     let js_error_create_fn = &*self.js_error_create_fn;
+    let pending_uncaught_exception = &mut self.pending_uncaught_exception;
     let v8_isolate = self.v8_isolate.as_mut().unwrap();
 
     let mut hs = v8::HandleScope::new(v8_isolate);
@@ -437,7 +564,11 @@ impl CoreIsolate {
       };
 
     match script.run(scope, context) {
-      Some(_) => Ok(()),
+      Some(_) => check_uncaught_exception(
+        scope,
+        pending_uncaught_exception,
+        js_error_create_fn,
+      ),
       None => {
         assert!(tc.has_caught());
         let exception = tc.exception().unwrap();
@@ -487,7 +618,9 @@ impl Future for CoreIsolate {
     let js_error_create_fn = &*inner.js_error_create_fn;
     let js_recv_cb = &inner.js_recv_cb;
     let js_macrotask_cb = &inner.js_macrotask_cb;
+    let js_promise_reject_cb = &inner.js_promise_reject_cb;
     let pending_promise_exceptions = &mut inner.pending_promise_exceptions;
+    let pending_uncaught_exception = &mut inner.pending_uncaught_exception;
 
     let mut hs = v8::HandleScope::new(v8_isolate);
     let scope = hs.enter();
@@ -498,6 +631,8 @@ impl Future for CoreIsolate {
     check_promise_exceptions(
       scope,
       pending_promise_exceptions,
+      js_promise_reject_cb,
+      pending_uncaught_exception,
       js_error_create_fn,
     )?;
 
@@ -546,6 +681,8 @@ impl Future for CoreIsolate {
     check_promise_exceptions(
       scope,
       pending_promise_exceptions,
+      js_promise_reject_cb,
+      pending_uncaught_exception,
       js_error_create_fn,
     )?;
 
@@ -682,18 +819,80 @@ pub(crate) fn exception_to_err_result<'s, T>(
   Err(js_error)
 }
 
+/// Returns the exception reported with `Deno.core.reportUncaughtException()`,
+/// if any.
+fn check_uncaught_exception<'s>(
+  scope: &mut impl v8::ToLocal<'s>,
+  pending_uncaught_exception: &mut v8::Global<v8::Value>,
+  js_error_create_fn: &JSErrorCreateFn,
+) -> Result<(), ErrBox> {
+  match pending_uncaught_exception.get(scope) {
+    Some(exception) => {
+      pending_uncaught_exception.reset(scope);
+      exception_to_err_result(scope, exception, js_error_create_fn)
+    }
+    None => Ok(()),
+  }
+}
+
+/// Passes the promises rejected without a handler to the callback set with
+/// `Deno.core.setPromiseRejectCallback()`, which returns whether it handled
+/// the rejection. The first rejection that isn't handled is returned, as is
+/// an exception reported while handling them.
 fn check_promise_exceptions<'s>(
   scope: &mut impl v8::ToLocal<'s>,
-  pending_promise_exceptions: &mut HashMap<i32, v8::Global<v8::Value>>,
+  pending_promise_exceptions: &mut HashMap<i32, PendingPromiseException>,
+  js_promise_reject_cb: &v8::Global<v8::Function>,
+  pending_uncaught_exception: &mut v8::Global<v8::Value>,
   js_error_create_fn: &JSErrorCreateFn,
 ) -> Result<(), ErrBox> {
-  if let Some(&key) = pending_promise_exceptions.keys().next() {
-    let handle = pending_promise_exceptions.remove(&key).unwrap();
-    let exception = handle.get(scope).expect("empty error handle");
-    exception_to_err_result(scope, exception, js_error_create_fn)
-  } else {
-    Ok(())
+  check_uncaught_exception(
+    scope,
+    pending_uncaught_exception,
+    js_error_create_fn,
+  )?;
+
+  let context = scope.get_current_context().unwrap();
+  let global: v8::Local<v8::Value> = context.global(scope).into();
+  let js_promise_reject_cb = js_promise_reject_cb.get(scope);
+
+  // The callback may reject more promises, which are handled in turn.
+  while let Some(&key) = pending_promise_exceptions.keys().next() {
+    let mut pending = pending_promise_exceptions.remove(&key).unwrap();
+    let promise = pending.promise.get(scope).expect("empty promise handle");
+    let reason = pending.reason.get(scope).expect("empty error handle");
+    pending.reset(scope);
+
+    let handled = match js_promise_reject_cb {
+      Some(js_promise_reject_cb) => {
+        let mut try_catch = v8::TryCatch::new(scope);
+        let tc = try_catch.enter();
+
+        let handled = js_promise_reject_cb.call(
+          scope,
+          context,
+          global,
+          &[promise.into(), reason],
+        );
+
+        if let Some(exception) = tc.exception() {
+          return exception_to_err_result(scope, exception, js_error_create_fn);
+        }
+        handled.unwrap().is_true()
+      }
+      None => false,
+    };
+
+    check_uncaught_exception(
+      scope,
+      pending_uncaught_exception,
+      js_error_create_fn,
+    )?;
+    if !handled {
+      return exception_to_err_result(scope, reason, js_error_create_fn);
+    }
   }
+  Ok(())
 }
 
 pub fn js_check<T>(r: Result<T, ErrBox>) -> T {
@@ -937,6 +1136,7 @@ pub mod tests {
         assert_eq!(e.to_string(), "Uncaught Error: execution terminated")
       }
     };
+    assert!(isolate.is_execution_terminated());
 
     // Cancel the execution-terminating exception in order to allow script
     // execution again.
@@ -954,10 +1154,54 @@ pub mod tests {
     isolate
       .execute("simple.js", "1 + 1")
       .expect("execution should be possible again");
+    isolate
+      .execute("throw.js", "throw new Error()")
+      .unwrap_err();
+    assert!(!isolate.is_execution_terminated());
 
     terminator_thread.join().unwrap();
   }
 
+  #[test]
+  fn eval_interrupt_handle() {
+    let (mut isolate, _dispatch_count) = setup(Mode::Async);
+    let handle = isolate.eval_interrupt_handle();
+
+    // Without an evaluation running, nothing is interrupted.
+    assert!(!handle.interrupt());
+    js_check(isolate.execute("not_interrupted.js", "1 + 1"));
+
+    let interrupter_thread = std::thread::spawn(move || {
+      // allow evalContext to start the infinite loop
+      std::thread::sleep(std::time::Duration::from_millis(100));
+      assert!(handle.interrupt());
+    });
+
+    // The interrupted evalContext returns and execution carries on.
+    js_check(isolate.execute(
+      "eval_interrupt.js",
+      r#"
+        const [result, errInfo] = Deno.core.evalContext("for(;;) {}");
+        if (result !== null || !errInfo.isInterrupted) {
+          throw Error("evalContext should be interrupted");
+        }
+      "#,
+    ));
+
+    interrupter_thread.join().unwrap();
+
+    // The interrupt was cleared.
+    js_check(isolate.execute(
+      "after_interrupt.js",
+      r#"
+        const [result2, errInfo2] = Deno.core.evalContext("1 + 1");
+        if (result2 !== 2 || errInfo2 !== null) {
+          throw Error("evalContext should not be interrupted");
+        }
+      "#,
+    ));
+  }
+
   #[test]
   fn dangling_shared_isolate() {
     let v8_isolate_handle = {
@@ -972,6 +1216,19 @@ pub mod tests {
     v8_isolate_handle.terminate_execution();
   }
 
+  #[test]
+  fn drop_closes_resources() {
+    let resource_table = {
+      // isolate is dropped at the end of this block
+      let (isolate, _dispatch_count) = setup(Mode::Async);
+      let resource_table = isolate.resource_table.clone();
+      resource_table.borrow_mut().add("fake", Box::new(42u32));
+      resource_table
+    };
+
+    assert!(resource_table.borrow().entries().is_empty());
+  }
+
   #[test]
   fn overflow_req_sync() {
     let (mut isolate, dispatch_count) = setup(Mode::OverflowReqSync);
@@ -992,6 +1249,48 @@ pub mod tests {
     assert_eq!(dispatch_count.load(Ordering::Relaxed), 1);
   }
 
+  #[test]
+  fn sync_op_timing_fn() {
+    let (mut isolate, dispatch_count) = setup(Mode::Async);
+    isolate.register_op("sync", |_isolate, _control, _zero_copy| {
+      Op::Sync(vec![43u8].into_boxed_slice())
+    });
+    let timings = Rc::new(RefCell::new(Vec::new()));
+    let timings_ = timings.clone();
+    isolate.set_sync_op_timing_fn(move |name, _duration| {
+      timings_.borrow_mut().push(name.to_string());
+    });
+    js_check(isolate.execute(
+      "sync_op_timing_fn.js",
+      r#"
+        // Async ops are not timed.
+        Deno.core.dispatch(1, new Uint8Array([42]));
+        Deno.core.dispatch(2, new Uint8Array([42]));
+        "#,
+    ));
+    assert_eq!(dispatch_count.load(Ordering::Relaxed), 1);
+    assert_eq!(*timings.borrow(), vec!["sync".to_string()]);
+  }
+
+  #[test]
+  fn current_op_id() {
+    let mut isolate = CoreIsolate::new(StartupData::None, false);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen_ = seen.clone();
+    let op_id = isolate.register_op("test", move |isolate, _, _| {
+      seen_.borrow_mut().push(isolate.current_op_id());
+      Op::Sync(Box::new([]))
+    });
+    js_check(isolate.execute(
+      "current_op_id.js",
+      r#"
+        Deno.core.dispatch(1, new Uint8Array([42]));
+        "#,
+    ));
+    assert_eq!(*seen.borrow(), vec![Some(op_id)]);
+    assert_eq!(isolate.current_op_id(), None);
+  }
+
   #[test]
   fn overflow_res_sync() {
     // TODO(ry) This test is quite slow due to memcpy-ing 100MB into JS. We
@@ -1125,6 +1424,56 @@ pub mod tests {
     });
   }
 
+  #[test]
+  fn test_promise_reject_callback() {
+    run_in_task(|mut cx| {
+      let (mut isolate, _dispatch_count) = setup(Mode::Async);
+      js_check(isolate.execute(
+        "promise_reject_callback.js",
+        r#"
+          let rejections = 0;
+          Deno.core.setPromiseRejectCallback((promise, reason) => {
+            assert(promise instanceof Promise);
+            rejections++;
+            return reason === "handled";
+          });
+          Promise.reject("handled");
+          // A handler added in the same tick makes the rejection handled.
+          Promise.reject("caught").catch(() => {});
+        "#,
+      ));
+      if let Poll::Ready(Err(_)) = isolate.poll_unpin(&mut cx) {
+        unreachable!();
+      }
+      js_check(isolate.execute("check.js", "assert(rejections === 1);"));
+
+      js_check(
+        isolate
+          .execute("unhandled.js", "Promise.reject(new Error(\"unhandled\"));"),
+      );
+      match isolate.poll_unpin(&mut cx) {
+        Poll::Ready(Err(err)) => assert!(err.to_string().contains("unhandled")),
+        _ => unreachable!(),
+      };
+    });
+  }
+
+  #[test]
+  fn test_report_uncaught_exception() {
+    let (mut isolate, _dispatch_count) = setup(Mode::Async);
+    let err = isolate
+      .execute(
+        "report_uncaught_exception.js",
+        r#"
+          Deno.core.reportUncaughtException(new Error("first"));
+          Deno.core.reportUncaughtException(new Error("second"));
+        "#,
+      )
+      .unwrap_err();
+    assert!(err.to_string().contains("first"));
+    js_check(isolate.execute("after.js", "1 + 1"));
+  }
+
   #[test]
   fn test_js() {
     run_in_task(|mut cx| {
@@ -27,6 +27,7 @@ pub type OpDispatcher =
 #[derive(Default)]
 pub struct OpRegistry {
   dispatchers: Vec<Rc<OpDispatcher>>,
+  names: Vec<String>,
   name_to_id: HashMap<String, OpId>,
 }
 
@@ -53,6 +54,7 @@ impl OpRegistry {
       format!("Op already registered: {}", name)
     );
     self.dispatchers.push(Rc::new(op));
+    self.names.push(name.to_string());
     op_id
   }
 
@@ -64,6 +66,11 @@ impl OpRegistry {
   pub fn get(&self, op_id: OpId) -> Option<Rc<OpDispatcher>> {
     self.dispatchers.get(op_id as usize).map(Rc::clone)
   }
+
+  /// Returns name under which op was registered.
+  pub fn name(&self, op_id: OpId) -> Option<&str> {
+    self.names.get(op_id as usize).map(String::as_str)
+  }
 }
 
 #[test]
@@ -85,6 +92,8 @@ fn test_op_registry() {
   expected.insert("ops".to_string(), 0);
   expected.insert("test".to_string(), 1);
   assert_eq!(op_registry.name_to_id, expected);
+  assert_eq!(op_registry.name(test_id), Some("test"));
+  assert_eq!(op_registry.name(100), None);
 
   let mut isolate = CoreIsolate::new(crate::StartupData::None, false);
 
@@ -73,6 +73,12 @@ impl ResourceTable {
     self.map.remove(&rid).map(|(_name, _resource)| ())
   }
 
+  /// Closes all resources. Called when the isolate owning the table is
+  /// dropped, as op futures may hold on to the table past that point.
+  pub fn close_all(&mut self) {
+    self.map.clear();
+  }
+
   pub fn remove<T: Resource>(&mut self, rid: ResourceId) -> Option<Box<T>> {
     if let Some((_name, resource)) = self.map.remove(&rid) {
       let res = match resource.downcast::<T>() {
@@ -150,6 +156,24 @@ mod tests {
     assert_eq!(table.map.len(), 0);
   }
 
+  #[test]
+  fn test_close_all_drops_resources() {
+    struct DropFlag(std::rc::Rc<std::cell::Cell<bool>>);
+    impl Drop for DropFlag {
+      fn drop(&mut self) {
+        self.0.set(true);
+      }
+    }
+
+    let dropped = std::rc::Rc::new(std::cell::Cell::new(false));
+    let mut table = ResourceTable::default();
+    table.add("fake1", Box::new(FakeResource::new(1)));
+    table.add("flag", Box::new(DropFlag(dropped.clone())));
+    table.close_all();
+    assert_eq!(table.map.len(), 0);
+    assert!(dropped.get());
+  }
+
   #[test]
   fn test_take_from_resource_table() {
     let mut table = ResourceTable::default();
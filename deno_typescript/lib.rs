@@ -204,9 +204,10 @@ pub fn mksnapshot_bundle(
   js_check(isolate.execute("system_loader.js", SYSTEM_LOADER));
   let source_code_vec = std::fs::read(bundle_filename).unwrap();
   let bundle_source_code = std::str::from_utf8(&source_code_vec).unwrap();
-  js_check(
-    isolate.execute(&bundle_filename.to_string_lossy(), bundle_source_code),
-  );
+  // The script name ends up in the snapshot; it's only the file name, so the
+  // snapshot doesn't depend on where it's built.
+  let script_name = bundle_filename.file_name().unwrap().to_string_lossy();
+  js_check(isolate.execute(&script_name, bundle_source_code));
   let script = &format!("__instantiate(\"{}\");", main_module_name);
   js_check(isolate.execute("anon", script));
   write_snapshot(isolate, snapshot_filename)?;
@@ -325,6 +326,19 @@ pub fn trace_serializer() {
   assert_eq!(r, vec![dummy]);
 }
 
+/// Sets V8 flags that make snapshots of the same bundle byte-for-byte
+/// identical: V8 otherwise seeds `Math.random()` and string hashing randomly.
+/// Must be called before the first isolate is created.
+pub fn predictable_snapshots() {
+  let dummy = "foo".to_string();
+  let r = deno_core::v8_set_flags(vec![
+    dummy.clone(),
+    "--random-seed=1".to_string(),
+    "--hash-seed=1".to_string(),
+  ]);
+  assert_eq!(r, vec![dummy]);
+}
+
 /// Warning: Returns a non-JSON op dispatcher. Must be manually attached to
 /// CoreIsolate.
 pub fn op_fetch_asset<S: ::std::hash::BuildHasher>(
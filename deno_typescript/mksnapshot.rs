@@ -0,0 +1,75 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! Creates a V8 snapshot of a bundle made by `compile_bundle()`.
+//!
+//! A snapshot can only be loaded by V8 built for the architecture that
+//! created it, so build scripts that cross compile run this binary, built
+//! for the target, instead of snapshotting in process on the host.
+//!
+//!   deno_mksnapshot <runtime|compiler> <bundle> <main module> <snapshot>
+//!     [<lib name>=<path>...]
+//!
+//! Compiler snapshots include typescript.js, and libs given as arguments are
+//! served to it by `op_fetch_asset`.
+
+use deno_core::CoreIsolate;
+use deno_core::StartupData;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: deno_mksnapshot <runtime|compiler> <bundle> \
+                     <main module> <snapshot> [<lib name>=<path>...]";
+
+fn main() {
+  let args: Vec<String> = std::env::args().collect();
+  if args.len() < 5 {
+    eprintln!("{}", USAGE);
+    std::process::exit(1);
+  }
+  let kind = args[1].as_str();
+  let bundle_path = Path::new(&args[2]);
+  let main_module_name = &args[3];
+  let snapshot_path = Path::new(&args[4]);
+
+  deno_typescript::predictable_snapshots();
+  let mut isolate = CoreIsolate::new(StartupData::None, true);
+  let result = match kind {
+    "runtime" => deno_typescript::mksnapshot_bundle(
+      &mut isolate,
+      snapshot_path,
+      bundle_path,
+      main_module_name,
+    ),
+    "compiler" => {
+      let mut custom_libs: HashMap<String, PathBuf> = HashMap::new();
+      for lib in &args[5..] {
+        let mut parts = lib.splitn(2, '=');
+        let name = parts.next().unwrap();
+        let path = parts.next().unwrap_or_else(|| {
+          eprintln!("Bad lib \"{}\", expected <lib name>=<path>", lib);
+          std::process::exit(1);
+        });
+        custom_libs.insert(name.to_string(), PathBuf::from(path));
+      }
+      isolate.register_op(
+        "op_fetch_asset",
+        deno_typescript::op_fetch_asset(custom_libs),
+      );
+      deno_typescript::mksnapshot_bundle_ts(
+        &mut isolate,
+        snapshot_path,
+        bundle_path,
+        main_module_name,
+      )
+    }
+    _ => {
+      eprintln!("{}", USAGE);
+      std::process::exit(1);
+    }
+  };
+  if let Err(err) = result {
+    eprintln!("Failed to create snapshot: {}", err);
+    std::process::exit(1);
+  }
+}
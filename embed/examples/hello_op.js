@@ -0,0 +1,4 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+const { hello } = Deno.core.ops();
+const greeting = Deno.core.dispatch(hello, Deno.core.encode("embedder"));
+Deno.core.print(`${Deno.core.decode(greeting)}\n`);
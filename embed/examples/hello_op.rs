@@ -0,0 +1,30 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! The smallest embedder: a runtime with one sync op, called from a script.
+//!
+//!   cargo run -p deno_embed --example hello_op
+
+use deno_embed::CoreIsolate;
+use deno_embed::Op;
+use deno_embed::Runtime;
+use deno_embed::ZeroCopyBuf;
+
+/// Greets the UTF-8 encoded name in `control`.
+fn op_hello(
+  _isolate: &mut CoreIsolate,
+  control: &[u8],
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Op {
+  let name = std::str::from_utf8(control).unwrap_or("stranger");
+  let greeting = format!("Hello, {}!", name);
+  Op::Sync(greeting.into_bytes().into_boxed_slice())
+}
+
+fn main() {
+  let mut runtime = Runtime::builder().op("hello", op_hello).build();
+  let result = runtime.execute("hello_op.js", include_str!("hello_op.js"));
+  if let Err(err) = result {
+    eprintln!("{}", err);
+    std::process::exit(1);
+  }
+}
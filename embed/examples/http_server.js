@@ -0,0 +1,58 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+// A minimal HTTP server, which answers every request with "Hello World".
+// Ops are async; they take [promiseId, rid] as control and respond with
+// [promiseId, result], where a negative result is an error.
+
+const ops = Deno.core.ops();
+const promises = new Map();
+let nextPromiseId = 1;
+
+function handleAsyncMsgFromRust(buf) {
+  const [promiseId, result] = new Int32Array(buf.buffer, buf.byteOffset, 2);
+  const { resolve, reject } = promises.get(promiseId);
+  promises.delete(promiseId);
+  if (result < 0) {
+    reject(new Error(`op failed (${result})`));
+  } else {
+    resolve(result);
+  }
+}
+
+for (const opId of Object.values(ops)) {
+  Deno.core.setAsyncHandler(opId, handleAsyncMsgFromRust);
+}
+
+function dispatch(opName, rid, zeroCopy = null) {
+  const promiseId = nextPromiseId++;
+  const control = new Int32Array([promiseId, rid]);
+  return new Promise((resolve, reject) => {
+    promises.set(promiseId, { resolve, reject });
+    Deno.core.dispatch(ops[opName], new Uint8Array(control.buffer), zeroCopy);
+  });
+}
+
+const response = Deno.core.encode(
+  "HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\n" +
+    "Hello World\n"
+);
+
+async function serve(rid) {
+  const request = new Uint8Array(64 * 1024);
+  try {
+    await dispatch("read", rid, request);
+    await dispatch("write", rid, response);
+  } finally {
+    await dispatch("close", rid);
+  }
+}
+
+async function main() {
+  const listenerRid = await dispatch("listen", 0);
+  Deno.core.print("Listening on http://127.0.0.1:4545/\n");
+  while (true) {
+    const rid = await dispatch("accept", listenerRid);
+    serve(rid).catch((err) => Deno.core.print(`${err}\n`, true));
+  }
+}
+
+main();
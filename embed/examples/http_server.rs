@@ -0,0 +1,177 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! A minimal HTTP server embedder: TCP ops implemented with tokio, and the
+//! server itself written as an ES module that's loaded from disk.
+//!
+//!   cargo run -p deno_embed --example http_server
+//!   curl http://127.0.0.1:4545/
+
+use deno_embed::CoreIsolate;
+use deno_embed::ErrBox;
+use deno_embed::FsModuleLoader;
+use deno_embed::ModuleSpecifier;
+use deno_embed::Op;
+use deno_embed::ResourceTable;
+use deno_embed::Runtime;
+use deno_embed::ZeroCopyBuf;
+use futures::future::poll_fn;
+use futures::future::FutureExt;
+use std::cell::RefCell;
+use std::convert::TryInto;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::rc::Rc;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+
+type Resources = Rc<RefCell<ResourceTable>>;
+
+/// Turns `handler` into an async op. Control is `[promise_id, rid]` and the
+/// response `[promise_id, result]`, as i32s; errors are reported as -1.
+fn async_op<F, R>(
+  handler: F,
+) -> impl Fn(&mut CoreIsolate, &[u8], Option<ZeroCopyBuf>) -> Op
+where
+  F: Fn(Resources, u32, Option<ZeroCopyBuf>) -> R + 'static,
+  R: Future<Output = Result<u32, ErrBox>> + 'static,
+{
+  move |isolate: &mut CoreIsolate,
+        control: &[u8],
+        zero_copy: Option<ZeroCopyBuf>|
+        -> Op {
+    let promise_id = i32::from_le_bytes(control[0..4].try_into().unwrap());
+    let rid = u32::from_le_bytes(control[4..8].try_into().unwrap());
+    let fut = handler(isolate.resource_table.clone(), rid, zero_copy);
+    Op::Async(
+      async move {
+        let result = match fut.await {
+          Ok(result) => result as i32,
+          Err(err) => {
+            eprintln!("{}", err);
+            -1
+          }
+        };
+        let mut response = promise_id.to_le_bytes().to_vec();
+        response.extend_from_slice(&result.to_le_bytes());
+        response.into_boxed_slice()
+      }
+      .boxed_local(),
+    )
+  }
+}
+
+fn bad_resource() -> ErrBox {
+  io::Error::new(io::ErrorKind::NotFound, "bad resource id").into()
+}
+
+async fn op_listen(
+  resources: Resources,
+  _rid: u32,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<u32, ErrBox> {
+  let addr: SocketAddr = "127.0.0.1:4545".parse()?;
+  let listener = TcpListener::bind(addr).await?;
+  let rid = resources
+    .borrow_mut()
+    .add("tcpListener", Box::new(listener));
+  Ok(rid)
+}
+
+async fn op_accept(
+  resources: Resources,
+  rid: u32,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<u32, ErrBox> {
+  let stream = poll_fn(|cx| {
+    let mut resource_table = resources.borrow_mut();
+    let listener = resource_table
+      .get_mut::<TcpListener>(rid)
+      .ok_or_else(bad_resource)?;
+    listener
+      .poll_accept(cx)
+      .map_ok(|(stream, _addr)| stream)
+      .map_err(ErrBox::from)
+  })
+  .await?;
+  let rid = resources.borrow_mut().add("tcpStream", Box::new(stream));
+  Ok(rid)
+}
+
+async fn op_read(
+  resources: Resources,
+  rid: u32,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<u32, ErrBox> {
+  let mut buf = zero_copy.ok_or_else(bad_resource)?;
+  let nread = poll_fn(|cx| {
+    let mut resource_table = resources.borrow_mut();
+    let stream = resource_table
+      .get_mut::<TcpStream>(rid)
+      .ok_or_else(bad_resource)?;
+    Pin::new(stream)
+      .poll_read(cx, &mut buf)
+      .map_err(ErrBox::from)
+  })
+  .await?;
+  Ok(nread as u32)
+}
+
+async fn op_write(
+  resources: Resources,
+  rid: u32,
+  zero_copy: Option<ZeroCopyBuf>,
+) -> Result<u32, ErrBox> {
+  let buf = zero_copy.ok_or_else(bad_resource)?;
+  let nwritten = poll_fn(|cx| {
+    let mut resource_table = resources.borrow_mut();
+    let stream = resource_table
+      .get_mut::<TcpStream>(rid)
+      .ok_or_else(bad_resource)?;
+    Pin::new(stream).poll_write(cx, &buf).map_err(ErrBox::from)
+  })
+  .await?;
+  Ok(nwritten as u32)
+}
+
+async fn op_close(
+  resources: Resources,
+  rid: u32,
+  _zero_copy: Option<ZeroCopyBuf>,
+) -> Result<u32, ErrBox> {
+  resources.borrow_mut().close(rid).ok_or_else(bad_resource)?;
+  Ok(0)
+}
+
+fn main() {
+  let main_module = ModuleSpecifier::resolve_url_or_path(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/http_server.js"
+  ))
+  .unwrap();
+  let mut runtime = Runtime::builder()
+    .module_loader(Rc::new(FsModuleLoader))
+    .op("listen", async_op(op_listen))
+    .op("accept", async_op(op_accept))
+    .op("read", async_op(op_read))
+    .op("write", async_op(op_write))
+    .op("close", async_op(op_close))
+    .build();
+
+  let mut tokio_runtime = tokio::runtime::Builder::new()
+    .basic_scheduler()
+    .enable_all()
+    .build()
+    .unwrap();
+  let result = tokio_runtime.block_on(async {
+    runtime.execute_module(&main_module).await?;
+    (&mut runtime).await
+  });
+  if let Err(err) = result {
+    eprintln!("{}", err);
+    std::process::exit(1);
+  }
+}
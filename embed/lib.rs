@@ -0,0 +1,80 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+//! The API to embed a JavaScript runtime built on `deno_core` in a Rust
+//! program, without depending on the Deno CLI.
+//!
+//! A [`Runtime`](struct.Runtime.html) is created with a
+//! [`RuntimeBuilder`](struct.RuntimeBuilder.html), which sets what the
+//! runtime starts from (a script or a snapshot), the ops JavaScript can
+//! dispatch with `Deno.core.dispatch()`, and the
+//! [`ModuleLoader`](trait.ModuleLoader.html) that resolves and loads ES
+//! modules. Ops keep Rust objects around for JavaScript, eg. sockets, in the
+//! runtime's [`ResourceTable`](struct.ResourceTable.html), referred to by
+//! [`ResourceId`](type.ResourceId.html).
+//!
+//! ```no_run
+//! use deno_embed::{CoreIsolate, Op, Runtime, ZeroCopyBuf};
+//!
+//! fn op_hello(
+//!   _isolate: &mut CoreIsolate,
+//!   control: &[u8],
+//!   _zero_copy: Option<ZeroCopyBuf>,
+//! ) -> Op {
+//!   let name = std::str::from_utf8(control).unwrap();
+//!   let greeting = format!("Hello, {}!", name);
+//!   Op::Sync(greeting.into_bytes().into_boxed_slice())
+//! }
+//!
+//! let mut runtime = Runtime::builder().op("hello", op_hello).build();
+//! runtime
+//!   .execute(
+//!     "hello.js",
+//!     r#"
+//!       const { hello } = Deno.core.ops();
+//!       const greeting = Deno.core.dispatch(hello, Deno.core.encode("you"));
+//!       Deno.core.print(Deno.core.decode(greeting) + "\n");
+//!     "#,
+//!   )
+//!   .unwrap();
+//! ```
+//!
+//! # Stability
+//!
+//! This crate follows semver: everything exported here is the stable API,
+//! and a breaking change to any of it, including the items re-exported from
+//! `deno_core`, is released as a new major version (a new minor version
+//! while the crate is at 0.x). Items of `deno_core` that aren't re-exported
+//! here may change in any release.
+
+extern crate deno_core;
+extern crate futures;
+
+mod module_loader;
+mod runtime;
+
+pub use crate::module_loader::FsModuleLoader;
+pub use crate::runtime::Runtime;
+pub use crate::runtime::RuntimeBuilder;
+
+pub use deno_core::v8_set_flags;
+pub use deno_core::v8_version;
+pub use deno_core::Buf;
+pub use deno_core::CoreIsolate;
+pub use deno_core::ErrBox;
+pub use deno_core::JSError;
+pub use deno_core::ModuleId;
+pub use deno_core::ModuleLoadId;
+pub use deno_core::ModuleLoader;
+pub use deno_core::ModuleResolutionError;
+pub use deno_core::ModuleSource;
+pub use deno_core::ModuleSourceFuture;
+pub use deno_core::ModuleSpecifier;
+pub use deno_core::Op;
+pub use deno_core::OpAsyncFuture;
+pub use deno_core::OpId;
+pub use deno_core::Resource;
+pub use deno_core::ResourceId;
+pub use deno_core::ResourceTable;
+pub use deno_core::Script;
+pub use deno_core::Snapshot;
+pub use deno_core::ZeroCopyBuf;
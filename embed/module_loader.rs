@@ -0,0 +1,54 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::ErrBox;
+use deno_core::ModuleLoader;
+use deno_core::ModuleSource;
+use deno_core::ModuleSourceFuture;
+use deno_core::ModuleSpecifier;
+use futures::future::FutureExt;
+use std::io;
+use std::pin::Pin;
+
+/// Loads JavaScript modules from the file system. Imports are resolved
+/// relative to the importing module, and only `file:` URLs can be loaded.
+pub struct FsModuleLoader;
+
+impl ModuleLoader for FsModuleLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    _is_main: bool,
+  ) -> Result<ModuleSpecifier, ErrBox> {
+    Ok(ModuleSpecifier::resolve_import(specifier, referrer)?)
+  }
+
+  fn load(
+    &self,
+    module_specifier: &ModuleSpecifier,
+    _maybe_referrer: Option<ModuleSpecifier>,
+    _is_dyn_import: bool,
+  ) -> Pin<Box<ModuleSourceFuture>> {
+    load_file(module_specifier.clone()).boxed_local()
+  }
+}
+
+async fn load_file(
+  module_specifier: ModuleSpecifier,
+) -> Result<ModuleSource, ErrBox> {
+  let path = module_specifier.as_url().to_file_path().map_err(|_| {
+    io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!(
+        "Only file: URLs can be loaded, not \"{}\"",
+        module_specifier
+      ),
+    )
+  })?;
+  let code = std::fs::read_to_string(path)?;
+  Ok(ModuleSource {
+    code,
+    module_url_specified: module_specifier.to_string(),
+    module_url_found: module_specifier.to_string(),
+  })
+}
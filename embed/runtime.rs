@@ -0,0 +1,267 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::CoreIsolate;
+use deno_core::ErrBox;
+use deno_core::EsIsolate;
+use deno_core::ModuleLoader;
+use deno_core::ModuleSource;
+use deno_core::ModuleSourceFuture;
+use deno_core::ModuleSpecifier;
+use deno_core::Op;
+use deno_core::ResourceTable;
+use deno_core::Script;
+use deno_core::Snapshot;
+use deno_core::StartupData;
+use deno_core::ZeroCopyBuf;
+use futures::future::FutureExt;
+use std::cell::RefCell;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+
+type OpFn = dyn Fn(&mut CoreIsolate, &[u8], Option<ZeroCopyBuf>) -> Op;
+
+/// Configures and creates a [`Runtime`](struct.Runtime.html).
+pub struct RuntimeBuilder<'a> {
+  startup_data: StartupData<'a>,
+  will_snapshot: bool,
+  module_loader: Rc<dyn ModuleLoader>,
+  ops: Vec<(String, Box<OpFn>)>,
+}
+
+impl<'a> Default for RuntimeBuilder<'a> {
+  fn default() -> Self {
+    Self {
+      startup_data: StartupData::None,
+      will_snapshot: false,
+      module_loader: Rc::new(NoModuleLoader),
+      ops: Vec::new(),
+    }
+  }
+}
+
+impl<'a> RuntimeBuilder<'a> {
+  /// Starts the runtime from a snapshot, eg. one taken with
+  /// [`Runtime::snapshot()`](struct.Runtime.html#method.snapshot) at build
+  /// time.
+  pub fn snapshot(mut self, snapshot: Snapshot) -> Self {
+    self.startup_data = StartupData::Snapshot(snapshot);
+    self
+  }
+
+  /// Runs `script` when the runtime starts.
+  pub fn startup_script(mut self, script: Script<'a>) -> Self {
+    self.startup_data = StartupData::Script(script);
+    self
+  }
+
+  /// Creates a runtime that a snapshot can be taken of.
+  pub fn will_snapshot(mut self, will_snapshot: bool) -> Self {
+    self.will_snapshot = will_snapshot;
+    self
+  }
+
+  /// Sets how modules are resolved and loaded. Without a module loader,
+  /// loading modules fails.
+  pub fn module_loader(mut self, module_loader: Rc<dyn ModuleLoader>) -> Self {
+    self.module_loader = module_loader;
+    self
+  }
+
+  /// Registers an op. JavaScript looks up its id by `name` in
+  /// `Deno.core.ops()` and dispatches it with `Deno.core.dispatch()`.
+  pub fn op<F>(mut self, name: &str, op: F) -> Self
+  where
+    F: Fn(&mut CoreIsolate, &[u8], Option<ZeroCopyBuf>) -> Op + 'static,
+  {
+    self.ops.push((name.to_string(), Box::new(op)));
+    self
+  }
+
+  pub fn build(self) -> Runtime {
+    let mut isolate =
+      EsIsolate::new(self.module_loader, self.startup_data, self.will_snapshot);
+    for (name, op) in self.ops {
+      isolate.register_op(&name, op);
+    }
+    Runtime { isolate }
+  }
+}
+
+/// A JavaScript runtime. It's a future that completes once all pending ops
+/// and dynamic imports are done, or with the first uncaught error.
+pub struct Runtime {
+  isolate: Box<EsIsolate>,
+}
+
+impl Runtime {
+  pub fn builder<'a>() -> RuntimeBuilder<'a> {
+    RuntimeBuilder::default()
+  }
+
+  /// Runs a classic script, ie. one that isn't an ES module.
+  pub fn execute(
+    &mut self,
+    filename: &str,
+    source: &str,
+  ) -> Result<(), ErrBox> {
+    self.isolate.execute(filename, source)
+  }
+
+  /// Loads the ES module `specifier` and its imports with the module loader,
+  /// and evaluates it. Ops it dispatched complete while the runtime is
+  /// polled.
+  pub async fn execute_module(
+    &mut self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(), ErrBox> {
+    let id = self.isolate.load_module(specifier, None).await?;
+    self.isolate.mod_evaluate(id)
+  }
+
+  /// The table of resources ops keep for JavaScript. It's shared with the
+  /// runtime, and its resources are closed when the runtime is dropped.
+  pub fn resource_table(&self) -> Rc<RefCell<ResourceTable>> {
+    self.isolate.resource_table.clone()
+  }
+
+  /// Takes a snapshot of the heap, to start other runtimes from. Requires a
+  /// runtime built with `will_snapshot(true)` that has only run classic
+  /// scripts.
+  pub fn snapshot(&mut self) -> Snapshot {
+    Snapshot::JustCreated(self.isolate.snapshot())
+  }
+}
+
+impl Future for Runtime {
+  type Output = Result<(), ErrBox>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    self.get_mut().isolate.poll_unpin(cx)
+  }
+}
+
+/// The module loader of runtimes that weren't given one.
+struct NoModuleLoader;
+
+impl ModuleLoader for NoModuleLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    _is_main: bool,
+  ) -> Result<ModuleSpecifier, ErrBox> {
+    Ok(ModuleSpecifier::resolve_import(specifier, referrer)?)
+  }
+
+  fn load(
+    &self,
+    module_specifier: &ModuleSpecifier,
+    _maybe_referrer: Option<ModuleSpecifier>,
+    _is_dyn_import: bool,
+  ) -> Pin<Box<ModuleSourceFuture>> {
+    let message = format!(
+      "Can't load \"{}\", the runtime has no module loader",
+      module_specifier
+    );
+    let error = io::Error::new(io::ErrorKind::Other, message);
+    futures::future::err::<ModuleSource, _>(ErrBox::from(error)).boxed_local()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::executor::block_on;
+
+  fn op_echo(
+    _isolate: &mut CoreIsolate,
+    control: &[u8],
+    _zero_copy: Option<ZeroCopyBuf>,
+  ) -> Op {
+    Op::Sync(control.to_vec().into_boxed_slice())
+  }
+
+  #[test]
+  fn builder_registers_ops() {
+    let mut runtime = Runtime::builder().op("echo", op_echo).build();
+    runtime
+      .execute(
+        "echo.js",
+        r#"
+          const { echo } = Deno.core.ops();
+          const response = Deno.core.dispatch(echo, Deno.core.encode("hi"));
+          if (Deno.core.decode(response) !== "hi") {
+            throw Error("bad");
+          }
+        "#,
+      )
+      .unwrap();
+  }
+
+  #[test]
+  fn snapshot_round_trip() {
+    let mut runtime = Runtime::builder().will_snapshot(true).build();
+    runtime.execute("a.js", "globalThis.a = 42;").unwrap();
+    let snapshot = runtime.snapshot();
+
+    let mut runtime = Runtime::builder().snapshot(snapshot).build();
+    runtime
+      .execute("b.js", "if (a !== 42) throw Error('bad');")
+      .unwrap();
+  }
+
+  #[test]
+  fn modules_need_a_loader() {
+    let mut runtime = Runtime::builder().build();
+    let specifier = ModuleSpecifier::resolve_url("file:///main.js").unwrap();
+    let error = block_on(runtime.execute_module(&specifier)).unwrap_err();
+    assert!(error.to_string().contains("has no module loader"));
+  }
+
+  #[test]
+  fn execute_module() {
+    struct Loader;
+
+    impl ModuleLoader for Loader {
+      fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        _is_main: bool,
+      ) -> Result<ModuleSpecifier, ErrBox> {
+        Ok(ModuleSpecifier::resolve_import(specifier, referrer)?)
+      }
+
+      fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        _maybe_referrer: Option<ModuleSpecifier>,
+        _is_dyn_import: bool,
+      ) -> Pin<Box<ModuleSourceFuture>> {
+        let code = match module_specifier.as_str() {
+          "file:///main.js" => "import { x } from './x.js'; globalThis.y = x;",
+          "file:///x.js" => "export const x = 1;",
+          _ => unreachable!(),
+        };
+        let module_source = ModuleSource {
+          code: code.to_string(),
+          module_url_specified: module_specifier.to_string(),
+          module_url_found: module_specifier.to_string(),
+        };
+        futures::future::ok(module_source).boxed_local()
+      }
+    }
+
+    let mut runtime = Runtime::builder().module_loader(Rc::new(Loader)).build();
+    let specifier = ModuleSpecifier::resolve_url("file:///main.js").unwrap();
+    block_on(runtime.execute_module(&specifier)).unwrap();
+    block_on(&mut runtime).unwrap();
+    runtime
+      .execute("check.js", "if (y !== 1) throw Error('bad');")
+      .unwrap();
+  }
+}
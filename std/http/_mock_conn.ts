@@ -12,6 +12,8 @@ export function mockConn(base: Partial<Deno.Conn> = {}): Deno.Conn {
       port: 0,
     },
     rid: -1,
+    readable: new ReadableStream(),
+    writable: new WritableStream(),
     closeWrite: (): void => {},
     read: (): Promise<number | null> => {
       return Promise.resolve(0);
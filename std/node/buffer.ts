@@ -0,0 +1,169 @@
+import * as hex from "../encoding/hex.ts";
+import { notImplemented } from "./_utils.ts";
+
+const notImplementedEncodings = ["ucs2", "ucs-2", "utf16le", "utf-16le"];
+
+function normalizeEncoding(encoding = "utf8"): string {
+  const enc = encoding.toLowerCase();
+  if (enc === "utf-8") return "utf8";
+  if (enc === "binary") return "latin1";
+  if (notImplementedEncodings.includes(enc)) {
+    notImplemented(`"${encoding}" encoding`);
+  }
+  if (!["utf8", "hex", "base64", "ascii", "latin1"].includes(enc)) {
+    throw new TypeError(`Unknown encoding: ${encoding}`);
+  }
+  return enc;
+}
+
+function latin1ToBytes(str: string): Uint8Array {
+  const bytes = new Uint8Array(str.length);
+  for (let i = 0; i < str.length; i++) {
+    bytes[i] = str.charCodeAt(i) & 0xff;
+  }
+  return bytes;
+}
+
+function bytesToLatin1(bytes: Uint8Array): string {
+  let str = "";
+  for (let i = 0; i < bytes.length; i++) {
+    str += String.fromCharCode(bytes[i]);
+  }
+  return str;
+}
+
+function stringToBytes(str: string, encoding: string): Uint8Array {
+  switch (normalizeEncoding(encoding)) {
+    case "hex":
+      return hex.decodeString(str);
+    case "base64":
+      return latin1ToBytes(atob(str));
+    case "ascii":
+    case "latin1":
+      return latin1ToBytes(str);
+    default:
+      return new TextEncoder().encode(str);
+  }
+}
+
+/**
+ * A subset of the Node.js `Buffer` class, backed by `Uint8Array`.
+ * See https://nodejs.org/api/buffer.html
+ */
+export class Buffer extends Uint8Array {
+  static alloc(size: number, fill?: number | string): Buffer {
+    const buf = new Buffer(size);
+    if (typeof fill === "number") {
+      buf.fill(fill);
+    } else if (typeof fill === "string" && fill.length > 0) {
+      const bytes = new TextEncoder().encode(fill);
+      for (let i = 0; i < size; i++) {
+        buf[i] = bytes[i % bytes.length];
+      }
+    }
+    return buf;
+  }
+
+  static allocUnsafe(size: number): Buffer {
+    return new Buffer(size);
+  }
+
+  static byteLength(
+    value: string | ArrayBufferView | ArrayBuffer,
+    encoding = "utf8"
+  ): number {
+    if (typeof value !== "string") return value.byteLength;
+    return stringToBytes(value, encoding).byteLength;
+  }
+
+  static concat(list: Uint8Array[], totalLength?: number): Buffer {
+    if (totalLength === undefined) {
+      totalLength = list.reduce((len, buf) => len + buf.length, 0);
+    }
+    const buf = Buffer.alloc(totalLength);
+    let pos = 0;
+    for (const item of list) {
+      if (pos >= totalLength) break;
+      const chunk = item.subarray(0, totalLength - pos);
+      buf.set(chunk, pos);
+      pos += chunk.length;
+    }
+    return buf;
+  }
+
+  static from(
+    value: string | ArrayBuffer | ArrayLike<number>,
+    encodingOrOffset?: string | number,
+    length?: number
+  ): Buffer {
+    if (typeof value === "string") {
+      const encoding =
+        typeof encodingOrOffset === "string" ? encodingOrOffset : "utf8";
+      const bytes = stringToBytes(value, encoding);
+      return new Buffer(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+    }
+    if (value instanceof ArrayBuffer) {
+      const offset =
+        typeof encodingOrOffset === "number" ? encodingOrOffset : 0;
+      return new Buffer(value, offset, length ?? value.byteLength - offset);
+    }
+    const buf = new Buffer(value.length);
+    buf.set(value);
+    return buf;
+  }
+
+  static isBuffer(obj: unknown): obj is Buffer {
+    return obj instanceof Buffer;
+  }
+
+  static isEncoding(encoding: string): boolean {
+    try {
+      normalizeEncoding(encoding);
+      return true;
+    } catch {
+      return false;
+    }
+  }
+
+  /** Unlike `Uint8Array.prototype.slice`, shares memory with the original. */
+  slice(start?: number, end?: number): Buffer {
+    return this.subarray(start, end) as Buffer;
+  }
+
+  equals(other: Uint8Array): boolean {
+    if (this.length !== other.length) return false;
+    for (let i = 0; i < this.length; i++) {
+      if (this[i] !== other[i]) return false;
+    }
+    return true;
+  }
+
+  toJSON(): { type: "Buffer"; data: number[] } {
+    return { type: "Buffer", data: Array.from(this) };
+  }
+
+  toString(encoding = "utf8", start = 0, end = this.length): string {
+    const bytes = this.subarray(start, end);
+    switch (normalizeEncoding(encoding)) {
+      case "hex":
+        return hex.encodeToString(bytes);
+      case "base64":
+        return btoa(bytesToLatin1(bytes));
+      case "ascii":
+        return bytesToLatin1(bytes.map((b) => b & 0x7f));
+      case "latin1":
+        return bytesToLatin1(bytes);
+      default:
+        return new TextDecoder().decode(bytes);
+    }
+  }
+
+  write(string: string, offset = 0, encoding = "utf8"): number {
+    const bytes = stringToBytes(string, encoding).subarray(
+      0,
+      this.length - offset
+    );
+    this.set(bytes, offset);
+    return bytes.length;
+  }
+}
@@ -0,0 +1,51 @@
+const { test } = Deno;
+import { assert, assertEquals, assertThrows } from "../testing/asserts.ts";
+import { Buffer } from "./buffer.ts";
+
+test({
+  name: "Buffer.from string with encodings",
+  fn() {
+    assertEquals(Buffer.from("hello").toString(), "hello");
+    assertEquals(Buffer.from("68656c6c6f", "hex").toString(), "hello");
+    assertEquals(Buffer.from("aGVsbG8=", "base64").toString(), "hello");
+    assertEquals(Buffer.from("hello").toString("hex"), "68656c6c6f");
+    assertEquals(Buffer.from("hello").toString("base64"), "aGVsbG8=");
+    assertEquals(Buffer.byteLength("ü"), 2);
+  },
+});
+
+test({
+  name: "Buffer.alloc and concat",
+  fn() {
+    assertEquals(Array.from(Buffer.alloc(3, 1)), [1, 1, 1]);
+    const buf = Buffer.concat([Buffer.from("ab"), Buffer.from("cd")]);
+    assert(Buffer.isBuffer(buf));
+    assertEquals(buf.toString(), "abcd");
+    assertEquals(Buffer.concat([Buffer.from("abcd")], 2).toString(), "ab");
+  },
+});
+
+test({
+  name: "Buffer.slice shares memory",
+  fn() {
+    const buf = Buffer.from("abc");
+    const slice = buf.slice(1);
+    slice[0] = "x".charCodeAt(0);
+    assertEquals(buf.toString(), "axc");
+    assert(Buffer.isBuffer(slice));
+  },
+});
+
+test({
+  name: "Buffer rejects unknown encodings",
+  fn() {
+    assert(!Buffer.isEncoding("foo"));
+    assertThrows(
+      () => {
+        Buffer.from("abc", "foo");
+      },
+      TypeError,
+      "Unknown encoding"
+    );
+  },
+});
@@ -1,3 +1,6 @@
+import { process } from "./process.ts";
+import { Buffer } from "./buffer.ts";
+
 Object.defineProperty(globalThis, Symbol.toStringTag, {
   value: "global",
   writable: false,
@@ -7,3 +10,9 @@ Object.defineProperty(globalThis, Symbol.toStringTag, {
 
 // @ts-ignore
 globalThis["global"] = globalThis;
+
+// @ts-ignore
+globalThis["process"] = process;
+
+// @ts-ignore
+globalThis["Buffer"] = Buffer;
@@ -0,0 +1,201 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { basename, dirname, fromFileUrl, join } from "../path/mod.ts";
+import { ensureDir } from "../fs/ensure_dir.ts";
+import { bold, gray, green, red } from "../fmt/colors.ts";
+import diff, { DiffResult, DiffType } from "./diff.ts";
+import { AssertionError } from "./asserts.ts";
+
+/** Whether snapshots should be rewritten instead of compared, which
+ * `deno test --update` asks for. */
+const UPDATE = Deno.args.includes("--update");
+
+interface Snapshots {
+  [name: string]: string;
+}
+
+// Snapshot files that were already read, by path.
+const snapshotFiles = new Map<string, Snapshots>();
+
+/** Returns the path of the file that stores the snapshots of the test module
+ * at `testUrl`: `__snapshots__/<module file name>.snap` next to the module.
+ */
+export function snapshotPath(testUrl: string): string {
+  const url = new URL(testUrl);
+  if (url.protocol !== "file:") {
+    throw new TypeError(
+      `Snapshots need a local test module, got "${testUrl}"`
+    );
+  }
+  const testPath = fromFileUrl(url);
+  return join(
+    dirname(testPath),
+    "__snapshots__",
+    `${basename(testPath)}.snap`
+  );
+}
+
+/** Converts `value` to the string stored in a snapshot. Strings are stored
+ * as they are, other values as printed by `Deno.inspect()`. */
+export function serialize(value: unknown): string {
+  if (typeof value === "string") {
+    return value;
+  }
+  return Deno.inspect(value, { depth: Infinity });
+}
+
+async function readSnapshots(path: string): Promise<Snapshots> {
+  let snapshots = snapshotFiles.get(path);
+  if (snapshots == null) {
+    try {
+      snapshots = JSON.parse(await Deno.readTextFile(path)) as Snapshots;
+    } catch (err) {
+      if (!(err instanceof Deno.errors.NotFound)) {
+        throw err;
+      }
+      snapshots = {};
+    }
+    snapshotFiles.set(path, snapshots);
+  }
+  return snapshots;
+}
+
+async function writeSnapshots(
+  path: string,
+  snapshots: Snapshots
+): Promise<void> {
+  // Sorted keys keep the file stable across runs and easy to review.
+  const sorted: Snapshots = {};
+  for (const name of Object.keys(snapshots).sort()) {
+    sorted[name] = snapshots[name];
+  }
+  await ensureDir(dirname(path));
+  await Deno.writeTextFile(path, JSON.stringify(sorted, null, 2) + "\n");
+}
+
+interface DiffLine extends DiffResult<string> {
+  // Number of lines of the old and the new text before this one.
+  oldIndex: number;
+  newIndex: number;
+}
+
+function hunkRange(start: number, count: number): string {
+  // An empty range starts at the line before it, per the unified format.
+  return `${count === 0 ? start : start + 1},${count}`;
+}
+
+/** Formats the line differences between `expected` and `actual` as a
+ * unified diff with `context` unchanged lines around each change. Returns an
+ * empty string if both are the same. */
+export function unifiedDiff(
+  expected: string,
+  actual: string,
+  context = 3
+): string {
+  const lines: DiffLine[] = [];
+  let oldIndex = 0;
+  let newIndex = 0;
+  for (const result of diff(expected.split("\n"), actual.split("\n"))) {
+    lines.push({ ...result, oldIndex, newIndex });
+    if (result.type !== DiffType.added) oldIndex++;
+    if (result.type !== DiffType.removed) newIndex++;
+  }
+
+  const output: string[] = [];
+  let i = 0;
+  while (i < lines.length) {
+    if (lines[i].type === DiffType.common) {
+      i++;
+      continue;
+    }
+    // Changes separated by at most twice the context share a hunk.
+    let lastChange = i;
+    for (let j = i + 1; j < lines.length; j++) {
+      if (lines[j].type !== DiffType.common) {
+        if (j - lastChange - 1 > 2 * context) break;
+        lastChange = j;
+      }
+    }
+    const hunk = lines.slice(
+      Math.max(0, i - context),
+      Math.min(lines.length, lastChange + context + 1)
+    );
+    const oldCount = hunk.filter((l) => l.type !== DiffType.added).length;
+    const newCount = hunk.filter((l) => l.type !== DiffType.removed).length;
+    output.push(
+      `@@ -${hunkRange(hunk[0].oldIndex, oldCount)} ` +
+        `+${hunkRange(hunk[0].newIndex, newCount)} @@`
+    );
+    for (const line of hunk) {
+      const sign =
+        line.type === DiffType.added
+          ? "+"
+          : line.type === DiffType.removed
+          ? "-"
+          : " ";
+      output.push(`${sign}${line.value}`);
+    }
+    i = lastChange + context + 1;
+  }
+
+  if (output.length === 0) {
+    return "";
+  }
+  return ["--- snapshot", "+++ actual", ...output].join("\n");
+}
+
+function colorDiff(unified: string): string {
+  return unified
+    .split("\n")
+    .map((line): string => {
+      if (line.startsWith("+")) return green(bold(line));
+      if (line.startsWith("-")) return red(bold(line));
+      if (line.startsWith("@@")) return gray(line);
+      return line;
+    })
+    .join("\n");
+}
+
+/**
+ * Make an assertion that `actual` matches the snapshot called `name` of the
+ * test module at `testUrl`, which is usually `import.meta.url`. Snapshots
+ * are stored next to the test module, see `snapshotPath()`.
+ *
+ * Run `deno test --update` to create or update snapshots instead of comparing
+ * them. This writes to the `__snapshots__` directory, so it needs
+ * `--allow-write` for that directory.
+ */
+export async function assertSnapshot(
+  testUrl: string,
+  name: string,
+  actual: unknown,
+  msg?: string
+): Promise<void> {
+  const path = snapshotPath(testUrl);
+  const snapshots = await readSnapshots(path);
+  const serialized = serialize(actual);
+
+  if (UPDATE) {
+    if (snapshots[name] !== serialized) {
+      snapshots[name] = serialized;
+      await writeSnapshots(path, snapshots);
+    }
+    return;
+  }
+
+  const expected = snapshots[name];
+  if (expected === undefined) {
+    throw new AssertionError(
+      msg ??
+        `Missing snapshot "${name}" in ${path}.\n` +
+          `Run "deno test --update" to create it.`
+    );
+  }
+  if (expected !== serialized) {
+    throw new AssertionError(
+      msg ??
+        `Snapshot "${name}" does not match:\n\n` +
+          `${colorDiff(unifiedDiff(expected, serialized))}\n\n` +
+          `Run "deno test --update" if the change is expected.`
+    );
+  }
+}
@@ -0,0 +1,120 @@
+// Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+import { assertEquals, assertThrowsAsync, AssertionError } from "./asserts.ts";
+import {
+  assertSnapshot,
+  serialize,
+  snapshotPath,
+  unifiedDiff,
+} from "./snapshot.ts";
+import { join } from "../path/mod.ts";
+const { test } = Deno;
+
+// The tests comparing against wrong snapshots would overwrite them instead.
+const ignore = Deno.args.includes("--update");
+
+function toFileUrl(path: string): string {
+  const url = new URL("file:///");
+  url.pathname = path.replace(/\\/g, "/");
+  return url.href;
+}
+
+// Creates a test module URL in a temporary directory whose snapshot file has
+// the given contents.
+async function setupSnapshots(snapshots: {
+  [name: string]: string;
+}): Promise<{ dir: string; testUrl: string }> {
+  const dir = await Deno.makeTempDir();
+  const testUrl = toFileUrl(join(dir, "example_test.ts"));
+  await Deno.mkdir(join(dir, "__snapshots__"));
+  await Deno.writeTextFile(snapshotPath(testUrl), JSON.stringify(snapshots));
+  return { dir, testUrl };
+}
+
+test("snapshotPath", function (): void {
+  assertEquals(
+    snapshotPath("file:///src/foo_test.ts"),
+    join("/src", "__snapshots__", "foo_test.ts.snap")
+  );
+});
+
+test("serialize", function (): void {
+  assertEquals(serialize("line 1\nline 2"), "line 1\nline 2");
+  assertEquals(serialize({ a: [1, { b: "c" }] }), '{ a: [ 1, { b: "c" } ] }');
+});
+
+test("unifiedDiff", function (): void {
+  const expected = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10";
+  const actual = expected.replace("5", "five");
+  assertEquals(unifiedDiff(expected, expected), "");
+  assertEquals(
+    unifiedDiff(expected, actual),
+    [
+      "--- snapshot",
+      "+++ actual",
+      "@@ -2,7 +2,7 @@",
+      " 2",
+      " 3",
+      " 4",
+      "-5",
+      "+five",
+      " 6",
+      " 7",
+      " 8",
+    ].join("\n")
+  );
+});
+
+test("unifiedDiffSeparateHunks", function (): void {
+  const expected = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10";
+  const actual = expected.replace("1\n", "one\n").replace("10", "ten");
+  assertEquals(
+    unifiedDiff(expected, actual, 1),
+    [
+      "--- snapshot",
+      "+++ actual",
+      "@@ -1,2 +1,2 @@",
+      "-1",
+      "+one",
+      " 2",
+      "@@ -9,2 +9,2 @@",
+      " 9",
+      "-10",
+      "+ten",
+    ].join("\n")
+  );
+});
+
+test({
+  name: "assertSnapshotMatches",
+  ignore,
+  async fn(): Promise<void> {
+    const { dir, testUrl } = await setupSnapshots({
+      greeting: "hello world",
+      object: '{ a: 1, b: [ "c" ] }',
+    });
+    await assertSnapshot(testUrl, "greeting", "hello world");
+    await assertSnapshot(testUrl, "object", { a: 1, b: ["c"] });
+    await Deno.remove(dir, { recursive: true });
+  },
+});
+
+test({
+  name: "assertSnapshotMismatch",
+  ignore,
+  async fn(): Promise<void> {
+    const { dir, testUrl } = await setupSnapshots({
+      greeting: "hello\nworld",
+    });
+    await assertThrowsAsync(
+      (): Promise<void> => assertSnapshot(testUrl, "greeting", "hello\nDeno"),
+      AssertionError,
+      'Snapshot "greeting" does not match'
+    );
+    await assertThrowsAsync(
+      (): Promise<void> => assertSnapshot(testUrl, "missing", "hello"),
+      AssertionError,
+      'Missing snapshot "missing"'
+    );
+    await Deno.remove(dir, { recursive: true });
+  },
+});
@@ -0,0 +1,37 @@
+use std::os::raw::c_char;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[no_mangle]
+pub extern "C" fn add_u32(a: u32, b: u32) -> u32 {
+  a + b
+}
+
+#[no_mangle]
+pub extern "C" fn add_i8(a: i8, b: i8) -> i8 {
+  a.wrapping_add(b)
+}
+
+#[no_mangle]
+pub extern "C" fn add_f64(a: f64, b: f64) -> f64 {
+  a + b
+}
+
+#[no_mangle]
+pub extern "C" fn fill_buffer(buf: *mut u8, len: usize, value: u8) {
+  let buf = unsafe { std::slice::from_raw_parts_mut(buf, len) };
+  for byte in buf {
+    *byte = value;
+  }
+}
+
+#[no_mangle]
+pub extern "C" fn static_string() -> *const c_char {
+  b"Hello from C\0".as_ptr() as *const c_char
+}
+
+#[no_mangle]
+pub extern "C" fn sleep_ms(ms: u32) -> u32 {
+  sleep(Duration::from_millis(ms as u64));
+  ms
+}
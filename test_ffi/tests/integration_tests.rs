@@ -0,0 +1,64 @@
+// To run this test manually:
+//   cd test_ffi
+//   ../target/debug/deno run --allow-ffi --unstable tests/test.js debug
+
+use deno::test_util::*;
+use std::process::Command;
+
+#[cfg(debug_assertions)]
+const BUILD_VARIANT: &str = "debug";
+
+#[cfg(not(debug_assertions))]
+const BUILD_VARIANT: &str = "release";
+
+#[test]
+fn basic() {
+  let mut build = Command::new("cargo");
+  let mut build = build.arg("build").arg("-p").arg("test_ffi");
+  if BUILD_VARIANT == "release" {
+    build = build.arg("--release");
+  }
+  let build_output = build.output().unwrap();
+  assert!(build_output.status.success());
+  let output = Command::new(deno_exe_path())
+    .arg("run")
+    .arg("--allow-ffi")
+    .arg("--unstable")
+    .arg("tests/test.js")
+    .arg(BUILD_VARIANT)
+    .output()
+    .unwrap();
+  let stdout = std::str::from_utf8(&output.stdout).unwrap();
+  let stderr = std::str::from_utf8(&output.stderr).unwrap();
+  if !output.status.success() {
+    println!("stdout {}", stdout);
+    println!("stderr {}", stderr);
+  }
+  assert!(output.status.success());
+  let expected = "\
+    3\n\
+    56\n\
+    0.75\n\
+    Uint8Array(4) [ 7, 7, 7, 7 ]\n\
+    Hello from C\n\
+    72\n\
+    9\n\
+    before sleep_ms resolved\n\
+    10\n";
+  assert_eq!(stdout, expected);
+  assert_eq!(stderr, "");
+}
+
+#[test]
+fn requires_permission() {
+  let output = Command::new(deno_exe_path())
+    .arg("run")
+    .arg("--unstable")
+    .arg("tests/test.js")
+    .arg(BUILD_VARIANT)
+    .output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr = std::str::from_utf8(&output.stderr).unwrap();
+  assert!(stderr.contains("--allow-ffi"));
+}
@@ -0,0 +1,45 @@
+// Run with `--allow-ffi --unstable`; the first argument is the build variant.
+const [libPrefix, libSuffix] = {
+  windows: ["", "dll"],
+  darwin: ["lib", "dylib"],
+  linux: ["lib", "so"],
+}[Deno.build.os];
+
+const libPath = `../target/${Deno.args[0]}/${libPrefix}test_ffi.${libSuffix}`;
+
+const resourcesPre = Deno.resources();
+
+const lib = Deno.dlopen(libPath, {
+  add_u32: { parameters: ["u32", "u32"], result: "u32" },
+  add_i8: { parameters: ["i8", "i8"], result: "i8" },
+  add_f64: { parameters: ["f64", "f64"], result: "f64" },
+  fill_buffer: { parameters: ["buffer", "usize", "u8"], result: "void" },
+  static_string: { parameters: [], result: "pointer" },
+  sleep_ms: { parameters: ["u32"], result: "u32", nonblocking: true },
+});
+
+console.log(lib.symbols.add_u32(1, 2));
+console.log(lib.symbols.add_i8(-100, -100));
+console.log(lib.symbols.add_f64(0.5, 0.25));
+
+const buf = new Uint8Array(4);
+lib.symbols.fill_buffer(buf, buf.length, 7);
+console.log(buf);
+
+const view = new Deno.UnsafePointerView(lib.symbols.static_string());
+console.log(view.getCString());
+console.log(view.getUint8(0));
+
+const ptr = Deno.UnsafePointer.of(buf);
+new Deno.UnsafePointerView(ptr).set("u8", 9, 1);
+console.log(buf[1]);
+
+const pending = lib.symbols.sleep_ms(10);
+console.log("before sleep_ms resolved");
+console.log(await pending);
+
+lib.close();
+const resourcesPost = Deno.resources();
+if (JSON.stringify(resourcesPre) !== JSON.stringify(resourcesPost)) {
+  throw new Error("library resource not closed");
+}
@@ -113,6 +113,23 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
                 self.wfile.write(bytes("console.log('etag')"))
             return
 
+        if "max_age_script.ts" in self.path:
+            self.protocol_version = 'HTTP/1.1'
+            if_not_match = self.headers.getheader('if-none-match')
+            if if_not_match == "5d41402abc4b2a76b9719d91":
+                self.send_response(304, 'Not Modified')
+                self.send_header('Cache-Control', 'max-age=0')
+                self.send_header('ETag', '5d41402abc4b2a76b9719d91')
+                self.end_headers()
+            else:
+                self.send_response(200, 'OK')
+                self.send_header('Content-type', 'application/typescript')
+                self.send_header('Cache-Control', 'max-age=0')
+                self.send_header('ETag', '5d41402abc4b2a76b9719d91')
+                self.end_headers()
+                self.wfile.write(bytes("console.log('max-age')"))
+            return
+
         if "xTypeScriptTypes.js" in self.path:
             self.protocol_version = "HTTP/1.1"
             self.send_response(200, 'OK')
@@ -175,7 +192,11 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
                 self.send_header('user-agent',
                                  self.headers.getheader('user-agent'))
             self.end_headers()
-            data_string = self.rfile.read(int(self.headers['Content-Length']))
+            if self.headers.getheader('transfer-encoding') == 'chunked':
+                data_string = self.read_chunked_body()
+            else:
+                data_string = self.rfile.read(
+                    int(self.headers['Content-Length']))
             self.wfile.write(bytes(data_string))
             return
         self.protocol_version = 'HTTP/1.1'
@@ -184,6 +205,17 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
         self.end_headers()
         self.wfile.write(bytes('Server does not support this operation'))
 
+    def read_chunked_body(self):
+        body = b''
+        while True:
+            size = int(self.rfile.readline().strip(), 16)
+            if size == 0:
+                # The trailer ends with an empty line.
+                self.rfile.readline()
+                return body
+            body += self.rfile.read(size)
+            self.rfile.readline()
+
     def guess_type(self, path):
         if ".t1." in path:
             return "text/typescript"